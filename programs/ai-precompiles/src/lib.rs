@@ -8,6 +8,7 @@ use solana_program::{
     clock::Clock,
     entrypoint,
     entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
     msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
@@ -15,7 +16,7 @@ use solana_program::{
     rent::Rent,
     system_instruction,
     system_program,
-    sysvar::Sysvar,
+    sysvar::{self, Sysvar},
 };
 
 // ---------------------------------------------------------------------------
@@ -37,7 +38,28 @@ const MAX_SUPPORTED_MODELS: usize = 16;
 const MAX_INPUT_DATA: usize = 10_240; // 10 KB
 /// Logit tolerance in basis points (100 = 1%). Integer to avoid f32 non-determinism.
 const LOGIT_TOLERANCE_BPS: u64 = 100;
+/// Logits are stored as Q16.16 fixed point: `(logit * 65536) as i32`.
+const LOGIT_FIXED_ONE: i32 = 1 << 16;
+/// Sampled positions kept per result (the inference server samples up to 32).
+const MAX_FINGERPRINT_POSITIONS: usize = 32;
 const CHALLENGE_WINDOW_SLOTS: u64 = 100;
+const MAX_MODEL_VALIDATORS: usize = 32;
+/// Slots the assigned validator has to submit before the job can be reassigned.
+const ASSIGNMENT_DEADLINE_SLOTS: u64 = 150;
+/// Slots an unserved request waits before its escrow can be refunded.
+const REQUEST_EXPIRY_SLOTS: u64 = 3_000;
+/// Slots between RequestUnstake and WithdrawStake, long enough for any
+/// in-flight result to clear its challenge window and dispute.
+const UNSTAKE_COOLDOWN_SLOTS: u64 = 216_000; // ~1 day
+/// Share of the submitter's stake slashed when a dispute is upheld.
+const DISPUTE_SLASH_BPS: u64 = 5_000; // 50 %
+/// Share of a verifier's stake slashed for voting against the final outcome.
+const DISSENT_SLASH_BPS: u64 = 1_000; // 10 %
+/// Upper bound on `AIConfig::verifier_quorum`; each vote samples its own position.
+const MAX_VERIFIER_QUORUM: u8 = 16;
+const DEFAULT_VERIFIER_QUORUM: u8 = 1;
+const DEFAULT_DISPUTE_THRESHOLD_BPS: u16 = 5_000; // 50 %
+const DEFAULT_VERIFIER_REWARD_BPS: u16 = 1_000; // 10 %, out of the validator share
 
 // Fee split (of escrowed amount)
 const VALIDATOR_FEE_BPS: u64 = 5_000; // 50 %
@@ -68,6 +90,18 @@ pub enum AiInstruction {
     ClaimInferenceFee = 6,
     Pause = 7,
     Unpause = 8,
+    InvokeCallback = 9,
+    AssignValidator = 10,
+    ExpireRequest = 11,
+    ResolveDispute = 12,
+    RequestUnstake = 13,
+    WithdrawStake = 14,
+    SetVerificationParams = 15,
+    ClaimVerifierReward = 16,
+    PenalizeDissenter = 17,
+    MigrateAccount = 18,
+    CreateValidatorSet = 19,
+    SettleVotes = 20,
 }
 
 impl TryFrom<u8> for AiInstruction {
@@ -83,6 +117,18 @@ impl TryFrom<u8> for AiInstruction {
             6 => Ok(Self::ClaimInferenceFee),
             7 => Ok(Self::Pause),
             8 => Ok(Self::Unpause),
+            9 => Ok(Self::InvokeCallback),
+            10 => Ok(Self::AssignValidator),
+            11 => Ok(Self::ExpireRequest),
+            12 => Ok(Self::ResolveDispute),
+            13 => Ok(Self::RequestUnstake),
+            14 => Ok(Self::WithdrawStake),
+            15 => Ok(Self::SetVerificationParams),
+            16 => Ok(Self::ClaimVerifierReward),
+            17 => Ok(Self::PenalizeDissenter),
+            18 => Ok(Self::MigrateAccount),
+            19 => Ok(Self::CreateValidatorSet),
+            20 => Ok(Self::SettleVotes),
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
@@ -92,7 +138,7 @@ impl TryFrom<u8> for AiInstruction {
 // State Accounts
 // ---------------------------------------------------------------------------
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[derive(BorshSerialize, Debug, Clone)]
 pub struct AIConfig {
     pub is_initialized: bool,
     pub admin: Pubkey,
@@ -103,11 +149,56 @@ pub struct AIConfig {
     pub foundation: Pubkey,
     pub is_paused: bool,
     pub bump: u8,
+    // v2 fields. V1 accounts read them as the defaults until MigrateAccount
+    // grows them.
+    /// Verification votes that settle a result.
+    pub verifier_quorum: u8,
+    /// Share of mismatch votes in the quorum at which a result is disputed.
+    pub dispute_threshold_bps: u16,
+    /// Share of the escrow paid to verifiers on the winning side, taken out
+    /// of the validator's share.
+    pub verifier_reward_bps: u16,
+}
+
+// Custom deserialization: supports both v1 (123-byte) and v2 (128-byte) accounts.
+impl BorshDeserialize for AIConfig {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let is_initialized = bool::deserialize_reader(reader)?;
+        let admin = Pubkey::deserialize_reader(reader)?;
+        let registration_fee = u64::deserialize_reader(reader)?;
+        let min_stake = u64::deserialize_reader(reader)?;
+        let request_nonce = u64::deserialize_reader(reader)?;
+        let burn_address = Pubkey::deserialize_reader(reader)?;
+        let foundation = Pubkey::deserialize_reader(reader)?;
+        let is_paused = bool::deserialize_reader(reader)?;
+        let bump = u8::deserialize_reader(reader)?;
+        // v2 fields — default if account is v1 (123 bytes)
+        let verifier_quorum = u8::deserialize_reader(reader).unwrap_or(DEFAULT_VERIFIER_QUORUM);
+        let dispute_threshold_bps =
+            u16::deserialize_reader(reader).unwrap_or(DEFAULT_DISPUTE_THRESHOLD_BPS);
+        let verifier_reward_bps =
+            u16::deserialize_reader(reader).unwrap_or(DEFAULT_VERIFIER_REWARD_BPS);
+        Ok(Self {
+            is_initialized, admin, registration_fee, min_stake, request_nonce,
+            burn_address, foundation, is_paused, bump, verifier_quorum,
+            dispute_threshold_bps, verifier_reward_bps,
+        })
+    }
 }
 
 impl AIConfig {
     pub const SEED: &'static [u8] = b"ai_config";
-    pub const LEN: usize = 1 + 32 + 8 + 8 + 8 + 32 + 32 + 1 + 1; // 123
+    pub const LEN: usize = 1 + 32 + 8 + 8 + 8 + 32 + 32 + 1 + 1 + 1 + 2 + 2; // 128
+    pub const V1_LEN: usize = 123;
+
+    /// Serialize to account data, writing only the bytes that fit.
+    /// V1 accounts are grown to LEN by MigrateAccount.
+    pub fn serialize_to_account(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        let full = borsh::to_vec(self).map_err(|_| ProgramError::BorshIoError("serialize".to_string()))?;
+        let len = data.len().min(full.len());
+        data[..len].copy_from_slice(&full[..len]);
+        Ok(())
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
@@ -131,7 +222,7 @@ impl ModelAccount {
         + (4 + MAX_ARCHITECTURE) + (4 + MAX_STORAGE_URI) + 8 + 1 + 1; // ~465
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[derive(BorshSerialize, Debug, Clone)]
 pub struct AIValidator {
     pub authority: Pubkey,
     pub stake_amount: u64,
@@ -143,13 +234,89 @@ pub struct AIValidator {
     pub slash_count: u64,
     pub is_active: bool,
     pub bump: u8,
+    /// Slot RequestUnstake was called in; 0 while staked. Added in v2.
+    pub unstake_requested_at: u64,
+    /// Requests assigned to this validator that have not settled yet: from
+    /// AssignValidator until the fee is claimed, the request expires or is
+    /// reassigned, or a dispute against it is upheld. Added in v2.
+    pub open_assignments: u32,
+    /// Results of this validator awaiting ResolveDispute. Added in v2.
+    pub open_disputes: u32,
+}
+
+// Custom deserialization: supports both v1 (614-byte) and v2 (630-byte) accounts.
+impl BorshDeserialize for AIValidator {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let authority = Pubkey::deserialize_reader(reader)?;
+        let stake_amount = u64::deserialize_reader(reader)?;
+        let gpu_model = String::deserialize_reader(reader)?;
+        let vram_gb = u16::deserialize_reader(reader)?;
+        let supported_model_count = u16::deserialize_reader(reader)?;
+        let supported_models = Vec::<[u8; 32]>::deserialize_reader(reader)?;
+        let inference_count = u64::deserialize_reader(reader)?;
+        let slash_count = u64::deserialize_reader(reader)?;
+        let is_active = bool::deserialize_reader(reader)?;
+        let bump = u8::deserialize_reader(reader)?;
+        // v2 fields — zero if a full v1 account has no room for them
+        let unstake_requested_at = u64::deserialize_reader(reader).unwrap_or(0);
+        let open_assignments = u32::deserialize_reader(reader).unwrap_or(0);
+        let open_disputes = u32::deserialize_reader(reader).unwrap_or(0);
+        Ok(Self {
+            authority, stake_amount, gpu_model, vram_gb, supported_model_count,
+            supported_models, inference_count, slash_count, is_active, bump,
+            unstake_requested_at, open_assignments, open_disputes,
+        })
+    }
 }
 
 impl AIValidator {
     pub const SEED: &'static [u8] = b"ai_validator";
-    // 32 + 8 + (4+32) + 2 + 2 + (4 + 16*32) + 8 + 8 + 1 + 1 = ~614
+    // 32 + 8 + (4+32) + 2 + 2 + (4 + 16*32) + 8 + 8 + 1 + 1 + 8 + 4 + 4 = 630
     pub const LEN: usize = 32 + 8 + (4 + MAX_GPU_MODEL) + 2 + 2
-        + (4 + MAX_SUPPORTED_MODELS * 32) + 8 + 8 + 1 + 1;
+        + (4 + MAX_SUPPORTED_MODELS * 32) + 8 + 8 + 1 + 1 + 8 + 4 + 4;
+    pub const V1_LEN: usize = 614;
+
+    /// Serialize to account data, writing only the bytes that fit.
+    /// V1 accounts are grown to LEN by MigrateAccount.
+    pub fn serialize_to_account(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        let full = borsh::to_vec(self).map_err(|_| ProgramError::BorshIoError("serialize".to_string()))?;
+        let len = data.len().min(full.len());
+        data[..len].copy_from_slice(&full[..len]);
+        Ok(())
+    }
+}
+
+/// Validators that registered support for a model; the pool `AssignValidator`
+/// draws from.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ModelValidatorSet {
+    pub model_hash: [u8; 32],
+    pub bump: u8,
+    /// Members in registration order.
+    pub validators: Vec<SetMember>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetMember {
+    /// AIValidator PDA address.
+    pub validator: Pubkey,
+    /// Stake as last seen by RegisterValidator or AssignValidator. Stake only
+    /// shrinks (slashing), so this is an upper bound on the live amount.
+    pub stake: u64,
+}
+
+impl ModelValidatorSet {
+    pub const SEED: &'static [u8] = b"model_validators";
+    pub const LEN: usize = 32 + 1 + (4 + MAX_MODEL_VALIDATORS * (32 + 8)); // 1317
+
+    /// Index of the member with the smallest stake snapshot.
+    pub fn lowest_staked(&self) -> Option<usize> {
+        self.validators
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, m)| m.stake)
+            .map(|(i, _)| i)
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
@@ -163,7 +330,7 @@ pub enum InferenceStatus {
     Failed = 5,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[derive(BorshSerialize, Debug, Clone)]
 pub struct InferenceRequest {
     pub requester: Pubkey,
     pub model_hash: [u8; 32],
@@ -177,11 +344,95 @@ pub struct InferenceRequest {
     pub completed_at: i64,
     pub nonce: u64,
     pub bump: u8,
+    // v2 fields. V1 accounts read them as zero until MigrateAccount grows them.
+    /// Slot the result was submitted in; the challenge window runs from here.
+    pub completed_slot: u64,
+    /// Program invoked once the result is final. `Pubkey::default()` means none.
+    pub callback_program: Pubkey,
+    /// Prefix of the callback instruction data, if the callback wants one.
+    pub callback_discriminator: Option<[u8; 8]>,
+    pub callback_invoked: bool,
+    /// Last slot `assigned_validator` may submit in.
+    pub assignment_deadline: u64,
+    /// Number of times the request has been assigned; mixed into the draw.
+    pub assignment_round: u32,
+    /// Slot the request was created in; expiry counts from here.
+    pub created_slot: u64,
+    pub match_votes: u8,
+    pub mismatch_votes: u8,
+    /// Bit i set once a verifier has sampled token position i.
+    pub sampled_positions: u32,
+    /// Reward owed to each verifier on the winning side, fixed once the
+    /// fee is claimed or the dispute is upheld.
+    pub verifier_reward_each: u64,
+    /// The next assignment draw is seeded from the hash of the first slot
+    /// after this one, so the seed is unknown when the draw is committed.
+    pub draw_slot: u64,
+}
+
+// Custom deserialization: supports both v1 (174-byte) and v2 (266-byte) accounts.
+impl BorshDeserialize for InferenceRequest {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let requester = Pubkey::deserialize_reader(reader)?;
+        let model_hash = <[u8; 32]>::deserialize_reader(reader)?;
+        let input_hash = <[u8; 32]>::deserialize_reader(reader)?;
+        let max_output_len = u32::deserialize_reader(reader)?;
+        let max_fee = u64::deserialize_reader(reader)?;
+        let escrowed_amount = u64::deserialize_reader(reader)?;
+        let status = InferenceStatus::deserialize_reader(reader)?;
+        let assigned_validator = Pubkey::deserialize_reader(reader)?;
+        let created_at = i64::deserialize_reader(reader)?;
+        let completed_at = i64::deserialize_reader(reader)?;
+        let nonce = u64::deserialize_reader(reader)?;
+        let bump = u8::deserialize_reader(reader)?;
+        // v2 fields — default if account is v1 (174 bytes)
+        let completed_slot = u64::deserialize_reader(reader).unwrap_or(0);
+        let callback_program = Pubkey::deserialize_reader(reader).unwrap_or_default();
+        let callback_discriminator = Option::<[u8; 8]>::deserialize_reader(reader).unwrap_or(None);
+        let callback_invoked = bool::deserialize_reader(reader).unwrap_or(false);
+        let assignment_deadline = u64::deserialize_reader(reader).unwrap_or(0);
+        let assignment_round = u32::deserialize_reader(reader).unwrap_or(0);
+        let created_slot = u64::deserialize_reader(reader).unwrap_or(0);
+        let match_votes = u8::deserialize_reader(reader).unwrap_or(0);
+        let mismatch_votes = u8::deserialize_reader(reader).unwrap_or(0);
+        let sampled_positions = u32::deserialize_reader(reader).unwrap_or(0);
+        let verifier_reward_each = u64::deserialize_reader(reader).unwrap_or(0);
+        let draw_slot = u64::deserialize_reader(reader).unwrap_or(0);
+        Ok(Self {
+            requester, model_hash, input_hash, max_output_len, max_fee, escrowed_amount,
+            status, assigned_validator, created_at, completed_at, nonce, bump,
+            completed_slot, callback_program, callback_discriminator, callback_invoked,
+            assignment_deadline, assignment_round, created_slot, match_votes,
+            mismatch_votes, sampled_positions, verifier_reward_each, draw_slot,
+        })
+    }
 }
 
 impl InferenceRequest {
     pub const SEED: &'static [u8] = b"inference";
-    pub const LEN: usize = 32 + 32 + 32 + 4 + 8 + 8 + 1 + 32 + 8 + 8 + 8 + 1; // 174
+    pub const LEN: usize = 32 + 32 + 32 + 4 + 8 + 8 + 1 + 32 + 8 + 8 + 8 + 1
+        + 8 + 32 + 9 + 1 + 8 + 4 + 8 + 1 + 1 + 4 + 8 + 8; // 266
+    pub const V1_LEN: usize = 174;
+
+    pub fn has_callback(&self) -> bool {
+        self.callback_program != Pubkey::default()
+    }
+
+    /// Whether the assignee's `open_assignments` counts this request. Requests
+    /// assigned before AssignValidator existed were never counted.
+    pub fn counts_assignment(&self) -> bool {
+        self.assignment_round > 0
+    }
+
+    /// Serialize to account data, writing only the bytes that fit.
+    /// V1 accounts are grown to LEN by MigrateAccount; instructions that
+    /// set v2 fields require it.
+    pub fn serialize_to_account(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        let full = borsh::to_vec(self).map_err(|_| ProgramError::BorshIoError("serialize".to_string()))?;
+        let len = data.len().min(full.len());
+        data[..len].copy_from_slice(&full[..len]);
+        Ok(())
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
@@ -200,6 +451,25 @@ impl InferenceResult {
     pub const LEN: usize = 32 + 32 + 32 + 32 + 8 + 8 + 1; // 145
 }
 
+/// Companion to `InferenceResult` holding the submitter's logit fingerprint in
+/// fixed point, so verifiers can be compared against it on-chain.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct LogitFingerprint {
+    pub request: Pubkey,
+    pub bump: u8,
+    /// Length of the output in tokens, as reported by the submitter.
+    pub output_tokens: u32,
+    /// Top-4 logits per sampled position, Q16.16. Entry `i` holds the logits
+    /// of output token `fingerprint_token_index(request, output_hash, i,
+    /// output_tokens)`.
+    pub positions: Vec<[i32; 4]>,
+}
+
+impl LogitFingerprint {
+    pub const SEED: &'static [u8] = b"logits";
+    pub const LEN: usize = 32 + 1 + 4 + (4 + MAX_FINGERPRINT_POSITIONS * 16); // 553
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct VerificationRecord {
     pub request: Pubkey,
@@ -208,11 +478,13 @@ pub struct VerificationRecord {
     pub matched: bool,
     pub verified_at: i64,
     pub bump: u8,
+    /// Set once the verifier has been rewarded or penalized.
+    pub settled: bool,
 }
 
 impl VerificationRecord {
     pub const SEED: &'static [u8] = b"verification";
-    pub const LEN: usize = 32 + 32 + 4 + 1 + 8 + 1; // 78
+    pub const LEN: usize = 32 + 32 + 4 + 1 + 8 + 1 + 1; // 79
 }
 
 // ---------------------------------------------------------------------------
@@ -245,13 +517,45 @@ pub struct RegisterValidatorArgs {
     pub supported_models: Vec<[u8; 32]>,
 }
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize)]
 pub struct RequestInferenceArgs {
     pub model_hash: [u8; 32],
     pub input_data: Vec<u8>,
     pub max_output_len: u32,
     pub callback_program: Option<Pubkey>,
     pub max_fee: u64,
+    /// Prefix for the callback instruction data. Optional trailing field:
+    /// clients that predate it simply leave it off.
+    pub callback_discriminator: Option<[u8; 8]>,
+}
+
+impl BorshDeserialize for RequestInferenceArgs {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let model_hash = <[u8; 32]>::deserialize_reader(reader)?;
+        let input_data = Vec::<u8>::deserialize_reader(reader)?;
+        let max_output_len = u32::deserialize_reader(reader)?;
+        let callback_program = Option::<Pubkey>::deserialize_reader(reader)?;
+        let max_fee = u64::deserialize_reader(reader)?;
+        let callback_discriminator = Option::<[u8; 8]>::deserialize_reader(reader).unwrap_or(None);
+        Ok(Self {
+            model_hash, input_data, max_output_len, callback_program, max_fee,
+            callback_discriminator,
+        })
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SetVerificationParamsArgs {
+    pub verifier_quorum: u8,
+    pub dispute_threshold_bps: u16,
+    pub verifier_reward_bps: u16,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ResolveDisputeArgs {
+    /// True if the dispute stands: the submitter is slashed and the requester
+    /// refunded. False restores the result as verified.
+    pub upheld: bool,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -259,10 +563,13 @@ pub struct SubmitResultArgs {
     pub output_hash: [u8; 32],
     pub logit_fingerprint: Vec<[f32; 4]>,
     pub compute_units_used: u64,
+    pub output_tokens: u32,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct VerifyLogitsArgs {
+    /// Fingerprint entry to check. The verifier re-runs the output token at
+    /// `fingerprint_token_index` of that entry.
     pub token_position: u32,
     pub expected_logits: [f32; 4],
 }
@@ -271,7 +578,7 @@ pub struct VerifyLogitsArgs {
 // Errors
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
 pub enum AiError {
     #[error("Already initialized")]
     AlreadyInitialized,
@@ -305,6 +612,58 @@ pub enum AiError {
     Overflow,
     #[error("Program is paused")]
     ProgramPaused,
+    #[error("Invalid callback program")]
+    InvalidCallbackProgram,
+    #[error("Request has no callback")]
+    NoCallback,
+    #[error("Callback already invoked")]
+    CallbackAlreadyInvoked,
+    #[error("Model validator set is full")]
+    ValidatorSetFull,
+    #[error("No eligible validator for model")]
+    NoEligibleValidator,
+    #[error("Assignment deadline not reached")]
+    AssignmentActive,
+    #[error("Assignment deadline passed")]
+    AssignmentExpired,
+    #[error("Signer is not the assigned validator")]
+    NotAssignee,
+    #[error("Request has not expired")]
+    RequestNotExpired,
+    #[error("Unstake already requested")]
+    UnstakeAlreadyRequested,
+    #[error("Unstake not requested")]
+    UnstakeNotRequested,
+    #[error("Unstake cooldown not elapsed")]
+    CooldownActive,
+    #[error("Logit fingerprint has too many positions")]
+    FingerprintTooLong,
+    #[error("Logit fingerprint has too few positions")]
+    FingerprintTooShort,
+    #[error("Output token count is zero or above max_output_len")]
+    InvalidOutputTokens,
+    #[error("Token position not in fingerprint")]
+    InvalidTokenPosition,
+    #[error("Token position already sampled")]
+    PositionAlreadySampled,
+    #[error("Challenge window closed")]
+    ChallengeWindowClosed,
+    #[error("Invalid verification parameters")]
+    InvalidVerificationParams,
+    #[error("Verification already settled")]
+    AlreadySettled,
+    #[error("Verifier is not on the winning side")]
+    NotOnWinningSide,
+    #[error("Verifier agreed with the outcome")]
+    NotDissenter,
+    #[error("No verifier reward available")]
+    NoVerifierReward,
+    #[error("Account must be migrated with MigrateAccount first")]
+    AccountNotMigrated,
+    #[error("No slot after the committed draw slot yet")]
+    DrawNotReady,
+    #[error("Validator has open assignments or disputes")]
+    ObligationsOpen,
 }
 
 impl From<AiError> for ProgramError {
@@ -342,6 +701,22 @@ pub fn process_instruction(
         }
         AiInstruction::Pause => process_pause(program_id, accounts),
         AiInstruction::Unpause => process_unpause(program_id, accounts),
+        AiInstruction::InvokeCallback => process_invoke_callback(program_id, accounts),
+        AiInstruction::AssignValidator => process_assign_validator(program_id, accounts),
+        AiInstruction::ExpireRequest => process_expire_request(program_id, accounts),
+        AiInstruction::ResolveDispute => process_resolve_dispute(program_id, accounts, rest),
+        AiInstruction::RequestUnstake => process_request_unstake(program_id, accounts),
+        AiInstruction::WithdrawStake => process_withdraw_stake(program_id, accounts),
+        AiInstruction::SetVerificationParams => {
+            process_set_verification_params(program_id, accounts, rest)
+        }
+        AiInstruction::ClaimVerifierReward => {
+            process_claim_verifier_reward(program_id, accounts)
+        }
+        AiInstruction::PenalizeDissenter => process_penalize_dissenter(program_id, accounts),
+        AiInstruction::MigrateAccount => process_migrate_account(program_id, accounts),
+        AiInstruction::CreateValidatorSet => process_create_validator_set(program_id, accounts),
+        AiInstruction::SettleVotes => process_settle_votes(program_id, accounts),
     }
 }
 
@@ -388,6 +763,384 @@ fn create_pda_account<'a>(
     )
 }
 
+/// Grow a program-owned account to `new_size`, topping up rent from `payer`.
+fn grow_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    new_size: usize,
+) -> ProgramResult {
+    let rent = Rent::get()?;
+    let shortfall = rent
+        .minimum_balance(new_size)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, shortfall),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    account.realloc(new_size, false)
+}
+
+/// Instructions that set v2 fields of an account need it at full size, or
+/// `serialize_to_account` would drop them.
+fn assert_migrated(info: &AccountInfo, len: usize) -> ProgramResult {
+    if info.data_len() < len {
+        return Err(AiError::AccountNotMigrated.into());
+    }
+    Ok(())
+}
+
+/// Callback instruction data: the optional discriminator, then the request
+/// and the output hash.
+fn callback_data(
+    discriminator: Option<[u8; 8]>,
+    request: &Pubkey,
+    output_hash: &[u8; 32],
+) -> Vec<u8> {
+    let mut data = Vec::with_capacity(8 + 32 + 32);
+    if let Some(discriminator) = discriminator {
+        data.extend_from_slice(&discriminator);
+    }
+    data.extend_from_slice(request.as_ref());
+    data.extend_from_slice(output_hash);
+    data
+}
+
+/// Load the AIValidator PDA of `authority`, apply `update` and write it back.
+fn update_validator(
+    program_id: &Pubkey,
+    info: &AccountInfo,
+    authority: &Pubkey,
+    update: impl FnOnce(&mut AIValidator),
+) -> ProgramResult {
+    assert_writable(info)?;
+    assert_owned_by(info, program_id)?;
+    let (pda, _) = Pubkey::find_program_address(&[AIValidator::SEED, authority.as_ref()], program_id);
+    if pda != *info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let mut validator = AIValidator::deserialize(&mut &info.try_borrow_data()?[..])?;
+    update(&mut validator);
+    validator.serialize_to_account(&mut info.try_borrow_mut_data()?)
+}
+
+/// A request may be expired once REQUEST_EXPIRY_SLOTS have passed without a
+/// result: still Pending, or Assigned with the deadline lapsed.
+fn check_expirable(req: &InferenceRequest, slot: u64) -> Result<(), AiError> {
+    let unserved = match req.status {
+        InferenceStatus::Pending => true,
+        InferenceStatus::Assigned => slot > req.assignment_deadline,
+        _ => false,
+    };
+    if !unserved {
+        return Err(AiError::InvalidStatus);
+    }
+    if slot.saturating_sub(req.created_slot) < REQUEST_EXPIRY_SLOTS {
+        return Err(AiError::RequestNotExpired);
+    }
+    Ok(())
+}
+
+/// Stake can be withdrawn UNSTAKE_COOLDOWN_SLOTS after RequestUnstake, once
+/// nothing that could slash it is still open.
+fn check_withdrawable(validator: &AIValidator, slot: u64) -> Result<(), AiError> {
+    if validator.unstake_requested_at == 0 {
+        return Err(AiError::UnstakeNotRequested);
+    }
+    if slot.saturating_sub(validator.unstake_requested_at) < UNSTAKE_COOLDOWN_SLOTS {
+        return Err(AiError::CooldownActive);
+    }
+    if validator.open_assignments > 0 || validator.open_disputes > 0 {
+        return Err(AiError::ObligationsOpen);
+    }
+    Ok(())
+}
+
+/// Amount to move out of a vault holding `balance` so it keeps either nothing
+/// or at least `rent_minimum`: a payout that would leave less sweeps the rest.
+fn vault_payout(balance: u64, amount: u64, rent_minimum: u64) -> u64 {
+    let rest = balance.saturating_sub(amount);
+    if rest > 0 && rest < rent_minimum {
+        balance
+    } else {
+        amount.min(balance)
+    }
+}
+
+/// Pay up to `amount` out of a system-owned vault PDA per `vault_payout`.
+/// Returns the amount moved.
+fn payout_from_vault<'a>(
+    vault: &AccountInfo<'a>,
+    to: &AccountInfo<'a>,
+    amount: u64,
+    system_program: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+) -> Result<u64, ProgramError> {
+    let rent_minimum = Rent::get()?.minimum_balance(vault.data_len());
+    let paid = vault_payout(vault.lamports(), amount, rent_minimum);
+    if paid > 0 {
+        transfer_from_vault(vault, to, paid, system_program, seeds)?;
+    }
+    Ok(paid)
+}
+
+/// Create the empty validator set of `model_hash`.
+fn create_validator_set<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    set_info: &AccountInfo<'a>,
+    system_prog: &AccountInfo<'a>,
+    model_hash: &[u8; 32],
+) -> ProgramResult {
+    assert_writable(set_info)?;
+    let (set_pda, set_bump) =
+        Pubkey::find_program_address(&[ModelValidatorSet::SEED, model_hash], program_id);
+    if set_pda != *set_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+
+    let seeds: &[&[u8]] = &[ModelValidatorSet::SEED, model_hash, &[set_bump]];
+    create_pda_account(payer, ModelValidatorSet::LEN, program_id, system_prog, set_info, seeds)?;
+
+    let set = ModelValidatorSet {
+        model_hash: *model_hash,
+        bump: set_bump,
+        validators: Vec::new(),
+    };
+    set.serialize(&mut &mut set_info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Most entries the SlotHashes sysvar holds.
+const SLOT_HASHES_MAX_ENTRIES: u64 = 512;
+
+/// Hash of the first slot after `draw_slot`, from SlotHashes sysvar data
+/// (u64 entry count, then (slot: u64, hash: [u8; 32]) newest first).
+/// `Ok(None)` if that slot may have aged out of the sysvar; `DrawNotReady` if
+/// no later slot has been recorded yet.
+fn committed_slot_hash(data: &[u8], draw_slot: u64) -> Result<Option<[u8; 32]>, ProgramError> {
+    let count = data
+        .get(..8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    let mut first_after = None;
+    let mut covers_draw_slot = count < SLOT_HASHES_MAX_ENTRIES;
+    for entry in data[8..].chunks_exact(40).take(count as usize) {
+        let slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        if slot <= draw_slot {
+            covers_draw_slot = true;
+            break;
+        }
+        first_after = Some(<[u8; 32]>::try_from(&entry[8..]).unwrap());
+    }
+
+    match first_after {
+        None => Err(AiError::DrawNotReady.into()),
+        Some(_) if !covers_draw_slot => Ok(None),
+        Some(hash) => Ok(Some(hash)),
+    }
+}
+
+/// Drop the validator that let the assignment lapse, unless nobody else is
+/// eligible.
+fn exclude_lapsed(candidates: &mut Vec<(Pubkey, u64)>, lapsed: Option<Pubkey>) {
+    if let Some(prev) = lapsed {
+        if candidates.iter().any(|(authority, _)| *authority != prev) {
+            candidates.retain(|(authority, _)| *authority != prev);
+        }
+    }
+}
+
+/// Pick a candidate with probability proportional to its stake.
+fn draw_weighted(candidates: &[(Pubkey, u64)], seed: &[u8; 32]) -> Option<Pubkey> {
+    let total_stake: u128 = candidates.iter().map(|(_, stake)| *stake as u128).sum();
+    if total_stake == 0 {
+        return None;
+    }
+
+    let mut draw = u64::from_le_bytes(seed[..8].try_into().unwrap()) as u128 % total_stake;
+    for (authority, stake) in candidates {
+        if draw < *stake as u128 {
+            return Some(*authority);
+        }
+        draw -= *stake as u128;
+    }
+    None
+}
+
+/// Fewest fingerprint entries a result may carry: one per output token up to
+/// MAX_FINGERPRINT_POSITIONS, and never fewer than the verifier quorum, since
+/// each vote samples its own entry.
+fn required_fingerprint_positions(output_tokens: u32, verifier_quorum: u8) -> usize {
+    (output_tokens as usize)
+        .min(MAX_FINGERPRINT_POSITIONS)
+        .max(verifier_quorum as usize)
+        .min(MAX_FINGERPRINT_POSITIONS)
+}
+
+/// Output token whose logits fingerprint entry `entry` holds. Derived from
+/// the request and the committed output, so the submitter cannot pick which
+/// tokens get checked.
+pub fn fingerprint_token_index(
+    request: &Pubkey,
+    output_hash: &[u8; 32],
+    entry: u32,
+    output_tokens: u32,
+) -> u32 {
+    let mut input = Vec::with_capacity(32 + 32 + 4);
+    input.extend_from_slice(request.as_ref());
+    input.extend_from_slice(output_hash);
+    input.extend_from_slice(&entry.to_le_bytes());
+    let hash = sha256(&input);
+    u32::from_le_bytes(hash[..4].try_into().unwrap()) % output_tokens.max(1)
+}
+
+/// Converts logits to Q16.16. The cast saturates (NaN becomes 0), so the
+/// result is the same on every validator.
+fn to_fixed_logits(logits: &[f32; 4]) -> [i32; 4] {
+    logits.map(|l| (l * LOGIT_FIXED_ONE as f32) as i32)
+}
+
+/// True if every observed logit is within LOGIT_TOLERANCE_BPS of the stored
+/// one. Tolerance is relative to the stored magnitude, floored at 1.0 so drift
+/// around zero is not amplified.
+fn logits_within_tolerance(stored: &[i32; 4], observed: &[i32; 4]) -> bool {
+    stored.iter().zip(observed).all(|(&s, &o)| {
+        let diff = (s as i64 - o as i64).unsigned_abs();
+        let magnitude = (s as i64).unsigned_abs().max(LOGIT_FIXED_ONE as u64);
+        diff * BPS_DENOMINATOR <= magnitude * LOGIT_TOLERANCE_BPS
+    })
+}
+
+/// Whether verifiers who found the result matching (`true`) or mismatching
+/// (`false`) won, once the outcome is final.
+fn winning_side(req: &InferenceRequest) -> Result<bool, ProgramError> {
+    match req.status {
+        InferenceStatus::Verified => Ok(true),
+        InferenceStatus::Failed if req.mismatch_votes > 0 => Ok(false),
+        _ => Err(AiError::InvalidStatus.into()),
+    }
+}
+
+/// Pays out of a system-owned vault PDA (escrow, stake) by signing a system
+/// transfer with the vault's seeds.
+fn transfer_from_vault<'a>(
+    vault: &AccountInfo<'a>,
+    to: &AccountInfo<'a>,
+    amount: u64,
+    system_program: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    invoke_signed(
+        &system_instruction::transfer(vault.key, to.key, amount),
+        &[vault.clone(), to.clone(), system_program.clone()],
+        &[seeds],
+    )
+}
+
+/// Whether CHALLENGE_WINDOW_SLOTS have passed since the result was submitted.
+fn challenge_window_closed(req: &InferenceRequest, clock: &Clock) -> bool {
+    clock.slot.saturating_sub(req.completed_slot) >= CHALLENGE_WINDOW_SLOTS
+}
+
+/// A result is final once verified, or once it has sat unchallenged for
+/// `CHALLENGE_WINDOW_SLOTS`. Votes cast short of the quorum hold it open until
+/// SettleVotes settles them.
+fn result_is_final(req: &InferenceRequest, clock: &Clock) -> bool {
+    match req.status {
+        InferenceStatus::Verified => true,
+        InferenceStatus::Completed => {
+            challenge_window_closed(req, clock) && req.match_votes == 0 && req.mismatch_votes == 0
+        }
+        _ => false,
+    }
+}
+
+/// Outcome of the votes on a completed result: none until the quorum has
+/// voted or the challenge window has closed on at least one vote, then
+/// Disputed if the mismatch share reaches `dispute_threshold_bps` and
+/// Verified otherwise.
+fn vote_outcome(
+    match_votes: u8,
+    mismatch_votes: u8,
+    verifier_quorum: u8,
+    dispute_threshold_bps: u16,
+    window_closed: bool,
+) -> Option<InferenceStatus> {
+    let votes = match_votes as u64 + mismatch_votes as u64;
+    if votes == 0 || (votes < verifier_quorum.max(1) as u64 && !window_closed) {
+        return None;
+    }
+    let disputed = mismatch_votes as u64 * BPS_DENOMINATOR >= dispute_threshold_bps as u64 * votes;
+    Some(if disputed { InferenceStatus::Disputed } else { InferenceStatus::Verified })
+}
+
+/// Equal share of `pool` for each of `winners`. Zero if a share is too small
+/// to leave the escrow rent-exempt between claims; the pool then stays with
+/// whoever the escrow pays out to instead.
+fn reward_each(pool: u64, winners: u8, rent_minimum: u64) -> u64 {
+    if winners == 0 {
+        return 0;
+    }
+    let each = pool / winners as u64;
+    if each < rent_minimum {
+        0
+    } else {
+        each
+    }
+}
+
+/// Fix the match voters' reward as the request is verified, so later changes
+/// to `verifier_reward_bps` do not move it.
+fn set_verified_reward(req: &mut InferenceRequest, verifier_reward_bps: u16) -> ProgramResult {
+    let cut = req
+        .escrowed_amount
+        .checked_mul(verifier_reward_bps as u64)
+        .ok_or(AiError::Overflow)?
+        / BPS_DENOMINATOR;
+    req.verifier_reward_each = reward_each(cut, req.match_votes, Rent::get()?.minimum_balance(0));
+    Ok(())
+}
+
+/// Move a completed request to the vote outcome: Disputed opens a dispute
+/// against the submitter, Verified fixes the verifier reward.
+fn settle_votes(
+    program_id: &Pubkey,
+    req: &mut InferenceRequest,
+    request_key: &Pubkey,
+    outcome: InferenceStatus,
+    config: &AIConfig,
+    submitter_info: &AccountInfo,
+) -> ProgramResult {
+    if outcome == InferenceStatus::Disputed {
+        // ResolveDispute settles the slash and refund
+        req.status = InferenceStatus::Disputed;
+        update_validator(program_id, submitter_info, &req.assigned_validator, |v| {
+            v.open_disputes = v.open_disputes.saturating_add(1);
+        })?;
+        msg!(
+            "EVENT:LogitMismatch:{{\"request\":\"{}\",\"match_votes\":{},\"mismatch_votes\":{}}}",
+            request_key,
+            req.match_votes,
+            req.mismatch_votes
+        );
+    } else {
+        req.status = InferenceStatus::Verified;
+        set_verified_reward(req, config.verifier_reward_bps)?;
+        msg!(
+            "EVENT:LogitVerified:{{\"request\":\"{}\",\"match_votes\":{},\"mismatch_votes\":{},\"verifier_reward_each\":{}}}",
+            request_key,
+            req.match_votes,
+            req.mismatch_votes,
+            req.verifier_reward_each
+        );
+    }
+    Ok(())
+}
+
 fn sha256(data: &[u8]) -> [u8; 32] {
     use sha2::Digest;
     let mut hasher = sha2::Sha256::new();
@@ -410,22 +1163,6 @@ fn transfer_lamports<'a>(
     )
 }
 
-fn transfer_lamports_signed<'a>(
-    from: &AccountInfo<'a>,
-    to: &AccountInfo<'a>,
-    amount: u64,
-) -> ProgramResult {
-    let from_balance = from.lamports();
-    let to_balance = to.lamports();
-    **from.try_borrow_mut_lamports()? = from_balance
-        .checked_sub(amount)
-        .ok_or(AiError::Overflow)?;
-    **to.try_borrow_mut_lamports()? = to_balance
-        .checked_add(amount)
-        .ok_or(AiError::Overflow)?;
-    Ok(())
-}
-
 // ---------------------------------------------------------------------------
 // 0 — Initialize
 // ---------------------------------------------------------------------------
@@ -468,9 +1205,12 @@ fn process_initialize(
         foundation: args.foundation,
         is_paused: false,
         bump,
+        verifier_quorum: DEFAULT_VERIFIER_QUORUM,
+        dispute_threshold_bps: DEFAULT_DISPUTE_THRESHOLD_BPS,
+        verifier_reward_bps: DEFAULT_VERIFIER_REWARD_BPS,
     };
 
-    config.serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+    config.serialize_to_account(&mut config_info.try_borrow_mut_data()?)?;
 
     msg!(
         "EVENT:Initialized:{{\"admin\":\"{}\",\"registration_fee\":{},\"min_stake\":{}}}",
@@ -484,6 +1224,10 @@ fn process_initialize(
 
 // ---------------------------------------------------------------------------
 // 1 — RegisterModel
+// Accounts: 0=[signer] owner, 1=[writable] model PDA, 2=[writable] config PDA,
+//           3=[writable] burn address, 4=[] system program,
+//           5=[writable] validator set PDA (optional; CreateValidatorSet
+//           creates it later otherwise)
 // ---------------------------------------------------------------------------
 
 fn process_register_model(
@@ -498,13 +1242,14 @@ fn process_register_model(
     let config_info = next_account_info(iter)?;
     let burn_info = next_account_info(iter)?;
     let system_prog = next_account_info(iter)?;
+    let validator_set_info = next_account_info(iter).ok();
 
     assert_signer(owner)?;
     assert_writable(model_info)?;
     assert_writable(config_info)?;
     assert_owned_by(config_info, program_id)?;
 
-    let config = AIConfig::try_from_slice(&config_info.try_borrow_data()?)?;
+    let config = AIConfig::deserialize(&mut &config_info.try_borrow_data()?[..])?;
     if !config.is_initialized {
         return Err(AiError::NotInitialized.into());
     }
@@ -564,6 +1309,10 @@ fn process_register_model(
 
     model.serialize(&mut &mut model_info.try_borrow_mut_data()?[..])?;
 
+    if let Some(set_info) = validator_set_info {
+        create_validator_set(program_id, owner, set_info, system_prog, &args.model_weights_hash)?;
+    }
+
     msg!(
         "EVENT:ModelRegistered:{{\"owner\":\"{}\",\"model_name\":\"{}\",\"parameter_count\":{}}}",
         owner.key,
@@ -595,7 +1344,7 @@ fn process_register_validator(
     assert_writable(validator_info)?;
     assert_owned_by(config_info, program_id)?;
 
-    let config = AIConfig::try_from_slice(&config_info.try_borrow_data()?)?;
+    let config = AIConfig::deserialize(&mut &config_info.try_borrow_data()?[..])?;
     if !config.is_initialized {
         return Err(AiError::NotInitialized.into());
     }
@@ -634,6 +1383,42 @@ fn process_register_validator(
         return Err(AiError::InvalidPDA.into());
     }
 
+    // Join the validator set of every supported model. One set account per
+    // entry in `supported_models`, in the same order, follows the system program.
+    // A full set evicts its lowest-staked member if the newcomer stakes more;
+    // the evicted validator is no longer drawn for that model.
+    for model_hash in &args.supported_models {
+        let set_info = next_account_info(iter)?;
+        assert_writable(set_info)?;
+        assert_owned_by(set_info, program_id)?;
+
+        let (set_pda, _) =
+            Pubkey::find_program_address(&[ModelValidatorSet::SEED, model_hash], program_id);
+        if set_pda != *set_info.key {
+            return Err(AiError::InvalidPDA.into());
+        }
+
+        let mut set = ModelValidatorSet::deserialize(&mut &set_info.try_borrow_data()?[..])?;
+        if set.validators.iter().any(|m| m.validator == validator_pda) {
+            continue;
+        }
+        if set.validators.len() >= MAX_MODEL_VALIDATORS {
+            let lowest = set.lowest_staked().ok_or(AiError::ValidatorSetFull)?;
+            if set.validators[lowest].stake >= args.stake_amount {
+                return Err(AiError::ValidatorSetFull.into());
+            }
+            let evicted = set.validators.remove(lowest);
+            msg!(
+                "EVENT:ValidatorEvicted:{{\"model_hash\":\"{}\",\"validator\":\"{}\",\"stake\":{}}}",
+                hex::encode(model_hash),
+                evicted.validator,
+                evicted.stake
+            );
+        }
+        set.validators.push(SetMember { validator: validator_pda, stake: args.stake_amount });
+        set.serialize(&mut &mut set_info.try_borrow_mut_data()?[..])?;
+    }
+
     // Transfer stake to vault
     transfer_lamports(authority, stake_vault, args.stake_amount, system_prog)?;
 
@@ -659,9 +1444,12 @@ fn process_register_validator(
         slash_count: 0,
         is_active: true,
         bump,
+        unstake_requested_at: 0,
+        open_assignments: 0,
+        open_disputes: 0,
     };
 
-    validator.serialize(&mut &mut validator_info.try_borrow_mut_data()?[..])?;
+    validator.serialize_to_account(&mut validator_info.try_borrow_mut_data()?)?;
 
     msg!(
         "EVENT:ValidatorRegistered:{{\"authority\":\"{}\",\"stake\":{},\"gpu\":\"{}\"}}",
@@ -699,7 +1487,7 @@ fn process_request_inference(
         return Err(AiError::InputTooLarge.into());
     }
 
-    let mut config = AIConfig::try_from_slice(&config_info.try_borrow_data()?)?;
+    let mut config = AIConfig::deserialize(&mut &config_info.try_borrow_data()?[..])?;
     if !config.is_initialized {
         return Err(AiError::NotInitialized.into());
     }
@@ -711,6 +1499,13 @@ fn process_request_inference(
         return Err(ProgramError::IncorrectProgramId);
     }
 
+    // The callback is invoked with the request PDA as signer, so it must not
+    // be this program.
+    let callback_program = args.callback_program.unwrap_or_default();
+    if callback_program == *program_id {
+        return Err(AiError::InvalidCallbackProgram.into());
+    }
+
     let nonce = config.request_nonce;
     let nonce_bytes = nonce.to_le_bytes();
 
@@ -762,13 +1557,25 @@ fn process_request_inference(
         completed_at: 0,
         nonce,
         bump,
+        completed_slot: 0,
+        callback_program,
+        callback_discriminator: args.callback_discriminator,
+        callback_invoked: false,
+        assignment_deadline: 0,
+        assignment_round: 0,
+        created_slot: clock.slot,
+        match_votes: 0,
+        mismatch_votes: 0,
+        sampled_positions: 0,
+        verifier_reward_each: 0,
+        draw_slot: clock.slot,
     };
 
-    req.serialize(&mut &mut request_info.try_borrow_mut_data()?[..])?;
+    req.serialize_to_account(&mut request_info.try_borrow_mut_data()?)?;
 
     // Increment nonce
     config.request_nonce = nonce.checked_add(1).ok_or(AiError::Overflow)?;
-    config.serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+    config.serialize_to_account(&mut config_info.try_borrow_mut_data()?)?;
 
     msg!(
         "EVENT:InferenceRequested:{{\"requester\":\"{}\",\"nonce\":{},\"max_fee\":{},\"model_hash\":\"{}\"}}",
@@ -783,6 +1590,14 @@ fn process_request_inference(
 
 // ---------------------------------------------------------------------------
 // 4 — SubmitResult
+// Accounts: 0=[signer, writable] validator authority, 1=[writable] request PDA,
+//           2=[writable] result PDA, 3=[writable] AIValidator PDA,
+//           4=[] system program, 5=[writable] logit fingerprint PDA,
+//           6=[] config PDA
+//
+// The fingerprint must cover every output token up to
+// MAX_FINGERPRINT_POSITIONS, and at least verifier_quorum entries; entry `i`
+// holds the logits of output token `fingerprint_token_index(.., i, ..)`.
 // ---------------------------------------------------------------------------
 
 fn process_submit_result(
@@ -797,15 +1612,18 @@ fn process_submit_result(
     let result_info = next_account_info(iter)?;
     let validator_info = next_account_info(iter)?;
     let system_prog = next_account_info(iter)?;
+    let fingerprint_info = next_account_info(iter)?;
+    let config_info = next_account_info(iter)?;
 
     assert_signer(validator_authority)?;
     assert_writable(request_info)?;
     assert_writable(result_info)?;
+    assert_writable(fingerprint_info)?;
     assert_owned_by(request_info, program_id)?;
     assert_owned_by(validator_info, program_id)?;
 
     // Validate validator is active
-    let mut validator_state = AIValidator::try_from_slice(&validator_info.try_borrow_data()?)?;
+    let mut validator_state = AIValidator::deserialize(&mut &validator_info.try_borrow_data()?[..])?;
     if !validator_state.is_active {
         return Err(AiError::ValidatorNotActive.into());
     }
@@ -813,11 +1631,20 @@ fn process_submit_result(
         return Err(AiError::Unauthorized.into());
     }
 
-    // Load request
-    let mut req = InferenceRequest::try_from_slice(&request_info.try_borrow_data()?)?;
-    if req.status != InferenceStatus::Pending && req.status != InferenceStatus::Assigned {
+    // Load request — only the assigned validator may submit, and only before
+    // its deadline
+    let clock = Clock::get()?;
+    assert_migrated(request_info, InferenceRequest::LEN)?;
+    let mut req = InferenceRequest::deserialize(&mut &request_info.try_borrow_data()?[..])?;
+    if req.status != InferenceStatus::Assigned {
         return Err(AiError::InvalidStatus.into());
     }
+    if req.assigned_validator != *validator_authority.key {
+        return Err(AiError::NotAssignee.into());
+    }
+    if clock.slot > req.assignment_deadline {
+        return Err(AiError::AssignmentExpired.into());
+    }
 
     if *system_prog.key != system_program::id() {
         return Err(ProgramError::IncorrectProgramId);
@@ -832,16 +1659,40 @@ fn process_submit_result(
         return Err(AiError::InvalidPDA.into());
     }
 
-    // Hash the logit fingerprint for storage
-    let logit_bytes: Vec<u8> = args
-        .logit_fingerprint
+    let (fingerprint_pda, fingerprint_bump) = Pubkey::find_program_address(
+        &[LogitFingerprint::SEED, request_info.key.as_ref()],
+        program_id,
+    );
+    if fingerprint_pda != *fingerprint_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+
+    if args.output_tokens == 0
+        || (req.max_output_len > 0 && args.output_tokens > req.max_output_len)
+    {
+        return Err(AiError::InvalidOutputTokens.into());
+    }
+    if args.logit_fingerprint.len() > MAX_FINGERPRINT_POSITIONS {
+        return Err(AiError::FingerprintTooLong.into());
+    }
+    assert_owned_by(config_info, program_id)?;
+    let (config_pda, _) = Pubkey::find_program_address(&[AIConfig::SEED], program_id);
+    if config_pda != *config_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let config = AIConfig::deserialize(&mut &config_info.try_borrow_data()?[..])?;
+    if args.logit_fingerprint.len() < required_fingerprint_positions(args.output_tokens, config.verifier_quorum) {
+        return Err(AiError::FingerprintTooShort.into());
+    }
+
+    // Store the fingerprint in fixed point; the hash commits to that form
+    let positions: Vec<[i32; 4]> = args.logit_fingerprint.iter().map(to_fixed_logits).collect();
+    let logit_bytes: Vec<u8> = positions
         .iter()
-        .flat_map(|arr| arr.iter().flat_map(|f| f.to_le_bytes()))
+        .flat_map(|arr| arr.iter().flat_map(|l| l.to_le_bytes()))
         .collect();
     let logit_fingerprint_hash = sha256(&logit_bytes);
 
-    let clock = Clock::get()?;
-
     // Create result PDA
     let seeds: &[&[u8]] = &[InferenceResult::SEED, request_info.key.as_ref(), &[bump]];
     create_pda_account(
@@ -865,18 +1716,40 @@ fn process_submit_result(
 
     result.serialize(&mut &mut result_info.try_borrow_mut_data()?[..])?;
 
+    let fingerprint_seeds: &[&[u8]] = &[
+        LogitFingerprint::SEED,
+        request_info.key.as_ref(),
+        &[fingerprint_bump],
+    ];
+    create_pda_account(
+        validator_authority,
+        LogitFingerprint::LEN,
+        program_id,
+        system_prog,
+        fingerprint_info,
+        fingerprint_seeds,
+    )?;
+
+    let fingerprint = LogitFingerprint {
+        request: *request_info.key,
+        bump: fingerprint_bump,
+        output_tokens: args.output_tokens,
+        positions,
+    };
+    fingerprint.serialize(&mut &mut fingerprint_info.try_borrow_mut_data()?[..])?;
+
     // Update request status
     req.status = InferenceStatus::Completed;
-    req.assigned_validator = *validator_authority.key;
     req.completed_at = clock.unix_timestamp;
-    req.serialize(&mut &mut request_info.try_borrow_mut_data()?[..])?;
+    req.completed_slot = clock.slot;
+    req.serialize_to_account(&mut request_info.try_borrow_mut_data()?)?;
 
     // Update validator stats
     validator_state.inference_count = validator_state
         .inference_count
         .checked_add(1)
         .ok_or(AiError::Overflow)?;
-    validator_state.serialize(&mut &mut validator_info.try_borrow_mut_data()?[..])?;
+    validator_state.serialize_to_account(&mut validator_info.try_borrow_mut_data()?)?;
 
     msg!(
         "EVENT:ResultSubmitted:{{\"request\":\"{}\",\"validator\":\"{}\",\"compute_units\":{}}}",
@@ -906,8 +1779,11 @@ fn process_verify_logits(
     let verifier_validator_info = next_account_info(iter)?;
     let submitter_validator_info = next_account_info(iter)?;
     let system_prog = next_account_info(iter)?;
+    let fingerprint_info = next_account_info(iter)?;
+    let config_info = next_account_info(iter)?;
 
     assert_signer(verifier_authority)?;
+    assert_writable(request_info)?;
     assert_writable(verification_info)?;
     assert_writable(submitter_validator_info)?;
     assert_writable(verifier_validator_info)?;
@@ -915,19 +1791,42 @@ fn process_verify_logits(
     assert_owned_by(result_info, program_id)?;
     assert_owned_by(verifier_validator_info, program_id)?;
     assert_owned_by(submitter_validator_info, program_id)?;
+    assert_migrated(request_info, InferenceRequest::LEN)?;
 
-    let mut req = InferenceRequest::try_from_slice(&request_info.try_borrow_data()?)?;
+    let mut req = InferenceRequest::deserialize(&mut &request_info.try_borrow_data()?[..])?;
     if req.status != InferenceStatus::Completed {
         return Err(AiError::InvalidStatus.into());
     }
 
+    // Votes are only counted while the result can still be challenged
+    let clock = Clock::get()?;
+    if challenge_window_closed(&req, &clock) {
+        return Err(AiError::ChallengeWindowClosed.into());
+    }
+
     let inf_result = InferenceResult::try_from_slice(&result_info.try_borrow_data()?)?;
     if inf_result.request != *request_info.key {
         return Err(ProgramError::InvalidAccountData);
     }
 
+    assert_owned_by(config_info, program_id)?;
+    let (config_pda, _) = Pubkey::find_program_address(&[AIConfig::SEED], program_id);
+    if config_pda != *config_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let config = AIConfig::deserialize(&mut &config_info.try_borrow_data()?[..])?;
+
+    // Each verifier samples a position nobody has checked yet
+    let position_bit = 1u32
+        .checked_shl(args.token_position)
+        .ok_or(AiError::InvalidTokenPosition)?;
+    if req.sampled_positions & position_bit != 0 {
+        return Err(AiError::PositionAlreadySampled.into());
+    }
+
     // Verifier must be different from submitter
-    let verifier_state = AIValidator::try_from_slice(&verifier_validator_info.try_borrow_data()?)?;
+    let verifier_state =
+        AIValidator::deserialize(&mut &verifier_validator_info.try_borrow_data()?[..])?;
     if verifier_state.authority != *verifier_authority.key {
         return Err(AiError::Unauthorized.into());
     }
@@ -955,38 +1854,22 @@ fn process_verify_logits(
         return Err(AiError::InvalidPDA.into());
     }
 
-    // Compare logits — the verifier provides what they computed for that position.
-    // In a real system, the original logits would be retrieved off-chain;
-    // here we check if the expected_logits hash matches stored fingerprint.
-    // For on-chain logic: check if any logit element differs by more than tolerance.
-    // Since the original logit_fingerprint is stored as a hash, the actual comparison
-    // is: verifier claims these are the expected logits. If the hash of the verifier's
-    // logits at this position differs from the stored hash, there's a mismatch.
-    //
-    // Simplified: the verifier re-ran the forward pass and provides expected_logits.
-    // The hash comparison determines match.
-    let verifier_logit_bytes: Vec<u8> = args
-        .expected_logits
-        .iter()
-        .flat_map(|f| f.to_le_bytes())
-        .collect();
-
-    // For this on-chain check, we mark as matched if the logits are within tolerance
-    // of the stored fingerprint hash. Since exact logit comparison requires the
-    // original data, we store the verification record and let the result stand.
-    // A full implementation would have the original logits stored off-chain with
-    // the hash serving as commitment.
-    let _verifier_hash = sha256(&verifier_logit_bytes);
-
-    // Determine match — simplified: always store the verification.
-    // In production, an oracle or the original logit data would be compared.
-    // Here we assume the verifier's provided logits are ground truth and
-    // check against the stored fingerprint hash.
-    let matched = true; // Placeholder — real impl compares off-chain data
-
-    // If mismatch detected (matched == false): slash submitter, reward verifier
-    // For now, the mismatch path is included for completeness
-    let clock = Clock::get()?;
+    // Compare the verifier's re-run logits at this position with the
+    // submitter's, element by element within LOGIT_TOLERANCE_BPS
+    assert_owned_by(fingerprint_info, program_id)?;
+    let (fingerprint_pda, _) = Pubkey::find_program_address(
+        &[LogitFingerprint::SEED, request_info.key.as_ref()],
+        program_id,
+    );
+    if fingerprint_pda != *fingerprint_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let fingerprint = LogitFingerprint::deserialize(&mut &fingerprint_info.try_borrow_data()?[..])?;
+    let stored = fingerprint
+        .positions
+        .get(args.token_position as usize)
+        .ok_or(AiError::InvalidTokenPosition)?;
+    let matched = logits_within_tolerance(stored, &to_fixed_logits(&args.expected_logits));
 
     let seeds: &[&[u8]] = &[
         VerificationRecord::SEED,
@@ -1010,57 +1893,61 @@ fn process_verify_logits(
         matched,
         verified_at: clock.unix_timestamp,
         bump,
+        settled: false,
     };
 
     record.serialize(&mut &mut verification_info.try_borrow_mut_data()?[..])?;
 
-    if !matched {
-        // Slash submitter
-        let mut submitter_state =
-            AIValidator::try_from_slice(&submitter_validator_info.try_borrow_data()?)?;
-        let slash_amount = submitter_state.stake_amount / 2;
-        submitter_state.stake_amount = submitter_state
-            .stake_amount
-            .checked_sub(slash_amount)
-            .ok_or(AiError::Overflow)?;
-        submitter_state.slash_count = submitter_state
-            .slash_count
-            .checked_add(1)
-            .ok_or(AiError::Overflow)?;
-        submitter_state
-            .serialize(&mut &mut submitter_validator_info.try_borrow_mut_data()?[..])?;
+    // Tally the vote
+    req.sampled_positions |= position_bit;
+    if matched {
+        req.match_votes = req.match_votes.checked_add(1).ok_or(AiError::Overflow)?;
+    } else {
+        req.mismatch_votes = req.mismatch_votes.checked_add(1).ok_or(AiError::Overflow)?;
+    }
 
-        // Reward verifier
-        transfer_lamports_signed(submitter_validator_info, verifier_validator_info, slash_amount)?;
+    msg!(
+        "EVENT:VerificationVote:{{\"request\":\"{}\",\"verifier\":\"{}\",\"position\":{},\"token_index\":{},\"matched\":{}}}",
+        request_info.key,
+        verifier_authority.key,
+        args.token_position,
+        fingerprint_token_index(
+            request_info.key,
+            &inf_result.output_hash,
+            args.token_position,
+            fingerprint.output_tokens
+        ),
+        matched
+    );
 
-        // Mark request as disputed
-        req.status = InferenceStatus::Disputed;
-        req.serialize(&mut &mut request_info.try_borrow_mut_data()?[..])?;
-
-        msg!(
-            "EVENT:LogitMismatch:{{\"request\":\"{}\",\"verifier\":\"{}\",\"position\":{},\"slash_amount\":{}}}",
-            request_info.key,
-            verifier_authority.key,
-            args.token_position,
-            slash_amount
-        );
-    } else {
-        req.status = InferenceStatus::Verified;
-        req.serialize(&mut &mut request_info.try_borrow_mut_data()?[..])?;
-
-        msg!(
-            "EVENT:LogitVerified:{{\"request\":\"{}\",\"verifier\":\"{}\",\"position\":{}}}",
-            request_info.key,
-            verifier_authority.key,
-            args.token_position
-        );
+    // Settle once the quorum has voted
+    let outcome = vote_outcome(
+        req.match_votes,
+        req.mismatch_votes,
+        config.verifier_quorum,
+        config.dispute_threshold_bps,
+        false,
+    );
+    if let Some(outcome) = outcome {
+        settle_votes(program_id, &mut req, request_info.key, outcome, &config, submitter_validator_info)?;
     }
 
+    req.serialize_to_account(&mut request_info.try_borrow_mut_data()?)?;
+
     Ok(())
 }
 
 // ---------------------------------------------------------------------------
 // 6 — ClaimInferenceFee
+// Accounts: 0=[signer, writable] validator authority, 1=[writable] request PDA,
+//           2=[writable] escrow vault, 3=[] config PDA, 4=[writable] foundation,
+//           5=[writable] burn address, 6=[] system program,
+//           7=[writable] validator's AIValidator PDA (omitted for requests
+//           assigned before AssignValidator existed)
+//
+// Pays out the escrow once the result is final and closes the validator's
+// assignment. The escrow is a system-owned PDA, so it pays through the system
+// program and never keeps less than its rent-exempt minimum.
 // ---------------------------------------------------------------------------
 
 fn process_claim_inference_fee(
@@ -1075,6 +1962,7 @@ fn process_claim_inference_fee(
     let config_info = next_account_info(iter)?;
     let foundation_info = next_account_info(iter)?;
     let burn_info = next_account_info(iter)?;
+    let system_prog = next_account_info(iter)?;
 
     assert_signer(validator_authority)?;
     assert_writable(request_info)?;
@@ -1082,8 +1970,12 @@ fn process_claim_inference_fee(
     assert_owned_by(request_info, program_id)?;
     assert_owned_by(config_info, program_id)?;
 
+    if *system_prog.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
     // Validate escrow vault PDA
-    let (expected_escrow, _) = Pubkey::find_program_address(
+    let (expected_escrow, escrow_bump) = Pubkey::find_program_address(
         &[b"escrow", request_info.key.as_ref()],
         program_id,
     );
@@ -1091,7 +1983,7 @@ fn process_claim_inference_fee(
         return Err(AiError::InvalidPDA.into());
     }
 
-    let config = AIConfig::try_from_slice(&config_info.try_borrow_data()?)?;
+    let config = AIConfig::deserialize(&mut &config_info.try_borrow_data()?[..])?;
     if !config.is_initialized {
         return Err(AiError::NotInitialized.into());
     }
@@ -1103,7 +1995,7 @@ fn process_claim_inference_fee(
         return Err(ProgramError::InvalidAccountData);
     }
 
-    let mut req = InferenceRequest::try_from_slice(&request_info.try_borrow_data()?)?;
+    let mut req = InferenceRequest::deserialize(&mut &request_info.try_borrow_data()?[..])?;
     if req.status != InferenceStatus::Completed && req.status != InferenceStatus::Verified {
         return Err(AiError::InvalidStatus.into());
     }
@@ -1111,16 +2003,18 @@ fn process_claim_inference_fee(
     if req.assigned_validator != *validator_authority.key {
         return Err(AiError::Unauthorized.into());
     }
+    if req.escrowed_amount == 0 {
+        return Err(AiError::AlreadySettled.into());
+    }
 
     // Check challenge window
     let clock = Clock::get()?;
-    let slots_since = clock.slot.saturating_sub(req.completed_at as u64);
-    if req.status == InferenceStatus::Completed && slots_since < CHALLENGE_WINDOW_SLOTS {
+    if !result_is_final(&req, &clock) {
         return Err(AiError::ChallengeWindowOpen.into());
     }
 
     let total = req.escrowed_amount;
-    let validator_share = total
+    let mut validator_share = total
         .checked_mul(VALIDATOR_FEE_BPS)
         .ok_or(AiError::Overflow)?
         / BPS_DENOMINATOR;
@@ -1133,22 +2027,48 @@ fn process_claim_inference_fee(
         .ok_or(AiError::Overflow)?
         / BPS_DENOMINATOR;
 
-    // Transfer from escrow vault (PDA or system account controlled by program)
-    transfer_lamports_signed(escrow_vault, validator_authority, validator_share)?;
-    transfer_lamports_signed(escrow_vault, foundation_info, foundation_share)?;
-    transfer_lamports_signed(escrow_vault, burn_info, burn_share)?;
+    // Verifiers who voted a result through take their cut, fixed when it was
+    // verified, out of the validator share; it stays in escrow for
+    // ClaimVerifierReward
+    let mut verifier_pool = 0;
+    if req.status == InferenceStatus::Verified {
+        verifier_pool = req
+            .verifier_reward_each
+            .checked_mul(req.match_votes as u64)
+            .ok_or(AiError::Overflow)?;
+        validator_share = validator_share
+            .checked_sub(verifier_pool)
+            .ok_or(AiError::Overflow)?;
+    }
+
+    // The escrow is a system-owned PDA; the last payout sweeps any remainder
+    // that could not stay rent-exempt
+    let seeds: &[&[u8]] = &[b"escrow", request_info.key.as_ref(), &[escrow_bump]];
+    let validator_share =
+        payout_from_vault(escrow_vault, validator_authority, validator_share, system_prog, seeds)?;
+    let foundation_share =
+        payout_from_vault(escrow_vault, foundation_info, foundation_share, system_prog, seeds)?;
+    let burn_share = payout_from_vault(escrow_vault, burn_info, burn_share, system_prog, seeds)?;
+
+    if req.counts_assignment() {
+        let validator_info = next_account_info(iter)?;
+        update_validator(program_id, validator_info, validator_authority.key, |v| {
+            v.open_assignments = v.open_assignments.saturating_sub(1);
+        })?;
+    }
 
     // Mark request as completed/claimed by setting escrowed to 0
     req.escrowed_amount = 0;
-    req.serialize(&mut &mut request_info.try_borrow_mut_data()?[..])?;
+    req.serialize_to_account(&mut request_info.try_borrow_mut_data()?)?;
 
     msg!(
-        "EVENT:FeeClaimed:{{\"request\":\"{}\",\"validator\":\"{}\",\"validator_share\":{},\"foundation_share\":{},\"burned\":{}}}",
+        "EVENT:FeeClaimed:{{\"request\":\"{}\",\"validator\":\"{}\",\"validator_share\":{},\"foundation_share\":{},\"burned\":{},\"verifier_pool\":{}}}",
         request_info.key,
         validator_authority.key,
         validator_share,
         foundation_share,
-        burn_share
+        burn_share,
+        verifier_pool
     );
 
     Ok(())
@@ -1189,7 +2109,7 @@ fn process_pause(
         return Err(AiError::InvalidPDA.into());
     }
 
-    let mut config = AIConfig::try_from_slice(&config_info.try_borrow_data()?)?;
+    let mut config = AIConfig::deserialize(&mut &config_info.try_borrow_data()?[..])?;
     if !config.is_initialized {
         return Err(AiError::NotInitialized.into());
     }
@@ -1198,7 +2118,7 @@ fn process_pause(
     }
 
     config.is_paused = true;
-    config.serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+    config.serialize_to_account(&mut config_info.try_borrow_mut_data()?)?;
 
     msg!("EVENT:Paused:{{\"admin\":\"{}\"}}", admin.key);
     Ok(())
@@ -1226,7 +2146,7 @@ fn process_unpause(
         return Err(AiError::InvalidPDA.into());
     }
 
-    let mut config = AIConfig::try_from_slice(&config_info.try_borrow_data()?)?;
+    let mut config = AIConfig::deserialize(&mut &config_info.try_borrow_data()?[..])?;
     if !config.is_initialized {
         return Err(AiError::NotInitialized.into());
     }
@@ -1235,8 +2155,1569 @@ fn process_unpause(
     }
 
     config.is_paused = false;
-    config.serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+    config.serialize_to_account(&mut config_info.try_borrow_mut_data()?)?;
 
     msg!("EVENT:Unpaused:{{\"admin\":\"{}\"}}", admin.key);
     Ok(())
 }
+
+// ---------------------------------------------------------------------------
+// 9 — InvokeCallback (permissionless crank)
+// Accounts: 0=[writable] request PDA, 1=[] result PDA, 2=[] callback program,
+//           3..=accounts forwarded to the callback
+//
+// Callback instruction data: callback_discriminator(8, if set) || request(32)
+// || output_hash(32). Callback accounts: 0=[signer] request PDA, 1=[] result PDA,
+// then the forwarded accounts. The request PDA signs so the callback can check
+// it is owned by this program.
+// ---------------------------------------------------------------------------
+
+fn process_invoke_callback(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let iter = &mut accounts.iter();
+    let request_info = next_account_info(iter)?;
+    let result_info = next_account_info(iter)?;
+    let callback_info = next_account_info(iter)?;
+    let forwarded: Vec<AccountInfo> = iter.cloned().collect();
+
+    assert_writable(request_info)?;
+    assert_owned_by(request_info, program_id)?;
+    assert_owned_by(result_info, program_id)?;
+
+    let mut req = InferenceRequest::deserialize(&mut &request_info.try_borrow_data()?[..])?;
+    if !req.has_callback() {
+        return Err(AiError::NoCallback.into());
+    }
+    if req.callback_invoked {
+        return Err(AiError::CallbackAlreadyInvoked.into());
+    }
+    if *callback_info.key != req.callback_program {
+        return Err(AiError::InvalidCallbackProgram.into());
+    }
+
+    let clock = Clock::get()?;
+    if !result_is_final(&req, &clock) {
+        return Err(AiError::ChallengeWindowOpen.into());
+    }
+
+    let (result_pda, _) = Pubkey::find_program_address(
+        &[InferenceResult::SEED, request_info.key.as_ref()],
+        program_id,
+    );
+    if result_pda != *result_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let inf_result = InferenceResult::try_from_slice(&result_info.try_borrow_data()?)?;
+    if inf_result.request != *request_info.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Mark before the CPI so a re-entrant crank cannot fire the callback twice.
+    req.callback_invoked = true;
+    req.serialize_to_account(&mut request_info.try_borrow_mut_data()?)?;
+
+    let data = callback_data(req.callback_discriminator, request_info.key, &inf_result.output_hash);
+
+    let mut metas = vec![
+        AccountMeta::new_readonly(*request_info.key, true),
+        AccountMeta::new_readonly(*result_info.key, false),
+    ];
+    metas.extend(forwarded.iter().map(|a| AccountMeta {
+        pubkey: *a.key,
+        is_signer: a.is_signer,
+        is_writable: a.is_writable,
+    }));
+
+    let mut infos = vec![request_info.clone(), result_info.clone()];
+    infos.extend(forwarded);
+    infos.push(callback_info.clone());
+
+    let nonce_bytes = req.nonce.to_le_bytes();
+    let seeds: &[&[u8]] = &[InferenceRequest::SEED, &nonce_bytes, &[req.bump]];
+    invoke_signed(
+        &Instruction {
+            program_id: req.callback_program,
+            accounts: metas,
+            data,
+        },
+        &infos,
+        &[seeds],
+    )?;
+
+    msg!(
+        "EVENT:CallbackInvoked:{{\"request\":\"{}\",\"callback_program\":\"{}\",\"output_hash\":\"{}\"}}",
+        request_info.key,
+        req.callback_program,
+        hex::encode(inf_result.output_hash)
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 10 — AssignValidator (permissionless crank)
+// Accounts: 0=[writable] request PDA, 1=[writable] model validator set,
+//           2=[] SlotHashes sysvar, 3..=[writable] every AIValidator in the
+//           set, in order, then the lapsed assignee's AIValidator if it has
+//           left the set
+//
+// Draws a validator that supports the request's model, weighted by stake. The
+// draw is committed to `draw_slot` (the request's creation, then each
+// assignment's deadline) and seeded from the hash of the first slot after it,
+// so the cranker cannot pick the seed by choosing when to crank. If that hash
+// has aged out of SlotHashes the draw is recommitted to the current slot. A
+// Pending request is assigned; an Assigned one is reassigned once its
+// deadline has passed, excluding the validator that let it lapse if anyone
+// else is eligible. Only migrated validators are drawn, since the draw
+// counts the assignment against the assignee.
+// ---------------------------------------------------------------------------
+
+fn process_assign_validator(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let iter = &mut accounts.iter();
+    let request_info = next_account_info(iter)?;
+    let set_info = next_account_info(iter)?;
+    let slot_hashes_info = next_account_info(iter)?;
+
+    assert_writable(request_info)?;
+    assert_writable(set_info)?;
+    assert_owned_by(request_info, program_id)?;
+    assert_owned_by(set_info, program_id)?;
+
+    if *slot_hashes_info.key != sysvar::slot_hashes::id() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let clock = Clock::get()?;
+    assert_migrated(request_info, InferenceRequest::LEN)?;
+    let mut req = InferenceRequest::deserialize(&mut &request_info.try_borrow_data()?[..])?;
+    let lapsed = match req.status {
+        InferenceStatus::Pending => None,
+        InferenceStatus::Assigned if clock.slot > req.assignment_deadline => {
+            Some(req.assigned_validator)
+        }
+        InferenceStatus::Assigned => return Err(AiError::AssignmentActive.into()),
+        _ => return Err(AiError::InvalidStatus.into()),
+    };
+
+    let (set_pda, _) =
+        Pubkey::find_program_address(&[ModelValidatorSet::SEED, &req.model_hash], program_id);
+    if set_pda != *set_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let mut set = ModelValidatorSet::deserialize(&mut &set_info.try_borrow_data()?[..])?;
+
+    // Every member must be passed so the cranker cannot narrow the draw. Their
+    // stake snapshots are refreshed on the way.
+    let mut candidates: Vec<(Pubkey, u64)> = Vec::with_capacity(set.validators.len());
+    let mut member_infos = Vec::with_capacity(set.validators.len());
+    for member in set.validators.iter_mut() {
+        let validator_info = next_account_info(iter)?;
+        if *validator_info.key != member.validator {
+            return Err(AiError::InvalidPDA.into());
+        }
+        assert_owned_by(validator_info, program_id)?;
+
+        let state = AIValidator::deserialize(&mut &validator_info.try_borrow_data()?[..])?;
+        member.stake = state.stake_amount;
+        if state.is_active
+            && state.stake_amount > 0
+            && state.supported_models.contains(&req.model_hash)
+            && validator_info.data_len() >= AIValidator::LEN
+        {
+            candidates.push((state.authority, state.stake_amount));
+        }
+        member_infos.push((state.authority, validator_info));
+    }
+    set.serialize(&mut &mut set_info.try_borrow_mut_data()?[..])?;
+    exclude_lapsed(&mut candidates, lapsed);
+
+    let committed_hash = {
+        let data = slot_hashes_info.try_borrow_data()?;
+        committed_slot_hash(&data, req.draw_slot)?
+    };
+    let Some(committed_hash) = committed_hash else {
+        req.draw_slot = clock.slot;
+        req.serialize_to_account(&mut request_info.try_borrow_mut_data()?)?;
+        msg!(
+            "EVENT:AssignmentRecommitted:{{\"request\":\"{}\",\"draw_slot\":{}}}",
+            request_info.key,
+            req.draw_slot
+        );
+        return Ok(());
+    };
+
+    let mut seed_input = Vec::with_capacity(32 + 32 + 4);
+    seed_input.extend_from_slice(&committed_hash);
+    seed_input.extend_from_slice(request_info.key.as_ref());
+    seed_input.extend_from_slice(&req.assignment_round.to_le_bytes());
+    let assignee = draw_weighted(&candidates, &sha256(&seed_input))
+        .ok_or(AiError::NoEligibleValidator)?;
+
+    let member_info = |authority: &Pubkey| {
+        member_infos
+            .iter()
+            .find(|(a, _)| a == authority)
+            .map(|(_, info)| *info)
+    };
+    if let Some(prev) = lapsed.filter(|_| req.counts_assignment()) {
+        let lapsed_info = match member_info(&prev) {
+            Some(info) => info,
+            None => next_account_info(iter)?,
+        };
+        update_validator(program_id, lapsed_info, &prev, |v| {
+            v.open_assignments = v.open_assignments.saturating_sub(1);
+        })?;
+    }
+    let assignee_info = member_info(&assignee).ok_or(AiError::InvalidPDA)?;
+    update_validator(program_id, assignee_info, &assignee, |v| {
+        v.open_assignments = v.open_assignments.saturating_add(1);
+    })?;
+
+    req.status = InferenceStatus::Assigned;
+    req.assigned_validator = assignee;
+    req.assignment_deadline = clock
+        .slot
+        .checked_add(ASSIGNMENT_DEADLINE_SLOTS)
+        .ok_or(AiError::Overflow)?;
+    req.assignment_round = req.assignment_round.checked_add(1).ok_or(AiError::Overflow)?;
+    // A redraw after this assignment lapses is committed to its deadline
+    req.draw_slot = req.assignment_deadline;
+    req.serialize_to_account(&mut request_info.try_borrow_mut_data()?)?;
+
+    msg!(
+        "EVENT:ValidatorAssigned:{{\"request\":\"{}\",\"validator\":\"{}\",\"deadline\":{},\"round\":{}}}",
+        request_info.key,
+        assignee,
+        req.assignment_deadline,
+        req.assignment_round
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 11 — ExpireRequest (permissionless crank)
+// Accounts: 0=[writable] request PDA, 1=[writable] escrow vault,
+//           2=[writable] requester, 3=[] system program,
+//           4=[writable] assignee AIValidator (Assigned requests only; omitted
+//           if assigned before AssignValidator existed)
+//
+// Refunds the escrow of a request nobody served within REQUEST_EXPIRY_SLOTS:
+// still Pending, or Assigned with the assignee's deadline lapsed.
+// ---------------------------------------------------------------------------
+
+fn process_expire_request(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let iter = &mut accounts.iter();
+    let request_info = next_account_info(iter)?;
+    let escrow_vault = next_account_info(iter)?;
+    let requester = next_account_info(iter)?;
+    let system_prog = next_account_info(iter)?;
+
+    assert_writable(request_info)?;
+    assert_writable(escrow_vault)?;
+    assert_writable(requester)?;
+    assert_owned_by(request_info, program_id)?;
+
+    if *system_prog.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut req = InferenceRequest::deserialize(&mut &request_info.try_borrow_data()?[..])?;
+    let clock = Clock::get()?;
+    check_expirable(&req, clock.slot)?;
+
+    if *requester.key != req.requester {
+        return Err(AiError::Unauthorized.into());
+    }
+
+    let (expected_escrow, escrow_bump) = Pubkey::find_program_address(
+        &[b"escrow", request_info.key.as_ref()],
+        program_id,
+    );
+    if *escrow_vault.key != expected_escrow {
+        return Err(AiError::InvalidPDA.into());
+    }
+
+    if req.status == InferenceStatus::Assigned && req.counts_assignment() {
+        let assignee_info = next_account_info(iter)?;
+        update_validator(program_id, assignee_info, &req.assigned_validator, |v| {
+            v.open_assignments = v.open_assignments.saturating_sub(1);
+        })?;
+    }
+
+    let refund = req.escrowed_amount;
+    if refund > 0 {
+        let seeds: &[&[u8]] = &[b"escrow", request_info.key.as_ref(), &[escrow_bump]];
+        transfer_from_vault(escrow_vault, requester, refund, system_prog, seeds)?;
+    }
+
+    req.status = InferenceStatus::Failed;
+    req.escrowed_amount = 0;
+    req.serialize_to_account(&mut request_info.try_borrow_mut_data()?)?;
+
+    msg!(
+        "EVENT:RequestExpired:{{\"request\":\"{}\",\"requester\":\"{}\",\"refund\":{}}}",
+        request_info.key,
+        requester.key,
+        refund
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 12 — ResolveDispute (admin-only)
+// Accounts: 0=[signer] admin, 1=[] config PDA, 2=[writable] request PDA,
+//           3=[writable] submitter AIValidator, 4=[writable] submitter stake
+//           vault, 5=[writable] escrow vault, 6=[writable] requester,
+//           7=[] system program
+//
+// Upheld: slash DISPUTE_SLASH_BPS of the submitter's stake into the escrow,
+// shared by the mismatch voters via ClaimVerifierReward, and refund the fee to
+// the requester. Rejected: the result stands as verified. Either way the
+// submitter's open dispute is closed.
+// ---------------------------------------------------------------------------
+
+fn process_resolve_dispute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = ResolveDisputeArgs::try_from_slice(data)?;
+    let iter = &mut accounts.iter();
+    let admin = next_account_info(iter)?;
+    let config_info = next_account_info(iter)?;
+    let request_info = next_account_info(iter)?;
+    let submitter_validator_info = next_account_info(iter)?;
+    let stake_vault = next_account_info(iter)?;
+    let escrow_vault = next_account_info(iter)?;
+    let requester = next_account_info(iter)?;
+    let system_prog = next_account_info(iter)?;
+
+    assert_signer(admin)?;
+    assert_writable(request_info)?;
+    assert_owned_by(config_info, program_id)?;
+    assert_owned_by(request_info, program_id)?;
+
+    let (config_pda, _) = Pubkey::find_program_address(&[AIConfig::SEED], program_id);
+    if config_pda != *config_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let config = AIConfig::deserialize(&mut &config_info.try_borrow_data()?[..])?;
+    if !config.is_initialized {
+        return Err(AiError::NotInitialized.into());
+    }
+    if *admin.key != config.admin {
+        return Err(AiError::Unauthorized.into());
+    }
+
+    if *system_prog.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut req = InferenceRequest::deserialize(&mut &request_info.try_borrow_data()?[..])?;
+    if req.status != InferenceStatus::Disputed {
+        return Err(AiError::InvalidStatus.into());
+    }
+
+    if !args.upheld {
+        update_validator(program_id, submitter_validator_info, &req.assigned_validator, |v| {
+            v.open_disputes = v.open_disputes.saturating_sub(1);
+        })?;
+        req.status = InferenceStatus::Verified;
+        set_verified_reward(&mut req, config.verifier_reward_bps)?;
+        req.serialize_to_account(&mut request_info.try_borrow_mut_data()?)?;
+
+        msg!(
+            "EVENT:DisputeRejected:{{\"request\":\"{}\",\"match_votes\":{},\"mismatch_votes\":{}}}",
+            request_info.key,
+            req.match_votes,
+            req.mismatch_votes
+        );
+        return Ok(());
+    }
+
+    assert_writable(submitter_validator_info)?;
+    assert_writable(stake_vault)?;
+    assert_writable(escrow_vault)?;
+    assert_writable(requester)?;
+    assert_owned_by(submitter_validator_info, program_id)?;
+
+    let submitter = req.assigned_validator;
+    let (submitter_pda, _) =
+        Pubkey::find_program_address(&[AIValidator::SEED, submitter.as_ref()], program_id);
+    if submitter_pda != *submitter_validator_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let (expected_stake_vault, stake_vault_bump) =
+        Pubkey::find_program_address(&[b"stake_vault", submitter.as_ref()], program_id);
+    if expected_stake_vault != *stake_vault.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let (expected_escrow, escrow_bump) = Pubkey::find_program_address(
+        &[b"escrow", request_info.key.as_ref()],
+        program_id,
+    );
+    if expected_escrow != *escrow_vault.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    if *requester.key != req.requester {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Slash the submitter's stake into the escrow for the mismatch voters
+    let mut submitter_state =
+        AIValidator::deserialize(&mut &submitter_validator_info.try_borrow_data()?[..])?;
+    let slash_amount = submitter_state
+        .stake_amount
+        .checked_mul(DISPUTE_SLASH_BPS)
+        .ok_or(AiError::Overflow)?
+        / BPS_DENOMINATOR;
+    let seeds: &[&[u8]] = &[b"stake_vault", submitter.as_ref(), &[stake_vault_bump]];
+    let slash_amount = payout_from_vault(stake_vault, escrow_vault, slash_amount, system_prog, seeds)?;
+    submitter_state.stake_amount = submitter_state.stake_amount.saturating_sub(slash_amount);
+    submitter_state.slash_count = submitter_state
+        .slash_count
+        .checked_add(1)
+        .ok_or(AiError::Overflow)?;
+    submitter_state.open_disputes = submitter_state.open_disputes.saturating_sub(1);
+    if req.counts_assignment() {
+        submitter_state.open_assignments = submitter_state.open_assignments.saturating_sub(1);
+    }
+    submitter_state.serialize_to_account(&mut submitter_validator_info.try_borrow_mut_data()?)?;
+
+    // Mismatch voters share the slash, unless a share is too small to leave
+    // the escrow rent-exempt between claims; then it goes to the requester
+    let rent_minimum = Rent::get()?.minimum_balance(escrow_vault.data_len());
+    req.verifier_reward_each = reward_each(slash_amount, req.mismatch_votes, rent_minimum);
+    let mut refund = req.escrowed_amount;
+    if req.verifier_reward_each == 0 {
+        refund = refund.checked_add(slash_amount).ok_or(AiError::Overflow)?;
+    }
+
+    // Refund the requester
+    if refund > 0 {
+        let seeds: &[&[u8]] = &[b"escrow", request_info.key.as_ref(), &[escrow_bump]];
+        refund = payout_from_vault(escrow_vault, requester, refund, system_prog, seeds)?;
+    }
+
+    req.status = InferenceStatus::Failed;
+    req.escrowed_amount = 0;
+    req.serialize_to_account(&mut request_info.try_borrow_mut_data()?)?;
+
+    msg!(
+        "EVENT:DisputeUpheld:{{\"request\":\"{}\",\"validator\":\"{}\",\"slash_amount\":{},\"refund\":{},\"mismatch_votes\":{}}}",
+        request_info.key,
+        submitter,
+        slash_amount,
+        refund,
+        req.mismatch_votes
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 13 — RequestUnstake
+// Accounts: 0=[signer] authority, 1=[writable] AIValidator PDA,
+//           2..=[writable] validator set of each supported model, in order
+//
+// Deactivates the validator and leaves its model sets. Stake stays slashable
+// until WithdrawStake after UNSTAKE_COOLDOWN_SLOTS.
+// ---------------------------------------------------------------------------
+
+fn process_request_unstake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let iter = &mut accounts.iter();
+    let authority = next_account_info(iter)?;
+    let validator_info = next_account_info(iter)?;
+
+    assert_signer(authority)?;
+    assert_writable(validator_info)?;
+    assert_owned_by(validator_info, program_id)?;
+    assert_migrated(validator_info, AIValidator::LEN)?;
+
+    let mut validator = AIValidator::deserialize(&mut &validator_info.try_borrow_data()?[..])?;
+    if validator.authority != *authority.key {
+        return Err(AiError::Unauthorized.into());
+    }
+    if validator.unstake_requested_at != 0 {
+        return Err(AiError::UnstakeAlreadyRequested.into());
+    }
+
+    for model_hash in &validator.supported_models {
+        let set_info = next_account_info(iter)?;
+        assert_writable(set_info)?;
+        assert_owned_by(set_info, program_id)?;
+
+        let (set_pda, _) =
+            Pubkey::find_program_address(&[ModelValidatorSet::SEED, model_hash], program_id);
+        if set_pda != *set_info.key {
+            return Err(AiError::InvalidPDA.into());
+        }
+
+        let mut set = ModelValidatorSet::deserialize(&mut &set_info.try_borrow_data()?[..])?;
+        set.validators.retain(|m| m.validator != *validator_info.key);
+        set.serialize(&mut &mut set_info.try_borrow_mut_data()?[..])?;
+    }
+
+    let clock = Clock::get()?;
+    validator.is_active = false;
+    validator.unstake_requested_at = clock.slot;
+    validator.serialize_to_account(&mut validator_info.try_borrow_mut_data()?)?;
+
+    msg!(
+        "EVENT:UnstakeRequested:{{\"authority\":\"{}\",\"stake\":{},\"withdrawable_at\":{}}}",
+        authority.key,
+        validator.stake_amount,
+        clock.slot.saturating_add(UNSTAKE_COOLDOWN_SLOTS)
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 14 — WithdrawStake
+// Accounts: 0=[signer, writable] authority, 1=[writable] AIValidator PDA,
+//           2=[writable] stake vault, 3=[] system program
+//
+// Blocked while the validator has open assignments or disputes.
+// ---------------------------------------------------------------------------
+
+fn process_withdraw_stake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let iter = &mut accounts.iter();
+    let authority = next_account_info(iter)?;
+    let validator_info = next_account_info(iter)?;
+    let stake_vault = next_account_info(iter)?;
+    let system_prog = next_account_info(iter)?;
+
+    assert_signer(authority)?;
+    assert_writable(authority)?;
+    assert_writable(validator_info)?;
+    assert_writable(stake_vault)?;
+    assert_owned_by(validator_info, program_id)?;
+
+    if *system_prog.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut validator = AIValidator::deserialize(&mut &validator_info.try_borrow_data()?[..])?;
+    if validator.authority != *authority.key {
+        return Err(AiError::Unauthorized.into());
+    }
+    let clock = Clock::get()?;
+    check_withdrawable(&validator, clock.slot)?;
+
+    let (expected_stake_vault, stake_vault_bump) =
+        Pubkey::find_program_address(&[b"stake_vault", authority.key.as_ref()], program_id);
+    if *stake_vault.key != expected_stake_vault {
+        return Err(AiError::InvalidPDA.into());
+    }
+
+    let seeds: &[&[u8]] = &[b"stake_vault", authority.key.as_ref(), &[stake_vault_bump]];
+    let amount = payout_from_vault(stake_vault, authority, validator.stake_amount, system_prog, seeds)?;
+
+    validator.stake_amount = 0;
+    validator.serialize_to_account(&mut validator_info.try_borrow_mut_data()?)?;
+
+    msg!(
+        "EVENT:StakeWithdrawn:{{\"authority\":\"{}\",\"amount\":{}}}",
+        authority.key,
+        amount
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 15 — SetVerificationParams (admin-only)
+// Accounts: 0=[signer] admin, 1=[writable] config PDA
+// ---------------------------------------------------------------------------
+
+fn process_set_verification_params(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SetVerificationParamsArgs::try_from_slice(data)?;
+    let iter = &mut accounts.iter();
+    let admin = next_account_info(iter)?;
+    let config_info = next_account_info(iter)?;
+
+    assert_signer(admin)?;
+    assert_writable(config_info)?;
+    assert_owned_by(config_info, program_id)?;
+
+    let (config_pda, _) = Pubkey::find_program_address(&[AIConfig::SEED], program_id);
+    if *config_info.key != config_pda {
+        return Err(AiError::InvalidPDA.into());
+    }
+    assert_migrated(config_info, AIConfig::LEN)?;
+
+    let mut config = AIConfig::deserialize(&mut &config_info.try_borrow_data()?[..])?;
+    if !config.is_initialized {
+        return Err(AiError::NotInitialized.into());
+    }
+    if admin.key != &config.admin {
+        return Err(AiError::Unauthorized.into());
+    }
+
+    if args.verifier_quorum == 0
+        || args.verifier_quorum > MAX_VERIFIER_QUORUM
+        || args.dispute_threshold_bps == 0
+        || args.dispute_threshold_bps as u64 > BPS_DENOMINATOR
+        || args.verifier_reward_bps as u64 > VALIDATOR_FEE_BPS
+    {
+        return Err(AiError::InvalidVerificationParams.into());
+    }
+
+    config.verifier_quorum = args.verifier_quorum;
+    config.dispute_threshold_bps = args.dispute_threshold_bps;
+    config.verifier_reward_bps = args.verifier_reward_bps;
+    config.serialize_to_account(&mut config_info.try_borrow_mut_data()?)?;
+
+    msg!(
+        "EVENT:VerificationParamsSet:{{\"quorum\":{},\"dispute_threshold_bps\":{},\"verifier_reward_bps\":{}}}",
+        args.verifier_quorum,
+        args.dispute_threshold_bps,
+        args.verifier_reward_bps
+    );
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 16 — ClaimVerifierReward
+// Accounts: 0=[signer, writable] verifier authority, 1=[] request PDA,
+//           2=[writable] verification record, 3=[writable] escrow vault,
+//           4=[] system program
+//
+// Pays `verifier_reward_each` to a verifier who voted with the final outcome:
+// the fee cut, fixed when the result was verified, or the slashed stake once
+// a dispute is upheld.
+// ---------------------------------------------------------------------------
+
+fn process_claim_verifier_reward(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let iter = &mut accounts.iter();
+    let verifier = next_account_info(iter)?;
+    let request_info = next_account_info(iter)?;
+    let verification_info = next_account_info(iter)?;
+    let escrow_vault = next_account_info(iter)?;
+    let system_prog = next_account_info(iter)?;
+
+    assert_signer(verifier)?;
+    assert_writable(verifier)?;
+    assert_writable(verification_info)?;
+    assert_writable(escrow_vault)?;
+    assert_owned_by(request_info, program_id)?;
+    assert_owned_by(verification_info, program_id)?;
+
+    if *system_prog.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let req = InferenceRequest::deserialize(&mut &request_info.try_borrow_data()?[..])?;
+    let winners_matched = winning_side(&req)?;
+    if req.verifier_reward_each == 0 {
+        return Err(AiError::NoVerifierReward.into());
+    }
+
+    let (record_pda, _) = Pubkey::find_program_address(
+        &[
+            VerificationRecord::SEED,
+            request_info.key.as_ref(),
+            verifier.key.as_ref(),
+        ],
+        program_id,
+    );
+    if record_pda != *verification_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let mut record = VerificationRecord::try_from_slice(&verification_info.try_borrow_data()?)?;
+    if record.settled {
+        return Err(AiError::AlreadySettled.into());
+    }
+    if record.matched != winners_matched {
+        return Err(AiError::NotOnWinningSide.into());
+    }
+
+    let (expected_escrow, escrow_bump) = Pubkey::find_program_address(
+        &[b"escrow", request_info.key.as_ref()],
+        program_id,
+    );
+    if *escrow_vault.key != expected_escrow {
+        return Err(AiError::InvalidPDA.into());
+    }
+
+    // The last claim sweeps the rounding remainder with it
+    let seeds: &[&[u8]] = &[b"escrow", request_info.key.as_ref(), &[escrow_bump]];
+    let amount = payout_from_vault(escrow_vault, verifier, req.verifier_reward_each, system_prog, seeds)?;
+
+    record.settled = true;
+    record.serialize(&mut &mut verification_info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "EVENT:VerifierRewarded:{{\"request\":\"{}\",\"verifier\":\"{}\",\"amount\":{}}}",
+        request_info.key,
+        verifier.key,
+        amount
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 17 — PenalizeDissenter (permissionless crank)
+// Accounts: 0=[] request PDA, 1=[writable] verification record,
+//           2=[writable] dissenter AIValidator, 3=[writable] dissenter stake
+//           vault, 4=[] config PDA, 5=[writable] burn address,
+//           6=[] system program
+//
+// Burns DISSENT_SLASH_BPS of the stake of a verifier who voted against the
+// final outcome.
+// ---------------------------------------------------------------------------
+
+fn process_penalize_dissenter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let iter = &mut accounts.iter();
+    let request_info = next_account_info(iter)?;
+    let verification_info = next_account_info(iter)?;
+    let validator_info = next_account_info(iter)?;
+    let stake_vault = next_account_info(iter)?;
+    let config_info = next_account_info(iter)?;
+    let burn_info = next_account_info(iter)?;
+    let system_prog = next_account_info(iter)?;
+
+    assert_writable(verification_info)?;
+    assert_writable(validator_info)?;
+    assert_writable(stake_vault)?;
+    assert_writable(burn_info)?;
+    assert_owned_by(request_info, program_id)?;
+    assert_owned_by(verification_info, program_id)?;
+    assert_owned_by(validator_info, program_id)?;
+    assert_owned_by(config_info, program_id)?;
+
+    if *system_prog.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (config_pda, _) = Pubkey::find_program_address(&[AIConfig::SEED], program_id);
+    if config_pda != *config_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let config = AIConfig::deserialize(&mut &config_info.try_borrow_data()?[..])?;
+    if !config.is_initialized {
+        return Err(AiError::NotInitialized.into());
+    }
+    if *burn_info.key != config.burn_address {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let req = InferenceRequest::deserialize(&mut &request_info.try_borrow_data()?[..])?;
+    let winners_matched = winning_side(&req)?;
+
+    let mut record = VerificationRecord::try_from_slice(&verification_info.try_borrow_data()?)?;
+    let (record_pda, _) = Pubkey::find_program_address(
+        &[
+            VerificationRecord::SEED,
+            request_info.key.as_ref(),
+            record.verifier.as_ref(),
+        ],
+        program_id,
+    );
+    if record_pda != *verification_info.key || record.request != *request_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    if record.settled {
+        return Err(AiError::AlreadySettled.into());
+    }
+    if record.matched == winners_matched {
+        return Err(AiError::NotDissenter.into());
+    }
+
+    let (validator_pda, _) =
+        Pubkey::find_program_address(&[AIValidator::SEED, record.verifier.as_ref()], program_id);
+    if validator_pda != *validator_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let (expected_stake_vault, stake_vault_bump) =
+        Pubkey::find_program_address(&[b"stake_vault", record.verifier.as_ref()], program_id);
+    if expected_stake_vault != *stake_vault.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+
+    let mut validator = AIValidator::deserialize(&mut &validator_info.try_borrow_data()?[..])?;
+    let slash_amount = validator
+        .stake_amount
+        .checked_mul(DISSENT_SLASH_BPS)
+        .ok_or(AiError::Overflow)?
+        / BPS_DENOMINATOR;
+    let seeds: &[&[u8]] = &[b"stake_vault", record.verifier.as_ref(), &[stake_vault_bump]];
+    let slash_amount = payout_from_vault(stake_vault, burn_info, slash_amount, system_prog, seeds)?;
+    validator.stake_amount = validator.stake_amount.saturating_sub(slash_amount);
+    validator.slash_count = validator.slash_count.checked_add(1).ok_or(AiError::Overflow)?;
+    validator.serialize_to_account(&mut validator_info.try_borrow_mut_data()?)?;
+
+    record.settled = true;
+    record.serialize(&mut &mut verification_info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "EVENT:DissenterPenalized:{{\"request\":\"{}\",\"verifier\":\"{}\",\"slash_amount\":{}}}",
+        request_info.key,
+        record.verifier,
+        slash_amount
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 18 — MigrateAccount (permissionless)
+// Accounts: 0=[signer, writable] payer, 1=[writable] AIConfig,
+//           InferenceRequest or AIValidator PDA, 2=[] system program
+//
+// Grows a v1 account to the current layout; the payer covers the extra rent.
+// V2 fields start zeroed, or at their defaults for the config: a migrated
+// pending request is expirable at once and a migrated completed request is
+// already final.
+// ---------------------------------------------------------------------------
+
+fn process_migrate_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let iter = &mut accounts.iter();
+    let payer = next_account_info(iter)?;
+    let account_info = next_account_info(iter)?;
+    let system_prog = next_account_info(iter)?;
+
+    assert_signer(payer)?;
+    assert_writable(payer)?;
+    assert_writable(account_info)?;
+    assert_owned_by(account_info, program_id)?;
+    if *system_prog.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (kind, new_len) = match account_info.data_len() {
+        AIConfig::V1_LEN => {
+            let config = AIConfig::deserialize(&mut &account_info.try_borrow_data()?[..])?;
+            let (pda, _) = Pubkey::find_program_address(&[AIConfig::SEED], program_id);
+            if pda != *account_info.key {
+                return Err(AiError::InvalidPDA.into());
+            }
+            grow_account(payer, account_info, system_prog, AIConfig::LEN)?;
+            config.serialize_to_account(&mut account_info.try_borrow_mut_data()?)?;
+            ("AIConfig", AIConfig::LEN)
+        }
+        InferenceRequest::V1_LEN => {
+            let req = InferenceRequest::deserialize(&mut &account_info.try_borrow_data()?[..])?;
+            let nonce_bytes = req.nonce.to_le_bytes();
+            let pda = Pubkey::create_program_address(
+                &[InferenceRequest::SEED, &nonce_bytes, &[req.bump]],
+                program_id,
+            )
+            .map_err(|_| AiError::InvalidPDA)?;
+            if pda != *account_info.key {
+                return Err(AiError::InvalidPDA.into());
+            }
+            grow_account(payer, account_info, system_prog, InferenceRequest::LEN)?;
+            req.serialize_to_account(&mut account_info.try_borrow_mut_data()?)?;
+            ("InferenceRequest", InferenceRequest::LEN)
+        }
+        AIValidator::V1_LEN => {
+            let validator = AIValidator::deserialize(&mut &account_info.try_borrow_data()?[..])?;
+            let pda = Pubkey::create_program_address(
+                &[AIValidator::SEED, validator.authority.as_ref(), &[validator.bump]],
+                program_id,
+            )
+            .map_err(|_| AiError::InvalidPDA)?;
+            if pda != *account_info.key {
+                return Err(AiError::InvalidPDA.into());
+            }
+            grow_account(payer, account_info, system_prog, AIValidator::LEN)?;
+            validator.serialize_to_account(&mut account_info.try_borrow_mut_data()?)?;
+            ("AIValidator", AIValidator::LEN)
+        }
+        _ => return Err(ProgramError::InvalidAccountData),
+    };
+
+    msg!(
+        "EVENT:AccountMigrated:{{\"account\":\"{}\",\"kind\":\"{}\",\"len\":{}}}",
+        account_info.key,
+        kind,
+        new_len
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 19 — CreateValidatorSet (permissionless)
+// Accounts: 0=[signer, writable] payer, 1=[] model PDA,
+//           2=[writable] validator set PDA, 3=[] system program
+//
+// Creates the validator set of a model registered without one, so validators
+// can join it and AssignValidator can draw from it.
+// ---------------------------------------------------------------------------
+
+fn process_create_validator_set(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let iter = &mut accounts.iter();
+    let payer = next_account_info(iter)?;
+    let model_info = next_account_info(iter)?;
+    let set_info = next_account_info(iter)?;
+    let system_prog = next_account_info(iter)?;
+
+    assert_signer(payer)?;
+    assert_writable(payer)?;
+    assert_owned_by(model_info, program_id)?;
+    if *system_prog.key != system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let model = ModelAccount::deserialize(&mut &model_info.try_borrow_data()?[..])?;
+    let (model_pda, _) = Pubkey::find_program_address(
+        &[ModelAccount::SEED, &model.model_weights_hash],
+        program_id,
+    );
+    if model_pda != *model_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    if set_info.data_len() > 0 {
+        return Err(AiError::AlreadyInitialized.into());
+    }
+
+    create_validator_set(program_id, payer, set_info, system_prog, &model.model_weights_hash)?;
+
+    msg!(
+        "EVENT:ValidatorSetCreated:{{\"model_hash\":\"{}\",\"set\":\"{}\"}}",
+        hex::encode(model.model_weights_hash),
+        set_info.key
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// 20 — SettleVotes (permissionless)
+// Accounts: 0=[writable] request, 1=[writable] submitter validator PDA,
+//           2=[] config PDA
+//
+// Settles the votes on a result whose challenge window closed before the
+// quorum voted, so they are not dropped when the window closes.
+// ---------------------------------------------------------------------------
+
+fn process_settle_votes(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let iter = &mut accounts.iter();
+    let request_info = next_account_info(iter)?;
+    let submitter_validator_info = next_account_info(iter)?;
+    let config_info = next_account_info(iter)?;
+
+    assert_writable(request_info)?;
+    assert_writable(submitter_validator_info)?;
+    assert_owned_by(request_info, program_id)?;
+    assert_owned_by(submitter_validator_info, program_id)?;
+    assert_owned_by(config_info, program_id)?;
+    assert_migrated(request_info, InferenceRequest::LEN)?;
+
+    let (config_pda, _) = Pubkey::find_program_address(&[AIConfig::SEED], program_id);
+    if config_pda != *config_info.key {
+        return Err(AiError::InvalidPDA.into());
+    }
+    let config = AIConfig::deserialize(&mut &config_info.try_borrow_data()?[..])?;
+
+    let mut req = InferenceRequest::deserialize(&mut &request_info.try_borrow_data()?[..])?;
+    if req.status != InferenceStatus::Completed {
+        return Err(AiError::InvalidStatus.into());
+    }
+    if !challenge_window_closed(&req, &Clock::get()?) {
+        return Err(AiError::ChallengeWindowOpen.into());
+    }
+
+    let outcome = vote_outcome(
+        req.match_votes,
+        req.mismatch_votes,
+        config.verifier_quorum,
+        config.dispute_threshold_bps,
+        true,
+    )
+    .ok_or(AiError::InvalidStatus)?;
+    settle_votes(program_id, &mut req, request_info.key, outcome, &config, submitter_validator_info)?;
+    req.serialize_to_account(&mut request_info.try_borrow_mut_data()?)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> InferenceRequest {
+        InferenceRequest {
+            requester: Pubkey::new_unique(),
+            model_hash: [1; 32],
+            input_hash: [2; 32],
+            max_output_len: 512,
+            max_fee: 1_000,
+            escrowed_amount: 1_000,
+            status: InferenceStatus::Pending,
+            assigned_validator: Pubkey::default(),
+            created_at: 7,
+            completed_at: 0,
+            nonce: 3,
+            bump: 254,
+            completed_slot: 11,
+            callback_program: Pubkey::new_unique(),
+            callback_discriminator: Some([9; 8]),
+            callback_invoked: false,
+            assignment_deadline: 12,
+            assignment_round: 1,
+            created_slot: 10,
+            match_votes: 2,
+            mismatch_votes: 1,
+            sampled_positions: 3,
+            verifier_reward_each: 50,
+            draw_slot: 10,
+        }
+    }
+
+    fn validator(models: usize) -> AIValidator {
+        AIValidator {
+            authority: Pubkey::new_unique(),
+            stake_amount: 5_000,
+            gpu_model: "x".repeat(MAX_GPU_MODEL),
+            vram_gb: 80,
+            supported_model_count: models as u16,
+            supported_models: vec![[4; 32]; models],
+            inference_count: 6,
+            slash_count: 0,
+            is_active: true,
+            bump: 253,
+            unstake_requested_at: 99,
+            open_assignments: 2,
+            open_disputes: 1,
+        }
+    }
+
+    #[test]
+    fn test_request_len_fits_largest_encoding() {
+        assert_eq!(borsh::to_vec(&request()).unwrap().len(), InferenceRequest::LEN);
+        let mut req = request();
+        req.callback_discriminator = None;
+        assert!(borsh::to_vec(&req).unwrap().len() < InferenceRequest::LEN);
+    }
+
+    #[test]
+    fn test_v1_request_reads_v2_fields_as_zero() {
+        let req = request();
+        let mut data = vec![0u8; InferenceRequest::V1_LEN];
+        req.serialize_to_account(&mut data).unwrap();
+
+        let read = InferenceRequest::deserialize(&mut &data[..]).unwrap();
+        assert_eq!(read.requester, req.requester);
+        assert_eq!(read.nonce, 3);
+        assert_eq!(read.bump, 254);
+        assert_eq!(read.completed_slot, 0);
+        assert!(!read.has_callback());
+        assert_eq!(read.callback_discriminator, None);
+        assert_eq!(read.created_slot, 0);
+        assert_eq!(read.verifier_reward_each, 0);
+        assert_eq!(read.draw_slot, 0);
+    }
+
+    #[test]
+    fn test_v1_config_reads_verification_defaults() {
+        let config = AIConfig {
+            is_initialized: true,
+            admin: Pubkey::new_unique(),
+            registration_fee: 10,
+            min_stake: 20,
+            request_nonce: 30,
+            burn_address: Pubkey::new_unique(),
+            foundation: Pubkey::new_unique(),
+            is_paused: false,
+            bump: 252,
+            verifier_quorum: 5,
+            dispute_threshold_bps: 6_000,
+            verifier_reward_bps: 700,
+        };
+        assert_eq!(borsh::to_vec(&config).unwrap().len(), AIConfig::LEN);
+
+        let mut data = vec![0u8; AIConfig::V1_LEN];
+        config.serialize_to_account(&mut data).unwrap();
+        let read = AIConfig::deserialize(&mut &data[..]).unwrap();
+        assert_eq!(read.admin, config.admin);
+        assert_eq!(read.request_nonce, 30);
+        assert_eq!(read.bump, 252);
+        assert_eq!(read.verifier_quorum, DEFAULT_VERIFIER_QUORUM);
+        assert_eq!(read.dispute_threshold_bps, DEFAULT_DISPUTE_THRESHOLD_BPS);
+        assert_eq!(read.verifier_reward_bps, DEFAULT_VERIFIER_REWARD_BPS);
+
+        let mut data = vec![0u8; AIConfig::LEN];
+        config.serialize_to_account(&mut data).unwrap();
+        let read = AIConfig::deserialize(&mut &data[..]).unwrap();
+        assert_eq!(read.verifier_quorum, 5);
+        assert_eq!(read.dispute_threshold_bps, 6_000);
+        assert_eq!(read.verifier_reward_bps, 700);
+    }
+
+    #[test]
+    fn test_request_round_trips_at_full_len() {
+        let mut data = vec![0u8; InferenceRequest::LEN];
+        request().serialize_to_account(&mut data).unwrap();
+        let read = InferenceRequest::deserialize(&mut &data[..]).unwrap();
+        assert_eq!(read.callback_discriminator, Some([9; 8]));
+        assert_eq!(read.assignment_round, 1);
+        assert_eq!(read.sampled_positions, 3);
+        assert_eq!(read.verifier_reward_each, 50);
+    }
+
+    #[test]
+    fn test_request_args_without_discriminator_parse() {
+        // Layout used by clients that predate the callback discriminator
+        let mut data = Vec::new();
+        data.extend_from_slice(&[5; 32]);
+        data.extend_from_slice(&borsh::to_vec(&vec![1u8, 2, 3]).unwrap());
+        data.extend_from_slice(&64u32.to_le_bytes());
+        data.extend_from_slice(&borsh::to_vec(&Some(Pubkey::new_unique())).unwrap());
+        data.extend_from_slice(&10u64.to_le_bytes());
+
+        let args = RequestInferenceArgs::try_from_slice(&data).unwrap();
+        assert_eq!(args.max_fee, 10);
+        assert_eq!(args.callback_discriminator, None);
+
+        data.push(1);
+        data.extend_from_slice(&[8; 8]);
+        let args = RequestInferenceArgs::try_from_slice(&data).unwrap();
+        assert_eq!(args.callback_discriminator, Some([8; 8]));
+    }
+
+    #[test]
+    fn test_full_v1_validator_drops_v2_fields() {
+        // 16 models fill a v1 account exactly; the v2 fields have no room
+        let v = validator(MAX_SUPPORTED_MODELS);
+        assert_eq!(borsh::to_vec(&v).unwrap().len(), AIValidator::LEN);
+
+        let mut data = vec![0u8; AIValidator::V1_LEN];
+        v.serialize_to_account(&mut data).unwrap();
+        let read = AIValidator::deserialize(&mut &data[..]).unwrap();
+        assert_eq!(read.supported_models.len(), MAX_SUPPORTED_MODELS);
+        assert_eq!(read.bump, 253);
+        assert_eq!(read.unstake_requested_at, 0);
+        assert_eq!(read.open_assignments, 0);
+
+        let mut data = vec![0u8; AIValidator::LEN];
+        v.serialize_to_account(&mut data).unwrap();
+        let read = AIValidator::deserialize(&mut &data[..]).unwrap();
+        assert_eq!(read.unstake_requested_at, 99);
+        assert_eq!(read.open_assignments, 2);
+        assert_eq!(read.open_disputes, 1);
+    }
+
+    #[test]
+    fn test_v1_validator_with_room_keeps_unstake_slot() {
+        let v = validator(1);
+        let mut data = vec![0u8; AIValidator::V1_LEN];
+        v.serialize_to_account(&mut data).unwrap();
+        let read = AIValidator::deserialize(&mut &data[..]).unwrap();
+        assert_eq!(read.unstake_requested_at, 99);
+    }
+
+    #[test]
+    fn test_callback_data_prefixes_optional_discriminator() {
+        let request = Pubkey::new_unique();
+        let output_hash = [7; 32];
+
+        let data = callback_data(Some([1; 8]), &request, &output_hash);
+        assert_eq!(data.len(), 72);
+        assert_eq!(&data[..8], &[1; 8]);
+        assert_eq!(&data[8..40], request.as_ref());
+
+        let data = callback_data(None, &request, &output_hash);
+        assert_eq!(data.len(), 64);
+        assert_eq!(&data[..32], request.as_ref());
+        assert_eq!(&data[32..], &output_hash);
+    }
+
+    fn slot_hashes(slots: &[u64]) -> Vec<u8> {
+        let mut data = (slots.len() as u64).to_le_bytes().to_vec();
+        for slot in slots {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(&[*slot as u8; 32]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_committed_slot_hash_uses_first_slot_after_draw_slot() {
+        // Newest first; slot 102 was skipped
+        let data = slot_hashes(&[105, 104, 103, 101, 100]);
+        assert_eq!(committed_slot_hash(&data, 100).unwrap(), Some([101; 32]));
+        assert_eq!(committed_slot_hash(&data, 101).unwrap(), Some([103; 32]));
+        assert_eq!(committed_slot_hash(&data, 104).unwrap(), Some([105; 32]));
+    }
+
+    #[test]
+    fn test_committed_slot_hash_waits_for_a_later_slot() {
+        let data = slot_hashes(&[105, 104]);
+        assert_eq!(
+            committed_slot_hash(&data, 105).unwrap_err(),
+            AiError::DrawNotReady.into()
+        );
+    }
+
+    #[test]
+    fn test_committed_slot_hash_recommits_once_aged_out() {
+        let slots: Vec<u64> = (1_000..1_000 + SLOT_HASHES_MAX_ENTRIES).rev().collect();
+        let data = slot_hashes(&slots);
+        assert_eq!(committed_slot_hash(&data, 500).unwrap(), None);
+        assert!(committed_slot_hash(&data, 1_000).unwrap().is_some());
+
+        // A young chain still has every slot since genesis
+        let data = slot_hashes(&[3, 2, 1]);
+        assert_eq!(committed_slot_hash(&data, 0).unwrap(), Some([1; 32]));
+    }
+
+    #[test]
+    fn test_draw_weighted_follows_stake() {
+        let candidates = vec![
+            (Pubkey::new_unique(), 1_000),
+            (Pubkey::new_unique(), 3_000),
+            (Pubkey::new_unique(), 6_000),
+        ];
+        let mut picks = [0u32; 3];
+        for i in 0u32..10_000 {
+            let drawn = draw_weighted(&candidates, &sha256(&i.to_le_bytes())).unwrap();
+            let idx = candidates.iter().position(|(k, _)| *k == drawn).unwrap();
+            picks[idx] += 1;
+        }
+        // Expected 1,000 / 3,000 / 6,000 picks
+        assert!((800..1_200).contains(&picks[0]), "{:?}", picks);
+        assert!((2_700..3_300).contains(&picks[1]), "{:?}", picks);
+        assert!((5_600..6_400).contains(&picks[2]), "{:?}", picks);
+    }
+
+    #[test]
+    fn test_draw_weighted_skips_zero_stake() {
+        let staked = Pubkey::new_unique();
+        let candidates = vec![(Pubkey::new_unique(), 0), (staked, 5)];
+        for i in 0u32..100 {
+            assert_eq!(draw_weighted(&candidates, &sha256(&i.to_le_bytes())), Some(staked));
+        }
+        assert_eq!(draw_weighted(&[(Pubkey::new_unique(), 0)], &[0; 32]), None);
+        assert_eq!(draw_weighted(&[], &[0; 32]), None);
+    }
+
+    #[test]
+    fn test_exclude_lapsed_assignee() {
+        let lapsed = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+
+        let mut candidates = vec![(lapsed, 100), (other, 1)];
+        exclude_lapsed(&mut candidates, Some(lapsed));
+        assert_eq!(candidates, vec![(other, 1)]);
+        for i in 0u32..100 {
+            assert_eq!(draw_weighted(&candidates, &sha256(&i.to_le_bytes())), Some(other));
+        }
+
+        // The only eligible validator gets another chance
+        let mut candidates = vec![(lapsed, 100)];
+        exclude_lapsed(&mut candidates, Some(lapsed));
+        assert_eq!(candidates, vec![(lapsed, 100)]);
+
+        let mut candidates = vec![(lapsed, 100), (other, 1)];
+        exclude_lapsed(&mut candidates, None);
+        assert_eq!(candidates.len(), 2);
+    }
+
+    #[test]
+    fn test_full_validator_set_fits_and_finds_lowest_stake() {
+        let mut set = ModelValidatorSet {
+            model_hash: [1; 32],
+            bump: 255,
+            validators: (0..MAX_MODEL_VALIDATORS as u64)
+                .map(|i| SetMember { validator: Pubkey::new_unique(), stake: 100 + (i * 7) % 31 })
+                .collect(),
+        };
+        assert_eq!(borsh::to_vec(&set).unwrap().len(), ModelValidatorSet::LEN);
+
+        set.validators[20].stake = 1;
+        assert_eq!(set.lowest_staked(), Some(20));
+        set.validators.clear();
+        assert_eq!(set.lowest_staked(), None);
+    }
+
+    #[test]
+    fn test_pending_request_expires_at_expiry_boundary() {
+        let mut req = request();
+        req.created_slot = 1_000;
+        assert_eq!(
+            check_expirable(&req, 1_000 + REQUEST_EXPIRY_SLOTS - 1).unwrap_err(),
+            AiError::RequestNotExpired
+        );
+        assert!(check_expirable(&req, 1_000 + REQUEST_EXPIRY_SLOTS).is_ok());
+    }
+
+    #[test]
+    fn test_assigned_request_expires_only_after_deadline() {
+        let mut req = request();
+        req.status = InferenceStatus::Assigned;
+        req.created_slot = 1_000;
+        let slot = 1_000 + REQUEST_EXPIRY_SLOTS;
+
+        // The deadline slot itself still belongs to the assignee
+        req.assignment_deadline = slot;
+        assert_eq!(check_expirable(&req, slot).unwrap_err(), AiError::InvalidStatus);
+        req.assignment_deadline = slot - 1;
+        assert!(check_expirable(&req, slot).is_ok());
+    }
+
+    #[test]
+    fn test_served_request_never_expires() {
+        let mut req = request();
+        req.created_slot = 0;
+        for status in [
+            InferenceStatus::Completed,
+            InferenceStatus::Verified,
+            InferenceStatus::Disputed,
+            InferenceStatus::Failed,
+        ] {
+            req.status = status;
+            assert_eq!(check_expirable(&req, u64::MAX).unwrap_err(), AiError::InvalidStatus);
+        }
+    }
+
+    #[test]
+    fn test_migrated_request_expires_immediately() {
+        // v1 requests read created_slot as zero
+        let mut data = vec![0u8; InferenceRequest::V1_LEN];
+        request().serialize_to_account(&mut data).unwrap();
+        let req = InferenceRequest::deserialize(&mut &data[..]).unwrap();
+        assert!(check_expirable(&req, REQUEST_EXPIRY_SLOTS).is_ok());
+    }
+
+    #[test]
+    fn test_withdraw_waits_for_cooldown_boundary() {
+        let mut v = validator(1);
+        v.open_assignments = 0;
+        v.open_disputes = 0;
+        v.unstake_requested_at = 500;
+        assert_eq!(
+            check_withdrawable(&v, 500 + UNSTAKE_COOLDOWN_SLOTS - 1).unwrap_err(),
+            AiError::CooldownActive
+        );
+        assert!(check_withdrawable(&v, 500 + UNSTAKE_COOLDOWN_SLOTS).is_ok());
+
+        v.unstake_requested_at = 0;
+        assert_eq!(check_withdrawable(&v, u64::MAX).unwrap_err(), AiError::UnstakeNotRequested);
+    }
+
+    #[test]
+    fn test_withdraw_blocked_by_open_obligations() {
+        let mut v = validator(1);
+        v.unstake_requested_at = 1;
+        let slot = 1 + UNSTAKE_COOLDOWN_SLOTS;
+
+        v.open_assignments = 1;
+        v.open_disputes = 0;
+        assert_eq!(check_withdrawable(&v, slot).unwrap_err(), AiError::ObligationsOpen);
+        v.open_assignments = 0;
+        v.open_disputes = 1;
+        assert_eq!(check_withdrawable(&v, slot).unwrap_err(), AiError::ObligationsOpen);
+        v.open_disputes = 0;
+        assert!(check_withdrawable(&v, slot).is_ok());
+    }
+
+    #[test]
+    fn test_vault_payout_keeps_zero_or_rent_minimum() {
+        let rent = 890_880;
+        // Enough left behind
+        assert_eq!(vault_payout(2_000_000, 1_000_000, rent), 1_000_000);
+        assert_eq!(vault_payout(2_000_000, 2_000_000 - rent, rent), 2_000_000 - rent);
+        // Would leave dust: sweep it
+        assert_eq!(vault_payout(2_000_000, 2_000_000 - rent + 1, rent), 2_000_000);
+        assert_eq!(vault_payout(2_000_000, 1_999_999, rent), 2_000_000);
+        // Never more than the balance
+        assert_eq!(vault_payout(2_000_000, 3_000_000, rent), 2_000_000);
+        assert_eq!(vault_payout(0, 10, rent), 0);
+        assert_eq!(vault_payout(2_000_000, 0, rent), 0);
+    }
+
+    #[test]
+    fn test_counts_assignment_only_after_assign_validator() {
+        let mut req = request();
+        req.assignment_round = 0;
+        assert!(!req.counts_assignment());
+        req.assignment_round = 1;
+        assert!(req.counts_assignment());
+    }
+
+    #[test]
+    fn test_to_fixed_logits_converts_to_q16() {
+        assert_eq!(
+            to_fixed_logits(&[1.0, -1.5, 0.0, 0.25]),
+            [LOGIT_FIXED_ONE, -98_304, 0, 16_384]
+        );
+    }
+
+    #[test]
+    fn test_vote_outcome_waits_for_quorum_while_window_open() {
+        assert_eq!(vote_outcome(0, 0, 3, 5_000, false), None);
+        assert_eq!(vote_outcome(1, 1, 3, 5_000, false), None);
+        assert_eq!(vote_outcome(3, 0, 3, 5_000, false), Some(InferenceStatus::Verified));
+        // A zero quorum still needs one vote
+        assert_eq!(vote_outcome(0, 0, 0, 5_000, false), None);
+        assert_eq!(vote_outcome(0, 1, 0, 5_000, false), Some(InferenceStatus::Disputed));
+    }
+
+    #[test]
+    fn test_vote_outcome_disputes_at_threshold_boundary() {
+        // 2 of 4 is exactly 50%
+        assert_eq!(vote_outcome(2, 2, 4, 5_000, false), Some(InferenceStatus::Disputed));
+        assert_eq!(vote_outcome(2, 2, 4, 5_001, false), Some(InferenceStatus::Verified));
+        // 1 of 3 is just under 33.34%
+        assert_eq!(vote_outcome(2, 1, 3, 3_333, false), Some(InferenceStatus::Disputed));
+        assert_eq!(vote_outcome(2, 1, 3, 3_334, false), Some(InferenceStatus::Verified));
+        assert_eq!(vote_outcome(0, 3, 3, 10_000, false), Some(InferenceStatus::Disputed));
+        assert_eq!(vote_outcome(u8::MAX, u8::MAX, 3, 5_000, false), Some(InferenceStatus::Disputed));
+    }
+
+    #[test]
+    fn test_vote_outcome_settles_short_quorum_once_window_closes() {
+        assert_eq!(vote_outcome(1, 0, 3, 5_000, true), Some(InferenceStatus::Verified));
+        assert_eq!(vote_outcome(0, 1, 3, 5_000, true), Some(InferenceStatus::Disputed));
+        assert_eq!(vote_outcome(1, 1, 3, 5_000, true), Some(InferenceStatus::Disputed));
+        // Nothing to settle without votes
+        assert_eq!(vote_outcome(0, 0, 3, 5_000, true), None);
+    }
+
+    #[test]
+    fn test_result_is_final_waits_for_votes_to_settle() {
+        let mut req = request();
+        req.status = InferenceStatus::Completed;
+        req.completed_slot = 1_000;
+        let open = Clock { slot: 1_000 + CHALLENGE_WINDOW_SLOTS - 1, ..Clock::default() };
+        let closed = Clock { slot: 1_000 + CHALLENGE_WINDOW_SLOTS, ..Clock::default() };
+
+        req.match_votes = 0;
+        req.mismatch_votes = 0;
+        assert!(!result_is_final(&req, &open));
+        assert!(result_is_final(&req, &closed));
+
+        req.match_votes = 1;
+        assert!(challenge_window_closed(&req, &closed));
+        assert!(!result_is_final(&req, &closed));
+
+        req.status = InferenceStatus::Verified;
+        assert!(result_is_final(&req, &open));
+    }
+
+    #[test]
+    fn test_reward_each_splits_pool_above_rent_minimum() {
+        let rent = 890_880;
+        assert_eq!(reward_each(3_000_000, 3, rent), 1_000_000);
+        assert_eq!(reward_each(3 * rent, 3, rent), rent);
+        // Too small to leave the escrow rent-exempt
+        assert_eq!(reward_each(3 * rent - 1, 3, rent), 0);
+        assert_eq!(reward_each(3_000_000, 0, rent), 0);
+    }
+
+    #[test]
+    fn test_to_fixed_logits_saturates_and_zeroes_nan() {
+        assert_eq!(
+            to_fixed_logits(&[f32::NAN, f32::INFINITY, f32::NEG_INFINITY, 1e10]),
+            [0, i32::MAX, i32::MIN, i32::MAX]
+        );
+        assert_eq!(to_fixed_logits(&[-1e10; 4]), [i32::MIN; 4]);
+    }
+
+    #[test]
+    fn test_tolerance_boundary_is_one_percent_of_stored() {
+        // 100.0 in Q16.16; 1% of it is exactly 1.0
+        let stored = [100 * LOGIT_FIXED_ONE; 4];
+        let mut observed = stored;
+        observed[2] += LOGIT_FIXED_ONE;
+        assert!(logits_within_tolerance(&stored, &observed));
+        observed[2] += 1;
+        assert!(!logits_within_tolerance(&stored, &observed));
+
+        let mut observed = stored;
+        observed[0] -= LOGIT_FIXED_ONE;
+        assert!(logits_within_tolerance(&stored, &observed));
+        observed[0] -= 1;
+        assert!(!logits_within_tolerance(&stored, &observed));
+    }
+
+    #[test]
+    fn test_tolerance_floors_magnitude_at_one() {
+        // Around zero the tolerance is 1% of 1.0 = 655.36 raw units
+        let stored = [0, 10, -10, LOGIT_FIXED_ONE / 2];
+        assert!(logits_within_tolerance(&stored, &[655, 665, -665, LOGIT_FIXED_ONE / 2 + 655]));
+        assert!(!logits_within_tolerance(&stored, &[656, 10, -10, LOGIT_FIXED_ONE / 2]));
+        assert!(!logits_within_tolerance(&stored, &[0, 10, -10, LOGIT_FIXED_ONE / 2 - 656]));
+    }
+
+    #[test]
+    fn test_tolerance_handles_nan_and_saturated_logits() {
+        let nan = to_fixed_logits(&[f32::NAN; 4]);
+        assert!(logits_within_tolerance(&nan, &to_fixed_logits(&[0.0; 4])));
+        assert!(!logits_within_tolerance(&nan, &to_fixed_logits(&[1.0; 4])));
+
+        // Extremes do not overflow the comparison
+        let max = [i32::MAX; 4];
+        assert!(logits_within_tolerance(&max, &to_fixed_logits(&[f32::INFINITY; 4])));
+        assert!(!logits_within_tolerance(&max, &[i32::MIN; 4]));
+        assert!(!logits_within_tolerance(&[i32::MIN; 4], &max));
+        // 1% of i32::MAX is still well inside the range
+        assert!(logits_within_tolerance(&max, &[i32::MAX - 21_474_836; 4]));
+        assert!(!logits_within_tolerance(&max, &[i32::MAX - 21_474_837; 4]));
+    }
+
+    #[test]
+    fn test_required_fingerprint_positions() {
+        // One per output token, capped
+        assert_eq!(required_fingerprint_positions(5, 1), 5);
+        assert_eq!(required_fingerprint_positions(1_000, 1), MAX_FINGERPRINT_POSITIONS);
+        // Short outputs still need an entry per quorum vote
+        assert_eq!(required_fingerprint_positions(2, 3), 3);
+        assert_eq!(required_fingerprint_positions(1, MAX_VERIFIER_QUORUM), MAX_VERIFIER_QUORUM as usize);
+        assert_eq!(required_fingerprint_positions(100, MAX_VERIFIER_QUORUM), MAX_FINGERPRINT_POSITIONS);
+    }
+
+    #[test]
+    fn test_fingerprint_token_index_is_deterministic_and_in_range() {
+        let request = Pubkey::new_unique();
+        let output_hash = [3; 32];
+        for entry in 0..MAX_FINGERPRINT_POSITIONS as u32 {
+            let index = fingerprint_token_index(&request, &output_hash, entry, 40);
+            assert!(index < 40);
+            assert_eq!(index, fingerprint_token_index(&request, &output_hash, entry, 40));
+        }
+        assert_eq!(fingerprint_token_index(&request, &output_hash, 7, 1), 0);
+
+        // Bound to the committed output
+        let a: Vec<u32> = (0..8).map(|e| fingerprint_token_index(&request, &[1; 32], e, 1_000)).collect();
+        let b: Vec<u32> = (0..8).map(|e| fingerprint_token_index(&request, &[2; 32], e, 1_000)).collect();
+        assert_ne!(a, b);
+    }
+}
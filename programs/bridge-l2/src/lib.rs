@@ -1,6 +1,6 @@
 // Mythic L2 Bridge — Native Transfer Model
 //
-// This bridge uses native MYTH token transfers (no SPL wrapping/minting).
+// MYTH moves via native transfers (no SPL wrapping/minting).
 // A bridge reserve PDA holds native MYTH. When users bridge from L1,
 // the reserve sends native MYTH to the recipient. When users bridge to L1,
 // they send native MYTH back to the reserve.
@@ -8,6 +8,11 @@
 // Supply conservation: L1_circulating + L1_vault = L1_supply
 //                      L2_circulating + L2_reserve = L2_genesis
 //                      Total usable supply across both chains = 1B MYTH
+//
+// Every other asset (SOL, USDC, wBTC, ...) is a wrapped SPL token. The admin
+// registers an L1 mint once; the program then owns the L2 mint authority,
+// mints 1:1 against deposits locked in the L1 per-mint vault, and burns on
+// the way back so the L1 vault can release them.
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use sha2::{Digest, Sha256};
@@ -17,6 +22,7 @@ use solana_program::{
     declare_id,
     entrypoint,
     entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
     msg,
     program::invoke_signed,
     program_error::ProgramError,
@@ -34,6 +40,32 @@ declare_id!("5t8JwXzGQ3c7PCY6p6oJqZgFt8gff2d6uTLrqa1jFrKP");
 const L2_BRIDGE_CONFIG_SEED: &[u8] = b"l2_bridge_config";
 const BRIDGE_RESERVE_SEED: &[u8] = b"bridge_reserve";
 const PROCESSED_SEED: &[u8] = b"processed";
+const WRAPPED_MINT_SEED: &[u8] = b"wrapped_mint";
+const L2_MINT_SEED: &[u8] = b"mint";
+const ATTESTATION_SEED: &[u8] = b"attestation";
+const MESSAGE_ORIGIN_SEED: &[u8] = b"message_origin";
+
+/// Cross-domain message limits, matching programs/bridge.
+pub const MAX_MESSAGE_PAYLOAD: usize = 512;
+pub const MAX_MESSAGE_ACCOUNTS: usize = 8;
+
+/// Maximum relayers in the attestation committee.
+pub const MAX_RELAYER_COMMITTEE: usize = 16;
+
+/// Token-2022 program ID
+const TOKEN_2022_PROGRAM_ID: Pubkey = solana_program::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Legacy SPL Token program ID
+const LEGACY_TOKEN_PROGRAM_ID: Pubkey = solana_program::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// SPL mint account size (82 bytes for both legacy and Token-2022 without extensions)
+const SPL_MINT_LEN: usize = 82;
+
+/// Offset of the decimals byte in an SPL / Token-2022 mint.
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+/// Slots per daily release window (~24hrs at 400ms/slot).
+const DAILY_WINDOW_SLOTS: u64 = 216_000;
 
 /// Decimal scaling factor: L1 MYTH has 6 decimals, L2 has 9.
 /// L2 amounts must be divisible by this factor when bridging back to L1.
@@ -49,6 +81,15 @@ const IX_UPDATE_CONFIG: u8 = 4;
 const IX_PAUSE_BRIDGE: u8 = 5;
 const IX_UNPAUSE_BRIDGE: u8 = 6;
 const IX_UPDATE_LIMITS: u8 = 7;
+const IX_REGISTER_WRAPPED_TOKEN: u8 = 8;
+const IX_MINT_WRAPPED: u8 = 9;
+const IX_BURN_WRAPPED: u8 = 10;
+const IX_ATTEST_DEPOSIT: u8 = 11;
+const IX_ADD_RELAYER: u8 = 12;
+const IX_REMOVE_RELAYER: u8 = 13;
+const IX_SEND_MESSAGE: u8 = 14;
+const IX_RECEIVE_MESSAGE: u8 = 15;
+const IX_UPDATE_WRAPPED_LIMITS: u8 = 16;
 
 // ── Error Codes ──────────────────────────────────────────────────────────────
 
@@ -78,6 +119,28 @@ pub enum BridgeL2Error {
     AmountAboveMaxRelease,
     #[error("Daily release limit exceeded")]
     DailyReleaseLimitExceeded,
+    #[error("Wrapped token not registered")]
+    WrappedTokenNotRegistered,
+    #[error("Invalid token account")]
+    InvalidTokenAccount,
+    #[error("Signer is not a relayer committee member")]
+    NotCommitteeMember,
+    #[error("Relayer already attested to this deposit")]
+    DuplicateAttestation,
+    #[error("Not enough committee attestations")]
+    InsufficientAttestations,
+    #[error("Relayer committee is full")]
+    CommitteeFull,
+    #[error("Invalid attestation threshold")]
+    InvalidThreshold,
+    #[error("Message payload or account list too large")]
+    MessageTooLarge,
+    #[error("Invalid message target program")]
+    InvalidMessageTarget,
+    #[error("Message accounts do not match")]
+    MessageAccountMismatch,
+    #[error("Deposit decimals do not match the wrapped mint")]
+    DecimalsMismatch,
 }
 
 impl From<BridgeL2Error> for ProgramError {
@@ -90,7 +153,7 @@ pub const ERROR_BRIDGE_PAUSED: u32 = 100;
 
 // ── State ────────────────────────────────────────────────────────────────────
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, Debug)]
 pub struct L2BridgeConfig {
     pub admin: Pubkey,
     pub relayer: Pubkey,
@@ -107,11 +170,53 @@ pub struct L2BridgeConfig {
     pub daily_release_limit: u64,
     pub daily_released: u64,
     pub last_reset_slot: u64,
+    // Relayer committee (appended for backward compatibility). While the
+    // committee is empty the single `relayer` key is trusted as before.
+    pub relayer_threshold: u8,
+    pub relayer_committee: Vec<Pubkey>,
+}
+
+impl BorshDeserialize for L2BridgeConfig {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let admin = Pubkey::deserialize_reader(reader)?;
+        let relayer = Pubkey::deserialize_reader(reader)?;
+        let withdraw_nonce = u64::deserialize_reader(reader)?;
+        let total_released = u64::deserialize_reader(reader)?;
+        let total_received = u64::deserialize_reader(reader)?;
+        let is_initialized = bool::deserialize_reader(reader)?;
+        let bump = u8::deserialize_reader(reader)?;
+        let paused = bool::deserialize_reader(reader)?;
+        let reserve_bump = u8::deserialize_reader(reader)?;
+        let min_release = u64::deserialize_reader(reader)?;
+        let max_release = u64::deserialize_reader(reader)?;
+        let daily_release_limit = u64::deserialize_reader(reader)?;
+        let daily_released = u64::deserialize_reader(reader)?;
+        let last_reset_slot = u64::deserialize_reader(reader)?;
+        // v2 fields — default if account is v1 (132 bytes)
+        let relayer_threshold = u8::deserialize_reader(reader).unwrap_or(0);
+        let relayer_committee = Vec::<Pubkey>::deserialize_reader(reader).unwrap_or_default();
+        Ok(Self {
+            admin, relayer, withdraw_nonce, total_released, total_received,
+            is_initialized, bump, paused, reserve_bump, min_release, max_release,
+            daily_release_limit, daily_released, last_reset_slot,
+            relayer_threshold, relayer_committee,
+        })
+    }
 }
 
 impl L2BridgeConfig {
-    // 32 + 32 + 8 + 8 + 8 + 1 + 1 + 1 + 1 + 8 + 8 + 8 + 8 + 8 = 132
-    pub const LEN: usize = 132;
+    // 132 (v1) + 1 + 4 + 32 * MAX_RELAYER_COMMITTEE = 649
+    pub const LEN: usize = 132 + 1 + 4 + 32 * MAX_RELAYER_COMMITTEE;
+    pub const V1_LEN: usize = 132;
+
+    /// Serialize to account data, writing only the bytes that fit.
+    /// V1 accounts are grown to LEN by the first AddRelayer.
+    pub fn serialize_to_account(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        let full = borsh::to_vec(self).map_err(|_| ProgramError::BorshIoError("serialize".to_string()))?;
+        let len = data.len().min(full.len());
+        data[..len].copy_from_slice(&full[..len]);
+        Ok(())
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
@@ -126,6 +231,65 @@ impl ProcessedDeposit {
     pub const LEN: usize = 8 + 64 + 8 + 1; // 81
 }
 
+/// Registry entry for an L1 mint bridged as a wrapped SPL token.
+/// PDA seeds: ["wrapped_mint", l1_mint]. The PDA is also the L2 mint authority.
+///
+/// Release limits are in the token's own base units, so each mint carries
+/// its own. They start at zero: nothing is minted until UpdateWrappedLimits.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct WrappedTokenInfo {
+    pub l1_mint: Pubkey,
+    pub l2_mint: Pubkey,
+    pub is_active: bool,
+    pub bump: u8,
+    /// Decimals of the L1 mint; the L2 mint is created with the same.
+    pub decimals: u8,
+    pub min_release: u64,
+    pub max_release: u64,
+    pub daily_release_limit: u64,
+    pub daily_released: u64,
+    pub last_reset_slot: u64,
+}
+
+impl WrappedTokenInfo {
+    pub const LEN: usize = 32 + 32 + 1 + 1 + 1 + 8 * 5; // 107
+}
+
+/// An account the target program receives when a message is delivered.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct MessageAccount {
+    pub pubkey: Pubkey,
+    pub is_writable: bool,
+}
+
+/// Cross-domain message. Identical layout in programs/bridge; `message_hash`
+/// is sha256 of its Borsh encoding.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CrossDomainMessage {
+    pub nonce: u64,
+    /// Signer of SendMessage on the origin chain (a wallet or a program PDA).
+    pub sender: Pubkey,
+    pub target_program: Pubkey,
+    pub accounts: Vec<MessageAccount>,
+    pub payload: Vec<u8>,
+}
+
+/// Committee votes for one (deposit nonce, deposit hash) pair.
+/// PDA seeds: ["attestation", nonce, deposit_hash]. Relayers that disagree on
+/// the hash accumulate votes in separate PDAs, so only a hash that reaches the
+/// threshold can be released.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct DepositAttestation {
+    pub nonce: u64,
+    pub deposit_hash: [u8; 32],
+    pub bump: u8,
+    pub attesters: Vec<Pubkey>,
+}
+
+impl DepositAttestation {
+    pub const LEN: usize = 8 + 32 + 1 + 4 + 32 * MAX_RELAYER_COMMITTEE; // 557
+}
+
 // ── Instruction Payloads ─────────────────────────────────────────────────────
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -167,6 +331,65 @@ pub struct UpdateLimitsParams {
     pub daily_release_limit: u64,
 }
 
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RegisterWrappedTokenParams {
+    pub l1_mint: Pubkey,
+    pub decimals: u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct MintWrappedParams {
+    pub l1_deposit_nonce: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub l1_mint: Pubkey,
+    pub l1_tx_signature: [u8; 64],
+    /// Decimals of the L1 mint as read by the relayer; must match the
+    /// registered wrapped mint or the amount would be mis-scaled.
+    pub l1_decimals: u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct UpdateWrappedLimitsParams {
+    pub l1_mint: Pubkey,
+    pub min_release: u64,
+    pub max_release: u64,
+    pub daily_release_limit: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct BurnWrappedParams {
+    pub amount: u64,
+    pub l1_recipient: [u8; 32],
+    pub l1_mint: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AttestDepositParams {
+    pub l1_deposit_nonce: u64,
+    pub deposit_hash: [u8; 32],
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SendMessageParams {
+    pub target_program: Pubkey,
+    pub accounts: Vec<MessageAccount>,
+    pub payload: Vec<u8>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReceiveMessageParams {
+    pub message: CrossDomainMessage,
+    pub l1_tx_signature: [u8; 64],
+}
+
+/// Add or remove a committee member and set the resulting threshold.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct UpdateCommitteeParams {
+    pub relayer: Pubkey,
+    pub threshold: u8,
+}
+
 // ── Entrypoint ───────────────────────────────────────────────────────────────
 
 entrypoint!(process_instruction);
@@ -191,6 +414,15 @@ pub fn process_instruction(
         IX_PAUSE_BRIDGE => process_pause_bridge(program_id, accounts),
         IX_UNPAUSE_BRIDGE => process_unpause_bridge(program_id, accounts),
         IX_UPDATE_LIMITS => process_update_limits(program_id, accounts, data),
+        IX_REGISTER_WRAPPED_TOKEN => process_register_wrapped_token(program_id, accounts, data),
+        IX_MINT_WRAPPED => process_mint_wrapped(program_id, accounts, data),
+        IX_BURN_WRAPPED => process_burn_wrapped(program_id, accounts, data),
+        IX_ATTEST_DEPOSIT => process_attest_deposit(program_id, accounts, data),
+        IX_ADD_RELAYER => process_add_relayer(program_id, accounts, data),
+        IX_REMOVE_RELAYER => process_remove_relayer(program_id, accounts, data),
+        IX_SEND_MESSAGE => process_send_message(program_id, accounts, data),
+        IX_RECEIVE_MESSAGE => process_receive_message(program_id, accounts, data),
+        IX_UPDATE_WRAPPED_LIMITS => process_update_wrapped_limits(program_id, accounts, data),
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
@@ -267,9 +499,11 @@ fn process_initialize(
         daily_release_limit: 10_000_000_000_000, // 10,000 MYTH
         daily_released: 0,
         last_reset_slot: 0,
+        relayer_threshold: 0,
+        relayer_committee: Vec::new(),
     };
 
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     let (reserve_pda, _) =
         Pubkey::find_program_address(&[BRIDGE_RESERVE_SEED], program_id);
@@ -325,7 +559,7 @@ fn process_fund_reserve(
     if config_account.owner != program_id {
         return Err(ProgramError::IllegalOwner);
     }
-    let config = L2BridgeConfig::try_from_slice(&config_account.data.borrow())?;
+    let config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
     if !config.is_initialized {
         return Err(BridgeL2Error::UninitializedAccount.into());
     }
@@ -355,15 +589,18 @@ fn process_fund_reserve(
 
 // ── Release Bridged ──────────────────────────────────────────────────────────
 // Relayer-only: transfers native MYTH from the bridge reserve PDA to a
-// recipient when an L1 deposit has been confirmed.
+// recipient when an L1 deposit has been confirmed. Once a relayer committee
+// is configured, the deposit_hash must also carry threshold attestations.
 // Accounts:
 //   0. [signer] relayer
 //   1. [signer, writable] payer (for processed_deposit PDA rent)
-//   2. [] l2_bridge_config PDA
+//   2. [writable] l2_bridge_config PDA
 //   3. [writable] bridge_reserve PDA
 //   4. [writable] recipient
 //   5. [writable] processed_deposit PDA
 //   6. [] system_program
+//   7. [writable] deposit_attestation PDA (required when a committee is
+//      configured; closed to the payer on release)
 
 fn process_release_bridged(
     program_id: &Pubkey,
@@ -378,6 +615,7 @@ fn process_release_bridged(
     let recipient = next_account_info(accounts_iter)?;
     let processed_account = next_account_info(accounts_iter)?;
     let system_program_info = next_account_info(accounts_iter)?;
+    let attestation_account = accounts_iter.next();
 
     if !relayer.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
@@ -404,31 +642,36 @@ fn process_release_bridged(
     if config_account.owner != program_id {
         return Err(ProgramError::IllegalOwner);
     }
-    let mut config = L2BridgeConfig::try_from_slice(&config_account.data.borrow())?;
+    let mut config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
     if !config.is_initialized {
         return Err(BridgeL2Error::UninitializedAccount.into());
     }
     if config.paused {
         return Err(ProgramError::Custom(ERROR_BRIDGE_PAUSED));
     }
-    if *relayer.key != config.relayer {
-        return Err(BridgeL2Error::InvalidRelayer.into());
-    }
 
     // C-02: Verify deposit hash = sha256(l1_tx_signature || nonce || amount || recipient)
     // This ensures the relayer cannot fabricate release parameters without a real L1 deposit.
-    {
-        let mut hasher = Sha256::new();
-        hasher.update(&params.l1_tx_signature);
-        hasher.update(&params.l1_deposit_nonce.to_le_bytes());
-        hasher.update(&params.amount.to_le_bytes());
-        hasher.update(params.recipient.as_ref());
-        let computed_hash: [u8; 32] = hasher.finalize().into();
-        if computed_hash != params.deposit_hash {
-            msg!("ERROR: deposit_hash mismatch — computed vs provided");
-            return Err(BridgeL2Error::DepositHashMismatch.into());
-        }
-    }
+    let computed_hash = deposit_hash(
+        &params.l1_tx_signature,
+        params.l1_deposit_nonce,
+        params.amount,
+        &params.recipient,
+        None,
+    );
+    if computed_hash != params.deposit_hash {
+        msg!("ERROR: deposit_hash mismatch — computed vs provided");
+        return Err(BridgeL2Error::DepositHashMismatch.into());
+    }
+
+    check_release_authorized(
+        program_id,
+        &config,
+        relayer.key,
+        attestation_account,
+        params.l1_deposit_nonce,
+        &params.deposit_hash,
+    )?;
 
     // Validate recipient matches params
     if *recipient.key != params.recipient {
@@ -444,25 +687,15 @@ fn process_release_bridged(
 
     // C-04: Enforce rate limits
     let clock = Clock::get()?;
-    let current_slot = clock.slot;
-    if params.amount < config.min_release {
-        return Err(BridgeL2Error::AmountBelowMinRelease.into());
-    }
-    if params.amount > config.max_release {
-        return Err(BridgeL2Error::AmountAboveMaxRelease.into());
-    }
-    // Reset daily volume if >216000 slots have passed (~24hrs at 400ms/slot)
-    if current_slot.saturating_sub(config.last_reset_slot) > 216_000 {
-        config.daily_released = 0;
-        config.last_reset_slot = current_slot;
-    }
-    config.daily_released = config
-        .daily_released
-        .checked_add(params.amount)
-        .ok_or(ProgramError::ArithmeticOverflow)?;
-    if config.daily_released > config.daily_release_limit {
-        return Err(BridgeL2Error::DailyReleaseLimitExceeded.into());
-    }
+    apply_release_limits(
+        params.amount,
+        clock.slot,
+        config.min_release,
+        config.max_release,
+        config.daily_release_limit,
+        &mut config.daily_released,
+        &mut config.last_reset_slot,
+    )?;
 
     // Ensure reserve has sufficient balance (leave rent-exempt minimum)
     let rent = Rent::get()?;
@@ -523,13 +756,14 @@ fn process_release_bridged(
         bump: processed_bump,
     };
     processed.serialize(&mut &mut processed_account.data.borrow_mut()[..])?;
+    close_attestation(&config, attestation_account, payer)?;
 
     // Update accounting
     config.total_released = config
         .total_released
         .checked_add(params.amount)
         .ok_or(ProgramError::ArithmeticOverflow)?;
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!(
         "EVENT:ReleaseBridged:{{\"recipient\":\"{}\",\"amount\":{},\"l1_deposit_nonce\":{},\"deposit_hash\":\"{}\",\"reserve_balance\":{}}}",
@@ -587,7 +821,7 @@ fn process_bridge_to_l1(
     if config_account.owner != program_id {
         return Err(ProgramError::IllegalOwner);
     }
-    let mut config = L2BridgeConfig::try_from_slice(&config_account.data.borrow())?;
+    let mut config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
     if !config.is_initialized {
         return Err(BridgeL2Error::UninitializedAccount.into());
     }
@@ -616,7 +850,7 @@ fn process_bridge_to_l1(
         .total_received
         .checked_add(params.amount)
         .ok_or(ProgramError::ArithmeticOverflow)?;
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     let l1_hex = hex_encode(&params.l1_recipient);
     msg!(
@@ -657,7 +891,7 @@ fn process_update_config(
         return Err(ProgramError::IllegalOwner);
     }
 
-    let mut config = L2BridgeConfig::try_from_slice(&config_account.data.borrow())?;
+    let mut config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
     if !config.is_initialized {
         return Err(BridgeL2Error::UninitializedAccount.into());
     }
@@ -671,7 +905,7 @@ fn process_update_config(
         config.relayer = new_relayer;
     }
 
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!(
         "EVENT:UpdateConfig:{{\"relayer\":\"{}\"}}",
@@ -710,7 +944,7 @@ fn process_pause_bridge(
         return Err(ProgramError::IllegalOwner);
     }
 
-    let mut config = L2BridgeConfig::try_from_slice(&config_account.data.borrow())?;
+    let mut config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
     if !config.is_initialized {
         return Err(BridgeL2Error::UninitializedAccount.into());
     }
@@ -719,7 +953,7 @@ fn process_pause_bridge(
     }
 
     config.paused = true;
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!("EVENT:PauseBridge:{{\"admin\":\"{}\"}}", admin.key);
     Ok(())
@@ -754,7 +988,7 @@ fn process_unpause_bridge(
         return Err(ProgramError::IllegalOwner);
     }
 
-    let mut config = L2BridgeConfig::try_from_slice(&config_account.data.borrow())?;
+    let mut config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
     if !config.is_initialized {
         return Err(BridgeL2Error::UninitializedAccount.into());
     }
@@ -763,7 +997,7 @@ fn process_unpause_bridge(
     }
 
     config.paused = false;
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!("EVENT:UnpauseBridge:{{\"admin\":\"{}\"}}", admin.key);
     Ok(())
@@ -800,7 +1034,7 @@ fn process_update_limits(
         return Err(ProgramError::IllegalOwner);
     }
 
-    let mut config = L2BridgeConfig::try_from_slice(&config_account.data.borrow())?;
+    let mut config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
     if !config.is_initialized {
         return Err(BridgeL2Error::UninitializedAccount.into());
     }
@@ -822,7 +1056,7 @@ fn process_update_limits(
     config.max_release = params.max_release;
     config.daily_release_limit = params.daily_release_limit;
 
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!(
         "EVENT:UpdateLimits:{{\"admin\":\"{}\",\"min_release\":{},\"max_release\":{},\"daily_release_limit\":{}}}",
@@ -832,12 +1066,1222 @@ fn process_update_limits(
     Ok(())
 }
 
-// ── Helpers ──────────────────────────────────────────────────────────────────
+// ── Register Wrapped Token ───────────────────────────────────────────────────
+// Admin-only: registers an L1 mint and creates its L2 wrapped mint with the
+// L1 mint's decimals. The mint authority is the wrapped_token_info PDA, so
+// only this program can mint. Limits start at zero; set them with
+// UpdateWrappedLimits before the first deposit is relayed.
+// Accounts:
+//   0. [signer, writable] admin (payer)
+//   1. [] l2_bridge_config PDA
+//   2. [writable] wrapped_token_info PDA (seeds: ["wrapped_mint", l1_mint])
+//   3. [writable] l2_mint PDA (seeds: ["mint", l1_mint])
+//   4. [] token_program
+//   5. [] system_program
+//   6. [] rent sysvar
+
+fn process_register_wrapped_token(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let wrapped_info_account = next_account_info(accounts_iter)?;
+    let l2_mint = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let system_program_info = next_account_info(accounts_iter)?;
+    let rent_sysvar = next_account_info(accounts_iter)?;
 
-fn hex_encode(bytes: &[u8]) -> String {
-    let mut s = String::with_capacity(bytes.len() * 2);
-    for b in bytes {
-        s.push_str(&format!("{:02x}", b));
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
     }
-    s
+    if !admin.is_writable || !wrapped_info_account.is_writable || !l2_mint.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = RegisterWrappedTokenParams::try_from_slice(data)?;
+
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
+    if !config.is_initialized {
+        return Err(BridgeL2Error::UninitializedAccount.into());
+    }
+    if *admin.key != config.admin {
+        return Err(BridgeL2Error::InvalidAuthority.into());
+    }
+
+    if !is_valid_token_program(token_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (info_pda, info_bump) = Pubkey::find_program_address(
+        &[WRAPPED_MINT_SEED, params.l1_mint.as_ref()],
+        program_id,
+    );
+    if info_pda != *wrapped_info_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let (mint_pda, mint_bump) =
+        Pubkey::find_program_address(&[L2_MINT_SEED, params.l1_mint.as_ref()], program_id);
+    if mint_pda != *l2_mint.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !wrapped_info_account.data_is_empty() || !l2_mint.data_is_empty() {
+        return Err(BridgeL2Error::AlreadyInitialized.into());
+    }
+
+    let rent = Rent::get()?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            wrapped_info_account.key,
+            rent.minimum_balance(WrappedTokenInfo::LEN),
+            WrappedTokenInfo::LEN as u64,
+            program_id,
+        ),
+        &[admin.clone(), wrapped_info_account.clone(), system_program_info.clone()],
+        &[&[WRAPPED_MINT_SEED, params.l1_mint.as_ref(), &[info_bump]]],
+    )?;
+
+    // Create the mint at its PDA address, owned by the token program
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            l2_mint.key,
+            rent.minimum_balance(SPL_MINT_LEN),
+            SPL_MINT_LEN as u64,
+            token_program.key,
+        ),
+        &[admin.clone(), l2_mint.clone(), system_program_info.clone()],
+        &[&[L2_MINT_SEED, params.l1_mint.as_ref(), &[mint_bump]]],
+    )?;
+
+    solana_program::program::invoke(
+        &build_initialize_mint_ix(token_program.key, l2_mint.key, &info_pda, params.decimals),
+        &[l2_mint.clone(), rent_sysvar.clone(), token_program.clone()],
+    )?;
+
+    let info = WrappedTokenInfo {
+        l1_mint: params.l1_mint,
+        l2_mint: mint_pda,
+        is_active: true,
+        bump: info_bump,
+        decimals: params.decimals,
+        min_release: 0,
+        max_release: 0,
+        daily_release_limit: 0,
+        daily_released: 0,
+        last_reset_slot: 0,
+    };
+    info.serialize(&mut &mut wrapped_info_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "EVENT:RegisterWrappedToken:{{\"l1_mint\":\"{}\",\"l2_mint\":\"{}\",\"decimals\":{}}}",
+        params.l1_mint, mint_pda, params.decimals
+    );
+
+    Ok(())
+}
+
+// ── Update Wrapped Limits ────────────────────────────────────────────────────
+// Admin-only: sets the release limits of one wrapped token, in its base units.
+// Accounts:
+//   0. [signer] admin
+//   1. [] l2_bridge_config PDA
+//   2. [writable] wrapped_token_info PDA
+
+fn process_update_wrapped_limits(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let wrapped_info_account = next_account_info(accounts_iter)?;
+
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !wrapped_info_account.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
+    if !config.is_initialized {
+        return Err(BridgeL2Error::UninitializedAccount.into());
+    }
+    if *admin.key != config.admin {
+        return Err(BridgeL2Error::InvalidAuthority.into());
+    }
+
+    let params = UpdateWrappedLimitsParams::try_from_slice(data)?;
+    if params.min_release > params.max_release {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if params.max_release > params.daily_release_limit {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut info = load_wrapped_token_info(program_id, wrapped_info_account, &params.l1_mint)?;
+    info.min_release = params.min_release;
+    info.max_release = params.max_release;
+    info.daily_release_limit = params.daily_release_limit;
+    info.serialize(&mut &mut wrapped_info_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "EVENT:UpdateWrappedLimits:{{\"l1_mint\":\"{}\",\"min_release\":{},\"max_release\":{},\"daily_release_limit\":{}}}",
+        params.l1_mint, params.min_release, params.max_release, params.daily_release_limit
+    );
+
+    Ok(())
+}
+
+// ── Mint Wrapped ─────────────────────────────────────────────────────────────
+// Relayer-only: mints wrapped tokens 1:1 for an L1 deposit into the per-mint
+// vault. Shares the processed_deposit PDA namespace with ReleaseBridged since
+// both are keyed by the single L1 deposit nonce.
+// Accounts:
+//   0. [signer] relayer
+//   1. [signer, writable] payer (for processed_deposit PDA rent)
+//   2. [] l2_bridge_config PDA
+//   3. [writable] wrapped_token_info PDA (per-mint release limits)
+//   4. [writable] l2_mint PDA
+//   5. [writable] recipient token account
+//   6. [writable] processed_deposit PDA
+//   7. [] token_program (the program that owns l2_mint)
+//   8. [] system_program
+//   9. [writable] deposit_attestation PDA (required when a committee is
+//      configured; closed to the payer on release)
+//
+// The attested deposit_hash additionally commits to the L1 mint:
+// sha256(l1_tx_signature || nonce || amount || recipient || l1_mint).
+// Amounts are checked against the mint's own limits, not the MYTH limits
+// in config.
+
+fn process_mint_wrapped(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let relayer = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let wrapped_info_account = next_account_info(accounts_iter)?;
+    let l2_mint = next_account_info(accounts_iter)?;
+    let recipient_token = next_account_info(accounts_iter)?;
+    let processed_account = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let system_program_info = next_account_info(accounts_iter)?;
+    let attestation_account = accounts_iter.next();
+
+    if !relayer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !payer.is_signer || !payer.is_writable {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !wrapped_info_account.is_writable
+        || !l2_mint.is_writable
+        || !recipient_token.is_writable
+        || !processed_account.is_writable
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = MintWrappedParams::try_from_slice(data)?;
+    if params.amount == 0 {
+        return Err(BridgeL2Error::ZeroAmount.into());
+    }
+
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
+    if !config.is_initialized {
+        return Err(BridgeL2Error::UninitializedAccount.into());
+    }
+    if config.paused {
+        return Err(ProgramError::Custom(ERROR_BRIDGE_PAUSED));
+    }
+
+    let computed_hash = deposit_hash(
+        &params.l1_tx_signature,
+        params.l1_deposit_nonce,
+        params.amount,
+        &params.recipient,
+        Some(&params.l1_mint),
+    );
+    check_release_authorized(
+        program_id,
+        &config,
+        relayer.key,
+        attestation_account,
+        params.l1_deposit_nonce,
+        &computed_hash,
+    )?;
+
+    let mut info = load_wrapped_token_info(program_id, wrapped_info_account, &params.l1_mint)?;
+    check_wrapped_mint(&info, l2_mint, token_program)?;
+    check_token_account(recipient_token, token_program.key, l2_mint.key, &params.recipient)?;
+    if params.l1_decimals != info.decimals {
+        return Err(BridgeL2Error::DecimalsMismatch.into());
+    }
+
+    let clock = Clock::get()?;
+    apply_release_limits(
+        params.amount,
+        clock.slot,
+        info.min_release,
+        info.max_release,
+        info.daily_release_limit,
+        &mut info.daily_released,
+        &mut info.last_reset_slot,
+    )?;
+    info.serialize(&mut &mut wrapped_info_account.data.borrow_mut()[..])?;
+
+    let nonce_bytes = params.l1_deposit_nonce.to_le_bytes();
+    let (processed_pda, processed_bump) =
+        Pubkey::find_program_address(&[PROCESSED_SEED, &nonce_bytes], program_id);
+    if processed_pda != *processed_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !processed_account.data_is_empty() {
+        return Err(BridgeL2Error::DepositAlreadyProcessed.into());
+    }
+
+    // The wrapped_token_info PDA is the mint authority
+    invoke_signed(
+        &build_mint_to_ix(
+            token_program.key,
+            l2_mint.key,
+            recipient_token.key,
+            wrapped_info_account.key,
+            params.amount,
+        ),
+        &[
+            l2_mint.clone(),
+            recipient_token.clone(),
+            wrapped_info_account.clone(),
+            token_program.clone(),
+        ],
+        &[&[WRAPPED_MINT_SEED, info.l1_mint.as_ref(), &[info.bump]]],
+    )?;
+
+    let rent = Rent::get()?;
+    let space = ProcessedDeposit::LEN;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            processed_account.key,
+            rent.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[
+            payer.clone(),
+            processed_account.clone(),
+            system_program_info.clone(),
+        ],
+        &[&[PROCESSED_SEED, &nonce_bytes, &[processed_bump]]],
+    )?;
+
+    let processed = ProcessedDeposit {
+        nonce: params.l1_deposit_nonce,
+        l1_tx_signature: params.l1_tx_signature,
+        processed_at: clock.unix_timestamp,
+        bump: processed_bump,
+    };
+    processed.serialize(&mut &mut processed_account.data.borrow_mut()[..])?;
+    close_attestation(&config, attestation_account, payer)?;
+
+    msg!(
+        "EVENT:MintWrapped:{{\"recipient\":\"{}\",\"amount\":{},\"l1_mint\":\"{}\",\"l2_mint\":\"{}\",\"l1_deposit_nonce\":{}}}",
+        params.recipient, params.amount, info.l1_mint, info.l2_mint, params.l1_deposit_nonce
+    );
+
+    Ok(())
+}
+
+// ── Burn Wrapped ─────────────────────────────────────────────────────────────
+// User burns wrapped tokens and names an L1 recipient. Burns take the next
+// withdraw_nonce, shared with BridgeToL1, so each L1 withdrawal PDA is unique.
+// Accounts:
+//   0. [signer] burner (token owner)
+//   1. [writable] burner token account
+//   2. [writable] l2_mint PDA
+//   3. [] wrapped_token_info PDA
+//   4. [writable] l2_bridge_config PDA
+//   5. [] token_program
+
+fn process_burn_wrapped(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let burner = next_account_info(accounts_iter)?;
+    let burner_token = next_account_info(accounts_iter)?;
+    let l2_mint = next_account_info(accounts_iter)?;
+    let wrapped_info_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+
+    if !burner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !burner_token.is_writable || !l2_mint.is_writable || !config_account.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = BurnWrappedParams::try_from_slice(data)?;
+    if params.amount == 0 {
+        return Err(BridgeL2Error::ZeroAmount.into());
+    }
+
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let mut config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
+    if !config.is_initialized {
+        return Err(BridgeL2Error::UninitializedAccount.into());
+    }
+    if config.paused {
+        return Err(ProgramError::Custom(ERROR_BRIDGE_PAUSED));
+    }
+
+    let info = load_wrapped_token_info(program_id, wrapped_info_account, &params.l1_mint)?;
+    check_wrapped_mint(&info, l2_mint, token_program)?;
+
+    // The token program enforces that burner owns burner_token
+    solana_program::program::invoke(
+        &build_burn_ix(
+            token_program.key,
+            burner_token.key,
+            l2_mint.key,
+            burner.key,
+            params.amount,
+        ),
+        &[
+            burner_token.clone(),
+            l2_mint.clone(),
+            burner.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    let nonce = config.withdraw_nonce;
+    config.withdraw_nonce = nonce
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
+
+    let l1_hex = hex_encode(&params.l1_recipient);
+    msg!(
+        "EVENT:BurnWrapped:{{\"sender\":\"{}\",\"l1_recipient\":\"{}\",\"amount\":{},\"l1_mint\":\"{}\",\"withdraw_nonce\":{}}}",
+        burner.key, l1_hex, params.amount, info.l1_mint, nonce
+    );
+
+    Ok(())
+}
+
+// ── Attest Deposit ───────────────────────────────────────────────────────────
+// Committee-only: records the signer's vote that `deposit_hash` describes the
+// L1 deposit with this nonce. ReleaseBridged / MintWrapped succeed once
+// `relayer_threshold` current members have voted for the same hash.
+// Accounts:
+//   0. [signer, writable] relayer (pays for the attestation PDA)
+//   1. [] l2_bridge_config PDA
+//   2. [writable] deposit_attestation PDA (seeds: ["attestation", nonce, deposit_hash])
+//   3. [] system_program
+
+fn process_attest_deposit(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let relayer = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let attestation_account = next_account_info(accounts_iter)?;
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    if !relayer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !relayer.is_writable || !attestation_account.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = AttestDepositParams::try_from_slice(data)?;
+
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
+    if !config.is_initialized {
+        return Err(BridgeL2Error::UninitializedAccount.into());
+    }
+    if !config.relayer_committee.contains(relayer.key) {
+        return Err(BridgeL2Error::NotCommitteeMember.into());
+    }
+
+    let nonce_bytes = params.l1_deposit_nonce.to_le_bytes();
+    let (attestation_pda, attestation_bump) = Pubkey::find_program_address(
+        &[ATTESTATION_SEED, &nonce_bytes, &params.deposit_hash],
+        program_id,
+    );
+    if attestation_pda != *attestation_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let mut attestation = if attestation_account.data_is_empty() {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                relayer.key,
+                attestation_account.key,
+                rent.minimum_balance(DepositAttestation::LEN),
+                DepositAttestation::LEN as u64,
+                program_id,
+            ),
+            &[
+                relayer.clone(),
+                attestation_account.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[ATTESTATION_SEED, &nonce_bytes, &params.deposit_hash, &[attestation_bump]]],
+        )?;
+        DepositAttestation {
+            nonce: params.l1_deposit_nonce,
+            deposit_hash: params.deposit_hash,
+            bump: attestation_bump,
+            attesters: Vec::new(),
+        }
+    } else {
+        if attestation_account.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        DepositAttestation::deserialize(&mut &attestation_account.data.borrow()[..])?
+    };
+
+    if attestation.attesters.contains(relayer.key) {
+        return Err(BridgeL2Error::DuplicateAttestation.into());
+    }
+    // Votes from removed members no longer count; drop them to keep room.
+    attestation
+        .attesters
+        .retain(|a| config.relayer_committee.contains(a));
+    attestation.attesters.push(*relayer.key);
+    attestation.serialize(&mut &mut attestation_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "EVENT:AttestDeposit:{{\"relayer\":\"{}\",\"l1_deposit_nonce\":{},\"deposit_hash\":\"{}\",\"attestations\":{},\"threshold\":{}}}",
+        relayer.key, params.l1_deposit_nonce, hex_encode(&params.deposit_hash),
+        attestation.attesters.len(), config.relayer_threshold
+    );
+
+    Ok(())
+}
+
+// ── Add Relayer ──────────────────────────────────────────────────────────────
+// Admin-only: adds a committee member and sets the new threshold.
+// V1 config accounts are grown to L2BridgeConfig::LEN (admin pays).
+// Accounts:
+//   0. [signer, writable] admin
+//   1. [writable] l2_bridge_config PDA
+//   2. [] system_program
+
+fn process_add_relayer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !admin.is_writable || !config_account.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
+    if !config.is_initialized {
+        return Err(BridgeL2Error::UninitializedAccount.into());
+    }
+    if *admin.key != config.admin {
+        return Err(BridgeL2Error::InvalidAuthority.into());
+    }
+
+    let params = UpdateCommitteeParams::try_from_slice(data)?;
+
+    if config.relayer_committee.contains(&params.relayer) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if config.relayer_committee.len() >= MAX_RELAYER_COMMITTEE {
+        return Err(BridgeL2Error::CommitteeFull.into());
+    }
+    config.relayer_committee.push(params.relayer);
+    if !is_valid_threshold(params.threshold, config.relayer_committee.len()) {
+        return Err(BridgeL2Error::InvalidThreshold.into());
+    }
+    config.relayer_threshold = params.threshold;
+
+    if config_account.data_len() < L2BridgeConfig::LEN {
+        grow_account(admin, config_account, system_program_info, L2BridgeConfig::LEN)?;
+    }
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
+
+    msg!(
+        "EVENT:AddRelayer:{{\"relayer\":\"{}\",\"committee_size\":{},\"threshold\":{}}}",
+        params.relayer, config.relayer_committee.len(), config.relayer_threshold
+    );
+
+    Ok(())
+}
+
+// ── Remove Relayer ───────────────────────────────────────────────────────────
+// Admin-only: removes a committee member and sets the new threshold. Removing
+// the last member (threshold 0) returns the bridge to the single `relayer`.
+// Accounts:
+//   0. [signer] admin
+//   1. [writable] l2_bridge_config PDA
+
+fn process_remove_relayer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !config_account.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
+    if !config.is_initialized {
+        return Err(BridgeL2Error::UninitializedAccount.into());
+    }
+    if *admin.key != config.admin {
+        return Err(BridgeL2Error::InvalidAuthority.into());
+    }
+
+    let params = UpdateCommitteeParams::try_from_slice(data)?;
+
+    let index = config
+        .relayer_committee
+        .iter()
+        .position(|r| *r == params.relayer)
+        .ok_or(BridgeL2Error::NotCommitteeMember)?;
+    config.relayer_committee.remove(index);
+    if !is_valid_threshold(params.threshold, config.relayer_committee.len()) {
+        return Err(BridgeL2Error::InvalidThreshold.into());
+    }
+    config.relayer_threshold = params.threshold;
+
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
+
+    msg!(
+        "EVENT:RemoveRelayer:{{\"relayer\":\"{}\",\"committee_size\":{},\"threshold\":{}}}",
+        params.relayer, config.relayer_committee.len(), config.relayer_threshold
+    );
+
+    Ok(())
+}
+
+// ── Send Message ─────────────────────────────────────────────────────────────
+// Emits an L2→L1 message. It takes the next withdraw_nonce, so the L2 node
+// commits it to the withdrawal tree alongside burns, and is delivered on L1
+// by programs/bridge ReceiveMessage with a Merkle proof.
+// Accounts:
+//   0. [signer] sender (wallet, or a program PDA signing via invoke_signed)
+//   1. [writable] l2_bridge_config PDA
+
+fn process_send_message(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let sender = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    if !sender.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !config_account.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = SendMessageParams::try_from_slice(data)?;
+    if params.payload.len() > MAX_MESSAGE_PAYLOAD || params.accounts.len() > MAX_MESSAGE_ACCOUNTS {
+        return Err(BridgeL2Error::MessageTooLarge.into());
+    }
+
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let mut config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
+    if !config.is_initialized {
+        return Err(BridgeL2Error::UninitializedAccount.into());
+    }
+    if config.paused {
+        return Err(ProgramError::Custom(ERROR_BRIDGE_PAUSED));
+    }
+
+    let nonce = config.withdraw_nonce;
+    config.withdraw_nonce = nonce
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
+
+    let message = CrossDomainMessage {
+        nonce,
+        sender: *sender.key,
+        target_program: params.target_program,
+        accounts: params.accounts,
+        payload: params.payload,
+    };
+
+    msg!(
+        "EVENT:SendMessage:{{\"sender\":\"{}\",\"target_program\":\"{}\",\"accounts\":{},\"payload\":\"{}\",\"nonce\":{},\"message_hash\":\"{}\"}}",
+        message.sender, message.target_program, message_accounts_json(&message.accounts),
+        hex_encode(&message.payload), nonce, hex_encode(&message_hash(&message))
+    );
+
+    Ok(())
+}
+
+// ── Receive Message ──────────────────────────────────────────────────────────
+// Relayer-only: delivers an L1→L2 message. Authorized like a deposit release
+// (single relayer, or committee attestations on the message hash), and shares
+// the processed_deposit PDA since messages use L1 deposit nonces. The target
+// program gets the payload as instruction data and the message_origin PDA for
+// `message.sender` as its first account and signer.
+// Accounts:
+//   0. [signer] relayer
+//   1. [signer, writable] payer (for processed_deposit PDA rent)
+//   2. [] l2_bridge_config PDA
+//   3. [writable] processed_deposit PDA (seeds: ["processed", nonce])
+//   4. [writable] deposit_attestation PDA (seeds: ["attestation", nonce, message_hash];
+//      closed to the payer when a committee is configured)
+//   5. [] message_origin PDA (seeds: ["message_origin", message.sender])
+//   6. [] target program
+//   7. [] system_program
+//   8.. message.accounts, in order
+
+fn process_receive_message(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let relayer = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let processed_account = next_account_info(accounts_iter)?;
+    let attestation_account = next_account_info(accounts_iter)?;
+    let origin_account = next_account_info(accounts_iter)?;
+    let target_program = next_account_info(accounts_iter)?;
+    let system_program_info = next_account_info(accounts_iter)?;
+    let message_accounts = accounts_iter.as_slice();
+
+    if !relayer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !payer.is_signer || !payer.is_writable {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !processed_account.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = ReceiveMessageParams::try_from_slice(data)?;
+    let message = params.message;
+
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config = L2BridgeConfig::deserialize(&mut &config_account.data.borrow()[..])?;
+    if !config.is_initialized {
+        return Err(BridgeL2Error::UninitializedAccount.into());
+    }
+    if config.paused {
+        return Err(ProgramError::Custom(ERROR_BRIDGE_PAUSED));
+    }
+
+    // The bridge must never invoke itself with a signer it derives
+    if message.target_program == *program_id || *target_program.key != message.target_program {
+        return Err(BridgeL2Error::InvalidMessageTarget.into());
+    }
+
+    let hash = message_hash(&message);
+    check_release_authorized(
+        program_id,
+        &config,
+        relayer.key,
+        Some(attestation_account),
+        message.nonce,
+        &hash,
+    )?;
+
+    let nonce_bytes = message.nonce.to_le_bytes();
+    let (processed_pda, processed_bump) =
+        Pubkey::find_program_address(&[PROCESSED_SEED, &nonce_bytes], program_id);
+    if processed_pda != *processed_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !processed_account.data_is_empty() {
+        return Err(BridgeL2Error::DepositAlreadyProcessed.into());
+    }
+
+    let (origin_pda, origin_bump) = Pubkey::find_program_address(
+        &[MESSAGE_ORIGIN_SEED, message.sender.as_ref()],
+        program_id,
+    );
+    if origin_pda != *origin_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if message_accounts.len() != message.accounts.len()
+        || message_accounts
+            .iter()
+            .zip(&message.accounts)
+            .any(|(info, meta)| *info.key != meta.pubkey)
+    {
+        return Err(BridgeL2Error::MessageAccountMismatch.into());
+    }
+
+    // Record delivery before the CPI so the message can never run twice
+    let rent = Rent::get()?;
+    let space = ProcessedDeposit::LEN;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            processed_account.key,
+            rent.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[
+            payer.clone(),
+            processed_account.clone(),
+            system_program_info.clone(),
+        ],
+        &[&[PROCESSED_SEED, &nonce_bytes, &[processed_bump]]],
+    )?;
+    let processed = ProcessedDeposit {
+        nonce: message.nonce,
+        l1_tx_signature: params.l1_tx_signature,
+        processed_at: Clock::get()?.unix_timestamp,
+        bump: processed_bump,
+    };
+    processed.serialize(&mut &mut processed_account.data.borrow_mut()[..])?;
+    close_attestation(&config, Some(attestation_account), payer)?;
+
+    let mut metas = Vec::with_capacity(message.accounts.len() + 1);
+    metas.push(AccountMeta::new_readonly(origin_pda, true));
+    metas.extend(message.accounts.iter().map(|a| AccountMeta {
+        pubkey: a.pubkey,
+        is_signer: false,
+        is_writable: a.is_writable,
+    }));
+    let mut infos = Vec::with_capacity(message_accounts.len() + 2);
+    infos.push(origin_account.clone());
+    infos.extend(message_accounts.iter().cloned());
+    infos.push(target_program.clone());
+
+    invoke_signed(
+        &Instruction {
+            program_id: message.target_program,
+            accounts: metas,
+            data: message.payload,
+        },
+        &infos,
+        &[&[MESSAGE_ORIGIN_SEED, message.sender.as_ref(), &[origin_bump]]],
+    )?;
+
+    msg!(
+        "EVENT:ReceiveMessage:{{\"sender\":\"{}\",\"target_program\":\"{}\",\"nonce\":{},\"message_hash\":\"{}\"}}",
+        message.sender, message.target_program, message.nonce, hex_encode(&hash)
+    );
+
+    Ok(())
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────────────
+
+fn hex_encode(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        s.push_str(&format!("{:02x}", b));
+    }
+    s
+}
+
+fn is_valid_token_program(key: &Pubkey) -> bool {
+    *key == LEGACY_TOKEN_PROGRAM_ID || *key == TOKEN_2022_PROGRAM_ID
+}
+
+/// Load and validate the wrapped_token_info PDA for `l1_mint`.
+fn load_wrapped_token_info(
+    program_id: &Pubkey,
+    info_account: &AccountInfo,
+    l1_mint: &Pubkey,
+) -> Result<WrappedTokenInfo, ProgramError> {
+    let (info_pda, _) =
+        Pubkey::find_program_address(&[WRAPPED_MINT_SEED, l1_mint.as_ref()], program_id);
+    if info_pda != *info_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if info_account.owner != program_id || info_account.data_is_empty() {
+        return Err(BridgeL2Error::WrappedTokenNotRegistered.into());
+    }
+    let info = WrappedTokenInfo::try_from_slice(&info_account.data.borrow())?;
+    if !info.is_active {
+        return Err(BridgeL2Error::WrappedTokenNotRegistered.into());
+    }
+    Ok(info)
+}
+
+/// The L2 mint must be the registered one and owned by the given token program.
+fn check_wrapped_mint(
+    info: &WrappedTokenInfo,
+    l2_mint: &AccountInfo,
+    token_program: &AccountInfo,
+) -> ProgramResult {
+    if *l2_mint.key != info.l2_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if !is_valid_token_program(token_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if l2_mint.owner != token_program.key {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if l2_mint.data.borrow().get(MINT_DECIMALS_OFFSET) != Some(&info.decimals) {
+        return Err(BridgeL2Error::DecimalsMismatch.into());
+    }
+    Ok(())
+}
+
+/// Check `amount` against per-release bounds and add it to the rolling daily
+/// total, resetting the total once DAILY_WINDOW_SLOTS have passed.
+fn apply_release_limits(
+    amount: u64,
+    current_slot: u64,
+    min_release: u64,
+    max_release: u64,
+    daily_release_limit: u64,
+    daily_released: &mut u64,
+    last_reset_slot: &mut u64,
+) -> ProgramResult {
+    if amount < min_release {
+        return Err(BridgeL2Error::AmountBelowMinRelease.into());
+    }
+    if amount > max_release {
+        return Err(BridgeL2Error::AmountAboveMaxRelease.into());
+    }
+    if current_slot.saturating_sub(*last_reset_slot) > DAILY_WINDOW_SLOTS {
+        *daily_released = 0;
+        *last_reset_slot = current_slot;
+    }
+    let total = daily_released
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    if total > daily_release_limit {
+        return Err(BridgeL2Error::DailyReleaseLimitExceeded.into());
+    }
+    *daily_released = total;
+    Ok(())
+}
+
+/// SPL token account layout: bytes 0..32 = mint, 32..64 = owner.
+fn check_token_account(
+    token_account: &AccountInfo,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> ProgramResult {
+    if token_account.owner != token_program_id {
+        return Err(BridgeL2Error::InvalidTokenAccount.into());
+    }
+    let data = token_account.data.borrow();
+    if data.len() < 64 || data[0..32] != mint.to_bytes() || data[32..64] != owner.to_bytes() {
+        return Err(BridgeL2Error::InvalidTokenAccount.into());
+    }
+    Ok(())
+}
+
+/// Build an InitializeMint instruction compatible with both SPL Token and Token-2022.
+fn build_initialize_mint_ix(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    decimals: u8,
+) -> Instruction {
+    let mut data = Vec::with_capacity(35);
+    data.push(0); // InitializeMint instruction discriminator
+    data.push(decimals);
+    data.extend_from_slice(mint_authority.as_ref());
+    data.push(0); // no freeze authority
+    Instruction {
+        program_id: *token_program_id,
+        accounts: vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Build a MintTo instruction compatible with both SPL Token and Token-2022.
+fn build_mint_to_ix(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut data = Vec::with_capacity(9);
+    data.push(7); // MintTo instruction discriminator
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: *token_program_id,
+        accounts: vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+/// Build a Burn instruction compatible with both SPL Token and Token-2022.
+fn build_burn_ix(
+    token_program_id: &Pubkey,
+    account: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut data = Vec::with_capacity(9);
+    data.push(8); // Burn instruction discriminator
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: *token_program_id,
+        accounts: vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+        data,
+    }
+}
+
+/// sha256(l1_tx_signature || nonce || amount || recipient [|| l1_mint]).
+/// Native MYTH releases omit the mint; wrapped mints commit to it.
+fn deposit_hash(
+    l1_tx_signature: &[u8; 64],
+    nonce: u64,
+    amount: u64,
+    recipient: &Pubkey,
+    l1_mint: Option<&Pubkey>,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(l1_tx_signature);
+    hasher.update(nonce.to_le_bytes());
+    hasher.update(amount.to_le_bytes());
+    hasher.update(recipient.as_ref());
+    if let Some(mint) = l1_mint {
+        hasher.update(mint.as_ref());
+    }
+    hasher.finalize().into()
+}
+
+/// With no committee the single `config.relayer` is trusted. Otherwise the
+/// signer must be a member and `relayer_threshold` current members must have
+/// attested to `deposit_hash` for this nonce.
+fn check_release_authorized(
+    program_id: &Pubkey,
+    config: &L2BridgeConfig,
+    relayer: &Pubkey,
+    attestation_account: Option<&AccountInfo>,
+    nonce: u64,
+    deposit_hash: &[u8; 32],
+) -> ProgramResult {
+    if config.relayer_committee.is_empty() {
+        if *relayer != config.relayer {
+            return Err(BridgeL2Error::InvalidRelayer.into());
+        }
+        return Ok(());
+    }
+    if !config.relayer_committee.contains(relayer) {
+        return Err(BridgeL2Error::NotCommitteeMember.into());
+    }
+
+    let attestation_account =
+        attestation_account.ok_or(BridgeL2Error::InsufficientAttestations)?;
+    let (attestation_pda, _) = Pubkey::find_program_address(
+        &[ATTESTATION_SEED, &nonce.to_le_bytes(), deposit_hash],
+        program_id,
+    );
+    if attestation_pda != *attestation_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if attestation_account.owner != program_id {
+        return Err(BridgeL2Error::InsufficientAttestations.into());
+    }
+    let attestation =
+        DepositAttestation::deserialize(&mut &attestation_account.data.borrow()[..])?;
+    let votes = attestation
+        .attesters
+        .iter()
+        .filter(|a| config.relayer_committee.contains(a))
+        .count();
+    if votes < config.relayer_threshold as usize {
+        return Err(BridgeL2Error::InsufficientAttestations.into());
+    }
+    Ok(())
+}
+
+/// Close the attestation behind a committee release; its rent goes to the
+/// release payer. Single-relayer releases have no attestation to close.
+/// Call only after check_release_authorized accepted the account.
+fn close_attestation(
+    config: &L2BridgeConfig,
+    attestation_account: Option<&AccountInfo>,
+    payer: &AccountInfo,
+) -> ProgramResult {
+    let Some(attestation_account) = attestation_account else {
+        return Ok(());
+    };
+    if config.relayer_committee.is_empty() {
+        return Ok(());
+    }
+    if !attestation_account.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let lamports = attestation_account.lamports();
+    **attestation_account.try_borrow_mut_lamports()? = 0;
+    **payer.try_borrow_mut_lamports()? = payer
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    attestation_account.data.borrow_mut().fill(0);
+    Ok(())
+}
+
+fn is_valid_threshold(threshold: u8, members: usize) -> bool {
+    if members == 0 {
+        threshold == 0
+    } else {
+        threshold >= 1 && threshold as usize <= members
+    }
+}
+
+/// Top up rent and realloc an account owned by this program.
+fn grow_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    new_size: usize,
+) -> ProgramResult {
+    let rent = Rent::get()?;
+    let shortfall = rent
+        .minimum_balance(new_size)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        solana_program::program::invoke(
+            &system_instruction::transfer(payer.key, account.key, shortfall),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    account.realloc(new_size, false)
+}
+
+/// sha256 of the Borsh-encoded message; the same value programs/bridge uses.
+fn message_hash(message: &CrossDomainMessage) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(borsh::to_vec(message).unwrap_or_default());
+    hasher.finalize().into()
+}
+
+fn message_accounts_json(accounts: &[MessageAccount]) -> String {
+    let entries: Vec<String> = accounts
+        .iter()
+        .map(|a| format!("{{\"pubkey\":\"{}\",\"is_writable\":{}}}", a.pubkey, a.is_writable))
+        .collect();
+    format!("[{}]", entries.join(","))
 }
@@ -1,15 +1,15 @@
 // Comprehensive test suite for Mythic Bridge L2 program
 // Tests: happy paths + attack/edge cases using solana-program-test
 
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::BorshDeserialize;
 use solana_program::pubkey::Pubkey;
 use solana_program::system_program;
 use solana_program_test::*;
 use solana_sdk::{
     account::Account,
-    instruction::{AccountMeta, Instruction},
+    instruction::{AccountMeta, Instruction, InstructionError},
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 use mythic_bridge_l2::*;
@@ -18,10 +18,21 @@ use mythic_bridge_l2::*;
 
 const L2_BRIDGE_CONFIG_SEED: &[u8] = b"l2_bridge_config";
 const WRAPPED_MINT_SEED: &[u8] = b"wrapped_mint";
+const L2_MINT_SEED: &[u8] = b"mint";
 const PROCESSED_SEED: &[u8] = b"processed";
+const ATTESTATION_SEED: &[u8] = b"attestation";
+const BRIDGE_RESERVE_SEED: &[u8] = b"bridge_reserve";
 const IX_INITIALIZE: u8 = 0;
-const IX_BURN_WRAPPED: u8 = 3;
+const IX_RELEASE_BRIDGED: u8 = 2;
 const IX_UPDATE_CONFIG: u8 = 4;
+const IX_REGISTER_WRAPPED_TOKEN: u8 = 8;
+const IX_MINT_WRAPPED: u8 = 9;
+const IX_ATTEST_DEPOSIT: u8 = 11;
+const IX_ADD_RELAYER: u8 = 12;
+const IX_UPDATE_WRAPPED_LIMITS: u8 = 16;
+
+const TOKEN_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 
 fn program_id() -> Pubkey {
     "MythBrdgL2111111111111111111111111111111111"
@@ -70,6 +81,240 @@ fn build_update_config_ix(
     }
 }
 
+fn build_register_wrapped_token_ix(
+    pid: &Pubkey,
+    admin: &Pubkey,
+    l1_mint: &Pubkey,
+    decimals: u8,
+) -> Instruction {
+    let (config_pda, _) = Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], pid);
+    let (info_pda, _) =
+        Pubkey::find_program_address(&[WRAPPED_MINT_SEED, l1_mint.as_ref()], pid);
+    let (mint_pda, _) = Pubkey::find_program_address(&[L2_MINT_SEED, l1_mint.as_ref()], pid);
+    let params = RegisterWrappedTokenParams {
+        l1_mint: *l1_mint,
+        decimals,
+    };
+    let mut data = vec![IX_REGISTER_WRAPPED_TOKEN];
+    data.extend_from_slice(&borsh::to_vec(&params).unwrap());
+
+    Instruction {
+        program_id: *pid,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(info_pda, false),
+            AccountMeta::new(mint_pda, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+        data,
+    }
+}
+
+fn build_update_wrapped_limits_ix(
+    pid: &Pubkey,
+    admin: &Pubkey,
+    l1_mint: &Pubkey,
+    min_release: u64,
+    max_release: u64,
+    daily_release_limit: u64,
+) -> Instruction {
+    let (config_pda, _) = Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], pid);
+    let (info_pda, _) =
+        Pubkey::find_program_address(&[WRAPPED_MINT_SEED, l1_mint.as_ref()], pid);
+    let params = UpdateWrappedLimitsParams {
+        l1_mint: *l1_mint,
+        min_release,
+        max_release,
+        daily_release_limit,
+    };
+    let mut data = vec![IX_UPDATE_WRAPPED_LIMITS];
+    data.extend_from_slice(&borsh::to_vec(&params).unwrap());
+
+    Instruction {
+        program_id: *pid,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(info_pda, false),
+        ],
+        data,
+    }
+}
+
+fn build_mint_wrapped_ix(
+    pid: &Pubkey,
+    relayer: &Pubkey,
+    recipient_token: &Pubkey,
+    params: &MintWrappedParams,
+) -> Instruction {
+    let l1_mint = params.l1_mint;
+    let (config_pda, _) = Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], pid);
+    let (info_pda, _) =
+        Pubkey::find_program_address(&[WRAPPED_MINT_SEED, l1_mint.as_ref()], pid);
+    let (mint_pda, _) = Pubkey::find_program_address(&[L2_MINT_SEED, l1_mint.as_ref()], pid);
+    let nonce_bytes = params.l1_deposit_nonce.to_le_bytes();
+    let (processed_pda, _) = Pubkey::find_program_address(&[PROCESSED_SEED, &nonce_bytes], pid);
+    let (attestation_pda, _) =
+        Pubkey::find_program_address(&[ATTESTATION_SEED, &nonce_bytes, &[0u8; 32]], pid);
+    let mut data = vec![IX_MINT_WRAPPED];
+    data.extend_from_slice(&borsh::to_vec(params).unwrap());
+
+    Instruction {
+        program_id: *pid,
+        accounts: vec![
+            AccountMeta::new_readonly(*relayer, true),
+            AccountMeta::new(*relayer, true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(info_pda, false),
+            AccountMeta::new(mint_pda, false),
+            AccountMeta::new(*recipient_token, false),
+            AccountMeta::new(processed_pda, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(attestation_pda, false),
+        ],
+        data,
+    }
+}
+
+fn build_add_relayer_ix(pid: &Pubkey, admin: &Pubkey, relayer: &Pubkey, threshold: u8) -> Instruction {
+    let (config_pda, _) = Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], pid);
+    let params = UpdateCommitteeParams { relayer: *relayer, threshold };
+    let mut data = vec![IX_ADD_RELAYER];
+    data.extend_from_slice(&borsh::to_vec(&params).unwrap());
+
+    Instruction {
+        program_id: *pid,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(config_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+fn attestation_pda(pid: &Pubkey, nonce: u64, deposit_hash: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[ATTESTATION_SEED, &nonce.to_le_bytes(), deposit_hash], pid).0
+}
+
+fn build_attest_deposit_ix(pid: &Pubkey, relayer: &Pubkey, nonce: u64, deposit_hash: [u8; 32]) -> Instruction {
+    let (config_pda, _) = Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], pid);
+    let params = AttestDepositParams { l1_deposit_nonce: nonce, deposit_hash };
+    let mut data = vec![IX_ATTEST_DEPOSIT];
+    data.extend_from_slice(&borsh::to_vec(&params).unwrap());
+
+    Instruction {
+        program_id: *pid,
+        accounts: vec![
+            AccountMeta::new(*relayer, true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(attestation_pda(pid, nonce, &deposit_hash), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+fn build_release_bridged_ix(pid: &Pubkey, relayer: &Pubkey, params: &ReleaseBridgedParams) -> Instruction {
+    let (config_pda, _) = Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], pid);
+    let (reserve_pda, _) = Pubkey::find_program_address(&[BRIDGE_RESERVE_SEED], pid);
+    let (processed_pda, _) = Pubkey::find_program_address(
+        &[PROCESSED_SEED, &params.l1_deposit_nonce.to_le_bytes()],
+        pid,
+    );
+    let mut data = vec![IX_RELEASE_BRIDGED];
+    data.extend_from_slice(&borsh::to_vec(params).unwrap());
+
+    Instruction {
+        program_id: *pid,
+        accounts: vec![
+            AccountMeta::new_readonly(*relayer, true),
+            AccountMeta::new(*relayer, true),
+            AccountMeta::new(config_pda, false),
+            AccountMeta::new(reserve_pda, false),
+            AccountMeta::new(params.recipient, false),
+            AccountMeta::new(processed_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(
+                attestation_pda(pid, params.l1_deposit_nonce, &params.deposit_hash),
+                false,
+            ),
+        ],
+        data,
+    }
+}
+
+/// sha256(l1_tx_signature || nonce || amount || recipient), as ReleaseBridged checks it.
+fn release_params(nonce: u64, amount: u64, recipient: Pubkey) -> ReleaseBridgedParams {
+    use sha2::{Digest, Sha256};
+    let l1_tx_signature = [nonce as u8; 64];
+    let mut hasher = Sha256::new();
+    hasher.update(l1_tx_signature);
+    hasher.update(nonce.to_le_bytes());
+    hasher.update(amount.to_le_bytes());
+    hasher.update(recipient.as_ref());
+    ReleaseBridgedParams {
+        l1_deposit_nonce: nonce,
+        recipient,
+        amount,
+        l1_tx_signature,
+        deposit_hash: hasher.finalize().into(),
+    }
+}
+
+async fn send(banks: &mut BanksClient, ix: Instruction, signer: &Keypair) -> Result<(), Option<u32>> {
+    let bh = banks.get_new_latest_blockhash(&Default::default()).await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&signer.pubkey()), &[signer], bh);
+    banks.process_transaction(tx).await.map_err(custom_error)
+}
+
+fn funded_account() -> Account {
+    Account {
+        lamports: 10_000_000_000,
+        data: vec![],
+        owner: system_program::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// An initialized SPL token account for `mint`, owned by `owner`, holding nothing.
+fn token_account(mint: &Pubkey, owner: &Pubkey) -> Account {
+    let mut data = vec![0u8; 165];
+    data[0..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[108] = 1; // AccountState::Initialized
+    Account {
+        lamports: 10_000_000,
+        data,
+        owner: TOKEN_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+async fn send_mint(
+    banks: &mut BanksClient,
+    relayer: &Keypair,
+    pid: &Pubkey,
+    recipient_token: &Pubkey,
+    params: MintWrappedParams,
+) -> Result<(), Option<u32>> {
+    let ix = build_mint_wrapped_ix(pid, &relayer.pubkey(), recipient_token, &params);
+    send(banks, ix, relayer).await
+}
+
+fn custom_error(err: BanksClientError) -> Option<u32> {
+    match err.unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(code),
+        _ => None,
+    }
+}
+
 // ── Happy Path Tests ─────────────────────────────────────────────────────────
 
 #[tokio::test]
@@ -77,7 +322,7 @@ async fn test_initialize_l2_bridge() {
     let pid = program_id();
     let mut pt = ProgramTest::new("mythic_bridge_l2", pid, processor!(process_instruction));
     pt.set_compute_max_units(200_000);
-    let (mut banks, payer, bh) = pt.start().await;
+    let (banks, payer, bh) = pt.start().await;
 
     let relayer = Keypair::new();
     let ix = build_initialize_ix(&pid, &payer.pubkey(), &relayer.pubkey());
@@ -86,12 +331,12 @@ async fn test_initialize_l2_bridge() {
 
     let (config_pda, _) = Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], &pid);
     let acct = banks.get_account(config_pda).await.unwrap().unwrap();
-    let config = L2BridgeConfig::try_from_slice(&acct.data).unwrap();
+    let config = L2BridgeConfig::deserialize(&mut &acct.data[..]).unwrap();
 
     assert!(config.is_initialized);
     assert_eq!(config.admin, payer.pubkey());
     assert_eq!(config.relayer, relayer.pubkey());
-    assert_eq!(config.burn_nonce, 0);
+    assert_eq!(config.withdraw_nonce, 0);
 }
 
 #[tokio::test]
@@ -99,7 +344,7 @@ async fn test_update_config_success() {
     let pid = program_id();
     let mut pt = ProgramTest::new("mythic_bridge_l2", pid, processor!(process_instruction));
     pt.set_compute_max_units(200_000);
-    let (mut banks, payer, bh) = pt.start().await;
+    let (banks, payer, bh) = pt.start().await;
 
     let relayer = Keypair::new();
     let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &relayer.pubkey());
@@ -117,10 +362,213 @@ async fn test_update_config_success() {
 
     let (config_pda, _) = Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], &pid);
     let acct = banks.get_account(config_pda).await.unwrap().unwrap();
-    let config = L2BridgeConfig::try_from_slice(&acct.data).unwrap();
+    let config = L2BridgeConfig::deserialize(&mut &acct.data[..]).unwrap();
     assert_eq!(config.relayer, new_relayer);
 }
 
+#[tokio::test]
+async fn test_register_wrapped_token() {
+    let pid = program_id();
+    let mut pt = ProgramTest::new("mythic_bridge_l2", pid, processor!(process_instruction));
+    pt.set_compute_max_units(200_000);
+    let (banks, payer, bh) = pt.start().await;
+
+    let relayer = Keypair::new();
+    let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &relayer.pubkey());
+    let l1_mint = Pubkey::new_unique();
+    let register_ix = build_register_wrapped_token_ix(&pid, &payer.pubkey(), &l1_mint, 6);
+    let tx = Transaction::new_signed_with_payer(
+        &[init_ix, register_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        bh,
+    );
+    banks.process_transaction(tx).await.unwrap();
+
+    let (info_pda, _) =
+        Pubkey::find_program_address(&[WRAPPED_MINT_SEED, l1_mint.as_ref()], &pid);
+    let (mint_pda, _) = Pubkey::find_program_address(&[L2_MINT_SEED, l1_mint.as_ref()], &pid);
+    let acct = banks.get_account(info_pda).await.unwrap().unwrap();
+    let info = WrappedTokenInfo::try_from_slice(&acct.data).unwrap();
+    assert_eq!(info.l1_mint, l1_mint);
+    assert_eq!(info.l2_mint, mint_pda);
+    assert!(info.is_active);
+
+    // The wrapped mint is owned by the token program with the info PDA as authority
+    let mint_acct = banks.get_account(mint_pda).await.unwrap().unwrap();
+    assert_eq!(mint_acct.owner, TOKEN_PROGRAM_ID);
+    assert_eq!(&mint_acct.data[4..36], info_pda.as_ref());
+    assert_eq!(mint_acct.data[44], 6);
+
+    // Registering the same L1 mint twice must fail
+    let again_ix = build_register_wrapped_token_ix(&pid, &payer.pubkey(), &l1_mint, 6);
+    let bh2 = banks.get_latest_blockhash().await.unwrap();
+    let again_tx =
+        Transaction::new_signed_with_payer(&[again_ix], Some(&payer.pubkey()), &[&payer], bh2);
+    assert!(
+        banks.process_transaction(again_tx).await.is_err(),
+        "Wrapped token must not be registered twice"
+    );
+}
+
+#[tokio::test]
+async fn test_mint_wrapped_enforces_per_mint_limits_and_decimals() {
+    let pid = program_id();
+    let mut pt = ProgramTest::new("mythic_bridge_l2", pid, processor!(process_instruction));
+    pt.set_compute_max_units(200_000);
+
+    let l1_mint = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let recipient_token = Pubkey::new_unique();
+    let (mint_pda, _) = Pubkey::find_program_address(&[L2_MINT_SEED, l1_mint.as_ref()], &pid);
+    pt.add_account(recipient_token, token_account(&mint_pda, &recipient));
+    let (mut banks, payer, bh) = pt.start().await;
+
+    let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &payer.pubkey());
+    let register_ix = build_register_wrapped_token_ix(&pid, &payer.pubkey(), &l1_mint, 8);
+    let tx = Transaction::new_signed_with_payer(
+        &[init_ix, register_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        bh,
+    );
+    banks.process_transaction(tx).await.unwrap();
+
+    let deposit = |nonce: u64, amount: u64, l1_decimals: u8| MintWrappedParams {
+        l1_deposit_nonce: nonce,
+        recipient,
+        amount,
+        l1_mint,
+        l1_tx_signature: [nonce as u8; 64],
+        l1_decimals,
+    };
+    // Limits start at zero, so nothing mints before the admin sets them
+    assert_eq!(
+        send_mint(&mut banks, &payer, &pid, &recipient_token, deposit(1, 100, 8)).await,
+        Err(Some(BridgeL2Error::AmountAboveMaxRelease as u32))
+    );
+
+    let limits_ix =
+        build_update_wrapped_limits_ix(&pid, &payer.pubkey(), &l1_mint, 10, 1_000, 1_500);
+    let bh = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[limits_ix], Some(&payer.pubkey()), &[&payer], bh);
+    banks.process_transaction(tx).await.unwrap();
+
+    assert_eq!(
+        send_mint(&mut banks, &payer, &pid, &recipient_token, deposit(1, 100, 9)).await,
+        Err(Some(BridgeL2Error::DecimalsMismatch as u32)),
+        "a deposit read with other decimals must not mint"
+    );
+    assert_eq!(
+        send_mint(&mut banks, &payer, &pid, &recipient_token, deposit(1, 9, 8)).await,
+        Err(Some(BridgeL2Error::AmountBelowMinRelease as u32))
+    );
+    assert_eq!(
+        send_mint(&mut banks, &payer, &pid, &recipient_token, deposit(1, 1_001, 8)).await,
+        Err(Some(BridgeL2Error::AmountAboveMaxRelease as u32))
+    );
+    assert_eq!(send_mint(&mut banks, &payer, &pid, &recipient_token, deposit(1, 1_000, 8)).await, Ok(()));
+    assert_eq!(
+        send_mint(&mut banks, &payer, &pid, &recipient_token, deposit(2, 600, 8)).await,
+        Err(Some(BridgeL2Error::DailyReleaseLimitExceeded as u32))
+    );
+    assert_eq!(send_mint(&mut banks, &payer, &pid, &recipient_token, deposit(2, 500, 8)).await, Ok(()));
+
+    let token = banks.get_account(recipient_token).await.unwrap().unwrap();
+    assert_eq!(u64::from_le_bytes(token.data[64..72].try_into().unwrap()), 1_500);
+
+    let (info_pda, _) =
+        Pubkey::find_program_address(&[WRAPPED_MINT_SEED, l1_mint.as_ref()], &pid);
+    let info_acct = banks.get_account(info_pda).await.unwrap().unwrap();
+    let info = WrappedTokenInfo::try_from_slice(&info_acct.data).unwrap();
+    assert_eq!(info.daily_released, 1_500);
+}
+
+#[tokio::test]
+async fn test_committee_release_needs_threshold_and_closes_attestation() {
+    let pid = program_id();
+    let mut pt = ProgramTest::new("mythic_bridge_l2", pid, processor!(process_instruction));
+    pt.set_compute_max_units(200_000);
+
+    let members: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    for member in &members {
+        pt.add_account(member.pubkey(), funded_account());
+    }
+    let (reserve_pda, _) = Pubkey::find_program_address(&[BRIDGE_RESERVE_SEED], &pid);
+    pt.add_account(reserve_pda, funded_account());
+    let (mut banks, payer, _) = pt.start().await;
+
+    let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &payer.pubkey());
+    send(&mut banks, init_ix, &payer).await.unwrap();
+    for (i, member) in members.iter().enumerate() {
+        let threshold = (i as u8 + 1).min(2);
+        let ix = build_add_relayer_ix(&pid, &payer.pubkey(), &member.pubkey(), threshold);
+        send(&mut banks, ix, &payer).await.unwrap();
+    }
+
+    let recipient = Pubkey::new_unique();
+    let params = release_params(7, 1_000_000_000, recipient);
+    let attestation = attestation_pda(&pid, 7, &params.deposit_hash);
+
+    // The former single relayer is no longer trusted on its own
+    let release_ix = build_release_bridged_ix(&pid, &payer.pubkey(), &params);
+    assert_eq!(
+        send(&mut banks, release_ix, &payer).await,
+        Err(Some(BridgeL2Error::NotCommitteeMember as u32))
+    );
+
+    // One vote is below the 2-of-3 threshold
+    let attest_ix = build_attest_deposit_ix(&pid, &members[0].pubkey(), 7, params.deposit_hash);
+    send(&mut banks, attest_ix, &members[0]).await.unwrap();
+    let release_ix = build_release_bridged_ix(&pid, &members[0].pubkey(), &params);
+    assert_eq!(
+        send(&mut banks, release_ix, &members[0]).await,
+        Err(Some(BridgeL2Error::InsufficientAttestations as u32))
+    );
+
+    // A vote for a different hash lands in another PDA and does not count
+    let forged = release_params(7, 2_000_000_000, recipient);
+    let attest_ix = build_attest_deposit_ix(&pid, &members[1].pubkey(), 7, forged.deposit_hash);
+    send(&mut banks, attest_ix, &members[1]).await.unwrap();
+    let release_ix = build_release_bridged_ix(&pid, &members[1].pubkey(), &forged);
+    assert_eq!(
+        send(&mut banks, release_ix, &members[1]).await,
+        Err(Some(BridgeL2Error::InsufficientAttestations as u32))
+    );
+
+    // A second vote for the same hash releases and closes the attestation
+    let attest_ix = build_attest_deposit_ix(&pid, &members[2].pubkey(), 7, params.deposit_hash);
+    send(&mut banks, attest_ix, &members[2]).await.unwrap();
+    let attestation_rent = banks.get_account(attestation).await.unwrap().unwrap().lamports;
+    let payer_before = banks.get_balance(members[2].pubkey()).await.unwrap();
+    let release_ix = build_release_bridged_ix(&pid, &members[2].pubkey(), &params);
+    send(&mut banks, release_ix, &members[2]).await.unwrap();
+
+    assert_eq!(banks.get_balance(recipient).await.unwrap(), 1_000_000_000);
+    assert!(banks.get_account(attestation).await.unwrap().is_none());
+    let processed_rent = banks
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(ProcessedDeposit::LEN);
+    assert_eq!(
+        banks.get_balance(members[2].pubkey()).await.unwrap(),
+        payer_before + attestation_rent - processed_rent - 5_000,
+        "the release payer recovers the attestation rent"
+    );
+
+    // The processed_deposit PDA still blocks a replay once the votes are gone
+    let attest_ix = build_attest_deposit_ix(&pid, &members[0].pubkey(), 7, params.deposit_hash);
+    send(&mut banks, attest_ix, &members[0]).await.unwrap();
+    let attest_ix = build_attest_deposit_ix(&pid, &members[1].pubkey(), 7, params.deposit_hash);
+    send(&mut banks, attest_ix, &members[1]).await.unwrap();
+    let release_ix = build_release_bridged_ix(&pid, &members[1].pubkey(), &params);
+    assert_eq!(
+        send(&mut banks, release_ix, &members[1]).await,
+        Err(Some(BridgeL2Error::DepositAlreadyProcessed as u32))
+    );
+}
+
 // ── Attack / Edge Case Tests ─────────────────────────────────────────────────
 
 #[tokio::test]
@@ -131,13 +579,8 @@ async fn test_double_initialize_l2() {
 
     // Pre-allocate config PDA with data to simulate already-initialized state.
     let (config_pda, bump) = Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], &pid);
-    let config = L2BridgeConfig {
-        admin: Pubkey::new_unique(),
-        relayer: Pubkey::new_unique(),
-        burn_nonce: 0,
-        is_initialized: true,
-        bump,
-    };
+    let mut config = sample_config();
+    config.bump = bump;
     let config_data = borsh::to_vec(&config).unwrap();
     pt.add_account(
         config_pda,
@@ -150,7 +593,7 @@ async fn test_double_initialize_l2() {
         },
     );
 
-    let (mut banks, payer, bh) = pt.start().await;
+    let (banks, payer, bh) = pt.start().await;
 
     // Try to initialize when config already exists — MUST FAIL
     let relayer = Keypair::new();
@@ -180,7 +623,7 @@ async fn test_update_config_unauthorized() {
         },
     );
 
-    let (mut banks, payer, bh) = pt.start().await;
+    let (banks, payer, bh) = pt.start().await;
 
     let relayer = Keypair::new();
     let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &relayer.pubkey());
@@ -208,7 +651,7 @@ async fn test_update_config_unauthorized() {
 async fn test_invalid_instruction_data_l2() {
     let pid = program_id();
     let pt = ProgramTest::new("mythic_bridge_l2", pid, processor!(process_instruction));
-    let (mut banks, payer, bh) = pt.start().await;
+    let (banks, payer, bh) = pt.start().await;
 
     // Empty data
     let ix = Instruction {
@@ -238,22 +681,44 @@ async fn test_invalid_instruction_data_l2() {
 
 // ── Serialization Tests ──────────────────────────────────────────────────────
 
-#[test]
-fn test_l2_bridge_config_serialization() {
-    let config = L2BridgeConfig {
+fn sample_config() -> L2BridgeConfig {
+    L2BridgeConfig {
         admin: Pubkey::new_unique(),
         relayer: Pubkey::new_unique(),
-        burn_nonce: 99,
+        withdraw_nonce: 99,
+        total_released: 0,
+        total_received: 0,
         is_initialized: true,
         bump: 253,
-    };
+        paused: false,
+        reserve_bump: 252,
+        min_release: 1,
+        max_release: 10,
+        daily_release_limit: 100,
+        daily_released: 0,
+        last_reset_slot: 0,
+        relayer_threshold: 2,
+        relayer_committee: (0..MAX_RELAYER_COMMITTEE).map(|_| Pubkey::new_unique()).collect(),
+    }
+}
+
+#[test]
+fn test_l2_bridge_config_serialization() {
+    let config = sample_config();
     let bytes = borsh::to_vec(&config).unwrap();
     assert_eq!(bytes.len(), L2BridgeConfig::LEN);
 
-    let deser = L2BridgeConfig::try_from_slice(&bytes).unwrap();
+    let deser = L2BridgeConfig::deserialize(&mut &bytes[..]).unwrap();
     assert_eq!(deser.admin, config.admin);
-    assert_eq!(deser.burn_nonce, 99);
+    assert_eq!(deser.withdraw_nonce, 99);
     assert!(deser.is_initialized);
+    assert_eq!(deser.relayer_committee, config.relayer_committee);
+
+    // A v1 account predates the committee and trusts the single relayer
+    let v1 = L2BridgeConfig::deserialize(&mut &bytes[..L2BridgeConfig::V1_LEN]).unwrap();
+    assert_eq!(v1.daily_release_limit, 100);
+    assert_eq!(v1.relayer_threshold, 0);
+    assert!(v1.relayer_committee.is_empty());
 }
 
 #[test]
@@ -263,6 +728,12 @@ fn test_wrapped_token_info_serialization() {
         l2_mint: Pubkey::new_unique(),
         is_active: true,
         bump: 250,
+        decimals: 8,
+        min_release: 1,
+        max_release: 2,
+        daily_release_limit: 3,
+        daily_released: 4,
+        last_reset_slot: 5,
     };
     let bytes = borsh::to_vec(&info).unwrap();
     assert_eq!(bytes.len(), WrappedTokenInfo::LEN);
@@ -270,6 +741,29 @@ fn test_wrapped_token_info_serialization() {
     let deser = WrappedTokenInfo::try_from_slice(&bytes).unwrap();
     assert_eq!(deser.l1_mint, info.l1_mint);
     assert!(deser.is_active);
+    assert_eq!(deser.decimals, 8);
+    assert_eq!(deser.last_reset_slot, 5);
+}
+
+#[test]
+fn test_deposit_attestation_fits_full_committee() {
+    let att = DepositAttestation {
+        nonce: 7,
+        deposit_hash: [0xAA; 32],
+        bump: 251,
+        attesters: (0..MAX_RELAYER_COMMITTEE).map(|_| Pubkey::new_unique()).collect(),
+    };
+    let bytes = borsh::to_vec(&att).unwrap();
+    assert_eq!(bytes.len(), DepositAttestation::LEN);
+
+    // Accounts are allocated at LEN, so partially filled ones carry trailing zeros
+    let mut data = vec![0u8; DepositAttestation::LEN];
+    let partial = DepositAttestation { attesters: att.attesters[..2].to_vec(), ..att };
+    data[..borsh::to_vec(&partial).unwrap().len()]
+        .copy_from_slice(&borsh::to_vec(&partial).unwrap());
+    let deser = DepositAttestation::deserialize(&mut &data[..]).unwrap();
+    assert_eq!(deser.attesters.len(), 2);
+    assert_eq!(deser.deposit_hash, [0xAA; 32]);
 }
 
 #[test]
@@ -311,9 +805,32 @@ fn test_mint_wrapped_params_serialization() {
         amount: 5_000_000_000,
         l1_mint: Pubkey::new_unique(),
         l1_tx_signature: [0xDD; 64],
+        l1_decimals: 8,
     };
     let bytes = borsh::to_vec(&params).unwrap();
     let deser = MintWrappedParams::try_from_slice(&bytes).unwrap();
     assert_eq!(deser.l1_deposit_nonce, 17);
     assert_eq!(deser.amount, 5_000_000_000);
 }
+
+#[test]
+fn test_cross_domain_message_matches_l1_encoding() {
+    use sha2::{Digest, Sha256};
+    // Same vector as test_message_hash_known_vector in programs/bridge
+    let message = CrossDomainMessage {
+        nonce: 5,
+        sender: Pubkey::new_from_array([1; 32]),
+        target_program: Pubkey::new_from_array([2; 32]),
+        accounts: vec![MessageAccount { pubkey: Pubkey::new_from_array([3; 32]), is_writable: true }],
+        payload: vec![0xde, 0xad, 0xbe, 0xef],
+    };
+    let encoded = borsh::to_vec(&message).unwrap();
+    let hash: [u8; 32] = Sha256::digest(&encoded).into();
+    let expected = "27357153368e16944ba70e33b9852096ce22fc10d07af0f7943f942b1b9363c7";
+    let hex: String = hash.iter().map(|b| format!("{:02x}", b)).collect();
+    assert_eq!(hex, expected);
+
+    let decoded = CrossDomainMessage::try_from_slice(&encoded).unwrap();
+    assert_eq!(decoded.accounts, message.accounts);
+    assert_eq!(decoded.payload, message.payload);
+}
@@ -14,6 +14,7 @@ use solana_program::{
     sysvar::Sysvar,
 };
 use solana_program::instruction::{AccountMeta, Instruction};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
 declare_id!("oEQfREm4FQkaVeRoxJHkJLB1feHprrntY6eJuW2zbqQ");
@@ -26,14 +27,42 @@ const VAULT_SEED: &[u8] = b"vault";
 const SOL_VAULT_SEED: &[u8] = b"sol_vault";
 const WITHDRAWAL_SEED: &[u8] = b"withdrawal";
 const CHALLENGE_BOND_SEED: &[u8] = b"challenge_bond";
+const MESSAGE_SEED: &[u8] = b"message";
+const MESSAGE_ORIGIN_SEED: &[u8] = b"message_origin";
+const FAST_WITHDRAWAL_SEED: &[u8] = b"fast_withdrawal";
+
+/// Settlement program (L1) — owns the `StateRootAccount`s that withdrawal
+/// Merkle proofs are checked against.
+const SETTLEMENT_PROGRAM_ID: Pubkey = solana_program::pubkey!("4TrowzShv4CrsuqZeUdLLVMdnDDkqkmnER1MZ5NsSaav");
+const STATE_ROOT_SEED: &[u8] = b"state_root";
+/// Tags of the settlement program's `StateRootStatus` enum.
+const STATE_ROOT_STATUS_FINALIZED: u8 = 2;
+const STATE_ROOT_STATUS_INVALIDATED: u8 = 3;
+
+/// Domain separators so a leaf can never be confused with an inner node.
+const MERKLE_LEAF_PREFIX: u8 = 0x00;
+const MERKLE_NODE_PREFIX: u8 = 0x01;
+/// Leaf prefix for L2→L1 messages, distinct from withdrawal leaves.
+const MESSAGE_LEAF_PREFIX: u8 = 0x02;
+/// Deep enough for 2^32 withdrawals per state root.
+const MAX_MERKLE_PROOF_DEPTH: usize = 32;
+
+/// Cross-domain message limits, so a delivery fits in one transaction.
+pub const MAX_MESSAGE_PAYLOAD: usize = 512;
+pub const MAX_MESSAGE_ACCOUNTS: usize = 8;
 
 /// Minimum challenger bond: 0.1 SOL
 const MIN_CHALLENGE_BOND: u64 = 100_000_000;
 /// Bond is 10% of the withdrawal amount
 const CHALLENGE_BOND_BPS: u64 = 1_000; // 10% = 1000 bps out of 10000
+/// Relayer bond escrowed in each WithdrawalRequest above its rent: 0.1 SOL.
+/// Paid to the challenger if a challenge is upheld, otherwise returned to
+/// the sequencer when the withdrawal finalizes.
+const RELAYER_WITHDRAWAL_BOND: u64 = 100_000_000;
 
 /// Native SOL mint address (sentinel for SOL deposits/withdrawals).
 const NATIVE_SOL_MINT_STR: &str = "So11111111111111111111111111111111111111112";
+const NATIVE_SOL_MINT: Pubkey = solana_program::pubkey!("So11111111111111111111111111111111111111112");
 
 /// Token-2022 (Token Extensions) program ID.
 /// MYTH on L1 is a Token-2022 mint, so we must accept this program in addition
@@ -106,6 +135,13 @@ const IX_SET_LIMITS: u8 = 9;
 const IX_FINALIZE_SOL_WITHDRAWAL: u8 = 10;
 const IX_CREATE_VAULT: u8 = 11;
 const IX_SEQUENCER_WITHDRAW_SOL: u8 = 12;
+const IX_RESOLVE_CHALLENGE: u8 = 13;
+const IX_SEND_MESSAGE: u8 = 14;
+const IX_RECEIVE_MESSAGE: u8 = 15;
+const IX_REQUEST_FAST_WITHDRAWAL: u8 = 16;
+const IX_FILL_FAST_WITHDRAWAL: u8 = 17;
+const IX_CLOSE_FAST_WITHDRAWAL: u8 = 18;
+const IX_MIGRATE_WITHDRAWAL: u8 = 19;
 
 // ── Error Codes ──────────────────────────────────────────────────────────────
 
@@ -131,6 +167,24 @@ pub enum BridgeError {
     InvalidMerkleProof,
     #[error("Invalid nonce")]
     InvalidNonce,
+    #[error("Invalid settlement state root account")]
+    InvalidStateRoot,
+    #[error("Settlement state root is not finalized")]
+    StateRootNotFinalized,
+    #[error("Message payload or account list too large")]
+    MessageTooLarge,
+    #[error("Invalid message target program")]
+    InvalidMessageTarget,
+    #[error("Message already processed")]
+    MessageAlreadyProcessed,
+    #[error("Message accounts do not match")]
+    MessageAccountMismatch,
+    #[error("Fast withdrawal already filled")]
+    FastWithdrawalFilled,
+    #[error("Fast withdrawal fee is invalid or does not match the offer")]
+    InvalidFastWithdrawalFee,
+    #[error("Withdrawal is not finalized or cancelled yet")]
+    WithdrawalNotSettled,
 }
 
 impl From<BridgeError> for ProgramError {
@@ -186,7 +240,8 @@ pub struct WithdrawalRequest {
     pub recipient: Pubkey,
     pub amount: u64,
     pub token_mint: Pubkey,
-    pub merkle_proof: [u8; 32],
+    /// L2 slot of the settlement state root the withdrawal was proven against.
+    pub l2_slot: u64,
     pub challenge_deadline: i64,
     pub status: WithdrawalStatus,
     pub nonce: u64,
@@ -194,9 +249,99 @@ pub struct WithdrawalRequest {
 }
 
 impl WithdrawalRequest {
+    pub const LEN: usize = 32 + 8 + 32 + 8 + 8 + 1 + 8 + 1; // 98
+}
+
+/// v1 layout of `WithdrawalRequest`, from before withdrawals were proven
+/// against settlement roots: `merkle_proof` was an unchecked hash where
+/// `l2_slot` now is. Only read by MigrateWithdrawal.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct WithdrawalRequestV1 {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub token_mint: Pubkey,
+    pub merkle_proof: [u8; 32],
+    pub challenge_deadline: i64,
+    pub status: WithdrawalStatus,
+    pub nonce: u64,
+    pub bump: u8,
+}
+
+impl WithdrawalRequestV1 {
     pub const LEN: usize = 32 + 8 + 32 + 32 + 8 + 1 + 8 + 1; // 122
 }
 
+/// Read-only view of the settlement program's `StateRootAccount`.
+/// Field order must match programs/settlement; `status` is the enum's u8 tag.
+#[derive(BorshDeserialize, Debug)]
+pub struct SettlementStateRoot {
+    pub l2_slot: u64,
+    pub state_root: [u8; 32],
+    pub transaction_count: u32,
+    pub transaction_batch_hash: [u8; 32],
+    pub ai_attestation_count: u16,
+    pub previous_state_root: [u8; 32],
+    pub sequencer: Pubkey,
+    pub posted_at: i64,
+    pub challenge_deadline: i64,
+    pub status: u8,
+    pub bump: u8,
+}
+
+/// An account the target program receives when a message is delivered.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct MessageAccount {
+    pub pubkey: Pubkey,
+    pub is_writable: bool,
+}
+
+/// Cross-domain message. Identical layout in bridge-l2; `message_hash` is
+/// sha256 of its Borsh encoding.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CrossDomainMessage {
+    pub nonce: u64,
+    /// Signer of SendMessage on the origin chain (a wallet or a program PDA).
+    pub sender: Pubkey,
+    pub target_program: Pubkey,
+    pub accounts: Vec<MessageAccount>,
+    pub payload: Vec<u8>,
+}
+
+/// Replay guard for delivered L2→L1 messages. PDA seeds: ["message", nonce].
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ProcessedMessage {
+    pub nonce: u64,
+    pub message_hash: [u8; 32],
+    pub processed_at: i64,
+    pub bump: u8,
+}
+
+impl ProcessedMessage {
+    pub const LEN: usize = 8 + 32 + 8 + 1; // 49
+}
+
+/// Fast-withdrawal offer and, once filled, the LP that owns the withdrawal.
+/// While `lp` is the default pubkey the offer is open and finalization pays
+/// the original recipient.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct FastWithdrawal {
+    pub withdrawal_nonce: u64,
+    pub recipient: Pubkey,
+    /// Amount the LP keeps; the recipient is paid `amount - fee` up front.
+    pub fee: u64,
+    pub lp: Pubkey,
+    pub filled_at: i64,
+    pub bump: u8,
+}
+
+impl FastWithdrawal {
+    pub const LEN: usize = 8 + 32 + 8 + 32 + 8 + 1; // 89
+
+    pub fn is_filled(&self) -> bool {
+        self.lp != Pubkey::default()
+    }
+}
+
 /// Pending timelocked update for bridge config or limits.
 /// Challenge bond escrow account.
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
@@ -237,8 +382,12 @@ pub struct InitiateWithdrawalParams {
     pub recipient: Pubkey,
     pub amount: u64,
     pub token_mint: Pubkey,
-    pub merkle_proof: [u8; 32],
     pub nonce: u64,
+    /// L2 slot of the settlement state root that commits to this withdrawal.
+    pub l2_slot: u64,
+    pub leaf_index: u64,
+    /// Sibling hashes from the withdrawal leaf up to the state root.
+    pub merkle_proof: Vec<[u8; 32]>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -250,6 +399,13 @@ pub struct ChallengeWithdrawalParams {
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct FinalizeWithdrawalParams {
     pub withdrawal_nonce: u64,
+    pub leaf_index: u64,
+    pub merkle_proof: Vec<[u8; 32]>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ResolveChallengeParams {
+    pub withdrawal_nonce: u64,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -265,7 +421,50 @@ pub struct SetLimitsParams {
     pub daily_limit_lamports: u64,
 }
 
-// ResolveChallengeParams, ProposeUpdateParams, CloseWithdrawalParams removed to reduce binary size
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SendMessageParams {
+    pub target_program: Pubkey,
+    pub accounts: Vec<MessageAccount>,
+    pub payload: Vec<u8>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReceiveMessageParams {
+    pub message: CrossDomainMessage,
+    /// L2 slot of the settlement state root that commits to this message.
+    pub l2_slot: u64,
+    pub leaf_index: u64,
+    pub merkle_proof: Vec<[u8; 32]>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RequestFastWithdrawalParams {
+    pub withdrawal_nonce: u64,
+    pub fee: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct FillFastWithdrawalParams {
+    pub withdrawal_nonce: u64,
+    /// Must equal the offer's fee so a reprice cannot front-run the fill.
+    pub fee: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct CloseFastWithdrawalParams {
+    pub withdrawal_nonce: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct MigrateWithdrawalParams {
+    pub withdrawal_nonce: u64,
+    /// L2 slot of the settlement state root that commits to the withdrawal.
+    pub l2_slot: u64,
+    pub leaf_index: u64,
+    pub merkle_proof: Vec<[u8; 32]>,
+}
+
+// ProposeUpdateParams, CloseWithdrawalParams removed to reduce binary size
 
 // ── Entrypoint ───────────────────────────────────────────────────────────────
 
@@ -296,6 +495,13 @@ pub fn process_instruction(
         IX_FINALIZE_SOL_WITHDRAWAL => process_finalize_sol_withdrawal(program_id, accounts, data),
         IX_CREATE_VAULT => process_create_vault(program_id, accounts),
         IX_SEQUENCER_WITHDRAW_SOL => process_sequencer_withdraw_sol(program_id, accounts, data),
+        IX_RESOLVE_CHALLENGE => process_resolve_challenge(program_id, accounts, data),
+        IX_SEND_MESSAGE => process_send_message(program_id, accounts, data),
+        IX_RECEIVE_MESSAGE => process_receive_message(program_id, accounts, data),
+        IX_REQUEST_FAST_WITHDRAWAL => process_request_fast_withdrawal(program_id, accounts, data),
+        IX_FILL_FAST_WITHDRAWAL => process_fill_fast_withdrawal(program_id, accounts, data),
+        IX_CLOSE_FAST_WITHDRAWAL => process_close_fast_withdrawal(program_id, accounts, data),
+        IX_MIGRATE_WITHDRAWAL => process_migrate_withdrawal(program_id, accounts, data),
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
@@ -589,13 +795,16 @@ fn process_deposit_sol(
 }
 
 // ── Initiate Withdrawal ──────────────────────────────────────────────────────
+// The payer funds the request's rent plus RELAYER_WITHDRAWAL_BOND, which a
+// challenger collects if the withdrawal turns out to be fraudulent.
+//
 // Accounts:
 //   0. [signer] sequencer
-//   1. [signer, writable] payer
+//   1. [signer, writable] payer (funds rent + relayer bond)
 //   2. [writable] withdrawal_request PDA
 //   3. [writable] bridge_config PDA (writable for C-05 accounting)
 //   4. [] system_program
-//   5. [] clock sysvar (optional — we use Sysvar::get)
+//   5. [] settlement state_root PDA for params.l2_slot
 
 fn process_initiate_withdrawal(
     program_id: &Pubkey,
@@ -608,6 +817,7 @@ fn process_initiate_withdrawal(
     let withdrawal_account = next_account_info(accounts_iter)?;
     let config_account = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
+    let state_root_account = next_account_info(accounts_iter)?;
 
     if !sequencer.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
@@ -637,6 +847,18 @@ fn process_initiate_withdrawal(
         return Err(BridgeError::InvalidSequencer.into());
     }
 
+    // The withdrawal must be committed to by a posted L2 state root. The root
+    // may still be inside its challenge window here; finalization re-checks
+    // the proof once settlement has finalized it.
+    let leaf = withdrawal_leaf_hash(&params.recipient, params.amount, &params.token_mint, params.nonce);
+    let state_root = load_settlement_state_root(state_root_account, params.l2_slot)?;
+    if state_root.status == STATE_ROOT_STATUS_INVALIDATED {
+        return Err(BridgeError::InvalidStateRoot.into());
+    }
+    if !verify_merkle_proof(&leaf, &params.merkle_proof, params.leaf_index, &state_root.state_root) {
+        return Err(BridgeError::InvalidMerkleProof.into());
+    }
+
     // C-05: Ensure total withdrawals never exceed total deposits
     // Derive withdrawal PDA
     let nonce_bytes = params.nonce.to_le_bytes();
@@ -658,7 +880,10 @@ fn process_initiate_withdrawal(
 
     let rent = Rent::get()?;
     let space = WithdrawalRequest::LEN;
-    let lamports = rent.minimum_balance(space);
+    let lamports = rent
+        .minimum_balance(space)
+        .checked_add(RELAYER_WITHDRAWAL_BOND)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
 
     invoke_signed(
         &system_instruction::create_account(
@@ -680,7 +905,7 @@ fn process_initiate_withdrawal(
         recipient: params.recipient,
         amount: params.amount,
         token_mint: params.token_mint,
-        merkle_proof: params.merkle_proof,
+        l2_slot: params.l2_slot,
         challenge_deadline,
         status: WithdrawalStatus::Pending,
         nonce: params.nonce,
@@ -690,8 +915,8 @@ fn process_initiate_withdrawal(
     withdrawal.serialize(&mut &mut withdrawal_account.data.borrow_mut()[..])?;
 
     msg!(
-        "EVENT:InitiateWithdrawal:{{\"recipient\":\"{}\",\"amount\":{},\"token_mint\":\"{}\",\"nonce\":{},\"challenge_deadline\":{}}}",
-        params.recipient, params.amount, params.token_mint, params.nonce, challenge_deadline
+        "EVENT:InitiateWithdrawal:{{\"recipient\":\"{}\",\"amount\":{},\"token_mint\":\"{}\",\"nonce\":{},\"l2_slot\":{},\"challenge_deadline\":{}}}",
+        params.recipient, params.amount, params.token_mint, params.nonce, params.l2_slot, challenge_deadline
     );
 
     Ok(())
@@ -699,9 +924,9 @@ fn process_initiate_withdrawal(
 
 // ── Challenge Withdrawal ─────────────────────────────────────────────────────
 // C-01: Challenger must escrow a bond (10% of withdrawal amount, min 0.1 SOL).
-// The bond is held in a challenge_bond PDA. If the challenge is upheld (admin
-// resolves in favor of cancellation), the bond is returned. If the challenge
-// is rejected, the bond is slashed to the SOL vault.
+// The bond is held in a challenge_bond PDA until ResolveChallenge. If the
+// challenge is upheld the bond is returned with the relayer bond as a reward;
+// if it is rejected the bond is paid to the withdrawal recipient.
 //
 // Accounts:
 //   0. [signer, writable] challenger (posts bond)
@@ -838,6 +1063,162 @@ fn process_challenge_withdrawal(
     Ok(())
 }
 
+// ── Resolve Challenge ────────────────────────────────────────────────────────
+// Settles a Challenged withdrawal and closes its challenge_bond PDA.
+//
+// The verdict follows the settlement state root the withdrawal was proven
+// against: Invalidated upholds the challenge, Finalized rejects it, and anyone
+// may crank either case. While the root is still Posted or Challenged the
+// outcome is unknown and resolution fails with StateRootNotFinalized.
+//
+//   Upheld:   withdrawal -> Cancelled; challenger gets the whole bond PDA
+//             balance back plus the relayer bond escrowed in the withdrawal
+//             request. Depositor funds in the vaults are never used.
+//   Rejected: withdrawal -> Pending; bond_amount goes to whoever the delayed
+//             withdrawal pays (the LP if it was filled fast, else the
+//             recipient) and the bond PDA rent goes back to the challenger.
+//
+// Accounts:
+//   0. [signer] resolver (anyone)
+//   1. [writable] withdrawal_request PDA
+//   2. [] bridge_config PDA
+//   3. [writable] challenge_bond PDA
+//   4. [writable] challenger
+//   5. [writable] withdrawal payee (the LP if filled fast, else the recipient)
+//   6. [] fast_withdrawal PDA (may be uninitialized)
+//   7. [] settlement state_root PDA for withdrawal.l2_slot
+
+fn process_resolve_challenge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let resolver = next_account_info(accounts_iter)?;
+    let withdrawal_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let bond_account = next_account_info(accounts_iter)?;
+    let challenger = next_account_info(accounts_iter)?;
+    let payee_account = next_account_info(accounts_iter)?;
+    let fast_withdrawal_account = next_account_info(accounts_iter)?;
+    let state_root_account = next_account_info(accounts_iter)?;
+
+    if !resolver.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !withdrawal_account.is_writable
+        || !bond_account.is_writable
+        || !challenger.is_writable
+        || !payee_account.is_writable
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = ResolveChallengeParams::try_from_slice(data)?;
+
+    // Validate config
+    let (config_pda, _) = Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config = BridgeConfig::try_from_slice(&config_account.data.borrow())?;
+    if !config.is_initialized {
+        return Err(BridgeError::UninitializedAccount.into());
+    }
+
+    // Validate withdrawal PDA
+    let nonce_bytes = params.withdrawal_nonce.to_le_bytes();
+    let (withdrawal_pda, _) =
+        Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce_bytes], program_id);
+    if withdrawal_pda != *withdrawal_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if withdrawal_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut withdrawal =
+        WithdrawalRequest::try_from_slice(&withdrawal_account.data.borrow())?;
+    if withdrawal.status != WithdrawalStatus::Challenged {
+        return Err(ProgramError::Custom(ERROR_NOT_CHALLENGED));
+    }
+    let payee = withdrawal_payee(program_id, fast_withdrawal_account, &withdrawal)?;
+    if *payee_account.key != payee {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Validate challenge_bond PDA
+    let (bond_pda, _) =
+        Pubkey::find_program_address(&[CHALLENGE_BOND_SEED, &nonce_bytes], program_id);
+    if bond_pda != *bond_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if bond_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let bond = ChallengeBond::try_from_slice(&bond_account.data.borrow())?;
+    if bond.withdrawal_nonce != params.withdrawal_nonce {
+        return Err(BridgeError::InvalidNonce.into());
+    }
+    if *challenger.key != bond.challenger {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let state_root = load_settlement_state_root(state_root_account, withdrawal.l2_slot)?;
+    let uphold = match state_root.status {
+        STATE_ROOT_STATUS_INVALIDATED => true,
+        STATE_ROOT_STATUS_FINALIZED => false,
+        _ => return Err(BridgeError::StateRootNotFinalized.into()),
+    };
+
+    let bond_lamports = bond_account.lamports();
+    let mut reward = 0;
+
+    if uphold {
+        withdrawal.status = WithdrawalStatus::Cancelled;
+
+        // Refund the whole bond PDA (bond + rent) to the challenger
+        **bond_account.try_borrow_mut_lamports()? = 0;
+        **challenger.try_borrow_mut_lamports()? = challenger
+            .lamports()
+            .checked_add(bond_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        // and slash the relayer bond behind the fraudulent withdrawal to them
+        reward = release_relayer_bond(withdrawal_account, challenger)?;
+    } else {
+        withdrawal.status = WithdrawalStatus::Pending;
+
+        // Slash the bond to whoever bore the delay; rent goes back to the challenger
+        let rent_lamports = bond_lamports.saturating_sub(bond.bond_amount);
+        let slashed = bond_lamports - rent_lamports;
+        **bond_account.try_borrow_mut_lamports()? = 0;
+        **payee_account.try_borrow_mut_lamports()? = payee_account
+            .lamports()
+            .checked_add(slashed)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **challenger.try_borrow_mut_lamports()? = challenger
+            .lamports()
+            .checked_add(rent_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+    }
+
+    // Close the bond PDA so the slot can be re-challenged if rejected
+    bond_account.data.borrow_mut().fill(0);
+
+    withdrawal.serialize(&mut &mut withdrawal_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "EVENT:ResolveChallenge:{{\"nonce\":{},\"challenger\":\"{}\",\"upheld\":{},\"bond_amount\":{},\"reward\":{},\"payee\":\"{}\",\"resolver\":\"{}\"}}",
+        params.withdrawal_nonce, bond.challenger, uphold, bond.bond_amount, reward, payee, resolver.key
+    );
+
+    Ok(())
+}
+
 // ── Finalize Withdrawal ──────────────────────────────────────────────────────
 // Accounts:
 //   0. [signer, writable] payer / anyone can finalize
@@ -846,8 +1227,19 @@ fn process_challenge_withdrawal(
 //   3. [writable] recipient token account
 //   4. [] token mint
 //   5. [] bridge_config PDA
-//   6. [] vault_authority PDA (= vault PDA itself, signer via invoke_signed)
-//   7. [] token_program
+//   6. [] token_program
+//   7. [] settlement state_root PDA for withdrawal.l2_slot (must be Finalized)
+//   8. [] fast_withdrawal PDA (may be uninitialized)
+//   9. [writable] sequencer (config.sequencer, gets the relayer bond back)
+//
+// The vault PDA is its own token authority and signs via invoke_signed.
+// If an LP filled the withdrawal, the LP is paid instead of the recipient.
+//
+// ABI: accounts 7 to 9 and the proof fields of FinalizeWithdrawalParams
+// are required. Callers built for the 7-account layout fail with
+// NotEnoughAccountKeys. Account 8 cannot be optional: omitting it would let
+// the recipient be paid after an LP already fronted the withdrawal.
+// Close the offer afterwards with CloseFastWithdrawal.
 
 fn process_finalize_withdrawal(
     program_id: &Pubkey,
@@ -862,11 +1254,17 @@ fn process_finalize_withdrawal(
     let token_mint = next_account_info(accounts_iter)?;
     let config_account = next_account_info(accounts_iter)?;
     let token_program = next_account_info(accounts_iter)?;
+    let state_root_account = next_account_info(accounts_iter)?;
+    let fast_withdrawal_account = next_account_info(accounts_iter)?;
+    let sequencer_account = next_account_info(accounts_iter)?;
 
     if !payer.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
-    if !withdrawal_account.is_writable || !vault_token.is_writable || !recipient_token.is_writable
+    if !withdrawal_account.is_writable
+        || !vault_token.is_writable
+        || !recipient_token.is_writable
+        || !sequencer_account.is_writable
     {
         return Err(ProgramError::InvalidAccountData);
     }
@@ -888,6 +1286,9 @@ fn process_finalize_withdrawal(
     if config.paused {
         return Err(ProgramError::Custom(ERROR_BRIDGE_PAUSED));
     }
+    if *sequencer_account.key != config.sequencer {
+        return Err(BridgeError::InvalidSequencer.into());
+    }
 
     // Validate token_program is either legacy SPL Token or Token-2022
     if !is_valid_token_program(token_program.key) {
@@ -922,7 +1323,9 @@ fn process_finalize_withdrawal(
         return Err(ProgramError::InvalidAccountData);
     }
 
-    // Validate recipient_token account belongs to the withdrawal recipient.
+    let payee = withdrawal_payee(program_id, fast_withdrawal_account, &withdrawal)?;
+
+    // Validate recipient_token account belongs to the payee.
     // SPL Token account layout: bytes 32..64 = owner pubkey.
     {
         let recipient_data = recipient_token.data.borrow();
@@ -931,9 +1334,9 @@ fn process_finalize_withdrawal(
         }
         let token_account_owner =
             Pubkey::try_from(&recipient_data[32..64]).map_err(|_| ProgramError::InvalidAccountData)?;
-        if token_account_owner != withdrawal.recipient {
-            msg!("ERROR: recipient token account owner {} does not match withdrawal recipient {}",
-                token_account_owner, withdrawal.recipient);
+        if token_account_owner != payee {
+            msg!("ERROR: recipient token account owner {} does not match withdrawal payee {}",
+                token_account_owner, payee);
             return Err(ProgramError::InvalidAccountData);
         }
     }
@@ -943,6 +1346,8 @@ fn process_finalize_withdrawal(
         return Err(BridgeError::ChallengePeriodActive.into());
     }
 
+    verify_finalized_withdrawal(state_root_account, &withdrawal, &params)?;
+
     // Validate vault PDA and transfer tokens to recipient
     let (vault_pda, vault_bump) =
         Pubkey::find_program_address(&[VAULT_SEED, token_mint.key.as_ref()], program_id);
@@ -968,13 +1373,14 @@ fn process_finalize_withdrawal(
         ],
         &[&[VAULT_SEED, token_mint.key.as_ref(), &[vault_bump]]],
     )?;
+    release_relayer_bond(withdrawal_account, sequencer_account)?;
 
     withdrawal.status = WithdrawalStatus::Finalized;
     withdrawal.serialize(&mut &mut withdrawal_account.data.borrow_mut()[..])?;
 
     msg!(
-        "EVENT:FinalizeWithdrawal:{{\"recipient\":\"{}\",\"amount\":{},\"token_mint\":\"{}\",\"nonce\":{}}}",
-        withdrawal.recipient, withdrawal.amount, withdrawal.token_mint, withdrawal.nonce
+        "EVENT:FinalizeWithdrawal:{{\"recipient\":\"{}\",\"payee\":\"{}\",\"amount\":{},\"token_mint\":\"{}\",\"nonce\":{}}}",
+        withdrawal.recipient, payee, withdrawal.amount, withdrawal.token_mint, withdrawal.nonce
     );
 
     Ok(())
@@ -987,9 +1393,15 @@ fn process_finalize_withdrawal(
 //   0. [signer, writable] payer / anyone can finalize
 //   1. [writable] withdrawal_request PDA
 //   2. [writable] sol_vault PDA
-//   3. [writable] recipient (receives native SOL)
+//   3. [writable] recipient (receives native SOL; the LP if filled fast)
 //   4. [] bridge_config PDA
 //   5. [] system_program
+//   6. [] settlement state_root PDA for withdrawal.l2_slot (must be Finalized)
+//   7. [] fast_withdrawal PDA (may be uninitialized)
+//   8. [writable] sequencer (config.sequencer, gets the relayer bond back)
+//
+// ABI: accounts 6 to 8 are required for the same reasons as in
+// FinalizeWithdrawal.
 
 fn process_finalize_sol_withdrawal(
     program_id: &Pubkey,
@@ -1003,11 +1415,18 @@ fn process_finalize_sol_withdrawal(
     let recipient = next_account_info(accounts_iter)?;
     let config_account = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
+    let state_root_account = next_account_info(accounts_iter)?;
+    let fast_withdrawal_account = next_account_info(accounts_iter)?;
+    let sequencer_account = next_account_info(accounts_iter)?;
 
     if !payer.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
-    if !withdrawal_account.is_writable || !sol_vault.is_writable || !recipient.is_writable {
+    if !withdrawal_account.is_writable
+        || !sol_vault.is_writable
+        || !recipient.is_writable
+        || !sequencer_account.is_writable
+    {
         return Err(ProgramError::InvalidAccountData);
     }
 
@@ -1028,6 +1447,9 @@ fn process_finalize_sol_withdrawal(
     if config.paused {
         return Err(ProgramError::Custom(ERROR_BRIDGE_PAUSED));
     }
+    if *sequencer_account.key != config.sequencer {
+        return Err(BridgeError::InvalidSequencer.into());
+    }
 
     // Validate withdrawal PDA
     let nonce_bytes = params.withdrawal_nonce.to_le_bytes();
@@ -1050,8 +1472,9 @@ fn process_finalize_sol_withdrawal(
         return Err(BridgeError::InsufficientFunds.into());
     }
 
-    // Verify recipient matches the withdrawal request
-    if *recipient.key != withdrawal.recipient {
+    // Verify recipient is whoever currently owns the withdrawal
+    let payee = withdrawal_payee(program_id, fast_withdrawal_account, &withdrawal)?;
+    if *recipient.key != payee {
         return Err(ProgramError::InvalidAccountData);
     }
 
@@ -1060,6 +1483,8 @@ fn process_finalize_sol_withdrawal(
         return Err(BridgeError::ChallengePeriodActive.into());
     }
 
+    verify_finalized_withdrawal(state_root_account, &withdrawal, &params)?;
+
     // Validate SOL vault PDA
     let (vault_pda, vault_bump) = Pubkey::find_program_address(&[SOL_VAULT_SEED], program_id);
     if vault_pda != *sol_vault.key {
@@ -1077,13 +1502,14 @@ fn process_finalize_sol_withdrawal(
         &[sol_vault.clone(), recipient.clone(), system_program.clone()],
         &[&[SOL_VAULT_SEED, &[vault_bump]]],
     )?;
+    release_relayer_bond(withdrawal_account, sequencer_account)?;
 
     withdrawal.status = WithdrawalStatus::Finalized;
     withdrawal.serialize(&mut &mut withdrawal_account.data.borrow_mut()[..])?;
 
     msg!(
-        "EVENT:FinalizeSOLWithdrawal:{{\"recipient\":\"{}\",\"amount\":{},\"nonce\":{}}}",
-        withdrawal.recipient, withdrawal.amount, withdrawal.nonce
+        "EVENT:FinalizeSOLWithdrawal:{{\"recipient\":\"{}\",\"payee\":\"{}\",\"amount\":{},\"nonce\":{}}}",
+        withdrawal.recipient, payee, withdrawal.amount, withdrawal.nonce
     );
 
     Ok(())
@@ -1478,8 +1904,744 @@ fn process_sequencer_withdraw_sol(
     Ok(())
 }
 
+// ── Send Message ─────────────────────────────────────────────────────────────
+// Emits an L1→L2 message for the relayer to deliver via bridge-l2
+// ReceiveMessage. Messages take the next deposit_nonce, so deposits and
+// messages share one ordered nonce space.
+// Accounts:
+//   0. [signer] sender (wallet, or a program PDA signing via invoke_signed)
+//   1. [writable] bridge_config PDA
+
+fn process_send_message(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let sender = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    if !sender.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !config_account.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = SendMessageParams::try_from_slice(data)?;
+    if params.payload.len() > MAX_MESSAGE_PAYLOAD || params.accounts.len() > MAX_MESSAGE_ACCOUNTS {
+        return Err(BridgeError::MessageTooLarge.into());
+    }
+
+    let (config_pda, _) = Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let mut config = BridgeConfig::try_from_slice(&config_account.data.borrow())?;
+    if !config.is_initialized {
+        return Err(BridgeError::UninitializedAccount.into());
+    }
+    if config.paused {
+        return Err(ProgramError::Custom(ERROR_BRIDGE_PAUSED));
+    }
+
+    let nonce = config.deposit_nonce;
+    config.deposit_nonce = nonce.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
+    let message = CrossDomainMessage {
+        nonce,
+        sender: *sender.key,
+        target_program: params.target_program,
+        accounts: params.accounts,
+        payload: params.payload,
+    };
+
+    msg!(
+        "EVENT:SendMessage:{{\"sender\":\"{}\",\"target_program\":\"{}\",\"accounts\":{},\"payload\":\"{}\",\"nonce\":{},\"message_hash\":\"{}\"}}",
+        message.sender, message.target_program, message_accounts_json(&message.accounts),
+        hex_encode(&message.payload), nonce, hex_encode(&message_hash(&message))
+    );
+
+    Ok(())
+}
+
+// ── Receive Message ──────────────────────────────────────────────────────────
+// Delivers an L2→L1 message once the settlement state root committing to it
+// is finalized. Permissionless: the Merkle proof is the authorization. The
+// target program is invoked with the message payload as instruction data and
+// the message_origin PDA for `message.sender` as its first account and signer,
+// so targets can check which L2 sender the message came from.
+// Accounts:
+//   0. [signer, writable] payer (for processed_message PDA rent)
+//   1. [] bridge_config PDA
+//   2. [writable] processed_message PDA (seeds: ["message", nonce])
+//   3. [] settlement state_root PDA for params.l2_slot (must be Finalized)
+//   4. [] message_origin PDA (seeds: ["message_origin", message.sender])
+//   5. [] target program
+//   6. [] system_program
+//   7.. message.accounts, in order
+
+fn process_receive_message(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let payer = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let processed_account = next_account_info(accounts_iter)?;
+    let state_root_account = next_account_info(accounts_iter)?;
+    let origin_account = next_account_info(accounts_iter)?;
+    let target_program = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let message_accounts = accounts_iter.as_slice();
+
+    if !payer.is_signer || !payer.is_writable {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !processed_account.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = ReceiveMessageParams::try_from_slice(data)?;
+    let message = params.message;
+
+    let (config_pda, _) = Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config = BridgeConfig::try_from_slice(&config_account.data.borrow())?;
+    if !config.is_initialized {
+        return Err(BridgeError::UninitializedAccount.into());
+    }
+    if config.paused {
+        return Err(ProgramError::Custom(ERROR_BRIDGE_PAUSED));
+    }
+
+    // The bridge must never invoke itself with a signer it derives
+    if message.target_program == *program_id || *target_program.key != message.target_program {
+        return Err(BridgeError::InvalidMessageTarget.into());
+    }
+
+    let hash = message_hash(&message);
+    let state_root = load_settlement_state_root(state_root_account, params.l2_slot)?;
+    if state_root.status != STATE_ROOT_STATUS_FINALIZED {
+        return Err(BridgeError::StateRootNotFinalized.into());
+    }
+    if !verify_merkle_proof(
+        &message_leaf_hash(&hash),
+        &params.merkle_proof,
+        params.leaf_index,
+        &state_root.state_root,
+    ) {
+        return Err(BridgeError::InvalidMerkleProof.into());
+    }
+
+    let nonce_bytes = message.nonce.to_le_bytes();
+    let (processed_pda, processed_bump) =
+        Pubkey::find_program_address(&[MESSAGE_SEED, &nonce_bytes], program_id);
+    if processed_pda != *processed_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !processed_account.data_is_empty() {
+        return Err(BridgeError::MessageAlreadyProcessed.into());
+    }
+
+    let (origin_pda, origin_bump) = Pubkey::find_program_address(
+        &[MESSAGE_ORIGIN_SEED, message.sender.as_ref()],
+        program_id,
+    );
+    if origin_pda != *origin_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if message_accounts.len() != message.accounts.len()
+        || message_accounts
+            .iter()
+            .zip(&message.accounts)
+            .any(|(info, meta)| *info.key != meta.pubkey)
+    {
+        return Err(BridgeError::MessageAccountMismatch.into());
+    }
+
+    // Record delivery before the CPI so the message can never run twice
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            processed_account.key,
+            rent.minimum_balance(ProcessedMessage::LEN),
+            ProcessedMessage::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), processed_account.clone(), system_program.clone()],
+        &[&[MESSAGE_SEED, &nonce_bytes, &[processed_bump]]],
+    )?;
+    let processed = ProcessedMessage {
+        nonce: message.nonce,
+        message_hash: hash,
+        processed_at: Clock::get()?.unix_timestamp,
+        bump: processed_bump,
+    };
+    processed.serialize(&mut &mut processed_account.data.borrow_mut()[..])?;
+
+    let mut metas = Vec::with_capacity(message.accounts.len() + 1);
+    metas.push(AccountMeta::new_readonly(origin_pda, true));
+    metas.extend(message.accounts.iter().map(|a| AccountMeta {
+        pubkey: a.pubkey,
+        is_signer: false,
+        is_writable: a.is_writable,
+    }));
+    let mut infos = Vec::with_capacity(message_accounts.len() + 2);
+    infos.push(origin_account.clone());
+    infos.extend(message_accounts.iter().cloned());
+    infos.push(target_program.clone());
+
+    invoke_signed(
+        &Instruction {
+            program_id: message.target_program,
+            accounts: metas,
+            data: message.payload,
+        },
+        &infos,
+        &[&[MESSAGE_ORIGIN_SEED, message.sender.as_ref(), &[origin_bump]]],
+    )?;
+
+    msg!(
+        "EVENT:ReceiveMessage:{{\"sender\":\"{}\",\"target_program\":\"{}\",\"nonce\":{},\"message_hash\":\"{}\"}}",
+        message.sender, message.target_program, message.nonce, hex_encode(&hash)
+    );
+
+    Ok(())
+}
+
+// ── Request Fast Withdrawal ──────────────────────────────────────────────────
+// The recipient of a Pending withdrawal offers it to LPs: whoever fills pays
+// `amount - fee` now and is paid the full amount at finalization. Calling
+// again before a fill reprices the offer.
+//
+// Accounts:
+//   0. [signer, writable] recipient (pays rent for the offer)
+//   1. [] withdrawal_request PDA
+//   2. [writable] fast_withdrawal PDA
+//   3. [] system_program
+
+fn process_request_fast_withdrawal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let recipient = next_account_info(accounts_iter)?;
+    let withdrawal_account = next_account_info(accounts_iter)?;
+    let fast_withdrawal_account = next_account_info(accounts_iter)?;
+    let system_program_info = next_account_info(accounts_iter)?;
+
+    if !recipient.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !recipient.is_writable || !fast_withdrawal_account.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = RequestFastWithdrawalParams::try_from_slice(data)?;
+    let withdrawal = load_withdrawal(program_id, withdrawal_account, params.withdrawal_nonce)?;
+    if withdrawal.status != WithdrawalStatus::Pending {
+        return Err(BridgeError::WithdrawalAlreadyFinalized.into());
+    }
+    if *recipient.key != withdrawal.recipient {
+        return Err(BridgeError::InvalidAuthority.into());
+    }
+    if params.fee >= withdrawal.amount {
+        return Err(BridgeError::InvalidFastWithdrawalFee.into());
+    }
+
+    let nonce_bytes = params.withdrawal_nonce.to_le_bytes();
+    let (fast_pda, fast_bump) =
+        Pubkey::find_program_address(&[FAST_WITHDRAWAL_SEED, &nonce_bytes], program_id);
+    if fast_pda != *fast_withdrawal_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let offer = if fast_withdrawal_account.data_is_empty() {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                recipient.key,
+                fast_withdrawal_account.key,
+                rent.minimum_balance(FastWithdrawal::LEN),
+                FastWithdrawal::LEN as u64,
+                program_id,
+            ),
+            &[recipient.clone(), fast_withdrawal_account.clone(), system_program_info.clone()],
+            &[&[FAST_WITHDRAWAL_SEED, &nonce_bytes, &[fast_bump]]],
+        )?;
+        FastWithdrawal {
+            withdrawal_nonce: params.withdrawal_nonce,
+            recipient: *recipient.key,
+            fee: params.fee,
+            lp: Pubkey::default(),
+            filled_at: 0,
+            bump: fast_bump,
+        }
+    } else {
+        if fast_withdrawal_account.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let mut offer = FastWithdrawal::try_from_slice(&fast_withdrawal_account.data.borrow())?;
+        if offer.is_filled() {
+            return Err(BridgeError::FastWithdrawalFilled.into());
+        }
+        offer.fee = params.fee;
+        offer
+    };
+    offer.serialize(&mut &mut fast_withdrawal_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "EVENT:RequestFastWithdrawal:{{\"nonce\":{},\"recipient\":\"{}\",\"amount\":{},\"token_mint\":\"{}\",\"fee\":{},\"challenge_deadline\":{}}}",
+        params.withdrawal_nonce, recipient.key, withdrawal.amount, withdrawal.token_mint,
+        params.fee, withdrawal.challenge_deadline
+    );
+
+    Ok(())
+}
+
+// ── Fill Fast Withdrawal ─────────────────────────────────────────────────────
+// An LP pays the recipient `amount - fee` from its own funds and takes over
+// the withdrawal: FinalizeWithdrawal / FinalizeSOLWithdrawal then pay the LP.
+// If the withdrawal is challenged and cancelled the LP is never repaid.
+//
+// Accounts:
+//   0. [signer, writable] lp
+//   1. [] withdrawal_request PDA
+//   2. [writable] fast_withdrawal PDA
+//   3. [writable] recipient token account, or the recipient for native SOL
+//   4. [] token_program, or system_program for native SOL
+//   5. [writable] lp token account (SPL withdrawals only)
+
+fn process_fill_fast_withdrawal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let lp = next_account_info(accounts_iter)?;
+    let withdrawal_account = next_account_info(accounts_iter)?;
+    let fast_withdrawal_account = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    let transfer_program = next_account_info(accounts_iter)?;
+
+    if !lp.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !lp.is_writable || !fast_withdrawal_account.is_writable || !destination.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = FillFastWithdrawalParams::try_from_slice(data)?;
+    let withdrawal = load_withdrawal(program_id, withdrawal_account, params.withdrawal_nonce)?;
+    if withdrawal.status != WithdrawalStatus::Pending {
+        return Err(BridgeError::WithdrawalAlreadyFinalized.into());
+    }
+
+    let nonce_bytes = params.withdrawal_nonce.to_le_bytes();
+    let (fast_pda, _) =
+        Pubkey::find_program_address(&[FAST_WITHDRAWAL_SEED, &nonce_bytes], program_id);
+    if fast_pda != *fast_withdrawal_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if fast_withdrawal_account.owner != program_id {
+        return Err(BridgeError::UninitializedAccount.into());
+    }
+    let mut offer = FastWithdrawal::try_from_slice(&fast_withdrawal_account.data.borrow())?;
+    if offer.is_filled() {
+        return Err(BridgeError::FastWithdrawalFilled.into());
+    }
+    if offer.fee != params.fee {
+        return Err(BridgeError::InvalidFastWithdrawalFee.into());
+    }
+    let payout = withdrawal
+        .amount
+        .checked_sub(offer.fee)
+        .ok_or(BridgeError::InvalidFastWithdrawalFee)?;
+
+    if withdrawal.token_mint == NATIVE_SOL_MINT {
+        if *destination.key != withdrawal.recipient {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        invoke(
+            &system_instruction::transfer(lp.key, destination.key, payout),
+            &[lp.clone(), destination.clone(), transfer_program.clone()],
+        )?;
+    } else {
+        let lp_token = next_account_info(accounts_iter)?;
+        if !is_valid_token_program(transfer_program.key) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        // SPL Token account layout: bytes 0..32 = mint, 32..64 = owner.
+        {
+            let dest_data = destination.data.borrow();
+            if dest_data.len() < 64
+                || dest_data[0..32] != withdrawal.token_mint.to_bytes()
+                || dest_data[32..64] != withdrawal.recipient.to_bytes()
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+        invoke(
+            &build_token_transfer_ix(
+                transfer_program.key,
+                lp_token.key,
+                destination.key,
+                lp.key,
+                payout,
+            ),
+            &[lp_token.clone(), destination.clone(), lp.clone(), transfer_program.clone()],
+        )?;
+    }
+
+    offer.lp = *lp.key;
+    offer.filled_at = Clock::get()?.unix_timestamp;
+    offer.serialize(&mut &mut fast_withdrawal_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "EVENT:FillFastWithdrawal:{{\"nonce\":{},\"lp\":\"{}\",\"recipient\":\"{}\",\"payout\":{},\"fee\":{}}}",
+        params.withdrawal_nonce, lp.key, withdrawal.recipient, payout, offer.fee
+    );
+
+    Ok(())
+}
+
+// ── Close Fast Withdrawal ────────────────────────────────────────────────────
+// Permissionless: once the withdrawal is Finalized or Cancelled the offer has
+// no further use, so its rent goes back to the recipient who paid for it.
+// The finalization crank appends this after FinalizeWithdrawal.
+//
+// Accounts:
+//   0. [] withdrawal_request PDA
+//   1. [writable] fast_withdrawal PDA
+//   2. [writable] offer recipient (receives the rent)
+
+fn process_close_fast_withdrawal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let withdrawal_account = next_account_info(accounts_iter)?;
+    let fast_withdrawal_account = next_account_info(accounts_iter)?;
+    let recipient = next_account_info(accounts_iter)?;
+
+    if !fast_withdrawal_account.is_writable || !recipient.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = CloseFastWithdrawalParams::try_from_slice(data)?;
+    let withdrawal = load_withdrawal(program_id, withdrawal_account, params.withdrawal_nonce)?;
+    if !matches!(withdrawal.status, WithdrawalStatus::Finalized | WithdrawalStatus::Cancelled) {
+        return Err(BridgeError::WithdrawalNotSettled.into());
+    }
+
+    let (fast_pda, _) = Pubkey::find_program_address(
+        &[FAST_WITHDRAWAL_SEED, &params.withdrawal_nonce.to_le_bytes()],
+        program_id,
+    );
+    if fast_pda != *fast_withdrawal_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if fast_withdrawal_account.owner != program_id || fast_withdrawal_account.data_is_empty() {
+        return Err(BridgeError::UninitializedAccount.into());
+    }
+    let offer = FastWithdrawal::try_from_slice(&fast_withdrawal_account.data.borrow())?;
+    if *recipient.key != offer.recipient {
+        return Err(BridgeError::InvalidAuthority.into());
+    }
+
+    let rent = fast_withdrawal_account.lamports();
+    **fast_withdrawal_account.try_borrow_mut_lamports()? = 0;
+    **recipient.try_borrow_mut_lamports()? = recipient
+        .lamports()
+        .checked_add(rent)
+        .ok_or(ProgramError::Custom(ERROR_OVERFLOW))?;
+    fast_withdrawal_account.data.borrow_mut().fill(0);
+
+    msg!(
+        "EVENT:CloseFastWithdrawal:{{\"nonce\":{},\"recipient\":\"{}\",\"lp\":\"{}\",\"rent\":{}}}",
+        params.withdrawal_nonce, offer.recipient, offer.lp, rent
+    );
+
+    Ok(())
+}
+
+// ── Migrate Withdrawal ───────────────────────────────────────────────────────
+// Rewrites a v1 WithdrawalRequest in the current layout so it can be
+// challenged and finalized again. v1 requests never named a state root, so
+// the sequencer proves the withdrawal against a posted root as
+// InitiateWithdrawal does; the account shrinks to WithdrawalRequest::LEN and
+// the freed rent goes to the sequencer, who paid it.
+//
+// Accounts:
+//   0. [signer, writable] sequencer
+//   1. [writable] withdrawal_request PDA (v1)
+//   2. [] bridge_config PDA
+//   3. [] settlement state_root PDA for params.l2_slot
+
+fn process_migrate_withdrawal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let sequencer = next_account_info(accounts_iter)?;
+    let withdrawal_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let state_root_account = next_account_info(accounts_iter)?;
+
+    if !sequencer.is_signer || !sequencer.is_writable {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !withdrawal_account.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let params = MigrateWithdrawalParams::try_from_slice(data)?;
+
+    let (config_pda, _) = Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], program_id);
+    if config_pda != *config_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config = BridgeConfig::try_from_slice(&config_account.data.borrow())?;
+    if !config.is_initialized {
+        return Err(BridgeError::UninitializedAccount.into());
+    }
+    if *sequencer.key != config.sequencer {
+        return Err(BridgeError::InvalidSequencer.into());
+    }
+
+    let (withdrawal_pda, _) = Pubkey::find_program_address(
+        &[WITHDRAWAL_SEED, &params.withdrawal_nonce.to_le_bytes()],
+        program_id,
+    );
+    if withdrawal_pda != *withdrawal_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if withdrawal_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if withdrawal_account.data_len() != WithdrawalRequestV1::LEN {
+        return Err(BridgeError::AlreadyInitialized.into());
+    }
+    let legacy = WithdrawalRequestV1::try_from_slice(&withdrawal_account.data.borrow())?;
+
+    let leaf = withdrawal_leaf_hash(&legacy.recipient, legacy.amount, &legacy.token_mint, legacy.nonce);
+    let state_root = load_settlement_state_root(state_root_account, params.l2_slot)?;
+    if state_root.status == STATE_ROOT_STATUS_INVALIDATED {
+        return Err(BridgeError::InvalidStateRoot.into());
+    }
+    if !verify_merkle_proof(&leaf, &params.merkle_proof, params.leaf_index, &state_root.state_root) {
+        return Err(BridgeError::InvalidMerkleProof.into());
+    }
+
+    let withdrawal = WithdrawalRequest {
+        recipient: legacy.recipient,
+        amount: legacy.amount,
+        token_mint: legacy.token_mint,
+        l2_slot: params.l2_slot,
+        challenge_deadline: legacy.challenge_deadline,
+        status: legacy.status,
+        nonce: legacy.nonce,
+        bump: legacy.bump,
+    };
+    withdrawal_account.realloc(WithdrawalRequest::LEN, false)?;
+    withdrawal.serialize(&mut &mut withdrawal_account.data.borrow_mut()[..])?;
+
+    let excess = withdrawal_account
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(WithdrawalRequest::LEN));
+    **withdrawal_account.try_borrow_mut_lamports()? -= excess;
+    **sequencer.try_borrow_mut_lamports()? = sequencer
+        .lamports()
+        .checked_add(excess)
+        .ok_or(ProgramError::Custom(ERROR_OVERFLOW))?;
+
+    msg!(
+        "EVENT:MigrateWithdrawal:{{\"nonce\":{},\"l2_slot\":{},\"rent_returned\":{}}}",
+        params.withdrawal_nonce, params.l2_slot, excess
+    );
+
+    Ok(())
+}
+
 // ── Helpers ──────────────────────────────────────────────────────────────────
 
+/// Leaf committed to by the L2 state root for a bridged withdrawal:
+/// sha256(0x00 || recipient || amount || token_mint || nonce).
+pub fn withdrawal_leaf_hash(recipient: &Pubkey, amount: u64, token_mint: &Pubkey, nonce: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([MERKLE_LEAF_PREFIX]);
+    hasher.update(recipient.as_ref());
+    hasher.update(amount.to_le_bytes());
+    hasher.update(token_mint.as_ref());
+    hasher.update(nonce.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// sha256 of the Borsh-encoded message; the same value bridge-l2 uses.
+pub fn message_hash(message: &CrossDomainMessage) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(borsh::to_vec(message).unwrap_or_default());
+    hasher.finalize().into()
+}
+
+/// Leaf committed to by the L2 state root for an L2→L1 message:
+/// sha256(0x02 || message_hash).
+pub fn message_leaf_hash(message_hash: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([MESSAGE_LEAF_PREFIX]);
+    hasher.update(message_hash);
+    hasher.finalize().into()
+}
+
+/// Walks `proof` from `leaf` to the root. Bit i of `leaf_index` selects whether
+/// the running hash is the right (1) or left (0) child at depth i.
+pub fn verify_merkle_proof(leaf: &[u8; 32], proof: &[[u8; 32]], leaf_index: u64, root: &[u8; 32]) -> bool {
+    if proof.len() > MAX_MERKLE_PROOF_DEPTH {
+        return false;
+    }
+    // Reject indices that do not fit the tree height so one leaf has exactly one valid index
+    if (leaf_index >> proof.len()) != 0 {
+        return false;
+    }
+
+    let mut computed = *leaf;
+    for (depth, sibling) in proof.iter().enumerate() {
+        let mut hasher = Sha256::new();
+        hasher.update([MERKLE_NODE_PREFIX]);
+        if (leaf_index >> depth) & 1 == 0 {
+            hasher.update(computed);
+            hasher.update(sibling);
+        } else {
+            hasher.update(sibling);
+            hasher.update(computed);
+        }
+        computed = hasher.finalize().into();
+    }
+    computed == *root
+}
+
+/// Reads a settlement `StateRootAccount` for `l2_slot`, checking owner and PDA.
+/// Trailing bytes are ignored so fields appended by settlement upgrades do not
+/// break the bridge.
+fn load_settlement_state_root(
+    state_root_account: &AccountInfo,
+    l2_slot: u64,
+) -> Result<SettlementStateRoot, ProgramError> {
+    if *state_root_account.owner != SETTLEMENT_PROGRAM_ID {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let (state_root_pda, _) = Pubkey::find_program_address(
+        &[STATE_ROOT_SEED, &l2_slot.to_le_bytes()],
+        &SETTLEMENT_PROGRAM_ID,
+    );
+    if state_root_pda != *state_root_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let state_root = SettlementStateRoot::deserialize(&mut &state_root_account.data.borrow()[..])
+        .map_err(|_| BridgeError::InvalidStateRoot)?;
+    if state_root.l2_slot != l2_slot {
+        return Err(BridgeError::InvalidStateRoot.into());
+    }
+    Ok(state_root)
+}
+
+/// Payout gate shared by token and SOL finalization: the withdrawal must be
+/// provably included in a state root that settlement has finalized.
+fn verify_finalized_withdrawal(
+    state_root_account: &AccountInfo,
+    withdrawal: &WithdrawalRequest,
+    params: &FinalizeWithdrawalParams,
+) -> ProgramResult {
+    let state_root = load_settlement_state_root(state_root_account, withdrawal.l2_slot)?;
+    if state_root.status != STATE_ROOT_STATUS_FINALIZED {
+        return Err(BridgeError::StateRootNotFinalized.into());
+    }
+    let leaf = withdrawal_leaf_hash(
+        &withdrawal.recipient,
+        withdrawal.amount,
+        &withdrawal.token_mint,
+        withdrawal.nonce,
+    );
+    if !verify_merkle_proof(&leaf, &params.merkle_proof, params.leaf_index, &state_root.state_root) {
+        return Err(BridgeError::InvalidMerkleProof.into());
+    }
+    Ok(())
+}
+
+/// Loads the WithdrawalRequest for `nonce`, checking its PDA and owner.
+fn load_withdrawal(
+    program_id: &Pubkey,
+    withdrawal_account: &AccountInfo,
+    nonce: u64,
+) -> Result<WithdrawalRequest, ProgramError> {
+    let (withdrawal_pda, _) =
+        Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce.to_le_bytes()], program_id);
+    if withdrawal_pda != *withdrawal_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if withdrawal_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(WithdrawalRequest::try_from_slice(&withdrawal_account.data.borrow())?)
+}
+
+/// Moves the relayer bond, i.e. whatever a WithdrawalRequest holds above its
+/// rent minimum, to `destination`. Requests migrated from v1 carry none.
+fn release_relayer_bond(
+    withdrawal_account: &AccountInfo,
+    destination: &AccountInfo,
+) -> Result<u64, ProgramError> {
+    let rent = Rent::get()?.minimum_balance(withdrawal_account.data_len());
+    let bond = withdrawal_account.lamports().saturating_sub(rent);
+    **withdrawal_account.try_borrow_mut_lamports()? -= bond;
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(bond)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(bond)
+}
+
+/// Who finalization pays: the LP if the withdrawal was filled fast, otherwise
+/// the original recipient. The fast_withdrawal PDA is always passed so the
+/// LP cannot be skipped.
+fn withdrawal_payee(
+    program_id: &Pubkey,
+    fast_withdrawal_account: &AccountInfo,
+    withdrawal: &WithdrawalRequest,
+) -> Result<Pubkey, ProgramError> {
+    let (fast_pda, _) = Pubkey::find_program_address(
+        &[FAST_WITHDRAWAL_SEED, &withdrawal.nonce.to_le_bytes()],
+        program_id,
+    );
+    if fast_pda != *fast_withdrawal_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if fast_withdrawal_account.owner != program_id || fast_withdrawal_account.data_is_empty() {
+        return Ok(withdrawal.recipient);
+    }
+    let offer = FastWithdrawal::try_from_slice(&fast_withdrawal_account.data.borrow())?;
+    Ok(if offer.is_filled() { offer.lp } else { withdrawal.recipient })
+}
+
 fn hex_encode(bytes: &[u8]) -> String {
     let mut s = String::with_capacity(bytes.len() * 2);
     for b in bytes {
@@ -1487,3 +2649,11 @@ fn hex_encode(bytes: &[u8]) -> String {
     }
     s
 }
+
+fn message_accounts_json(accounts: &[MessageAccount]) -> String {
+    let entries: Vec<String> = accounts
+        .iter()
+        .map(|a| format!("{{\"pubkey\":\"{}\",\"is_writable\":{}}}", a.pubkey, a.is_writable))
+        .collect();
+    format!("[{}]", entries.join(","))
+}
@@ -1,7 +1,7 @@
 // Comprehensive test suite for Mythic Bridge L1 program
 // Tests: happy paths + attack/edge cases using solana-program-test
 
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::BorshDeserialize;
 use solana_program::pubkey::Pubkey;
 use solana_program::system_program;
 use solana_program_test::*;
@@ -20,10 +20,21 @@ use mythic_bridge::*;
 const BRIDGE_CONFIG_SEED: &[u8] = b"bridge_config";
 const SOL_VAULT_SEED: &[u8] = b"sol_vault";
 const WITHDRAWAL_SEED: &[u8] = b"withdrawal";
+const STATE_ROOT_SEED: &[u8] = b"state_root";
+const CHALLENGE_BOND_SEED: &[u8] = b"challenge_bond";
+const FAST_WITHDRAWAL_SEED: &[u8] = b"fast_withdrawal";
 const IX_INITIALIZE: u8 = 0;
 const IX_DEPOSIT_SOL: u8 = 2;
 const IX_INITIATE_WITHDRAWAL: u8 = 3;
 const IX_UPDATE_CONFIG: u8 = 6;
+const IX_FINALIZE_SOL_WITHDRAWAL: u8 = 10;
+const IX_RESOLVE_CHALLENGE: u8 = 13;
+const IX_REQUEST_FAST_WITHDRAWAL: u8 = 16;
+const IX_FILL_FAST_WITHDRAWAL: u8 = 17;
+const IX_CLOSE_FAST_WITHDRAWAL: u8 = 18;
+const IX_MIGRATE_WITHDRAWAL: u8 = 19;
+/// RELAYER_WITHDRAWAL_BOND in the program
+const RELAYER_WITHDRAWAL_BOND: u64 = 100_000_000;
 
 fn program_id() -> Pubkey {
     "MythBrdg11111111111111111111111111111111111"
@@ -31,6 +42,109 @@ fn program_id() -> Pubkey {
         .unwrap()
 }
 
+fn settlement_program_id() -> Pubkey {
+    "4TrowzShv4CrsuqZeUdLLVMdnDDkqkmnER1MZ5NsSaav"
+        .parse()
+        .unwrap()
+}
+
+/// Borsh layout of settlement's StateRootAccount (status: 0=Posted, 2=Finalized).
+fn state_root_account_data(l2_slot: u64, state_root: [u8; 32], status: u8) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&l2_slot.to_le_bytes());
+    data.extend_from_slice(&state_root);
+    data.extend_from_slice(&0u32.to_le_bytes()); // transaction_count
+    data.extend_from_slice(&[0u8; 32]); // transaction_batch_hash
+    data.extend_from_slice(&0u16.to_le_bytes()); // ai_attestation_count
+    data.extend_from_slice(&[0u8; 32]); // previous_state_root
+    data.extend_from_slice(&[0u8; 32]); // sequencer
+    data.extend_from_slice(&0i64.to_le_bytes()); // posted_at
+    data.extend_from_slice(&0i64.to_le_bytes()); // challenge_deadline
+    data.push(status);
+    data.push(255); // bump
+    data
+}
+
+/// Adds a settlement state root at `l2_slot` whose root is the single withdrawal leaf.
+fn add_state_root_for_withdrawal(
+    pt: &mut ProgramTest,
+    l2_slot: u64,
+    recipient: &Pubkey,
+    amount: u64,
+    token_mint: &Pubkey,
+    nonce: u64,
+    status: u8,
+) {
+    let (state_root_pda, _) = Pubkey::find_program_address(
+        &[STATE_ROOT_SEED, &l2_slot.to_le_bytes()],
+        &settlement_program_id(),
+    );
+    let leaf = withdrawal_leaf_hash(recipient, amount, token_mint, nonce);
+    pt.add_account(
+        state_root_pda,
+        Account {
+            lamports: 10_000_000,
+            data: state_root_account_data(l2_slot, leaf, status),
+            owner: settlement_program_id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+/// Adds withdrawal nonce 0 in the Challenged state with its challenge_bond PDA.
+fn add_challenged_withdrawal(
+    pt: &mut ProgramTest,
+    pid: &Pubkey,
+    recipient: &Pubkey,
+    token_mint: &Pubkey,
+    challenger: &Pubkey,
+    bond_amount: u64,
+    bond_rent: u64,
+) {
+    let nonce_bytes = 0u64.to_le_bytes();
+    let (withdrawal_pda, withdrawal_bump) =
+        Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce_bytes], pid);
+    let withdrawal = WithdrawalRequest {
+        recipient: *recipient,
+        amount: 1_000_000,
+        token_mint: *token_mint,
+        l2_slot: 100,
+        challenge_deadline: i64::MAX,
+        status: WithdrawalStatus::Challenged,
+        nonce: 0,
+        bump: withdrawal_bump,
+    };
+    pt.add_account(
+        withdrawal_pda,
+        Account {
+            lamports: 10_000_000,
+            data: borsh::to_vec(&withdrawal).unwrap(),
+            owner: *pid,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let (bond_pda, bond_bump) =
+        Pubkey::find_program_address(&[CHALLENGE_BOND_SEED, &nonce_bytes], pid);
+    let bond = ChallengeBond {
+        challenger: *challenger,
+        withdrawal_nonce: 0,
+        bond_amount,
+        bump: bond_bump,
+    };
+    pt.add_account(
+        bond_pda,
+        Account {
+            lamports: bond_amount + bond_rent,
+            data: borsh::to_vec(&bond).unwrap(),
+            owner: *pid,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
 // ── Instruction Builders ─────────────────────────────────────────────────────
 
 fn build_initialize_ix(
@@ -85,6 +199,7 @@ fn build_deposit_sol_ix(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_initiate_withdrawal_ix(
     pid: &Pubkey,
     sequencer: &Pubkey,
@@ -92,20 +207,27 @@ fn build_initiate_withdrawal_ix(
     recipient: &Pubkey,
     amount: u64,
     token_mint: &Pubkey,
-    merkle_proof: [u8; 32],
+    merkle_proof: Vec<[u8; 32]>,
     nonce: u64,
+    l2_slot: u64,
 ) -> Instruction {
     let (config_pda, _) = Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], pid);
     let nonce_bytes = nonce.to_le_bytes();
     let (withdrawal_pda, _) =
         Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce_bytes], pid);
+    let (state_root_pda, _) = Pubkey::find_program_address(
+        &[STATE_ROOT_SEED, &l2_slot.to_le_bytes()],
+        &settlement_program_id(),
+    );
 
     let params = InitiateWithdrawalParams {
         recipient: *recipient,
         amount,
         token_mint: *token_mint,
-        merkle_proof,
         nonce,
+        l2_slot,
+        leaf_index: 0,
+        merkle_proof,
     };
     let mut data = vec![IX_INITIATE_WITHDRAWAL];
     data.extend_from_slice(&borsh::to_vec(&params).unwrap());
@@ -116,8 +238,73 @@ fn build_initiate_withdrawal_ix(
             AccountMeta::new_readonly(*sequencer, true),
             AccountMeta::new(*payer, true),
             AccountMeta::new(withdrawal_pda, false),
-            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(config_pda, false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(state_root_pda, false),
+        ],
+        data,
+    }
+}
+
+fn build_migrate_withdrawal_ix(pid: &Pubkey, sequencer: &Pubkey, nonce: u64, l2_slot: u64) -> Instruction {
+    let (config_pda, _) = Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], pid);
+    let (withdrawal_pda, _) =
+        Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce.to_le_bytes()], pid);
+    let (state_root_pda, _) = Pubkey::find_program_address(
+        &[STATE_ROOT_SEED, &l2_slot.to_le_bytes()],
+        &settlement_program_id(),
+    );
+
+    let params = MigrateWithdrawalParams {
+        withdrawal_nonce: nonce,
+        l2_slot,
+        leaf_index: 0,
+        merkle_proof: vec![],
+    };
+    let mut data = vec![IX_MIGRATE_WITHDRAWAL];
+    data.extend_from_slice(&borsh::to_vec(&params).unwrap());
+
+    Instruction {
+        program_id: *pid,
+        accounts: vec![
+            AccountMeta::new(*sequencer, true),
+            AccountMeta::new(withdrawal_pda, false),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new_readonly(state_root_pda, false),
+        ],
+        data,
+    }
+}
+
+fn build_resolve_challenge_ix(
+    pid: &Pubkey,
+    resolver: &Pubkey,
+    challenger: &Pubkey,
+    payee: &Pubkey,
+) -> Instruction {
+    let nonce_bytes = 0u64.to_le_bytes();
+    let (config_pda, _) = Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], pid);
+    let (withdrawal_pda, _) = Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce_bytes], pid);
+    let (bond_pda, _) = Pubkey::find_program_address(&[CHALLENGE_BOND_SEED, &nonce_bytes], pid);
+    let (fast_pda, _) = Pubkey::find_program_address(&[FAST_WITHDRAWAL_SEED, &nonce_bytes], pid);
+    let (state_root_pda, _) = Pubkey::find_program_address(
+        &[STATE_ROOT_SEED, &100u64.to_le_bytes()],
+        &settlement_program_id(),
+    );
+    let params = ResolveChallengeParams { withdrawal_nonce: 0 };
+    let mut data = vec![IX_RESOLVE_CHALLENGE];
+    data.extend_from_slice(&borsh::to_vec(&params).unwrap());
+    Instruction {
+        program_id: *pid,
+        accounts: vec![
+            AccountMeta::new_readonly(*resolver, true),
+            AccountMeta::new(withdrawal_pda, false),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(bond_pda, false),
+            AccountMeta::new(*challenger, false),
+            AccountMeta::new(*payee, false),
+            AccountMeta::new_readonly(fast_pda, false),
+            AccountMeta::new_readonly(state_root_pda, false),
         ],
         data,
     }
@@ -154,7 +341,7 @@ async fn test_initialize_bridge() {
     let pid = program_id();
     let mut pt = ProgramTest::new("mythic_bridge", pid, processor!(process_instruction));
     pt.set_compute_max_units(200_000);
-    let (mut banks, payer, bh) = pt.start().await;
+    let (banks, payer, bh) = pt.start().await;
 
     let sequencer = Keypair::new();
     let ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
@@ -190,7 +377,7 @@ async fn test_lock_sol_success() {
         },
     );
 
-    let (mut banks, payer, bh) = pt.start().await;
+    let (banks, payer, bh) = pt.start().await;
 
     let sequencer = Keypair::new();
     let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
@@ -228,15 +415,17 @@ async fn test_initiate_withdrawal_success() {
         },
     );
 
-    let (mut banks, payer, bh) = pt.start().await;
+    let recipient = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    add_state_root_for_withdrawal(&mut pt, 100, &recipient, 5_000_000, &token_mint, 0, 0);
+
+    let (banks, payer, bh) = pt.start().await;
 
     let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
     let init_tx =
         Transaction::new_signed_with_payer(&[init_ix], Some(&payer.pubkey()), &[&payer], bh);
     banks.process_transaction(init_tx).await.unwrap();
 
-    let recipient = Pubkey::new_unique();
-    let token_mint = Pubkey::new_unique();
     let ix = build_initiate_withdrawal_ix(
         &pid,
         &sequencer.pubkey(),
@@ -244,8 +433,9 @@ async fn test_initiate_withdrawal_success() {
         &recipient,
         5_000_000,
         &token_mint,
-        [0xCC; 32],
+        vec![],
         0,
+        100,
     );
     let bh2 = banks.get_latest_blockhash().await.unwrap();
     let tx = Transaction::new_signed_with_payer(
@@ -265,6 +455,154 @@ async fn test_initiate_withdrawal_success() {
     assert_eq!(w.amount, 5_000_000);
     assert_eq!(w.status, WithdrawalStatus::Pending);
     assert_eq!(w.nonce, 0);
+    assert_eq!(w.l2_slot, 100);
+
+    // The payer escrows the relayer bond on top of rent
+    let rent = solana_sdk::rent::Rent::default().minimum_balance(WithdrawalRequest::LEN);
+    assert_eq!(acct.lamports, rent + RELAYER_WITHDRAWAL_BOND);
+}
+
+#[tokio::test]
+async fn test_migrate_v1_withdrawal_proves_it_against_a_posted_root() {
+    let pid = program_id();
+    let mut pt = ProgramTest::new("mythic_bridge", pid, processor!(process_instruction));
+    pt.set_compute_max_units(200_000);
+
+    let sequencer = Keypair::new();
+    pt.add_account(
+        sequencer.pubkey(),
+        Account {
+            lamports: 10_000_000_000,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Withdrawal opened before the upgrade, still in the v1 layout
+    let recipient = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    let (withdrawal_pda, withdrawal_bump) =
+        Pubkey::find_program_address(&[WITHDRAWAL_SEED, &0u64.to_le_bytes()], &pid);
+    let legacy = WithdrawalRequestV1 {
+        recipient,
+        amount: 5_000_000,
+        token_mint,
+        merkle_proof: [7; 32],
+        challenge_deadline: 1_700_000_000,
+        status: WithdrawalStatus::Pending,
+        nonce: 0,
+        bump: withdrawal_bump,
+    };
+    let legacy_data = borsh::to_vec(&legacy).unwrap();
+    assert_eq!(legacy_data.len(), WithdrawalRequestV1::LEN);
+    assert!(WithdrawalRequest::try_from_slice(&legacy_data).is_err());
+    pt.add_account(
+        withdrawal_pda,
+        Account {
+            lamports: 10_000_000,
+            data: legacy_data,
+            owner: pid,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    add_state_root_for_withdrawal(&mut pt, 100, &recipient, 5_000_000, &token_mint, 0, 2);
+
+    let (banks, payer, bh) = pt.start().await;
+
+    let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
+    let init_tx =
+        Transaction::new_signed_with_payer(&[init_ix], Some(&payer.pubkey()), &[&payer], bh);
+    banks.process_transaction(init_tx).await.unwrap();
+
+    // Only the sequencer may migrate
+    let outsider = Keypair::new();
+    let ix = build_migrate_withdrawal_ix(&pid, &outsider.pubkey(), 0, 100);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer, &outsider], bh);
+    assert!(banks.process_transaction(tx).await.is_err());
+
+    let ix = build_migrate_withdrawal_ix(&pid, &sequencer.pubkey(), 0, 100);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&sequencer.pubkey()), &[&sequencer], bh);
+    banks.process_transaction(tx).await.unwrap();
+
+    let acct = banks.get_account(withdrawal_pda).await.unwrap().unwrap();
+    assert_eq!(acct.data.len(), WithdrawalRequest::LEN);
+    let w = WithdrawalRequest::try_from_slice(&acct.data).unwrap();
+    assert_eq!(w.recipient, recipient);
+    assert_eq!(w.amount, 5_000_000);
+    assert_eq!(w.token_mint, token_mint);
+    assert_eq!(w.l2_slot, 100);
+    assert_eq!(w.challenge_deadline, 1_700_000_000);
+    assert_eq!(w.status, WithdrawalStatus::Pending);
+    assert_eq!(w.bump, withdrawal_bump);
+
+    // Migrating twice MUST FAIL. A different fee payer keeps it from being
+    // deduplicated as the same signature.
+    let ix = build_migrate_withdrawal_ix(&pid, &sequencer.pubkey(), 0, 100);
+    let bh2 = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer, &sequencer], bh2);
+    assert!(banks.process_transaction(tx).await.is_err());
+}
+
+#[tokio::test]
+async fn test_migrate_withdrawal_rejects_a_leaf_the_root_does_not_commit_to() {
+    let pid = program_id();
+    let mut pt = ProgramTest::new("mythic_bridge", pid, processor!(process_instruction));
+    pt.set_compute_max_units(200_000);
+
+    let sequencer = Keypair::new();
+    pt.add_account(
+        sequencer.pubkey(),
+        Account {
+            lamports: 10_000_000_000,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let recipient = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    let (withdrawal_pda, withdrawal_bump) =
+        Pubkey::find_program_address(&[WITHDRAWAL_SEED, &0u64.to_le_bytes()], &pid);
+    let legacy = WithdrawalRequestV1 {
+        recipient,
+        amount: 5_000_000,
+        token_mint,
+        merkle_proof: [7; 32],
+        challenge_deadline: 1_700_000_000,
+        status: WithdrawalStatus::Pending,
+        nonce: 0,
+        bump: withdrawal_bump,
+    };
+    pt.add_account(
+        withdrawal_pda,
+        Account {
+            lamports: 10_000_000,
+            data: borsh::to_vec(&legacy).unwrap(),
+            owner: pid,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    // The root commits to a different amount
+    add_state_root_for_withdrawal(&mut pt, 100, &recipient, 6_000_000, &token_mint, 0, 2);
+
+    let (banks, payer, bh) = pt.start().await;
+
+    let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
+    let init_tx =
+        Transaction::new_signed_with_payer(&[init_ix], Some(&payer.pubkey()), &[&payer], bh);
+    banks.process_transaction(init_tx).await.unwrap();
+
+    let ix = build_migrate_withdrawal_ix(&pid, &sequencer.pubkey(), 0, 100);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&sequencer.pubkey()), &[&sequencer], bh);
+    assert!(banks.process_transaction(tx).await.is_err());
+    let acct = banks.get_account(withdrawal_pda).await.unwrap().unwrap();
+    assert_eq!(acct.data.len(), WithdrawalRequestV1::LEN);
 }
 
 // ── Attack / Edge Case Tests ─────────────────────────────────────────────────
@@ -287,7 +625,7 @@ async fn test_lock_zero_amount() {
         },
     );
 
-    let (mut banks, payer, bh) = pt.start().await;
+    let (banks, payer, bh) = pt.start().await;
 
     let sequencer = Keypair::new();
     let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
@@ -322,6 +660,12 @@ async fn test_double_initialize() {
         deposit_nonce: 0,
         is_initialized: true,
         bump,
+        paused: false,
+        min_deposit_lamports: 0,
+        max_deposit_lamports: u64::MAX,
+        daily_limit_lamports: u64::MAX,
+        daily_volume: 0,
+        last_reset_slot: 0,
     };
     let config_data = borsh::to_vec(&config).unwrap();
     pt.add_account(
@@ -335,7 +679,7 @@ async fn test_double_initialize() {
         },
     );
 
-    let (mut banks, payer, bh) = pt.start().await;
+    let (banks, payer, bh) = pt.start().await;
 
     // Try to initialize when config already exists — MUST FAIL (AlreadyInitialized)
     let sequencer = Keypair::new();
@@ -366,7 +710,7 @@ async fn test_withdrawal_wrong_sequencer() {
         },
     );
 
-    let (mut banks, payer, bh) = pt.start().await;
+    let (banks, payer, bh) = pt.start().await;
 
     let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
     let init_tx =
@@ -381,8 +725,9 @@ async fn test_withdrawal_wrong_sequencer() {
         &Pubkey::new_unique(),
         1_000_000,
         &Pubkey::new_unique(),
-        [0xCC; 32],
+        vec![],
         0,
+        100,
     );
     let bh2 = banks.get_latest_blockhash().await.unwrap();
     let tx = Transaction::new_signed_with_payer(
@@ -415,7 +760,11 @@ async fn test_replay_withdrawal_nonce() {
         },
     );
 
-    let (mut banks, payer, bh) = pt.start().await;
+    let recipient = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    add_state_root_for_withdrawal(&mut pt, 100, &recipient, 1_000_000, &token_mint, 0, 0);
+
+    let (banks, payer, bh) = pt.start().await;
 
     let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
     let init_tx =
@@ -427,11 +776,12 @@ async fn test_replay_withdrawal_nonce() {
         &pid,
         &sequencer.pubkey(),
         &sequencer.pubkey(),
-        &Pubkey::new_unique(),
+        &recipient,
         1_000_000,
-        &Pubkey::new_unique(),
-        [0xCC; 32],
+        &token_mint,
+        vec![],
         0,
+        100,
     );
     let bh2 = banks.get_latest_blockhash().await.unwrap();
     let tx1 = Transaction::new_signed_with_payer(
@@ -442,22 +792,25 @@ async fn test_replay_withdrawal_nonce() {
     );
     banks.process_transaction(tx1).await.unwrap();
 
-    // Replay same nonce MUST FAIL
+    // Replay same nonce MUST FAIL. A different fee payer keeps the replay
+    // from being deduplicated as the same signature when the blockhash
+    // has not advanced.
     let ix2 = build_initiate_withdrawal_ix(
         &pid,
         &sequencer.pubkey(),
-        &sequencer.pubkey(),
-        &Pubkey::new_unique(),
+        &payer.pubkey(),
+        &recipient,
         1_000_000,
-        &Pubkey::new_unique(),
-        [0xCC; 32],
+        &token_mint,
+        vec![],
         0,
+        100,
     );
     let bh3 = banks.get_latest_blockhash().await.unwrap();
     let tx2 = Transaction::new_signed_with_payer(
         &[ix2],
-        Some(&sequencer.pubkey()),
-        &[&sequencer],
+        Some(&payer.pubkey()),
+        &[&payer, &sequencer],
         bh3,
     );
     assert!(
@@ -466,6 +819,477 @@ async fn test_replay_withdrawal_nonce() {
     );
 }
 
+#[tokio::test]
+async fn test_initiate_withdrawal_invalid_merkle_proof() {
+    let pid = program_id();
+    let mut pt = ProgramTest::new("mythic_bridge", pid, processor!(process_instruction));
+    pt.set_compute_max_units(200_000);
+
+    let sequencer = Keypair::new();
+    pt.add_account(
+        sequencer.pubkey(),
+        Account {
+            lamports: 10_000_000_000,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // State root commits to a 1 MYTH withdrawal; the sequencer claims 2 MYTH
+    let recipient = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    add_state_root_for_withdrawal(&mut pt, 100, &recipient, 1_000_000, &token_mint, 0, 0);
+
+    let (banks, payer, bh) = pt.start().await;
+
+    let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
+    let init_tx =
+        Transaction::new_signed_with_payer(&[init_ix], Some(&payer.pubkey()), &[&payer], bh);
+    banks.process_transaction(init_tx).await.unwrap();
+
+    let ix = build_initiate_withdrawal_ix(
+        &pid,
+        &sequencer.pubkey(),
+        &sequencer.pubkey(),
+        &recipient,
+        2_000_000,
+        &token_mint,
+        vec![],
+        0,
+        100,
+    );
+    let bh2 = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&sequencer.pubkey()),
+        &[&sequencer],
+        bh2,
+    );
+    assert!(
+        banks.process_transaction(tx).await.is_err(),
+        "Withdrawal not committed to by the state root must be rejected"
+    );
+}
+
+#[tokio::test]
+async fn test_resolve_challenge_waits_for_the_root_to_be_decided() {
+    let pid = program_id();
+    let mut pt = ProgramTest::new("mythic_bridge", pid, processor!(process_instruction));
+    pt.set_compute_max_units(200_000);
+
+    // Challenged withdrawal proven against a root that is still Posted
+    let recipient = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    let challenger = Pubkey::new_unique();
+    add_state_root_for_withdrawal(&mut pt, 100, &recipient, 1_000_000, &token_mint, 0, 0);
+    add_challenged_withdrawal(&mut pt, &pid, &recipient, &token_mint, &challenger, 100_000_000, 10_000_000);
+
+    let (banks, payer, bh) = pt.start().await;
+
+    let sequencer = Keypair::new();
+    let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
+    let init_tx =
+        Transaction::new_signed_with_payer(&[init_ix], Some(&payer.pubkey()), &[&payer], bh);
+    banks.process_transaction(init_tx).await.unwrap();
+
+    let ix = build_resolve_challenge_ix(&pid, &payer.pubkey(), &challenger, &recipient);
+
+    // Resolving before settlement decides the root MUST FAIL, even for the admin
+    let bh2 = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], bh2);
+    assert!(
+        banks.process_transaction(tx).await.is_err(),
+        "The outcome must come from settlement, not the resolver"
+    );
+}
+
+#[tokio::test]
+async fn test_resolve_rejected_challenge_pays_bond_to_lp() {
+    let pid = program_id();
+    let mut pt = ProgramTest::new("mythic_bridge", pid, processor!(process_instruction));
+    pt.set_compute_max_units(200_000);
+
+    let cranker = Keypair::new();
+    let lp = Pubkey::new_unique();
+    for key in [cranker.pubkey(), lp] {
+        pt.add_account(
+            key,
+            Account {
+                lamports: 10_000_000_000,
+                data: vec![],
+                owner: system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    // Challenged withdrawal proven against a Finalized root, already filled by an LP
+    let recipient = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    let challenger = Pubkey::new_unique();
+    let bond_amount = 100_000_000u64;
+    let bond_rent = 10_000_000u64;
+    add_state_root_for_withdrawal(&mut pt, 100, &recipient, 1_000_000, &token_mint, 0, 2);
+    add_challenged_withdrawal(&mut pt, &pid, &recipient, &token_mint, &challenger, bond_amount, bond_rent);
+
+    let nonce_bytes = 0u64.to_le_bytes();
+    let (fast_pda, fast_bump) =
+        Pubkey::find_program_address(&[FAST_WITHDRAWAL_SEED, &nonce_bytes], &pid);
+    let offer = FastWithdrawal {
+        withdrawal_nonce: 0,
+        recipient,
+        fee: 5_000,
+        lp,
+        filled_at: 1,
+        bump: fast_bump,
+    };
+    pt.add_account(
+        fast_pda,
+        Account {
+            lamports: 10_000_000,
+            data: borsh::to_vec(&offer).unwrap(),
+            owner: pid,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, payer, bh) = pt.start().await;
+
+    let sequencer = Keypair::new();
+    let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
+    let init_tx =
+        Transaction::new_signed_with_payer(&[init_ix], Some(&payer.pubkey()), &[&payer], bh);
+    banks.process_transaction(init_tx).await.unwrap();
+
+    // Paying the original recipient instead of the LP MUST FAIL
+    let bh2 = banks.get_latest_blockhash().await.unwrap();
+    let wrong_ix = build_resolve_challenge_ix(&pid, &cranker.pubkey(), &challenger, &recipient);
+    let wrong_tx = Transaction::new_signed_with_payer(
+        &[wrong_ix],
+        Some(&cranker.pubkey()),
+        &[&cranker],
+        bh2,
+    );
+    assert!(
+        banks.process_transaction(wrong_tx).await.is_err(),
+        "Rejected bond must go to the LP that owns the withdrawal"
+    );
+
+    // Anyone may crank once the root is Finalized
+    let ix = build_resolve_challenge_ix(&pid, &cranker.pubkey(), &challenger, &lp);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&cranker.pubkey()), &[&cranker], bh2);
+    banks.process_transaction(tx).await.unwrap();
+
+    let lp_balance = banks.get_balance(lp).await.unwrap();
+    assert_eq!(lp_balance, 10_000_000_000 + bond_amount);
+    let challenger_balance = banks.get_balance(challenger).await.unwrap();
+    assert_eq!(challenger_balance, bond_rent);
+
+    let (withdrawal_pda, _) = Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce_bytes], &pid);
+    let account = banks.get_account(withdrawal_pda).await.unwrap().unwrap();
+    let withdrawal = WithdrawalRequest::try_from_slice(&account.data).unwrap();
+    assert_eq!(withdrawal.status, WithdrawalStatus::Pending);
+    // The relayer bond stays escrowed until finalization
+    assert_eq!(account.lamports, 10_000_000);
+}
+
+#[tokio::test]
+async fn test_resolve_upheld_challenge_pays_relayer_bond_without_vault_reward() {
+    let pid = program_id();
+    let mut pt = ProgramTest::new("mythic_bridge", pid, processor!(process_instruction));
+    pt.set_compute_max_units(200_000);
+
+    let cranker = Keypair::new();
+    pt.add_account(
+        cranker.pubkey(),
+        Account {
+            lamports: 10_000_000_000,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Challenged withdrawal proven against an Invalidated root
+    let recipient = Pubkey::new_unique();
+    let token_mint = Pubkey::new_unique();
+    let challenger = Pubkey::new_unique();
+    let bond_amount = 100_000_000u64;
+    let bond_rent = 10_000_000u64;
+    add_state_root_for_withdrawal(&mut pt, 100, &recipient, 1_000_000, &token_mint, 0, 3);
+    add_challenged_withdrawal(&mut pt, &pid, &recipient, &token_mint, &challenger, bond_amount, bond_rent);
+    let (sol_vault, _) = Pubkey::find_program_address(&[SOL_VAULT_SEED], &pid);
+    pt.add_account(
+        sol_vault,
+        Account {
+            lamports: 5_000_000_000,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, payer, bh) = pt.start().await;
+
+    let sequencer = Keypair::new();
+    let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
+    let init_tx =
+        Transaction::new_signed_with_payer(&[init_ix], Some(&payer.pubkey()), &[&payer], bh);
+    banks.process_transaction(init_tx).await.unwrap();
+
+    let ix = build_resolve_challenge_ix(&pid, &cranker.pubkey(), &challenger, &recipient);
+    let bh2 = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&cranker.pubkey()), &[&cranker], bh2);
+    banks.process_transaction(tx).await.unwrap();
+
+    // Challenger gets its bond PDA back plus everything the withdrawal request
+    // held above rent; depositor SOL is untouched
+    let withdrawal_rent = solana_sdk::rent::Rent::default().minimum_balance(WithdrawalRequest::LEN);
+    let relayer_bond = 10_000_000 - withdrawal_rent;
+    let challenger_balance = banks.get_balance(challenger).await.unwrap();
+    assert_eq!(challenger_balance, bond_amount + bond_rent + relayer_bond);
+    let vault_balance = banks.get_balance(sol_vault).await.unwrap();
+    assert_eq!(vault_balance, 5_000_000_000);
+
+    let nonce_bytes = 0u64.to_le_bytes();
+    let (withdrawal_pda, _) = Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce_bytes], &pid);
+    let account = banks.get_account(withdrawal_pda).await.unwrap().unwrap();
+    let withdrawal = WithdrawalRequest::try_from_slice(&account.data).unwrap();
+    assert_eq!(withdrawal.status, WithdrawalStatus::Cancelled);
+    assert_eq!(account.lamports, withdrawal_rent);
+}
+
+#[tokio::test]
+async fn test_fast_sol_withdrawal_pays_lp_at_finalization() {
+    let pid = program_id();
+    let mut pt = ProgramTest::new("mythic_bridge", pid, processor!(process_instruction));
+    pt.set_compute_max_units(200_000);
+
+    let recipient = Keypair::new();
+    let lp = Keypair::new();
+    for key in [recipient.pubkey(), lp.pubkey()] {
+        pt.add_account(
+            key,
+            Account {
+                lamports: 10_000_000_000,
+                data: vec![],
+                owner: system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    // Pending SOL withdrawal past its deadline, proven against a Finalized root
+    let amount = 1_000_000_000u64;
+    let fee = 5_000_000u64;
+    let sol_mint: Pubkey = "So11111111111111111111111111111111111111112".parse().unwrap();
+    add_state_root_for_withdrawal(&mut pt, 100, &recipient.pubkey(), amount, &sol_mint, 0, 2);
+
+    let nonce_bytes = 0u64.to_le_bytes();
+    let (withdrawal_pda, withdrawal_bump) =
+        Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce_bytes], &pid);
+    let withdrawal = WithdrawalRequest {
+        recipient: recipient.pubkey(),
+        amount,
+        token_mint: sol_mint,
+        l2_slot: 100,
+        challenge_deadline: 0,
+        status: WithdrawalStatus::Pending,
+        nonce: 0,
+        bump: withdrawal_bump,
+    };
+    pt.add_account(
+        withdrawal_pda,
+        Account {
+            lamports: 10_000_000,
+            data: borsh::to_vec(&withdrawal).unwrap(),
+            owner: pid,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let (sol_vault, _) = Pubkey::find_program_address(&[SOL_VAULT_SEED], &pid);
+    pt.add_account(
+        sol_vault,
+        Account {
+            lamports: 5_000_000_000,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (mut banks, payer, bh) = pt.start().await;
+
+    let sequencer = Keypair::new();
+    let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
+    let init_tx =
+        Transaction::new_signed_with_payer(&[init_ix], Some(&payer.pubkey()), &[&payer], bh);
+    banks.process_transaction(init_tx).await.unwrap();
+
+    let (fast_pda, _) = Pubkey::find_program_address(&[FAST_WITHDRAWAL_SEED, &nonce_bytes], &pid);
+    let mut data = vec![IX_REQUEST_FAST_WITHDRAWAL];
+    data.extend_from_slice(
+        &borsh::to_vec(&RequestFastWithdrawalParams { withdrawal_nonce: 0, fee }).unwrap(),
+    );
+    let request_ix = Instruction {
+        program_id: pid,
+        accounts: vec![
+            AccountMeta::new(recipient.pubkey(), true),
+            AccountMeta::new_readonly(withdrawal_pda, false),
+            AccountMeta::new(fast_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    };
+    let bh2 = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[request_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &recipient],
+        bh2,
+    );
+    banks.process_transaction(tx).await.unwrap();
+    let before = banks.get_balance(recipient.pubkey()).await.unwrap();
+
+    let mut data = vec![IX_FILL_FAST_WITHDRAWAL];
+    data.extend_from_slice(
+        &borsh::to_vec(&FillFastWithdrawalParams { withdrawal_nonce: 0, fee }).unwrap(),
+    );
+    let fill_ix = Instruction {
+        program_id: pid,
+        accounts: vec![
+            AccountMeta::new(lp.pubkey(), true),
+            AccountMeta::new_readonly(withdrawal_pda, false),
+            AccountMeta::new(fast_pda, false),
+            AccountMeta::new(recipient.pubkey(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    };
+    let tx = Transaction::new_signed_with_payer(&[fill_ix], Some(&payer.pubkey()), &[&payer, &lp], bh2);
+    banks.process_transaction(tx).await.unwrap();
+
+    assert_eq!(banks.get_balance(recipient.pubkey()).await.unwrap(), before + amount - fee);
+    let acct = banks.get_account(fast_pda).await.unwrap().unwrap();
+    let offer = FastWithdrawal::try_from_slice(&acct.data).unwrap();
+    assert_eq!(offer.lp, lp.pubkey());
+
+    let (config_pda, _) = Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], &pid);
+    let (state_root_pda, _) = Pubkey::find_program_address(
+        &[STATE_ROOT_SEED, &100u64.to_le_bytes()],
+        &settlement_program_id(),
+    );
+    let finalize_ix = |to: Pubkey| {
+        let params = FinalizeWithdrawalParams {
+            withdrawal_nonce: 0,
+            leaf_index: 0,
+            merkle_proof: vec![],
+        };
+        let mut data = vec![IX_FINALIZE_SOL_WITHDRAWAL];
+        data.extend_from_slice(&borsh::to_vec(&params).unwrap());
+        Instruction {
+            program_id: pid,
+            accounts: vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(withdrawal_pda, false),
+                AccountMeta::new(sol_vault, false),
+                AccountMeta::new(to, false),
+                AccountMeta::new_readonly(config_pda, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(state_root_pda, false),
+                AccountMeta::new_readonly(fast_pda, false),
+                AccountMeta::new(sequencer.pubkey(), false),
+            ],
+            data,
+        }
+    };
+
+    let close_ix = |to: Pubkey| {
+        let mut data = vec![IX_CLOSE_FAST_WITHDRAWAL];
+        data.extend_from_slice(
+            &borsh::to_vec(&CloseFastWithdrawalParams { withdrawal_nonce: 0 }).unwrap(),
+        );
+        Instruction {
+            program_id: pid,
+            accounts: vec![
+                AccountMeta::new_readonly(withdrawal_pda, false),
+                AccountMeta::new(fast_pda, false),
+                AccountMeta::new(to, false),
+            ],
+            data,
+        }
+    };
+
+    // Closing the offer before finalization MUST FAIL
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix(recipient.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer],
+        bh2,
+    );
+    assert!(banks.process_transaction(tx).await.is_err());
+
+    // Paying the original recipient after a fill MUST FAIL
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize_ix(recipient.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer],
+        bh2,
+    );
+    assert!(banks.process_transaction(tx).await.is_err());
+
+    let lp_before = banks.get_balance(lp.pubkey()).await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize_ix(lp.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer],
+        bh2,
+    );
+    banks.process_transaction(tx).await.unwrap();
+    assert_eq!(banks.get_balance(lp.pubkey()).await.unwrap(), lp_before + amount);
+
+    // The relayer bond goes back to the sequencer
+    let withdrawal_rent = solana_sdk::rent::Rent::default().minimum_balance(WithdrawalRequest::LEN);
+    assert_eq!(
+        banks.get_balance(sequencer.pubkey()).await.unwrap(),
+        10_000_000 - withdrawal_rent
+    );
+
+    // Once finalized the offer closes, but only to its recipient
+    let bh3 = banks.get_new_latest_blockhash(&bh2).await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix(lp.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer],
+        bh3,
+    );
+    assert!(banks.process_transaction(tx).await.is_err());
+
+    let rent = banks.get_balance(fast_pda).await.unwrap();
+    let recipient_before = banks.get_balance(recipient.pubkey()).await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix(recipient.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer],
+        bh3,
+    );
+    banks.process_transaction(tx).await.unwrap();
+    assert_eq!(
+        banks.get_balance(recipient.pubkey()).await.unwrap(),
+        recipient_before + rent
+    );
+    assert!(banks.get_account(fast_pda).await.unwrap().is_none());
+}
+
 #[tokio::test]
 async fn test_update_config_wrong_admin() {
     let pid = program_id();
@@ -484,7 +1308,7 @@ async fn test_update_config_wrong_admin() {
         },
     );
 
-    let (mut banks, payer, bh) = pt.start().await;
+    let (banks, payer, bh) = pt.start().await;
 
     let sequencer = Keypair::new();
     let init_ix = build_initialize_ix(&pid, &payer.pubkey(), &sequencer.pubkey(), 604_800);
@@ -512,7 +1336,7 @@ async fn test_update_config_wrong_admin() {
 async fn test_invalid_instruction_data() {
     let pid = program_id();
     let pt = ProgramTest::new("mythic_bridge", pid, processor!(process_instruction));
-    let (mut banks, payer, bh) = pt.start().await;
+    let (banks, payer, bh) = pt.start().await;
 
     // Empty data
     let ix = Instruction {
@@ -551,6 +1375,12 @@ fn test_bridge_config_serialization() {
         deposit_nonce: 42,
         is_initialized: true,
         bump: 255,
+        paused: false,
+        min_deposit_lamports: 10_000_000,
+        max_deposit_lamports: 100_000_000_000,
+        daily_limit_lamports: 1_000_000_000_000,
+        daily_volume: 0,
+        last_reset_slot: 0,
     };
     let bytes = borsh::to_vec(&config).unwrap();
     assert_eq!(bytes.len(), BridgeConfig::LEN);
@@ -567,7 +1397,7 @@ fn test_withdrawal_request_serialization() {
         recipient: Pubkey::new_unique(),
         amount: 1_000_000,
         token_mint: Pubkey::new_unique(),
-        merkle_proof: [0xAB; 32],
+        l2_slot: 100,
         challenge_deadline: 1_700_000_000,
         status: WithdrawalStatus::Pending,
         nonce: 7,
@@ -581,9 +1411,71 @@ fn test_withdrawal_request_serialization() {
     assert_eq!(deser.status, WithdrawalStatus::Pending);
 }
 
+#[test]
+fn test_verify_merkle_proof() {
+    let leaves: Vec<[u8; 32]> = (0..4u64)
+        .map(|n| withdrawal_leaf_hash(&Pubkey::new_unique(), 1_000 * (n + 1), &Pubkey::new_unique(), n))
+        .collect();
+    let node = |l: &[u8; 32], r: &[u8; 32]| -> [u8; 32] {
+        use sha2::{Digest, Sha256};
+        let mut h = Sha256::new();
+        h.update([0x01u8]);
+        h.update(l);
+        h.update(r);
+        h.finalize().into()
+    };
+    let n01 = node(&leaves[0], &leaves[1]);
+    let n23 = node(&leaves[2], &leaves[3]);
+    let root = node(&n01, &n23);
+
+    assert!(verify_merkle_proof(&leaves[2], &[leaves[3], n01], 2, &root));
+    assert!(verify_merkle_proof(&leaves[1], &[leaves[0], n23], 1, &root));
+    // Wrong index, wrong sibling, and out-of-range index all fail
+    assert!(!verify_merkle_proof(&leaves[2], &[leaves[3], n01], 3, &root));
+    assert!(!verify_merkle_proof(&leaves[2], &[leaves[0], n01], 2, &root));
+    assert!(!verify_merkle_proof(&leaves[2], &[leaves[3], n01], 6, &root));
+}
+
 #[test]
 fn test_withdrawal_status_variants() {
     assert_ne!(WithdrawalStatus::Pending, WithdrawalStatus::Challenged);
     assert_ne!(WithdrawalStatus::Challenged, WithdrawalStatus::Finalized);
     assert_ne!(WithdrawalStatus::Finalized, WithdrawalStatus::Cancelled);
 }
+
+/// Fixed message shared with the bridge-l2 and relayer tests, so all three
+/// encodings of CrossDomainMessage are pinned to the same hash.
+fn sample_message() -> CrossDomainMessage {
+    CrossDomainMessage {
+        nonce: 5,
+        sender: Pubkey::new_from_array([1; 32]),
+        target_program: Pubkey::new_from_array([2; 32]),
+        accounts: vec![MessageAccount { pubkey: Pubkey::new_from_array([3; 32]), is_writable: true }],
+        payload: vec![0xde, 0xad, 0xbe, 0xef],
+    }
+}
+
+#[test]
+fn test_message_hash_known_vector() {
+    let message = sample_message();
+    let hash = message_hash(&message);
+    assert_eq!(
+        hash.to_vec(),
+        hex_decode("27357153368e16944ba70e33b9852096ce22fc10d07af0f7943f942b1b9363c7")
+    );
+    assert_eq!(
+        message_leaf_hash(&hash).to_vec(),
+        hex_decode("e9c6bf93411bcf54c9edab28db167a4f16e52fb1b349067e6b9077790ad2e31e")
+    );
+
+    // Borsh round-trip keeps the hash; any field change moves it
+    let decoded = CrossDomainMessage::try_from_slice(&borsh::to_vec(&message).unwrap()).unwrap();
+    assert_eq!(message_hash(&decoded), hash);
+    let mut tampered = message.clone();
+    tampered.accounts[0].is_writable = false;
+    assert_ne!(message_hash(&tampered), hash);
+}
+
+fn hex_decode(hex: &str) -> Vec<u8> {
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+}
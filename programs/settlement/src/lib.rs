@@ -5,15 +5,17 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     clock::Clock,
+    ed25519_program,
     entrypoint,
     entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
     msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
     pubkey::Pubkey,
     rent::Rent,
     system_instruction,
-    sysvar::Sysvar,
+    sysvar::{instructions as sysvar_instructions, Sysvar},
 };
 
 // ---------------------------------------------------------------------------
@@ -33,6 +35,22 @@ const STATE_ROOT_SEED: &[u8] = b"state_root";
 const CHALLENGE_SEED: &[u8] = b"challenge";
 const PENDING_CONFIG_SEED: &[u8] = b"pending_config";
 const TIMELOCK_DELAY: u64 = 216_000; // ~24 hours at 400ms/slot
+const DISPUTE_ROUND_SLOTS: u64 = 9_000; // ~1 hour per bisection move
+const SEQUENCER_SET_SEED: &[u8] = b"sequencer_set";
+const SEQUENCER_VAULT_SEED: &[u8] = b"sequencer_vault";
+const MAX_SEQUENCERS: usize = 16;
+const BATCH_DATA_SEED: &[u8] = b"batch_data";
+const BATCH_CHUNK_SEED: &[u8] = b"batch_chunk";
+const BATCH_CHUNK_SIZE: usize = 8_192;
+const MAX_BATCH_CHUNKS: usize = 256; // 2 MB per batch
+/// Domain separator for the off-chain state root commitments a sequencer signs.
+const STATE_ROOT_MESSAGE_PREFIX: &[u8] = b"MYTHIC_STATE_ROOT";
+
+/// Token-2022 (Token Extensions) program ID — MYTH on L1 is a Token-2022 mint.
+const TOKEN_2022_PROGRAM_ID: Pubkey = solana_program::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Legacy SPL Token program ID.
+const LEGACY_TOKEN_PROGRAM_ID: Pubkey = solana_program::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 
 // ---------------------------------------------------------------------------
 // Entrypoint
@@ -66,6 +84,22 @@ pub fn process_instruction(
         11 => process_propose_config_update(program_id, accounts, data),
         12 => process_execute_config_update(program_id, accounts),
         13 => process_close_state_root(program_id, accounts, data),
+        14 => process_bisect(program_id, accounts, data),
+        15 => process_respond_bisection(program_id, accounts, data),
+        16 => process_prove_step(program_id, accounts, data),
+        // 17 was DepositSequencerBond (lamport bonds), retired when bonds moved
+        // into the MYTH sequencer set. It stays reserved so stale clients fail
+        // instead of hitting RegisterSequencer with a different account list.
+        17 => Err(SettlementError::InvalidInstruction.into()),
+        18 => process_prove_double_sequencing(program_id, accounts, data),
+        19 => process_initialize_sequencer_set(program_id, accounts, data),
+        20 => process_request_sequencer_exit(program_id, accounts),
+        21 => process_withdraw_sequencer_bond(program_id, accounts),
+        22 => process_init_batch_data(program_id, accounts, data),
+        23 => process_write_batch_chunk(program_id, accounts, data),
+        24 => process_seal_batch_chunks(program_id, accounts, data),
+        25 => process_close_batch_data(program_id, accounts, data),
+        26 => process_register_sequencer(program_id, accounts, data),
         _ => Err(SettlementError::InvalidInstruction.into()),
     }
 }
@@ -126,6 +160,56 @@ pub enum SettlementError {
     NoPendingConfig,
     #[error("State root cannot be closed in current status")]
     StateRootNotCloseable,
+    #[error("Fraud proof type has no on-chain resolution")]
+    UnsupportedFraudProof,
+    #[error("Not this party's turn in the dispute")]
+    NotYourTurn,
+    #[error("Dispute round deadline has passed")]
+    DisputeRoundExpired,
+    #[error("Dispute round deadline has not passed yet")]
+    DisputeRoundNotExpired,
+    #[error("No step verifier program configured")]
+    StepVerifierNotSet,
+    #[error("Step verifier program mismatch")]
+    InvalidStepVerifier,
+    #[error("Config account must be migrated before setting this field")]
+    ConfigNotMigrated,
+    #[error("Conflicting state roots must differ")]
+    RootsNotConflicting,
+    #[error("Missing ed25519 signature from the sequencer")]
+    MissingSequencerSignature,
+    #[error("Malformed ed25519 instruction")]
+    InvalidSignatureInstruction,
+    #[error("Sequencer set is full")]
+    SequencerSetFull,
+    #[error("Bond is below the sequencer set minimum")]
+    BondBelowMinimum,
+    #[error("Sequencer is not registered in the set")]
+    SequencerNotRegistered,
+    #[error("Sequencer has requested exit")]
+    SequencerExiting,
+    #[error("Sequencer exit delay has not elapsed")]
+    ExitDelayNotElapsed,
+    #[error("Not this sequencer's turn to post")]
+    NotSequencerTurn,
+    #[error("Transaction batch data has not been made available")]
+    BatchDataUnavailable,
+    #[error("Published batch data does not match transaction_batch_hash")]
+    BatchHashMismatch,
+    #[error("Invalid batch chunk index or range")]
+    InvalidBatchChunk,
+    #[error("Batch data is already available and cannot be modified")]
+    BatchDataSealed,
+    #[error("Step verifier can only be replaced through the config timelock")]
+    StepVerifierLocked,
+    #[error("Sequencer has disputes still open against its roots")]
+    SequencerHasOpenChallenges,
+    #[error("Token account has the wrong mint or owner")]
+    InvalidTokenAccount,
+    #[error("State root does not build on the last finalized root")]
+    ParentNotFinalized,
+    #[error("Challenge predates dispute games; resolve it for a refund")]
+    LegacyChallenge,
 }
 
 impl From<SettlementError> for ProgramError {
@@ -138,7 +222,7 @@ impl From<SettlementError> for ProgramError {
 // State
 // ---------------------------------------------------------------------------
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[derive(BorshSerialize, Debug, Clone)]
 pub struct SettlementConfig {
     pub is_initialized: bool,
     pub admin: Pubkey,
@@ -154,10 +238,76 @@ pub struct SettlementConfig {
     pub is_paused: bool,
     pub bump: u8,
     pub pending_admin: Pubkey,
+    // v2 — program that verifies a single disputed step of a bisection game
+    pub step_verifier: Pubkey,
+    // v3 — state root at last_finalized_slot, which the next root to finalize
+    // must build on
+    pub last_finalized_root: [u8; 32],
+    // v3 — unfinalized roots at or below this slot were discarded by a rewind
+    pub rewound_through_slot: u64,
+}
+
+// Custom deserialization: supports v1 (195-byte), v2 (227-byte) and v3 (267-byte) accounts.
+impl BorshDeserialize for SettlementConfig {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let is_initialized = bool::deserialize_reader(reader)?;
+        let admin = Pubkey::deserialize_reader(reader)?;
+        let sequencer = Pubkey::deserialize_reader(reader)?;
+        let challenge_period_slots = u64::deserialize_reader(reader)?;
+        let l2_chain_id = <[u8; 16]>::deserialize_reader(reader)?;
+        let min_challenger_bond = u64::deserialize_reader(reader)?;
+        let last_posted_slot = u64::deserialize_reader(reader)?;
+        let last_state_root = <[u8; 32]>::deserialize_reader(reader)?;
+        let last_finalized_slot = u64::deserialize_reader(reader)?;
+        let total_roots_posted = u64::deserialize_reader(reader)?;
+        let total_challenges = u64::deserialize_reader(reader)?;
+        let is_paused = bool::deserialize_reader(reader)?;
+        let bump = u8::deserialize_reader(reader)?;
+        let pending_admin = Pubkey::deserialize_reader(reader)?;
+        // v2 field — default if account is v1 (195 bytes)
+        let step_verifier = Pubkey::deserialize_reader(reader).unwrap_or_default();
+        // v3 fields — default if account is v1 or v2 (227 bytes)
+        let last_finalized_root = <[u8; 32]>::deserialize_reader(reader).unwrap_or_default();
+        let rewound_through_slot = u64::deserialize_reader(reader).unwrap_or_default();
+        Ok(Self {
+            is_initialized, admin, sequencer, challenge_period_slots, l2_chain_id,
+            min_challenger_bond, last_posted_slot, last_state_root, last_finalized_slot,
+            total_roots_posted, total_challenges, is_paused, bump, pending_admin,
+            step_verifier, last_finalized_root, rewound_through_slot,
+        })
+    }
 }
 
 impl SettlementConfig {
-    pub const SIZE: usize = 1 + 32 + 32 + 8 + 16 + 8 + 8 + 32 + 8 + 8 + 8 + 1 + 1 + 32; // 195
+    pub const SIZE: usize = 1 + 32 + 32 + 8 + 16 + 8 + 8 + 32 + 8 + 8 + 8 + 1 + 1 + 32 + 32 + 32 + 8; // 267
+    pub const V1_SIZE: usize = 195;
+    pub const V2_SIZE: usize = 227;
+
+    /// The root the next finalized root must build on: zero before anything
+    /// is finalized, then the last finalized root. None for a pre-v3 config
+    /// that finalized a root without recording it.
+    pub fn finalized_anchor(&self) -> Option<[u8; 32]> {
+        if self.last_finalized_slot != 0 && self.last_finalized_root == [0u8; 32] {
+            None
+        } else {
+            Some(self.last_finalized_root)
+        }
+    }
+
+    /// Whether a root at `l2_slot` that is not finalized has dropped off the
+    /// chain: discarded by a rewind, or passed over by a later finalization.
+    pub fn is_discarded(&self, l2_slot: u64) -> bool {
+        l2_slot <= self.rewound_through_slot || l2_slot <= self.last_finalized_slot
+    }
+
+    /// Serialize to account data, writing only the bytes that fit.
+    /// V1 accounts are grown to SIZE by the next PostStateRoot.
+    pub fn serialize_to_account(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        let full = borsh::to_vec(self).map_err(|_| ProgramError::BorshIoError("serialize".to_string()))?;
+        let len = data.len().min(full.len());
+        data[..len].copy_from_slice(&full[..len]);
+        Ok(())
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
@@ -169,7 +319,7 @@ pub enum StateRootStatus {
     Invalidated = 3,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[derive(BorshSerialize, Debug, Clone)]
 pub struct StateRootAccount {
     pub l2_slot: u64,
     pub state_root: [u8; 32],
@@ -182,10 +332,46 @@ pub struct StateRootAccount {
     pub challenge_deadline: i64,
     pub status: StateRootStatus,
     pub bump: u8,
+    // v2 — dispute games still in progress against this root
+    pub open_challenges: u16,
+}
+
+// Custom deserialization: supports both v1 (160-byte) and v2 (162-byte) accounts.
+impl BorshDeserialize for StateRootAccount {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let l2_slot = u64::deserialize_reader(reader)?;
+        let state_root = <[u8; 32]>::deserialize_reader(reader)?;
+        let transaction_count = u32::deserialize_reader(reader)?;
+        let transaction_batch_hash = <[u8; 32]>::deserialize_reader(reader)?;
+        let ai_attestation_count = u16::deserialize_reader(reader)?;
+        let previous_state_root = <[u8; 32]>::deserialize_reader(reader)?;
+        let sequencer = Pubkey::deserialize_reader(reader)?;
+        let posted_at = i64::deserialize_reader(reader)?;
+        let challenge_deadline = i64::deserialize_reader(reader)?;
+        let status = StateRootStatus::deserialize_reader(reader)?;
+        let bump = u8::deserialize_reader(reader)?;
+        // v2 field — zero if account is v1 (160 bytes)
+        let open_challenges = u16::deserialize_reader(reader).unwrap_or(0);
+        Ok(Self {
+            l2_slot, state_root, transaction_count, transaction_batch_hash,
+            ai_attestation_count, previous_state_root, sequencer, posted_at,
+            challenge_deadline, status, bump, open_challenges,
+        })
+    }
 }
 
 impl StateRootAccount {
-    pub const SIZE: usize = 8 + 32 + 4 + 32 + 2 + 32 + 32 + 8 + 8 + 1 + 1; // 160
+    pub const SIZE: usize = 8 + 32 + 4 + 32 + 2 + 32 + 32 + 8 + 8 + 1 + 1 + 2; // 162
+    pub const V1_SIZE: usize = 160;
+
+    /// Serialize to account data, writing only the bytes that fit.
+    /// V1 accounts are grown to SIZE when they are first challenged.
+    pub fn serialize_to_account(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        let full = borsh::to_vec(self).map_err(|_| ProgramError::BorshIoError("serialize".to_string()))?;
+        let len = data.len().min(full.len());
+        data[..len].copy_from_slice(&full[..len]);
+        Ok(())
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
@@ -205,7 +391,22 @@ pub enum ChallengeStatus {
     Rejected = 2,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+/// Whose move it is in a bisection dispute game.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+#[repr(u8)]
+pub enum DisputeTurn {
+    /// Sequencer must post the state hash at the midpoint of [lo_step, hi_step].
+    Defender = 0,
+    /// Challenger must agree or disagree with the posted midpoint hash.
+    Challenger = 1,
+    /// Range narrowed to one step; sequencer must prove it via the step verifier.
+    OneStep = 2,
+}
+
+/// A challenge doubles as a bisection game over the root's transaction range.
+/// Both parties agree on `lo_hash` at `lo_step` and disagree on `hi_hash` at
+/// `hi_step`; each round halves the range until a single step remains.
+#[derive(BorshSerialize, Debug, Clone)]
 pub struct ChallengeAccount {
     pub l2_slot: u64,
     pub challenger: Pubkey,
@@ -215,10 +416,174 @@ pub struct ChallengeAccount {
     pub created_at: i64,
     pub status: ChallengeStatus,
     pub bump: u8,
+    pub defender: Pubkey,
+    pub defender_bond: u64,
+    pub lo_step: u32,
+    pub lo_hash: [u8; 32],
+    pub hi_step: u32,
+    pub hi_hash: [u8; 32],
+    pub mid_hash: [u8; 32],
+    pub turn: DisputeTurn,
+    pub round: u16,
+    pub round_deadline: u64,
+}
+
+// Custom deserialization: supports both v1 (91-byte) and v2 (246-byte) accounts.
+impl BorshDeserialize for ChallengeAccount {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let l2_slot = u64::deserialize_reader(reader)?;
+        let challenger = Pubkey::deserialize_reader(reader)?;
+        let fraud_proof_type = FraudProofType::deserialize_reader(reader)?;
+        let proof_data_hash = <[u8; 32]>::deserialize_reader(reader)?;
+        let bond_amount = u64::deserialize_reader(reader)?;
+        let created_at = i64::deserialize_reader(reader)?;
+        let status = ChallengeStatus::deserialize_reader(reader)?;
+        let bump = u8::deserialize_reader(reader)?;
+        // v2 fields — default if account is v1 (91 bytes), which has no game
+        let defender = Pubkey::deserialize_reader(reader).unwrap_or_default();
+        let defender_bond = u64::deserialize_reader(reader).unwrap_or_default();
+        let lo_step = u32::deserialize_reader(reader).unwrap_or_default();
+        let lo_hash = <[u8; 32]>::deserialize_reader(reader).unwrap_or_default();
+        let hi_step = u32::deserialize_reader(reader).unwrap_or_default();
+        let hi_hash = <[u8; 32]>::deserialize_reader(reader).unwrap_or_default();
+        let mid_hash = <[u8; 32]>::deserialize_reader(reader).unwrap_or_default();
+        let turn = DisputeTurn::deserialize_reader(reader).unwrap_or(DisputeTurn::Defender);
+        let round = u16::deserialize_reader(reader).unwrap_or_default();
+        let round_deadline = u64::deserialize_reader(reader).unwrap_or_default();
+        Ok(Self {
+            l2_slot, challenger, fraud_proof_type, proof_data_hash, bond_amount,
+            created_at, status, bump, defender, defender_bond, lo_step, lo_hash,
+            hi_step, hi_hash, mid_hash, turn, round, round_deadline,
+        })
+    }
 }
 
 impl ChallengeAccount {
-    pub const SIZE: usize = 8 + 32 + 1 + 32 + 8 + 8 + 1 + 1; // 91
+    // 91 + 32 + 8 + 4 + 32 + 4 + 32 + 32 + 1 + 2 + 8 = 246
+    pub const SIZE: usize = 8 + 32 + 1 + 32 + 8 + 8 + 1 + 1 + 32 + 8 + 4 + 32 + 4 + 32 + 32 + 1 + 2 + 8;
+    pub const V1_SIZE: usize = 91;
+
+    /// Step the defender must post a state hash for.
+    pub fn mid_step(&self) -> u32 {
+        self.lo_step + (self.hi_step - self.lo_step) / 2
+    }
+
+    /// Apply the challenger's verdict on `mid_hash`, keeping the half of the
+    /// range that is still disputed, and hand the turn to the next mover.
+    pub fn apply_response(&mut self, agree: bool) -> ProgramResult {
+        let mid_step = self.mid_step();
+        if agree {
+            self.lo_step = mid_step;
+            self.lo_hash = self.mid_hash;
+        } else {
+            self.hi_step = mid_step;
+            self.hi_hash = self.mid_hash;
+        }
+        self.mid_hash = [0u8; 32];
+        self.round = self.round.checked_add(1).ok_or(SettlementError::Overflow)?;
+        self.turn = if self.hi_step - self.lo_step == 1 {
+            DisputeTurn::OneStep
+        } else {
+            DisputeTurn::Defender
+        };
+        Ok(())
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SequencerEntry {
+    pub sequencer: Pubkey,
+    /// MYTH held in the bond vault for this sequencer
+    pub bond: u64,
+    pub registered_at: u64,
+    /// L1 slot the exit was requested at; 0 while active
+    pub exit_requested_at: u64,
+    /// Dispute games still open against roots this sequencer posted; the
+    /// bond cannot be withdrawn while any remain.
+    pub open_challenges: u16,
+}
+
+impl SequencerEntry {
+    pub const SIZE: usize = 32 + 8 + 8 + 8 + 2; // 58
+}
+
+/// Bonded sequencers taking turns by L2 slot range.
+/// Window `l2_slot / rotation_slots` belongs to active member
+/// `window % active_count`; every `skip_after_slots` L1 slots without a post
+/// passes the turn to the next active member.
+/// PDA seeds: ["sequencer_set"]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SequencerSet {
+    pub is_initialized: bool,
+    pub myth_mint: Pubkey,
+    pub min_bond: u64,
+    pub rotation_slots: u64,
+    pub skip_after_slots: u64,
+    pub exit_delay_slots: u64,
+    /// L1 slot of the most recent PostStateRoot
+    pub last_post_slot: u64,
+    pub vault_bump: u8,
+    pub bump: u8,
+    pub members: Vec<SequencerEntry>,
+}
+
+impl SequencerSet {
+    // 1 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 4 + 16 * 58 = 1007
+    pub const SIZE: usize = 1 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 4 + MAX_SEQUENCERS * SequencerEntry::SIZE;
+
+    /// Sequencer allowed to post `l2_slot` at L1 slot `current_slot`, or
+    /// None if no member is active.
+    pub fn expected_sequencer(&self, l2_slot: u64, current_slot: u64) -> Option<Pubkey> {
+        let active: Vec<&SequencerEntry> = self
+            .members
+            .iter()
+            .filter(|m| m.exit_requested_at == 0 && m.bond >= self.min_bond)
+            .collect();
+        if active.is_empty() {
+            return None;
+        }
+        let window = l2_slot / self.rotation_slots;
+        let skipped = current_slot.saturating_sub(self.last_post_slot) / self.skip_after_slots;
+        let index = window.wrapping_add(skipped) % active.len() as u64;
+        Some(active[index as usize].sequencer)
+    }
+}
+
+/// Data-availability header for the transaction batch behind a state root.
+/// The batch is split into BATCH_CHUNK_SIZE-byte chunks, each stored raw in
+/// its own PDA (seeds: ["batch_chunk", l2_slot, chunk_index]). The batch is
+/// available once every chunk is sealed and
+/// sha256(chunk_hash_0 || ... || chunk_hash_n) == transaction_batch_hash.
+/// PDA seeds: ["batch_data", l2_slot]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct BatchDataHeader {
+    pub l2_slot: u64,
+    pub poster: Pubkey,
+    pub total_len: u32,
+    pub available: bool,
+    pub bump: u8,
+    /// sha256 of each sealed chunk; zero while unsealed
+    pub chunk_hashes: Vec<[u8; 32]>,
+}
+
+impl BatchDataHeader {
+    pub fn size(chunk_count: usize) -> usize {
+        8 + 32 + 4 + 1 + 1 + 4 + 32 * chunk_count
+    }
+
+    pub fn chunk_len(&self, chunk_index: usize) -> usize {
+        let start = chunk_index * BATCH_CHUNK_SIZE;
+        (self.total_len as usize - start).min(BATCH_CHUNK_SIZE)
+    }
+
+    /// sha256 over the concatenated chunk hashes, or None while any chunk
+    /// is still unsealed.
+    pub fn batch_hash(&self) -> Option<[u8; 32]> {
+        if self.chunk_hashes.iter().any(|h| h == &[0u8; 32]) {
+            return None;
+        }
+        Some(hash_proof_data(&self.chunk_hashes.concat()))
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
@@ -226,6 +591,7 @@ pub struct PendingConfigUpdate {
     pub sequencer: Option<Pubkey>,
     pub challenge_period_slots: Option<u64>,
     pub min_challenger_bond: Option<u64>,
+    pub step_verifier: Option<Pubkey>,
     pub propose_slot: u64,
     pub execution_slot: u64,
     pub proposer: Pubkey,
@@ -233,8 +599,8 @@ pub struct PendingConfigUpdate {
 }
 
 impl PendingConfigUpdate {
-    // 1+32 + 1+8 + 1+8 + 8 + 8 + 32 + 1 = 100
-    pub const SIZE: usize = 33 + 9 + 9 + 8 + 8 + 32 + 1;
+    // 1+32 + 1+8 + 1+8 + 1+32 + 8 + 8 + 32 + 1 = 133
+    pub const SIZE: usize = 33 + 9 + 9 + 33 + 8 + 8 + 32 + 1;
 }
 
 // ---------------------------------------------------------------------------
@@ -269,7 +635,88 @@ pub struct ChallengeStateRootArgs {
 pub struct ResolveChallengeArgs {
     pub l2_slot: u64,
     pub challenger: Pubkey,
-    pub is_valid: bool,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct BisectArgs {
+    pub l2_slot: u64,
+    pub challenger: Pubkey,
+    pub mid_hash: [u8; 32],
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RespondBisectionArgs {
+    pub l2_slot: u64,
+    pub agree: bool,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ProveStepArgs {
+    pub l2_slot: u64,
+    pub challenger: Pubkey,
+    pub proof: Vec<u8>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct InitializeSequencerSetArgs {
+    pub min_bond: u64,
+    pub rotation_slots: u64,
+    pub skip_after_slots: u64,
+    pub exit_delay_slots: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RegisterSequencerArgs {
+    pub bond_amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ProveDoubleSequencingArgs {
+    pub l2_slot: u64,
+    pub root_a: [u8; 32],
+    pub root_b: [u8; 32],
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct InitBatchDataArgs {
+    pub l2_slot: u64,
+    pub total_len: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct WriteBatchChunkArgs {
+    pub l2_slot: u64,
+    pub chunk_index: u16,
+    pub offset: u32,
+    pub data: Vec<u8>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SealBatchChunksArgs {
+    pub l2_slot: u64,
+    /// Chunk accounts are passed in order starting at this index
+    pub first_chunk: u16,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct CloseBatchDataArgs {
+    pub l2_slot: u64,
+    /// Chunk accounts are passed in order starting at this index
+    pub first_chunk: u16,
+    pub close_header: bool,
+}
+
+/// Instruction data passed to the configured step verifier program.
+/// The verifier must fail unless executing step `step` of the batch
+/// transforms `pre_state_hash` into `post_state_hash`.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct VerifyStepArgs {
+    pub l2_slot: u64,
+    pub step: u32,
+    pub pre_state_hash: [u8; 32],
+    pub post_state_hash: [u8; 32],
+    pub transaction_batch_hash: [u8; 32],
+    pub proof: Vec<u8>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -282,6 +729,7 @@ pub struct UpdateConfigArgs {
     pub sequencer: Option<Pubkey>,
     pub challenge_period_slots: Option<u64>,
     pub min_challenger_bond: Option<u64>,
+    pub step_verifier: Option<Pubkey>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -289,6 +737,7 @@ pub struct ProposeConfigUpdateArgs {
     pub sequencer: Option<Pubkey>,
     pub challenge_period_slots: Option<u64>,
     pub min_challenger_bond: Option<u64>,
+    pub step_verifier: Option<Pubkey>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -349,6 +798,427 @@ fn hash_proof_data(data: &[u8]) -> [u8; 32] {
     out
 }
 
+/// Message a sequencer signs to commit to `state_root` at `l2_slot`:
+/// "MYTHIC_STATE_ROOT" || l2_chain_id || l2_slot (LE) || state_root
+pub fn state_root_message(l2_chain_id: &[u8; 16], l2_slot: u64, state_root: &[u8; 32]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(STATE_ROOT_MESSAGE_PREFIX.len() + 16 + 8 + 32);
+    message.extend_from_slice(STATE_ROOT_MESSAGE_PREFIX);
+    message.extend_from_slice(l2_chain_id);
+    message.extend_from_slice(&l2_slot.to_le_bytes());
+    message.extend_from_slice(state_root);
+    message
+}
+
+/// Collect (public key, message) pairs from every ed25519 precompile
+/// instruction in the transaction. The runtime rejects the transaction if any
+/// of these signatures is invalid, so reaching this code means they verified.
+/// Offsets must point into the precompile instruction itself; signatures
+/// sourced from other instructions are rejected.
+fn load_ed25519_signatures(
+    instructions_sysvar: &AccountInfo,
+) -> Result<Vec<(Pubkey, Vec<u8>)>, ProgramError> {
+    const OFFSETS_START: usize = 2;
+    const OFFSETS_SIZE: usize = 14;
+    const PUBKEY_SIZE: usize = 32;
+
+    let read_u16 = |data: &[u8], at: usize| -> Result<usize, ProgramError> {
+        data.get(at..at + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+            .ok_or_else(|| SettlementError::InvalidSignatureInstruction.into())
+    };
+
+    let current_index = sysvar_instructions::load_current_index_checked(instructions_sysvar)?;
+    let mut signatures = Vec::new();
+
+    for index in 0..current_index {
+        let ix = sysvar_instructions::load_instruction_at_checked(index as usize, instructions_sysvar)?;
+        if ix.program_id != ed25519_program::ID {
+            continue;
+        }
+        let data = &ix.data;
+        let count = *data.first().ok_or(SettlementError::InvalidSignatureInstruction)? as usize;
+
+        for i in 0..count {
+            let base = OFFSETS_START + i * OFFSETS_SIZE;
+            let signature_ix = read_u16(data, base + 2)?;
+            let pubkey_offset = read_u16(data, base + 4)?;
+            let pubkey_ix = read_u16(data, base + 6)?;
+            let message_offset = read_u16(data, base + 8)?;
+            let message_size = read_u16(data, base + 10)?;
+            let message_ix = read_u16(data, base + 12)?;
+
+            let this_ix = u16::MAX as usize;
+            if signature_ix != this_ix || pubkey_ix != this_ix || message_ix != this_ix {
+                return Err(SettlementError::InvalidSignatureInstruction.into());
+            }
+
+            let pubkey_bytes = data
+                .get(pubkey_offset..pubkey_offset + PUBKEY_SIZE)
+                .ok_or(SettlementError::InvalidSignatureInstruction)?;
+            let message = data
+                .get(message_offset..message_offset + message_size)
+                .ok_or(SettlementError::InvalidSignatureInstruction)?;
+            let pubkey = Pubkey::try_from(pubkey_bytes)
+                .map_err(|_| SettlementError::InvalidSignatureInstruction)?;
+
+            signatures.push((pubkey, message.to_vec()));
+        }
+    }
+
+    Ok(signatures)
+}
+
+fn is_valid_token_program(key: &Pubkey) -> bool {
+    *key == LEGACY_TOKEN_PROGRAM_ID || *key == TOKEN_2022_PROGRAM_ID
+}
+
+/// Build a Transfer instruction compatible with both SPL Token and Token-2022.
+fn build_token_transfer_ix(
+    token_program_id: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut data = Vec::with_capacity(9);
+    data.push(3); // Transfer instruction discriminator
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: *token_program_id,
+        accounts: vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+/// Build an InitializeAccount instruction compatible with both SPL Token and Token-2022.
+fn build_init_account_ix(
+    token_program_id: &Pubkey,
+    account: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *token_program_id,
+        accounts: vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+        ],
+        data: vec![1], // InitializeAccount instruction discriminator
+    }
+}
+
+fn load_sequencer_set(
+    program_id: &Pubkey,
+    set_account: &AccountInfo,
+) -> Result<SequencerSet, ProgramError> {
+    assert_writable(set_account)?;
+    assert_owned_by(set_account, program_id)?;
+    let (set_pda, _) = Pubkey::find_program_address(&[SEQUENCER_SET_SEED], program_id);
+    if set_account.key != &set_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    // Member list is shorter than the allocated space, so tolerate trailing bytes
+    let set = SequencerSet::deserialize(&mut &set_account.data.borrow()[..])?;
+    if !set.is_initialized {
+        return Err(SettlementError::NotInitialized.into());
+    }
+    Ok(set)
+}
+
+fn check_bond_vault(
+    program_id: &Pubkey,
+    vault_account: &AccountInfo,
+    token_program: &AccountInfo,
+) -> ProgramResult {
+    assert_writable(vault_account)?;
+    let (vault_pda, _) = Pubkey::find_program_address(&[SEQUENCER_VAULT_SEED], program_id);
+    if vault_account.key != &vault_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    if !is_valid_token_program(token_program.key) || vault_account.owner != token_program.key {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// Move a sequencer's entire bond from the vault to `destination` and drop it
+/// from the set. Returns the slashed amount (0 if it is not a member, e.g. the
+/// legacy single sequencer).
+fn slash_sequencer<'a>(
+    set: &mut SequencerSet,
+    sequencer: &Pubkey,
+    vault_account: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+) -> Result<u64, ProgramError> {
+    let index = match set.members.iter().position(|m| &m.sequencer == sequencer) {
+        Some(index) => index,
+        None => return Ok(0),
+    };
+    let amount = set.members[index].bond;
+    if amount > 0 {
+        invoke_signed(
+            &build_token_transfer_ix(
+                token_program.key,
+                vault_account.key,
+                destination.key,
+                vault_account.key,
+                amount,
+            ),
+            &[
+                vault_account.clone(),
+                destination.clone(),
+                vault_account.clone(),
+                token_program.clone(),
+            ],
+            &[&[SEQUENCER_VAULT_SEED, &[set.vault_bump]]],
+        )?;
+    }
+    set.members.remove(index);
+
+    msg!(
+        "EVENT:SequencerSlashed:{{\"sequencer\":\"{}\",\"amount\":{},\"recipient\":\"{}\"}}",
+        sequencer,
+        amount,
+        destination.key,
+    );
+
+    Ok(amount)
+}
+
+/// Count a dispute opening or closing against `sequencer`. Nothing is tracked
+/// without a sequencer set or once the member has been slashed out of it.
+fn track_open_challenge(
+    program_id: &Pubkey,
+    set_account: &AccountInfo,
+    sequencer: &Pubkey,
+    opened: bool,
+) -> ProgramResult {
+    let (set_pda, _) = Pubkey::find_program_address(&[SEQUENCER_SET_SEED], program_id);
+    if set_account.key != &set_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    if set_account.data_is_empty() {
+        return Ok(());
+    }
+    let mut set = load_sequencer_set(program_id, set_account)?;
+    if let Some(member) = set.members.iter_mut().find(|m| &m.sequencer == sequencer) {
+        member.open_challenges = if opened {
+            member.open_challenges.checked_add(1).ok_or(SettlementError::Overflow)?
+        } else {
+            member.open_challenges.saturating_sub(1)
+        };
+        set.serialize(&mut &mut set_account.data.borrow_mut()[..])?;
+    }
+    Ok(())
+}
+
+/// Check `token_account` is a `mint` account owned by `owner`.
+/// SPL Token / Token-2022 layout: mint at 0..32, owner at 32..64.
+fn check_token_account(
+    token_account: &AccountInfo,
+    token_program: &AccountInfo,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> ProgramResult {
+    if token_account.owner != token_program.key {
+        return Err(SettlementError::InvalidOwner.into());
+    }
+    let data = token_account.data.borrow();
+    if data.len() < 64 || &data[..32] != mint.as_ref() || &data[32..64] != owner.as_ref() {
+        return Err(SettlementError::InvalidTokenAccount.into());
+    }
+    Ok(())
+}
+
+fn batch_chunk_pda(program_id: &Pubkey, l2_slot: u64, chunk_index: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[BATCH_CHUNK_SEED, &l2_slot.to_le_bytes(), &chunk_index.to_le_bytes()],
+        program_id,
+    )
+}
+
+fn load_batch_header(
+    program_id: &Pubkey,
+    header_account: &AccountInfo,
+    l2_slot: u64,
+) -> Result<BatchDataHeader, ProgramError> {
+    assert_owned_by(header_account, program_id)?;
+    let (header_pda, _) = Pubkey::find_program_address(
+        &[BATCH_DATA_SEED, &l2_slot.to_le_bytes()],
+        program_id,
+    );
+    if header_account.key != &header_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    Ok(BatchDataHeader::try_from_slice(&header_account.data.borrow())?)
+}
+
+/// Zero a program-owned account and move its lamports to `recipient`.
+fn close_account(account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    let data_len = account.data.borrow().len();
+    account.data.borrow_mut()[..data_len].fill(0);
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    **recipient.try_borrow_mut_lamports()? = recipient
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(SettlementError::Overflow)?;
+    Ok(())
+}
+
+/// Grow a program-owned account to `new_size`, topping up rent from `payer`.
+fn grow_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    new_size: usize,
+) -> ProgramResult {
+    let rent = Rent::get()?;
+    let shortfall = rent
+        .minimum_balance(new_size)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, shortfall),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    account.realloc(new_size, false)
+}
+
+/// Load an Active challenge for (l2_slot, challenger) and check the round
+/// deadline has not passed.
+fn load_active_dispute(
+    program_id: &Pubkey,
+    challenge_account: &AccountInfo,
+    l2_slot: u64,
+    challenger: &Pubkey,
+    current_slot: u64,
+) -> Result<ChallengeAccount, ProgramError> {
+    assert_owned_by(challenge_account, program_id)?;
+    let (challenge_pda, _) = Pubkey::find_program_address(
+        &[CHALLENGE_SEED, &l2_slot.to_le_bytes(), challenger.as_ref()],
+        program_id,
+    );
+    if challenge_account.key != &challenge_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    if challenge_account.data_len() < ChallengeAccount::SIZE {
+        return Err(SettlementError::LegacyChallenge.into());
+    }
+
+    let challenge = ChallengeAccount::try_from_slice(&challenge_account.data.borrow())?;
+    if challenge.status != ChallengeStatus::Active {
+        return Err(SettlementError::ChallengeNotActive.into());
+    }
+    if current_slot > challenge.round_deadline {
+        return Err(SettlementError::DisputeRoundExpired.into());
+    }
+    Ok(challenge)
+}
+
+/// Load a Challenged state root for `l2_slot`.
+fn load_challenged_root(
+    program_id: &Pubkey,
+    state_root_account: &AccountInfo,
+    l2_slot: u64,
+) -> Result<StateRootAccount, ProgramError> {
+    assert_owned_by(state_root_account, program_id)?;
+    let (state_root_pda, _) =
+        Pubkey::find_program_address(&[STATE_ROOT_SEED, &l2_slot.to_le_bytes()], program_id);
+    if state_root_account.key != &state_root_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+
+    let state_root = StateRootAccount::try_from_slice(&state_root_account.data.borrow())?;
+    if state_root.status != StateRootStatus::Challenged {
+        return Err(SettlementError::InvalidStateRootStatus.into());
+    }
+    Ok(state_root)
+}
+
+/// Invalidate `state_root` and rewind the chain tip past it. Every root not
+/// yet finalized is discarded and posting resumes from the last finalized
+/// root, so nothing built on the invalid root can finalize and a single
+/// slot bound describes what was thrown away. A root that an earlier rewind
+/// already discarded leaves the tip alone.
+fn invalidate_state_root(config: &mut SettlementConfig, state_root: &mut StateRootAccount) {
+    state_root.status = StateRootStatus::Invalidated;
+    if config.is_discarded(state_root.l2_slot) {
+        return;
+    }
+    match config.finalized_anchor() {
+        Some(anchor) => {
+            config.last_state_root = anchor;
+            config.rewound_through_slot = config.last_posted_slot;
+        }
+        // A pre-v3 config learns its anchor at the next finalization; until
+        // then resume from the invalid root's parent.
+        None => config.last_state_root = state_root.previous_state_root,
+    }
+
+    msg!(
+        "EVENT:RootChainRewound:{{\"invalidated_slot\":{},\"discarded_through\":{},\"last_finalized_slot\":{}}}",
+        state_root.l2_slot,
+        config.rewound_through_slot,
+        config.last_finalized_slot,
+    );
+}
+
+/// Close a decided dispute: the winner takes everything escrowed in the
+/// challenge PDA (rent + both bonds). A challenger win invalidates the root
+/// and rewinds the chain tip; otherwise the root returns to Posted once no
+/// other games remain open.
+fn settle_dispute(
+    config: &mut SettlementConfig,
+    state_root: &mut StateRootAccount,
+    challenge_account: &AccountInfo,
+    challenge: &mut ChallengeAccount,
+    winner: &AccountInfo,
+    challenger_won: bool,
+) -> ProgramResult {
+    state_root.open_challenges = state_root.open_challenges.saturating_sub(1);
+    if challenger_won {
+        challenge.status = ChallengeStatus::Accepted;
+        invalidate_state_root(config, state_root);
+    } else {
+        challenge.status = ChallengeStatus::Rejected;
+        if state_root.status == StateRootStatus::Challenged && state_root.open_challenges == 0 {
+            state_root.status = StateRootStatus::Posted;
+        }
+    }
+
+    let challenge_lamports = challenge_account.lamports();
+    **challenge_account.try_borrow_mut_lamports()? = 0;
+    **winner.try_borrow_mut_lamports()? = winner
+        .lamports()
+        .checked_add(challenge_lamports)
+        .ok_or(SettlementError::Overflow)?;
+
+    // Zero out challenge account data before closing
+    let data_len = challenge_account.data.borrow().len();
+    challenge_account.data.borrow_mut()[..data_len].fill(0);
+
+    msg!(
+        "EVENT:DisputeSettled:{{\"l2_slot\":{},\"challenger\":\"{}\",\"winner\":\"{}\",\"challenger_won\":{},\"rounds\":{},\"payout\":{}}}",
+        challenge.l2_slot,
+        challenge.challenger,
+        winner.key,
+        challenger_won,
+        challenge.round,
+        challenge_lamports,
+    );
+
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Instruction: Initialize
 // ---------------------------------------------------------------------------
@@ -413,9 +1283,12 @@ fn process_initialize(
         is_paused: false,
         bump: config_bump,
         pending_admin: Pubkey::default(),
+        step_verifier: Pubkey::default(),
+        last_finalized_root: [0u8; 32],
+        rewound_through_slot: 0,
     };
 
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!(
         "EVENT:SettlementInitialized:{{\"admin\":\"{}\",\"sequencer\":\"{}\",\"challenge_period\":{}}}",
@@ -429,6 +1302,9 @@ fn process_initialize(
 
 // ---------------------------------------------------------------------------
 // Instruction: PostStateRoot
+// Accounts: 0=[signer, writable] sequencer, 1=[writable] config PDA,
+//           2=[writable] state_root PDA, 3=[] system_program,
+//           4=[writable] sequencer_set PDA (may be uninitialized)
 // ---------------------------------------------------------------------------
 
 fn process_post_state_root(
@@ -444,6 +1320,7 @@ fn process_post_state_root(
     let config_account = next_account_info(account_iter)?;
     let state_root_account = next_account_info(account_iter)?;
     let system_program = next_account_info(account_iter)?;
+    let set_account = next_account_info(account_iter)?;
 
     assert_signer(sequencer)?;
     assert_writable(config_account)?;
@@ -456,13 +1333,41 @@ fn process_post_state_root(
     if !config.is_initialized {
         return Err(SettlementError::NotInitialized.into());
     }
-    if sequencer.key != &config.sequencer {
-        return Err(SettlementError::InvalidSequencer.into());
-    }
     if config.is_paused {
         return Err(SettlementError::ProgramPaused.into());
     }
 
+    let clock = Clock::get()?;
+
+    // Poster must be the active set member whose turn it is; with no set (or
+    // no active members) the single configured sequencer posts.
+    let (set_pda, _) = Pubkey::find_program_address(&[SEQUENCER_SET_SEED], program_id);
+    if set_account.key != &set_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    let mut sequencer_set = if set_account.data_is_empty() {
+        None
+    } else {
+        Some(load_sequencer_set(program_id, set_account)?)
+    };
+    let expected = sequencer_set
+        .as_ref()
+        .and_then(|set| set.expected_sequencer(args.l2_slot, clock.slot));
+    match expected {
+        Some(expected) if sequencer.key != &expected => {
+            return Err(SettlementError::NotSequencerTurn.into());
+        }
+        None if sequencer.key != &config.sequencer => {
+            return Err(SettlementError::InvalidSequencer.into());
+        }
+        _ => {}
+    }
+
+    // Migrate v1 config accounts to the current layout (sequencer pays the rent delta)
+    if config_account.data_len() < SettlementConfig::SIZE {
+        grow_account(sequencer, config_account, system_program, SettlementConfig::SIZE)?;
+    }
+
     // Validate slot ordering
     if args.l2_slot <= config.last_posted_slot && config.last_posted_slot != 0 {
         return Err(SettlementError::SlotNotIncreasing.into());
@@ -491,7 +1396,6 @@ fn process_post_state_root(
         &[STATE_ROOT_SEED, &l2_slot_bytes, &[state_root_bump]],
     )?;
 
-    let clock = Clock::get()?;
     let deadline_slot = clock
         .slot
         .checked_add(config.challenge_period_slots)
@@ -511,9 +1415,10 @@ fn process_post_state_root(
         challenge_deadline,
         status: StateRootStatus::Posted,
         bump: state_root_bump,
+        open_challenges: 0,
     };
 
-    state_root.serialize(&mut &mut state_root_account.data.borrow_mut()[..])?;
+    state_root.serialize_to_account(&mut state_root_account.data.borrow_mut())?;
 
     // Update config
     config.last_posted_slot = args.l2_slot;
@@ -523,9 +1428,14 @@ fn process_post_state_root(
         .checked_add(1)
         .ok_or(SettlementError::Overflow)?;
 
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
-    msg!(
+    if let Some(set) = sequencer_set.as_mut() {
+        set.last_post_slot = clock.slot;
+        set.serialize(&mut &mut set_account.data.borrow_mut()[..])?;
+    }
+
+    msg!(
         "EVENT:StateRootPosted:{{\"l2_slot\":{},\"tx_count\":{},\"ai_attestations\":{}}}",
         args.l2_slot,
         args.transaction_count,
@@ -537,6 +1447,10 @@ fn process_post_state_root(
 
 // ---------------------------------------------------------------------------
 // Instruction: ChallengeStateRoot
+// Accounts: 0=[signer, writable] challenger, 1=[writable] config PDA,
+//           2=[writable] state_root PDA, 3=[writable] challenge PDA,
+//           4=[] system_program,
+//           5=[writable] sequencer_set PDA (may be uninitialized)
 // ---------------------------------------------------------------------------
 
 fn process_challenge_state_root(
@@ -550,6 +1464,10 @@ fn process_challenge_state_root(
     if args.proof_data.len() > MAX_PROOF_DATA_LEN {
         return Err(SettlementError::ProofDataTooLarge.into());
     }
+    // Only state transitions can be settled on-chain, via the bisection game.
+    if args.fraud_proof_type != FraudProofType::InvalidStateTransition {
+        return Err(SettlementError::UnsupportedFraudProof.into());
+    }
 
     let account_iter = &mut accounts.iter();
     let challenger = next_account_info(account_iter)?;
@@ -557,6 +1475,7 @@ fn process_challenge_state_root(
     let state_root_account = next_account_info(account_iter)?;
     let challenge_account = next_account_info(account_iter)?;
     let system_program = next_account_info(account_iter)?;
+    let set_account = next_account_info(account_iter)?;
 
     assert_signer(challenger)?;
     assert_writable(state_root_account)?;
@@ -574,6 +1493,14 @@ fn process_challenge_state_root(
         return Err(SettlementError::ProgramPaused.into());
     }
 
+    // Validate state root PDA
+    let l2_slot_bytes = args.l2_slot.to_le_bytes();
+    let (state_root_pda, _) =
+        Pubkey::find_program_address(&[STATE_ROOT_SEED, &l2_slot_bytes], program_id);
+    if state_root_account.key != &state_root_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+
     // Load state root
     let mut state_root =
         StateRootAccount::try_from_slice(&state_root_account.data.borrow())?;
@@ -598,7 +1525,6 @@ fn process_challenge_state_root(
     }
 
     // Derive challenge PDA
-    let l2_slot_bytes = args.l2_slot.to_le_bytes();
     let (challenge_pda, challenge_bump) = Pubkey::find_program_address(
         &[CHALLENGE_SEED, &l2_slot_bytes, challenger.key.as_ref()],
         program_id,
@@ -636,6 +1562,14 @@ fn process_challenge_state_root(
 
     let proof_data_hash = hash_proof_data(&args.proof_data);
 
+    // The game starts over the whole batch: both sides agree on the previous
+    // root at step 0 and dispute the posted root at the last step.
+    let hi_step = state_root.transaction_count.max(1);
+    let round_deadline = clock
+        .slot
+        .checked_add(DISPUTE_ROUND_SLOTS)
+        .ok_or(SettlementError::Overflow)?;
+
     let challenge = ChallengeAccount {
         l2_slot: args.l2_slot,
         challenger: *challenger.key,
@@ -645,13 +1579,35 @@ fn process_challenge_state_root(
         created_at: clock.unix_timestamp,
         status: ChallengeStatus::Active,
         bump: challenge_bump,
+        defender: state_root.sequencer,
+        defender_bond: 0,
+        lo_step: 0,
+        lo_hash: state_root.previous_state_root,
+        hi_step,
+        hi_hash: state_root.state_root,
+        mid_hash: [0u8; 32],
+        turn: if hi_step == 1 { DisputeTurn::OneStep } else { DisputeTurn::Defender },
+        round: 0,
+        round_deadline,
     };
 
     challenge.serialize(&mut &mut challenge_account.data.borrow_mut()[..])?;
 
+    // v1 state roots have no room for the open challenge count
+    if state_root_account.data_len() < StateRootAccount::SIZE {
+        grow_account(challenger, state_root_account, system_program, StateRootAccount::SIZE)?;
+    }
+
     // Update state root status
     state_root.status = StateRootStatus::Challenged;
-    state_root.serialize(&mut &mut state_root_account.data.borrow_mut()[..])?;
+    state_root.open_challenges = state_root
+        .open_challenges
+        .checked_add(1)
+        .ok_or(SettlementError::Overflow)?;
+    state_root.serialize_to_account(&mut state_root_account.data.borrow_mut())?;
+
+    // Hold the poster's bond in place until this game is settled
+    track_open_challenge(program_id, set_account, &state_root.sequencer, true)?;
 
     // Update config stats
     config.total_challenges = config
@@ -659,7 +1615,7 @@ fn process_challenge_state_root(
         .checked_add(1)
         .ok_or(SettlementError::Overflow)?;
     assert_writable(config_account)?;
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!(
         "EVENT:StateRootChallenged:{{\"l2_slot\":{},\"challenger\":\"{}\",\"fraud_type\":{}}}",
@@ -672,7 +1628,21 @@ fn process_challenge_state_root(
 }
 
 // ---------------------------------------------------------------------------
-// Instruction: ResolveChallenge
+// Instruction: ResolveChallenge (permissionless)
+// Settles a dispute game once the party whose turn it is misses the round
+// deadline: the timed-out party loses and the other side takes both bonds.
+// If another game already invalidated the root, both bonds are refunded.
+// A defender who loses also has their sequencer set bond slashed to the
+// challenger's MYTH account, which must belong to the challenger so a
+// third-party cranker cannot redirect the payout. A challenger win rewinds
+// the chain tip in the config. A v1 challenge never started a game and is
+// refunded to the challenger in full.
+// Accounts: 0=[signer] caller, 1=[writable] config PDA, 2=[writable] state_root PDA,
+//           3=[writable] challenge PDA, 4=[writable] challenger,
+//           5=[writable] state root poster (defender),
+//           6=[writable] sequencer_set PDA (may be uninitialized),
+//           7=[writable] bond vault PDA, 8=[writable] challenger MYTH token account,
+//           9=[] token program
 // ---------------------------------------------------------------------------
 
 fn process_resolve_challenge(
@@ -684,14 +1654,19 @@ fn process_resolve_challenge(
         .map_err(|_| ProgramError::InvalidInstructionData)?;
 
     let account_iter = &mut accounts.iter();
-    let admin = next_account_info(account_iter)?;
+    let caller = next_account_info(account_iter)?;
     let config_account = next_account_info(account_iter)?;
     let state_root_account = next_account_info(account_iter)?;
     let challenge_account = next_account_info(account_iter)?;
     let challenger_account = next_account_info(account_iter)?;
     let state_root_poster = next_account_info(account_iter)?;
+    let set_account = next_account_info(account_iter)?;
+    let vault_account = next_account_info(account_iter)?;
+    let challenger_token = next_account_info(account_iter)?;
+    let token_program = next_account_info(account_iter)?;
 
-    assert_signer(admin)?;
+    assert_signer(caller)?;
+    assert_writable(config_account)?;
     assert_writable(state_root_account)?;
     assert_writable(challenge_account)?;
     assert_writable(challenger_account)?;
@@ -701,17 +1676,26 @@ fn process_resolve_challenge(
     assert_owned_by(challenge_account, program_id)?;
 
     // Load config
-    let config =
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[SETTLEMENT_CONFIG_SEED], program_id);
+    if *config_account.key != config_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    let mut config =
         SettlementConfig::try_from_slice(&config_account.data.borrow())?;
     if !config.is_initialized {
         return Err(SettlementError::NotInitialized.into());
     }
-    if admin.key != &config.admin {
-        return Err(SettlementError::Unauthorized.into());
+
+    // Validate state root PDA
+    let l2_slot_bytes = args.l2_slot.to_le_bytes();
+    let (state_root_pda, _) =
+        Pubkey::find_program_address(&[STATE_ROOT_SEED, &l2_slot_bytes], program_id);
+    if state_root_account.key != &state_root_pda {
+        return Err(SettlementError::InvalidPDA.into());
     }
 
     // Validate challenge PDA
-    let l2_slot_bytes = args.l2_slot.to_le_bytes();
     let (challenge_pda, _) = Pubkey::find_program_address(
         &[CHALLENGE_SEED, &l2_slot_bytes, args.challenger.as_ref()],
         program_id,
@@ -729,73 +1713,361 @@ fn process_resolve_challenge(
     if challenger_account.key != &challenge.challenger {
         return Err(SettlementError::Unauthorized.into());
     }
+    let legacy = challenge_account.data_len() < ChallengeAccount::SIZE;
+    if !legacy && state_root_poster.key != &challenge.defender {
+        return Err(SettlementError::InvalidSequencer.into());
+    }
 
     // Load state root
     let mut state_root =
         StateRootAccount::try_from_slice(&state_root_account.data.borrow())?;
 
-    // Validate that state_root_poster matches the sequencer who posted this root
-    if state_root_poster.key != &state_root.sequencer {
-        return Err(SettlementError::InvalidSequencer.into());
-    }
-
-    if args.is_valid {
-        // Challenge accepted: state root is invalid, return escrowed bond to challenger
-        challenge.status = ChallengeStatus::Accepted;
-        state_root.status = StateRootStatus::Invalidated;
-
-        // Return all lamports (rent + escrowed bond) from challenge PDA to challenger
-        let challenge_lamports = challenge_account.lamports();
+    if legacy {
+        // Opened for an admin verdict that no longer exists; nobody moved.
+        let refund = challenge_account.lamports();
         **challenge_account.try_borrow_mut_lamports()? = 0;
         **challenger_account.try_borrow_mut_lamports()? = challenger_account
             .lamports()
-            .checked_add(challenge_lamports)
+            .checked_add(refund)
             .ok_or(SettlementError::Overflow)?;
 
+        let data_len = challenge_account.data.borrow().len();
+        challenge_account.data.borrow_mut()[..data_len].fill(0);
+
+        state_root.open_challenges = state_root.open_challenges.saturating_sub(1);
+        if state_root.status == StateRootStatus::Challenged && state_root.open_challenges == 0 {
+            state_root.status = StateRootStatus::Posted;
+        }
+        state_root.serialize_to_account(&mut state_root_account.data.borrow_mut())?;
+
         msg!(
-            "EVENT:ChallengeAccepted:{{\"l2_slot\":{},\"challenger\":\"{}\",\"bond_returned\":{}}}",
+            "EVENT:LegacyChallengeRefunded:{{\"l2_slot\":{},\"challenger\":\"{}\",\"refund\":{}}}",
             args.l2_slot,
             args.challenger,
-            challenge.bond_amount,
+            refund,
         );
-    } else {
-        // Challenge rejected: slash the escrowed bond to the state root poster
-        challenge.status = ChallengeStatus::Rejected;
+        return Ok(());
+    }
 
-        // Transfer all lamports (rent + escrowed bond) to the state root poster
+    if state_root.status == StateRootStatus::Invalidated {
+        // Another game already invalidated this root — nothing left to decide.
+        // Refund the defender's matched bond; the challenger gets the rest.
         let challenge_lamports = challenge_account.lamports();
+        let challenger_share = challenge_lamports
+            .checked_sub(challenge.defender_bond)
+            .ok_or(SettlementError::Overflow)?;
         **challenge_account.try_borrow_mut_lamports()? = 0;
         **state_root_poster.try_borrow_mut_lamports()? = state_root_poster
             .lamports()
-            .checked_add(challenge_lamports)
+            .checked_add(challenge.defender_bond)
+            .ok_or(SettlementError::Overflow)?;
+        **challenger_account.try_borrow_mut_lamports()? = challenger_account
+            .lamports()
+            .checked_add(challenger_share)
             .ok_or(SettlementError::Overflow)?;
 
-        // If no other active challenges, revert state root to Posted
-        // (simplification: admin can finalize later if no valid challenges remain)
-        if state_root.status == StateRootStatus::Challenged {
-            state_root.status = StateRootStatus::Posted;
-        }
+        let data_len = challenge_account.data.borrow().len();
+        challenge_account.data.borrow_mut()[..data_len].fill(0);
+
+        state_root.open_challenges = state_root.open_challenges.saturating_sub(1);
+        state_root.serialize_to_account(&mut state_root_account.data.borrow_mut())?;
+        track_open_challenge(program_id, set_account, &challenge.defender, false)?;
 
         msg!(
-            "EVENT:ChallengeRejected:{{\"l2_slot\":{},\"challenger\":\"{}\",\"bond_slashed\":{},\"slashed_to\":\"{}\"}}",
+            "EVENT:DisputeRefunded:{{\"l2_slot\":{},\"challenger\":\"{}\",\"challenger_refund\":{},\"defender_refund\":{}}}",
             args.l2_slot,
             args.challenger,
-            challenge.bond_amount,
-            state_root_poster.key,
+            challenger_share,
+            challenge.defender_bond,
         );
+        return Ok(());
     }
 
-    // Zero out challenge account data before closing
-    let data_len = challenge_account.data.borrow().len();
-    challenge_account.data.borrow_mut()[..data_len].fill(0);
+    let clock = Clock::get()?;
+    if clock.slot <= challenge.round_deadline {
+        return Err(SettlementError::DisputeRoundNotExpired.into());
+    }
+
+    // Whoever was due to move has timed out and forfeits.
+    let challenger_won = challenge.turn != DisputeTurn::Challenger;
+    let winner = if challenger_won { challenger_account } else { state_root_poster };
+    settle_dispute(&mut config, &mut state_root, challenge_account, &mut challenge, winner, challenger_won)?;
+
+    state_root.serialize_to_account(&mut state_root_account.data.borrow_mut())?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
+
+    let (set_pda, _) = Pubkey::find_program_address(&[SEQUENCER_SET_SEED], program_id);
+    if set_account.key != &set_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    if challenger_won && !set_account.data_is_empty() {
+        let mut set = load_sequencer_set(program_id, set_account)?;
+        check_bond_vault(program_id, vault_account, token_program)?;
+        check_token_account(challenger_token, token_program, &set.myth_mint, &challenge.challenger)?;
+        slash_sequencer(
+            &mut set,
+            &challenge.defender,
+            vault_account,
+            challenger_token,
+            token_program,
+        )?;
+        set.serialize(&mut &mut set_account.data.borrow_mut()[..])?;
+    } else {
+        track_open_challenge(program_id, set_account, &challenge.defender, false)?;
+    }
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Instruction: Bisect (defender move)
+// Sequencer posts its claimed state hash at the midpoint of the disputed
+// range. The first move escrows a bond matching the challenger's.
+// Accounts: 0=[signer, writable] sequencer, 1=[] state_root PDA,
+//           2=[writable] challenge PDA, 3=[] system_program
+// ---------------------------------------------------------------------------
+
+fn process_bisect(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = BisectArgs::try_from_slice(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let account_iter = &mut accounts.iter();
+    let sequencer = next_account_info(account_iter)?;
+    let state_root_account = next_account_info(account_iter)?;
+    let challenge_account = next_account_info(account_iter)?;
+    let system_program = next_account_info(account_iter)?;
+
+    assert_signer(sequencer)?;
+    assert_writable(challenge_account)?;
+
+    load_challenged_root(program_id, state_root_account, args.l2_slot)?;
+
+    let clock = Clock::get()?;
+    let mut challenge = load_active_dispute(
+        program_id,
+        challenge_account,
+        args.l2_slot,
+        &args.challenger,
+        clock.slot,
+    )?;
+    if sequencer.key != &challenge.defender {
+        return Err(SettlementError::InvalidSequencer.into());
+    }
+    if challenge.turn != DisputeTurn::Defender {
+        return Err(SettlementError::NotYourTurn.into());
+    }
+
+    // Match the challenger's bond on the first move
+    if challenge.defender_bond == 0 && challenge.bond_amount > 0 {
+        invoke(
+            &system_instruction::transfer(
+                sequencer.key,
+                challenge_account.key,
+                challenge.bond_amount,
+            ),
+            &[sequencer.clone(), challenge_account.clone(), system_program.clone()],
+        )?;
+        challenge.defender_bond = challenge.bond_amount;
+    }
+
+    challenge.mid_hash = args.mid_hash;
+    challenge.turn = DisputeTurn::Challenger;
+    challenge.round_deadline = clock
+        .slot
+        .checked_add(DISPUTE_ROUND_SLOTS)
+        .ok_or(SettlementError::Overflow)?;
+
+    challenge.serialize(&mut &mut challenge_account.data.borrow_mut()[..])?;
+
+    let mid_step = challenge.mid_step();
+    msg!(
+        "EVENT:DisputeBisected:{{\"l2_slot\":{},\"challenger\":\"{}\",\"round\":{},\"lo_step\":{},\"mid_step\":{},\"hi_step\":{}}}",
+        args.l2_slot,
+        args.challenger,
+        challenge.round,
+        challenge.lo_step,
+        mid_step,
+        challenge.hi_step,
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Instruction: RespondBisection (challenger move)
+// Challenger agrees or disagrees with the posted midpoint hash, keeping the
+// half of the range that is still disputed.
+// Accounts: 0=[signer] challenger, 1=[] state_root PDA,
+//           2=[writable] challenge PDA
+// ---------------------------------------------------------------------------
+
+fn process_respond_bisection(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = RespondBisectionArgs::try_from_slice(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let account_iter = &mut accounts.iter();
+    let challenger = next_account_info(account_iter)?;
+    let state_root_account = next_account_info(account_iter)?;
+    let challenge_account = next_account_info(account_iter)?;
+
+    assert_signer(challenger)?;
+    assert_writable(challenge_account)?;
+
+    load_challenged_root(program_id, state_root_account, args.l2_slot)?;
+
+    let clock = Clock::get()?;
+    let mut challenge = load_active_dispute(
+        program_id,
+        challenge_account,
+        args.l2_slot,
+        challenger.key,
+        clock.slot,
+    )?;
+    if challenge.turn != DisputeTurn::Challenger {
+        return Err(SettlementError::NotYourTurn.into());
+    }
+
+    challenge.apply_response(args.agree)?;
+    challenge.round_deadline = clock
+        .slot
+        .checked_add(DISPUTE_ROUND_SLOTS)
+        .ok_or(SettlementError::Overflow)?;
+
+    challenge.serialize(&mut &mut challenge_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "EVENT:DisputeResponded:{{\"l2_slot\":{},\"challenger\":\"{}\",\"round\":{},\"agree\":{},\"lo_step\":{},\"hi_step\":{}}}",
+        args.l2_slot,
+        challenger.key,
+        challenge.round,
+        args.agree,
+        challenge.lo_step,
+        challenge.hi_step,
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Instruction: ProveStep (defender, final move)
+// Once a single step remains, the sequencer proves lo_hash -> hi_hash by
+// CPI into the configured step verifier. Success wins the game outright.
+// Accounts: 0=[signer, writable] sequencer, 1=[] config PDA,
+//           2=[writable] state_root PDA, 3=[writable] challenge PDA,
+//           4=[] step verifier program,
+//           5=[writable] sequencer_set PDA (may be uninitialized),
+//           6..=accounts forwarded to the verifier
+// ---------------------------------------------------------------------------
+
+fn process_prove_step(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = ProveStepArgs::try_from_slice(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    if args.proof.len() > MAX_PROOF_DATA_LEN {
+        return Err(SettlementError::ProofDataTooLarge.into());
+    }
+
+    let account_iter = &mut accounts.iter();
+    let sequencer = next_account_info(account_iter)?;
+    let config_account = next_account_info(account_iter)?;
+    let state_root_account = next_account_info(account_iter)?;
+    let challenge_account = next_account_info(account_iter)?;
+    let verifier_program = next_account_info(account_iter)?;
+    let set_account = next_account_info(account_iter)?;
+    let verifier_accounts: Vec<AccountInfo> = account_iter.cloned().collect();
+
+    assert_signer(sequencer)?;
+    assert_writable(sequencer)?;
+    assert_writable(state_root_account)?;
+    assert_writable(challenge_account)?;
+    assert_owned_by(config_account, program_id)?;
+
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[SETTLEMENT_CONFIG_SEED], program_id);
+    if *config_account.key != config_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    let mut config =
+        SettlementConfig::try_from_slice(&config_account.data.borrow())?;
+    if config.step_verifier == Pubkey::default() {
+        return Err(SettlementError::StepVerifierNotSet.into());
+    }
+    if verifier_program.key != &config.step_verifier {
+        return Err(SettlementError::InvalidStepVerifier.into());
+    }
+
+    let mut state_root = load_challenged_root(program_id, state_root_account, args.l2_slot)?;
+
+    let clock = Clock::get()?;
+    let mut challenge = load_active_dispute(
+        program_id,
+        challenge_account,
+        args.l2_slot,
+        &args.challenger,
+        clock.slot,
+    )?;
+    if sequencer.key != &challenge.defender {
+        return Err(SettlementError::InvalidSequencer.into());
+    }
+    if challenge.turn != DisputeTurn::OneStep {
+        return Err(SettlementError::NotYourTurn.into());
+    }
+
+    let verify_args = VerifyStepArgs {
+        l2_slot: args.l2_slot,
+        step: challenge.lo_step,
+        pre_state_hash: challenge.lo_hash,
+        post_state_hash: challenge.hi_hash,
+        transaction_batch_hash: state_root.transaction_batch_hash,
+        proof: args.proof,
+    };
+    let verify_ix = Instruction {
+        program_id: *verifier_program.key,
+        accounts: verifier_accounts
+            .iter()
+            .map(|a| AccountMeta {
+                pubkey: *a.key,
+                is_signer: a.is_signer,
+                is_writable: a.is_writable,
+            })
+            .collect(),
+        data: borsh::to_vec(&verify_args)
+            .map_err(|_| ProgramError::BorshIoError("serialize".to_string()))?,
+    };
+    let mut cpi_accounts = verifier_accounts;
+    cpi_accounts.push(verifier_program.clone());
+    // Any error from the verifier aborts the transaction; the sequencer can
+    // retry until the round deadline, after which the challenger wins.
+    invoke(&verify_ix, &cpi_accounts)?;
+
+    settle_dispute(&mut config, &mut state_root, challenge_account, &mut challenge, sequencer, false)?;
 
-    state_root.serialize(&mut &mut state_root_account.data.borrow_mut()[..])?;
+    state_root.serialize_to_account(&mut state_root_account.data.borrow_mut())?;
+    track_open_challenge(program_id, set_account, &challenge.defender, false)?;
 
     Ok(())
 }
 
 // ---------------------------------------------------------------------------
 // Instruction: FinalizeStateRoot
+// Roots finalize in chain order: each must build on the last finalized root.
+// A root discarded by a rewind is marked Invalidated instead.
+// Accounts: 0=[] caller (anyone), 1=[writable] config PDA,
+//           2=[writable] state_root PDA, 3=[] batch_data PDA,
+//           4=[] (optional) state_root PDA at last_finalized_slot, required
+//             while a pre-v3 config has not recorded the finalized root
 // ---------------------------------------------------------------------------
 
 fn process_finalize_state_root(
@@ -810,6 +2082,8 @@ fn process_finalize_state_root(
     let _caller = next_account_info(account_iter)?; // anyone can call
     let config_account = next_account_info(account_iter)?;
     let state_root_account = next_account_info(account_iter)?;
+    let batch_header_account = next_account_info(account_iter)?;
+    let anchor_account = next_account_info(account_iter).ok();
 
     assert_writable(config_account)?;
     assert_writable(state_root_account)?;
@@ -817,6 +2091,11 @@ fn process_finalize_state_root(
     assert_owned_by(state_root_account, program_id)?;
 
     // Load config
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[SETTLEMENT_CONFIG_SEED], program_id);
+    if *config_account.key != config_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
     let mut config =
         SettlementConfig::try_from_slice(&config_account.data.borrow())?;
     if !config.is_initialized {
@@ -849,12 +2128,63 @@ fn process_finalize_state_root(
         return Err(SettlementError::ChallengePeriodNotExpired.into());
     }
 
+    // A rewind already threw this root away; settle its status so anything
+    // waiting on it (bridge withdrawal challenges) can resolve.
+    if config.is_discarded(args.l2_slot) {
+        state_root.status = StateRootStatus::Invalidated;
+        state_root.serialize_to_account(&mut state_root_account.data.borrow_mut())?;
+        msg!(
+            "EVENT:StateRootDiscarded:{{\"l2_slot\":{}}}",
+            args.l2_slot,
+        );
+        return Ok(());
+    }
+
+    // The root must extend the last finalized root, so nothing built on a
+    // root still under dispute (or later invalidated) can finalize first.
+    let anchor = match config.finalized_anchor() {
+        Some(anchor) => anchor,
+        None => {
+            let anchor_account = anchor_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+            assert_owned_by(anchor_account, program_id)?;
+            let (anchor_pda, _) = Pubkey::find_program_address(
+                &[STATE_ROOT_SEED, &config.last_finalized_slot.to_le_bytes()],
+                program_id,
+            );
+            if anchor_account.key != &anchor_pda {
+                return Err(SettlementError::InvalidPDA.into());
+            }
+            let anchor_root =
+                StateRootAccount::try_from_slice(&anchor_account.data.borrow())?;
+            if anchor_root.status != StateRootStatus::Finalized {
+                return Err(SettlementError::InvalidStateRootStatus.into());
+            }
+            anchor_root.state_root
+        }
+    };
+    if state_root.previous_state_root != anchor {
+        return Err(SettlementError::ParentNotFinalized.into());
+    }
+
+    // The batch behind a root that moves the state must have been published
+    // to L1, whatever transaction count the poster claimed
+    if state_root.transaction_count > 0 || state_root.state_root != state_root.previous_state_root {
+        if batch_header_account.data_is_empty() {
+            return Err(SettlementError::BatchDataUnavailable.into());
+        }
+        let header = load_batch_header(program_id, batch_header_account, args.l2_slot)?;
+        if !header.available {
+            return Err(SettlementError::BatchDataUnavailable.into());
+        }
+    }
+
     state_root.status = StateRootStatus::Finalized;
-    state_root.serialize(&mut &mut state_root_account.data.borrow_mut()[..])?;
+    state_root.serialize_to_account(&mut state_root_account.data.borrow_mut())?;
 
     // Update config
     config.last_finalized_slot = args.l2_slot;
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.last_finalized_root = state_root.state_root;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!(
         "EVENT:StateRootFinalized:{{\"l2_slot\":{}}}",
@@ -866,6 +2196,8 @@ fn process_finalize_state_root(
 
 // ---------------------------------------------------------------------------
 // Instruction: UpdateConfig
+// Immediate admin update. step_verifier can only be set here while it is
+// still unset; replacing it requires the timelocked config update.
 // ---------------------------------------------------------------------------
 
 fn process_update_config(
@@ -906,8 +2238,19 @@ fn process_update_config(
     if let Some(bond) = args.min_challenger_bond {
         config.min_challenger_bond = bond;
     }
+    if let Some(step_verifier) = args.step_verifier {
+        if config_account.data_len() < SettlementConfig::V2_SIZE {
+            return Err(SettlementError::ConfigNotMigrated.into());
+        }
+        // The verifier decides every dispute, so once set it may only be
+        // replaced through ProposeConfigUpdate / ExecuteConfigUpdate.
+        if config.step_verifier != Pubkey::default() {
+            return Err(SettlementError::StepVerifierLocked.into());
+        }
+        config.step_verifier = step_verifier;
+    }
 
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!("EVENT:ConfigUpdated:{{\"admin\":\"{}\"}}", admin.key);
 
@@ -976,7 +2319,7 @@ fn process_pause(
     }
 
     config.is_paused = true;
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!("EVENT:Paused:{{\"admin\":\"{}\"}}", admin.key);
     Ok(())
@@ -1015,7 +2358,7 @@ fn process_unpause(
     }
 
     config.is_paused = false;
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!("EVENT:Unpaused:{{\"admin\":\"{}\"}}", admin.key);
     Ok(())
@@ -1061,7 +2404,7 @@ fn process_propose_admin(
         .map_err(|_| ProgramError::InvalidInstructionData)?;
 
     config.pending_admin = new_admin;
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!(
         "EVENT:ProposeAdmin:{{\"current_admin\":\"{}\",\"pending_admin\":\"{}\"}}",
@@ -1110,7 +2453,7 @@ fn process_accept_admin(
     let old_admin = config.admin;
     config.admin = config.pending_admin;
     config.pending_admin = Pubkey::default();
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     msg!(
         "EVENT:AcceptAdmin:{{\"old_admin\":\"{}\",\"new_admin\":\"{}\"}}",
@@ -1199,6 +2542,7 @@ fn process_propose_config_update(
         sequencer: args.sequencer,
         challenge_period_slots: args.challenge_period_slots,
         min_challenger_bond: args.min_challenger_bond,
+        step_verifier: args.step_verifier,
         propose_slot,
         execution_slot,
         proposer: *admin.key,
@@ -1283,8 +2627,14 @@ fn process_execute_config_update(
     if let Some(bond) = pending.min_challenger_bond {
         config.min_challenger_bond = bond;
     }
+    if let Some(step_verifier) = pending.step_verifier {
+        if config_account.data_len() < SettlementConfig::V2_SIZE {
+            return Err(SettlementError::ConfigNotMigrated.into());
+        }
+        config.step_verifier = step_verifier;
+    }
 
-    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    config.serialize_to_account(&mut config_account.data.borrow_mut())?;
 
     // Close the pending config account: zero data, return lamports to admin
     let pending_lamports = pending_config_account.lamports();
@@ -1362,6 +2712,11 @@ fn process_close_state_root(
     if state_root.status != StateRootStatus::Finalized {
         return Err(SettlementError::StateRootNotCloseable.into());
     }
+    // A pre-v3 config reads the finalized root from this account until it
+    // has recorded one itself
+    if config.finalized_anchor().is_none() && args.l2_slot == config.last_finalized_slot {
+        return Err(SettlementError::StateRootNotCloseable.into());
+    }
 
     // Zero account data before closing
     let data_len = state_root_account.data.borrow().len();
@@ -1383,3 +2738,1054 @@ fn process_close_state_root(
 
     Ok(())
 }
+
+// ---------------------------------------------------------------------------
+// Instruction: InitializeSequencerSet (admin-only)
+// Creates the sequencer set and the MYTH bond vault. Until at least one
+// sequencer is active, posting falls back to SettlementConfig.sequencer.
+// Accounts: 0=[signer, writable] admin, 1=[] config PDA,
+//           2=[writable] sequencer_set PDA, 3=[writable] bond vault PDA,
+//           4=[] MYTH mint, 5=[] token program, 6=[] system_program,
+//           7=[] rent sysvar
+// ---------------------------------------------------------------------------
+
+fn process_initialize_sequencer_set(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = InitializeSequencerSetArgs::try_from_slice(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let account_iter = &mut accounts.iter();
+    let admin = next_account_info(account_iter)?;
+    let config_account = next_account_info(account_iter)?;
+    let set_account = next_account_info(account_iter)?;
+    let vault_account = next_account_info(account_iter)?;
+    let myth_mint = next_account_info(account_iter)?;
+    let token_program = next_account_info(account_iter)?;
+    let system_program = next_account_info(account_iter)?;
+    let rent_sysvar = next_account_info(account_iter)?;
+
+    assert_signer(admin)?;
+    assert_writable(set_account)?;
+    assert_writable(vault_account)?;
+    assert_owned_by(config_account, program_id)?;
+
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[SETTLEMENT_CONFIG_SEED], program_id);
+    if *config_account.key != config_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    let config =
+        SettlementConfig::try_from_slice(&config_account.data.borrow())?;
+    if !config.is_initialized {
+        return Err(SettlementError::NotInitialized.into());
+    }
+    if admin.key != &config.admin {
+        return Err(SettlementError::Unauthorized.into());
+    }
+
+    if args.rotation_slots == 0 || args.skip_after_slots == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    // Bonds must stay slashable for every root the sequencer could have posted
+    if args.exit_delay_slots < config.challenge_period_slots {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if !is_valid_token_program(token_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (set_pda, set_bump) =
+        Pubkey::find_program_address(&[SEQUENCER_SET_SEED], program_id);
+    if set_account.key != &set_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    if !set_account.data_is_empty() {
+        return Err(SettlementError::AlreadyInitialized.into());
+    }
+
+    let (vault_pda, vault_bump) =
+        Pubkey::find_program_address(&[SEQUENCER_VAULT_SEED], program_id);
+    if vault_account.key != &vault_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        admin,
+        SequencerSet::SIZE,
+        program_id,
+        system_program,
+        set_account,
+        &[SEQUENCER_SET_SEED, &[set_bump]],
+    )?;
+
+    // SPL Token account size is 165 bytes for both legacy and Token-2022
+    create_pda_account(
+        admin,
+        165,
+        token_program.key,
+        system_program,
+        vault_account,
+        &[SEQUENCER_VAULT_SEED, &[vault_bump]],
+    )?;
+    // The vault PDA is its own token authority
+    invoke_signed(
+        &build_init_account_ix(token_program.key, vault_account.key, myth_mint.key, &vault_pda),
+        &[
+            vault_account.clone(),
+            myth_mint.clone(),
+            vault_account.clone(),
+            rent_sysvar.clone(),
+            token_program.clone(),
+        ],
+        &[&[SEQUENCER_VAULT_SEED, &[vault_bump]]],
+    )?;
+
+    let clock = Clock::get()?;
+    let set = SequencerSet {
+        is_initialized: true,
+        myth_mint: *myth_mint.key,
+        min_bond: args.min_bond,
+        rotation_slots: args.rotation_slots,
+        skip_after_slots: args.skip_after_slots,
+        exit_delay_slots: args.exit_delay_slots,
+        last_post_slot: clock.slot,
+        vault_bump,
+        bump: set_bump,
+        members: Vec::new(),
+    };
+    set.serialize(&mut &mut set_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "EVENT:SequencerSetInitialized:{{\"myth_mint\":\"{}\",\"min_bond\":{},\"rotation_slots\":{},\"exit_delay_slots\":{}}}",
+        myth_mint.key,
+        args.min_bond,
+        args.rotation_slots,
+        args.exit_delay_slots,
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Instruction: RegisterSequencer (discriminator 26)
+// Joins the set (or tops up an existing bond) by depositing MYTH.
+// Accounts: 0=[signer] sequencer, 1=[writable] sequencer_set PDA,
+//           2=[writable] sequencer MYTH token account,
+//           3=[writable] bond vault PDA, 4=[] token program
+// ---------------------------------------------------------------------------
+
+fn process_register_sequencer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = RegisterSequencerArgs::try_from_slice(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    if args.bond_amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let account_iter = &mut accounts.iter();
+    let sequencer = next_account_info(account_iter)?;
+    let set_account = next_account_info(account_iter)?;
+    let source_token = next_account_info(account_iter)?;
+    let vault_account = next_account_info(account_iter)?;
+    let token_program = next_account_info(account_iter)?;
+
+    assert_signer(sequencer)?;
+    assert_writable(source_token)?;
+
+    let mut set = load_sequencer_set(program_id, set_account)?;
+    check_bond_vault(program_id, vault_account, token_program)?;
+
+    invoke(
+        &build_token_transfer_ix(
+            token_program.key,
+            source_token.key,
+            vault_account.key,
+            sequencer.key,
+            args.bond_amount,
+        ),
+        &[
+            source_token.clone(),
+            vault_account.clone(),
+            sequencer.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    let clock = Clock::get()?;
+    let total_bond = match set.members.iter_mut().find(|m| &m.sequencer == sequencer.key) {
+        Some(member) => {
+            if member.exit_requested_at != 0 {
+                return Err(SettlementError::SequencerExiting.into());
+            }
+            member.bond = member
+                .bond
+                .checked_add(args.bond_amount)
+                .ok_or(SettlementError::Overflow)?;
+            member.bond
+        }
+        None => {
+            if set.members.len() >= MAX_SEQUENCERS {
+                return Err(SettlementError::SequencerSetFull.into());
+            }
+            if args.bond_amount < set.min_bond {
+                return Err(SettlementError::BondBelowMinimum.into());
+            }
+            set.members.push(SequencerEntry {
+                sequencer: *sequencer.key,
+                bond: args.bond_amount,
+                registered_at: clock.slot,
+                exit_requested_at: 0,
+                open_challenges: 0,
+            });
+            args.bond_amount
+        }
+    };
+
+    set.serialize(&mut &mut set_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "EVENT:SequencerRegistered:{{\"sequencer\":\"{}\",\"deposit\":{},\"bond\":{}}}",
+        sequencer.key,
+        args.bond_amount,
+        total_bond,
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Instruction: RequestSequencerExit
+// Leaves the rotation immediately; the bond stays slashable until the exit
+// delay has passed.
+// Accounts: 0=[signer] sequencer, 1=[writable] sequencer_set PDA
+// ---------------------------------------------------------------------------
+
+fn process_request_sequencer_exit(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+    let sequencer = next_account_info(account_iter)?;
+    let set_account = next_account_info(account_iter)?;
+
+    assert_signer(sequencer)?;
+
+    let mut set = load_sequencer_set(program_id, set_account)?;
+    let clock = Clock::get()?;
+
+    let member = set
+        .members
+        .iter_mut()
+        .find(|m| &m.sequencer == sequencer.key)
+        .ok_or(SettlementError::SequencerNotRegistered)?;
+    if member.exit_requested_at != 0 {
+        return Err(SettlementError::SequencerExiting.into());
+    }
+    member.exit_requested_at = clock.slot;
+
+    set.serialize(&mut &mut set_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "EVENT:SequencerExitRequested:{{\"sequencer\":\"{}\",\"withdrawable_at\":{}}}",
+        sequencer.key,
+        clock.slot.saturating_add(set.exit_delay_slots),
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Instruction: WithdrawSequencerBond
+// Returns the bond once the exit delay has elapsed and no dispute against the
+// sequencer's roots is still open, and removes the member.
+// Accounts: 0=[signer] sequencer, 1=[writable] sequencer_set PDA,
+//           2=[writable] bond vault PDA,
+//           3=[writable] sequencer MYTH token account, 4=[] token program
+// ---------------------------------------------------------------------------
+
+fn process_withdraw_sequencer_bond(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+    let sequencer = next_account_info(account_iter)?;
+    let set_account = next_account_info(account_iter)?;
+    let vault_account = next_account_info(account_iter)?;
+    let destination_token = next_account_info(account_iter)?;
+    let token_program = next_account_info(account_iter)?;
+
+    assert_signer(sequencer)?;
+    assert_writable(destination_token)?;
+
+    let mut set = load_sequencer_set(program_id, set_account)?;
+    check_bond_vault(program_id, vault_account, token_program)?;
+
+    let index = set
+        .members
+        .iter()
+        .position(|m| &m.sequencer == sequencer.key)
+        .ok_or(SettlementError::SequencerNotRegistered)?;
+    let member = &set.members[index];
+    if member.exit_requested_at == 0 {
+        return Err(SettlementError::ExitDelayNotElapsed.into());
+    }
+    if member.open_challenges > 0 {
+        return Err(SettlementError::SequencerHasOpenChallenges.into());
+    }
+    let clock = Clock::get()?;
+    let withdrawable_at = member
+        .exit_requested_at
+        .checked_add(set.exit_delay_slots)
+        .ok_or(SettlementError::Overflow)?;
+    if clock.slot < withdrawable_at {
+        return Err(SettlementError::ExitDelayNotElapsed.into());
+    }
+
+    let amount = member.bond;
+    if amount > 0 {
+        invoke_signed(
+            &build_token_transfer_ix(
+                token_program.key,
+                vault_account.key,
+                destination_token.key,
+                vault_account.key,
+                amount,
+            ),
+            &[
+                vault_account.clone(),
+                destination_token.clone(),
+                vault_account.clone(),
+                token_program.clone(),
+            ],
+            &[&[SEQUENCER_VAULT_SEED, &[set.vault_bump]]],
+        )?;
+    }
+
+    set.members.remove(index);
+    set.serialize(&mut &mut set_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "EVENT:SequencerBondWithdrawn:{{\"sequencer\":\"{}\",\"amount\":{}}}",
+        sequencer.key,
+        amount,
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Instruction: ProveDoubleSequencing (permissionless)
+// Proves the sequencer that posted a root signed two different state roots
+// for the same L2 slot. The transaction must include ed25519 precompile
+// instructions (before this one) verifying that sequencer's signature over
+// state_root_message() for both roots. The sequencer's whole bond goes to the
+// prover. A root that is still open is invalidated; a Finalized root stays
+// final, since the bridge may already have paid withdrawals against it.
+// Without a sequencer set there is no bond to slash and the proof only
+// invalidates the root. Invalidating an open root rewinds the chain tip.
+// Accounts: 0=[signer] challenger, 1=[writable] config PDA,
+//           2=[writable] state_root PDA,
+//           3=[writable] sequencer_set PDA (may be uninitialized),
+//           4=[writable] bond vault PDA, 5=[writable] challenger MYTH token account,
+//           6=[] token program, 7=[] instructions sysvar
+// This replaced the lamport-bond layout (3=[writable] sequencer_bond PDA,
+// 4=[] instructions sysvar) when bonds moved into the sequencer set.
+// ---------------------------------------------------------------------------
+
+fn process_prove_double_sequencing(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = ProveDoubleSequencingArgs::try_from_slice(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    if args.root_a == args.root_b {
+        return Err(SettlementError::RootsNotConflicting.into());
+    }
+
+    let account_iter = &mut accounts.iter();
+    let challenger = next_account_info(account_iter)?;
+    let config_account = next_account_info(account_iter)?;
+    let state_root_account = next_account_info(account_iter)?;
+    let set_account = next_account_info(account_iter)?;
+    let vault_account = next_account_info(account_iter)?;
+    let challenger_token = next_account_info(account_iter)?;
+    let token_program = next_account_info(account_iter)?;
+    let instructions_sysvar = next_account_info(account_iter)?;
+
+    assert_signer(challenger)?;
+    assert_writable(config_account)?;
+    assert_writable(state_root_account)?;
+    assert_writable(challenger_token)?;
+    assert_owned_by(config_account, program_id)?;
+    assert_owned_by(state_root_account, program_id)?;
+
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[SETTLEMENT_CONFIG_SEED], program_id);
+    if *config_account.key != config_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    let mut config =
+        SettlementConfig::try_from_slice(&config_account.data.borrow())?;
+    if !config.is_initialized {
+        return Err(SettlementError::NotInitialized.into());
+    }
+
+    // Validate state root PDA
+    let (state_root_pda, _) = Pubkey::find_program_address(
+        &[STATE_ROOT_SEED, &args.l2_slot.to_le_bytes()],
+        program_id,
+    );
+    if state_root_account.key != &state_root_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    let mut state_root =
+        StateRootAccount::try_from_slice(&state_root_account.data.borrow())?;
+    if state_root.status == StateRootStatus::Invalidated {
+        return Err(SettlementError::InvalidStateRootStatus.into());
+    }
+
+    let (set_pda, _) = Pubkey::find_program_address(&[SEQUENCER_SET_SEED], program_id);
+    if set_account.key != &set_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    let mut sequencer_set = if set_account.data_is_empty() {
+        None
+    } else {
+        let set = load_sequencer_set(program_id, set_account)?;
+        check_bond_vault(program_id, vault_account, token_program)?;
+        check_token_account(challenger_token, token_program, &set.myth_mint, challenger.key)?;
+        Some(set)
+    };
+
+    // Both conflicting commitments must carry a verified signature from the poster
+    let signatures = load_ed25519_signatures(instructions_sysvar)?;
+    let message_a = state_root_message(&config.l2_chain_id, args.l2_slot, &args.root_a);
+    let message_b = state_root_message(&config.l2_chain_id, args.l2_slot, &args.root_b);
+    let signed = |message: &[u8]| {
+        signatures
+            .iter()
+            .any(|(pubkey, signed)| pubkey == &state_root.sequencer && signed == message)
+    };
+    if !signed(&message_a) || !signed(&message_b) {
+        return Err(SettlementError::MissingSequencerSignature.into());
+    }
+
+    let invalidated = state_root.status != StateRootStatus::Finalized;
+    if invalidated {
+        invalidate_state_root(&mut config, &mut state_root);
+        state_root.serialize_to_account(&mut state_root_account.data.borrow_mut())?;
+        config.serialize_to_account(&mut config_account.data.borrow_mut())?;
+    }
+
+    let slashed = match sequencer_set.as_mut() {
+        Some(set) => {
+            let slashed = slash_sequencer(
+                set,
+                &state_root.sequencer,
+                vault_account,
+                challenger_token,
+                token_program,
+            )?;
+            set.serialize(&mut &mut set_account.data.borrow_mut()[..])?;
+            slashed
+        }
+        None => 0,
+    };
+
+    msg!(
+        "EVENT:DoubleSequencingProven:{{\"l2_slot\":{},\"sequencer\":\"{}\",\"challenger\":\"{}\",\"fraud_type\":{},\"slashed\":{},\"invalidated\":{}}}",
+        args.l2_slot,
+        state_root.sequencer,
+        challenger.key,
+        FraudProofType::DoubleSequencing as u8,
+        slashed,
+        invalidated,
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Instruction: InitBatchData
+// Opens the data-availability header for a posted root. Only the sequencer
+// that posted the root may publish its batch.
+// Accounts: 0=[signer, writable] poster, 1=[] state_root PDA,
+//           2=[writable] batch_data PDA, 3=[] system_program
+// ---------------------------------------------------------------------------
+
+fn process_init_batch_data(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = InitBatchDataArgs::try_from_slice(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let account_iter = &mut accounts.iter();
+    let poster = next_account_info(account_iter)?;
+    let state_root_account = next_account_info(account_iter)?;
+    let header_account = next_account_info(account_iter)?;
+    let system_program = next_account_info(account_iter)?;
+
+    assert_signer(poster)?;
+    assert_writable(header_account)?;
+    assert_owned_by(state_root_account, program_id)?;
+
+    let l2_slot_bytes = args.l2_slot.to_le_bytes();
+    let (state_root_pda, _) =
+        Pubkey::find_program_address(&[STATE_ROOT_SEED, &l2_slot_bytes], program_id);
+    if state_root_account.key != &state_root_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    let state_root =
+        StateRootAccount::try_from_slice(&state_root_account.data.borrow())?;
+    if poster.key != &state_root.sequencer {
+        return Err(SettlementError::InvalidSequencer.into());
+    }
+
+    let total_len = args.total_len as usize;
+    let chunk_count = total_len.div_ceil(BATCH_CHUNK_SIZE);
+    if chunk_count == 0 || chunk_count > MAX_BATCH_CHUNKS {
+        return Err(SettlementError::InvalidBatchChunk.into());
+    }
+
+    let (header_pda, header_bump) =
+        Pubkey::find_program_address(&[BATCH_DATA_SEED, &l2_slot_bytes], program_id);
+    if header_account.key != &header_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    if !header_account.data_is_empty() {
+        return Err(SettlementError::AlreadyInitialized.into());
+    }
+
+    create_pda_account(
+        poster,
+        BatchDataHeader::size(chunk_count),
+        program_id,
+        system_program,
+        header_account,
+        &[BATCH_DATA_SEED, &l2_slot_bytes, &[header_bump]],
+    )?;
+
+    let header = BatchDataHeader {
+        l2_slot: args.l2_slot,
+        poster: *poster.key,
+        total_len: args.total_len,
+        available: false,
+        bump: header_bump,
+        chunk_hashes: vec![[0u8; 32]; chunk_count],
+    };
+    header.serialize(&mut &mut header_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "EVENT:BatchDataInitialized:{{\"l2_slot\":{},\"total_len\":{},\"chunks\":{}}}",
+        args.l2_slot,
+        args.total_len,
+        chunk_count,
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Instruction: WriteBatchChunk
+// Writes bytes into one chunk account, creating it on first write. Any write
+// unseals the chunk.
+// Accounts: 0=[signer, writable] poster, 1=[writable] batch_data PDA,
+//           2=[writable] batch_chunk PDA, 3=[] system_program
+// ---------------------------------------------------------------------------
+
+fn process_write_batch_chunk(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = WriteBatchChunkArgs::try_from_slice(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let account_iter = &mut accounts.iter();
+    let poster = next_account_info(account_iter)?;
+    let header_account = next_account_info(account_iter)?;
+    let chunk_account = next_account_info(account_iter)?;
+    let system_program = next_account_info(account_iter)?;
+
+    assert_signer(poster)?;
+    assert_writable(header_account)?;
+    assert_writable(chunk_account)?;
+
+    let mut header = load_batch_header(program_id, header_account, args.l2_slot)?;
+    if poster.key != &header.poster {
+        return Err(SettlementError::Unauthorized.into());
+    }
+    if header.available {
+        return Err(SettlementError::BatchDataSealed.into());
+    }
+
+    let chunk_index = args.chunk_index as usize;
+    if chunk_index >= header.chunk_hashes.len() {
+        return Err(SettlementError::InvalidBatchChunk.into());
+    }
+    let chunk_len = header.chunk_len(chunk_index);
+    let start = args.offset as usize;
+    let end = start
+        .checked_add(args.data.len())
+        .ok_or(SettlementError::Overflow)?;
+    if end > chunk_len {
+        return Err(SettlementError::InvalidBatchChunk.into());
+    }
+
+    let (chunk_pda, chunk_bump) = batch_chunk_pda(program_id, args.l2_slot, args.chunk_index);
+    if chunk_account.key != &chunk_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    if chunk_account.data_is_empty() {
+        create_pda_account(
+            poster,
+            chunk_len,
+            program_id,
+            system_program,
+            chunk_account,
+            &[
+                BATCH_CHUNK_SEED,
+                &args.l2_slot.to_le_bytes(),
+                &args.chunk_index.to_le_bytes(),
+                &[chunk_bump],
+            ],
+        )?;
+    } else {
+        assert_owned_by(chunk_account, program_id)?;
+    }
+
+    chunk_account.data.borrow_mut()[start..end].copy_from_slice(&args.data);
+    header.chunk_hashes[chunk_index] = [0u8; 32];
+    header.serialize(&mut &mut header_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Instruction: SealBatchChunks (permissionless)
+// Hashes the passed chunk accounts into the header. Once every chunk is
+// sealed, the combined hash must equal the root's transaction_batch_hash and
+// the batch is marked available.
+// Accounts: 0=[] state_root PDA, 1=[writable] batch_data PDA,
+//           2..=[] batch_chunk PDAs from first_chunk, in order
+// ---------------------------------------------------------------------------
+
+fn process_seal_batch_chunks(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SealBatchChunksArgs::try_from_slice(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let account_iter = &mut accounts.iter();
+    let state_root_account = next_account_info(account_iter)?;
+    let header_account = next_account_info(account_iter)?;
+
+    assert_writable(header_account)?;
+    assert_owned_by(state_root_account, program_id)?;
+
+    let (state_root_pda, _) = Pubkey::find_program_address(
+        &[STATE_ROOT_SEED, &args.l2_slot.to_le_bytes()],
+        program_id,
+    );
+    if state_root_account.key != &state_root_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    let state_root =
+        StateRootAccount::try_from_slice(&state_root_account.data.borrow())?;
+
+    let mut header = load_batch_header(program_id, header_account, args.l2_slot)?;
+    if header.available {
+        return Err(SettlementError::BatchDataSealed.into());
+    }
+
+    for (chunk_index, chunk_account) in (args.first_chunk as usize..).zip(account_iter) {
+        if chunk_index >= header.chunk_hashes.len() {
+            return Err(SettlementError::InvalidBatchChunk.into());
+        }
+        let (chunk_pda, _) = batch_chunk_pda(program_id, args.l2_slot, chunk_index as u16);
+        if chunk_account.key != &chunk_pda {
+            return Err(SettlementError::InvalidPDA.into());
+        }
+        assert_owned_by(chunk_account, program_id)?;
+        let chunk_data = chunk_account.data.borrow();
+        if chunk_data.len() != header.chunk_len(chunk_index) {
+            return Err(SettlementError::InvalidBatchChunk.into());
+        }
+        header.chunk_hashes[chunk_index] = hash_proof_data(&chunk_data);
+    }
+
+    if let Some(batch_hash) = header.batch_hash() {
+        if batch_hash != state_root.transaction_batch_hash {
+            return Err(SettlementError::BatchHashMismatch.into());
+        }
+        header.available = true;
+
+        msg!(
+            "EVENT:BatchDataAvailable:{{\"l2_slot\":{},\"total_len\":{},\"chunks\":{}}}",
+            args.l2_slot,
+            header.total_len,
+            header.chunk_hashes.len(),
+        );
+    }
+
+    header.serialize(&mut &mut header_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Instruction: CloseBatchData
+// Reclaims rent from batch data once the root is Finalized, Invalidated or
+// already closed.
+// Accounts: 0=[signer, writable] poster, 1=[] state_root PDA,
+//           2=[writable] batch_data PDA,
+//           3..=[writable] batch_chunk PDAs from first_chunk, in order
+// ---------------------------------------------------------------------------
+
+fn process_close_batch_data(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = CloseBatchDataArgs::try_from_slice(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let account_iter = &mut accounts.iter();
+    let poster = next_account_info(account_iter)?;
+    let state_root_account = next_account_info(account_iter)?;
+    let header_account = next_account_info(account_iter)?;
+
+    assert_signer(poster)?;
+    assert_writable(poster)?;
+    assert_writable(header_account)?;
+
+    let (state_root_pda, _) = Pubkey::find_program_address(
+        &[STATE_ROOT_SEED, &args.l2_slot.to_le_bytes()],
+        program_id,
+    );
+    if state_root_account.key != &state_root_pda {
+        return Err(SettlementError::InvalidPDA.into());
+    }
+    // A closed state root has already been finalized
+    if !state_root_account.data_is_empty() {
+        assert_owned_by(state_root_account, program_id)?;
+        let state_root =
+            StateRootAccount::try_from_slice(&state_root_account.data.borrow())?;
+        if state_root.status != StateRootStatus::Finalized
+            && state_root.status != StateRootStatus::Invalidated
+        {
+            return Err(SettlementError::InvalidStateRootStatus.into());
+        }
+    }
+
+    let header = load_batch_header(program_id, header_account, args.l2_slot)?;
+    if poster.key != &header.poster {
+        return Err(SettlementError::Unauthorized.into());
+    }
+
+    let mut chunk_index = args.first_chunk;
+    let mut closed = 0u32;
+    for chunk_account in account_iter {
+        let (chunk_pda, _) = batch_chunk_pda(program_id, args.l2_slot, chunk_index);
+        if chunk_account.key != &chunk_pda {
+            return Err(SettlementError::InvalidPDA.into());
+        }
+        assert_writable(chunk_account)?;
+        if !chunk_account.data_is_empty() {
+            assert_owned_by(chunk_account, program_id)?;
+            close_account(chunk_account, poster)?;
+            closed += 1;
+        }
+        chunk_index = chunk_index.checked_add(1).ok_or(SettlementError::Overflow)?;
+    }
+
+    if args.close_header {
+        close_account(header_account, poster)?;
+    }
+
+    msg!(
+        "EVENT:BatchDataClosed:{{\"l2_slot\":{},\"chunks_closed\":{},\"header_closed\":{}}}",
+        args.l2_slot,
+        closed,
+        args.close_header,
+    );
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_config() -> SettlementConfig {
+        SettlementConfig {
+            is_initialized: true,
+            admin: Pubkey::new_unique(),
+            sequencer: Pubkey::new_unique(),
+            challenge_period_slots: DEFAULT_CHALLENGE_PERIOD_SLOTS,
+            l2_chain_id: [7u8; 16],
+            min_challenger_bond: 1_000_000,
+            last_posted_slot: 42,
+            last_state_root: [9u8; 32],
+            last_finalized_slot: 40,
+            total_roots_posted: 3,
+            total_challenges: 1,
+            is_paused: false,
+            bump: 254,
+            pending_admin: Pubkey::default(),
+            step_verifier: Pubkey::new_unique(),
+            last_finalized_root: [6u8; 32],
+            rewound_through_slot: 0,
+        }
+    }
+
+    fn sample_challenge(transaction_count: u32) -> ChallengeAccount {
+        let hi_step = transaction_count.max(1);
+        ChallengeAccount {
+            l2_slot: 100,
+            challenger: Pubkey::new_unique(),
+            fraud_proof_type: FraudProofType::InvalidStateTransition,
+            proof_data_hash: [0u8; 32],
+            bond_amount: 1_000_000,
+            created_at: 0,
+            status: ChallengeStatus::Active,
+            bump: 255,
+            defender: Pubkey::new_unique(),
+            defender_bond: 0,
+            lo_step: 0,
+            lo_hash: [1u8; 32],
+            hi_step,
+            hi_hash: [2u8; 32],
+            mid_hash: [0u8; 32],
+            turn: if hi_step == 1 { DisputeTurn::OneStep } else { DisputeTurn::Defender },
+            round: 0,
+            round_deadline: DISPUTE_ROUND_SLOTS,
+        }
+    }
+
+    #[test]
+    fn test_settlement_config_size() {
+        let serialized = borsh::to_vec(&sample_config()).unwrap();
+        assert_eq!(serialized.len(), SettlementConfig::SIZE);
+    }
+
+    #[test]
+    fn test_v1_config_deserializes_without_step_verifier() {
+        let config = sample_config();
+        let serialized = borsh::to_vec(&config).unwrap();
+        let v1 = &serialized[..SettlementConfig::V1_SIZE];
+
+        let decoded = SettlementConfig::try_from_slice(v1).unwrap();
+        assert_eq!(decoded.admin, config.admin);
+        assert_eq!(decoded.pending_admin, config.pending_admin);
+        assert_eq!(decoded.last_state_root, config.last_state_root);
+        assert_eq!(decoded.step_verifier, Pubkey::default());
+    }
+
+    #[test]
+    fn test_v2_config_deserializes_without_finalized_root() {
+        let config = sample_config();
+        let serialized = borsh::to_vec(&config).unwrap();
+        let v2 = &serialized[..SettlementConfig::V2_SIZE];
+
+        let decoded = SettlementConfig::try_from_slice(v2).unwrap();
+        assert_eq!(decoded.step_verifier, config.step_verifier);
+        assert_eq!(decoded.last_finalized_root, [0u8; 32]);
+        assert_eq!(decoded.rewound_through_slot, 0);
+        // Past genesis the finalized root has to be read from its account
+        assert_eq!(decoded.finalized_anchor(), None);
+    }
+
+    #[test]
+    fn test_v3_config_round_trips() {
+        let mut config = sample_config();
+        config.rewound_through_slot = 42;
+        let serialized = borsh::to_vec(&config).unwrap();
+        let decoded = SettlementConfig::try_from_slice(&serialized).unwrap();
+        assert_eq!(decoded.step_verifier, config.step_verifier);
+        assert_eq!(decoded.total_roots_posted, config.total_roots_posted);
+        assert_eq!(decoded.finalized_anchor(), Some(config.last_finalized_root));
+        assert!(decoded.is_discarded(42));
+        assert!(!decoded.is_discarded(43));
+    }
+
+    #[test]
+    fn test_config_serialize_to_v1_account_truncates() {
+        let config = sample_config();
+        let mut data = vec![0u8; SettlementConfig::V1_SIZE];
+        config.serialize_to_account(&mut data).unwrap();
+
+        let decoded = SettlementConfig::try_from_slice(&data).unwrap();
+        assert_eq!(decoded.admin, config.admin);
+        assert_eq!(decoded.step_verifier, Pubkey::default());
+    }
+
+    #[test]
+    fn test_v1_state_root_deserializes_with_no_open_challenges() {
+        let root = StateRootAccount {
+            l2_slot: 100,
+            state_root: [3u8; 32],
+            transaction_count: 8,
+            transaction_batch_hash: [4u8; 32],
+            ai_attestation_count: 0,
+            previous_state_root: [5u8; 32],
+            sequencer: Pubkey::new_unique(),
+            posted_at: 1,
+            challenge_deadline: 2,
+            status: StateRootStatus::Challenged,
+            bump: 255,
+            open_challenges: 2,
+        };
+        let serialized = borsh::to_vec(&root).unwrap();
+        assert_eq!(serialized.len(), StateRootAccount::SIZE);
+
+        let v1 = StateRootAccount::try_from_slice(&serialized[..StateRootAccount::V1_SIZE]).unwrap();
+        assert_eq!(v1.status, StateRootStatus::Challenged);
+        assert_eq!(v1.open_challenges, 0);
+
+        let v2 = StateRootAccount::try_from_slice(&serialized).unwrap();
+        assert_eq!(v2.open_challenges, 2);
+    }
+
+    #[test]
+    fn test_challenge_account_size() {
+        let serialized = borsh::to_vec(&sample_challenge(8)).unwrap();
+        assert_eq!(serialized.len(), ChallengeAccount::SIZE);
+    }
+
+    #[test]
+    fn test_v1_challenge_deserializes_without_a_game() {
+        let challenge = sample_challenge(8);
+        let serialized = borsh::to_vec(&challenge).unwrap();
+        let v1 = &serialized[..ChallengeAccount::V1_SIZE];
+
+        let decoded = ChallengeAccount::try_from_slice(v1).unwrap();
+        assert_eq!(decoded.challenger, challenge.challenger);
+        assert_eq!(decoded.bond_amount, challenge.bond_amount);
+        assert_eq!(decoded.status, ChallengeStatus::Active);
+        assert_eq!(decoded.defender, Pubkey::default());
+        assert_eq!(decoded.defender_bond, 0);
+        assert_eq!(decoded.round_deadline, 0);
+    }
+
+    #[test]
+    fn test_bisection_narrows_to_one_step() {
+        let mut challenge = sample_challenge(8);
+        assert_eq!(challenge.turn, DisputeTurn::Defender);
+
+        // [0, 8] -> agree at 4 -> [4, 8]
+        assert_eq!(challenge.mid_step(), 4);
+        challenge.mid_hash = [44u8; 32];
+        challenge.apply_response(true).unwrap();
+        assert_eq!((challenge.lo_step, challenge.hi_step), (4, 8));
+        assert_eq!(challenge.lo_hash, [44u8; 32]);
+        assert_eq!(challenge.hi_hash, [2u8; 32]);
+        assert_eq!(challenge.mid_hash, [0u8; 32]);
+        assert_eq!(challenge.turn, DisputeTurn::Defender);
+
+        // [4, 8] -> disagree at 6 -> [4, 6]
+        assert_eq!(challenge.mid_step(), 6);
+        challenge.mid_hash = [66u8; 32];
+        challenge.apply_response(false).unwrap();
+        assert_eq!((challenge.lo_step, challenge.hi_step), (4, 6));
+        assert_eq!(challenge.hi_hash, [66u8; 32]);
+        assert_eq!(challenge.turn, DisputeTurn::Defender);
+
+        // [4, 6] -> agree at 5 -> [5, 6], a single step remains
+        challenge.mid_hash = [55u8; 32];
+        challenge.apply_response(true).unwrap();
+        assert_eq!((challenge.lo_step, challenge.hi_step), (5, 6));
+        assert_eq!(challenge.turn, DisputeTurn::OneStep);
+        assert_eq!(challenge.round, 3);
+    }
+
+    #[test]
+    fn test_bisection_odd_range_terminates() {
+        let mut challenge = sample_challenge(5);
+        let mut rounds = 0;
+        while challenge.turn == DisputeTurn::Defender {
+            challenge.apply_response(false).unwrap();
+            rounds += 1;
+            assert!(rounds <= 3, "5 steps must narrow within ceil(log2(5)) rounds");
+        }
+        assert_eq!(challenge.hi_step - challenge.lo_step, 1);
+        assert_eq!(challenge.turn, DisputeTurn::OneStep);
+    }
+
+    fn sample_batch_header(total_len: usize) -> BatchDataHeader {
+        BatchDataHeader {
+            l2_slot: 100,
+            poster: Pubkey::new_unique(),
+            total_len: total_len as u32,
+            available: false,
+            bump: 255,
+            chunk_hashes: vec![[0u8; 32]; total_len.div_ceil(BATCH_CHUNK_SIZE)],
+        }
+    }
+
+    #[test]
+    fn test_batch_header_size() {
+        let header = sample_batch_header(3 * BATCH_CHUNK_SIZE);
+        let serialized = borsh::to_vec(&header).unwrap();
+        assert_eq!(serialized.len(), BatchDataHeader::size(3));
+    }
+
+    #[test]
+    fn test_batch_chunk_len_trims_last_chunk() {
+        let header = sample_batch_header(2 * BATCH_CHUNK_SIZE + 10);
+        assert_eq!(header.chunk_hashes.len(), 3);
+        assert_eq!(header.chunk_len(0), BATCH_CHUNK_SIZE);
+        assert_eq!(header.chunk_len(1), BATCH_CHUNK_SIZE);
+        assert_eq!(header.chunk_len(2), 10);
+
+        let exact = sample_batch_header(BATCH_CHUNK_SIZE);
+        assert_eq!(exact.chunk_hashes.len(), 1);
+        assert_eq!(exact.chunk_len(0), BATCH_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn test_batch_hash_matches_chunked_commitment() {
+        let batch: Vec<u8> = (0..BATCH_CHUNK_SIZE + 100).map(|i| i as u8).collect();
+        let mut header = sample_batch_header(batch.len());
+        assert_eq!(header.batch_hash(), None);
+
+        let mut chunks = batch.chunks(BATCH_CHUNK_SIZE);
+        header.chunk_hashes[0] = hash_proof_data(chunks.next().unwrap());
+        assert_eq!(header.batch_hash(), None, "one chunk is still unsealed");
+        header.chunk_hashes[1] = hash_proof_data(chunks.next().unwrap());
+
+        // Same commitment the SDK's batch_data_hash produces for posters.
+        let mut expected = Vec::new();
+        for chunk in batch.chunks(BATCH_CHUNK_SIZE) {
+            expected.extend_from_slice(&hash_proof_data(chunk));
+        }
+        assert_eq!(header.batch_hash(), Some(hash_proof_data(&expected)));
+
+        let mut tampered = batch.clone();
+        tampered[BATCH_CHUNK_SIZE] ^= 1;
+        header.chunk_hashes[1] = hash_proof_data(&tampered[BATCH_CHUNK_SIZE..]);
+        assert_ne!(header.batch_hash(), Some(hash_proof_data(&expected)));
+    }
+
+    #[test]
+    fn test_single_transaction_batch_starts_at_one_step() {
+        let challenge = sample_challenge(0);
+        assert_eq!((challenge.lo_step, challenge.hi_step), (0, 1));
+        assert_eq!(challenge.turn, DisputeTurn::OneStep);
+    }
+}
@@ -0,0 +1,773 @@
+// Integration tests for the Mythic settlement program
+// Exercises fraud proofs end to end using solana-program-test
+
+use borsh::BorshDeserialize;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    ed25519_program,
+    instruction::{AccountMeta, Instruction},
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::Transaction,
+};
+
+use mythic_settlement::*;
+
+// ── Constants (mirror the program) ───────────────────────────────────────────
+
+const SETTLEMENT_CONFIG_SEED: &[u8] = b"settlement_config";
+const STATE_ROOT_SEED: &[u8] = b"state_root";
+const SEQUENCER_SET_SEED: &[u8] = b"sequencer_set";
+const SEQUENCER_VAULT_SEED: &[u8] = b"sequencer_vault";
+const CHALLENGE_SEED: &[u8] = b"challenge";
+const IX_RESOLVE_CHALLENGE: u8 = 3;
+const IX_FINALIZE_STATE_ROOT: u8 = 4;
+const IX_PROVE_DOUBLE_SEQUENCING: u8 = 18;
+const IX_WITHDRAW_SEQUENCER_BOND: u8 = 21;
+
+const L2_CHAIN_ID: [u8; 16] = *b"mythic-testnet-1";
+const L2_SLOT: u64 = 100;
+const CHILD_SLOT: u64 = 200;
+const BOND: u64 = 5_000_000;
+
+fn token_program_id() -> Pubkey {
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA".parse().unwrap()
+}
+
+// ── Account fixtures ─────────────────────────────────────────────────────────
+
+/// SPL Token mint layout (82 bytes) with no authorities.
+fn mint_data(decimals: u8) -> Vec<u8> {
+    let mut data = vec![0u8; 82];
+    data[44] = decimals;
+    data[45] = 1; // is_initialized
+    data
+}
+
+/// SPL Token account layout (165 bytes), initialized.
+fn token_account_data(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 165];
+    data[..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = 1; // AccountState::Initialized
+    data
+}
+
+fn token_amount(account: &Account) -> u64 {
+    u64::from_le_bytes(account.data[64..72].try_into().unwrap())
+}
+
+fn add_account(pt: &mut ProgramTest, key: Pubkey, owner: Pubkey, data: Vec<u8>) {
+    pt.add_account(
+        key,
+        Account {
+            lamports: 1_000_000_000,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+struct Fixture {
+    state_root_pda: Pubkey,
+    set_pda: Pubkey,
+    vault_pda: Pubkey,
+    myth_mint: Pubkey,
+    challenger_token: Pubkey,
+}
+
+/// How the poster appears in the sequencer set; `None` leaves the set
+/// uninitialized (single configured sequencer).
+#[derive(Clone, Copy)]
+struct Member {
+    exit_requested_at: u64,
+    open_challenges: u16,
+}
+
+const ACTIVE: Option<Member> = Some(Member { exit_requested_at: 0, open_challenges: 0 });
+
+/// Config whose chain tip is the root `last_state_root` at `last_posted_slot`,
+/// with nothing finalized yet. A later call replaces the earlier account.
+fn add_config(
+    pt: &mut ProgramTest,
+    pid: &Pubkey,
+    sequencer: &Pubkey,
+    last_posted_slot: u64,
+    last_state_root: [u8; 32],
+) {
+    let (config_pda, config_bump) = Pubkey::find_program_address(&[SETTLEMENT_CONFIG_SEED], pid);
+    let config = SettlementConfig {
+        is_initialized: true,
+        admin: Pubkey::new_unique(),
+        sequencer: *sequencer,
+        challenge_period_slots: 900,
+        l2_chain_id: L2_CHAIN_ID,
+        min_challenger_bond: 1_000_000,
+        last_posted_slot,
+        last_state_root,
+        last_finalized_slot: 0,
+        total_roots_posted: 1,
+        total_challenges: 0,
+        is_paused: false,
+        bump: config_bump,
+        pending_admin: Pubkey::default(),
+        step_verifier: Pubkey::default(),
+        last_finalized_root: [0u8; 32],
+        rewound_through_slot: 0,
+    };
+    add_account(pt, config_pda, *pid, borsh::to_vec(&config).unwrap());
+}
+
+/// Config, a state root posted by `sequencer` with `status`, a sequencer set
+/// holding its bond, the bond vault and a MYTH account for `challenger`.
+fn add_fixture(
+    pt: &mut ProgramTest,
+    pid: &Pubkey,
+    sequencer: &Pubkey,
+    challenger: &Pubkey,
+    status: StateRootStatus,
+    member: Option<Member>,
+) -> Fixture {
+    add_config(pt, pid, sequencer, L2_SLOT, [1u8; 32]);
+
+    let (state_root_pda, state_root_bump) =
+        Pubkey::find_program_address(&[STATE_ROOT_SEED, &L2_SLOT.to_le_bytes()], pid);
+    let state_root = StateRootAccount {
+        l2_slot: L2_SLOT,
+        state_root: [1u8; 32],
+        transaction_count: 4,
+        transaction_batch_hash: [2u8; 32],
+        ai_attestation_count: 0,
+        previous_state_root: [0u8; 32],
+        sequencer: *sequencer,
+        posted_at: 0,
+        challenge_deadline: 1_000_000,
+        status,
+        bump: state_root_bump,
+        open_challenges: member.map_or(0, |m| m.open_challenges),
+    };
+    add_account(pt, state_root_pda, *pid, borsh::to_vec(&state_root).unwrap());
+
+    let myth_mint = Pubkey::new_unique();
+    add_account(pt, myth_mint, token_program_id(), mint_data(6));
+
+    let (vault_pda, vault_bump) = Pubkey::find_program_address(&[SEQUENCER_VAULT_SEED], pid);
+    add_account(pt, vault_pda, token_program_id(), token_account_data(&myth_mint, &vault_pda, BOND));
+
+    let (set_pda, set_bump) = Pubkey::find_program_address(&[SEQUENCER_SET_SEED], pid);
+    if let Some(member) = member {
+        let set = SequencerSet {
+            is_initialized: true,
+            myth_mint,
+            min_bond: BOND,
+            rotation_slots: 100,
+            skip_after_slots: 100,
+            exit_delay_slots: 100,
+            last_post_slot: 0,
+            vault_bump,
+            bump: set_bump,
+            members: vec![SequencerEntry {
+                sequencer: *sequencer,
+                bond: BOND,
+                registered_at: 0,
+                exit_requested_at: member.exit_requested_at,
+                open_challenges: member.open_challenges,
+            }],
+        };
+        let mut set_data = borsh::to_vec(&set).unwrap();
+        set_data.resize(SequencerSet::SIZE, 0);
+        add_account(pt, set_pda, *pid, set_data);
+    }
+
+    let challenger_token = Pubkey::new_unique();
+    add_account(pt, challenger_token, token_program_id(), token_account_data(&myth_mint, challenger, 0));
+
+    Fixture { state_root_pda, set_pda, vault_pda, myth_mint, challenger_token }
+}
+
+/// Posted root claiming no transactions, with its challenge period over and
+/// no batch published.
+fn add_posted_root(
+    pt: &mut ProgramTest,
+    pid: &Pubkey,
+    sequencer: &Pubkey,
+    l2_slot: u64,
+    previous_state_root: [u8; 32],
+    state_root: [u8; 32],
+) -> Pubkey {
+    let (root_pda, root_bump) =
+        Pubkey::find_program_address(&[STATE_ROOT_SEED, &l2_slot.to_le_bytes()], pid);
+    let root = StateRootAccount {
+        l2_slot,
+        state_root,
+        transaction_count: 0,
+        transaction_batch_hash: [0u8; 32],
+        ai_attestation_count: 0,
+        previous_state_root,
+        sequencer: *sequencer,
+        posted_at: 0,
+        challenge_deadline: 1,
+        status: StateRootStatus::Posted,
+        bump: root_bump,
+        open_challenges: 0,
+    };
+    add_account(pt, root_pda, *pid, borsh::to_vec(&root).unwrap());
+    root_pda
+}
+
+/// Posted root at CHILD_SLOT building on the fixture's root, with its
+/// challenge period over; the config tip moves to it.
+fn add_child_root(pt: &mut ProgramTest, pid: &Pubkey, sequencer: &Pubkey) -> Pubkey {
+    add_config(pt, pid, sequencer, CHILD_SLOT, [3u8; 32]);
+    add_posted_root(pt, pid, sequencer, CHILD_SLOT, [1u8; 32], [3u8; 32])
+}
+
+// ── Instruction builders ─────────────────────────────────────────────────────
+
+/// ed25519 precompile instruction carrying one signature, with every offset
+/// pointing into the instruction itself.
+fn build_ed25519_ix(signer: &Keypair, message: &[u8]) -> Instruction {
+    const DATA_START: usize = 2 + 14;
+    let signature = signer.sign_message(message);
+    let pubkey_offset = DATA_START;
+    let signature_offset = pubkey_offset + 32;
+    let message_offset = signature_offset + 64;
+
+    let mut data = vec![1u8, 0];
+    for value in [
+        signature_offset as u16,
+        u16::MAX,
+        pubkey_offset as u16,
+        u16::MAX,
+        message_offset as u16,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signature.as_ref());
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+fn build_prove_double_sequencing_ix(
+    pid: &Pubkey,
+    challenger: &Pubkey,
+    fixture: &Fixture,
+    root_a: [u8; 32],
+    root_b: [u8; 32],
+) -> Instruction {
+    let (config_pda, _) = Pubkey::find_program_address(&[SETTLEMENT_CONFIG_SEED], pid);
+    let args = ProveDoubleSequencingArgs {
+        l2_slot: L2_SLOT,
+        root_a,
+        root_b,
+    };
+    let mut data = vec![IX_PROVE_DOUBLE_SEQUENCING];
+    data.extend_from_slice(&borsh::to_vec(&args).unwrap());
+
+    Instruction {
+        program_id: *pid,
+        accounts: vec![
+            AccountMeta::new_readonly(*challenger, true),
+            AccountMeta::new(config_pda, false),
+            AccountMeta::new(fixture.state_root_pda, false),
+            AccountMeta::new(fixture.set_pda, false),
+            AccountMeta::new(fixture.vault_pda, false),
+            AccountMeta::new(fixture.challenger_token, false),
+            AccountMeta::new_readonly(token_program_id(), false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+        data,
+    }
+}
+
+fn build_resolve_challenge_ix(
+    pid: &Pubkey,
+    caller: &Pubkey,
+    challenger: &Pubkey,
+    sequencer: &Pubkey,
+    fixture: &Fixture,
+    payout_token: &Pubkey,
+) -> Instruction {
+    let (config_pda, _) = Pubkey::find_program_address(&[SETTLEMENT_CONFIG_SEED], pid);
+    let (challenge_pda, _) = Pubkey::find_program_address(
+        &[CHALLENGE_SEED, &L2_SLOT.to_le_bytes(), challenger.as_ref()],
+        pid,
+    );
+    let args = ResolveChallengeArgs {
+        l2_slot: L2_SLOT,
+        challenger: *challenger,
+    };
+    let mut data = vec![IX_RESOLVE_CHALLENGE];
+    data.extend_from_slice(&borsh::to_vec(&args).unwrap());
+
+    Instruction {
+        program_id: *pid,
+        accounts: vec![
+            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new(config_pda, false),
+            AccountMeta::new(fixture.state_root_pda, false),
+            AccountMeta::new(challenge_pda, false),
+            AccountMeta::new(*challenger, false),
+            AccountMeta::new(*sequencer, false),
+            AccountMeta::new(fixture.set_pda, false),
+            AccountMeta::new(fixture.vault_pda, false),
+            AccountMeta::new(*payout_token, false),
+            AccountMeta::new_readonly(token_program_id(), false),
+        ],
+        data,
+    }
+}
+
+fn build_finalize_state_root_ix(pid: &Pubkey, caller: &Pubkey, l2_slot: u64) -> Instruction {
+    let (config_pda, _) = Pubkey::find_program_address(&[SETTLEMENT_CONFIG_SEED], pid);
+    let (state_root_pda, _) =
+        Pubkey::find_program_address(&[STATE_ROOT_SEED, &l2_slot.to_le_bytes()], pid);
+    let args = FinalizeStateRootArgs { l2_slot };
+    let mut data = vec![IX_FINALIZE_STATE_ROOT];
+    data.extend_from_slice(&borsh::to_vec(&args).unwrap());
+
+    Instruction {
+        program_id: *pid,
+        accounts: vec![
+            AccountMeta::new_readonly(*caller, false),
+            AccountMeta::new(config_pda, false),
+            AccountMeta::new(state_root_pda, false),
+            // Batch header, never published
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+        ],
+        data,
+    }
+}
+
+fn build_withdraw_sequencer_bond_ix(
+    sequencer: &Pubkey,
+    fixture: &Fixture,
+    destination_token: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: mythic_settlement::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*sequencer, true),
+            AccountMeta::new(fixture.set_pda, false),
+            AccountMeta::new(fixture.vault_pda, false),
+            AccountMeta::new(*destination_token, false),
+            AccountMeta::new_readonly(token_program_id(), false),
+        ],
+        data: vec![IX_WITHDRAW_SEQUENCER_BOND],
+    }
+}
+
+/// Adds an Active dispute against the fixture's root whose round deadline has
+/// passed while it was `turn`'s move.
+fn add_expired_dispute(
+    pt: &mut ProgramTest,
+    pid: &Pubkey,
+    challenger: &Pubkey,
+    sequencer: &Pubkey,
+    turn: DisputeTurn,
+) {
+    let (challenge_pda, challenge_bump) = Pubkey::find_program_address(
+        &[CHALLENGE_SEED, &L2_SLOT.to_le_bytes(), challenger.as_ref()],
+        pid,
+    );
+    let challenge = ChallengeAccount {
+        l2_slot: L2_SLOT,
+        challenger: *challenger,
+        fraud_proof_type: FraudProofType::InvalidStateTransition,
+        proof_data_hash: [0u8; 32],
+        bond_amount: 1_000_000,
+        created_at: 0,
+        status: ChallengeStatus::Active,
+        bump: challenge_bump,
+        defender: *sequencer,
+        defender_bond: 0,
+        lo_step: 0,
+        lo_hash: [0u8; 32],
+        hi_step: 4,
+        hi_hash: [1u8; 32],
+        mid_hash: [0u8; 32],
+        turn,
+        round: 0,
+        round_deadline: 1,
+    };
+    add_account(pt, challenge_pda, *pid, borsh::to_vec(&challenge).unwrap());
+}
+
+/// Adds an Active challenge against the fixture's root in the v1 layout,
+/// opened before dispute games existed.
+fn add_v1_challenge(pt: &mut ProgramTest, pid: &Pubkey, challenger: &Pubkey) -> Pubkey {
+    let (challenge_pda, challenge_bump) = Pubkey::find_program_address(
+        &[CHALLENGE_SEED, &L2_SLOT.to_le_bytes(), challenger.as_ref()],
+        pid,
+    );
+    let challenge = ChallengeAccount {
+        l2_slot: L2_SLOT,
+        challenger: *challenger,
+        fraud_proof_type: FraudProofType::InvalidStateTransition,
+        proof_data_hash: [0u8; 32],
+        bond_amount: 1_000_000,
+        created_at: 0,
+        status: ChallengeStatus::Active,
+        bump: challenge_bump,
+        defender: Pubkey::default(),
+        defender_bond: 0,
+        lo_step: 0,
+        lo_hash: [0u8; 32],
+        hi_step: 0,
+        hi_hash: [0u8; 32],
+        mid_hash: [0u8; 32],
+        turn: DisputeTurn::Defender,
+        round: 0,
+        round_deadline: 0,
+    };
+    let mut data = borsh::to_vec(&challenge).unwrap();
+    data.truncate(ChallengeAccount::V1_SIZE);
+    add_account(pt, challenge_pda, *pid, data);
+    challenge_pda
+}
+
+async fn prove_double_sequencing(
+    status: StateRootStatus,
+    member: Option<Member>,
+    sign_both: bool,
+) -> (Result<(), BanksClientError>, BanksClient, Fixture) {
+    let pid = mythic_settlement::id();
+    let mut pt = ProgramTest::new("mythic_settlement", pid, processor!(process_instruction));
+
+    let sequencer = Keypair::new();
+    let challenger = Keypair::new();
+    add_account(&mut pt, challenger.pubkey(), solana_sdk::system_program::id(), vec![]);
+    let fixture = add_fixture(&mut pt, &pid, &sequencer.pubkey(), &challenger.pubkey(), status, member);
+
+    let (banks, _payer, _bh) = pt.start().await;
+
+    let root_a = [1u8; 32];
+    let root_b = [9u8; 32];
+    let sig_a = build_ed25519_ix(&sequencer, &state_root_message(&L2_CHAIN_ID, L2_SLOT, &root_a));
+    let sig_b = if sign_both {
+        build_ed25519_ix(&sequencer, &state_root_message(&L2_CHAIN_ID, L2_SLOT, &root_b))
+    } else {
+        // Second commitment signed by someone other than the poster
+        build_ed25519_ix(&Keypair::new(), &state_root_message(&L2_CHAIN_ID, L2_SLOT, &root_b))
+    };
+    let prove = build_prove_double_sequencing_ix(&pid, &challenger.pubkey(), &fixture, root_a, root_b);
+
+    let bh = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[sig_a, sig_b, prove],
+        Some(&challenger.pubkey()),
+        &[&challenger],
+        bh,
+    );
+    let result = banks.process_transaction(tx).await;
+    (result, banks, fixture)
+}
+
+async fn state_root_status(banks: &BanksClient, fixture: &Fixture) -> StateRootStatus {
+    let account = banks.get_account(fixture.state_root_pda).await.unwrap().unwrap();
+    StateRootAccount::try_from_slice(&account.data).unwrap().status
+}
+
+async fn sequencer_set(banks: &BanksClient, fixture: &Fixture) -> SequencerSet {
+    let account = banks.get_account(fixture.set_pda).await.unwrap().unwrap();
+    SequencerSet::deserialize(&mut &account.data[..]).unwrap()
+}
+
+// ── Tests ────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_double_sequencing_slashes_and_invalidates_posted_root() {
+    let (result, banks, fixture) = prove_double_sequencing(StateRootStatus::Posted, ACTIVE, true).await;
+    result.unwrap();
+
+    assert_eq!(state_root_status(&banks, &fixture).await, StateRootStatus::Invalidated);
+    let prover = banks.get_account(fixture.challenger_token).await.unwrap().unwrap();
+    assert_eq!(token_amount(&prover), BOND);
+    let vault = banks.get_account(fixture.vault_pda).await.unwrap().unwrap();
+    assert_eq!(token_amount(&vault), 0);
+}
+
+#[tokio::test]
+async fn test_double_sequencing_keeps_finalized_root_final() {
+    let (result, banks, fixture) = prove_double_sequencing(StateRootStatus::Finalized, ACTIVE, true).await;
+    result.unwrap();
+
+    // The bond is still slashed, but bridge payouts against the root stand
+    assert_eq!(state_root_status(&banks, &fixture).await, StateRootStatus::Finalized);
+    let prover = banks.get_account(fixture.challenger_token).await.unwrap().unwrap();
+    assert_eq!(token_amount(&prover), BOND);
+}
+
+#[tokio::test]
+async fn test_double_sequencing_requires_both_poster_signatures() {
+    let (result, banks, fixture) = prove_double_sequencing(StateRootStatus::Posted, ACTIVE, false).await;
+    assert!(result.is_err(), "A commitment not signed by the poster must be rejected");
+
+    assert_eq!(state_root_status(&banks, &fixture).await, StateRootStatus::Posted);
+    let vault = banks.get_account(fixture.vault_pda).await.unwrap().unwrap();
+    assert_eq!(token_amount(&vault), BOND);
+}
+
+#[tokio::test]
+async fn test_double_sequencing_without_sequencer_set_invalidates_only() {
+    let (result, banks, fixture) = prove_double_sequencing(StateRootStatus::Posted, None, true).await;
+    result.unwrap();
+
+    assert_eq!(state_root_status(&banks, &fixture).await, StateRootStatus::Invalidated);
+    let prover = banks.get_account(fixture.challenger_token).await.unwrap().unwrap();
+    assert_eq!(token_amount(&prover), 0);
+}
+
+#[tokio::test]
+async fn test_resolve_challenge_slash_cannot_be_redirected_by_cranker() {
+    let pid = mythic_settlement::id();
+    let mut pt = ProgramTest::new("mythic_settlement", pid, processor!(process_instruction));
+
+    let sequencer = Pubkey::new_unique();
+    let challenger = Pubkey::new_unique();
+    let cranker = Keypair::new();
+    for key in [sequencer, challenger, cranker.pubkey()] {
+        add_account(&mut pt, key, solana_sdk::system_program::id(), vec![]);
+    }
+    let open = Some(Member { exit_requested_at: 0, open_challenges: 1 });
+    let fixture = add_fixture(&mut pt, &pid, &sequencer, &challenger, StateRootStatus::Challenged, open);
+    // Defender missed its bisection move, so the challenger wins
+    add_expired_dispute(&mut pt, &pid, &challenger, &sequencer, DisputeTurn::Defender);
+
+    // The cranker's own MYTH account
+    let cranker_token = Pubkey::new_unique();
+    add_account(
+        &mut pt,
+        cranker_token,
+        token_program_id(),
+        token_account_data(&fixture.myth_mint, &cranker.pubkey(), 0),
+    );
+
+    let mut context = pt.start_with_context().await;
+    context.warp_to_slot(100).unwrap();
+    let banks = context.banks_client.clone();
+    let bh = banks.get_latest_blockhash().await.unwrap();
+
+    // Routing the slashed bond to the cranker MUST FAIL
+    let redirect = build_resolve_challenge_ix(&pid, &cranker.pubkey(), &challenger, &sequencer, &fixture, &cranker_token);
+    let tx = Transaction::new_signed_with_payer(&[redirect], Some(&cranker.pubkey()), &[&cranker], bh);
+    assert!(
+        banks.process_transaction(tx).await.is_err(),
+        "Slashed bond must go to the challenger's own token account"
+    );
+
+    let resolve = build_resolve_challenge_ix(
+        &pid,
+        &cranker.pubkey(),
+        &challenger,
+        &sequencer,
+        &fixture,
+        &fixture.challenger_token,
+    );
+    let tx = Transaction::new_signed_with_payer(&[resolve], Some(&cranker.pubkey()), &[&cranker], bh);
+    banks.process_transaction(tx).await.unwrap();
+
+    assert_eq!(state_root_status(&banks, &fixture).await, StateRootStatus::Invalidated);
+    let challenger_token = banks.get_account(fixture.challenger_token).await.unwrap().unwrap();
+    assert_eq!(token_amount(&challenger_token), BOND);
+    let cranker_account = banks.get_account(cranker_token).await.unwrap().unwrap();
+    assert_eq!(token_amount(&cranker_account), 0);
+}
+
+#[tokio::test]
+async fn test_withdraw_sequencer_bond_waits_for_open_challenges() {
+    let pid = mythic_settlement::id();
+    let mut pt = ProgramTest::new("mythic_settlement", pid, processor!(process_instruction));
+
+    let sequencer = Keypair::new();
+    let challenger = Pubkey::new_unique();
+    for key in [sequencer.pubkey(), challenger] {
+        add_account(&mut pt, key, solana_sdk::system_program::id(), vec![]);
+    }
+    // Exit requested long ago, but a dispute against its root is still open
+    let exiting = Some(Member { exit_requested_at: 1, open_challenges: 1 });
+    let fixture = add_fixture(&mut pt, &pid, &sequencer.pubkey(), &challenger, StateRootStatus::Challenged, exiting);
+    // Challenger missed its response, so the sequencer wins
+    add_expired_dispute(&mut pt, &pid, &challenger, &sequencer.pubkey(), DisputeTurn::Challenger);
+
+    let sequencer_token = Pubkey::new_unique();
+    add_account(
+        &mut pt,
+        sequencer_token,
+        token_program_id(),
+        token_account_data(&fixture.myth_mint, &sequencer.pubkey(), 0),
+    );
+
+    let mut context = pt.start_with_context().await;
+    context.warp_to_slot(1_000).unwrap();
+    let mut banks = context.banks_client.clone();
+    let bh = banks.get_latest_blockhash().await.unwrap();
+
+    // Withdrawing while the dispute is open MUST FAIL
+    let withdraw = build_withdraw_sequencer_bond_ix(&sequencer.pubkey(), &fixture, &sequencer_token);
+    let tx = Transaction::new_signed_with_payer(std::slice::from_ref(&withdraw), Some(&sequencer.pubkey()), &[&sequencer], bh);
+    assert!(
+        banks.process_transaction(tx).await.is_err(),
+        "Bond must stay slashable while a dispute is open"
+    );
+
+    let resolve = build_resolve_challenge_ix(
+        &pid,
+        &sequencer.pubkey(),
+        &challenger,
+        &sequencer.pubkey(),
+        &fixture,
+        &fixture.challenger_token,
+    );
+    let tx = Transaction::new_signed_with_payer(&[resolve], Some(&sequencer.pubkey()), &[&sequencer], bh);
+    banks.process_transaction(tx).await.unwrap();
+    assert_eq!(sequencer_set(&banks, &fixture).await.members[0].open_challenges, 0);
+
+    let bh = banks.get_new_latest_blockhash(&bh).await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[withdraw], Some(&sequencer.pubkey()), &[&sequencer], bh);
+    banks.process_transaction(tx).await.unwrap();
+
+    let destination = banks.get_account(sequencer_token).await.unwrap().unwrap();
+    assert_eq!(token_amount(&destination), BOND);
+    assert!(sequencer_set(&banks, &fixture).await.members.is_empty());
+}
+
+#[tokio::test]
+async fn test_child_of_disputed_root_cannot_finalize() {
+    let pid = mythic_settlement::id();
+    let mut pt = ProgramTest::new("mythic_settlement", pid, processor!(process_instruction));
+
+    let sequencer = Pubkey::new_unique();
+    let challenger = Pubkey::new_unique();
+    let cranker = Keypair::new();
+    for key in [sequencer, challenger, cranker.pubkey()] {
+        add_account(&mut pt, key, solana_sdk::system_program::id(), vec![]);
+    }
+    let open = Some(Member { exit_requested_at: 0, open_challenges: 1 });
+    let fixture = add_fixture(&mut pt, &pid, &sequencer, &challenger, StateRootStatus::Challenged, open);
+    add_expired_dispute(&mut pt, &pid, &challenger, &sequencer, DisputeTurn::Defender);
+    let child_pda = add_child_root(&mut pt, &pid, &sequencer);
+
+    let mut context = pt.start_with_context().await;
+    context.warp_to_slot(100).unwrap();
+    let mut banks = context.banks_client.clone();
+    let bh = banks.get_latest_blockhash().await.unwrap();
+
+    // The child's challenge period is over, but its parent is still disputed
+    let finalize = build_finalize_state_root_ix(&pid, &cranker.pubkey(), CHILD_SLOT);
+    let tx = Transaction::new_signed_with_payer(std::slice::from_ref(&finalize), Some(&cranker.pubkey()), &[&cranker], bh);
+    assert!(
+        banks.process_transaction(tx).await.is_err(),
+        "A root must not finalize before the root it builds on"
+    );
+
+    // The challenger wins, invalidating the parent and rewinding the tip
+    let resolve = build_resolve_challenge_ix(
+        &pid,
+        &cranker.pubkey(),
+        &challenger,
+        &sequencer,
+        &fixture,
+        &fixture.challenger_token,
+    );
+    let tx = Transaction::new_signed_with_payer(&[resolve], Some(&cranker.pubkey()), &[&cranker], bh);
+    banks.process_transaction(tx).await.unwrap();
+    assert_eq!(state_root_status(&banks, &fixture).await, StateRootStatus::Invalidated);
+
+    let (config_pda, _) = Pubkey::find_program_address(&[SETTLEMENT_CONFIG_SEED], &pid);
+    let config = banks.get_account(config_pda).await.unwrap().unwrap();
+    let config = SettlementConfig::try_from_slice(&config.data).unwrap();
+    assert_eq!(config.last_state_root, [0u8; 32], "Posting resumes from the last finalized root");
+    assert_eq!(config.rewound_through_slot, CHILD_SLOT);
+
+    // Finalizing the child now only records that it was discarded
+    let bh = banks.get_new_latest_blockhash(&bh).await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[finalize], Some(&cranker.pubkey()), &[&cranker], bh);
+    banks.process_transaction(tx).await.unwrap();
+
+    let child = banks.get_account(child_pda).await.unwrap().unwrap();
+    assert_eq!(StateRootAccount::try_from_slice(&child.data).unwrap().status, StateRootStatus::Invalidated);
+    let config = banks.get_account(config_pda).await.unwrap().unwrap();
+    assert_eq!(SettlementConfig::try_from_slice(&config.data).unwrap().last_finalized_slot, 0);
+}
+
+#[tokio::test]
+async fn test_resolve_v1_challenge_refunds_the_challenger() {
+    let pid = mythic_settlement::id();
+    let mut pt = ProgramTest::new("mythic_settlement", pid, processor!(process_instruction));
+
+    let sequencer = Pubkey::new_unique();
+    let challenger = Pubkey::new_unique();
+    let cranker = Keypair::new();
+    for key in [sequencer, challenger, cranker.pubkey()] {
+        add_account(&mut pt, key, solana_sdk::system_program::id(), vec![]);
+    }
+    let fixture = add_fixture(&mut pt, &pid, &sequencer, &challenger, StateRootStatus::Challenged, None);
+    let challenge_pda = add_v1_challenge(&mut pt, &pid, &challenger);
+
+    let (banks, _payer, bh) = pt.start().await;
+    let escrowed = banks.get_balance(challenge_pda).await.unwrap();
+    let before = banks.get_balance(challenger).await.unwrap();
+
+    let resolve = build_resolve_challenge_ix(
+        &pid,
+        &cranker.pubkey(),
+        &challenger,
+        &sequencer,
+        &fixture,
+        &fixture.challenger_token,
+    );
+    let tx = Transaction::new_signed_with_payer(&[resolve], Some(&cranker.pubkey()), &[&cranker], bh);
+    banks.process_transaction(tx).await.unwrap();
+
+    assert_eq!(banks.get_balance(challenger).await.unwrap(), before + escrowed);
+    assert!(banks.get_account(challenge_pda).await.unwrap().is_none());
+    assert_eq!(state_root_status(&banks, &fixture).await, StateRootStatus::Posted);
+}
+
+#[tokio::test]
+async fn test_finalize_requires_batch_data_when_the_root_moves() {
+    let pid = mythic_settlement::id();
+    let mut pt = ProgramTest::new("mythic_settlement", pid, processor!(process_instruction));
+
+    let sequencer = Pubkey::new_unique();
+    let cranker = Keypair::new();
+    add_account(&mut pt, cranker.pubkey(), solana_sdk::system_program::id(), vec![]);
+    add_config(&mut pt, &pid, &sequencer, CHILD_SLOT, [3u8; 32]);
+    // An empty root that leaves the state alone needs no batch
+    add_posted_root(&mut pt, &pid, &sequencer, L2_SLOT, [0u8; 32], [0u8; 32]);
+    // This one claims no transactions but still changes the state
+    let moved_pda = add_posted_root(&mut pt, &pid, &sequencer, CHILD_SLOT, [0u8; 32], [3u8; 32]);
+
+    let mut context = pt.start_with_context().await;
+    context.warp_to_slot(100).unwrap();
+    let banks = context.banks_client.clone();
+    let bh = banks.get_latest_blockhash().await.unwrap();
+
+    let finalize = build_finalize_state_root_ix(&pid, &cranker.pubkey(), L2_SLOT);
+    let tx = Transaction::new_signed_with_payer(&[finalize], Some(&cranker.pubkey()), &[&cranker], bh);
+    banks.process_transaction(tx).await.unwrap();
+
+    // Builds on the finalized root, but its batch was never published
+    let finalize = build_finalize_state_root_ix(&pid, &cranker.pubkey(), CHILD_SLOT);
+    let tx = Transaction::new_signed_with_payer(&[finalize], Some(&cranker.pubkey()), &[&cranker], bh);
+    assert!(
+        banks.process_transaction(tx).await.is_err(),
+        "A root that moves the state must not finalize without its batch"
+    );
+    let moved = banks.get_account(moved_pda).await.unwrap().unwrap();
+    assert_eq!(StateRootAccount::try_from_slice(&moved.data).unwrap().status, StateRootStatus::Posted);
+}
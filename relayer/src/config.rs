@@ -0,0 +1,790 @@
+// Relayer configuration and command line.
+//
+// Settings are layered: built-in defaults, then the TOML config file
+// (`--config`, $RELAYER_CONFIG, or ./relayer.toml if present), then
+// environment variables, then command-line flags. Each layer is a TOML
+// table merged over the ones below it, and only the merged result is
+// validated, so a setting means the same thing wherever it is given.
+
+use crate::metrics::HealthThresholds;
+use crate::store::Queue;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub const USAGE: &str = "\
+USAGE:
+    mythic-relayer [COMMAND] [OPTIONS]
+
+COMMANDS:
+    run                      Relay deposits, burns and messages until stopped (default)
+    watchtower               Check every L1 withdrawal against L2 and challenge mismatches
+    post-roots               Post, publish and finalize settlement state roots as the sequencer
+    backfill --from-nonce N  Re-queue events from nonce N on, including dead-lettered ones,
+                             rescanning history for any the store is missing
+                             [--queue deposits|burns, default deposits]
+    replay <L1-SIGNATURE>    Relay the deposits and messages of one L1 transaction now,
+                             regardless of the confirmation policy
+    status                   Print store and chain progress, then exit
+    dry-run                  Build and simulate every pending relay without sending or
+                             recording anything
+
+OPTIONS:
+    --config <PATH>          TOML config file (default: $RELAYER_CONFIG, else ./relayer.toml if present)
+    --set <KEY>=<VALUE>      Override one config setting, e.g. --set fees.priority_fee_micro_lamports=5000
+    --l1-rpc <URL>           L1 RPC endpoint; repeat for fallbacks, tried in order
+    --l2-rpc <URL>           L2 RPC endpoint; repeat for fallbacks, tried in order
+    --keypair <PATH>         Relayer keypair JSON file
+    --store <PATH>           Relayer store (SQLite)
+    -h, --help               Print this help
+
+ENVIRONMENT (overrides the config file; flags override these):
+    RELAYER_CONFIG               Config file path
+    RELAYER_KEYPAIR_PATH         keypair_path
+    L1_RPC_URL, L2_RPC_URL       l1.rpc_urls, l2.rpc_urls (comma-separated fallbacks)
+    L1_WS_URL, L2_WS_URL         l1.ws_url, l2.ws_url
+    L1_CONFIRMATION              l1.confirmation: finalized | confirmed+<slots>
+    L2_CONFIRMATION              l2.confirmation: finalized | confirmed+<slots>
+    BRIDGE_L1_PROGRAM            programs.bridge_l1
+    BRIDGE_L2_PROGRAM            programs.bridge_l2
+    PRIORITY_FEE_MICRO_LAMPORTS  fees.priority_fee_micro_lamports
+    COMPUTE_UNIT_LIMIT           fees.compute_unit_limit
+    MAX_SEND_RETRIES             limits.max_send_retries
+    MAX_RELAY_ATTEMPTS           limits.max_relay_attempts
+    MAX_RELAYS_PER_POLL          limits.max_relays_per_poll
+    MAX_IN_FLIGHT                limits.max_in_flight
+    WORK_QUEUE_CAPACITY          limits.queue_capacity
+    POLL_INTERVAL_MS             poll_interval_ms
+    AUTO_FINALIZE                auto_finalize
+    SUBSCRIBE_LOGS               subscribe_logs
+    POST_INTERVAL_SLOTS          post_interval_slots
+    HEALTH_PORT                  health.port
+    HEALTH_MAX_NONCE_LAG         health.max_nonce_lag
+    HEALTH_MAX_POLL_ERRORS       health.max_poll_errors
+    STORE_PATH                   store_path
+    STATE_FILE                   state_file (legacy JSON state, imported once)
+
+Give watchtower and post-roots their own store; post-roots needs the settlement
+sequencer keypair.";
+
+/// Every setting with its default. Also the reference for the file format.
+const DEFAULTS: &str = r#"
+keypair_path = "relayer-keypair.json"
+store_path = "relayer_state.db"
+state_file = "relayer_state.json"
+poll_interval_ms = 2000
+auto_finalize = true
+subscribe_logs = true
+post_interval_slots = 100
+
+[l1]
+rpc_urls = ["http://localhost:8899"]
+ws_url = ""
+confirmation = "finalized"
+
+[l2]
+rpc_urls = ["http://localhost:8999"]
+ws_url = ""
+confirmation = "finalized"
+
+[programs]
+bridge_l1 = "BE2pz9kxPJLHd65B9tVBuZUwp3y5mKYczb6JLMsyPymA"
+bridge_l2 = "5t8JwXzGQ3c7PCY6p6oJqZgFt8gff2d6uTLrqa1jFrKP"
+
+[fees]
+priority_fee_micro_lamports = 0
+compute_unit_limit = 0
+
+[limits]
+max_send_retries = 5
+max_relay_attempts = 20
+max_relays_per_poll = 0
+max_in_flight = 16
+queue_capacity = 256
+
+[health]
+port = 9090
+max_nonce_lag = 100
+max_poll_errors = 5
+"#;
+
+/// Config file read when neither --config nor RELAYER_CONFIG names one.
+const DEFAULT_CONFIG_FILE: &str = "relayer.toml";
+
+/// How an environment variable's value is turned into a setting.
+#[derive(Clone, Copy)]
+enum EnvKind {
+    Str,
+    Int,
+    Bool,
+    /// Comma-separated list of strings.
+    List,
+}
+
+/// Environment variables and the setting each one overrides.
+const ENV_SETTINGS: &[(&str, &str, EnvKind)] = &[
+    ("RELAYER_KEYPAIR_PATH", "keypair_path", EnvKind::Str),
+    ("STORE_PATH", "store_path", EnvKind::Str),
+    ("STATE_FILE", "state_file", EnvKind::Str),
+    ("POLL_INTERVAL_MS", "poll_interval_ms", EnvKind::Int),
+    ("AUTO_FINALIZE", "auto_finalize", EnvKind::Bool),
+    ("SUBSCRIBE_LOGS", "subscribe_logs", EnvKind::Bool),
+    ("POST_INTERVAL_SLOTS", "post_interval_slots", EnvKind::Int),
+    ("L1_RPC_URL", "l1.rpc_urls", EnvKind::List),
+    ("L1_WS_URL", "l1.ws_url", EnvKind::Str),
+    ("L1_CONFIRMATION", "l1.confirmation", EnvKind::Str),
+    ("L2_RPC_URL", "l2.rpc_urls", EnvKind::List),
+    ("L2_WS_URL", "l2.ws_url", EnvKind::Str),
+    ("L2_CONFIRMATION", "l2.confirmation", EnvKind::Str),
+    ("BRIDGE_L1_PROGRAM", "programs.bridge_l1", EnvKind::Str),
+    ("BRIDGE_L2_PROGRAM", "programs.bridge_l2", EnvKind::Str),
+    ("PRIORITY_FEE_MICRO_LAMPORTS", "fees.priority_fee_micro_lamports", EnvKind::Int),
+    ("COMPUTE_UNIT_LIMIT", "fees.compute_unit_limit", EnvKind::Int),
+    ("MAX_SEND_RETRIES", "limits.max_send_retries", EnvKind::Int),
+    ("MAX_RELAY_ATTEMPTS", "limits.max_relay_attempts", EnvKind::Int),
+    ("MAX_RELAYS_PER_POLL", "limits.max_relays_per_poll", EnvKind::Int),
+    ("MAX_IN_FLIGHT", "limits.max_in_flight", EnvKind::Int),
+    ("WORK_QUEUE_CAPACITY", "limits.queue_capacity", EnvKind::Int),
+    ("HEALTH_PORT", "health.port", EnvKind::Int),
+    ("HEALTH_MAX_NONCE_LAG", "health.max_nonce_lag", EnvKind::Int),
+    ("HEALTH_MAX_POLL_ERRORS", "health.max_poll_errors", EnvKind::Int),
+];
+
+// ── Command Line ────────────────────────────────────────────────────────────
+
+/// What the binary was asked to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Run,
+    Watchtower,
+    PostRoots,
+    Backfill { queue: Queue, from_nonce: u64 },
+    Replay { l1_signature: String },
+    Status,
+    DryRun,
+    Help,
+}
+
+pub struct Cli {
+    pub command: Command,
+    config_file: Option<PathBuf>,
+    /// Settings given as flags, as a TOML table.
+    overrides: toml::Table,
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Cli, String> {
+    let args: Vec<String> = args.into_iter().collect();
+
+    let mut positional: Vec<String> = Vec::new();
+    let mut config_file: Option<PathBuf> = None;
+    let mut overrides = toml::Table::new();
+    let mut l1_rpc_urls: Vec<String> = Vec::new();
+    let mut l2_rpc_urls: Vec<String> = Vec::new();
+    let mut from_nonce: Option<u64> = None;
+    let mut queue = Queue::Deposits;
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let mut value = || {
+            i += 1;
+            args.get(i).cloned().ok_or_else(|| format!("Missing value for {}", flag))
+        };
+        match flag {
+            "--config" => config_file = Some(PathBuf::from(value()?)),
+            "--set" => {
+                let setting = value()?;
+                let (key, raw) = setting
+                    .split_once('=')
+                    .ok_or_else(|| format!("Expected --set KEY=VALUE, got {}", setting))?;
+                set_path(&mut overrides, key.trim(), parse_value(raw.trim()))?;
+            }
+            "--l1-rpc" => l1_rpc_urls.push(value()?),
+            "--l2-rpc" => l2_rpc_urls.push(value()?),
+            "--keypair" => set_path(&mut overrides, "keypair_path", toml::Value::String(value()?))?,
+            "--store" => set_path(&mut overrides, "store_path", toml::Value::String(value()?))?,
+            "--from-nonce" => {
+                let n = value()?;
+                from_nonce = Some(n.parse().map_err(|e| format!("Invalid --from-nonce {}: {}", n, e))?);
+            }
+            "--queue" => {
+                queue = match value()?.as_str() {
+                    "deposits" => Queue::Deposits,
+                    "burns" => Queue::Burns,
+                    other => return Err(format!("Invalid --queue {} (expected deposits or burns)", other)),
+                };
+            }
+            "--help" | "-h" => {
+                return Ok(Cli {
+                    command: Command::Help,
+                    config_file,
+                    overrides,
+                })
+            }
+            other if other.starts_with('-') => return Err(format!("Unknown option: {}", other)),
+            other => positional.push(other.to_string()),
+        }
+        i += 1;
+    }
+
+    for (key, urls) in [("l1.rpc_urls", l1_rpc_urls), ("l2.rpc_urls", l2_rpc_urls)] {
+        if !urls.is_empty() {
+            let urls = urls.into_iter().map(toml::Value::String).collect();
+            set_path(&mut overrides, key, toml::Value::Array(urls))?;
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let command = match positional.next().as_deref() {
+        None | Some("run") | Some("relay") => Command::Run,
+        Some("watchtower") => Command::Watchtower,
+        Some("post-roots") => Command::PostRoots,
+        Some("backfill") => Command::Backfill {
+            queue,
+            from_nonce: from_nonce.ok_or("backfill requires --from-nonce")?,
+        },
+        Some("replay") => Command::Replay {
+            l1_signature: positional.next().ok_or("replay requires an L1 transaction signature")?,
+        },
+        Some("status") => Command::Status,
+        Some("dry-run") => Command::DryRun,
+        Some("help") => Command::Help,
+        Some(other) => return Err(format!("Unknown command: {}", other)),
+    };
+    if let Some(extra) = positional.next() {
+        return Err(format!("Unexpected argument: {}", extra));
+    }
+
+    Ok(Cli {
+        command,
+        config_file,
+        overrides,
+    })
+}
+
+/// A flag value as TOML (number, bool, array...), or a bare string.
+fn parse_value(raw: &str) -> toml::Value {
+    format!("v = {}", raw)
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("v"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Set a dotted `key` (e.g. `l1.confirmation`) in `table`.
+fn set_path(table: &mut toml::Table, key: &str, value: toml::Value) -> Result<(), String> {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().filter(|k| !k.is_empty()).ok_or_else(|| format!("Invalid setting: {}", key))?;
+    let mut table = table;
+    for part in parts {
+        table = table
+            .entry(part)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| format!("Invalid setting: {}", key))?;
+    }
+    table.insert(last.to_string(), value);
+    Ok(())
+}
+
+/// Merge `over` into `base`, descending into tables present in both.
+fn merge(base: &mut toml::Table, over: toml::Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(over)) => merge(base, over),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn env_layer() -> Result<toml::Table, String> {
+    let mut table = toml::Table::new();
+    for &(var, key, kind) in ENV_SETTINGS {
+        let Ok(raw) = std::env::var(var) else { continue };
+        let value = match kind {
+            EnvKind::Str => toml::Value::String(raw),
+            EnvKind::Int => toml::Value::Integer(
+                raw.trim().parse().map_err(|e| format!("Invalid {}: {}", var, e))?,
+            ),
+            EnvKind::Bool => toml::Value::Boolean(
+                raw.trim().parse().map_err(|e| format!("Invalid {}: {}", var, e))?,
+            ),
+            EnvKind::List => toml::Value::Array(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| toml::Value::String(s.to_string()))
+                    .collect(),
+            ),
+        };
+        set_path(&mut table, key, value)?;
+    }
+    Ok(table)
+}
+
+/// Merge `layers` over the built-in defaults, lowest first.
+fn resolve(layers: impl IntoIterator<Item = toml::Table>) -> Result<Settings, String> {
+    let mut table: toml::Table = DEFAULTS.parse().expect("built-in defaults are valid TOML");
+    for layer in layers {
+        merge(&mut table, layer);
+    }
+    toml::Value::Table(table)
+        .try_into()
+        .map_err(|e| format!("Invalid configuration: {}", e))
+}
+
+// ── Settings ────────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    keypair_path: String,
+    store_path: PathBuf,
+    state_file: PathBuf,
+    poll_interval_ms: u64,
+    auto_finalize: bool,
+    subscribe_logs: bool,
+    post_interval_slots: u64,
+    l1: ChainSettings,
+    l2: ChainSettings,
+    programs: ProgramSettings,
+    fees: FeeSettings,
+    limits: LimitSettings,
+    health: HealthSettings,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChainSettings {
+    /// Tried in order; later URLs are fallbacks.
+    rpc_urls: Vec<String>,
+    /// Empty to derive it from whichever RPC URL is active.
+    ws_url: String,
+    confirmation: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProgramSettings {
+    bridge_l1: String,
+    bridge_l2: String,
+}
+
+/// Compute-budget settings applied to every transaction the relayer sends.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct FeeSettings {
+    /// Priority fee per compute unit; 0 sends none.
+    pub priority_fee_micro_lamports: u64,
+    /// Compute unit limit; 0 keeps the runtime default.
+    pub compute_unit_limit: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LimitSettings {
+    max_send_retries: u32,
+    max_relay_attempts: u32,
+    /// 0 means no limit.
+    max_relays_per_poll: usize,
+    max_in_flight: usize,
+    queue_capacity: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HealthSettings {
+    port: u16,
+    max_nonce_lag: u64,
+    max_poll_errors: u32,
+}
+
+pub struct RelayerConfig {
+    /// Endpoints per chain, in failover order.
+    pub l1_rpc_urls: Vec<String>,
+    pub l2_rpc_urls: Vec<String>,
+    pub relayer_keypair: Keypair,
+    pub bridge_l1_program: Pubkey,
+    pub bridge_l2_program: Pubkey,
+    pub health_port: u16,
+    pub poll_interval_ms: u64,
+    pub health_thresholds: HealthThresholds,
+    /// Finalize initiated L1 withdrawals once their challenge period ends.
+    pub auto_finalize: bool,
+    /// Stream bridge logs over websocket instead of waiting for the next poll.
+    pub subscribe_logs: bool,
+    /// Websocket endpoints; None follows the active RPC endpoint.
+    pub l1_ws_url: Option<String>,
+    pub l2_ws_url: Option<String>,
+    /// Depth L1 transactions need before deposits are relayed to L2.
+    pub l1_confirmation: ConfirmationPolicy,
+    /// Depth L2 transactions need before burns are relayed to L1.
+    pub l2_confirmation: ConfirmationPolicy,
+    /// Embedded relayer store (SQLite).
+    pub store_path: PathBuf,
+    /// Legacy JSON state file, imported into a fresh store if present.
+    pub state_file: PathBuf,
+    /// L2 slots covered by each settlement state root (root-poster mode).
+    pub post_interval_slots: u64,
+    pub fees: FeeSettings,
+    /// Attempts per transaction before a relay is marked failed.
+    pub max_send_retries: u32,
+    /// Failed relays per event before it is dead-lettered and left alone.
+    pub max_relay_attempts: u32,
+    /// Relays sent per queue per poll; None for no limit.
+    pub max_relays_per_poll: Option<usize>,
+    /// Relay transactions submitted and awaiting confirmation at once.
+    pub max_in_flight: usize,
+    /// Relays the pollers may queue ahead of the submitters.
+    pub queue_capacity: usize,
+    /// Simulate transactions instead of sending them.
+    pub dry_run: bool,
+}
+
+impl RelayerConfig {
+    /// Resolve every layer for `cli` and validate the result.
+    pub fn load(cli: &Cli) -> Result<Self, String> {
+        let config_file = cli
+            .config_file
+            .clone()
+            .or_else(|| std::env::var("RELAYER_CONFIG").ok().map(PathBuf::from));
+        let config_file = match config_file {
+            Some(path) => Some(path),
+            None => Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|path| path.exists()),
+        };
+        let file = match config_file {
+            Some(path) => {
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                text.parse()
+                    .map_err(|e| format!("Invalid {}: {}", path.display(), e))?
+            }
+            None => toml::Table::new(),
+        };
+        Self::from_settings(resolve([file, env_layer()?, cli.overrides.clone()])?)
+    }
+
+    fn from_settings(settings: Settings) -> Result<Self, String> {
+        let relayer_keypair = read_keypair_file(&settings.keypair_path)
+            .map_err(|e| format!("Failed to read keypair from {}: {}", settings.keypair_path, e))?;
+
+        let bridge_l1_program = Pubkey::from_str(&settings.programs.bridge_l1)
+            .map_err(|e| format!("Invalid programs.bridge_l1: {}", e))?;
+        let bridge_l2_program = Pubkey::from_str(&settings.programs.bridge_l2)
+            .map_err(|e| format!("Invalid programs.bridge_l2: {}", e))?;
+
+        let l1_confirmation: ConfirmationPolicy = settings
+            .l1
+            .confirmation
+            .parse()
+            .map_err(|e| format!("Invalid l1.confirmation: {}", e))?;
+        let l2_confirmation: ConfirmationPolicy = settings
+            .l2
+            .confirmation
+            .parse()
+            .map_err(|e| format!("Invalid l2.confirmation: {}", e))?;
+
+        if settings.l1.rpc_urls.is_empty() || settings.l2.rpc_urls.is_empty() {
+            return Err("l1.rpc_urls and l2.rpc_urls need at least one URL each".to_string());
+        }
+        if settings.post_interval_slots == 0 {
+            return Err("post_interval_slots must be at least 1".to_string());
+        }
+        if settings.limits.max_send_retries == 0 || settings.limits.max_relay_attempts == 0 {
+            return Err(
+                "limits.max_send_retries and limits.max_relay_attempts must be at least 1".to_string(),
+            );
+        }
+        if settings.limits.max_in_flight == 0 || settings.limits.queue_capacity == 0 {
+            return Err("limits.max_in_flight and limits.queue_capacity must be at least 1".to_string());
+        }
+
+        Ok(Self {
+            l1_rpc_urls: settings.l1.rpc_urls,
+            l2_rpc_urls: settings.l2.rpc_urls,
+            relayer_keypair,
+            bridge_l1_program,
+            bridge_l2_program,
+            health_port: settings.health.port,
+            poll_interval_ms: settings.poll_interval_ms,
+            health_thresholds: HealthThresholds {
+                max_nonce_lag: settings.health.max_nonce_lag,
+                max_consecutive_errors: settings.health.max_poll_errors,
+            },
+            auto_finalize: settings.auto_finalize,
+            subscribe_logs: settings.subscribe_logs,
+            l1_ws_url: Some(settings.l1.ws_url).filter(|url| !url.is_empty()),
+            l2_ws_url: Some(settings.l2.ws_url).filter(|url| !url.is_empty()),
+            l1_confirmation,
+            l2_confirmation,
+            store_path: settings.store_path,
+            state_file: settings.state_file,
+            post_interval_slots: settings.post_interval_slots,
+            fees: settings.fees,
+            max_send_retries: settings.limits.max_send_retries,
+            max_relay_attempts: settings.limits.max_relay_attempts,
+            max_relays_per_poll: Some(settings.limits.max_relays_per_poll).filter(|&n| n > 0),
+            max_in_flight: settings.limits.max_in_flight,
+            queue_capacity: settings.limits.queue_capacity,
+            dry_run: false,
+        })
+    }
+
+    /// How many pending relays to send per queue per poll.
+    pub fn relay_batch_limit(&self) -> usize {
+        self.max_relays_per_poll.unwrap_or(usize::MAX)
+    }
+}
+
+#[cfg(test)]
+impl RelayerConfig {
+    /// The defaults with `overrides` (TOML) on top, signing with a fresh keypair.
+    pub(crate) fn for_tests(overrides: &str) -> Self {
+        let keypair = tests::keypair_layer("for-tests");
+        let settings = resolve([keypair.clone(), overrides.parse().unwrap()]).unwrap();
+        let config = Self::from_settings(settings).unwrap();
+        std::fs::remove_file(keypair["keypair_path"].as_str().unwrap()).ok();
+        config
+    }
+}
+
+/// How deep a source-chain transaction must be before its events are relayed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfirmationPolicy {
+    /// Relay only once the transaction is finalized (rooted).
+    Finalized,
+    /// Relay once the transaction is confirmed and the confirmed tip is at
+    /// least this many slots past it.
+    ConfirmedPlus(u64),
+}
+
+impl FromStr for ConfirmationPolicy {
+    type Err = String;
+
+    /// Accepts `finalized`, `confirmed` or `confirmed+<slots>`.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.trim() {
+            "finalized" => Ok(ConfirmationPolicy::Finalized),
+            "confirmed" => Ok(ConfirmationPolicy::ConfirmedPlus(0)),
+            other => other
+                .strip_prefix("confirmed+")
+                .and_then(|n| n.parse().ok())
+                .map(ConfirmationPolicy::ConfirmedPlus)
+                .ok_or_else(|| {
+                    format!("expected finalized, confirmed or confirmed+<slots>, got {}", other)
+                }),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfirmationPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfirmationPolicy::Finalized => write!(f, "finalized"),
+            ConfirmationPolicy::ConfirmedPlus(slots) => write!(f, "confirmed+{}", slots),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirmation_policy_parses_each_form() {
+        assert_eq!("finalized".parse(), Ok(ConfirmationPolicy::Finalized));
+        assert_eq!("confirmed".parse(), Ok(ConfirmationPolicy::ConfirmedPlus(0)));
+        assert_eq!("confirmed+32".parse(), Ok(ConfirmationPolicy::ConfirmedPlus(32)));
+        assert_eq!(" confirmed+1 ".parse(), Ok(ConfirmationPolicy::ConfirmedPlus(1)));
+    }
+
+    #[test]
+    fn test_confirmation_policy_rejects_malformed_input() {
+        for input in ["", "processed", "confirmed+", "confirmed+-1", "confirmed+x", "finalized+1", "Finalized"] {
+            assert!(input.parse::<ConfirmationPolicy>().is_err(), "{:?} parsed", input);
+        }
+    }
+
+    #[test]
+    fn test_confirmation_policy_display_round_trips() {
+        for policy in [ConfirmationPolicy::Finalized, ConfirmationPolicy::ConfirmedPlus(0), ConfirmationPolicy::ConfirmedPlus(64)] {
+            assert_eq!(policy.to_string().parse(), Ok(policy));
+        }
+    }
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn layer(text: &str) -> toml::Table {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_args_reads_each_command() {
+        assert_eq!(parse_args(args("")).unwrap().command, Command::Run);
+        assert_eq!(parse_args(args("relay")).unwrap().command, Command::Run);
+        assert_eq!(parse_args(args("status")).unwrap().command, Command::Status);
+        assert_eq!(parse_args(args("dry-run")).unwrap().command, Command::DryRun);
+        assert_eq!(parse_args(args("--help")).unwrap().command, Command::Help);
+        assert_eq!(parse_args(args("backfill -h")).unwrap().command, Command::Help);
+        assert_eq!(
+            parse_args(args("backfill --from-nonce 7")).unwrap().command,
+            Command::Backfill { queue: Queue::Deposits, from_nonce: 7 }
+        );
+        assert_eq!(
+            parse_args(args("--queue burns backfill --from-nonce 0")).unwrap().command,
+            Command::Backfill { queue: Queue::Burns, from_nonce: 0 }
+        );
+        assert_eq!(
+            parse_args(args("replay 5sig")).unwrap().command,
+            Command::Replay { l1_signature: "5sig".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_parse_args_rejects_incomplete_commands() {
+        for line in [
+            "backfill",
+            "backfill --from-nonce x",
+            "backfill --from-nonce 1 --queue messages",
+            "replay",
+            "status extra",
+            "restart",
+            "--verbose",
+            "--keypair",
+            "--set fees",
+            "--set =1",
+        ] {
+            assert!(parse_args(args(line)).is_err(), "{:?} parsed", line);
+        }
+    }
+
+    #[test]
+    fn test_flags_become_setting_overrides() {
+        let cli = parse_args(args(
+            "--l1-rpc http://a --l1-rpc http://b --keypair k.json --set fees.priority_fee_micro_lamports=5000 \
+             --set l2.confirmation=confirmed+4 --set auto_finalize=false",
+        ))
+        .unwrap();
+        assert_eq!(
+            cli.overrides,
+            layer(
+                r#"
+                keypair_path = "k.json"
+                auto_finalize = false
+                [l1]
+                rpc_urls = ["http://a", "http://b"]
+                [l2]
+                confirmation = "confirmed+4"
+                [fees]
+                priority_fee_micro_lamports = 5000
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_value_keeps_toml_types_and_falls_back_to_strings() {
+        assert_eq!(parse_value("42"), toml::Value::Integer(42));
+        assert_eq!(parse_value("true"), toml::Value::Boolean(true));
+        assert_eq!(parse_value("\"quoted\""), toml::Value::String("quoted".to_string()));
+        assert_eq!(
+            parse_value("[\"x\", \"y\"]"),
+            toml::Value::Array(vec![toml::Value::String("x".to_string()), toml::Value::String("y".to_string())])
+        );
+        assert_eq!(parse_value("http://localhost:8899"), toml::Value::String("http://localhost:8899".to_string()));
+        assert_eq!(parse_value("confirmed+32"), toml::Value::String("confirmed+32".to_string()));
+    }
+
+    #[test]
+    fn test_later_layers_override_earlier_ones_key_by_key() {
+        let file = layer(
+            r#"
+            poll_interval_ms = 500
+            [l1]
+            rpc_urls = ["http://file"]
+            confirmation = "confirmed+8"
+            [limits]
+            max_in_flight = 4
+            "#,
+        );
+        let env = layer("[l1]\nconfirmation = \"confirmed+16\"");
+        let flags = layer("[limits]\nmax_in_flight = 2");
+        let settings = resolve([file, env, flags]).unwrap();
+
+        assert_eq!(settings.poll_interval_ms, 500);
+        assert_eq!(settings.l1.rpc_urls, ["http://file"]);
+        assert_eq!(settings.l1.confirmation, "confirmed+16");
+        assert_eq!(settings.limits.max_in_flight, 2);
+        // Siblings the layers leave out keep their defaults
+        assert_eq!(settings.limits.queue_capacity, 256);
+        assert_eq!(settings.l2.rpc_urls, ["http://localhost:8999"]);
+    }
+
+    #[test]
+    fn test_resolve_rejects_unknown_and_mistyped_settings() {
+        assert!(resolve([layer("pol_interval_ms = 1")]).is_err());
+        assert!(resolve([layer("[fees]\npriority_fee = 1")]).is_err());
+        assert!(resolve([layer("poll_interval_ms = \"fast\"")]).is_err());
+        assert!(resolve([layer("[health]\nport = 70000")]).is_err());
+    }
+
+    /// A keypair file for settings that must load one.
+    pub(super) fn keypair_layer(name: &str) -> toml::Table {
+        static FILES: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "relayer-config-{}-{}-{}.json",
+            name,
+            std::process::id(),
+            FILES.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        ));
+        solana_sdk::signature::write_keypair_file(&Keypair::new(), &path).unwrap();
+        let mut table = toml::Table::new();
+        set_path(&mut table, "keypair_path", toml::Value::String(path.display().to_string())).unwrap();
+        table
+    }
+
+    #[test]
+    fn test_from_settings_maps_zero_and_empty_to_unset() {
+        let keypair = keypair_layer("unset");
+        let config = RelayerConfig::from_settings(resolve([keypair.clone()]).unwrap()).unwrap();
+        assert_eq!(config.max_relays_per_poll, None);
+        assert_eq!(config.relay_batch_limit(), usize::MAX);
+        assert_eq!(config.l1_ws_url, None);
+        assert_eq!(config.l1_confirmation, ConfirmationPolicy::Finalized);
+
+        let set = layer("[limits]\nmax_relays_per_poll = 3\n[l2]\nws_url = \"ws://l2\"\nconfirmation = \"confirmed+2\"");
+        let config = RelayerConfig::from_settings(resolve([keypair.clone(), set]).unwrap()).unwrap();
+        assert_eq!(config.relay_batch_limit(), 3);
+        assert_eq!(config.l2_ws_url.as_deref(), Some("ws://l2"));
+        assert_eq!(config.l2_confirmation, ConfirmationPolicy::ConfirmedPlus(2));
+        std::fs::remove_file(keypair["keypair_path"].as_str().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_from_settings_rejects_invalid_values() {
+        let keypair = keypair_layer("invalid");
+        for bad in [
+            "[l1]\nrpc_urls = []",
+            "[l1]\nconfirmation = \"processed\"",
+            "[programs]\nbridge_l2 = \"not-a-pubkey\"",
+            "post_interval_slots = 0",
+            "[limits]\nmax_send_retries = 0",
+            "[limits]\nmax_relay_attempts = 0",
+            "[limits]\nmax_in_flight = 0",
+            "[limits]\nqueue_capacity = 0",
+        ] {
+            let settings = resolve([keypair.clone(), layer(bad)]).unwrap();
+            assert!(RelayerConfig::from_settings(settings).is_err(), "{:?} accepted", bad);
+        }
+        std::fs::remove_file(keypair["keypair_path"].as_str().unwrap()).ok();
+
+        let missing = layer("keypair_path = \"/nonexistent/relayer-keypair.json\"");
+        assert!(RelayerConfig::from_settings(resolve([missing]).unwrap()).is_err());
+    }
+}
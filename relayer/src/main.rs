@@ -1,68 +1,327 @@
 // mythic-relayer: Bridge relayer service for Mythic L2
-// Watches L1 deposits and L2 BridgeToL1 events, relays between chains.
-// Uses the Native Transfer Bridge model — no SPL tokens on L2.
+// Watches L1 deposits and L2 BridgeToL1 / BurnWrapped events, relays between chains,
+// and delivers SendMessage cross-domain messages in both directions. Each
+// direction is polled by its own task and relays are submitted concurrently
+// (see Main Loop).
+// `mythic-relayer watchtower` instead checks every L1 withdrawal against L2
+// and challenges the ones that do not match (watchtower.rs), and
+// `mythic-relayer post-roots` posts and finalizes settlement state roots as
+// the sequencer (poster.rs). `backfill`,
+// `replay`, `status` and `dry-run` are one-shot operator commands; settings
+// come from relayer.toml, RELAYER_* env vars and flags (see config.rs).
+// MYTH uses the Native Transfer Bridge model; every other L1 mint is minted
+// and burned as a wrapped SPL token on L2.
 
-use borsh::BorshSerialize;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use solana_client::rpc_client::RpcClient;
+use sha2::{Digest, Sha256};
+use futures::StreamExt;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::{
+    RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig, RpcTransactionLogsConfig,
+    RpcTransactionLogsFilter,
+};
+use solana_client::rpc_response::{RpcConfirmedTransactionStatusWithSignature, RpcLogsResponse};
+use solana_transaction_status::TransactionStatus;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    signature::{read_keypair_file, Keypair, Signer},
+    signature::{Signature, Signer},
     transaction::Transaction,
 };
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::io::Write;
+use std::future::Future;
+use std::io::{Read, Write};
 use std::net::TcpListener;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::{mpsc, Notify, Semaphore};
+use tokio::task::JoinSet;
+
+mod config;
+mod metrics;
+mod poster;
+mod store;
+mod watchtower;
+use config::{Command, ConfirmationPolicy, RelayerConfig};
+use metrics::Metrics;
+use store::{
+    DisputeState, EventRecord, EventStatus, LegacyProgress, Queue, RelayerStore, RootState, WatchVerdict,
+    WithdrawalState,
+};
 
 // ── Program Constants ───────────────────────────────────────────────────────
 
 const BRIDGE_CONFIG_SEED: &[u8] = b"bridge_config";
 const WITHDRAWAL_SEED: &[u8] = b"withdrawal";
+const VAULT_SEED: &[u8] = b"vault";
+const SOL_VAULT_SEED: &[u8] = b"sol_vault";
+const FAST_WITHDRAWAL_SEED: &[u8] = b"fast_withdrawal";
+const CHALLENGE_BOND_SEED: &[u8] = b"challenge_bond";
+const STATE_ROOT_SEED: &[u8] = b"state_root";
+const SETTLEMENT_CONFIG_SEED: &[u8] = b"settlement_config";
+const CHALLENGE_SEED: &[u8] = b"challenge";
+const SEQUENCER_SET_SEED: &[u8] = b"sequencer_set";
+const SEQUENCER_VAULT_SEED: &[u8] = b"sequencer_vault";
+const BATCH_DATA_SEED: &[u8] = b"batch_data";
+const BATCH_CHUNK_SEED: &[u8] = b"batch_chunk";
+const SETTLEMENT_PROGRAM_ID: &str = "4TrowzShv4CrsuqZeUdLLVMdnDDkqkmnER1MZ5NsSaav";
 const L2_BRIDGE_CONFIG_SEED: &[u8] = b"l2_bridge_config";
 const BRIDGE_RESERVE_SEED: &[u8] = b"bridge_reserve";
 const PROCESSED_SEED: &[u8] = b"processed";
+const WRAPPED_MINT_SEED: &[u8] = b"wrapped_mint";
+const L2_MINT_SEED: &[u8] = b"mint";
+const ATTESTATION_SEED: &[u8] = b"attestation";
+const MESSAGE_SEED: &[u8] = b"message";
+const MESSAGE_ORIGIN_SEED: &[u8] = b"message_origin";
+const MYTH_L1_MINT: &str = "5UP2iL9DefXC3yovX9b4XG2EiCnyxuVo3S2F6ik5pump";
+/// Mint recorded on L1 WithdrawalRequests for native SOL.
+const NATIVE_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
 
 // Instruction discriminators
 const IX_INITIATE_WITHDRAWAL: u8 = 3;
+const IX_CHALLENGE_WITHDRAWAL: u8 = 4;
+const IX_FINALIZE_WITHDRAWAL: u8 = 5;
+const IX_FINALIZE_SOL_WITHDRAWAL: u8 = 10;
+const IX_CLOSE_FAST_WITHDRAWAL: u8 = 18;
+const IX_MIGRATE_WITHDRAWAL: u8 = 19;
 const IX_RELEASE_BRIDGED: u8 = 2;
+const IX_MINT_WRAPPED: u8 = 9;
+const IX_ATTEST_DEPOSIT: u8 = 11;
+/// ReceiveMessage uses the same discriminator on both bridges.
+const IX_RECEIVE_MESSAGE: u8 = 15;
+// Settlement
+const IX_POST_STATE_ROOT: u8 = 1;
+const IX_CHALLENGE_STATE_ROOT: u8 = 2;
+const IX_RESOLVE_CHALLENGE: u8 = 3;
+const IX_FINALIZE_STATE_ROOT: u8 = 4;
+const IX_RESPOND_BISECTION: u8 = 15;
+const IX_INIT_BATCH_DATA: u8 = 22;
+const IX_WRITE_BATCH_CHUNK: u8 = 23;
+const IX_SEAL_BATCH_CHUNKS: u8 = 24;
+
+/// SettlementError codes PostStateRoot fails with when another post got
+/// there first: SlotNotIncreasing and PreviousStateRootMismatch.
+const SETTLEMENT_ERR_SLOT_NOT_INCREASING: u32 = 5;
+const SETTLEMENT_ERR_PREVIOUS_ROOT_MISMATCH: u32 = 6;
+
+/// Batch data is sealed in chunks of this many bytes, at most
+/// MAX_BATCH_CHUNKS per root (2 MB).
+const BATCH_CHUNK_SIZE: usize = 8_192;
+const MAX_BATCH_CHUNKS: usize = 256;
+/// Bytes written per WriteBatchChunk transaction, to stay under the
+/// 1232-byte packet limit.
+const BATCH_WRITE_BYTES: usize = 900;
+/// Chunk accounts passed per SealBatchChunks transaction.
+const SEAL_CHUNKS_PER_TX: usize = 16;
+/// Byte offset of `available` in the settlement BatchDataHeader
+/// (l2_slot, poster, total_len).
+const BATCH_AVAILABLE_OFFSET: usize = 8 + 32 + 4;
+/// Byte offset of the `chunk_hashes` length in BatchDataHeader
+/// (..., available, bump).
+const BATCH_CHUNK_COUNT_OFFSET: usize = BATCH_AVAILABLE_OFFSET + 1 + 1;
+
+/// ai-precompiles event counted into a root's `ai_attestation_count`.
+const AI_RESULT_EVENT_PREFIX: &str = "EVENT:ResultSubmitted:";
+
+/// Size of an L1 WithdrawalRequest still in the v1 layout, from before
+/// withdrawals named a state root; MigrateWithdrawal rewrites it.
+const L1_WITHDRAWAL_V1_LEN: usize = 122;
+
+/// Byte offset of `deposit_nonce` in the L1 BridgeConfig
+/// (admin, sequencer, challenge_period).
+const L1_CONFIG_DEPOSIT_NONCE_OFFSET: usize = 32 + 32 + 8;
+
+/// Byte offset of `withdraw_nonce` in L2BridgeConfig (admin, relayer).
+const L2_CONFIG_WITHDRAW_NONCE_OFFSET: usize = 32 + 32;
+
+/// Byte offset of `state_root` in the settlement StateRootAccount (l2_slot).
+const STATE_ROOT_OFFSET: usize = 8;
+/// Byte offset of `transaction_batch_hash` in StateRootAccount
+/// (l2_slot, state_root, transaction_count).
+const STATE_ROOT_BATCH_HASH_OFFSET: usize = 8 + 32 + 4;
+/// Byte offset of `challenge_deadline` in StateRootAccount (..., batch hash,
+/// ai_attestation_count, previous_state_root, sequencer, posted_at);
+/// `status` follows it.
+const STATE_ROOT_DEADLINE_OFFSET: usize = STATE_ROOT_BATCH_HASH_OFFSET + 32 + 2 + 32 + 32 + 8;
+
+/// Byte offset of `l2_chain_id` in SettlementConfig (is_initialized, admin,
+/// sequencer, challenge_period_slots).
+const SETTLEMENT_CHAIN_ID_OFFSET: usize = 1 + 32 + 32 + 8;
+/// Byte offset of `min_challenger_bond` in SettlementConfig, right after
+/// `l2_chain_id`.
+const SETTLEMENT_MIN_BOND_OFFSET: usize = SETTLEMENT_CHAIN_ID_OFFSET + 16;
+/// Byte offset of `last_posted_slot` in SettlementConfig, right after
+/// `min_challenger_bond`; `last_state_root` follows it.
+const SETTLEMENT_LAST_POSTED_SLOT_OFFSET: usize = SETTLEMENT_MIN_BOND_OFFSET + 8;
+/// Byte offset of `rewound_through_slot` in a v3 SettlementConfig (...,
+/// last_finalized_slot, total_roots_posted, total_challenges, is_paused,
+/// bump, pending_admin, step_verifier, last_finalized_root).
+const SETTLEMENT_REWOUND_SLOT_OFFSET: usize =
+    SETTLEMENT_LAST_POSTED_SLOT_OFFSET + 8 + 32 + 8 + 8 + 8 + 1 + 1 + 32 + 32 + 32;
+
+/// Size of the settlement ChallengeAccount; a root challenger pays its rent
+/// on top of the bond.
+const SETTLEMENT_CHALLENGE_SIZE: usize = 246;
+/// Byte offset of `status` in ChallengeAccount (l2_slot, challenger,
+/// fraud_proof_type, proof_data_hash, bond_amount, created_at).
+const CHALLENGE_STATUS_OFFSET: usize = 8 + 32 + 1 + 32 + 8 + 8;
+/// Byte offset of `defender` in ChallengeAccount (..., status, bump).
+const CHALLENGE_DEFENDER_OFFSET: usize = CHALLENGE_STATUS_OFFSET + 1 + 1;
+/// Byte offset of `lo_step` in ChallengeAccount (..., status, bump,
+/// defender, defender_bond); the bisection game runs to the end.
+const CHALLENGE_GAME_OFFSET: usize = CHALLENGE_STATUS_OFFSET + 1 + 1 + 32 + 8;
+/// FraudProofType::InvalidStateTransition, the only root dispute settlement
+/// can decide on-chain.
+const FRAUD_PROOF_INVALID_STATE_TRANSITION: u8 = 0;
+
+/// Byte offset of `bond_amount` in the L1 bridge's ChallengeBond
+/// (challenger, withdrawal_nonce).
+const CHALLENGE_BOND_AMOUNT_OFFSET: usize = 32 + 8;
+
+/// Domain separators of the L2 state root's withdrawal Merkle tree.
+const MERKLE_LEAF_PREFIX: u8 = 0x00;
+const MERKLE_NODE_PREFIX: u8 = 0x01;
+const MESSAGE_LEAF_PREFIX: u8 = 0x02;
+
+/// Byte offsets of `recipient` and `lp` in the L1 FastWithdrawal offer
+/// (withdrawal_nonce, recipient, fee, lp). `lp` is all zeroes until filled.
+const FAST_WITHDRAWAL_RECIPIENT_OFFSET: usize = 8;
+const FAST_WITHDRAWAL_LP_OFFSET: usize = 8 + 32 + 8;
+
+/// Byte offset of `relayer_threshold` in L2BridgeConfig (end of the v1 layout).
+const L2_CONFIG_THRESHOLD_OFFSET: usize = 132;
+/// Byte offset of the `attesters` length prefix in DepositAttestation
+/// (nonce, deposit_hash, bump).
+const ATTESTATION_ATTESTERS_OFFSET: usize = 8 + 32 + 1;
+
+/// Byte offset of `decimals` in an SPL / Token-2022 mint.
+const MINT_DECIMALS_OFFSET: usize = 44;
 
 /// Decimal scaling: L1 MYTH = 6 decimals, L2 MYTH = 9 decimals.
 const DECIMAL_SCALING_FACTOR: u64 = 1_000;
 
 // ── State Persistence ───────────────────────────────────────────────────────
 
-#[derive(Serialize, Deserialize, Debug, Default)]
-struct RelayerState {
-    last_deposit_nonce: u64,
-    last_burn_nonce: u64,
+/// Layout of the JSON state file used before the embedded store. Only read
+/// once, to import it into a fresh store.
+#[derive(Deserialize, Debug, Default)]
+struct LegacyState {
+    #[serde(default)]
+    last_deposit_nonce: Option<u64>,
+    #[serde(default)]
+    last_burn_nonce: Option<u64>,
     last_deposit_signature: Option<String>,
     last_burn_signature: Option<String>,
+    #[serde(default)]
+    deposit_ledger: BTreeMap<u64, LegacyLedgerEntry>,
 }
 
-impl RelayerState {
-    fn load(path: &PathBuf) -> Self {
-        match fs::read_to_string(path) {
-            Ok(data) => serde_json::from_str(&data).unwrap_or_default(),
-            Err(_) => Self::default(),
-        }
-    }
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+enum LegacyLedgerStatus {
+    Pending,
+    Relayed,
+    Failed,
+}
 
-    fn save(&self, path: &PathBuf) {
-        if let Ok(json) = serde_json::to_string_pretty(self) {
-            let _ = fs::write(path, json);
-        }
+#[derive(Deserialize, Debug)]
+struct LegacyLedgerEntry {
+    l1_signature: String,
+    event: L1Event,
+    status: LegacyLedgerStatus,
+    attempts: u32,
+    last_error: Option<String>,
+}
+
+/// An L1 bridge event that is relayed to L2 under its deposit nonce.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+enum L1Event {
+    Deposit(DepositEvent),
+    Message(SendMessageEvent),
+}
+
+/// An L2 bridge event that is relayed to L1 under its withdraw nonce.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+enum L2Event {
+    Burn(BridgeToL1Event),
+    Message(SendMessageEvent),
+}
+
+/// Import the legacy JSON state file into a fresh store, then rename it so
+/// it is never imported twice.
+fn migrate_legacy_state(store: &mut RelayerStore, path: &PathBuf) -> Result<(), String> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(_) => return Ok(()),
+    };
+    if !store.is_empty()? {
+        return Err(format!(
+            "Both {} and a populated store exist; remove one",
+            path.display()
+        ));
     }
+    let legacy: LegacyState = serde_json::from_str(&data)
+        .map_err(|e| format!("Unreadable legacy state {}: {}", path.display(), e))?;
+
+    let now = store::now();
+    let deposits: Vec<EventRecord<L1Event>> = legacy
+        .deposit_ledger
+        .into_iter()
+        .map(|(nonce, entry)| EventRecord {
+            nonce,
+            source_signature: entry.l1_signature,
+            source_slot: 0,
+            event: entry.event,
+            status: match entry.status {
+                LegacyLedgerStatus::Pending => EventStatus::Pending,
+                LegacyLedgerStatus::Relayed => EventStatus::Relayed,
+                LegacyLedgerStatus::Failed => EventStatus::Failed,
+            },
+            attempts: entry.attempts,
+            last_error: entry.last_error,
+            relay_signature: None,
+            observed_at: now,
+            updated_at: now,
+        })
+        .collect();
+
+    store.import(
+        &deposits,
+        &LegacyProgress {
+            signature: legacy.last_deposit_signature,
+            last_nonce: legacy.last_deposit_nonce,
+        },
+        &LegacyProgress {
+            signature: legacy.last_burn_signature,
+            last_nonce: legacy.last_burn_nonce,
+        },
+    )?;
+
+    let migrated = path.with_extension("json.migrated");
+    fs::rename(path, &migrated)
+        .map_err(|e| format!("Imported {} but could not rename it: {}", path.display(), e))?;
+    println!(
+        "[STORE] Imported {} ledger entries from {} (now {})",
+        deposits.len(),
+        path.display(),
+        migrated.display()
+    );
+    Ok(())
 }
 
 // ── Event Types ─────────────────────────────────────────────────────────────
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct DepositEvent {
     depositor: String,
     l2_recipient: String,
@@ -73,89 +332,81 @@ struct DepositEvent {
 }
 
 fn default_sol_mint() -> String {
-    "So11111111111111111111111111111111111111112".to_string()
+    NATIVE_SOL_MINT.to_string()
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct BridgeToL1Event {
     sender: String,
     l1_recipient: String,
     amount: u64,
     withdraw_nonce: u64,
+    /// Set by BurnWrapped; absent for native MYTH BridgeToL1 events.
+    #[serde(default)]
+    l1_mint: Option<String>,
 }
 
-// ── Config ──────────────────────────────────────────────────────────────────
-
-struct RelayerConfig {
-    l1_rpc_url: String,
-    l2_rpc_url: String,
-    relayer_keypair: Keypair,
-    bridge_l1_program: Pubkey,
-    bridge_l2_program: Pubkey,
-    health_port: u16,
-    poll_interval_ms: u64,
-    state_file: PathBuf,
+/// InitiateWithdrawal event from the L1 bridge.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct InitiateWithdrawalEvent {
+    recipient: String,
+    amount: u64,
+    token_mint: String,
+    nonce: u64,
+    l2_slot: u64,
+    challenge_deadline: i64,
 }
 
-impl RelayerConfig {
-    fn from_env() -> Result<Self, String> {
-        let l1_rpc_url =
-            std::env::var("L1_RPC_URL").unwrap_or_else(|_| "http://localhost:8899".to_string());
-        let l2_rpc_url =
-            std::env::var("L2_RPC_URL").unwrap_or_else(|_| "http://localhost:8999".to_string());
-
-        let keypair_path = std::env::var("RELAYER_KEYPAIR_PATH")
-            .unwrap_or_else(|_| "relayer-keypair.json".to_string());
-        let relayer_keypair = read_keypair_file(&keypair_path)
-            .map_err(|e| format!("Failed to read keypair from {}: {}", keypair_path, e))?;
-
-        let bridge_l1_program = Pubkey::from_str(
-            &std::env::var("BRIDGE_L1_PROGRAM")
-                .unwrap_or_else(|_| "BE2pz9kxPJLHd65B9tVBuZUwp3y5mKYczb6JLMsyPymA".to_string()),
-        )
-        .map_err(|e| format!("Invalid BRIDGE_L1_PROGRAM: {}", e))?;
-
-        let bridge_l2_program = Pubkey::from_str(
-            &std::env::var("BRIDGE_L2_PROGRAM")
-                .unwrap_or_else(|_| "5t8JwXzGQ3c7PCY6p6oJqZgFt8gff2d6uTLrqa1jFrKP".to_string()),
-        )
-        .map_err(|e| format!("Invalid BRIDGE_L2_PROGRAM: {}", e))?;
+/// SendMessage event, emitted identically by both bridges.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct SendMessageEvent {
+    sender: String,
+    target_program: String,
+    accounts: Vec<MessageAccountEvent>,
+    /// Hex-encoded payload.
+    payload: String,
+    nonce: u64,
+    message_hash: String,
+}
 
-        let health_port: u16 = std::env::var("HEALTH_PORT")
-            .unwrap_or_else(|_| "9090".to_string())
-            .parse()
-            .map_err(|e| format!("Invalid HEALTH_PORT: {}", e))?;
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct MessageAccountEvent {
+    pubkey: String,
+    is_writable: bool,
+}
 
-        let poll_interval_ms: u64 = std::env::var("POLL_INTERVAL_MS")
-            .unwrap_or_else(|_| "2000".to_string())
-            .parse()
-            .map_err(|e| format!("Invalid POLL_INTERVAL_MS: {}", e))?;
+// ── Instruction Builders ────────────────────────────────────────────────────
 
-        let state_file = PathBuf::from(
-            std::env::var("STATE_FILE").unwrap_or_else(|_| "relayer_state.json".to_string()),
-        );
+#[derive(BorshSerialize)]
+struct ReleaseBridgedParams {
+    l1_deposit_nonce: u64,
+    recipient: Pubkey,
+    amount: u64,
+    l1_tx_signature: [u8; 64],
+    deposit_hash: [u8; 32],
+}
 
-        Ok(Self {
-            l1_rpc_url,
-            l2_rpc_url,
-            relayer_keypair,
-            bridge_l1_program,
-            bridge_l2_program,
-            health_port,
-            poll_interval_ms,
-            state_file,
-        })
-    }
+#[derive(BorshSerialize)]
+struct FinalizeWithdrawalParams {
+    withdrawal_nonce: u64,
+    leaf_index: u64,
+    merkle_proof: Vec<[u8; 32]>,
 }
 
-// ── Instruction Builders ────────────────────────────────────────────────────
+#[derive(BorshSerialize)]
+struct AttestDepositParams {
+    l1_deposit_nonce: u64,
+    deposit_hash: [u8; 32],
+}
 
 #[derive(BorshSerialize)]
-struct ReleaseBridgedParams {
+struct MintWrappedParams {
     l1_deposit_nonce: u64,
     recipient: Pubkey,
     amount: u64,
+    l1_mint: Pubkey,
     l1_tx_signature: [u8; 64],
+    l1_decimals: u8,
 }
 
 #[derive(BorshSerialize)]
@@ -163,8 +414,180 @@ struct InitiateWithdrawalParams {
     recipient: Pubkey,
     amount: u64,
     token_mint: Pubkey,
-    merkle_proof: [u8; 32],
     nonce: u64,
+    l2_slot: u64,
+    leaf_index: u64,
+    merkle_proof: Vec<[u8; 32]>,
+}
+
+#[derive(BorshSerialize)]
+struct MigrateWithdrawalParams {
+    withdrawal_nonce: u64,
+    l2_slot: u64,
+    leaf_index: u64,
+    merkle_proof: Vec<[u8; 32]>,
+}
+
+#[derive(BorshSerialize, Clone)]
+struct MessageAccount {
+    pubkey: Pubkey,
+    is_writable: bool,
+}
+
+/// Mirrors CrossDomainMessage in both bridge programs.
+#[derive(BorshSerialize, Clone)]
+struct CrossDomainMessage {
+    nonce: u64,
+    sender: Pubkey,
+    target_program: Pubkey,
+    accounts: Vec<MessageAccount>,
+    payload: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+struct L2ReceiveMessageParams {
+    message: CrossDomainMessage,
+    l1_tx_signature: [u8; 64],
+}
+
+#[derive(BorshSerialize)]
+struct L1ReceiveMessageParams {
+    message: CrossDomainMessage,
+    l2_slot: u64,
+    leaf_index: u64,
+    merkle_proof: Vec<[u8; 32]>,
+}
+
+/// Inclusion proof for a withdrawal tree leaf under the state root posted
+/// for `l2_slot`, built by `withdrawal_proof`.
+#[derive(Debug, Clone, PartialEq)]
+struct WithdrawalProof {
+    l2_slot: u64,
+    leaf_index: u64,
+    /// Sibling hashes, leaf level first.
+    proof: Vec<[u8; 32]>,
+}
+
+/// Read-only mirror of the L1 bridge's `WithdrawalStatus`.
+#[derive(BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+enum L1WithdrawalStatus {
+    Pending,
+    Challenged,
+    Finalized,
+    Cancelled,
+}
+
+impl L1WithdrawalStatus {
+    /// The finalize crank's view of this status.
+    fn tracked_state(self) -> WithdrawalState {
+        match self {
+            L1WithdrawalStatus::Pending => WithdrawalState::Open,
+            L1WithdrawalStatus::Challenged => WithdrawalState::Challenged,
+            L1WithdrawalStatus::Finalized => WithdrawalState::Finalized,
+            L1WithdrawalStatus::Cancelled => WithdrawalState::Cancelled,
+        }
+    }
+}
+
+/// Read-only mirror of the L1 bridge's `WithdrawalRequest`, without the
+/// trailing bump.
+#[derive(BorshDeserialize, Debug)]
+struct L1WithdrawalRequest {
+    recipient: Pubkey,
+    amount: u64,
+    token_mint: Pubkey,
+    l2_slot: u64,
+    challenge_deadline: i64,
+    status: L1WithdrawalStatus,
+    nonce: u64,
+}
+
+/// Read-only mirror of the settlement `StateRootStatus`.
+#[derive(BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+enum SettlementRootStatus {
+    Posted,
+    Challenged,
+    Finalized,
+    Invalidated,
+}
+
+/// One L2 block in a published transaction batch: every transaction in
+/// wire format, in block order.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct L2BatchBlock {
+    slot: u64,
+    blockhash: [u8; 32],
+    transactions: Vec<Vec<u8>>,
+}
+
+/// sha256(l1_tx_signature || nonce || amount || recipient [|| l1_mint]),
+/// matching `deposit_hash` in bridge-l2. Wrapped mints commit to the L1 mint.
+fn deposit_hash(
+    l1_tx_signature: &[u8; 64],
+    deposit_nonce: u64,
+    amount: u64,
+    recipient: &Pubkey,
+    l1_mint: Option<&Pubkey>,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(l1_tx_signature);
+    hasher.update(deposit_nonce.to_le_bytes());
+    hasher.update(amount.to_le_bytes());
+    hasher.update(recipient.as_ref());
+    if let Some(mint) = l1_mint {
+        hasher.update(mint.as_ref());
+    }
+    hasher.finalize().into()
+}
+
+fn find_attestation_pda(bridge_l2_program: &Pubkey, deposit_nonce: u64, hash: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[ATTESTATION_SEED, &deposit_nonce.to_le_bytes(), hash],
+        bridge_l2_program,
+    )
+    .0
+}
+
+/// Build an AttestDeposit instruction recording this relayer's committee vote.
+fn build_attest_deposit_ix(
+    bridge_l2_program: &Pubkey,
+    relayer: &Pubkey,
+    deposit_nonce: u64,
+    hash: [u8; 32],
+) -> Instruction {
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], bridge_l2_program);
+    let attestation_pda = find_attestation_pda(bridge_l2_program, deposit_nonce, &hash);
+
+    let mut data = vec![IX_ATTEST_DEPOSIT];
+    let params = AttestDepositParams {
+        l1_deposit_nonce: deposit_nonce,
+        deposit_hash: hash,
+    };
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: *bridge_l2_program,
+        accounts: vec![
+            AccountMeta::new(*relayer, true),                    // 0. relayer (signer, payer)
+            AccountMeta::new_readonly(config_pda, false),        // 1. l2_bridge_config PDA
+            AccountMeta::new(attestation_pda, false),            // 2. deposit_attestation PDA
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 3. system_program
+        ],
+        data,
+    }
+}
+
+/// Committee threshold from the L2 bridge config; 0 means the single
+/// configured relayer releases without attestations.
+async fn fetch_committee_threshold(l2_client: &RpcClient, bridge_l2_program: &Pubkey) -> Result<u8, String> {
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], bridge_l2_program);
+    let data = l2_client
+        .get_account_data(&config_pda)
+        .await
+        .map_err(|e| format!("Failed to read L2 bridge config: {}", e))?;
+    Ok(data.get(L2_CONFIG_THRESHOLD_OFFSET).copied().unwrap_or(0))
 }
 
 /// Build a ReleaseBridged instruction for the L2 bridge.
@@ -176,6 +599,7 @@ fn build_release_bridged_ix(
     amount: u64,
     deposit_nonce: u64,
     l1_tx_signature: &[u8; 64],
+    hash: [u8; 32],
 ) -> Instruction {
     let (config_pda, _) =
         Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], bridge_l2_program);
@@ -193,32 +617,127 @@ fn build_release_bridged_ix(
         recipient: *l2_recipient,
         amount,
         l1_tx_signature: *l1_tx_signature,
+        deposit_hash: hash,
     };
     params.serialize(&mut data).unwrap();
+    let attestation_pda = find_attestation_pda(bridge_l2_program, deposit_nonce, &hash);
 
     // Account order must match process_release_bridged in bridge-l2:
     //   0. [signer]           relayer
     //   1. [signer, writable] payer
-    //   2. []                 l2_bridge_config PDA
+    //   2. [writable]         l2_bridge_config PDA
     //   3. [writable]         bridge_reserve PDA
     //   4. [writable]         recipient
     //   5. [writable]         processed_deposit PDA
     //   6. []                 system_program
+    //   7. []                 deposit_attestation PDA
     Instruction {
         program_id: *bridge_l2_program,
         accounts: vec![
             AccountMeta::new_readonly(*relayer, true),           // 0. relayer (signer)
             AccountMeta::new(*relayer, true),                    // 1. payer (signer, writable)
-            AccountMeta::new(*config_pda, false),                // 2. l2_bridge_config PDA (writable for accounting)
+            AccountMeta::new(config_pda, false),                 // 2. l2_bridge_config PDA (writable for accounting)
             AccountMeta::new(reserve_pda, false),                // 3. bridge_reserve PDA (writable)
             AccountMeta::new(*l2_recipient, false),              // 4. recipient (writable)
             AccountMeta::new(processed_pda, false),              // 5. processed_deposit PDA (writable)
             AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 6. system_program
+            AccountMeta::new(attestation_pda, false),            // 7. deposit_attestation PDA
         ],
         data,
     }
 }
 
+/// Build the instructions to mint wrapped tokens for a non-MYTH L1 deposit:
+/// an idempotent ATA create for the recipient, then MintWrapped.
+/// `l2_token_program` owns the wrapped mint; `l1_decimals` comes from the
+/// L1 mint and is checked against it on L2.
+#[allow(clippy::too_many_arguments)]
+fn build_mint_wrapped_ixs(
+    bridge_l2_program: &Pubkey,
+    relayer: &Pubkey,
+    l2_recipient: &Pubkey,
+    amount: u64,
+    l1_mint: &Pubkey,
+    l1_decimals: u8,
+    l2_token_program: &Pubkey,
+    deposit_nonce: u64,
+    l1_tx_signature: &[u8; 64],
+    hash: [u8; 32],
+) -> Vec<Instruction> {
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], bridge_l2_program);
+    let (wrapped_info_pda, _) =
+        Pubkey::find_program_address(&[WRAPPED_MINT_SEED, l1_mint.as_ref()], bridge_l2_program);
+    let (l2_mint_pda, _) =
+        Pubkey::find_program_address(&[L2_MINT_SEED, l1_mint.as_ref()], bridge_l2_program);
+    let (processed_pda, _) = Pubkey::find_program_address(
+        &[PROCESSED_SEED, &deposit_nonce.to_le_bytes()],
+        bridge_l2_program,
+    );
+    let attestation_pda = find_attestation_pda(bridge_l2_program, deposit_nonce, &hash);
+    let (recipient_ata, create_ata_ix) =
+        build_create_ata_ix(relayer, l2_recipient, &l2_mint_pda, l2_token_program);
+
+    let mut data = vec![IX_MINT_WRAPPED];
+    let params = MintWrappedParams {
+        l1_deposit_nonce: deposit_nonce,
+        recipient: *l2_recipient,
+        amount,
+        l1_mint: *l1_mint,
+        l1_tx_signature: *l1_tx_signature,
+        l1_decimals,
+    };
+    params.serialize(&mut data).unwrap();
+
+    // Account order must match process_mint_wrapped in bridge-l2
+    let mint_ix = Instruction {
+        program_id: *bridge_l2_program,
+        accounts: vec![
+            AccountMeta::new_readonly(*relayer, true),           // 0. relayer (signer)
+            AccountMeta::new(*relayer, true),                    // 1. payer (signer, writable)
+            AccountMeta::new_readonly(config_pda, false),        // 2. l2_bridge_config PDA
+            AccountMeta::new(wrapped_info_pda, false),           // 3. wrapped_token_info PDA (writable)
+            AccountMeta::new(l2_mint_pda, false),                // 4. l2_mint PDA (writable)
+            AccountMeta::new(recipient_ata, false),              // 5. recipient token account
+            AccountMeta::new(processed_pda, false),              // 6. processed_deposit PDA
+            AccountMeta::new_readonly(*l2_token_program, false), // 7. token_program
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 8. system_program
+            AccountMeta::new(attestation_pda, false),            // 9. deposit_attestation PDA
+        ],
+        data,
+    };
+
+    vec![create_ata_ix, mint_ix]
+}
+
+/// The owner's associated token account for `mint`, and a CreateIdempotent
+/// instruction (discriminator 1) that creates it if missing.
+fn build_create_ata_ix(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> (Pubkey, Instruction) {
+    let ata_program = Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).unwrap();
+    let (ata, _) = Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ata_program,
+    );
+    let ix = Instruction {
+        program_id: ata_program,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(ata, false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data: vec![1],
+    };
+    (ata, ix)
+}
+
 fn build_initiate_withdrawal_ix(
     bridge_l1_program: &Pubkey,
     relayer: &Pubkey,
@@ -226,20 +745,28 @@ fn build_initiate_withdrawal_ix(
     amount: u64,
     token_mint: &Pubkey,
     nonce: u64,
+    proof: WithdrawalProof,
 ) -> Instruction {
     let (config_pda, _) =
         Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], bridge_l1_program);
     let nonce_bytes = nonce.to_le_bytes();
     let (withdrawal_pda, _) =
         Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce_bytes], bridge_l1_program);
+    let settlement_program = Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap();
+    let (state_root_pda, _) = Pubkey::find_program_address(
+        &[STATE_ROOT_SEED, &proof.l2_slot.to_le_bytes()],
+        &settlement_program,
+    );
 
     let mut data = vec![IX_INITIATE_WITHDRAWAL];
     let params = InitiateWithdrawalParams {
         recipient: *recipient,
         amount,
         token_mint: *token_mint,
-        merkle_proof: [0u8; 32], // Placeholder — real proof computed off-chain
         nonce,
+        l2_slot: proof.l2_slot,
+        leaf_index: proof.leaf_index,
+        merkle_proof: proof.proof,
     };
     params.serialize(&mut data).unwrap();
 
@@ -247,512 +774,3390 @@ fn build_initiate_withdrawal_ix(
     //   0. [signer]           sequencer
     //   1. [signer, writable] payer
     //   2. [writable]         withdrawal_request PDA
-    //   3. []                 bridge_config PDA
+    //   3. [writable]         bridge_config PDA
     //   4. []                 system_program
+    //   5. []                 settlement state_root PDA
     Instruction {
         program_id: *bridge_l1_program,
         accounts: vec![
             AccountMeta::new_readonly(*relayer, true),  // 0. sequencer (signer)
             AccountMeta::new(*relayer, true),            // 1. payer (signer, writable)
             AccountMeta::new(withdrawal_pda, false),     // 2. withdrawal PDA (writable)
-            AccountMeta::new_readonly(config_pda, false),// 3. bridge config
+            AccountMeta::new(config_pda, false),         // 3. bridge config
             AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 4. system_program
+            AccountMeta::new_readonly(state_root_pda, false), // 5. settlement state root
         ],
         data,
     }
 }
 
-// ── Log Parser ──────────────────────────────────────────────────────────────
+/// Build FinalizeWithdrawal (SPL, IX 5) or FinalizeSOLWithdrawal (IX 10) for
+/// an L1 WithdrawalRequest whose challenge period is over. `payee` is the
+/// LP for fast-filled withdrawals, otherwise the recipient; `token_program`
+/// is None for native SOL. `payer` must be the bridge sequencer, which gets
+/// back the relayer bond it escrowed at InitiateWithdrawal.
+fn build_finalize_withdrawal_ixs(
+    bridge_l1_program: &Pubkey,
+    payer: &Pubkey,
+    withdrawal: &L1WithdrawalRequest,
+    payee: &Pubkey,
+    token_program: Option<&Pubkey>,
+    proof: WithdrawalProof,
+) -> Vec<Instruction> {
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], bridge_l1_program);
+    let nonce_bytes = withdrawal.nonce.to_le_bytes();
+    let (withdrawal_pda, _) =
+        Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce_bytes], bridge_l1_program);
+    let (fast_withdrawal_pda, _) =
+        Pubkey::find_program_address(&[FAST_WITHDRAWAL_SEED, &nonce_bytes], bridge_l1_program);
+    let settlement_program = Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap();
+    let (state_root_pda, _) = Pubkey::find_program_address(
+        &[STATE_ROOT_SEED, &withdrawal.l2_slot.to_le_bytes()],
+        &settlement_program,
+    );
 
-fn parse_deposit_events(logs: &[String]) -> Vec<DepositEvent> {
-    let mut events = Vec::new();
-    for log in logs {
-        // Match both SPL token deposits and SOL deposits
-        if let Some(json_str) = log
-            .strip_prefix("Program log: EVENT:Deposit:")
-            .or_else(|| log.strip_prefix("Program log: EVENT:DepositSOL:"))
-        {
-            if let Ok(event) = serde_json::from_str::<DepositEvent>(json_str) {
-                events.push(event);
-            }
-        }
-    }
-    events
+    let params = FinalizeWithdrawalParams {
+        withdrawal_nonce: withdrawal.nonce,
+        leaf_index: proof.leaf_index,
+        merkle_proof: proof.proof,
+    };
+
+    let Some(token_program) = token_program else {
+        let (sol_vault_pda, _) = Pubkey::find_program_address(&[SOL_VAULT_SEED], bridge_l1_program);
+        let mut data = vec![IX_FINALIZE_SOL_WITHDRAWAL];
+        params.serialize(&mut data).unwrap();
+
+        // Account order must match process_finalize_sol_withdrawal in bridge (L1)
+        return vec![Instruction {
+            program_id: *bridge_l1_program,
+            accounts: vec![
+                AccountMeta::new(*payer, true),                       // 0. payer (signer, writable)
+                AccountMeta::new(withdrawal_pda, false),              // 1. withdrawal_request PDA
+                AccountMeta::new(sol_vault_pda, false),               // 2. sol_vault PDA
+                AccountMeta::new(*payee, false),                      // 3. recipient (or LP)
+                AccountMeta::new_readonly(config_pda, false),         // 4. bridge_config PDA
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 5. system_program
+                AccountMeta::new_readonly(state_root_pda, false),     // 6. settlement state root
+                AccountMeta::new_readonly(fast_withdrawal_pda, false), // 7. fast_withdrawal PDA
+                AccountMeta::new(*payer, false),                      // 8. sequencer (relayer bond refund)
+            ],
+            data,
+        }];
+    };
+
+    let (vault_pda, _) = Pubkey::find_program_address(
+        &[VAULT_SEED, withdrawal.token_mint.as_ref()],
+        bridge_l1_program,
+    );
+    let (payee_ata, create_ata_ix) =
+        build_create_ata_ix(payer, payee, &withdrawal.token_mint, token_program);
+    let mut data = vec![IX_FINALIZE_WITHDRAWAL];
+    params.serialize(&mut data).unwrap();
+
+    // Account order must match process_finalize_withdrawal in bridge (L1)
+    let finalize_ix = Instruction {
+        program_id: *bridge_l1_program,
+        accounts: vec![
+            AccountMeta::new(*payer, true),                           // 0. payer (signer, writable)
+            AccountMeta::new(withdrawal_pda, false),                  // 1. withdrawal_request PDA
+            AccountMeta::new(vault_pda, false),                       // 2. vault token account
+            AccountMeta::new(payee_ata, false),                       // 3. recipient (or LP) token account
+            AccountMeta::new_readonly(withdrawal.token_mint, false),  // 4. token mint
+            AccountMeta::new_readonly(config_pda, false),             // 5. bridge_config PDA
+            AccountMeta::new_readonly(*token_program, false),         // 6. token_program
+            AccountMeta::new_readonly(state_root_pda, false),         // 7. settlement state root
+            AccountMeta::new_readonly(fast_withdrawal_pda, false),    // 8. fast_withdrawal PDA
+            AccountMeta::new(*payer, false),                          // 9. sequencer (relayer bond refund)
+        ],
+        data,
+    };
+
+    vec![create_ata_ix, finalize_ix]
 }
 
-fn parse_bridge_to_l1_events(logs: &[String]) -> Vec<BridgeToL1Event> {
-    let mut events = Vec::new();
-    for log in logs {
-        if let Some(json_str) = log.strip_prefix("Program log: EVENT:BridgeToL1:") {
-            if let Ok(event) = serde_json::from_str::<BridgeToL1Event>(json_str) {
-                events.push(event);
-            }
-        }
+/// Build CloseFastWithdrawal (IX 18), returning the offer's rent to the
+/// recipient who opened it. Only valid once the withdrawal is finalized.
+fn build_close_fast_withdrawal_ix(
+    bridge_l1_program: &Pubkey,
+    withdrawal_nonce: u64,
+    offer_recipient: &Pubkey,
+) -> Instruction {
+    let nonce_bytes = withdrawal_nonce.to_le_bytes();
+    let (withdrawal_pda, _) =
+        Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce_bytes], bridge_l1_program);
+    let (fast_withdrawal_pda, _) =
+        Pubkey::find_program_address(&[FAST_WITHDRAWAL_SEED, &nonce_bytes], bridge_l1_program);
+    let mut data = vec![IX_CLOSE_FAST_WITHDRAWAL];
+    data.extend_from_slice(&nonce_bytes);
+
+    // Account order must match process_close_fast_withdrawal in bridge (L1)
+    Instruction {
+        program_id: *bridge_l1_program,
+        accounts: vec![
+            AccountMeta::new_readonly(withdrawal_pda, false), // 0. withdrawal_request PDA
+            AccountMeta::new(fast_withdrawal_pda, false),     // 1. fast_withdrawal PDA
+            AccountMeta::new(*offer_recipient, false),        // 2. offer recipient
+        ],
+        data,
     }
-    events
 }
 
-// ── Retry Logic ─────────────────────────────────────────────────────────────
+/// Build MigrateWithdrawal (IX 19), rewriting a v1 WithdrawalRequest in the
+/// current layout under the root `proof` is for. Sequencer only.
+fn build_migrate_withdrawal_ix(
+    bridge_l1_program: &Pubkey,
+    sequencer: &Pubkey,
+    withdrawal_nonce: u64,
+    proof: WithdrawalProof,
+) -> Instruction {
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], bridge_l1_program);
+    let (withdrawal_pda, _) = Pubkey::find_program_address(
+        &[WITHDRAWAL_SEED, &withdrawal_nonce.to_le_bytes()],
+        bridge_l1_program,
+    );
+    let state_root_pda = find_settlement_pda(&[STATE_ROOT_SEED, &proof.l2_slot.to_le_bytes()]);
 
-fn retry_with_backoff<F, T>(mut f: F, max_retries: u32) -> Result<T, String>
-where
-    F: FnMut() -> Result<T, String>,
-{
-    let mut delay_ms = 1000u64;
-    for attempt in 0..max_retries {
-        match f() {
-            Ok(val) => return Ok(val),
-            Err(e) => {
-                if attempt == max_retries - 1 {
-                    return Err(format!("Failed after {} retries: {}", max_retries, e));
-                }
-                eprintln!(
-                    "[RETRY] Attempt {}/{} failed: {}. Retrying in {}ms...",
-                    attempt + 1,
-                    max_retries,
-                    e,
-                    delay_ms
-                );
-                std::thread::sleep(std::time::Duration::from_millis(delay_ms));
-                delay_ms = (delay_ms * 2).min(60_000);
-            }
-        }
+    let mut data = vec![IX_MIGRATE_WITHDRAWAL];
+    MigrateWithdrawalParams {
+        withdrawal_nonce,
+        l2_slot: proof.l2_slot,
+        leaf_index: proof.leaf_index,
+        merkle_proof: proof.proof,
+    }
+    .serialize(&mut data)
+    .unwrap();
+
+    // Account order must match process_migrate_withdrawal in bridge (L1)
+    Instruction {
+        program_id: *bridge_l1_program,
+        accounts: vec![
+            AccountMeta::new(*sequencer, true),               // 0. sequencer (signer, writable)
+            AccountMeta::new(withdrawal_pda, false),          // 1. withdrawal_request PDA
+            AccountMeta::new_readonly(config_pda, false),     // 2. bridge_config PDA
+            AccountMeta::new_readonly(state_root_pda, false), // 3. settlement state root
+        ],
+        data,
     }
-    Err("Retry exhausted".to_string())
+}
+
+fn find_settlement_pda(seeds: &[&[u8]]) -> Pubkey {
+    let settlement_program = Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap();
+    Pubkey::find_program_address(seeds, &settlement_program).0
+}
+
+/// sha256 of the message's Borsh encoding, matching `message_hash` in both bridges.
+fn message_hash(message: &CrossDomainMessage) -> [u8; 32] {
+    Sha256::digest(borsh::to_vec(message).unwrap()).into()
+}
+
+/// Rebuild the message from its event and check it against the emitted hash,
+/// so a malformed log can never be relayed as a different message.
+fn message_from_event(event: &SendMessageEvent) -> Result<CrossDomainMessage, String> {
+    let parse = |s: &str| Pubkey::from_str(s).map_err(|_| format!("Invalid pubkey: {}", s));
+    let accounts = event
+        .accounts
+        .iter()
+        .map(|a| {
+            Ok(MessageAccount {
+                pubkey: parse(&a.pubkey)?,
+                is_writable: a.is_writable,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let message = CrossDomainMessage {
+        nonce: event.nonce,
+        sender: parse(&event.sender)?,
+        target_program: parse(&event.target_program)?,
+        accounts,
+        payload: hex_to_bytes(&event.payload)
+            .ok_or_else(|| format!("Invalid payload hex: {}", event.payload))?,
+    };
+    if bytes_to_hex(&message_hash(&message)) != event.message_hash {
+        return Err(format!("Message hash mismatch for nonce={}", event.nonce));
+    }
+    Ok(message)
+}
+
+fn message_account_metas(message: &CrossDomainMessage) -> impl Iterator<Item = AccountMeta> + '_ {
+    message.accounts.iter().map(|a| AccountMeta {
+        pubkey: a.pubkey,
+        is_signer: false,
+        is_writable: a.is_writable,
+    })
+}
+
+/// Build a ReceiveMessage instruction delivering an L1→L2 message.
+fn build_l2_receive_message_ix(
+    bridge_l2_program: &Pubkey,
+    relayer: &Pubkey,
+    message: &CrossDomainMessage,
+    l1_tx_signature: &[u8; 64],
+) -> Instruction {
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], bridge_l2_program);
+    let (processed_pda, _) = Pubkey::find_program_address(
+        &[PROCESSED_SEED, &message.nonce.to_le_bytes()],
+        bridge_l2_program,
+    );
+    let attestation_pda = find_attestation_pda(bridge_l2_program, message.nonce, &message_hash(message));
+    let (origin_pda, _) = Pubkey::find_program_address(
+        &[MESSAGE_ORIGIN_SEED, message.sender.as_ref()],
+        bridge_l2_program,
+    );
+
+    // Account order must match process_receive_message in bridge-l2
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*relayer, true),           // 0. relayer (signer)
+        AccountMeta::new(*relayer, true),                    // 1. payer (signer, writable)
+        AccountMeta::new_readonly(config_pda, false),        // 2. l2_bridge_config PDA
+        AccountMeta::new(processed_pda, false),              // 3. processed_deposit PDA
+        AccountMeta::new(attestation_pda, false),            // 4. deposit_attestation PDA
+        AccountMeta::new_readonly(origin_pda, false),        // 5. message_origin PDA
+        AccountMeta::new_readonly(message.target_program, false), // 6. target program
+        AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 7. system_program
+    ];
+    accounts.extend(message_account_metas(message)); // 8+ message accounts
+
+    let mut data = vec![IX_RECEIVE_MESSAGE];
+    let params = L2ReceiveMessageParams {
+        message: message.clone(),
+        l1_tx_signature: *l1_tx_signature,
+    };
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: *bridge_l2_program,
+        accounts,
+        data,
+    }
+}
+
+/// Build a ReceiveMessage instruction delivering an L2→L1 message against
+/// a settlement state root. Only succeeds once that root is finalized.
+fn build_l1_receive_message_ix(
+    bridge_l1_program: &Pubkey,
+    payer: &Pubkey,
+    message: &CrossDomainMessage,
+    proof: WithdrawalProof,
+) -> Instruction {
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], bridge_l1_program);
+    let (processed_pda, _) = Pubkey::find_program_address(
+        &[MESSAGE_SEED, &message.nonce.to_le_bytes()],
+        bridge_l1_program,
+    );
+    let settlement_program = Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap();
+    let (state_root_pda, _) = Pubkey::find_program_address(
+        &[STATE_ROOT_SEED, &proof.l2_slot.to_le_bytes()],
+        &settlement_program,
+    );
+    let (origin_pda, _) = Pubkey::find_program_address(
+        &[MESSAGE_ORIGIN_SEED, message.sender.as_ref()],
+        bridge_l1_program,
+    );
+
+    // Account order must match process_receive_message in bridge (L1)
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),                      // 0. payer (signer, writable)
+        AccountMeta::new_readonly(config_pda, false),        // 1. bridge_config PDA
+        AccountMeta::new(processed_pda, false),              // 2. processed_message PDA
+        AccountMeta::new_readonly(state_root_pda, false),    // 3. settlement state root
+        AccountMeta::new_readonly(origin_pda, false),        // 4. message_origin PDA
+        AccountMeta::new_readonly(message.target_program, false), // 5. target program
+        AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 6. system_program
+    ];
+    accounts.extend(message_account_metas(message)); // 7+ message accounts
+
+    let mut data = vec![IX_RECEIVE_MESSAGE];
+    let params = L1ReceiveMessageParams {
+        message: message.clone(),
+        l2_slot: proof.l2_slot,
+        leaf_index: proof.leaf_index,
+        merkle_proof: proof.proof,
+    };
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: *bridge_l1_program,
+        accounts,
+        data,
+    }
+}
+
+/// The tip of the settlement root chain, as read from SettlementConfig.
+/// When a root is invalidated settlement discards every root not yet
+/// finalized and `last_state_root` goes back to the last finalized root.
+struct RootChainTip {
+    /// Chain the roots commit to; part of every signed root commitment.
+    l2_chain_id: [u8; 16],
+    last_posted_slot: u64,
+    last_state_root: [u8; 32],
+    last_finalized_slot: u64,
+    /// Unfinalized roots at or below this slot were discarded by a rewind.
+    rewound_through_slot: u64,
+}
+
+impl RootChainTip {
+    /// Slot of the root `last_state_root` belongs to: the last posted one,
+    /// or the last finalized one if a rewind discarded everything after it.
+    fn live_slot(&self) -> u64 {
+        if self.last_posted_slot <= self.rewound_through_slot {
+            self.last_finalized_slot
+        } else {
+            self.last_posted_slot
+        }
+    }
+
+    /// Whether an unfinalized root at `l2_slot` can no longer finalize.
+    fn is_discarded(&self, l2_slot: u64) -> bool {
+        l2_slot <= self.rewound_through_slot || l2_slot <= self.last_finalized_slot
+    }
+}
+
+/// Read the tip of the root chain from SettlementConfig.
+async fn read_root_chain_tip(l1_client: &RpcClient) -> Result<RootChainTip, String> {
+    let data = l1_client
+        .get_account_data(&find_settlement_pda(&[SETTLEMENT_CONFIG_SEED]))
+        .await
+        .map_err(|e| format!("Failed to read SettlementConfig: {}", e))?;
+    if data.first() != Some(&1) {
+        return Err("Settlement is not initialized".to_string());
+    }
+    let tip = data
+        .get(SETTLEMENT_LAST_POSTED_SLOT_OFFSET..SETTLEMENT_LAST_POSTED_SLOT_OFFSET + 8 + 32 + 8)
+        .ok_or("SettlementConfig too short")?;
+    // Configs from before rewinds existed have never discarded a root
+    let rewound_through_slot = data
+        .get(SETTLEMENT_REWOUND_SLOT_OFFSET..SETTLEMENT_REWOUND_SLOT_OFFSET + 8)
+        .map_or(0, |b| u64::from_le_bytes(b.try_into().unwrap()));
+    Ok(RootChainTip {
+        l2_chain_id: data[SETTLEMENT_CHAIN_ID_OFFSET..SETTLEMENT_CHAIN_ID_OFFSET + 16].try_into().unwrap(),
+        last_posted_slot: u64::from_le_bytes(tip[..8].try_into().unwrap()),
+        last_state_root: tip[8..40].try_into().unwrap(),
+        last_finalized_slot: u64::from_le_bytes(tip[40..].try_into().unwrap()),
+        rewound_through_slot,
+    })
+}
+
+/// Read the state root posted to settlement for `l2_slot`.
+async fn read_posted_state_root(l1_client: &RpcClient, l2_slot: u64) -> Result<[u8; 32], String> {
+    let data = l1_client
+        .get_account_data(&find_settlement_pda(&[STATE_ROOT_SEED, &l2_slot.to_le_bytes()]))
+        .await
+        .map_err(|e| format!("Failed to read posted root for L2 slot {}: {}", l2_slot, e))?;
+    data.get(STATE_ROOT_OFFSET..STATE_ROOT_OFFSET + 32)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| format!("State root account for L2 slot {} too short", l2_slot))
+}
+
+/// Build the withdrawal's Merkle path under the root posted for `l2_slot`
+/// from the burns in the store, checking the store's tree is the one that
+/// was posted.
+async fn posted_withdrawal_proof(
+    l1_client: &RpcClient,
+    store: &Mutex<RelayerStore>,
+    withdraw_nonce: u64,
+    l2_slot: u64,
+) -> Result<WithdrawalProof, String> {
+    let posted_root = read_posted_state_root(l1_client, l2_slot).await?;
+    let records = lock_store(store).events_through::<L2Event>(Queue::Burns, l2_slot)?;
+    let ours = withdrawal_tree_root(&records)?;
+    if ours != posted_root {
+        return Err(format!(
+            "Posted root for L2 slot {} is {}, but the indexed withdrawals give {}",
+            l2_slot,
+            bytes_to_hex(&posted_root),
+            bytes_to_hex(&ours)
+        ));
+    }
+    withdrawal_proof(&records, withdraw_nonce, l2_slot)
+}
+
+// ── RPC Failover ────────────────────────────────────────────────────────────
+
+/// An RPC client for one chain that moves to the next configured endpoint
+/// when the current one stops answering. Shared by the relayer tasks: each
+/// call to `client()` gets the endpoint current at that moment.
+struct FailoverRpc {
+    chain: &'static str,
+    urls: Vec<String>,
+    commitment: CommitmentConfig,
+    /// Index into `urls` and its client.
+    current: RwLock<(usize, Arc<RpcClient>)>,
+    /// Held while probing endpoints so concurrent failures switch only once.
+    switching: tokio::sync::Mutex<()>,
+}
+
+impl FailoverRpc {
+    /// Connect to the first endpoint that answers (the first one if none do).
+    async fn connect(chain: &'static str, urls: &[String], commitment: CommitmentConfig) -> Self {
+        let rpc = Self {
+            chain,
+            urls: urls.to_vec(),
+            commitment,
+            current: RwLock::new((
+                0,
+                Arc::new(RpcClient::new_with_commitment(urls[0].clone(), commitment)),
+            )),
+            switching: tokio::sync::Mutex::new(()),
+        };
+        rpc.check().await;
+        rpc
+    }
+
+    fn client(&self) -> Arc<RpcClient> {
+        self.current.read().unwrap_or_else(|e| e.into_inner()).1.clone()
+    }
+
+    fn url(&self) -> &str {
+        &self.urls[self.current.read().unwrap_or_else(|e| e.into_inner()).0]
+    }
+
+    /// Keep the current endpoint if it still answers, otherwise switch to
+    /// the next one that does. Called after a failed poll or send.
+    async fn check(&self) {
+        let _switching = self.switching.lock().await;
+        if self.client().get_slot().await.is_ok() {
+            return;
+        }
+        let current = self.current.read().unwrap_or_else(|e| e.into_inner()).0;
+        for step in 1..self.urls.len() {
+            let next = (current + step) % self.urls.len();
+            let client = RpcClient::new_with_commitment(self.urls[next].clone(), self.commitment);
+            if client.get_slot().await.is_ok() {
+                eprintln!(
+                    "[RPC] {} endpoint {} is not answering; switched to {}",
+                    self.chain, self.urls[current], self.urls[next]
+                );
+                *self.current.write().unwrap_or_else(|e| e.into_inner()) = (next, Arc::new(client));
+                return;
+            }
+        }
+        eprintln!("[RPC] No {} endpoint is answering; staying on {}", self.chain, self.url());
+    }
+}
+
+// ── Transaction Scanning ────────────────────────────────────────────────────
+
+/// Max page size accepted by `getSignaturesForAddress`.
+const SIGNATURE_PAGE_LIMIT: usize = 1_000;
+
+/// All signatures for `address` newer than `until`, paging backward with
+/// `before` and returned oldest-first. With no cursor only the newest page
+/// is read, unless `full_history` asks for everything the node retains.
+async fn fetch_signatures_since(
+    client: &RpcClient,
+    address: &Pubkey,
+    until: Option<&str>,
+    full_history: bool,
+) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, String> {
+    let until = until
+        .map(|s| s.parse().map_err(|e| format!("Invalid cursor signature {}: {}", s, e)))
+        .transpose()?;
+    let mut before = None;
+    let mut sigs = Vec::new();
+
+    loop {
+        let page = client
+            .get_signatures_for_address_with_config(
+                address,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(SIGNATURE_PAGE_LIMIT),
+                    commitment: Some(client.commitment()),
+                },
+            )
+            .await
+            .map_err(|e| format!("Failed to get signatures for {}: {}", address, e))?;
+        let full_page = page.len() == SIGNATURE_PAGE_LIMIT;
+        if let Some(last) = page.last() {
+            before = Some(
+                last.signature
+                    .parse()
+                    .map_err(|e| format!("Invalid signature: {}", e))?,
+            );
+        }
+        sigs.extend(page);
+        if !full_page || (until.is_none() && !full_history) {
+            break;
+        }
+    }
+
+    sigs.reverse();
+    Ok(sigs)
+}
+
+/// Log messages of a transaction; an empty list if it carries none.
+async fn fetch_transaction_logs(client: &RpcClient, signature: &str) -> Result<Vec<String>, String> {
+    let sig = signature
+        .parse()
+        .map_err(|e| format!("Invalid signature: {}", e))?;
+    let tx = client
+        .get_transaction_with_config(
+            &sig,
+            solana_client::rpc_config::RpcTransactionConfig {
+                encoding: Some(solana_transaction_status::UiTransactionEncoding::Json),
+                commitment: Some(client.commitment()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to get transaction {}: {}", signature, e))?;
+    Ok(match tx.transaction.meta.map(|meta| meta.log_messages) {
+        Some(solana_transaction_status::option_serializer::OptionSerializer::Some(logs)) => logs,
+        _ => Vec::new(),
+    })
+}
+
+/// Deposits and L1→L2 messages emitted by one L1 transaction.
+async fn scan_l1_transaction(client: &RpcClient, signature: &str) -> Result<Vec<(u64, L1Event)>, String> {
+    Ok(L1Event::from_logs(&fetch_transaction_logs(client, signature).await?))
+}
+
+/// Burns and L2→L1 messages emitted by one L2 transaction.
+async fn scan_l2_transaction(client: &RpcClient, signature: &str) -> Result<Vec<(u64, L2Event)>, String> {
+    Ok(L2Event::from_logs(&fetch_transaction_logs(client, signature).await?))
+}
+
+/// A bridge event type, scanned out of its source chain's transactions.
+trait ScannedEvent: Serialize + Sized {
+    /// The events in one transaction's log messages, with their nonces.
+    fn from_logs(logs: &[String]) -> Vec<(u64, Self)>;
+
+    async fn scan(client: &RpcClient, signature: &str) -> Result<Vec<(u64, Self)>, String> {
+        Ok(Self::from_logs(&fetch_transaction_logs(client, signature).await?))
+    }
+}
+
+impl ScannedEvent for L1Event {
+    fn from_logs(logs: &[String]) -> Vec<(u64, Self)> {
+        let mut events = Vec::new();
+        for event in parse_deposit_events(logs) {
+            events.push((event.nonce, L1Event::Deposit(event)));
+        }
+        // L1→L2 messages share the deposit nonce sequence
+        for event in parse_send_message_events(logs) {
+            events.push((event.nonce, L1Event::Message(event)));
+        }
+        events
+    }
+}
+
+impl ScannedEvent for L2Event {
+    fn from_logs(logs: &[String]) -> Vec<(u64, Self)> {
+        let mut events = Vec::new();
+        for event in parse_bridge_to_l1_events(logs) {
+            events.push((event.withdraw_nonce, L2Event::Burn(event)));
+        }
+        // L2→L1 messages share the withdraw nonce sequence and are proven
+        // against the same state roots as withdrawals.
+        for event in parse_send_message_events(logs) {
+            events.push((event.nonce, L2Event::Message(event)));
+        }
+        events
+    }
+}
+
+/// Index every L2 burn and L2→L1 message by withdraw nonce. The first run
+/// reads all the history the L2 node retains.
+async fn index_l2_withdrawals(
+    l2_client: &RpcClient,
+    config: &RelayerConfig,
+    store: &mut RelayerStore,
+) -> Result<(), String> {
+    let cursor = store.cursor(Queue::Burns)?;
+    let sigs = fetch_signatures_since(
+        l2_client,
+        &config.bridge_l2_program,
+        cursor.as_deref(),
+        cursor.is_none(),
+    )
+    .await?;
+
+    for sig_info in &sigs {
+        let events = match sig_info.err {
+            None => scan_l2_transaction(l2_client, &sig_info.signature).await?,
+            Some(_) => Vec::new(),
+        };
+        store.record_scanned(Queue::Burns, &sig_info.signature, sig_info.slot, &events)?;
+    }
+    Ok(())
+}
+
+/// Next deposit nonce the L1 bridge will issue.
+async fn fetch_l1_deposit_nonce(l1_client: &RpcClient, bridge_l1_program: &Pubkey) -> Result<u64, String> {
+    let (config_pda, _) = Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], bridge_l1_program);
+    let data = l1_client
+        .get_account_data(&config_pda)
+        .await
+        .map_err(|e| format!("Failed to read L1 bridge config: {}", e))?;
+    data.get(L1_CONFIG_DEPOSIT_NONCE_OFFSET..L1_CONFIG_DEPOSIT_NONCE_OFFSET + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| "L1 bridge config too short".to_string())
+}
+
+/// Next withdraw nonce the L2 bridge will issue.
+async fn fetch_l2_withdraw_nonce(
+    l2_client: &RpcClient,
+    bridge_l2_program: &Pubkey,
+    commitment: CommitmentConfig,
+) -> Result<u64, String> {
+    let (config_pda, _) = Pubkey::find_program_address(&[L2_BRIDGE_CONFIG_SEED], bridge_l2_program);
+    let data = l2_client
+        .get_account_with_commitment(&config_pda, commitment)
+        .await
+        .map_err(|e| format!("Failed to read L2 bridge config: {}", e))?
+        .value
+        .ok_or_else(|| "L2 bridge config not found".to_string())?
+        .data;
+    data.get(L2_CONFIG_WITHDRAW_NONCE_OFFSET..L2_CONFIG_WITHDRAW_NONCE_OFFSET + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| "L2 bridge config too short".to_string())
+}
+
+// ── Log Parser ──────────────────────────────────────────────────────────────
+
+fn parse_deposit_events(logs: &[String]) -> Vec<DepositEvent> {
+    let mut events = Vec::new();
+    for log in logs {
+        // Match both SPL token deposits and SOL deposits
+        if let Some(json_str) = log
+            .strip_prefix("Program log: EVENT:Deposit:")
+            .or_else(|| log.strip_prefix("Program log: EVENT:DepositSOL:"))
+        {
+            if let Ok(event) = serde_json::from_str::<DepositEvent>(json_str) {
+                events.push(event);
+            }
+        }
+    }
+    events
+}
+
+fn parse_bridge_to_l1_events(logs: &[String]) -> Vec<BridgeToL1Event> {
+    let mut events = Vec::new();
+    for log in logs {
+        // Match both native MYTH withdrawals and wrapped token burns
+        if let Some(json_str) = log
+            .strip_prefix("Program log: EVENT:BridgeToL1:")
+            .or_else(|| log.strip_prefix("Program log: EVENT:BurnWrapped:"))
+        {
+            if let Ok(event) = serde_json::from_str::<BridgeToL1Event>(json_str) {
+                events.push(event);
+            }
+        }
+    }
+    events
+}
+
+fn parse_initiate_withdrawal_events(logs: &[String]) -> Vec<InitiateWithdrawalEvent> {
+    logs.iter()
+        .filter_map(|log| log.strip_prefix("Program log: EVENT:InitiateWithdrawal:"))
+        .filter_map(|json_str| serde_json::from_str(json_str).ok())
+        .collect()
+}
+
+fn parse_send_message_events(logs: &[String]) -> Vec<SendMessageEvent> {
+    let mut events = Vec::new();
+    for log in logs {
+        if let Some(json_str) = log.strip_prefix("Program log: EVENT:SendMessage:") {
+            if let Ok(event) = serde_json::from_str::<SendMessageEvent>(json_str) {
+                events.push(event);
+            }
+        }
+    }
+    events
+}
+
+// ── Retry Logic ─────────────────────────────────────────────────────────────
+
+async fn retry_with_backoff<F, Fut, T>(mut f: F, max_retries: u32) -> Result<T, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let mut delay_ms = 1000u64;
+    for attempt in 0..max_retries {
+        match f().await {
+            Ok(val) => return Ok(val),
+            Err(e) => {
+                if attempt == max_retries - 1 {
+                    return Err(format!("Failed after {} retries: {}", max_retries, e));
+                }
+                eprintln!(
+                    "[RETRY] Attempt {}/{} failed: {}. Retrying in {}ms...",
+                    attempt + 1,
+                    max_retries,
+                    e,
+                    delay_ms
+                );
+                tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+                delay_ms = (delay_ms * 2).min(60_000);
+            }
+        }
+    }
+    Err("Retry exhausted".to_string())
 }
 
 // ── Health Check Server ─────────────────────────────────────────────────────
 
-fn start_health_server(port: u16, running: Arc<AtomicBool>) {
-    std::thread::spawn(move || {
-        let addr = format!("0.0.0.0:{}", port);
-        let listener = match TcpListener::bind(&addr) {
-            Ok(l) => l,
-            Err(e) => {
-                eprintln!("[HEALTH] Failed to bind {}: {}", addr, e);
-                return;
-            }
-        };
-        listener
-            .set_nonblocking(true)
-            .expect("Cannot set non-blocking");
-        println!("[HEALTH] Listening on {}", addr);
+/// Serves `/health` (200, or 503 with the failing checks), `/status` (JSON
+/// snapshot) and `/metrics` (Prometheus text).
+fn start_health_server(port: u16, running: Arc<AtomicBool>, metrics: Arc<Metrics>) {
+    std::thread::spawn(move || {
+        let addr = format!("0.0.0.0:{}", port);
+        let listener = match TcpListener::bind(&addr) {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("[HEALTH] Failed to bind {}: {}", addr, e);
+                return;
+            }
+        };
+        listener
+            .set_nonblocking(true)
+            .expect("Cannot set non-blocking");
+        println!("[HEALTH] Listening on {}", addr);
+
+        while running.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((mut stream, _)) => {
+                    let _ = stream.set_nonblocking(false);
+                    let _ = stream.set_read_timeout(Some(std::time::Duration::from_secs(2)));
+                    let mut buf = [0u8; 1024];
+                    let n = stream.read(&mut buf).unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buf[..n]);
+                    // "GET /path HTTP/1.1"
+                    let path = request
+                        .split_whitespace()
+                        .nth(1)
+                        .unwrap_or("/")
+                        .split('?')
+                        .next()
+                        .unwrap_or("/");
+
+                    let (status, content_type, body) = match path {
+                        "/metrics" => (
+                            "200 OK",
+                            "text/plain; version=0.0.4",
+                            metrics.render_prometheus(),
+                        ),
+                        "/status" => ("200 OK", "application/json", metrics.status_json()),
+                        "/" | "/health" => {
+                            let problems = metrics.health_problems();
+                            if problems.is_empty() {
+                                ("200 OK", "text/plain", "OK".to_string())
+                            } else {
+                                ("503 Service Unavailable", "text/plain", problems.join("\n"))
+                            }
+                        }
+                        _ => ("404 Not Found", "text/plain", "Not Found".to_string()),
+                    };
+                    let response = format!(
+                        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        content_type,
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes());
+                }
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                }
+                Err(e) => {
+                    eprintln!("[HEALTH] Accept error: {}", e);
+                }
+            }
+        }
+    });
+}
+
+/// Refresh the gauges that come from the store and from chain state. RPC
+/// failures leave the previous values in place.
+async fn refresh_metrics(
+    l1_client: &RpcClient,
+    l2_client: &RpcClient,
+    config: &RelayerConfig,
+    store: &Mutex<RelayerStore>,
+    metrics: &Metrics,
+) {
+    {
+        let store = lock_store(store);
+        for queue in [Queue::Deposits, Queue::Burns] {
+            let state = (|| {
+                Ok::<_, String>((
+                    store.max_relayed_nonce(queue)?,
+                    store.lowest_unrelayed_nonce(queue)?,
+                    store.status_counts(queue)?,
+                ))
+            })();
+            match state {
+                Ok((last, lowest, counts)) => metrics.set_queue_state(queue, last, lowest, counts),
+                Err(e) => eprintln!("[METRICS] {}", e),
+            }
+        }
+
+        match store.withdrawal_counts() {
+            Ok(counts) => metrics.set_withdrawals(counts),
+            Err(e) => eprintln!("[METRICS] {}", e),
+        }
+    }
+
+    let l1_deposit_nonce = fetch_l1_deposit_nonce(l1_client, &config.bridge_l1_program).await.ok();
+
+    let relayer = config.relayer_keypair.pubkey();
+    let (reserve_pda, _) =
+        Pubkey::find_program_address(&[BRIDGE_RESERVE_SEED], &config.bridge_l2_program);
+
+    metrics.set_chain_state(
+        l1_deposit_nonce,
+        l1_client.get_balance(&relayer).await.ok(),
+        l2_client.get_balance(&relayer).await.ok(),
+        l2_client.get_balance(&reserve_pda).await.ok(),
+    );
+}
+
+// ── Main Loop ───────────────────────────────────────────────────────────────
+// One producer task per direction scans its bridge into the store and queues
+// the relays that are due on a bounded channel; the submitter sends up to
+// `max_in_flight` of them at once, each on its own task awaiting its own
+// confirmation. Log subscriptions wake a producer early when new events land,
+// and the withdrawal crank and metrics refresh run alongside.
+
+/// State shared by the relayer tasks.
+struct Relayer {
+    config: RelayerConfig,
+    l1: FailoverRpc,
+    l2: FailoverRpc,
+    store: Mutex<RelayerStore>,
+    metrics: Arc<Metrics>,
+    /// Events queued or being submitted, so a poll never queues them twice.
+    in_flight: Mutex<HashSet<(Queue, u64)>>,
+    /// Woken by the log subscriptions when new events land.
+    deposits_due: Notify,
+    burns_due: Notify,
+    running: Arc<AtomicBool>,
+}
+
+impl Relayer {
+    fn store(&self) -> MutexGuard<'_, RelayerStore> {
+        lock_store(&self.store)
+    }
+
+    fn in_flight(&self) -> MutexGuard<'_, HashSet<(Queue, u64)>> {
+        self.in_flight.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Unrelayed events of `queue` not already queued or in flight, up to
+    /// the per-poll limit, now marked in flight. The store is read under the
+    /// in-flight lock so a relay finishing meanwhile is never picked up twice.
+    fn claim_unrelayed<T: DeserializeOwned>(&self, queue: Queue) -> Result<Vec<EventRecord<T>>, String> {
+        let mut in_flight = self.in_flight();
+        let mut claimed = Vec::new();
+        for record in self.store().unrelayed::<T>(queue)? {
+            if claimed.len() >= self.config.relay_batch_limit() {
+                break;
+            }
+            if in_flight.insert((queue, record.nonce)) {
+                claimed.push(record);
+            }
+        }
+        Ok(claimed)
+    }
+
+    /// Hand claimed events back to the next poll.
+    fn release<T>(&self, queue: Queue, records: &[EventRecord<T>]) {
+        let mut in_flight = self.in_flight();
+        for record in records {
+            in_flight.remove(&(queue, record.nonce));
+        }
+    }
+
+    fn due(&self, queue: Queue) -> &Notify {
+        match queue {
+            Queue::Deposits => &self.deposits_due,
+            Queue::Burns => &self.burns_due,
+        }
+    }
+
+    /// Sleep for one poll interval.
+    async fn pause(&self) {
+        tokio::time::sleep(std::time::Duration::from_millis(self.config.poll_interval_ms)).await;
+    }
+
+    /// Sleep for one poll interval, or until a subscription reports new
+    /// events for `queue`.
+    async fn pause_until_due(&self, queue: Queue) {
+        tokio::select! {
+            _ = self.pause() => {}
+            _ = self.due(queue).notified() => {}
+        }
+    }
+}
+
+/// One relay waiting for a submitter.
+enum RelayJob {
+    Deposit {
+        record: EventRecord<L1Event>,
+        /// L2 relayer committee threshold when the deposit was queued.
+        committee_threshold: u8,
+    },
+    Burn(EventRecord<L2Event>),
+}
+
+/// Never held across an await: the store is shared by every task.
+fn lock_store(store: &Mutex<RelayerStore>) -> MutexGuard<'_, RelayerStore> {
+    store.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+async fn run_relayer(config: RelayerConfig) {
+    let running = Arc::new(AtomicBool::new(true));
+    shutdown_on_signal(running.clone());
+
+    let l1 = FailoverRpc::connect("L1", &config.l1_rpc_urls, CommitmentConfig::confirmed()).await;
+    let l2 = FailoverRpc::connect("L2", &config.l2_rpc_urls, CommitmentConfig::confirmed()).await;
+
+    let mut store = match RelayerStore::open(&config.store_path) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("[STORE] Failed to open {}: {}", config.store_path.display(), e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = migrate_legacy_state(&mut store, &config.state_file) {
+        eprintln!("[STORE] Migration failed: {}", e);
+        std::process::exit(1);
+    }
+
+    println!("=== Mythic L2 Bridge Relayer ===");
+    println!("L1 RPC:          {}", config.l1_rpc_urls.join(", "));
+    println!("L2 RPC:          {}", config.l2_rpc_urls.join(", "));
+    println!("Bridge L1:       {}", config.bridge_l1_program);
+    println!("Bridge L2:       {}", config.bridge_l2_program);
+    println!("Relayer:         {}", config.relayer_keypair.pubkey());
+    println!("Health Port:     {}", config.health_port);
+    println!("Poll Interval:   {}ms", config.poll_interval_ms);
+    println!("Auto Finalize:   {}", config.auto_finalize);
+    println!("Subscribe Logs:  {}", config.subscribe_logs);
+    println!("L1 Confirmation: {}", config.l1_confirmation);
+    println!("L2 Confirmation: {}", config.l2_confirmation);
+    println!("In Flight:       {} (queue {})", config.max_in_flight, config.queue_capacity);
+    println!("Store:           {}", config.store_path.display());
+    println!(
+        "Last Deposit:    nonce={:?}",
+        store.max_relayed_nonce(Queue::Deposits).ok().flatten()
+    );
+    println!(
+        "Last Burn:       nonce={:?}",
+        store.max_relayed_nonce(Queue::Burns).ok().flatten()
+    );
+    println!();
+
+    let metrics = Arc::new(Metrics::new(config.health_thresholds));
+    start_health_server(config.health_port, running.clone(), metrics.clone());
+
+    let (jobs_tx, jobs_rx) = mpsc::channel(config.queue_capacity);
+    let relayer = Arc::new(Relayer {
+        config,
+        l1,
+        l2,
+        store: Mutex::new(store),
+        metrics,
+        in_flight: Mutex::new(HashSet::new()),
+        deposits_due: Notify::new(),
+        burns_due: Notify::new(),
+        running: running.clone(),
+    });
+
+    let mut tasks = JoinSet::new();
+    tasks.spawn(produce_relays(relayer.clone(), Queue::Deposits, jobs_tx.clone()));
+    tasks.spawn(produce_relays(relayer.clone(), Queue::Burns, jobs_tx));
+    tasks.spawn(submit_relays(relayer.clone(), jobs_rx));
+    if relayer.config.subscribe_logs {
+        tasks.spawn(subscribe_bridge_logs::<L1Event>(relayer.clone(), Queue::Deposits));
+        tasks.spawn(subscribe_bridge_logs::<L2Event>(relayer.clone(), Queue::Burns));
+    }
+    if relayer.config.auto_finalize {
+        tasks.spawn(run_withdrawal_crank(relayer.clone()));
+    }
+    tasks.spawn(run_metrics_refresh(relayer.clone()));
+
+    // The producers stop on shutdown, which closes the queue; the submitter
+    // then drains it and waits for the relays still in flight.
+    while let Some(result) = tasks.join_next().await {
+        if let Err(e) = result {
+            eprintln!("[SHUTDOWN] Relayer task failed: {}", e);
+            running.store(false, Ordering::Relaxed);
+        }
+    }
+
+    println!("[SHUTDOWN] Relayer stopped.");
+}
+
+/// Poll one direction until shutdown, queueing relays as they come due.
+async fn produce_relays(relayer: Arc<Relayer>, queue: Queue, jobs: mpsc::Sender<RelayJob>) {
+    let (tag, chain) = match queue {
+        Queue::Deposits => ("[DEPOSIT]", "L1"),
+        Queue::Burns => ("[BURN]", "L2"),
+    };
+    while relayer.running.load(Ordering::Relaxed) {
+        let result = match queue {
+            Queue::Deposits => poll_l1_deposits(&relayer, &jobs).await,
+            Queue::Burns => poll_l2_burns(&relayer, &jobs).await,
+        };
+        match result {
+            Ok(queued) => {
+                relayer.metrics.record_poll(queue, Ok(()));
+                if queued > 0 {
+                    println!("{} Queued {} relays", tag, queued);
+                }
+            }
+            Err(e) => {
+                relayer.metrics.record_poll(queue, Err(&e));
+                eprintln!("{} Error polling {}: {}", tag, chain, e);
+                // Move off endpoints that stopped answering
+                relayer.l1.check().await;
+                relayer.l2.check().await;
+            }
+        }
+        relayer.pause_until_due(queue).await;
+    }
+}
+
+/// Scan new L1 bridge transactions into the store, then queue every event
+/// that has not been relayed yet, oldest nonce first.
+async fn poll_l1_deposits(relayer: &Relayer, jobs: &mpsc::Sender<RelayJob>) -> Result<u64, String> {
+    let config = &relayer.config;
+    let l1_client = relayer.l1.client();
+    let cursor = relayer.store().cursor(Queue::Deposits)?;
+    let sigs =
+        fetch_signatures_since(&l1_client, &config.bridge_l1_program, cursor.as_deref(), false)
+            .await?;
+
+    for sig_info in &sigs {
+        // Stop at the first unreadable transaction so the cursor never
+        // moves past events we have not recorded.
+        let events = match sig_info.err {
+            None => scan_l1_transaction(&l1_client, &sig_info.signature).await?,
+            Some(_) => Vec::new(),
+        };
+        let replaced = relayer.store().record_scanned(
+            Queue::Deposits,
+            &sig_info.signature,
+            sig_info.slot,
+            &events,
+        )?;
+        alert_replaced("L1", &replaced, &sig_info.signature);
+        relayer.metrics.record_seen(Queue::Deposits, events.len() as u64);
+    }
+
+    let orphaned = confirm_seen_events(
+        &l1_client,
+        config.l1_confirmation,
+        &relayer.store,
+        Queue::Deposits,
+        "L1",
+    )
+    .await?;
+    relayer.metrics.record_orphaned(Queue::Deposits, orphaned);
+
+    let pending = relayer.claim_unrelayed::<L1Event>(Queue::Deposits)?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let committee_threshold =
+        match fetch_committee_threshold(&relayer.l2.client(), &config.bridge_l2_program).await {
+            Ok(threshold) => threshold,
+            Err(e) => {
+                relayer.release(Queue::Deposits, &pending);
+                return Err(e);
+            }
+        };
+    queue_relays(relayer, jobs, Queue::Deposits, pending, |record| RelayJob::Deposit {
+        record,
+        committee_threshold,
+    })
+    .await
+}
+
+/// Queue claimed events for the submitters, waiting for room while they
+/// are behind. Returns how many were queued.
+async fn queue_relays<T>(
+    relayer: &Relayer,
+    jobs: &mpsc::Sender<RelayJob>,
+    queue: Queue,
+    claimed: Vec<EventRecord<T>>,
+    job: impl Fn(EventRecord<T>) -> RelayJob,
+) -> Result<u64, String> {
+    let total = claimed.len() as u64;
+    for record in claimed {
+        let nonce = record.nonce;
+        if jobs.send(job(record)).await.is_err() {
+            // Only on shutdown; whatever was not queued is relayed next run
+            relayer.in_flight().retain(|&(q, n)| q != queue || n < nonce);
+            return Err("relay submitter has stopped".to_string());
+        }
+    }
+    Ok(total)
+}
+
+/// Take relays off the queue and submit up to `max_in_flight` at once.
+async fn submit_relays(relayer: Arc<Relayer>, mut jobs: mpsc::Receiver<RelayJob>) {
+    let slots = Arc::new(Semaphore::new(relayer.config.max_in_flight));
+    let mut in_flight = JoinSet::new();
+    while let Some(job) = jobs.recv().await {
+        let permit = slots.clone().acquire_owned().await.expect("semaphore is never closed");
+        let relayer = relayer.clone();
+        in_flight.spawn(async move {
+            submit_relay(&relayer, job).await;
+            drop(permit);
+        });
+        while in_flight.try_join_next().is_some() {}
+    }
+    while in_flight.join_next().await.is_some() {}
+}
+
+/// Send one relay, record its outcome and release its nonce for the next poll.
+async fn submit_relay(relayer: &Relayer, job: RelayJob) {
+    let config = &relayer.config;
+    let (queue, nonce, result) = match job {
+        RelayJob::Deposit { record, committee_threshold } => {
+            let outcome =
+                relay_l1_event(&relayer.l1.client(), &relayer.l2.client(), config, committee_threshold, &record).await;
+            let result = record_relay(relayer, Queue::Deposits, &record, outcome).map(|_| ());
+            (Queue::Deposits, record.nonce, result)
+        }
+        RelayJob::Burn(record) => {
+            let outcome =
+                relay_l2_event(&relayer.l1.client(), &relayer.store, config, &record).await;
+            let result = record_relay(relayer, Queue::Burns, &record, outcome).and_then(|relayed| {
+                match (relayed, &record.event) {
+                    (true, L2Event::Burn(_)) => relayer.store().track_withdrawal(record.nonce),
+                    _ => Ok(()),
+                }
+            });
+            (Queue::Burns, record.nonce, result)
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("[STORE] Failed to record relay of {:?} nonce={}: {}", queue, nonce, e);
+    }
+    relayer.in_flight().remove(&(queue, nonce));
+}
+
+/// Store and count the outcome of one relay. Returns whether the event is
+/// now relayed (sent, or already processed by the destination bridge).
+fn record_relay<T>(
+    relayer: &Relayer,
+    queue: Queue,
+    record: &EventRecord<T>,
+    outcome: Result<RelayOutcome, String>,
+) -> Result<bool, String> {
+    let (chain, tag) = match queue {
+        Queue::Deposits => ("L1", "[DEPOSIT]"),
+        Queue::Burns => ("L2", "[BRIDGE_TO_L1]"),
+    };
+    match outcome {
+        Ok(RelayOutcome::Waiting(reason)) => {
+            println!("{} nonce={} not relayable yet: {}", tag, record.nonce, reason);
+            relayer.store().mark_waiting(queue, record.nonce, &reason)?;
+            Ok(false)
+        }
+        Ok(RelayOutcome::Relayed(tx_sig)) => {
+            let tx_sig = tx_sig.map(|sig| sig.to_string());
+            if let Some(sig) = &tx_sig {
+                println!("RELAYED {} EVENT: nonce={} tx={}", chain, record.nonce, sig);
+                relayer.metrics.record_relayed(queue, record.observed_at);
+            }
+            relayer.store().mark_relayed(queue, record.nonce, tx_sig.as_deref())?;
+            Ok(true)
+        }
+        Err(e) => {
+            eprintln!(
+                "{} Failed to relay nonce={} (attempt {}): {}",
+                tag,
+                record.nonce,
+                record.attempts + 1,
+                e
+            );
+            relayer.metrics.record_failed(queue);
+            let max_attempts = relayer.config.max_relay_attempts;
+            if relayer.store().mark_failed(queue, record.nonce, &e, max_attempts)? {
+                eprintln!(
+                    "[DEAD-LETTER] {} nonce={} gave up after {} attempts; re-queue with backfill --from-nonce {}",
+                    chain, record.nonce, max_attempts, record.nonce
+                );
+            }
+            Ok(false)
+        }
+    }
+}
+
+/// Finalize L1 withdrawals as their challenge periods end, until shutdown.
+async fn run_withdrawal_crank(relayer: Arc<Relayer>) {
+    while relayer.running.load(Ordering::Relaxed) {
+        let result = crank_withdrawals(
+            &relayer.l1.client(),
+            &relayer.config,
+            &relayer.store,
+            &relayer.metrics,
+        )
+        .await;
+        match result {
+            Ok(count) => {
+                if count > 0 {
+                    println!("[FINALIZE] Finalized {} withdrawals", count);
+                }
+            }
+            Err(e) => {
+                eprintln!("[FINALIZE] Error cranking withdrawals: {}", e);
+                relayer.l1.check().await;
+            }
+        }
+        relayer.pause().await;
+    }
+}
+
+async fn run_metrics_refresh(relayer: Arc<Relayer>) {
+    while relayer.running.load(Ordering::Relaxed) {
+        refresh_metrics(
+            &relayer.l1.client(),
+            &relayer.l2.client(),
+            &relayer.config,
+            &relayer.store,
+            &relayer.metrics,
+        )
+        .await;
+        relayer.pause().await;
+    }
+}
+
+/// Maximum signatures per getSignatureStatuses request.
+const SIGNATURE_STATUS_BATCH: usize = 256;
+
+/// Promote `Seen` events whose source transaction now satisfies `policy`.
+/// An event whose transaction can no longer be found was on a fork that
+/// got abandoned: it is marked `Orphaned` and alerted on instead of being
+/// relayed, and resumes waiting if the transaction shows up again.
+/// Returns how many events were newly orphaned.
+async fn confirm_seen_events(
+    client: &RpcClient,
+    policy: ConfirmationPolicy,
+    store: &Mutex<RelayerStore>,
+    queue: Queue,
+    chain: &str,
+) -> Result<u64, String> {
+    let seen = lock_store(store).unconfirmed::<serde::de::IgnoredAny>(queue)?;
+    if seen.is_empty() {
+        return Ok(0);
+    }
+    let mut orphaned = 0;
+
+    let confirmed_tip = match policy {
+        ConfirmationPolicy::Finalized => 0,
+        ConfirmationPolicy::ConfirmedPlus(_) => client
+            .get_slot_with_commitment(CommitmentConfig::confirmed())
+            .await
+            .map_err(|e| format!("Failed to get {} slot: {}", chain, e))?,
+    };
+
+    for batch in seen.chunks(SIGNATURE_STATUS_BATCH) {
+        let sigs = batch
+            .iter()
+            .map(|record| {
+                Signature::from_str(&record.source_signature)
+                    .map_err(|e| format!("Invalid signature: {}", e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let statuses = client
+            .get_signature_statuses_with_history(&sigs)
+            .await
+            .map_err(|e| format!("Failed to get {} signature statuses: {}", chain, e))?
+            .value;
+
+        let mut store = lock_store(store);
+        for (record, status) in batch.iter().zip(statuses) {
+            match seen_transition(record.status, record.source_slot, status.as_ref(), policy, confirmed_tip) {
+                SeenTransition::Confirm => store.mark_confirmed(queue, record.nonce)?,
+                SeenTransition::Orphan => {
+                    eprintln!(
+                        "[ALERT] {} transaction {} for nonce={} disappeared (fork?); not relaying",
+                        chain, record.source_signature, record.nonce
+                    );
+                    store.mark_orphaned(queue, record.nonce)?;
+                    orphaned += 1;
+                }
+                SeenTransition::Reseen(slot) => {
+                    if record.status == EventStatus::Orphaned {
+                        println!(
+                            "[{}] Transaction {} for nonce={} reappeared in slot {}",
+                            chain, record.source_signature, record.nonce, slot
+                        );
+                    }
+                    store.mark_seen(queue, record.nonce, slot)?;
+                }
+                SeenTransition::Unchanged => {}
+            }
+        }
+    }
+
+    Ok(orphaned)
+}
+
+/// What to do with an unconfirmed event given its source transaction's
+/// current status.
+#[derive(Debug, PartialEq)]
+enum SeenTransition {
+    /// Meets the confirmation policy: relay it.
+    Confirm,
+    /// Newly missing from the chain.
+    Orphan,
+    /// On chain again, or now in a different slot: keep waiting from there.
+    Reseen(u64),
+    Unchanged,
+}
+
+/// Decide the next state of a `Seen` or `Orphaned` event. `status` is None
+/// when the node no longer knows the transaction; `confirmed_tip` is the
+/// confirmed slot (unused under the finalized policy).
+fn seen_transition(
+    current: EventStatus,
+    source_slot: u64,
+    status: Option<&TransactionStatus>,
+    policy: ConfirmationPolicy,
+    confirmed_tip: u64,
+) -> SeenTransition {
+    let Some(status) = status else {
+        return if current == EventStatus::Orphaned {
+            SeenTransition::Unchanged
+        } else {
+            SeenTransition::Orphan
+        };
+    };
+
+    let qualifies = match policy {
+        ConfirmationPolicy::Finalized => status.satisfies_commitment(CommitmentConfig::finalized()),
+        ConfirmationPolicy::ConfirmedPlus(depth) => {
+            status.satisfies_commitment(CommitmentConfig::confirmed())
+                && confirmed_tip >= status.slot.saturating_add(depth)
+        }
+    };
+
+    if qualifies {
+        SeenTransition::Confirm
+    } else if current == EventStatus::Orphaned || source_slot != status.slot {
+        SeenTransition::Reseen(status.slot)
+    } else {
+        SeenTransition::Unchanged
+    }
+}
+
+/// Alert on unconfirmed events whose nonce was taken over by a transaction
+/// on a different fork.
+fn alert_replaced(chain: &str, replaced: &[(u64, String)], signature: &str) {
+    for (nonce, old_signature) in replaced {
+        eprintln!(
+            "[ALERT] {} nonce={} moved from transaction {} to {} (fork); old event dropped",
+            chain, nonce, old_signature, signature
+        );
+    }
+}
+
+/// Whether an account exists, i.e. a bridge has already processed a nonce.
+async fn account_exists(client: &RpcClient, address: &Pubkey) -> Result<bool, String> {
+    client
+        .get_account_with_commitment(address, client.commitment())
+        .await
+        .map(|response| response.value.is_some())
+        .map_err(|e| format!("Failed to read {}: {}", address, e))
+}
+
+/// The owning token program and decimals of `mint`.
+async fn fetch_mint(client: &RpcClient, mint: &Pubkey) -> Result<(Pubkey, u8), String> {
+    let account = client
+        .get_account(mint)
+        .await
+        .map_err(|e| format!("Failed to read mint {}: {}", mint, e))?;
+    let decimals = account
+        .data
+        .get(MINT_DECIMALS_OFFSET)
+        .copied()
+        .ok_or_else(|| format!("{} is not a mint", mint))?;
+    Ok((account.owner, decimals))
+}
+
+/// What came of one relay.
+#[derive(Debug)]
+enum RelayOutcome {
+    /// Sent with this signature, or None when the destination bridge had
+    /// already processed the event.
+    Relayed(Option<Signature>),
+    /// Nothing the destination would accept yet: no posted root covers the
+    /// event, or the committee is short of its threshold. Retried on the
+    /// next poll without using up a relay attempt.
+    Waiting(String),
+}
+
+/// Relay one L1 event to L2. Returns `Relayed(None)` when the L2 bridge
+/// already has a ProcessedDeposit PDA for the nonce, so a restart never
+/// sends twice, and `Waiting` while a committee release lacks attestations.
+async fn relay_l1_event(
+    l1_client: &RpcClient,
+    l2_client: &RpcClient,
+    config: &RelayerConfig,
+    committee_threshold: u8,
+    record: &EventRecord<L1Event>,
+) -> Result<RelayOutcome, String> {
+    let nonce = record.nonce;
+    let (processed_pda, _) = Pubkey::find_program_address(
+        &[PROCESSED_SEED, &nonce.to_le_bytes()],
+        &config.bridge_l2_program,
+    );
+    if account_exists(l2_client, &processed_pda).await? {
+        return Ok(RelayOutcome::Relayed(None));
+    }
+
+    // Convert the L1 transaction signature to a 64-byte array
+    let l1_sig: Signature = record
+        .source_signature
+        .parse()
+        .map_err(|e| format!("Invalid signature: {}", e))?;
+    let l1_tx_sig_bytes: [u8; 64] = l1_sig.as_ref().try_into().unwrap_or([0u8; 64]);
+
+    let (hash, ixs) = match &record.event {
+        L1Event::Deposit(event) => {
+            // Parse the L2 recipient from hex
+            let l2_recipient = hex_to_pubkey(&event.l2_recipient)
+                .ok_or_else(|| format!("Invalid l2_recipient hex: {}", event.l2_recipient))?;
+            let token_mint = Pubkey::from_str(&event.token_mint)
+                .map_err(|_| format!("Invalid token_mint: {}", event.token_mint))?;
+
+            // MYTH is released natively from the reserve; every other mint
+            // (including native SOL) is minted 1:1 as its wrapped token.
+            if token_mint == Pubkey::from_str(MYTH_L1_MINT).unwrap() {
+                // Scale L1 amount (6 decimals) to L2 amount (9 decimals)
+                let l2_amount = event.amount
+                    .checked_mul(DECIMAL_SCALING_FACTOR)
+                    .unwrap_or(event.amount);
+                let hash = deposit_hash(&l1_tx_sig_bytes, nonce, l2_amount, &l2_recipient, None);
+
+                (hash, vec![build_release_bridged_ix(
+                    &config.bridge_l2_program,
+                    &config.relayer_keypair.pubkey(),
+                    &l2_recipient,
+                    l2_amount,
+                    nonce,
+                    &l1_tx_sig_bytes,
+                    hash,
+                )])
+            } else {
+                let hash = deposit_hash(
+                    &l1_tx_sig_bytes,
+                    nonce,
+                    event.amount,
+                    &l2_recipient,
+                    Some(&token_mint),
+                );
+                // The wrapped mint may live under SPL Token or Token-2022
+                let (l2_mint, _) = Pubkey::find_program_address(
+                    &[L2_MINT_SEED, token_mint.as_ref()],
+                    &config.bridge_l2_program,
+                );
+                let (_, l1_decimals) = fetch_mint(l1_client, &token_mint).await?;
+                let (l2_token_program, _) = fetch_mint(l2_client, &l2_mint).await?;
+                (hash, build_mint_wrapped_ixs(
+                    &config.bridge_l2_program,
+                    &config.relayer_keypair.pubkey(),
+                    &l2_recipient,
+                    event.amount,
+                    &token_mint,
+                    l1_decimals,
+                    &l2_token_program,
+                    nonce,
+                    &l1_tx_sig_bytes,
+                    hash,
+                ))
+            }
+        }
+        L1Event::Message(event) => {
+            let message = message_from_event(event)?;
+            (message_hash(&message), vec![build_l2_receive_message_ix(
+                &config.bridge_l2_program,
+                &config.relayer_keypair.pubkey(),
+                &message,
+                &l1_tx_sig_bytes,
+            )])
+        }
+    };
+
+    // With a relayer committee, vote first. The release below only
+    // succeeds once enough members have attested to the same hash.
+    if committee_threshold > 0 {
+        if config.dry_run {
+            // A simulated vote never lands, so simulating the release would
+            // only report the missing attestations. Check the vote instead.
+            let attest_ix = build_attest_deposit_ix(
+                &config.bridge_l2_program,
+                &config.relayer_keypair.pubkey(),
+                nonce,
+                hash,
+            );
+            let tx_sig = send_once(l2_client, config, &[attest_ix])
+                .await
+                .map_err(|e| format!("attestation {}", e))?;
+            println!(
+                "[DRY-RUN] Release of nonce={} needs {} committee attestations; not simulated",
+                nonce, committee_threshold
+            );
+            return Ok(RelayOutcome::Relayed(Some(tx_sig)));
+        }
+        attest_deposit(l2_client, config, nonce, hash).await;
+        let votes = committee_votes(l2_client, &config.bridge_l2_program, nonce, &hash).await?;
+        if votes < committee_threshold as usize {
+            return Ok(RelayOutcome::Waiting(format!(
+                "{} of {} committee attestations",
+                votes, committee_threshold
+            )));
+        }
+    }
+
+    send_with_retry(l2_client, config, &ixs).await.map(|sig| RelayOutcome::Relayed(Some(sig)))
+}
+
+/// Committee members that have attested to (nonce, hash) so far.
+async fn committee_votes(
+    l2_client: &RpcClient,
+    bridge_l2_program: &Pubkey,
+    nonce: u64,
+    hash: &[u8; 32],
+) -> Result<usize, String> {
+    let attestation = l2_client
+        .get_account_with_commitment(&find_attestation_pda(bridge_l2_program, nonce, hash), l2_client.commitment())
+        .await
+        .map_err(|e| format!("Failed to read attestation for nonce {}: {}", nonce, e))?
+        .value;
+    let Some(attestation) = attestation else {
+        return Ok(0);
+    };
+    attestation
+        .data
+        .get(ATTESTATION_ATTESTERS_OFFSET..ATTESTATION_ATTESTERS_OFFSET + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize)
+        .ok_or_else(|| format!("Attestation for nonce {} too short", nonce))
+}
+
+/// Scan new L2 bridge transactions into the store, then queue every burn and
+/// message that has not been relayed yet, oldest nonce first.
+async fn poll_l2_burns(relayer: &Relayer, jobs: &mpsc::Sender<RelayJob>) -> Result<u64, String> {
+    let config = &relayer.config;
+    let l2_client = relayer.l2.client();
+    let cursor = relayer.store().cursor(Queue::Burns)?;
+    let sigs =
+        fetch_signatures_since(&l2_client, &config.bridge_l2_program, cursor.as_deref(), false)
+            .await?;
+
+    for sig_info in &sigs {
+        let events = match sig_info.err {
+            None => scan_l2_transaction(&l2_client, &sig_info.signature).await?,
+            Some(_) => Vec::new(),
+        };
+        let replaced =
+            relayer.store().record_scanned(Queue::Burns, &sig_info.signature, sig_info.slot, &events)?;
+        alert_replaced("L2", &replaced, &sig_info.signature);
+        relayer.metrics.record_seen(Queue::Burns, events.len() as u64);
+    }
+
+    let orphaned = confirm_seen_events(
+        &l2_client,
+        config.l2_confirmation,
+        &relayer.store,
+        Queue::Burns,
+        "L2",
+    )
+    .await?;
+    relayer.metrics.record_orphaned(Queue::Burns, orphaned);
+
+    let pending = relayer.claim_unrelayed::<L2Event>(Queue::Burns)?;
+    queue_relays(relayer, jobs, Queue::Burns, pending, RelayJob::Burn).await
+}
+
+/// The (recipient, mint, amount) an L2 burn must produce on L1.
+fn expected_l1_withdrawal(event: &BridgeToL1Event) -> Result<(Pubkey, Pubkey, u64), String> {
+    // l1_recipient is emitted as hex-encoded bytes by the L2 bridge
+    let l1_recipient = hex_to_pubkey(&event.l1_recipient)
+        .ok_or_else(|| format!("Invalid l1_recipient hex: {}", event.l1_recipient))?;
+
+    // Wrapped burns carry their L1 mint and are already in L1 units;
+    // native MYTH is scaled from 9 decimals on L2 to 6 on L1.
+    match &event.l1_mint {
+        Some(mint) => Ok((
+            l1_recipient,
+            Pubkey::from_str(mint).map_err(|_| format!("Invalid l1_mint: {}", mint))?,
+            event.amount,
+        )),
+        None => Ok((
+            l1_recipient,
+            Pubkey::from_str(MYTH_L1_MINT).unwrap(),
+            event.amount / DECIMAL_SCALING_FACTOR,
+        )),
+    }
+}
+
+/// Relay one L2 event to L1. Returns `Relayed(None)` when the L1 bridge
+/// already has the WithdrawalRequest or ProcessedMessage PDA for the nonce.
+/// A burn is proven against the latest posted root and a message, which L1
+/// only accepts under a finalized root, against the latest finalized one;
+/// both are `Waiting` until such a root covers the event.
+async fn relay_l2_event(
+    l1_client: &RpcClient,
+    store: &Mutex<RelayerStore>,
+    config: &RelayerConfig,
+    record: &EventRecord<L2Event>,
+) -> Result<RelayOutcome, String> {
+    let nonce = record.nonce;
+    let seed = match &record.event {
+        L2Event::Burn(_) => WITHDRAWAL_SEED,
+        L2Event::Message(_) => MESSAGE_SEED,
+    };
+    let (processed_pda, _) =
+        Pubkey::find_program_address(&[seed, &nonce.to_le_bytes()], &config.bridge_l1_program);
+    if account_exists(l1_client, &processed_pda).await? {
+        return Ok(RelayOutcome::Relayed(None));
+    }
+
+    let tip = read_root_chain_tip(l1_client).await?;
+    let l2_slot = match &record.event {
+        L2Event::Burn(_) => tip.live_slot(),
+        L2Event::Message(_) => tip.last_finalized_slot,
+    };
+    if l2_slot == 0 || record.source_slot > l2_slot {
+        return Ok(RelayOutcome::Waiting(format!(
+            "no root covering L2 slot {} yet (root chain at {})",
+            record.source_slot, l2_slot
+        )));
+    }
+    let proof = posted_withdrawal_proof(l1_client, store, nonce, l2_slot).await?;
+    let ix = match &record.event {
+        L2Event::Burn(event) => {
+            let (l1_recipient, l1_mint, l1_amount) = expected_l1_withdrawal(event)?;
+
+            build_initiate_withdrawal_ix(
+                &config.bridge_l1_program,
+                &config.relayer_keypair.pubkey(),
+                &l1_recipient,
+                l1_amount,
+                &l1_mint,
+                nonce,
+                proof,
+            )
+        }
+        L2Event::Message(event) => build_l1_receive_message_ix(
+            &config.bridge_l1_program,
+            &config.relayer_keypair.pubkey(),
+            &message_from_event(event)?,
+            proof,
+        ),
+    };
+
+    send_with_retry(l1_client, config, &[ix]).await.map(|sig| RelayOutcome::Relayed(Some(sig)))
+}
+
+// ── Log Subscriptions ───────────────────────────────────────────────────────
+// Each bridge program's logs are streamed over websocket, and the events in
+// them recorded as soon as the transaction reaches the commitment the
+// confirmation policy asks for, so the producer can relay them without
+// waiting out the poll interval. The poll still scans every transaction and
+// is what catches anything missed while the stream was down.
+
+/// Seconds to wait before reconnecting a dropped subscription.
+const SUBSCRIBE_RETRY_SECS: u64 = 5;
+
+/// Keep a log subscription open for `queue`'s source bridge until shutdown.
+async fn subscribe_bridge_logs<T: ScannedEvent>(relayer: Arc<Relayer>, queue: Queue) {
+    let (chain, rpc, ws_url) = match queue {
+        Queue::Deposits => ("L1", &relayer.l1, &relayer.config.l1_ws_url),
+        Queue::Burns => ("L2", &relayer.l2, &relayer.config.l2_ws_url),
+    };
+    while relayer.running.load(Ordering::Relaxed) {
+        // Follow RPC failover unless a websocket endpoint is configured
+        let url = ws_url.clone().unwrap_or_else(|| websocket_url(rpc.url()));
+        if let Err(e) = stream_bridge_logs::<T>(&relayer, queue, chain, &url).await {
+            eprintln!("[WS] {} subscription at {} dropped: {}", chain, url, e);
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(SUBSCRIBE_RETRY_SECS)).await;
+    }
+}
+
+/// Record events from one websocket connection until it drops or the
+/// relayer stops.
+async fn stream_bridge_logs<T: ScannedEvent>(
+    relayer: &Relayer,
+    queue: Queue,
+    chain: &str,
+    url: &str,
+) -> Result<(), String> {
+    let (program, policy) = match queue {
+        Queue::Deposits => (relayer.config.bridge_l1_program, relayer.config.l1_confirmation),
+        Queue::Burns => (relayer.config.bridge_l2_program, relayer.config.l2_confirmation),
+    };
+    let commitment = match policy {
+        ConfirmationPolicy::Finalized => CommitmentConfig::finalized(),
+        ConfirmationPolicy::ConfirmedPlus(_) => CommitmentConfig::confirmed(),
+    };
+
+    let client = PubsubClient::new(url)
+        .await
+        .map_err(|e| format!("connect: {}", e))?;
+    let (mut notifications, unsubscribe) = client
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![program.to_string()]),
+            RpcTransactionLogsConfig {
+                commitment: Some(commitment),
+            },
+        )
+        .await
+        .map_err(|e| format!("logsSubscribe: {}", e))?;
+    println!("[WS] Streaming {} bridge logs from {}", chain, url);
+
+    // Wake up now and then to notice shutdown
+    let idle = std::time::Duration::from_millis(relayer.config.poll_interval_ms);
+    let result = loop {
+        if !relayer.running.load(Ordering::Relaxed) {
+            break Ok(());
+        }
+        let Ok(next) = tokio::time::timeout(idle, notifications.next()).await else {
+            continue;
+        };
+        let Some(response) = next else {
+            break Err("stream closed".to_string());
+        };
+        record_streamed_logs::<T>(relayer, queue, chain, response.context.slot, &response.value);
+    };
+
+    drop(notifications);
+    unsubscribe().await;
+    result
+}
+
+/// Record the bridge events in one streamed transaction as `Seen`, and wake
+/// `queue`'s producer if any are new. Failed transactions emit nothing.
+fn record_streamed_logs<T: ScannedEvent>(
+    relayer: &Relayer,
+    queue: Queue,
+    chain: &str,
+    slot: u64,
+    logs: &RpcLogsResponse,
+) {
+    if logs.err.is_some() {
+        return;
+    }
+    let events = T::from_logs(&logs.logs);
+    if events.is_empty() {
+        return;
+    }
+    let recorded = relayer.store().insert_missing(queue, &logs.signature, slot, &events);
+    match recorded {
+        Ok(0) => {}
+        Ok(count) => {
+            println!("[WS] {} new {} events in {}", count, chain, logs.signature);
+            relayer.due(queue).notify_one();
+        }
+        Err(e) => eprintln!("[STORE] Failed to record {} events from {}: {}", chain, logs.signature, e),
+    }
+}
+
+/// The websocket endpoint that goes with an RPC URL: ws or wss, on the
+/// next port up when one is given (the validator's default layout).
+fn websocket_url(rpc_url: &str) -> String {
+    let (scheme, rest) = match rpc_url.split_once("://") {
+        Some(("https", rest)) => ("wss", rest),
+        Some((_, rest)) => ("ws", rest),
+        None => ("ws", rpc_url),
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+    let authority = match authority.rsplit_once(':') {
+        Some((host, port)) => match port.parse::<u16>() {
+            Ok(port) => format!("{}:{}", host, port.saturating_add(1)),
+            Err(_) => authority.to_string(),
+        },
+        None => authority.to_string(),
+    };
+    format!("{}://{}{}", scheme, authority, path)
+}
+
+// ── Withdrawal Finalization ─────────────────────────────────────────────────
+
+/// Seconds to wait before retrying a withdrawal whose finalize failed.
+const FINALIZE_RETRY_SECS: i64 = 60;
+
+/// Finalize every tracked withdrawal that is still Pending once its
+/// challenge deadline has passed, and alert on any that get Challenged.
+/// Returns how many were finalized.
+async fn crank_withdrawals(
+    l1_client: &RpcClient,
+    config: &RelayerConfig,
+    store: &Mutex<RelayerStore>,
+    metrics: &Metrics,
+) -> Result<u64, String> {
+    let mut finalized = 0u64;
+
+    let open_withdrawals = lock_store(store).open_withdrawals()?;
+    for tracked in open_withdrawals {
+        let nonce = tracked.nonce;
+        let (withdrawal_pda, _) = Pubkey::find_program_address(
+            &[WITHDRAWAL_SEED, &nonce.to_le_bytes()],
+            &config.bridge_l1_program,
+        );
+        let Some(account) = l1_client
+            .get_account_with_commitment(&withdrawal_pda, l1_client.commitment())
+            .await
+            .map_err(|e| format!("Failed to read withdrawal {}: {}", nonce, e))?
+            .value
+        else {
+            // Not initiated on L1 yet
+            continue;
+        };
+        if account.data.len() == L1_WITHDRAWAL_V1_LEN {
+            match migrate_withdrawal(l1_client, config, store, nonce).await {
+                Ok(tx_sig) => println!("MIGRATED WITHDRAWAL: nonce={} tx={}", nonce, tx_sig),
+                Err(e) => eprintln!("[FINALIZE] Failed to migrate v1 withdrawal nonce={}: {}", nonce, e),
+            }
+            continue;
+        }
+        let withdrawal = match L1WithdrawalRequest::deserialize(&mut account.data.as_slice()) {
+            Ok(w) => w,
+            Err(e) => {
+                eprintln!("[FINALIZE] Unreadable WithdrawalRequest nonce={}: {}", nonce, e);
+                continue;
+            }
+        };
+
+        let state = withdrawal.status.tracked_state();
+        if state == WithdrawalState::Challenged && tracked.state != WithdrawalState::Challenged {
+            eprintln!(
+                "[ALERT] Withdrawal nonce={} ({} of {} to {}) is Challenged; needs ResolveChallenge",
+                nonce, withdrawal.amount, withdrawal.token_mint, withdrawal.recipient
+            );
+        }
+        if state != tracked.state || tracked.challenge_deadline != Some(withdrawal.challenge_deadline) {
+            lock_store(store).set_withdrawal_state(nonce, state, withdrawal.challenge_deadline)?;
+        }
+
+        if !finalize_due(&tracked, state, withdrawal.challenge_deadline, store::now()) {
+            continue;
+        }
+
+        match finalize_withdrawal(l1_client, store, config, &withdrawal).await {
+            Ok(tx_sig) => {
+                println!("FINALIZED WITHDRAWAL: nonce={} tx={}", nonce, tx_sig);
+                lock_store(store).record_finalize_attempt(nonce, Ok(&tx_sig.to_string()))?;
+                metrics.record_withdrawal_finalized();
+                finalized += 1;
+            }
+            Err(e) => {
+                eprintln!(
+                    "[FINALIZE] Failed to finalize nonce={} (attempt {}): {}",
+                    nonce,
+                    tracked.attempts + 1,
+                    e
+                );
+                lock_store(store).record_finalize_attempt(nonce, Err(&e))?;
+            }
+        }
+    }
+
+    Ok(finalized)
+}
+
+/// Rewrite a v1 WithdrawalRequest in the current layout, proven against the
+/// latest root still able to finalize, so the next pass can finalize it.
+async fn migrate_withdrawal(
+    l1_client: &RpcClient,
+    config: &RelayerConfig,
+    store: &Mutex<RelayerStore>,
+    nonce: u64,
+) -> Result<Signature, String> {
+    let tip = read_root_chain_tip(l1_client).await?;
+    let proof = posted_withdrawal_proof(l1_client, store, nonce, tip.live_slot()).await?;
+    let ix = build_migrate_withdrawal_ix(&config.bridge_l1_program, &config.relayer_keypair.pubkey(), nonce, proof);
+    send_with_retry(l1_client, config, &[ix]).await
+}
+
+/// Whether a withdrawal now in `state` should be finalized at `now`: still
+/// Pending, past its challenge deadline, and not inside the retry backoff
+/// after a failed attempt.
+fn finalize_due(tracked: &store::TrackedWithdrawal, state: WithdrawalState, challenge_deadline: i64, now: i64) -> bool {
+    let backing_off = tracked.last_error.is_some() && now - tracked.updated_at < FINALIZE_RETRY_SECS;
+    state == WithdrawalState::Open && now >= challenge_deadline && !backing_off
+}
+
+/// Send IX_FINALIZE_WITHDRAWAL or IX_FINALIZE_SOL_WITHDRAWAL for a Pending
+/// withdrawal, paying the LP instead of the recipient if it was filled fast.
+/// Any fast-withdrawal offer is closed in the same transaction.
+async fn finalize_withdrawal(
+    l1_client: &RpcClient,
+    store: &Mutex<RelayerStore>,
+    config: &RelayerConfig,
+    withdrawal: &L1WithdrawalRequest,
+) -> Result<Signature, String> {
+    let (fast_withdrawal_pda, _) = Pubkey::find_program_address(
+        &[FAST_WITHDRAWAL_SEED, &withdrawal.nonce.to_le_bytes()],
+        &config.bridge_l1_program,
+    );
+    let offer = l1_client
+        .get_account_with_commitment(&fast_withdrawal_pda, l1_client.commitment())
+        .await
+        .map_err(|e| format!("Failed to read fast withdrawal: {}", e))?
+        .value
+        .filter(|account| account.owner == config.bridge_l1_program)
+        .and_then(|account| {
+            let read = |offset: usize| {
+                account.data.get(offset..offset + 32).map(|b| Pubkey::try_from(b).unwrap())
+            };
+            Some((read(FAST_WITHDRAWAL_RECIPIENT_OFFSET)?, read(FAST_WITHDRAWAL_LP_OFFSET)?))
+        });
+    let payee = offer
+        .map(|(_, lp)| lp)
+        .filter(|lp| *lp != Pubkey::default())
+        .unwrap_or(withdrawal.recipient);
+
+    // SPL mints may live under either token program; native SOL has none.
+    let token_program = if withdrawal.token_mint == Pubkey::from_str(NATIVE_SOL_MINT).unwrap() {
+        None
+    } else {
+        let mint = l1_client
+            .get_account(&withdrawal.token_mint)
+            .await
+            .map_err(|e| format!("Failed to read mint {}: {}", withdrawal.token_mint, e))?;
+        Some(mint.owner)
+    };
+
+    let proof = posted_withdrawal_proof(l1_client, store, withdrawal.nonce, withdrawal.l2_slot).await?;
+
+    let mut ixs = build_finalize_withdrawal_ixs(
+        &config.bridge_l1_program,
+        &config.relayer_keypair.pubkey(),
+        withdrawal,
+        &payee,
+        token_program.as_ref(),
+        proof,
+    );
+    if let Some((offer_recipient, _)) = offer {
+        ixs.push(build_close_fast_withdrawal_ix(
+            &config.bridge_l1_program,
+            withdrawal.nonce,
+            &offer_recipient,
+        ));
+    }
+    send_with_retry(l1_client, config, &ixs).await
+}
+
+/// Sign `ixs` with the relayer keypair as fee payer, behind the configured
+/// compute-budget instructions.
+async fn sign_transaction(
+    client: &RpcClient,
+    config: &RelayerConfig,
+    ixs: &[Instruction],
+) -> Result<Transaction, String> {
+    let mut all_ixs = Vec::with_capacity(ixs.len() + 2);
+    if config.fees.compute_unit_limit > 0 {
+        all_ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(
+            config.fees.compute_unit_limit,
+        ));
+    }
+    if config.fees.priority_fee_micro_lamports > 0 {
+        all_ixs.push(ComputeBudgetInstruction::set_compute_unit_price(
+            config.fees.priority_fee_micro_lamports,
+        ));
+    }
+    all_ixs.extend_from_slice(ixs);
+
+    let recent_hash = client
+        .get_latest_blockhash()
+        .await
+        .map_err(|e| format!("blockhash: {}", e))?;
+    Ok(Transaction::new_signed_with_payer(
+        &all_ixs,
+        Some(&config.relayer_keypair.pubkey()),
+        &[&config.relayer_keypair],
+        recent_hash,
+    ))
+}
+
+/// Sign and send once. In dry-run mode the transaction is only simulated,
+/// and the signature it would have had is returned if it would succeed.
+async fn send_once(client: &RpcClient, config: &RelayerConfig, ixs: &[Instruction]) -> Result<Signature, String> {
+    let tx = sign_transaction(client, config, ixs).await?;
+    if !config.dry_run {
+        return client
+            .send_and_confirm_transaction(&tx)
+            .await
+            .map_err(|e| format!("tx: {}", e));
+    }
+
+    let result = client
+        .simulate_transaction(&tx)
+        .await
+        .map_err(|e| format!("simulate: {}", e))?
+        .value;
+    if let Some(err) = result.err {
+        let logs = result.logs.unwrap_or_default().join("\n    ");
+        return Err(format!("simulation failed: {}\n    {}", err, logs));
+    }
+    println!(
+        "[DRY-RUN] Simulated {} ({} compute units)",
+        tx.signatures[0],
+        result.units_consumed.unwrap_or(0)
+    );
+    Ok(tx.signatures[0])
+}
+
+/// Sign and send, retrying on failure up to `max_send_retries` times.
+async fn send_with_retry(
+    client: &RpcClient,
+    config: &RelayerConfig,
+    ixs: &[Instruction],
+) -> Result<Signature, String> {
+    if config.dry_run {
+        return send_once(client, config, ixs).await;
+    }
+    retry_with_backoff(|| send_once(client, config, ixs), config.max_send_retries).await
+}
+
+/// Record this relayer's committee vote for (nonce, hash). Failures are only
+/// logged: most likely the vote already landed on an earlier pass.
+async fn attest_deposit(l2_client: &RpcClient, config: &RelayerConfig, nonce: u64, hash: [u8; 32]) {
+    let attest_ix = build_attest_deposit_ix(
+        &config.bridge_l2_program,
+        &config.relayer_keypair.pubkey(),
+        nonce,
+        hash,
+    );
+    match send_once(l2_client, config, &[attest_ix]).await {
+        Ok(tx_sig) => println!(
+            "ATTESTED DEPOSIT: nonce={} hash={} tx={}",
+            nonce, bytes_to_hex(&hash), tx_sig
+        ),
+        Err(e) => eprintln!("[DEPOSIT] Attestation for nonce={} failed: {}", nonce, e),
+    }
+}
+
+// ── Helpers ─────────────────────────────────────────────────────────────────
+
+fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hex_to_pubkey(hex_str: &str) -> Option<Pubkey> {
+    hex_to_bytes32(hex_str).map(Pubkey::new_from_array)
+}
+
+fn hex_to_bytes(hex_str: &str) -> Option<Vec<u8>> {
+    if !hex_str.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex_str.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex_str.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn hex_to_bytes32(hex_str: &str) -> Option<[u8; 32]> {
+    if hex_str.len() != 64 {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for i in 0..32 {
+        bytes[i] = u8::from_str_radix(&hex_str[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// Clear `running` on Ctrl-C or SIGTERM so the relayer tasks wind down,
+/// finishing the relays already in flight.
+fn shutdown_on_signal(running: Arc<AtomicBool>) {
+    tokio::spawn(async move {
+        let mut terminate = match signal(SignalKind::terminate()) {
+            Ok(terminate) => terminate,
+            Err(e) => {
+                eprintln!("[SHUTDOWN] Cannot listen for SIGTERM: {}", e);
+                return;
+            }
+        };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+        println!("[SHUTDOWN] Stopping; waiting for in-flight relays...");
+        running.store(false, Ordering::Relaxed);
+    });
+}
+
+// ── Withdrawal Tree ─────────────────────────────────────────────────────────
+// The settlement state root commits to the L2 withdrawal tree, the tree the
+// L1 bridge proves withdrawals and L2→L1 messages against: leaf i is the
+// event with withdraw nonce i, padded with zero leaves to a power of two.
+// A root's transaction batch is the Borsh-encoded list of L2 blocks since
+// the previous root. Step k of a dispute over the root is the tree once the
+// batch's first k transactions have run, so step 0 is the previous root and
+// the last step the posted one. The poster posts these roots; the
+// watchtower replays them.
+
+/// sha256(0x00 || recipient || amount || token_mint || nonce), matching
+/// `withdrawal_leaf_hash` in the L1 bridge.
+fn withdrawal_leaf_hash(recipient: &Pubkey, amount: u64, token_mint: &Pubkey, nonce: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([MERKLE_LEAF_PREFIX]);
+    hasher.update(recipient.as_ref());
+    hasher.update(amount.to_le_bytes());
+    hasher.update(token_mint.as_ref());
+    hasher.update(nonce.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// sha256(0x02 || message_hash), matching `message_leaf_hash` in the L1 bridge.
+fn message_leaf_hash(message_hash: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([MESSAGE_LEAF_PREFIX]);
+    hasher.update(message_hash);
+    hasher.finalize().into()
+}
+
+/// sha256(0x01 || left || right).
+fn merkle_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([MERKLE_NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Fold a leaf up its Merkle path, as `verify_merkle_proof` in the L1 bridge.
+fn merkle_root(leaf: [u8; 32], siblings: &[[u8; 32]], leaf_index: u64) -> [u8; 32] {
+    let mut computed = leaf;
+    for (depth, sibling) in siblings.iter().enumerate() {
+        computed = if (leaf_index >> depth) & 1 == 0 {
+            merkle_node(&computed, sibling)
+        } else {
+            merkle_node(sibling, &computed)
+        };
+    }
+    computed
+}
+
+/// Root of the withdrawal tree over every L2 burn and L2→L1 message that
+/// landed at or before `l2_slot`.
+fn withdrawal_state_root(store: &RelayerStore, l2_slot: u64) -> Result<[u8; 32], String> {
+    withdrawal_tree_root(&store.events_through::<L2Event>(Queue::Burns, l2_slot)?)
+}
+
+/// State hash at `step` of a dispute over a root's batch: the withdrawal
+/// root once the batch's first `step` transactions have run. Step 0 is the
+/// previous root and the last step the posted one. `records` must cover
+/// the whole batch.
+fn batch_step_root(records: &[EventRecord<L2Event>], blocks: &[L2BatchBlock], step: u32) -> Result<[u8; 32], String> {
+    let first_slot = blocks.first().map(|block| block.slot).ok_or("An empty batch has no steps")?;
+    let mut executed = HashSet::new();
+    for block in blocks {
+        for tx in &block.transactions {
+            if executed.len() == step as usize {
+                break;
+            }
+            let tx: solana_sdk::transaction::VersionedTransaction = bincode::deserialize(tx)
+                .map_err(|e| format!("Undecodable transaction in L2 block {}: {}", block.slot, e))?;
+            executed.insert(tx.signatures.first().map(|sig| sig.to_string()).unwrap_or_default());
+        }
+    }
+    withdrawal_tree_root(
+        records
+            .iter()
+            .filter(|record| record.source_slot < first_slot || executed.contains(&record.source_signature)),
+    )
+}
+
+/// Root of the withdrawal tree over `records`, which must hold every nonce
+/// from 0 in order. Empty trees have the zero root.
+fn withdrawal_tree_root<'a>(
+    records: impl IntoIterator<Item = &'a EventRecord<L2Event>>,
+) -> Result<[u8; 32], String> {
+    let mut level = withdrawal_tree_leaves(records)?;
+    if level.is_empty() {
+        return Ok([0u8; 32]);
+    }
+    while level.len() > 1 {
+        level = merkle_parents(&level);
+    }
+    Ok(level[0])
+}
+
+/// Merkle path of withdraw nonce `nonce` in the tree `withdrawal_tree_root`
+/// builds over `records`, the events through `l2_slot`. Folds to that root
+/// under `merkle_root`, and so under `verify_merkle_proof` in the L1 bridge.
+fn withdrawal_proof(records: &[EventRecord<L2Event>], nonce: u64, l2_slot: u64) -> Result<WithdrawalProof, String> {
+    let mut level = withdrawal_tree_leaves(records)?;
+    if nonce >= records.len() as u64 {
+        return Err(format!("No root covering withdraw nonce {} at L2 slot {}", nonce, l2_slot));
+    }
+    let mut index = nonce as usize;
+    let mut proof = Vec::new();
+    while level.len() > 1 {
+        proof.push(level[index ^ 1]);
+        level = merkle_parents(&level);
+        index /= 2;
+    }
+    Ok(WithdrawalProof { l2_slot, leaf_index: nonce, proof })
+}
+
+/// Leaf hashes of the withdrawal tree over `records`, padded with zero
+/// leaves to a power of two.
+fn withdrawal_tree_leaves<'a>(
+    records: impl IntoIterator<Item = &'a EventRecord<L2Event>>,
+) -> Result<Vec<[u8; 32]>, String> {
+    let mut leaves = Vec::new();
+    for (index, record) in records.into_iter().enumerate() {
+        // A gap means history the L2 node no longer serves; never post a
+        // root that would strand the missing withdrawals.
+        if record.nonce != index as u64 {
+            return Err(format!("L2 withdrawal index is missing nonce {}", index));
+        }
+        leaves.push(match &record.event {
+            L2Event::Burn(event) => {
+                let (recipient, token_mint, amount) = expected_l1_withdrawal(event)?;
+                withdrawal_leaf_hash(&recipient, amount, &token_mint, record.nonce)
+            }
+            L2Event::Message(event) => message_leaf_hash(&message_hash(&message_from_event(event)?)),
+        });
+    }
+    if !leaves.is_empty() {
+        leaves.resize(leaves.len().next_power_of_two(), [0u8; 32]);
+    }
+    Ok(leaves)
+}
+
+/// The level above `level`, pairing nodes left to right.
+fn merkle_parents(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level.chunks(2).map(|pair| merkle_node(&pair[0], &pair[1])).collect()
+}
+
+/// `transaction_batch_hash`: sha256 over the concatenated sha256 of each
+/// BATCH_CHUNK_SIZE chunk, matching SealBatchChunks in settlement.
+fn batch_data_hash(batch: &[u8]) -> [u8; 32] {
+    let mut outer = Sha256::new();
+    for chunk in batch.chunks(BATCH_CHUNK_SIZE) {
+        outer.update(Sha256::digest(chunk));
+    }
+    outer.finalize().into()
+}
+
+// ── Operator Commands ───────────────────────────────────────────────────────
+// One-shot commands for recovering from incidents without touching the
+// store by hand. They share the store with a running relayer safely, but
+// backfill and replay are best run with it stopped.
+
+/// `backfill`: re-queue relayed and failed events from `from_nonce` on, and
+/// recover any the store never recorded by rescanning the bridge's full
+/// history (newest first, stopping below `from_nonce`).
+async fn run_backfill(config: &RelayerConfig, queue: Queue, from_nonce: u64) -> Result<(), String> {
+    let mut store = RelayerStore::open(&config.store_path)?;
+    let recovered = match queue {
+        Queue::Deposits => {
+            let l1 = FailoverRpc::connect("L1", &config.l1_rpc_urls, CommitmentConfig::confirmed()).await;
+            let program = &config.bridge_l1_program;
+            backfill_queue::<L1Event>(&mut store, queue, from_nonce, &l1.client(), program).await?
+        }
+        Queue::Burns => {
+            let l2 = FailoverRpc::connect("L2", &config.l2_rpc_urls, CommitmentConfig::confirmed()).await;
+            let program = &config.bridge_l2_program;
+            backfill_queue::<L2Event>(&mut store, queue, from_nonce, &l2.client(), program).await?
+        }
+    };
+    let requeued = store.requeue_from(queue, from_nonce)?;
+    println!(
+        "[BACKFILL] {:?} from nonce {}: {} recovered, {} re-queued",
+        queue, from_nonce, recovered, requeued
+    );
+    println!("[BACKFILL] Recovered events still wait for their confirmation policy.");
+    Ok(())
+}
+
+async fn backfill_queue<T: ScannedEvent>(
+    store: &mut RelayerStore,
+    queue: Queue,
+    from_nonce: u64,
+    client: &RpcClient,
+    program: &Pubkey,
+) -> Result<u64, String> {
+    let sigs = fetch_signatures_since(client, program, None, true).await?;
+    let mut recovered = 0u64;
+    // Nonces only grow, so everything older than the first event below
+    // from_nonce is below it too.
+    for sig_info in sigs.iter().rev() {
+        if sig_info.err.is_some() {
+            continue;
+        }
+        let events = T::scan(client, &sig_info.signature).await?;
+        let reached_start = events.iter().any(|(nonce, _)| *nonce < from_nonce);
+        let wanted: Vec<_> = events.into_iter().filter(|(nonce, _)| *nonce >= from_nonce).collect();
+        let inserted = store.insert_missing(queue, &sig_info.signature, sig_info.slot, &wanted)?;
+        if inserted > 0 {
+            println!("[BACKFILL] Recovered {} events from {}", inserted, sig_info.signature);
+        }
+        recovered += inserted;
+        if reached_start {
+            break;
+        }
+    }
+    Ok(recovered)
+}
+
+/// `replay`: relay the deposits and messages of one L1 transaction now,
+/// without waiting for the confirmation policy. Nonces the L2 bridge has
+/// already processed are only marked relayed.
+async fn run_replay(config: &RelayerConfig, l1_signature: &str) -> Result<(), String> {
+    let l1 = FailoverRpc::connect("L1", &config.l1_rpc_urls, CommitmentConfig::confirmed()).await;
+    let l2 = FailoverRpc::connect("L2", &config.l2_rpc_urls, CommitmentConfig::confirmed()).await;
+    let mut store = RelayerStore::open(&config.store_path)?;
+
+    let sig = Signature::from_str(l1_signature).map_err(|e| format!("Invalid signature: {}", e))?;
+    let status = l1
+        .client()
+        .get_signature_statuses_with_history(&[sig])
+        .await
+        .map_err(|e| format!("Failed to get L1 signature status: {}", e))?
+        .value
+        .pop()
+        .flatten()
+        .ok_or_else(|| format!("L1 transaction {} not found", l1_signature))?;
+    if let Some(err) = status.err {
+        return Err(format!("L1 transaction {} failed: {}", l1_signature, err));
+    }
+
+    let events = scan_l1_transaction(&l1.client(), l1_signature).await?;
+    if events.is_empty() {
+        println!("[REPLAY] {} emitted no deposits or messages", l1_signature);
+        return Ok(());
+    }
+    store.insert_missing(Queue::Deposits, l1_signature, status.slot, &events)?;
+
+    let committee_threshold = fetch_committee_threshold(&l2.client(), &config.bridge_l2_program).await?;
+    let mut failed = 0;
+    for (nonce, _) in &events {
+        let record = store
+            .event::<L1Event>(Queue::Deposits, *nonce)?
+            .ok_or_else(|| format!("nonce {} missing from store", nonce))?;
+        if record.source_signature != l1_signature {
+            eprintln!(
+                "[REPLAY] nonce={} is recorded from {}; not replaying",
+                nonce, record.source_signature
+            );
+            continue;
+        }
+        match relay_l1_event(&l1.client(), &l2.client(), config, committee_threshold, &record).await {
+            Ok(RelayOutcome::Waiting(reason)) => {
+                println!("[REPLAY] nonce={} not relayable yet: {}", nonce, reason);
+                store.mark_waiting(Queue::Deposits, *nonce, &reason)?;
+            }
+            Ok(RelayOutcome::Relayed(tx_sig)) => {
+                let tx_sig = tx_sig.map(|sig| sig.to_string());
+                match &tx_sig {
+                    Some(sig) => println!("RELAYED L1 EVENT: nonce={} tx={}", nonce, sig),
+                    None => println!("[REPLAY] nonce={} already processed on L2", nonce),
+                }
+                store.mark_relayed(Queue::Deposits, *nonce, tx_sig.as_deref())?;
+            }
+            Err(e) => {
+                eprintln!("[REPLAY] Failed to relay nonce={}: {}", nonce, e);
+                store.mark_failed(Queue::Deposits, *nonce, &e, config.max_relay_attempts)?;
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} events failed", failed, events.len()));
+    }
+    Ok(())
+}
+
+/// `status`: where the store stands against both chains.
+async fn run_status(config: &RelayerConfig) -> Result<(), String> {
+    let store = RelayerStore::open(&config.store_path)?;
+    let l1 = FailoverRpc::connect("L1", &config.l1_rpc_urls, CommitmentConfig::confirmed()).await;
+    let l2 = FailoverRpc::connect("L2", &config.l2_rpc_urls, CommitmentConfig::confirmed()).await;
+
+    println!("Store:           {}", config.store_path.display());
+    println!("L1 RPC:          {}", l1.url());
+    println!("L2 RPC:          {}", l2.url());
+    let chain_nonces = [
+        (Queue::Deposits, "L1 deposit nonce", fetch_l1_deposit_nonce(&l1.client(), &config.bridge_l1_program).await),
+        (Queue::Burns, "L2 withdraw nonce", fetch_l2_withdraw_nonce(&l2.client(), &config.bridge_l2_program, l2.client().commitment()).await),
+    ];
+    for (queue, label, next_nonce) in chain_nonces {
+        println!();
+        println!("{:?}:", queue);
+        match next_nonce {
+            Ok(next) => println!("  Next {}: {}", label, next),
+            Err(e) => println!("  Next {}: unavailable ({})", label, e),
+        }
+        println!("  Last relayed:  {:?}", store.max_relayed_nonce(queue)?);
+        println!("  Relayed below: {}", store.lowest_unrelayed_nonce(queue)?);
+        println!("  Cursor:        {}", store.cursor(queue)?.unwrap_or_else(|| "-".to_string()));
+        for (status, count) in store.status_counts(queue)? {
+            println!("  {:<14} {}", format!("{}:", status), count);
+        }
+        let dead = match queue {
+            Queue::Deposits => dead_letter_lines(store.dead_lettered::<L1Event>(queue)?),
+            Queue::Burns => dead_letter_lines(store.dead_lettered::<L2Event>(queue)?),
+        };
+        if !dead.is_empty() {
+            println!("  Dead-lettered (re-queue with backfill --from-nonce):");
+            for line in dead {
+                println!("    {}", line);
+            }
+        }
+    }
+
+    let withdrawals = store.withdrawal_counts()?;
+    if !withdrawals.is_empty() {
+        println!();
+        println!("L1 withdrawals:");
+        for (state, count) in withdrawals {
+            println!("  {:<14} {}", format!("{}:", state), count);
+        }
+    }
+    Ok(())
+}
+
+/// One line per dead-lettered event: nonce, source and last error.
+fn dead_letter_lines<T>(records: Vec<EventRecord<T>>) -> Vec<String> {
+    records
+        .into_iter()
+        .map(|r| {
+            format!(
+                "nonce={} attempts={} source={} error={}",
+                r.nonce,
+                r.attempts,
+                r.source_signature,
+                r.last_error.as_deref().unwrap_or("-")
+            )
+        })
+        .collect()
+}
+
+/// `dry-run`: build every relay the next poll would send, including events
+/// not scanned yet, and simulate them. Nothing is sent or recorded.
+async fn run_dry_run(config: &RelayerConfig) -> Result<(), String> {
+    let l1 = FailoverRpc::connect("L1", &config.l1_rpc_urls, CommitmentConfig::confirmed()).await;
+    let l2 = FailoverRpc::connect("L2", &config.l2_rpc_urls, CommitmentConfig::confirmed()).await;
+    let store = RelayerStore::open(&config.store_path)?;
+
+    let mut deposits = store.unrelayed::<L1Event>(Queue::Deposits)?;
+    deposits.extend(unscanned_events(&store, Queue::Deposits, &l1.client(), &config.bridge_l1_program).await?);
+    let mut burns = store.unrelayed::<L2Event>(Queue::Burns)?;
+    burns.extend(unscanned_events(&store, Queue::Burns, &l2.client(), &config.bridge_l2_program).await?);
+
+    let mut failed = 0;
+    if !deposits.is_empty() {
+        let committee_threshold = fetch_committee_threshold(&l2.client(), &config.bridge_l2_program).await?;
+        for record in deposits.iter().take(config.relay_batch_limit()) {
+            let outcome = relay_l1_event(&l1.client(), &l2.client(), config, committee_threshold, record).await;
+            failed += report_dry_run("L1", record, outcome);
+        }
+    }
+    let store = Mutex::new(store);
+    for record in burns.iter().take(config.relay_batch_limit()) {
+        let outcome = relay_l2_event(&l1.client(), &store, config, record).await;
+        failed += report_dry_run("L2", record, outcome);
+    }
+
+    println!(
+        "[DRY-RUN] {} L1 and {} L2 events checked, {} would fail",
+        deposits.len().min(config.relay_batch_limit()),
+        burns.len().min(config.relay_batch_limit()),
+        failed
+    );
+    if failed > 0 {
+        return Err(format!("{} relays would fail", failed));
+    }
+    Ok(())
+}
+
+/// Events in transactions past the store's cursor, as unsaved records.
+async fn unscanned_events<T: ScannedEvent>(
+    store: &RelayerStore,
+    queue: Queue,
+    client: &RpcClient,
+    program: &Pubkey,
+) -> Result<Vec<EventRecord<T>>, String> {
+    let sigs = fetch_signatures_since(client, program, store.cursor(queue)?.as_deref(), false).await?;
+    let mut records = Vec::new();
+    for sig_info in sigs.iter().filter(|s| s.err.is_none()) {
+        for (nonce, event) in T::scan(client, &sig_info.signature).await? {
+            records.push(EventRecord {
+                nonce,
+                source_signature: sig_info.signature.clone(),
+                source_slot: sig_info.slot,
+                event,
+                status: EventStatus::Seen,
+                attempts: 0,
+                last_error: None,
+                relay_signature: None,
+                observed_at: store::now(),
+                updated_at: store::now(),
+            });
+        }
+    }
+    Ok(records)
+}
+
+/// Print one dry-run outcome; returns 1 if it would fail.
+fn report_dry_run<T>(chain: &str, record: &EventRecord<T>, outcome: Result<RelayOutcome, String>) -> u32 {
+    match outcome {
+        Ok(RelayOutcome::Relayed(Some(_))) => {
+            println!("[DRY-RUN] {} nonce={} would relay ({})", chain, record.nonce, record.source_signature);
+            0
+        }
+        Ok(RelayOutcome::Relayed(None)) => {
+            println!("[DRY-RUN] {} nonce={} already processed", chain, record.nonce);
+            0
+        }
+        Ok(RelayOutcome::Waiting(reason)) => {
+            println!("[DRY-RUN] {} nonce={} not relayable yet: {}", chain, record.nonce, reason);
+            0
+        }
+        Err(e) => {
+            eprintln!("[DRY-RUN] {} nonce={} would fail: {}", chain, record.nonce, e);
+            1
+        }
+    }
+}
+
+// ── Main ────────────────────────────────────────────────────────────────────
+
+#[tokio::main]
+async fn main() {
+    println!("Mythic L2 Bridge Relayer v0.1.0");
+
+    let cli = match config::parse_args(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Run `mythic-relayer --help` for usage.");
+            std::process::exit(2);
+        }
+    };
+    if cli.command == Command::Help {
+        println!("{}", config::USAGE);
+        return;
+    }
+
+    let mut config = match RelayerConfig::load(&cli) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Configuration error: {}", e);
+            eprintln!("Run `mythic-relayer --help` for the available settings.");
+            std::process::exit(1);
+        }
+    };
+
+    let result = match cli.command {
+        Command::Run => {
+            run_relayer(config).await;
+            Ok(())
+        }
+        Command::Watchtower => {
+            watchtower::run_watchtower(config).await;
+            Ok(())
+        }
+        Command::PostRoots => {
+            poster::run_root_poster(config).await;
+            Ok(())
+        }
+        Command::Backfill { queue, from_nonce } => run_backfill(&config, queue, from_nonce).await,
+        Command::Replay { l1_signature } => run_replay(&config, &l1_signature).await,
+        Command::Status => run_status(&config).await,
+        Command::DryRun => {
+            config.dry_run = true;
+            run_dry_run(&config).await
+        }
+        Command::Help => Ok(()),
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_transaction_status::TransactionConfirmationStatus;
+
+    /// A SendMessage log line as both bridges print it. Same message as the
+    /// known-vector tests in programs/bridge and programs/bridge-l2.
+    fn send_message_log(payload_hex: &str, message_hash: &str) -> String {
+        format!(
+            "Program log: EVENT:SendMessage:{{\"sender\":\"{}\",\"target_program\":\"{}\",\"accounts\":[{{\"pubkey\":\"{}\",\"is_writable\":true}}],\"payload\":\"{}\",\"nonce\":5,\"message_hash\":\"{}\"}}",
+            Pubkey::new_from_array([1; 32]),
+            Pubkey::new_from_array([2; 32]),
+            Pubkey::new_from_array([3; 32]),
+            payload_hex,
+            message_hash,
+        )
+    }
+
+    const MESSAGE_HASH: &str = "27357153368e16944ba70e33b9852096ce22fc10d07af0f7943f942b1b9363c7";
+
+    /// A signature status as the RPC reports it; `confirmations` is None
+    /// only once the transaction is rooted.
+    fn tx_status(slot: u64, level: TransactionConfirmationStatus) -> TransactionStatus {
+        TransactionStatus {
+            slot,
+            confirmations: (level != TransactionConfirmationStatus::Finalized).then_some(1),
+            status: Ok(()),
+            err: None,
+            confirmation_status: Some(level),
+        }
+    }
+
+    #[test]
+    fn test_seen_transition_under_finalized_policy() {
+        use TransactionConfirmationStatus::*;
+        let policy = ConfirmationPolicy::Finalized;
+        let confirmed = tx_status(10, Confirmed);
+        let finalized = tx_status(10, Finalized);
+
+        assert_eq!(
+            seen_transition(EventStatus::Seen, 10, Some(&confirmed), policy, 1_000),
+            SeenTransition::Unchanged
+        );
+        assert_eq!(
+            seen_transition(EventStatus::Seen, 10, Some(&finalized), policy, 0),
+            SeenTransition::Confirm
+        );
+        // Processed in another slot after a fork: keep waiting from there
+        assert_eq!(
+            seen_transition(EventStatus::Seen, 9, Some(&tx_status(10, Processed)), policy, 0),
+            SeenTransition::Reseen(10)
+        );
+    }
+
+    #[test]
+    fn test_seen_transition_under_confirmed_plus_policy() {
+        use TransactionConfirmationStatus::*;
+        let policy = ConfirmationPolicy::ConfirmedPlus(32);
+        let confirmed = tx_status(100, Confirmed);
 
-        while running.load(Ordering::Relaxed) {
-            match listener.accept() {
-                Ok((mut stream, _)) => {
-                    let response = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK";
-                    let _ = stream.write_all(response.as_bytes());
-                }
-                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                    std::thread::sleep(std::time::Duration::from_millis(100));
-                }
-                Err(e) => {
-                    eprintln!("[HEALTH] Accept error: {}", e);
-                }
-            }
+        assert_eq!(
+            seen_transition(EventStatus::Seen, 100, Some(&confirmed), policy, 131),
+            SeenTransition::Unchanged
+        );
+        assert_eq!(
+            seen_transition(EventStatus::Seen, 100, Some(&confirmed), policy, 132),
+            SeenTransition::Confirm
+        );
+        // Deep enough by slot count, but only processed
+        assert_eq!(
+            seen_transition(EventStatus::Seen, 100, Some(&tx_status(100, Processed)), policy, 500),
+            SeenTransition::Unchanged
+        );
+    }
+
+    #[test]
+    fn test_seen_transition_orphans_and_recovers() {
+        use TransactionConfirmationStatus::*;
+        let policy = ConfirmationPolicy::ConfirmedPlus(0);
+
+        // Vanishing orphans the event once; staying gone changes nothing
+        assert_eq!(
+            seen_transition(EventStatus::Seen, 10, None, policy, 50),
+            SeenTransition::Orphan
+        );
+        assert_eq!(
+            seen_transition(EventStatus::Orphaned, 10, None, policy, 50),
+            SeenTransition::Unchanged
+        );
+
+        // Reappearing goes back to waiting, even in the same slot
+        let processed = tx_status(10, Processed);
+        assert_eq!(
+            seen_transition(EventStatus::Orphaned, 10, Some(&processed), policy, 50),
+            SeenTransition::Reseen(10)
+        );
+        // And is relayed straight away if it already qualifies
+        let confirmed = tx_status(12, Confirmed);
+        assert_eq!(
+            seen_transition(EventStatus::Orphaned, 10, Some(&confirmed), policy, 50),
+            SeenTransition::Confirm
+        );
+    }
+
+    fn tracked(last_error: Option<&str>, updated_at: i64) -> store::TrackedWithdrawal {
+        store::TrackedWithdrawal {
+            nonce: 7,
+            state: WithdrawalState::Open,
+            challenge_deadline: Some(1_000),
+            attempts: u32::from(last_error.is_some()),
+            last_error: last_error.map(str::to_string),
+            updated_at,
         }
-    });
-}
+    }
 
-// ── Main Loop ───────────────────────────────────────────────────────────────
+    /// A WithdrawalRequest account as the L1 bridge writes it, bump included.
+    fn withdrawal_account(mint: Pubkey, status: u8) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&[4; 32]);
+        data.extend_from_slice(&500u64.to_le_bytes());
+        data.extend_from_slice(mint.as_ref());
+        data.extend_from_slice(&100u64.to_le_bytes());
+        data.extend_from_slice(&1_000i64.to_le_bytes());
+        data.push(status);
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.push(255);
+        data
+    }
 
-fn run_relayer(config: RelayerConfig) {
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
+    #[test]
+    fn test_withdrawal_request_mirror_reads_the_l1_layout() {
+        let data = withdrawal_account(Pubkey::new_from_array([5; 32]), 1);
+        let withdrawal = L1WithdrawalRequest::deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(withdrawal.recipient, Pubkey::new_from_array([4; 32]));
+        assert_eq!((withdrawal.amount, withdrawal.l2_slot, withdrawal.nonce), (500, 100, 7));
+        assert_eq!(withdrawal.challenge_deadline, 1_000);
+        assert_eq!(withdrawal.status.tracked_state(), WithdrawalState::Challenged);
 
-    // Install signal handler for graceful shutdown
-    ctrlc_handler(r);
+        let states: Vec<_> = (0..4u8)
+            .map(|status| {
+                let data = withdrawal_account(Pubkey::default(), status);
+                L1WithdrawalRequest::deserialize(&mut data.as_slice()).unwrap().status.tracked_state()
+            })
+            .collect();
+        assert_eq!(
+            states,
+            [
+                WithdrawalState::Open,
+                WithdrawalState::Challenged,
+                WithdrawalState::Finalized,
+                WithdrawalState::Cancelled
+            ]
+        );
+    }
 
-    let l1_client = RpcClient::new_with_commitment(
-        config.l1_rpc_url.clone(),
-        CommitmentConfig::confirmed(),
-    );
-    let l2_client = RpcClient::new_with_commitment(
-        config.l2_rpc_url.clone(),
-        CommitmentConfig::confirmed(),
-    );
+    #[test]
+    fn test_finalize_due_waits_for_deadline_and_pending_status() {
+        let fresh = tracked(None, 0);
+        assert!(!finalize_due(&fresh, WithdrawalState::Open, 1_000, 999));
+        assert!(finalize_due(&fresh, WithdrawalState::Open, 1_000, 1_000));
+        for state in [WithdrawalState::Challenged, WithdrawalState::Finalized, WithdrawalState::Cancelled] {
+            assert!(!finalize_due(&fresh, state, 1_000, 5_000));
+        }
+    }
 
-    let mut state = RelayerState::load(&config.state_file);
+    #[test]
+    fn test_finalize_due_backs_off_after_a_failed_attempt() {
+        let failed = tracked(Some("root not finalized"), 2_000);
+        assert!(!finalize_due(&failed, WithdrawalState::Open, 1_000, 2_000 + FINALIZE_RETRY_SECS - 1));
+        assert!(finalize_due(&failed, WithdrawalState::Open, 1_000, 2_000 + FINALIZE_RETRY_SECS));
+    }
 
-    println!("=== Mythic L2 Bridge Relayer ===");
-    println!("L1 RPC:          {}", config.l1_rpc_url);
-    println!("L2 RPC:          {}", config.l2_rpc_url);
-    println!("Bridge L1:       {}", config.bridge_l1_program);
-    println!("Bridge L2:       {}", config.bridge_l2_program);
-    println!("Relayer:         {}", config.relayer_keypair.pubkey());
-    println!("Health Port:     {}", config.health_port);
-    println!("Poll Interval:   {}ms", config.poll_interval_ms);
-    println!(
-        "Last Deposit:    nonce={}",
-        state.last_deposit_nonce
-    );
-    println!(
-        "Last Burn:       nonce={}",
-        state.last_burn_nonce
-    );
-    println!();
+    fn sample_withdrawal(token_mint: Pubkey) -> L1WithdrawalRequest {
+        let data = withdrawal_account(token_mint, 0);
+        L1WithdrawalRequest::deserialize(&mut data.as_slice()).unwrap()
+    }
 
-    start_health_server(config.health_port, running.clone());
+    fn sample_proof() -> WithdrawalProof {
+        WithdrawalProof { l2_slot: 100, leaf_index: 3, proof: vec![[0x11; 32]] }
+    }
 
-    while running.load(Ordering::Relaxed) {
-        // ── Watch L1 Deposits ───────────────────────────────────────────
-        match poll_l1_deposits(
-            &l1_client,
-            &l2_client,
-            &config,
-            &mut state,
-        ) {
-            Ok(count) => {
-                if count > 0 {
-                    println!("[DEPOSIT] Relayed {} deposits", count);
-                    state.save(&config.state_file);
-                }
-            }
-            Err(e) => {
-                eprintln!("[DEPOSIT] Error polling L1: {}", e);
+    #[test]
+    fn test_finalize_sol_withdrawal_layout() {
+        let program = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let lp = Pubkey::new_unique();
+        let withdrawal = sample_withdrawal(Pubkey::from_str(NATIVE_SOL_MINT).unwrap());
+
+        let ixs = build_finalize_withdrawal_ixs(&program, &payer, &withdrawal, &lp, None, sample_proof());
+        assert_eq!(ixs.len(), 1);
+        let ix = &ixs[0];
+        assert_eq!(ix.data[0], IX_FINALIZE_SOL_WITHDRAWAL);
+        let params = FinalizeWithdrawalParams {
+            withdrawal_nonce: 7,
+            leaf_index: 3,
+            merkle_proof: vec![[0x11; 32]],
+        };
+        assert_eq!(ix.data[1..], borsh::to_vec(&params).unwrap());
+
+        assert_eq!(ix.accounts.len(), 9);
+        assert_eq!(ix.accounts[3].pubkey, lp);
+        assert_eq!(ix.accounts[8].pubkey, payer);
+        assert!(ix.accounts[8].is_writable);
+        let (fast_pda, _) = Pubkey::find_program_address(&[FAST_WITHDRAWAL_SEED, &7u64.to_le_bytes()], &program);
+        assert_eq!(ix.accounts[7].pubkey, fast_pda);
+        let (state_root, _) = Pubkey::find_program_address(
+            &[STATE_ROOT_SEED, &100u64.to_le_bytes()],
+            &Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap(),
+        );
+        assert_eq!(ix.accounts[6].pubkey, state_root);
+    }
+
+    #[test]
+    fn test_finalize_spl_withdrawal_creates_the_payee_token_account() {
+        let program = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token_program = Pubkey::new_unique();
+        let withdrawal = sample_withdrawal(mint);
+
+        let ixs = build_finalize_withdrawal_ixs(
+            &program,
+            &payer,
+            &withdrawal,
+            &recipient,
+            Some(&token_program),
+            sample_proof(),
+        );
+        assert_eq!(ixs.len(), 2);
+        let (ata, _) = build_create_ata_ix(&payer, &recipient, &mint, &token_program);
+        assert_eq!(ixs[0].accounts[1].pubkey, ata);
+        assert_eq!(ixs[1].data[0], IX_FINALIZE_WITHDRAWAL);
+        assert_eq!(ixs[1].accounts.len(), 10);
+        assert_eq!(ixs[1].accounts[3].pubkey, ata);
+        assert_eq!(ixs[1].accounts[6].pubkey, token_program);
+        assert_eq!(ixs[1].accounts[9].pubkey, payer);
+
+    }
+
+    #[test]
+    fn test_migrate_withdrawal_layout() {
+        let program = Pubkey::new_unique();
+        let sequencer = Pubkey::new_unique();
+        let ix = build_migrate_withdrawal_ix(&program, &sequencer, 7, sample_proof());
+        assert_eq!(ix.data[0], IX_MIGRATE_WITHDRAWAL);
+        let params = MigrateWithdrawalParams {
+            withdrawal_nonce: 7,
+            l2_slot: 100,
+            leaf_index: 3,
+            merkle_proof: vec![[0x11; 32]],
+        };
+        assert_eq!(ix.data[1..], borsh::to_vec(&params).unwrap());
+        assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+        assert!(ix.accounts[1].is_writable);
+        assert_eq!(ix.accounts[3].pubkey, find_settlement_pda(&[STATE_ROOT_SEED, &100u64.to_le_bytes()]));
+    }
+
+    #[test]
+    fn test_close_fast_withdrawal_layout() {
+        let program = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let ix = build_close_fast_withdrawal_ix(&program, 7, &recipient);
+        assert_eq!(ix.data, [&[IX_CLOSE_FAST_WITHDRAWAL][..], &7u64.to_le_bytes()].concat());
+        assert!(!ix.accounts[0].is_writable);
+        assert!(ix.accounts[1].is_writable && ix.accounts[2].is_writable);
+        assert_eq!(ix.accounts[2].pubkey, recipient);
+    }
+
+    /// A JSON-RPC endpoint on localhost answering `getSlot` and
+    /// `getLatestBlockhash`, enough for failover probes and signing.
+    fn fake_rpc() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                std::thread::spawn(move || serve_rpc(stream));
             }
-        }
+        });
+        url
+    }
 
-        // ── Watch L2 Burns ──────────────────────────────────────────────
-        match poll_l2_burns(
-            &l1_client,
-            &l2_client,
-            &config,
-            &mut state,
-        ) {
-            Ok(count) => {
-                if count > 0 {
-                    println!("[BURN] Relayed {} burns", count);
-                    state.save(&config.state_file);
+    fn serve_rpc(mut stream: std::net::TcpStream) {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            // One request per pass over the kept-alive connection
+            let Some(header_end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+                match stream.read(&mut chunk) {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                }
+                continue;
+            };
+            let headers = String::from_utf8_lossy(&buf[..header_end]).to_ascii_lowercase();
+            let length: usize = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|n| n.trim().parse().ok())
+                .unwrap_or(0);
+            let body_start = header_end + 4;
+            if buf.len() < body_start + length {
+                match stream.read(&mut chunk) {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => buf.extend_from_slice(&chunk[..n]),
                 }
+                continue;
             }
-            Err(e) => {
-                eprintln!("[BURN] Error polling L2: {}", e);
+            let request: serde_json::Value = serde_json::from_slice(&buf[body_start..body_start + length]).unwrap();
+            buf.drain(..body_start + length);
+
+            let result = match request["method"].as_str() {
+                Some("getSlot") => serde_json::json!(5),
+                Some("getLatestBlockhash") => serde_json::json!({
+                    "context": { "slot": 5 },
+                    "value": { "blockhash": Pubkey::new_from_array([9; 32]).to_string(), "lastValidBlockHeight": 100 },
+                }),
+                _ => serde_json::Value::Null,
+            };
+            let body = serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }).to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if stream.write_all(response.as_bytes()).is_err() {
+                return;
             }
         }
+    }
 
-        std::thread::sleep(std::time::Duration::from_millis(config.poll_interval_ms));
+    /// A URL nothing is listening on.
+    fn dead_rpc() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
     }
 
-    println!("[SHUTDOWN] Saving state...");
-    state.save(&config.state_file);
-    println!("[SHUTDOWN] Relayer stopped.");
-}
+    #[tokio::test]
+    async fn test_failover_moves_to_the_next_answering_endpoint() {
+        let live = fake_rpc();
+        let urls = [dead_rpc(), live.clone(), fake_rpc()];
+        let rpc = FailoverRpc::connect("L1", &urls, CommitmentConfig::confirmed()).await;
+        assert_eq!(rpc.url(), live);
+        assert_eq!(rpc.client().url(), live);
 
-fn poll_l1_deposits(
-    l1_client: &RpcClient,
-    l2_client: &RpcClient,
-    config: &RelayerConfig,
-    state: &mut RelayerState,
-) -> Result<u64, String> {
-    // Get recent transaction signatures for the bridge program
-    let sigs = l1_client
-        .get_signatures_for_address(&config.bridge_l1_program)
-        .map_err(|e| format!("Failed to get L1 signatures: {}", e))?;
+        // A healthy endpoint is kept rather than moving on again
+        rpc.check().await;
+        assert_eq!(rpc.url(), live);
 
-    let mut relayed = 0u64;
+        let none = [dead_rpc(), dead_rpc()];
+        let rpc = FailoverRpc::connect("L2", &none, CommitmentConfig::confirmed()).await;
+        assert_eq!(rpc.url(), none[0]);
+    }
 
-    for sig_info in &sigs {
-        if sig_info.err.is_some() {
-            continue;
+    /// A relayer over an in-memory store; its endpoints are never probed.
+    fn test_relayer(overrides: &str, url: &str) -> Relayer {
+        let rpc = |chain| FailoverRpc {
+            chain,
+            urls: vec![url.to_string()],
+            commitment: CommitmentConfig::confirmed(),
+            current: RwLock::new((0, Arc::new(RpcClient::new(url.to_string())))),
+            switching: tokio::sync::Mutex::new(()),
+        };
+        let config = RelayerConfig::for_tests(overrides);
+        Relayer {
+            metrics: Arc::new(Metrics::new(config.health_thresholds)),
+            config,
+            l1: rpc("L1"),
+            l2: rpc("L2"),
+            store: Mutex::new(RelayerStore::open_in_memory().unwrap()),
+            in_flight: Mutex::new(HashSet::new()),
+            deposits_due: Notify::new(),
+            burns_due: Notify::new(),
+            running: Arc::new(AtomicBool::new(true)),
         }
+    }
 
-        let sig = sig_info
-            .signature
-            .parse()
-            .map_err(|e| format!("Invalid signature: {}", e))?;
+    /// Record `nonces` as confirmed burns, ready to relay.
+    fn pending_burns(relayer: &Relayer, nonces: std::ops::Range<u64>) {
+        let mut store = relayer.store();
+        for nonce in nonces {
+            let record = burn_record(nonce, &format!("sig{}", nonce), nonce);
+            store.record_scanned(Queue::Burns, &record.source_signature, nonce, &[(nonce, record.event)]).unwrap();
+            store.mark_confirmed(Queue::Burns, nonce).unwrap();
+        }
+    }
 
-        // Skip already-processed signatures
-        if let Some(ref last_sig) = state.last_deposit_signature {
-            if &sig_info.signature == last_sig {
-                break;
-            }
+    fn nonces<T>(records: &[EventRecord<T>]) -> Vec<u64> {
+        records.iter().map(|record| record.nonce).collect()
+    }
+
+    #[test]
+    fn test_claims_skip_in_flight_events_and_honour_the_poll_limit() {
+        let relayer = test_relayer("[limits]\nmax_relays_per_poll = 2", &dead_rpc());
+        pending_burns(&relayer, 0..3);
+
+        let first = relayer.claim_unrelayed::<L2Event>(Queue::Burns).unwrap();
+        assert_eq!(nonces(&first), [0, 1]);
+        let second = relayer.claim_unrelayed::<L2Event>(Queue::Burns).unwrap();
+        assert_eq!(nonces(&second), [2]);
+        assert!(relayer.claim_unrelayed::<L2Event>(Queue::Burns).unwrap().is_empty());
+
+        relayer.release(Queue::Burns, &first[..1]);
+        assert_eq!(nonces(&relayer.claim_unrelayed::<L2Event>(Queue::Burns).unwrap()), [0]);
+        // Claims are per queue, so the same nonces stay free for deposits
+        assert!(relayer.in_flight().iter().all(|&(queue, _)| queue == Queue::Burns));
+    }
+
+    #[tokio::test]
+    async fn test_queue_relays_hands_jobs_over_in_nonce_order() {
+        let relayer = test_relayer("", &dead_rpc());
+        pending_burns(&relayer, 0..3);
+        let claimed = relayer.claim_unrelayed::<L2Event>(Queue::Burns).unwrap();
+
+        let (jobs, mut receiver) = mpsc::channel(8);
+        assert_eq!(queue_relays(&relayer, &jobs, Queue::Burns, claimed, RelayJob::Burn).await, Ok(3));
+        let mut queued = Vec::new();
+        while let Ok(RelayJob::Burn(record)) = receiver.try_recv() {
+            queued.push(record.nonce);
         }
+        assert_eq!(queued, [0, 1, 2]);
+        // Still in flight until a submitter records them
+        assert_eq!(relayer.in_flight().len(), 3);
+    }
 
-        let tx = match l1_client.get_transaction_with_config(
-            &sig,
-            solana_client::rpc_config::RpcTransactionConfig {
-                encoding: Some(solana_transaction_status::UiTransactionEncoding::Json),
-                commitment: Some(CommitmentConfig::confirmed()),
-                max_supported_transaction_version: Some(0),
-            },
-        ) {
-            Ok(t) => t,
-            Err(_) => continue,
-        };
+    #[tokio::test]
+    async fn test_queue_relays_releases_what_a_stopped_submitter_never_took() {
+        let relayer = test_relayer("", &dead_rpc());
+        pending_burns(&relayer, 0..3);
+        let claimed = relayer.claim_unrelayed::<L2Event>(Queue::Burns).unwrap();
 
-        let logs = match &tx.transaction.meta {
-            Some(meta) => match &meta.log_messages {
-                solana_transaction_status::option_serializer::OptionSerializer::Some(logs) => {
-                    logs.clone()
-                }
-                _ => continue,
-            },
-            None => continue,
+        let (jobs, receiver) = mpsc::channel(8);
+        drop(receiver);
+        assert!(queue_relays(&relayer, &jobs, Queue::Burns, claimed, RelayJob::Burn).await.is_err());
+        assert!(relayer.in_flight().is_empty());
+        assert_eq!(nonces(&relayer.claim_unrelayed::<L2Event>(Queue::Burns).unwrap()), [0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_signed_transactions_carry_the_configured_fee_settings() {
+        let url = fake_rpc();
+        let ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], Vec::new());
+        let programs = |tx: &Transaction| -> Vec<Pubkey> {
+            tx.message.instructions.iter().map(|ix| tx.message.account_keys[ix.program_id_index as usize]).collect()
         };
 
-        let events = parse_deposit_events(&logs);
-        for event in events {
-            if event.nonce <= state.last_deposit_nonce {
-                continue;
-            }
+        let relayer = test_relayer("", &url);
+        let tx = sign_transaction(&relayer.l2.client(), &relayer.config, std::slice::from_ref(&ix)).await.unwrap();
+        assert_eq!(programs(&tx), [ix.program_id]);
+        assert_eq!(tx.message.recent_blockhash.to_bytes(), [9; 32]);
 
-            // Parse the L2 recipient from hex
-            let l2_recipient = match hex_to_pubkey(&event.l2_recipient) {
-                Some(pk) => pk,
-                None => {
-                    eprintln!(
-                        "[DEPOSIT] Invalid l2_recipient hex: {}",
-                        event.l2_recipient
-                    );
-                    continue;
-                }
-            };
+        let relayer = test_relayer("[fees]\ncompute_unit_limit = 300000\npriority_fee_micro_lamports = 5000", &url);
+        let tx = sign_transaction(&relayer.l2.client(), &relayer.config, std::slice::from_ref(&ix)).await.unwrap();
+        assert_eq!(programs(&tx), [solana_sdk::compute_budget::id(), solana_sdk::compute_budget::id(), ix.program_id]);
+        assert_eq!(
+            tx.message.instructions[..2].iter().map(|ix| ix.data.clone()).collect::<Vec<_>>(),
+            [
+                ComputeBudgetInstruction::set_compute_unit_limit(300_000).data,
+                ComputeBudgetInstruction::set_compute_unit_price(5_000).data,
+            ]
+        );
+        assert!(tx.verify().is_ok());
+    }
 
-            // Convert the L1 transaction signature to a 64-byte array
-            let l1_tx_sig_bytes: [u8; 64] = sig.as_ref().try_into().unwrap_or([0u8; 64]);
+    #[test]
+    fn test_websocket_url_follows_the_rpc_endpoint() {
+        assert_eq!(websocket_url("http://localhost:8899"), "ws://localhost:8900");
+        assert_eq!(websocket_url("https://rpc.example.com"), "wss://rpc.example.com");
+        assert_eq!(websocket_url("https://rpc.example.com:443/key/abc"), "wss://rpc.example.com:444/key/abc");
+        assert_eq!(websocket_url("http://10.0.0.2:8999/"), "ws://10.0.0.2:9000/");
+        assert_eq!(websocket_url("localhost:8899"), "ws://localhost:8900");
+        // IPv6 hosts keep their brackets; only a numeric port is bumped
+        assert_eq!(websocket_url("http://[::1]:8899"), "ws://[::1]:8900");
+        assert_eq!(websocket_url("http://[::1]"), "ws://[::1]");
+    }
 
-            // Scale L1 amount (6 decimals) to L2 amount (9 decimals)
-            let l2_amount = event.amount
-                .checked_mul(DECIMAL_SCALING_FACTOR)
-                .unwrap_or(event.amount);
+    fn bridge_to_l1_log(nonce: u64) -> String {
+        format!(
+            "Program log: EVENT:BridgeToL1:{{\"sender\":\"{}\",\"l1_recipient\":\"{}\",\"amount\":5,\"withdraw_nonce\":{}}}",
+            Pubkey::new_from_array([1; 32]),
+            bytes_to_hex(&[2; 32]),
+            nonce
+        )
+    }
 
-            let ix = build_release_bridged_ix(
-                &config.bridge_l2_program,
-                &config.relayer_keypair.pubkey(),
-                &l2_recipient,
-                l2_amount,
-                event.nonce,
-                &l1_tx_sig_bytes,
-            );
+    fn streamed(signature: &str, err: Option<solana_sdk::transaction::TransactionError>, logs: Vec<String>) -> RpcLogsResponse {
+        RpcLogsResponse { signature: signature.to_string(), err, logs }
+    }
 
-            let result = retry_with_backoff(
-                || {
-                    let recent_hash = l2_client
-                        .get_latest_blockhash()
-                        .map_err(|e| format!("L2 blockhash: {}", e))?;
-                    let tx = Transaction::new_signed_with_payer(
-                        &[ix.clone()],
-                        Some(&config.relayer_keypair.pubkey()),
-                        &[&config.relayer_keypair],
-                        recent_hash,
-                    );
-                    l2_client
-                        .send_and_confirm_transaction(&tx)
-                        .map_err(|e| format!("L2 tx: {}", e))
-                },
-                5,
-            );
+    /// Whether `queue`'s producer has a wake-up waiting.
+    async fn woken(relayer: &Relayer, queue: Queue) -> bool {
+        tokio::time::timeout(std::time::Duration::from_millis(10), relayer.due(queue).notified())
+            .await
+            .is_ok()
+    }
 
-            match result {
-                Ok(tx_sig) => {
-                    println!(
-                        "RELAYED DEPOSIT: nonce={} amount={} recipient={} tx={}",
-                        event.nonce, event.amount, l2_recipient, tx_sig
-                    );
-                    state.last_deposit_nonce = event.nonce;
-                    relayed += 1;
-                }
-                Err(e) => {
-                    eprintln!(
-                        "[DEPOSIT] Failed to relay nonce={}: {}",
-                        event.nonce, e
-                    );
-                }
-            }
-        }
+    #[tokio::test]
+    async fn test_streamed_logs_record_new_events_and_wake_the_producer() {
+        let relayer = test_relayer("", &dead_rpc());
+        let logs = vec!["Program log: unrelated".to_string(), bridge_to_l1_log(0), bridge_to_l1_log(1)];
+        record_streamed_logs::<L2Event>(&relayer, Queue::Burns, "L2", 40, &streamed("sig", None, logs.clone()));
+
+        let seen = relayer.store().unconfirmed::<L2Event>(Queue::Burns).unwrap();
+        assert_eq!(nonces(&seen), [0, 1]);
+        assert!(seen.iter().all(|record| record.source_slot == 40 && record.source_signature == "sig"));
+        // The scan cursor is the poll's; a stream may run ahead of it
+        assert_eq!(relayer.store().cursor(Queue::Burns).unwrap(), None);
+        assert!(woken(&relayer, Queue::Burns).await);
+        assert!(!woken(&relayer, Queue::Deposits).await);
 
-        // Track the latest signature we've seen
-        state.last_deposit_signature = Some(sig_info.signature.clone());
+        // The same transaction again, e.g. after a reconnect, is nothing new
+        record_streamed_logs::<L2Event>(&relayer, Queue::Burns, "L2", 40, &streamed("sig", None, logs));
+        assert!(!woken(&relayer, Queue::Burns).await);
     }
 
-    Ok(relayed)
-}
+    #[tokio::test]
+    async fn test_streamed_logs_skip_failed_and_eventless_transactions() {
+        let relayer = test_relayer("", &dead_rpc());
+        let failed = streamed(
+            "failed",
+            Some(solana_sdk::transaction::TransactionError::AccountNotFound),
+            vec![bridge_to_l1_log(0)],
+        );
+        record_streamed_logs::<L2Event>(&relayer, Queue::Burns, "L2", 40, &failed);
+        record_streamed_logs::<L2Event>(&relayer, Queue::Burns, "L2", 41, &streamed("quiet", None, vec!["Program log: hi".to_string()]));
+        // Deposit logs are not burns
+        let deposit = "Program log: EVENT:DepositSOL:{\"depositor\":\"a\",\"l2_recipient\":\"b\",\"amount\":1,\"nonce\":0}";
+        record_streamed_logs::<L2Event>(&relayer, Queue::Burns, "L2", 42, &streamed("deposit", None, vec![deposit.to_string()]));
 
-fn poll_l2_burns(
-    l1_client: &RpcClient,
-    l2_client: &RpcClient,
-    config: &RelayerConfig,
-    state: &mut RelayerState,
-) -> Result<u64, String> {
-    let sigs = l2_client
-        .get_signatures_for_address(&config.bridge_l2_program)
-        .map_err(|e| format!("Failed to get L2 signatures: {}", e))?;
+        assert!(relayer.store().unconfirmed::<L2Event>(Queue::Burns).unwrap().is_empty());
+        assert!(!woken(&relayer, Queue::Burns).await);
 
-    let mut relayed = 0u64;
+        record_streamed_logs::<L1Event>(&relayer, Queue::Deposits, "L1", 42, &streamed("deposit", None, vec![deposit.to_string()]));
+        assert_eq!(nonces(&relayer.store().unconfirmed::<L1Event>(Queue::Deposits).unwrap()), [0]);
+        assert!(woken(&relayer, Queue::Deposits).await);
+    }
 
-    for sig_info in &sigs {
-        if sig_info.err.is_some() {
-            continue;
+    pub(crate) fn burn_record(nonce: u64, source_signature: &str, source_slot: u64) -> EventRecord<L2Event> {
+        EventRecord {
+            nonce,
+            source_signature: source_signature.to_string(),
+            source_slot,
+            event: L2Event::Burn(BridgeToL1Event {
+                sender: Pubkey::new_unique().to_string(),
+                l1_recipient: bytes_to_hex(&[nonce as u8 + 1; 32]),
+                amount: 1_000 * (nonce + 1),
+                withdraw_nonce: nonce,
+                l1_mint: None,
+            }),
+            status: EventStatus::Relayed,
+            attempts: 0,
+            last_error: None,
+            relay_signature: None,
+            observed_at: 0,
+            updated_at: 0,
         }
+    }
 
-        let sig = sig_info
-            .signature
-            .parse()
-            .map_err(|e| format!("Invalid signature: {}", e))?;
+    /// A signed L2 transaction in batch wire format, with its signature.
+    pub(crate) fn batch_tx() -> (String, Vec<u8>) {
+        let payer = solana_sdk::signature::Keypair::new();
+        let ix = solana_sdk::system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+        let tx = solana_sdk::transaction::VersionedTransaction::from(Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer],
+            solana_sdk::hash::Hash::default(),
+        ));
+        (tx.signatures[0].to_string(), bincode::serialize(&tx).unwrap())
+    }
 
-        if let Some(ref last_sig) = state.last_burn_signature {
-            if &sig_info.signature == last_sig {
-                break;
-            }
-        }
+    #[test]
+    fn test_batch_step_root_replays_the_batch_one_transaction_at_a_time() {
+        let (a, a_bytes) = batch_tx();
+        let (_, b_bytes) = batch_tx();
+        let (c, c_bytes) = batch_tx();
+        let blocks = vec![
+            L2BatchBlock { slot: 10, blockhash: [0; 32], transactions: vec![a_bytes, b_bytes] },
+            L2BatchBlock { slot: 12, blockhash: [0; 32], transactions: vec![c_bytes] },
+        ];
+        // Nonce 0 predates the batch; `b` emits nothing and `c` emits two
+        let records = vec![
+            burn_record(0, "earlier", 5),
+            burn_record(1, &a, 10),
+            burn_record(2, &c, 12),
+            burn_record(3, &c, 12),
+        ];
+        let root_of = |n: usize| withdrawal_tree_root(&records[..n]).unwrap();
 
-        let tx = match l2_client.get_transaction_with_config(
-            &sig,
-            solana_client::rpc_config::RpcTransactionConfig {
-                encoding: Some(solana_transaction_status::UiTransactionEncoding::Json),
-                commitment: Some(CommitmentConfig::confirmed()),
-                max_supported_transaction_version: Some(0),
-            },
-        ) {
-            Ok(t) => t,
-            Err(_) => continue,
-        };
+        assert_eq!(batch_step_root(&records, &blocks, 0).unwrap(), root_of(1));
+        assert_eq!(batch_step_root(&records, &blocks, 1).unwrap(), root_of(2));
+        assert_eq!(batch_step_root(&records, &blocks, 2).unwrap(), root_of(2));
+        assert_eq!(batch_step_root(&records, &blocks, 3).unwrap(), root_of(4));
+        assert_ne!(root_of(1), root_of(2));
+        assert!(batch_step_root(&records, &[], 0).is_err());
 
-        let logs = match &tx.transaction.meta {
-            Some(meta) => match &meta.log_messages {
-                solana_transaction_status::option_serializer::OptionSerializer::Some(logs) => {
-                    logs.clone()
-                }
-                _ => continue,
-            },
-            None => continue,
-        };
+        // A step that would leave a nonce gap cannot be replayed
+        let out_of_order = vec![burn_record(0, "earlier", 5), burn_record(1, &c, 12), burn_record(2, &a, 10)];
+        assert!(batch_step_root(&out_of_order, &blocks, 1).is_err());
+    }
 
-        let events = parse_bridge_to_l1_events(&logs);
-        for event in events {
-            if event.withdraw_nonce <= state.last_burn_nonce {
-                continue;
+    #[test]
+    fn test_withdrawal_state_root_folds_every_withdrawal_through_the_slot() {
+        let mut store = RelayerStore::open_in_memory().unwrap();
+        let records = [burn_record(0, "a", 5), burn_record(1, "b", 10), burn_record(2, "c", 12)];
+        for record in &records {
+            store
+                .record_scanned(Queue::Burns, &record.source_signature, record.source_slot, &[(record.nonce, record.event.clone())])
+                .unwrap();
+        }
+        let leaf = |i: usize| match &records[i].event {
+            L2Event::Burn(event) => {
+                let (recipient, mint, amount) = expected_l1_withdrawal(event).unwrap();
+                withdrawal_leaf_hash(&recipient, amount, &mint, i as u64)
             }
+            L2Event::Message(_) => unreachable!(),
+        };
 
-            // l1_recipient is emitted as hex-encoded bytes by the L2 bridge
-            let l1_recipient = match hex_to_pubkey(&event.l1_recipient) {
-                Some(pk) => pk,
-                None => {
-                    eprintln!(
-                        "[BRIDGE_TO_L1] Invalid l1_recipient hex: {}",
-                        event.l1_recipient
-                    );
-                    continue;
-                }
-            };
+        assert_eq!(withdrawal_state_root(&store, 4).unwrap(), [0; 32]);
+        assert_eq!(withdrawal_state_root(&store, 9).unwrap(), leaf(0));
+        assert_eq!(withdrawal_state_root(&store, 11).unwrap(), merkle_node(&leaf(0), &leaf(1)));
+        assert_eq!(
+            withdrawal_state_root(&store, 12).unwrap(),
+            merkle_node(&merkle_node(&leaf(0), &leaf(1)), &merkle_node(&leaf(2), &[0; 32]))
+        );
 
-            // MYTH L1 mint address
-            let myth_l1_mint = Pubkey::from_str("5UP2iL9DefXC3yovX9b4XG2EiCnyxuVo3S2F6ik5pump")
-                .unwrap();
+        // The root must never skip a withdrawal the index is missing
+        let mut gappy = RelayerStore::open_in_memory().unwrap();
+        gappy.record_scanned(Queue::Burns, "a", 5, &[(0, records[0].event.clone())]).unwrap();
+        gappy.record_scanned(Queue::Burns, "c", 12, &[(2, records[2].event.clone())]).unwrap();
+        assert!(withdrawal_state_root(&gappy, 12).is_err());
+    }
 
-            // Scale L2 amount (9 decimals) to L1 amount (6 decimals)
-            let l1_amount = event.amount / DECIMAL_SCALING_FACTOR;
+    #[test]
+    fn test_withdrawal_proof_verifies_against_the_posted_root() {
+        let records: Vec<_> = (0..5).map(|nonce| burn_record(nonce, "a", 10 + nonce)).collect();
+        let root = withdrawal_tree_root(&records).unwrap();
 
-            let ix = build_initiate_withdrawal_ix(
-                &config.bridge_l1_program,
-                &config.relayer_keypair.pubkey(),
-                &l1_recipient,
-                l1_amount,
-                &myth_l1_mint,
-                event.withdraw_nonce,
-            );
+        for record in &records {
+            let L2Event::Burn(event) = &record.event else { unreachable!() };
+            let (recipient, mint, amount) = expected_l1_withdrawal(event).unwrap();
+            let leaf = mythic_bridge::withdrawal_leaf_hash(&recipient, amount, &mint, record.nonce);
 
-            let result = retry_with_backoff(
-                || {
-                    let recent_hash = l1_client
-                        .get_latest_blockhash()
-                        .map_err(|e| format!("L1 blockhash: {}", e))?;
-                    let tx = Transaction::new_signed_with_payer(
-                        &[ix.clone()],
-                        Some(&config.relayer_keypair.pubkey()),
-                        &[&config.relayer_keypair],
-                        recent_hash,
-                    );
-                    l1_client
-                        .send_and_confirm_transaction(&tx)
-                        .map_err(|e| format!("L1 tx: {}", e))
-                },
-                5,
-            );
+            let proof = withdrawal_proof(&records, record.nonce, 14).unwrap();
+            assert_eq!(proof.l2_slot, 14);
+            assert_eq!(proof.leaf_index, record.nonce);
+            assert_eq!(proof.proof.len(), 3);
+            assert!(mythic_bridge::verify_merkle_proof(&leaf, &proof.proof, proof.leaf_index, &root));
+            assert!(!mythic_bridge::verify_merkle_proof(&leaf, &proof.proof, (proof.leaf_index + 1) % 8, &root));
+        }
 
-            match result {
-                Ok(tx_sig) => {
-                    println!(
-                        "RELAYED BRIDGE_TO_L1: nonce={} amount={} l1_recipient={} tx={}",
-                        event.withdraw_nonce, event.amount, l1_recipient, tx_sig
-                    );
-                    state.last_burn_nonce = event.withdraw_nonce;
-                    relayed += 1;
-                }
-                Err(e) => {
-                    eprintln!(
-                        "[BRIDGE_TO_L1] Failed to relay nonce={}: {}",
-                        event.withdraw_nonce, e
-                    );
+        // A lone withdrawal is its own root
+        let proof = withdrawal_proof(&records[..1], 0, 10).unwrap();
+        assert!(proof.proof.is_empty());
+
+        // Nothing to prove until a root covers the nonce
+        assert!(withdrawal_proof(&records, 5, 14).is_err());
+        assert!(withdrawal_proof(&records[1..], 1, 14).is_err());
+    }
+
+    #[test]
+    fn test_batch_data_hash_chains_chunk_hashes() {
+        let batch: Vec<u8> = (0..BATCH_CHUNK_SIZE * 2 + 5).map(|i| i as u8).collect();
+        let mut outer = Sha256::new();
+        outer.update(Sha256::digest(&batch[..BATCH_CHUNK_SIZE]));
+        outer.update(Sha256::digest(&batch[BATCH_CHUNK_SIZE..BATCH_CHUNK_SIZE * 2]));
+        outer.update(Sha256::digest(&batch[BATCH_CHUNK_SIZE * 2..]));
+        let expected: [u8; 32] = outer.finalize().into();
+        assert_eq!(batch_data_hash(&batch), expected);
+
+        // One chunk is still hashed twice, so it differs from a plain digest
+        let small = [7u8; 10];
+        assert_eq!(batch_data_hash(&small), <[u8; 32]>::from(Sha256::digest(Sha256::digest(small))));
+        assert_ne!(batch_data_hash(&small), <[u8; 32]>::from(Sha256::digest(small)));
+        assert_eq!(batch_data_hash(&[]), <[u8; 32]>::from(Sha256::digest([])));
+    }
+
+    #[test]
+    fn test_legacy_state_migrates_ledger_cursors_and_nonces() {
+        let path = std::env::temp_dir().join(format!("relayer-legacy-{}.json", std::process::id()));
+        let legacy = serde_json::json!({
+            "last_deposit_nonce": 4,
+            "last_burn_nonce": 9,
+            "last_deposit_signature": "dsig",
+            "last_burn_signature": "bsig",
+            "deposit_ledger": {
+                "5": {
+                    "l1_signature": "sig5",
+                    "event": { "Deposit": {
+                        "depositor": "a", "l2_recipient": "b", "amount": 100, "nonce": 5
+                    } },
+                    "status": "Failed",
+                    "attempts": 2,
+                    "last_error": "boom"
                 }
             }
-        }
+        });
+        fs::write(&path, legacy.to_string()).unwrap();
+
+        let mut store = RelayerStore::open_in_memory().unwrap();
+        migrate_legacy_state(&mut store, &path).unwrap();
+        let migrated = path.with_extension("json.migrated");
+        assert!(!path.exists() && migrated.exists());
+        fs::remove_file(&migrated).unwrap();
 
-        state.last_burn_signature = Some(sig_info.signature.clone());
+        assert_eq!(store.cursor(Queue::Deposits).unwrap().as_deref(), Some("dsig"));
+        assert_eq!(store.cursor(Queue::Burns).unwrap().as_deref(), Some("bsig"));
+        assert_eq!(store.max_relayed_nonce(Queue::Deposits).unwrap(), Some(4));
+        assert_eq!(store.max_relayed_nonce(Queue::Burns).unwrap(), Some(9));
+        let record = store.event::<L1Event>(Queue::Deposits, 5).unwrap().unwrap();
+        assert_eq!(record.status, EventStatus::Failed);
+        assert_eq!((record.attempts, record.last_error.as_deref()), (2, Some("boom")));
+        assert!(matches!(record.event, L1Event::Deposit(ref d) if d.token_mint == NATIVE_SOL_MINT));
+
+        // A populated store refuses a second import
+        fs::write(&path, legacy.to_string()).unwrap();
+        assert!(migrate_legacy_state(&mut store, &path).is_err());
+        fs::remove_file(&path).unwrap();
     }
 
-    Ok(relayed)
-}
+    #[test]
+    fn test_pre_ledger_state_file_migrates() {
+        let path = std::env::temp_dir().join(format!("relayer-v0-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{"last_deposit_nonce":12,"last_burn_nonce":3,"last_deposit_signature":null,"last_burn_signature":"bsig"}"#,
+        )
+        .unwrap();
 
-// ── Helpers ─────────────────────────────────────────────────────────────────
+        let mut store = RelayerStore::open_in_memory().unwrap();
+        migrate_legacy_state(&mut store, &path).unwrap();
+        fs::remove_file(path.with_extension("json.migrated")).unwrap();
 
-fn hex_to_pubkey(hex_str: &str) -> Option<Pubkey> {
-    if hex_str.len() != 64 {
-        return None;
-    }
-    let mut bytes = [0u8; 32];
-    for i in 0..32 {
-        bytes[i] = u8::from_str_radix(&hex_str[i * 2..i * 2 + 2], 16).ok()?;
+        assert_eq!(store.cursor(Queue::Deposits).unwrap(), None);
+        assert_eq!(store.cursor(Queue::Burns).unwrap().as_deref(), Some("bsig"));
+        assert_eq!(store.max_relayed_nonce(Queue::Deposits).unwrap(), Some(12));
+        assert_eq!(store.max_relayed_nonce(Queue::Burns).unwrap(), Some(3));
     }
-    Some(Pubkey::new_from_array(bytes))
-}
 
-fn ctrlc_handler(running: Arc<AtomicBool>) {
-    // Simple signal handling via polling — no external crate needed
-    // The running flag is checked in the main loop
-    std::thread::spawn(move || {
-        // Block on reading a line from stdin as a rudimentary "press enter to stop"
-        // In production, use the tokio signal handler. For now, rely on the OS
-        // sending SIGTERM which will terminate the process. The AtomicBool is
-        // primarily for the health server thread coordination.
-        let _buf = [0u8; 1];
-        // This thread just keeps running; SIGTERM/SIGINT will kill the process
-        // and the main loop periodically saves state anyway.
-        loop {
-            std::thread::sleep(std::time::Duration::from_secs(60));
-            if !running.load(Ordering::Relaxed) {
-                break;
-            }
-        }
-    });
-}
+    #[test]
+    fn test_message_event_round_trips_to_the_emitted_hash() {
+        let events = parse_send_message_events(&[send_message_log("deadbeef", MESSAGE_HASH)]);
+        assert_eq!(events.len(), 1);
 
-// ── Main ────────────────────────────────────────────────────────────────────
+        let message = message_from_event(&events[0]).unwrap();
+        assert_eq!(message.nonce, 5);
+        assert_eq!(message.payload, vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(bytes_to_hex(&message_hash(&message)), MESSAGE_HASH);
+        assert_eq!(
+            bytes_to_hex(&message_leaf_hash(&message_hash(&message))),
+            "e9c6bf93411bcf54c9edab28db167a4f16e52fb1b349067e6b9077790ad2e31e"
+        );
 
-fn main() {
-    println!("Mythic L2 Bridge Relayer v0.1.0");
+        let metas: Vec<AccountMeta> = message_account_metas(&message).collect();
+        assert_eq!(metas.len(), 1);
+        assert!(metas[0].is_writable && !metas[0].is_signer);
+    }
 
-    let config = match RelayerConfig::from_env() {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Configuration error: {}", e);
-            eprintln!();
-            eprintln!("Required environment variables:");
-            eprintln!("  RELAYER_KEYPAIR_PATH  Path to relayer keypair JSON file");
-            eprintln!();
-            eprintln!("Optional environment variables:");
-            eprintln!("  L1_RPC_URL            Solana L1 RPC URL (default: http://localhost:8899)");
-            eprintln!("  L2_RPC_URL            Mythic L2 RPC URL (default: http://localhost:8999)");
-            eprintln!("  BRIDGE_L1_PROGRAM     L1 bridge program ID");
-            eprintln!("  BRIDGE_L2_PROGRAM     L2 bridge program ID");
-            eprintln!("  HEALTH_PORT           Health check port (default: 9090)");
-            eprintln!("  POLL_INTERVAL_MS      Polling interval in ms (default: 2000)");
-            eprintln!("  STATE_FILE            State file path (default: relayer_state.json)");
-            std::process::exit(1);
-        }
-    };
+    #[test]
+    fn test_message_event_with_wrong_hash_is_rejected() {
+        let events = parse_send_message_events(&[send_message_log("deadbeee", MESSAGE_HASH)]);
+        assert!(message_from_event(&events[0]).is_err());
 
-    run_relayer(config);
+        let events = parse_send_message_events(&[send_message_log("zz", MESSAGE_HASH)]);
+        assert!(message_from_event(&events[0]).is_err());
+    }
 }
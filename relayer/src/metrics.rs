@@ -0,0 +1,381 @@
+// Relayer metrics, shared between the poll loop and the health server.
+//
+// Served three ways: Prometheus text on /metrics, a JSON snapshot on
+// /status, and /health, which answers 503 with the reasons once nonce lag
+// or consecutive poll errors cross the configured thresholds.
+
+use crate::store::{now, Queue};
+use serde::Serialize;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+/// Upper bounds (seconds) of the relay latency histogram buckets.
+const LATENCY_BUCKETS: [u64; 10] = [5, 15, 30, 60, 120, 300, 600, 1800, 3600, 21600];
+
+/// Reads one per-queue counter out of a snapshot.
+type QueueCounter = fn(&QueueStats) -> u64;
+
+/// Limits past which /health reports the relayer unhealthy.
+#[derive(Debug, Clone, Copy)]
+pub struct HealthThresholds {
+    /// Deposits emitted on L1 but not yet relayed.
+    pub max_nonce_lag: u64,
+    /// Polls in a row that failed on either side (RPC down, store error).
+    pub max_consecutive_errors: u32,
+}
+
+/// Time from first observing an event to relaying it.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    /// Non-cumulative count per `LATENCY_BUCKETS` entry, plus +Inf last.
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    pub count: u64,
+    pub sum_secs: u64,
+}
+
+impl LatencyHistogram {
+    fn observe(&mut self, secs: u64) {
+        let idx = LATENCY_BUCKETS
+            .iter()
+            .position(|&bound| secs <= bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[idx] += 1;
+        self.count += 1;
+        self.sum_secs += secs;
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct QueueStats {
+    pub seen: u64,
+    pub relayed: u64,
+    pub failed: u64,
+    pub orphaned: u64,
+    pub latency: LatencyHistogram,
+    /// Events currently in the store per status.
+    pub by_status: Vec<(String, u64)>,
+    pub last_relayed_nonce: Option<u64>,
+    /// Every nonce below this one has been relayed.
+    pub lowest_unrelayed_nonce: u64,
+    pub last_relay_at: Option<i64>,
+    pub poll_errors: u64,
+    pub consecutive_poll_errors: u32,
+    pub last_poll_ok_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Snapshot {
+    pub started_at: i64,
+    pub deposits: QueueStats,
+    pub burns: QueueStats,
+    /// Next nonce the L1 bridge will assign (`BridgeConfig.deposit_nonce`).
+    pub l1_deposit_nonce: Option<u64>,
+    pub relayer_l1_lamports: Option<u64>,
+    pub relayer_l2_lamports: Option<u64>,
+    pub l2_reserve_lamports: Option<u64>,
+    /// Finalize transactions sent by the withdrawal crank.
+    pub withdrawals_finalized: u64,
+    /// Tracked L1 withdrawals per state.
+    pub withdrawals: Vec<(String, u64)>,
+}
+
+impl Snapshot {
+    fn queue(&mut self, queue: Queue) -> &mut QueueStats {
+        match queue {
+            Queue::Deposits => &mut self.deposits,
+            Queue::Burns => &mut self.burns,
+        }
+    }
+
+    /// L1 deposits from the lowest unrelayed nonce on, so one stuck deposit
+    /// keeps counting even while later ones are relayed past it.
+    pub fn deposit_nonce_lag(&self) -> Option<u64> {
+        let next = self.l1_deposit_nonce?;
+        Some(next.saturating_sub(self.deposits.lowest_unrelayed_nonce))
+    }
+}
+
+pub struct Metrics {
+    snapshot: Mutex<Snapshot>,
+    thresholds: HealthThresholds,
+}
+
+impl Metrics {
+    pub fn new(thresholds: HealthThresholds) -> Self {
+        Self {
+            snapshot: Mutex::new(Snapshot {
+                started_at: now(),
+                ..Snapshot::default()
+            }),
+            thresholds,
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Snapshot)) {
+        let mut snapshot = self.snapshot.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut snapshot);
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        self.snapshot.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn record_seen(&self, queue: Queue, count: u64) {
+        self.update(|s| s.queue(queue).seen += count);
+    }
+
+    /// A relay transaction was sent for an event first observed at
+    /// `observed_at` (unix seconds).
+    pub fn record_relayed(&self, queue: Queue, observed_at: i64) {
+        let ts = now();
+        self.update(|s| {
+            let q = s.queue(queue);
+            q.relayed += 1;
+            q.last_relay_at = Some(ts);
+            q.latency.observe(ts.saturating_sub(observed_at).max(0) as u64);
+        });
+    }
+
+    pub fn record_failed(&self, queue: Queue) {
+        self.update(|s| s.queue(queue).failed += 1);
+    }
+
+    pub fn record_orphaned(&self, queue: Queue, count: u64) {
+        self.update(|s| s.queue(queue).orphaned += count);
+    }
+
+    pub fn record_poll(&self, queue: Queue, result: Result<(), &str>) {
+        let ts = now();
+        self.update(|s| {
+            let q = s.queue(queue);
+            match result {
+                Ok(()) => {
+                    q.consecutive_poll_errors = 0;
+                    q.last_poll_ok_at = Some(ts);
+                }
+                Err(e) => {
+                    q.poll_errors += 1;
+                    q.consecutive_poll_errors += 1;
+                    q.last_error = Some(e.to_string());
+                }
+            }
+        });
+    }
+
+    pub fn set_queue_state(
+        &self,
+        queue: Queue,
+        last_relayed_nonce: Option<u64>,
+        lowest_unrelayed_nonce: u64,
+        by_status: Vec<(String, u64)>,
+    ) {
+        self.update(|s| {
+            let q = s.queue(queue);
+            q.last_relayed_nonce = last_relayed_nonce;
+            q.lowest_unrelayed_nonce = lowest_unrelayed_nonce;
+            q.by_status = by_status;
+        });
+    }
+
+    pub fn record_withdrawal_finalized(&self) {
+        self.update(|s| s.withdrawals_finalized += 1);
+    }
+
+    pub fn set_withdrawals(&self, by_state: Vec<(String, u64)>) {
+        self.update(|s| s.withdrawals = by_state);
+    }
+
+    /// Chain-side gauges. `None` keeps the previous value (RPC failure).
+    pub fn set_chain_state(
+        &self,
+        l1_deposit_nonce: Option<u64>,
+        relayer_l1_lamports: Option<u64>,
+        relayer_l2_lamports: Option<u64>,
+        l2_reserve_lamports: Option<u64>,
+    ) {
+        self.update(|s| {
+            s.l1_deposit_nonce = l1_deposit_nonce.or(s.l1_deposit_nonce);
+            s.relayer_l1_lamports = relayer_l1_lamports.or(s.relayer_l1_lamports);
+            s.relayer_l2_lamports = relayer_l2_lamports.or(s.relayer_l2_lamports);
+            s.l2_reserve_lamports = l2_reserve_lamports.or(s.l2_reserve_lamports);
+        });
+    }
+
+    /// Reasons the relayer is unhealthy; empty when healthy.
+    pub fn health_problems(&self) -> Vec<String> {
+        let s = self.snapshot();
+        let mut problems = Vec::new();
+        let max_lag = self.thresholds.max_nonce_lag;
+        if let Some(lag) = s.deposit_nonce_lag().filter(|&lag| lag > max_lag) {
+            problems.push(format!("deposit nonce lag {} exceeds {}", lag, max_lag));
+        }
+        for (name, q) in [("deposits", &s.deposits), ("burns", &s.burns)] {
+            let dead = q
+                .by_status
+                .iter()
+                .find(|(status, _)| status == "dead_lettered")
+                .map_or(0, |(_, count)| *count);
+            if dead > 0 {
+                problems.push(format!("{} {} dead-lettered after too many failed relays", dead, name));
+            }
+            if q.consecutive_poll_errors > self.thresholds.max_consecutive_errors {
+                problems.push(format!(
+                    "{} poll failed {} times in a row: {}",
+                    name,
+                    q.consecutive_poll_errors,
+                    q.last_error.as_deref().unwrap_or("unknown error")
+                ));
+            }
+        }
+        problems
+    }
+
+    /// JSON body for /status.
+    pub fn status_json(&self) -> String {
+        let snapshot = self.snapshot();
+        let problems = self.health_problems();
+        serde_json::json!({
+            "healthy": problems.is_empty(),
+            "problems": problems,
+            "deposit_nonce_lag": snapshot.deposit_nonce_lag(),
+            "metrics": snapshot,
+        })
+        .to_string()
+    }
+
+    /// Prometheus text exposition format for /metrics.
+    pub fn render_prometheus(&self) -> String {
+        let s = self.snapshot();
+        let mut out = String::new();
+
+        let counters: [(&str, &str, QueueCounter); 5] = [
+            ("mythic_relayer_events_seen_total", "Bridge events observed", |q| q.seen),
+            ("mythic_relayer_events_relayed_total", "Relay transactions sent", |q| q.relayed),
+            ("mythic_relayer_events_failed_total", "Failed relay attempts", |q| q.failed),
+            ("mythic_relayer_events_orphaned_total", "Events whose source transaction vanished", |q| q.orphaned),
+            ("mythic_relayer_poll_errors_total", "Failed polls", |q| q.poll_errors),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}\n# TYPE {} counter", name, help, name);
+            for (queue, q) in [("deposits", &s.deposits), ("burns", &s.burns)] {
+                let _ = writeln!(out, "{}{{queue=\"{}\"}} {}", name, queue, value(q));
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP mythic_relayer_relay_latency_seconds Time from observing an event to relaying it\n\
+             # TYPE mythic_relayer_relay_latency_seconds histogram"
+        );
+        for (queue, q) in [("deposits", &s.deposits), ("burns", &s.burns)] {
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(q.latency.buckets.iter()) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "mythic_relayer_relay_latency_seconds_bucket{{queue=\"{}\",le=\"{}\"}} {}",
+                    queue, bound, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "mythic_relayer_relay_latency_seconds_bucket{{queue=\"{}\",le=\"+Inf\"}} {}\n\
+                 mythic_relayer_relay_latency_seconds_sum{{queue=\"{}\"}} {}\n\
+                 mythic_relayer_relay_latency_seconds_count{{queue=\"{}\"}} {}",
+                queue, q.latency.count, queue, q.latency.sum_secs, queue, q.latency.count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP mythic_relayer_consecutive_poll_errors Polls failed in a row\n\
+             # TYPE mythic_relayer_consecutive_poll_errors gauge"
+        );
+        for (queue, q) in [("deposits", &s.deposits), ("burns", &s.burns)] {
+            let _ = writeln!(
+                out,
+                "mythic_relayer_consecutive_poll_errors{{queue=\"{}\"}} {}",
+                queue, q.consecutive_poll_errors
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP mythic_relayer_events Events in the relayer store by status\n\
+             # TYPE mythic_relayer_events gauge"
+        );
+        for (queue, q) in [("deposits", &s.deposits), ("burns", &s.burns)] {
+            for (status, count) in &q.by_status {
+                let _ = writeln!(
+                    out,
+                    "mythic_relayer_events{{queue=\"{}\",status=\"{}\"}} {}",
+                    queue, status, count
+                );
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP mythic_relayer_last_relayed_nonce Highest relayed nonce\n\
+             # TYPE mythic_relayer_last_relayed_nonce gauge"
+        );
+        for (queue, q) in [("deposits", &s.deposits), ("burns", &s.burns)] {
+            if let Some(nonce) = q.last_relayed_nonce {
+                let _ = writeln!(out, "mythic_relayer_last_relayed_nonce{{queue=\"{}\"}} {}", queue, nonce);
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP mythic_relayer_lowest_unrelayed_nonce Lowest nonce not relayed yet\n\
+             # TYPE mythic_relayer_lowest_unrelayed_nonce gauge"
+        );
+        for (queue, q) in [("deposits", &s.deposits), ("burns", &s.burns)] {
+            let _ = writeln!(
+                out,
+                "mythic_relayer_lowest_unrelayed_nonce{{queue=\"{}\"}} {}",
+                queue, q.lowest_unrelayed_nonce
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP mythic_relayer_withdrawals_finalized_total Withdrawals finalized by the crank\n\
+             # TYPE mythic_relayer_withdrawals_finalized_total counter\n\
+             mythic_relayer_withdrawals_finalized_total {}\n\
+             # HELP mythic_relayer_withdrawals Tracked L1 withdrawals by state\n\
+             # TYPE mythic_relayer_withdrawals gauge",
+            s.withdrawals_finalized
+        );
+        for (state, count) in &s.withdrawals {
+            let _ = writeln!(out, "mythic_relayer_withdrawals{{state=\"{}\"}} {}", state, count);
+        }
+
+        let gauges = [
+            ("mythic_relayer_l1_deposit_nonce", "Next L1 deposit nonce (BridgeConfig.deposit_nonce)", "", s.l1_deposit_nonce),
+            ("mythic_relayer_deposit_nonce_lag", "L1 deposits not yet relayed", "", s.deposit_nonce_lag()),
+            ("mythic_relayer_balance_lamports", "Relayer fee payer balance", "{chain=\"l1\"}", s.relayer_l1_lamports),
+            ("mythic_relayer_balance_lamports", "", "{chain=\"l2\"}", s.relayer_l2_lamports),
+            ("mythic_relayer_l2_reserve_lamports", "bridge-l2 reserve PDA balance", "", s.l2_reserve_lamports),
+        ];
+        for (name, help, labels, value) in gauges {
+            if !help.is_empty() {
+                let _ = writeln!(out, "# HELP {} {}\n# TYPE {} gauge", name, help, name);
+            }
+            if let Some(value) = value {
+                let _ = writeln!(out, "{}{} {}", name, labels, value);
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP mythic_relayer_healthy 1 when /health passes\n\
+             # TYPE mythic_relayer_healthy gauge\n\
+             mythic_relayer_healthy {}",
+            u8::from(self.health_problems().is_empty())
+        );
+
+        out
+    }
+}
@@ -0,0 +1,830 @@
+// State-root poster mode (`mythic-relayer post-roots`).
+//
+// Runs the sequencer's side of settlement: every POST_INTERVAL_SLOTS
+// finalized L2 slots it posts a state root with the transaction batch those
+// slots carried, publishes the batch to L1 so the root can be finalized,
+// and finalizes each root once its challenge window has passed. The keypair
+// must be the settlement sequencer (or a member of the sequencer set). Each
+// post also carries the sequencer's ed25519 signature over the root, so two
+// conflicting roots for one slot are a DoubleSequencing proof anyone can
+// lift from L1. What a root commits to is defined under Withdrawal Tree in
+// main.rs.
+
+use super::*;
+
+/// The L2 transactions between two posted roots.
+struct L2Batch {
+    /// Last L2 slot covered; earlier than requested if the batch was cut
+    /// to fit the settlement size limit.
+    last_slot: u64,
+    blocks: Vec<L2BatchBlock>,
+    /// Borsh encoding of `blocks`, as published to L1.
+    bytes: Vec<u8>,
+    transaction_count: u32,
+    ai_attestation_count: u16,
+}
+
+#[derive(BorshSerialize)]
+struct PostStateRootParams {
+    l2_slot: u64,
+    state_root: [u8; 32],
+    transaction_count: u32,
+    transaction_batch_hash: [u8; 32],
+    ai_attestation_count: u16,
+    previous_state_root: [u8; 32],
+}
+
+#[derive(BorshSerialize)]
+struct FinalizeStateRootParams {
+    l2_slot: u64,
+}
+
+#[derive(BorshSerialize)]
+struct InitBatchDataParams {
+    l2_slot: u64,
+    total_len: u32,
+}
+
+#[derive(BorshSerialize)]
+struct WriteBatchChunkParams {
+    l2_slot: u64,
+    chunk_index: u16,
+    offset: u32,
+    data: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+struct SealBatchChunksParams {
+    l2_slot: u64,
+    first_chunk: u16,
+}
+
+/// Message a sequencer signs to commit to `state_root` at `l2_slot`, as in
+/// settlement: "MYTHIC_STATE_ROOT" || l2_chain_id || l2_slot (LE) || state_root
+fn state_root_message(l2_chain_id: &[u8; 16], l2_slot: u64, state_root: &[u8; 32]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(17 + 16 + 8 + 32);
+    message.extend_from_slice(b"MYTHIC_STATE_ROOT");
+    message.extend_from_slice(l2_chain_id);
+    message.extend_from_slice(&l2_slot.to_le_bytes());
+    message.extend_from_slice(state_root);
+    message
+}
+
+/// ed25519 precompile instruction carrying the sequencer's signature over
+/// `message`, with every offset pointing into the instruction itself (the
+/// layout ProveDoubleSequencing accepts). Returns the signature too.
+fn build_state_root_signature_ix(sequencer: &solana_sdk::signature::Keypair, message: &[u8]) -> (Instruction, Signature) {
+    const DATA_START: usize = 2 + 14; // count + padding, one offsets entry
+    let signature = sequencer.sign_message(message);
+    let pubkey_offset = DATA_START;
+    let signature_offset = pubkey_offset + 32;
+    let message_offset = signature_offset + 64;
+
+    let mut data = vec![1u8, 0];
+    for value in [
+        signature_offset as u16,
+        u16::MAX, // this instruction
+        pubkey_offset as u16,
+        u16::MAX,
+        message_offset as u16,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(sequencer.pubkey().as_ref());
+    data.extend_from_slice(signature.as_ref());
+    data.extend_from_slice(message);
+
+    let ix = Instruction {
+        program_id: solana_sdk::ed25519_program::id(),
+        accounts: vec![],
+        data,
+    };
+    (ix, signature)
+}
+
+fn build_post_state_root_ix(sequencer: &Pubkey, params: &PostStateRootParams) -> Instruction {
+    let config_pda = find_settlement_pda(&[SETTLEMENT_CONFIG_SEED]);
+    let state_root_pda = find_settlement_pda(&[STATE_ROOT_SEED, &params.l2_slot.to_le_bytes()]);
+    let set_pda = find_settlement_pda(&[SEQUENCER_SET_SEED]);
+
+    let mut data = vec![IX_POST_STATE_ROOT];
+    params.serialize(&mut data).unwrap();
+
+    // Account order must match process_post_state_root in settlement
+    Instruction {
+        program_id: Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap(),
+        accounts: vec![
+            AccountMeta::new(*sequencer, true),                  // 0. sequencer (signer, payer)
+            AccountMeta::new(config_pda, false),                 // 1. settlement_config PDA
+            AccountMeta::new(state_root_pda, false),             // 2. state_root PDA
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 3. system_program
+            AccountMeta::new(set_pda, false),                    // 4. sequencer_set PDA
+        ],
+        data,
+    }
+}
+
+fn build_finalize_state_root_ix(caller: &Pubkey, l2_slot: u64, last_finalized_slot: u64) -> Instruction {
+    let slot_bytes = l2_slot.to_le_bytes();
+    let config_pda = find_settlement_pda(&[SETTLEMENT_CONFIG_SEED]);
+    let state_root_pda = find_settlement_pda(&[STATE_ROOT_SEED, &slot_bytes]);
+    let batch_data_pda = find_settlement_pda(&[BATCH_DATA_SEED, &slot_bytes]);
+    let anchor_pda = find_settlement_pda(&[STATE_ROOT_SEED, &last_finalized_slot.to_le_bytes()]);
+
+    let mut data = vec![IX_FINALIZE_STATE_ROOT];
+    FinalizeStateRootParams { l2_slot }.serialize(&mut data).unwrap();
+
+    // Account order must match process_finalize_state_root in settlement
+    Instruction {
+        program_id: Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap(),
+        accounts: vec![
+            AccountMeta::new_readonly(*caller, false),           // 0. caller (anyone)
+            AccountMeta::new(config_pda, false),                 // 1. settlement_config PDA
+            AccountMeta::new(state_root_pda, false),             // 2. state_root PDA
+            AccountMeta::new_readonly(batch_data_pda, false),    // 3. batch_data PDA
+            AccountMeta::new_readonly(anchor_pda, false),        // 4. last finalized state_root PDA
+        ],
+        data,
+    }
+}
+
+fn build_init_batch_data_ix(sequencer: &Pubkey, l2_slot: u64, total_len: u32) -> Instruction {
+    let slot_bytes = l2_slot.to_le_bytes();
+    let state_root_pda = find_settlement_pda(&[STATE_ROOT_SEED, &slot_bytes]);
+    let batch_data_pda = find_settlement_pda(&[BATCH_DATA_SEED, &slot_bytes]);
+
+    let mut data = vec![IX_INIT_BATCH_DATA];
+    InitBatchDataParams { l2_slot, total_len }.serialize(&mut data).unwrap();
+
+    // Account order must match process_init_batch_data in settlement
+    Instruction {
+        program_id: Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap(),
+        accounts: vec![
+            AccountMeta::new(*sequencer, true),                  // 0. poster (signer, payer)
+            AccountMeta::new_readonly(state_root_pda, false),    // 1. state_root PDA
+            AccountMeta::new(batch_data_pda, false),             // 2. batch_data PDA
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 3. system_program
+        ],
+        data,
+    }
+}
+
+fn build_write_batch_chunk_ix(
+    sequencer: &Pubkey,
+    l2_slot: u64,
+    chunk_index: u16,
+    offset: u32,
+    bytes: Vec<u8>,
+) -> Instruction {
+    let slot_bytes = l2_slot.to_le_bytes();
+    let batch_data_pda = find_settlement_pda(&[BATCH_DATA_SEED, &slot_bytes]);
+    let chunk_pda = find_settlement_pda(&[BATCH_CHUNK_SEED, &slot_bytes, &chunk_index.to_le_bytes()]);
+
+    let mut data = vec![IX_WRITE_BATCH_CHUNK];
+    let params = WriteBatchChunkParams {
+        l2_slot,
+        chunk_index,
+        offset,
+        data: bytes,
+    };
+    params.serialize(&mut data).unwrap();
+
+    // Account order must match process_write_batch_chunk in settlement
+    Instruction {
+        program_id: Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap(),
+        accounts: vec![
+            AccountMeta::new(*sequencer, true),                  // 0. poster (signer, payer)
+            AccountMeta::new(batch_data_pda, false),             // 1. batch_data PDA
+            AccountMeta::new(chunk_pda, false),                  // 2. batch_chunk PDA
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 3. system_program
+        ],
+        data,
+    }
+}
+
+fn build_seal_batch_chunks_ix(l2_slot: u64, first_chunk: u16, count: u16) -> Instruction {
+    let slot_bytes = l2_slot.to_le_bytes();
+    let state_root_pda = find_settlement_pda(&[STATE_ROOT_SEED, &slot_bytes]);
+    let batch_data_pda = find_settlement_pda(&[BATCH_DATA_SEED, &slot_bytes]);
+
+    let mut data = vec![IX_SEAL_BATCH_CHUNKS];
+    SealBatchChunksParams { l2_slot, first_chunk }.serialize(&mut data).unwrap();
+
+    // Account order must match process_seal_batch_chunks in settlement
+    let mut accounts = vec![
+        AccountMeta::new_readonly(state_root_pda, false),        // 0. state_root PDA
+        AccountMeta::new(batch_data_pda, false),                 // 1. batch_data PDA
+    ];
+    accounts.extend((first_chunk..first_chunk + count).map(|i| {
+        let chunk_pda = find_settlement_pda(&[BATCH_CHUNK_SEED, &slot_bytes, &i.to_le_bytes()]);
+        AccountMeta::new_readonly(chunk_pda, false)              // 2.. batch_chunk PDAs
+    }));
+
+    Instruction {
+        program_id: Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap(),
+        accounts,
+        data,
+    }
+}
+
+pub async fn run_root_poster(config: RelayerConfig) {
+    let l1 = FailoverRpc::connect("L1", &config.l1_rpc_urls, CommitmentConfig::confirmed()).await;
+    // Only finalized L2 history is ever committed to
+    let l2 = FailoverRpc::connect("L2", &config.l2_rpc_urls, CommitmentConfig::finalized()).await;
+
+    let mut store = match RelayerStore::open(&config.store_path) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("[STORE] Failed to open {}: {}", config.store_path.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("=== Mythic L2 State Root Poster ===");
+    println!("L1 RPC:          {}", config.l1_rpc_urls.join(", "));
+    println!("L2 RPC:          {}", config.l2_rpc_urls.join(", "));
+    println!("Settlement:      {}", SETTLEMENT_PROGRAM_ID);
+    println!("Bridge L2:       {}", config.bridge_l2_program);
+    println!("Sequencer:       {}", config.relayer_keypair.pubkey());
+    println!("Post interval:   {} L2 slots", config.post_interval_slots);
+    println!("Store:           {}", config.store_path.display());
+    println!();
+
+    let mut tip: Option<RootChainTip> = None;
+    loop {
+        // Index first, so the withdrawal tree covers every posted slot
+        let mut failed = false;
+        match index_l2_withdrawals(&l2.client(), &config, &mut store).await {
+            Ok(()) => {
+                if let Err(e) = post_next_root(&l1.client(), &l2.client(), &config, &mut store, &mut tip).await {
+                    eprintln!("[POST] Error posting state root: {}", e);
+                    failed = true;
+                }
+            }
+            Err(e) => {
+                eprintln!("[POST] Error indexing L2: {}", e);
+                failed = true;
+            }
+        }
+        match crank_state_roots(&l1.client(), &l2.client(), &config, &mut store).await {
+            Ok(count) => {
+                if count > 0 {
+                    println!("[POST] Finalized {} state roots", count);
+                }
+            }
+            Err(e) => {
+                eprintln!("[POST] Error finalizing state roots: {}", e);
+                failed = true;
+            }
+        }
+        if failed {
+            l1.check().await;
+            l2.check().await;
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(config.poll_interval_ms)).await;
+    }
+}
+
+/// Post the next root once its last slot is finalized on L2. On
+/// SlotNotIncreasing or PreviousStateRootMismatch someone else extended
+/// (or rewound) the chain: the cached tip is dropped and re-read from
+/// SettlementConfig.
+async fn post_next_root(
+    l1_client: &RpcClient,
+    l2_client: &RpcClient,
+    config: &RelayerConfig,
+    store: &mut RelayerStore,
+    tip: &mut Option<RootChainTip>,
+) -> Result<(), String> {
+    let l2_finalized = l2_client
+        .get_slot()
+        .await
+        .map_err(|e| format!("Failed to read L2 slot: {}", e))?;
+
+    if tip.is_none() {
+        let fresh = read_root_chain_tip(l1_client).await?;
+        println!(
+            "[POST] Root chain at L2 slot {} (root {})",
+            fresh.live_slot(),
+            bytes_to_hex(&fresh.last_state_root)
+        );
+        if fresh.live_slot() != fresh.last_posted_slot {
+            println!(
+                "[POST] Roots through L2 slot {} were discarded; reposting from L2 slot {}",
+                fresh.last_posted_slot,
+                fresh.live_slot() + 1
+            );
+        }
+        // A root we can reproduce must match ours
+        if fresh.live_slot() != 0 && fresh.live_slot() <= l2_finalized {
+            let ours = withdrawal_state_root(store, fresh.live_slot())?;
+            if ours != fresh.last_state_root {
+                eprintln!(
+                    "[ALERT] Posted root for L2 slot {} is {}, but L2 gives {}",
+                    fresh.live_slot(),
+                    bytes_to_hex(&fresh.last_state_root),
+                    bytes_to_hex(&ours)
+                );
+            }
+        }
+        *tip = Some(fresh);
+    }
+    let current = tip.as_ref().unwrap();
+
+    let first_withdrawal_slot = store
+        .event::<L2Event>(Queue::Burns, 0)?
+        .map(|record| record.source_slot);
+    let Some((first_slot, target_slot)) = next_root_range(
+        current,
+        l2_finalized,
+        config.post_interval_slots,
+        first_withdrawal_slot,
+    ) else {
+        return Ok(());
+    };
+
+    let batch = build_l2_batch(l2_client, first_slot, target_slot).await?;
+    let l2_slot = batch.last_slot;
+    if l2_slot <= current.last_posted_slot {
+        // Only after a rewind: the discarded roots' PDAs still occupy these slots
+        return Err(format!(
+            "Batch from L2 slot {} was cut at {}, not past the discarded roots through {}",
+            first_slot, l2_slot, current.last_posted_slot
+        ));
+    }
+    let records = store.events_through::<L2Event>(Queue::Burns, l2_slot)?;
+    let state_root = batch_state_root(&records, &current.last_state_root, &batch)?;
+    let params = PostStateRootParams {
+        l2_slot,
+        state_root,
+        transaction_count: batch.transaction_count,
+        transaction_batch_hash: batch_data_hash(&batch.bytes),
+        ai_attestation_count: batch.ai_attestation_count,
+        previous_state_root: current.last_state_root,
+    };
+
+    // Publish the signed commitment alongside the root it commits to
+    let message = state_root_message(&current.l2_chain_id, l2_slot, &state_root);
+    let (signature_ix, commitment) = build_state_root_signature_ix(&config.relayer_keypair, &message);
+    let ix = build_post_state_root_ix(&config.relayer_keypair.pubkey(), &params);
+    let tx = sign_transaction(l1_client, config, &[signature_ix, ix]).await?;
+    let tx_sig = match l1_client.send_and_confirm_transaction(&tx).await {
+        Ok(tx_sig) => tx_sig,
+        Err(e) => {
+            let code = match e.get_transaction_error() {
+                Some(solana_sdk::transaction::TransactionError::InstructionError(
+                    _,
+                    solana_sdk::instruction::InstructionError::Custom(code),
+                )) => Some(code),
+                _ => None,
+            };
+            if matches!(
+                code,
+                Some(SETTLEMENT_ERR_SLOT_NOT_INCREASING | SETTLEMENT_ERR_PREVIOUS_ROOT_MISMATCH)
+            ) {
+                println!("[POST] Root chain moved past L2 slot {}; resyncing", current.last_posted_slot);
+                *tip = None;
+                return Ok(());
+            }
+            return Err(format!("PostStateRoot for L2 slot {}: {}", l2_slot, e));
+        }
+    };
+
+    store.record_posted_root(
+        l2_slot,
+        first_slot,
+        &bytes_to_hex(&state_root),
+        batch.transaction_count,
+        &tx_sig.to_string(),
+    )?;
+    println!(
+        "POSTED STATE ROOT: l2_slot={} root={} txs={} ai={} commitment={} tx={}",
+        l2_slot,
+        bytes_to_hex(&state_root),
+        batch.transaction_count,
+        batch.ai_attestation_count,
+        commitment,
+        tx_sig
+    );
+    *tip = Some(RootChainTip {
+        l2_chain_id: current.l2_chain_id,
+        last_posted_slot: l2_slot,
+        last_state_root: state_root,
+        last_finalized_slot: current.last_finalized_slot,
+        rewound_through_slot: current.rewound_through_slot,
+    });
+
+    // Publish now while the batch is in hand; the crank retries on failure
+    match publish_batch(l1_client, config, l2_slot, &batch).await {
+        Ok(()) => store.set_batch_available(l2_slot)?,
+        Err(e) => eprintln!("[POST] Publishing batch for L2 slot {} failed: {}", l2_slot, e),
+    }
+    Ok(())
+}
+
+/// The L2 slots the next root covers, once the last is finalized. The
+/// first root covers the newest full interval, reaching back to the first
+/// L2 withdrawal if there is one, so that the zero genesis root really is
+/// the withdrawal tree before its batch. After a rewind the batch starts
+/// right after the last finalized root but must end past the discarded
+/// ones, whose slots are taken.
+fn next_root_range(
+    tip: &RootChainTip,
+    l2_finalized: u64,
+    interval: u64,
+    first_withdrawal_slot: Option<u64>,
+) -> Option<(u64, u64)> {
+    let live_slot = tip.live_slot();
+    let (first_slot, target_slot) = if live_slot == 0 {
+        let target = l2_finalized / interval * interval;
+        let first = target.saturating_sub(interval) + 1;
+        (first_withdrawal_slot.map_or(first, |slot| slot.min(first)), target)
+    } else {
+        (live_slot + 1, tip.last_posted_slot + interval)
+    };
+    (target_slot > tip.last_posted_slot && target_slot <= l2_finalized).then_some((first_slot, target_slot))
+}
+
+/// The root to post for `batch`, checked against what a dispute over it
+/// would replay: step 0 must be the previous root and the last step this
+/// root, or an honest challenger could never agree with us. `records` are
+/// the indexed L2 withdrawals through the batch's last slot.
+fn batch_state_root(
+    records: &[EventRecord<L2Event>],
+    previous_state_root: &[u8; 32],
+    batch: &L2Batch,
+) -> Result<[u8; 32], String> {
+    let state_root = withdrawal_tree_root(records)?;
+    let (genesis, last_step) = if batch.blocks.is_empty() {
+        // Nothing ran, so the root cannot have moved
+        (state_root, state_root)
+    } else {
+        (
+            batch_step_root(records, &batch.blocks, 0)?,
+            batch_step_root(records, &batch.blocks, batch.transaction_count)?,
+        )
+    };
+    if genesis != *previous_state_root {
+        return Err(format!(
+            "Root chain tip {} is not the withdrawal root before this batch ({})",
+            bytes_to_hex(previous_state_root),
+            bytes_to_hex(&genesis)
+        ));
+    }
+    if last_step != state_root {
+        return Err(format!(
+            "L2 withdrawals through slot {} are not all in its batch",
+            batch.last_slot
+        ));
+    }
+    Ok(state_root)
+}
+
+/// Every L2 block from `first_slot` through `last_slot`, stopping early at
+/// a block boundary if the batch would exceed MAX_BATCH_CHUNKS.
+async fn build_l2_batch(l2_client: &RpcClient, first_slot: u64, last_slot: u64) -> Result<L2Batch, String> {
+    let max_len = BATCH_CHUNK_SIZE * MAX_BATCH_CHUNKS;
+    let slots = l2_client
+        .get_blocks(first_slot, Some(last_slot))
+        .await
+        .map_err(|e| format!("Failed to list L2 blocks {}..={}: {}", first_slot, last_slot, e))?;
+
+    let mut blocks = Vec::new();
+    let mut len = 4; // Borsh Vec length prefix
+    let mut batch_last_slot = last_slot;
+    let mut transaction_count = 0u32;
+    let mut ai_attestation_count = 0u16;
+    for slot in slots {
+        let block = l2_client
+            .get_block_with_config(
+                slot,
+                solana_client::rpc_config::RpcBlockConfig {
+                    encoding: Some(solana_transaction_status::UiTransactionEncoding::Base64),
+                    transaction_details: Some(solana_transaction_status::TransactionDetails::Full),
+                    rewards: Some(false),
+                    commitment: Some(l2_client.commitment()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await
+            .map_err(|e| format!("Failed to get L2 block {}: {}", slot, e))?;
+
+        let mut transactions = Vec::new();
+        let mut ai_results = 0usize;
+        for tx in block.transactions.unwrap_or_default() {
+            let decoded = tx
+                .transaction
+                .decode()
+                .ok_or_else(|| format!("Undecodable transaction in L2 block {}", slot))?;
+            transactions.push(
+                bincode::serialize(&decoded)
+                    .map_err(|e| format!("Failed to encode transaction in L2 block {}: {}", slot, e))?,
+            );
+            if let Some(solana_transaction_status::option_serializer::OptionSerializer::Some(logs)) =
+                tx.meta.map(|meta| meta.log_messages)
+            {
+                ai_results += logs.iter().filter(|l| l.contains(AI_RESULT_EVENT_PREFIX)).count();
+            }
+        }
+        let blockhash = solana_sdk::hash::Hash::from_str(&block.blockhash)
+            .map_err(|e| format!("Invalid blockhash for L2 block {}: {}", slot, e))?;
+        let entry = L2BatchBlock {
+            slot,
+            blockhash: blockhash.to_bytes(),
+            transactions,
+        };
+
+        let entry_len = borsh::to_vec(&entry).unwrap().len();
+        if len + entry_len > max_len {
+            if blocks.is_empty() {
+                return Err(format!("L2 block {} alone exceeds the batch size limit", slot));
+            }
+            batch_last_slot = slot - 1;
+            break;
+        }
+        len += entry_len;
+        transaction_count = transaction_count.saturating_add(entry.transactions.len() as u32);
+        ai_attestation_count =
+            ai_attestation_count.saturating_add(u16::try_from(ai_results).unwrap_or(u16::MAX));
+        blocks.push(entry);
+    }
+
+    Ok(L2Batch {
+        last_slot: batch_last_slot,
+        bytes: borsh::to_vec(&blocks).unwrap(),
+        blocks,
+        transaction_count,
+        ai_attestation_count,
+    })
+}
+
+/// Write the batch into its chunk accounts and seal them, skipping chunks
+/// already on chain. Roots without transactions need no batch.
+async fn publish_batch(
+    l1_client: &RpcClient,
+    config: &RelayerConfig,
+    l2_slot: u64,
+    batch: &L2Batch,
+) -> Result<(), String> {
+    if batch.transaction_count == 0 {
+        return Ok(());
+    }
+    let sequencer = config.relayer_keypair.pubkey();
+    let slot_bytes = l2_slot.to_le_bytes();
+
+    let header_pda = find_settlement_pda(&[BATCH_DATA_SEED, &slot_bytes]);
+    if !account_exists(l1_client, &header_pda).await? {
+        let ix = build_init_batch_data_ix(&sequencer, l2_slot, batch.bytes.len() as u32);
+        send_with_retry(l1_client, config, &[ix]).await?;
+    }
+    let header = l1_client
+        .get_account_data(&header_pda)
+        .await
+        .map_err(|e| format!("Failed to read batch header: {}", e))?;
+    if header.get(BATCH_AVAILABLE_OFFSET) == Some(&1) {
+        return Ok(());
+    }
+
+    let chunks: Vec<&[u8]> = batch.bytes.chunks(BATCH_CHUNK_SIZE).collect();
+    for (chunk_index, chunk) in chunks.iter().enumerate() {
+        let chunk_index = chunk_index as u16;
+        let chunk_pda = find_settlement_pda(&[BATCH_CHUNK_SEED, &slot_bytes, &chunk_index.to_le_bytes()]);
+        let on_chain = l1_client
+            .get_account_with_commitment(&chunk_pda, l1_client.commitment())
+            .await
+            .map_err(|e| format!("Failed to read batch chunk {}: {}", chunk_index, e))?
+            .value;
+        if on_chain.is_some_and(|account| account.data == *chunk) {
+            continue;
+        }
+        for (piece_index, piece) in chunk.chunks(BATCH_WRITE_BYTES).enumerate() {
+            let ix = build_write_batch_chunk_ix(
+                &sequencer,
+                l2_slot,
+                chunk_index,
+                (piece_index * BATCH_WRITE_BYTES) as u32,
+                piece.to_vec(),
+            );
+            send_with_retry(l1_client, config, &[ix]).await?;
+        }
+    }
+
+    for first in (0..chunks.len()).step_by(SEAL_CHUNKS_PER_TX) {
+        let count = SEAL_CHUNKS_PER_TX.min(chunks.len() - first);
+        let ix = build_seal_batch_chunks_ix(l2_slot, first as u16, count as u16);
+        send_with_retry(l1_client, config, &[ix]).await?;
+    }
+    println!(
+        "PUBLISHED BATCH: l2_slot={} bytes={} chunks={}",
+        l2_slot,
+        batch.bytes.len(),
+        chunks.len()
+    );
+    Ok(())
+}
+
+/// Bring every unsettled root we posted up to date: publish batches still
+/// missing, finalize roots whose challenge window has passed, and alert on
+/// challenges. Returns how many roots were finalized.
+async fn crank_state_roots(
+    l1_client: &RpcClient,
+    l2_client: &RpcClient,
+    config: &RelayerConfig,
+    store: &mut RelayerStore,
+) -> Result<u64, String> {
+    let roots = store.unsettled_roots()?;
+    if roots.is_empty() {
+        return Ok(0);
+    }
+    let l1_slot = l1_client
+        .get_slot()
+        .await
+        .map_err(|e| format!("Failed to read L1 slot: {}", e))?;
+
+    let mut chain = read_root_chain_tip(l1_client).await?;
+    let mut finalized = 0u64;
+    for root in roots {
+        let l2_slot = root.l2_slot;
+        let data = l1_client
+            .get_account_data(&find_settlement_pda(&[STATE_ROOT_SEED, &l2_slot.to_le_bytes()]))
+            .await
+            .map_err(|e| format!("Failed to read state root for L2 slot {}: {}", l2_slot, e))?;
+        let fields = data
+            .get(STATE_ROOT_BATCH_HASH_OFFSET..STATE_ROOT_DEADLINE_OFFSET + 8 + 1)
+            .ok_or_else(|| format!("StateRootAccount for L2 slot {} too short", l2_slot))?;
+        let batch_hash: [u8; 32] = fields[..32].try_into().unwrap();
+        let deadline_at = STATE_ROOT_DEADLINE_OFFSET - STATE_ROOT_BATCH_HASH_OFFSET;
+        let challenge_deadline = i64::from_le_bytes(fields[deadline_at..deadline_at + 8].try_into().unwrap());
+        let status = SettlementRootStatus::deserialize(&mut &fields[deadline_at + 8..])
+            .map_err(|e| format!("Unreadable status for L2 slot {}: {}", l2_slot, e))?;
+
+        match status {
+            SettlementRootStatus::Posted => {}
+            SettlementRootStatus::Challenged => {
+                if root.state != RootState::Challenged {
+                    eprintln!(
+                        "[ALERT] State root for L2 slot {} was challenged; it cannot finalize until the dispute resolves",
+                        l2_slot
+                    );
+                    store.set_root_state(l2_slot, RootState::Challenged, None)?;
+                }
+                continue;
+            }
+            SettlementRootStatus::Finalized => {
+                store.set_root_state(l2_slot, RootState::Finalized, None)?;
+                continue;
+            }
+            SettlementRootStatus::Invalidated => {
+                eprintln!("[ALERT] State root for L2 slot {} was invalidated", l2_slot);
+                store.set_root_state(l2_slot, RootState::Invalidated, None)?;
+                continue;
+            }
+        }
+
+        // A discarded root needs no batch; finalizing only marks it Invalidated
+        let discarded = chain.is_discarded(l2_slot);
+        if !root.batch_available && !discarded {
+            let batch = build_l2_batch(l2_client, root.first_slot, l2_slot).await?;
+            if batch.last_slot != l2_slot
+                || batch_data_hash(&batch.bytes) != batch_hash
+            {
+                eprintln!(
+                    "[ALERT] L2 blocks {}..={} no longer hash to the batch posted for L2 slot {}",
+                    root.first_slot, l2_slot, l2_slot
+                );
+                continue;
+            }
+            match publish_batch(l1_client, config, l2_slot, &batch).await {
+                Ok(()) => store.set_batch_available(l2_slot)?,
+                Err(e) => {
+                    eprintln!("[POST] Publishing batch for L2 slot {} failed: {}", l2_slot, e);
+                    continue;
+                }
+            }
+        }
+
+        if (l1_slot as i64) <= challenge_deadline {
+            continue;
+        }
+        let ix = build_finalize_state_root_ix(&config.relayer_keypair.pubkey(), l2_slot, chain.last_finalized_slot);
+        match send_with_retry(l1_client, config, &[ix]).await {
+            Ok(tx_sig) if discarded => {
+                eprintln!("[ALERT] State root for L2 slot {} was discarded by a rewind", l2_slot);
+                store.set_root_state(l2_slot, RootState::Invalidated, Some(&tx_sig.to_string()))?;
+            }
+            Ok(tx_sig) => {
+                println!("FINALIZED STATE ROOT: l2_slot={} tx={}", l2_slot, tx_sig);
+                store.set_root_state(l2_slot, RootState::Finalized, Some(&tx_sig.to_string()))?;
+                chain.last_finalized_slot = l2_slot;
+                finalized += 1;
+            }
+            Err(e) => {
+                eprintln!("[POST] Failed to finalize state root for L2 slot {}: {}", l2_slot, e);
+            }
+        }
+    }
+
+    Ok(finalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{batch_tx, burn_record};
+
+    fn tip(last_posted_slot: u64, last_finalized_slot: u64, rewound_through_slot: u64) -> RootChainTip {
+        RootChainTip {
+            l2_chain_id: [7u8; 16],
+            last_posted_slot,
+            last_state_root: [0u8; 32],
+            last_finalized_slot,
+            rewound_through_slot,
+        }
+    }
+
+    #[test]
+    fn test_first_root_reaches_back_to_the_first_withdrawal() {
+        assert_eq!(next_root_range(&tip(0, 0, 0), 250, 100, None), Some((101, 200)));
+        assert_eq!(next_root_range(&tip(0, 0, 0), 250, 100, Some(40)), Some((40, 200)));
+        assert_eq!(next_root_range(&tip(0, 0, 0), 250, 100, Some(230)), Some((101, 200)));
+        assert_eq!(next_root_range(&tip(0, 0, 0), 99, 100, None), None);
+
+        assert_eq!(next_root_range(&tip(200, 0, 0), 300, 100, Some(40)), Some((201, 300)));
+        assert_eq!(next_root_range(&tip(200, 0, 0), 299, 100, None), None);
+    }
+
+    #[test]
+    fn test_rewound_chain_reposts_from_the_last_finalized_root() {
+        // Roots through 400 discarded, 200 finalized
+        let rewound = tip(400, 200, 400);
+        assert_eq!(rewound.live_slot(), 200);
+        assert!(rewound.is_discarded(300));
+        assert_eq!(next_root_range(&rewound, 500, 100, None), Some((201, 500)));
+        assert_eq!(next_root_range(&rewound, 499, 100, None), None);
+
+        // Nothing finalized yet: back to genesis, past the discarded slots
+        let rewound = tip(200, 0, 200);
+        assert_eq!(rewound.live_slot(), 0);
+        assert_eq!(next_root_range(&rewound, 250, 100, Some(40)), None);
+        assert_eq!(next_root_range(&rewound, 350, 100, Some(40)), Some((40, 300)));
+
+        // A root posted since the rewind is live again
+        let reposted = tip(500, 200, 400);
+        assert_eq!(reposted.live_slot(), 500);
+        assert!(!reposted.is_discarded(500));
+    }
+
+    #[test]
+    fn test_posted_root_signature_verifies_in_the_precompile() {
+        let sequencer = solana_sdk::signature::Keypair::new();
+        let message = state_root_message(&[7u8; 16], 300, &[9u8; 32]);
+        let (ix, signature) = build_state_root_signature_ix(&sequencer, &message);
+
+        assert_eq!(ix.program_id, solana_sdk::ed25519_program::id());
+        assert!(signature.verify(sequencer.pubkey().as_ref(), &message));
+        solana_sdk::ed25519_instruction::verify(
+            &ix.data,
+            &[&ix.data],
+            &solana_sdk::feature_set::FeatureSet::all_enabled(),
+        )
+        .unwrap();
+        // The signed message is the one settlement rebuilds for the root
+        assert!(ix.data.ends_with(&message));
+    }
+
+    fn batch(blocks: Vec<L2BatchBlock>) -> L2Batch {
+        let transaction_count = blocks.iter().map(|block| block.transactions.len() as u32).sum();
+        L2Batch {
+            last_slot: blocks.last().map_or(20, |block| block.slot),
+            bytes: borsh::to_vec(&blocks).unwrap(),
+            blocks,
+            transaction_count,
+            ai_attestation_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_batch_state_root_matches_both_ends_of_the_dispute_game() {
+        let (a, a_bytes) = batch_tx();
+        let (_, b_bytes) = batch_tx();
+        let records = vec![burn_record(0, "earlier", 5), burn_record(1, &a, 10)];
+        let previous = withdrawal_tree_root(&records[..1]).unwrap();
+        let full = batch(vec![L2BatchBlock { slot: 10, blockhash: [0; 32], transactions: vec![a_bytes, b_bytes.clone()] }]);
+
+        assert_eq!(batch_state_root(&records, &previous, &full).unwrap(), withdrawal_tree_root(&records).unwrap());
+        // A tip that is not the tree before the batch would lose step 0
+        assert!(batch_state_root(&records, &[0; 32], &full).is_err());
+
+        // A withdrawal from a transaction missing from the batch
+        let partial = batch(vec![L2BatchBlock { slot: 10, blockhash: [0; 32], transactions: vec![b_bytes] }]);
+        assert!(batch_state_root(&records, &previous, &partial).is_err());
+
+        // With no blocks the root must stay where it was
+        let empty = batch(Vec::new());
+        assert_eq!(batch_state_root(&records[..1], &previous, &empty).unwrap(), previous);
+        assert!(batch_state_root(&records, &previous, &empty).is_err());
+    }
+}
@@ -0,0 +1,1461 @@
+// Durable relayer state in an embedded SQLite database.
+//
+// Every bridge event the relayer observes is a row keyed by its nonce, with
+// the source transaction, status, attempt count and timestamps. Each scanned
+// transaction (its events plus the scan cursor) and each status change is
+// committed atomically, so a crash or full disk can never move the cursor
+// past an event that was not recorded.
+//
+// Events enter as `Seen` and only become `Pending` (relayable) once their
+// source transaction satisfies the confirmation policy for that direction.
+//
+// Initiated L1 withdrawals are tracked separately by nonce until they are
+// finalized or cancelled. In watchtower mode, every WithdrawalRequest seen
+// on L1 is kept with the verdict of checking it against L2, and every
+// settlement root it disputed is kept until the dispute game ends. In
+// root-poster mode, every settlement state root we posted is kept until it is finalized
+// or invalidated.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Which side of the bridge an event is relayed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Queue {
+    /// L1 deposits and L1→L2 messages, keyed by the L1 deposit nonce.
+    Deposits,
+    /// L2 burns and L2→L1 messages, keyed by the L2 withdraw nonce.
+    Burns,
+}
+
+impl Queue {
+    fn table(self) -> &'static str {
+        match self {
+            Queue::Deposits => "deposits",
+            Queue::Burns => "burns",
+        }
+    }
+
+    fn cursor_key(self) -> &'static str {
+        match self {
+            Queue::Deposits => "last_deposit_signature",
+            Queue::Burns => "last_burn_signature",
+        }
+    }
+
+    /// Highest nonce the pre-store relayer had relayed, kept from its
+    /// `last_*_nonce` high-water mark.
+    fn legacy_nonce_key(self) -> &'static str {
+        match self {
+            Queue::Deposits => "legacy_last_deposit_nonce",
+            Queue::Burns => "legacy_last_burn_nonce",
+        }
+    }
+}
+
+/// One queue's progress in the legacy JSON state file.
+#[derive(Debug, Clone, Default)]
+pub struct LegacyProgress {
+    /// Newest source-chain signature scanned.
+    pub signature: Option<String>,
+    /// Highest nonce relayed (`last_deposit_nonce` / `last_burn_nonce`).
+    pub last_nonce: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventStatus {
+    /// Observed, but the source transaction is not deep enough to relay.
+    Seen,
+    /// Confirmed deeply enough; waiting to be relayed.
+    Pending,
+    Relayed,
+    Failed,
+    /// Failed too many times; no longer retried until re-queued by backfill.
+    DeadLettered,
+    /// The source transaction vanished from the chain (fork). Never relayed
+    /// unless it reappears.
+    Orphaned,
+}
+
+impl EventStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            EventStatus::Seen => "seen",
+            EventStatus::Pending => "pending",
+            EventStatus::Relayed => "relayed",
+            EventStatus::Failed => "failed",
+            EventStatus::DeadLettered => "dead_lettered",
+            EventStatus::Orphaned => "orphaned",
+        }
+    }
+
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "seen" => Ok(EventStatus::Seen),
+            "pending" => Ok(EventStatus::Pending),
+            "relayed" => Ok(EventStatus::Relayed),
+            "failed" => Ok(EventStatus::Failed),
+            "dead_lettered" => Ok(EventStatus::DeadLettered),
+            "orphaned" => Ok(EventStatus::Orphaned),
+            other => Err(format!("Unknown event status in store: {}", other)),
+        }
+    }
+}
+
+/// Where a tracked L1 WithdrawalRequest stands, as last read from chain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WithdrawalState {
+    /// Not initiated yet, or Pending in its challenge period.
+    Open,
+    Challenged,
+    Finalized,
+    Cancelled,
+}
+
+impl WithdrawalState {
+    fn as_str(self) -> &'static str {
+        match self {
+            WithdrawalState::Open => "open",
+            WithdrawalState::Challenged => "challenged",
+            WithdrawalState::Finalized => "finalized",
+            WithdrawalState::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "open" => Ok(WithdrawalState::Open),
+            "challenged" => Ok(WithdrawalState::Challenged),
+            "finalized" => Ok(WithdrawalState::Finalized),
+            "cancelled" => Ok(WithdrawalState::Cancelled),
+            other => Err(format!("Unknown withdrawal state in store: {}", other)),
+        }
+    }
+}
+
+/// Outcome of checking an L1 withdrawal against L2 (watchtower mode).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WatchVerdict {
+    /// Not checked yet, or no L2 evidence either way so far.
+    Unverified,
+    /// Matches its L2 event.
+    Valid,
+    /// Did not match L2; a challenge was filed.
+    Challenged,
+    /// The challenge period ended before it could be verified.
+    Expired,
+    /// Matches its L2 event, but was proven against a settlement root that
+    /// disagrees with L2; the root was disputed instead.
+    RootDisputed,
+}
+
+impl WatchVerdict {
+    fn as_str(self) -> &'static str {
+        match self {
+            WatchVerdict::Unverified => "unverified",
+            WatchVerdict::Valid => "valid",
+            WatchVerdict::Challenged => "challenged",
+            WatchVerdict::Expired => "expired",
+            WatchVerdict::RootDisputed => "root_disputed",
+        }
+    }
+}
+
+/// Outcome of a settlement dispute game the watchtower started.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisputeState {
+    /// The bisection game is still being played.
+    Open,
+    /// The root was invalidated.
+    Won,
+    /// The root stood.
+    Lost,
+}
+
+impl DisputeState {
+    fn as_str(self) -> &'static str {
+        match self {
+            DisputeState::Open => "open",
+            DisputeState::Won => "won",
+            DisputeState::Lost => "lost",
+        }
+    }
+}
+
+/// Settlement status of a state root we posted, as last read from L1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RootState {
+    Posted,
+    Challenged,
+    Finalized,
+    Invalidated,
+}
+
+impl RootState {
+    fn as_str(self) -> &'static str {
+        match self {
+            RootState::Posted => "posted",
+            RootState::Challenged => "challenged",
+            RootState::Finalized => "finalized",
+            RootState::Invalidated => "invalidated",
+        }
+    }
+
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "posted" => Ok(RootState::Posted),
+            "challenged" => Ok(RootState::Challenged),
+            "finalized" => Ok(RootState::Finalized),
+            "invalidated" => Ok(RootState::Invalidated),
+            other => Err(format!("Unknown root state in store: {}", other)),
+        }
+    }
+}
+
+/// A settlement state root posted by the root poster.
+#[derive(Debug, Clone)]
+pub struct PostedRoot {
+    pub l2_slot: u64,
+    /// First L2 slot of the transaction batch; the batch ends at `l2_slot`.
+    pub first_slot: u64,
+    pub state: RootState,
+    /// Whether the batch has been published and sealed on L1.
+    pub batch_available: bool,
+}
+
+/// An L1 withdrawal seen by the watchtower and not yet settled either way.
+#[derive(Debug, Clone)]
+pub struct WatchedWithdrawal<T> {
+    pub nonce: u64,
+    pub l1_signature: String,
+    pub event: T,
+}
+
+/// An L1 withdrawal the finalize crank is watching.
+#[derive(Debug, Clone)]
+pub struct TrackedWithdrawal {
+    pub nonce: u64,
+    pub state: WithdrawalState,
+    pub challenge_deadline: Option<i64>,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub updated_at: i64,
+}
+
+/// One observed bridge event.
+#[derive(Debug, Clone)]
+pub struct EventRecord<T> {
+    pub nonce: u64,
+    /// Transaction on the source chain that emitted the event.
+    pub source_signature: String,
+    /// Slot the source transaction was last seen in.
+    pub source_slot: u64,
+    pub event: T,
+    pub status: EventStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    /// Transaction on the destination chain that relayed it, if we sent one.
+    pub relay_signature: Option<String>,
+    pub observed_at: i64,
+    pub updated_at: i64,
+}
+
+pub struct RelayerStore {
+    conn: Connection,
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS cursors (
+        name  TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS deposits (
+        nonce            INTEGER PRIMARY KEY,
+        source_signature TEXT NOT NULL,
+        source_slot      INTEGER NOT NULL DEFAULT 0,
+        event            TEXT NOT NULL,
+        status           TEXT NOT NULL,
+        attempts         INTEGER NOT NULL DEFAULT 0,
+        last_error       TEXT,
+        relay_signature  TEXT,
+        observed_at      INTEGER NOT NULL,
+        updated_at       INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS burns (
+        nonce            INTEGER PRIMARY KEY,
+        source_signature TEXT NOT NULL,
+        source_slot      INTEGER NOT NULL DEFAULT 0,
+        event            TEXT NOT NULL,
+        status           TEXT NOT NULL,
+        attempts         INTEGER NOT NULL DEFAULT 0,
+        last_error       TEXT,
+        relay_signature  TEXT,
+        observed_at      INTEGER NOT NULL,
+        updated_at       INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS withdrawals (
+        nonce              INTEGER PRIMARY KEY,
+        state              TEXT NOT NULL,
+        challenge_deadline INTEGER,
+        attempts           INTEGER NOT NULL DEFAULT 0,
+        last_error         TEXT,
+        finalize_signature TEXT,
+        updated_at         INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS watched_withdrawals (
+        nonce               INTEGER PRIMARY KEY,
+        l1_signature        TEXT NOT NULL,
+        event               TEXT NOT NULL,
+        verdict             TEXT NOT NULL,
+        detail              TEXT,
+        challenge_signature TEXT,
+        observed_at         INTEGER NOT NULL,
+        updated_at          INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS posted_roots (
+        l2_slot            INTEGER PRIMARY KEY,
+        first_slot         INTEGER NOT NULL,
+        state_root         TEXT NOT NULL,
+        transaction_count  INTEGER NOT NULL,
+        state              TEXT NOT NULL,
+        batch_available    INTEGER NOT NULL DEFAULT 0,
+        post_signature     TEXT NOT NULL,
+        finalize_signature TEXT,
+        updated_at         INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS root_disputes (
+        l2_slot             INTEGER PRIMARY KEY,
+        detail              TEXT NOT NULL,
+        challenge_signature TEXT,
+        state               TEXT NOT NULL,
+        updated_at          INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS deposits_status ON deposits(status);
+    CREATE INDEX IF NOT EXISTS burns_status ON burns(status);
+    CREATE INDEX IF NOT EXISTS withdrawals_state ON withdrawals(state);
+";
+
+/// Current unix time in seconds.
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn db_err(e: rusqlite::Error) -> String {
+    format!("Store error: {}", e)
+}
+
+const RECORD_COLUMNS: &str =
+    "nonce, source_signature, source_slot, event, status, attempts, last_error, relay_signature, observed_at, updated_at";
+
+impl RelayerStore {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(path).map_err(db_err)?;
+        // WAL + FULL sync: a committed event survives a crash or power loss.
+        conn.pragma_update(None, "journal_mode", "WAL").map_err(db_err)?;
+        conn.pragma_update(None, "synchronous", "FULL").map_err(db_err)?;
+        Self::with_schema(conn)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self, String> {
+        Self::with_schema(Connection::open_in_memory().map_err(db_err)?)
+    }
+
+    fn with_schema(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(SCHEMA).map_err(db_err)?;
+        let store = Self { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    /// Bring stores created by older relayers up to the current schema.
+    fn migrate(&self) -> Result<(), String> {
+        for queue in [Queue::Deposits, Queue::Burns] {
+            let has_slot: bool = self
+                .conn
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = 'source_slot'",
+                    params![queue.table()],
+                    |row| row.get(0),
+                )
+                .map_err(db_err)?;
+            if !has_slot {
+                self.conn
+                    .execute_batch(&format!(
+                        "ALTER TABLE {} ADD COLUMN source_slot INTEGER NOT NULL DEFAULT 0",
+                        queue.table()
+                    ))
+                    .map_err(db_err)?;
+            }
+        }
+
+        // Withdrawals relayed before the crank existed still need finalizing.
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO withdrawals (nonce, state, updated_at)
+                 SELECT nonce, ?1, ?2 FROM burns
+                 WHERE status = ?3 AND json_type(event, '$.Burn') IS NOT NULL",
+                params![
+                    WithdrawalState::Open.as_str(),
+                    now(),
+                    EventStatus::Relayed.as_str()
+                ],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    /// True until the first cursor or event is written.
+    pub fn is_empty(&self) -> Result<bool, String> {
+        let rows: i64 = self
+            .conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM cursors)
+                      + (SELECT COUNT(*) FROM deposits)
+                      + (SELECT COUNT(*) FROM burns)",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(db_err)?;
+        Ok(rows == 0)
+    }
+
+    /// Newest source-chain signature whose events are recorded.
+    pub fn cursor(&self, queue: Queue) -> Result<Option<String>, String> {
+        self.conn
+            .query_row(
+                "SELECT value FROM cursors WHERE name = ?1",
+                params![queue.cursor_key()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_err)
+    }
+
+    /// Record the events of one scanned transaction as `Seen` and advance
+    /// the cursor to it, in a single transaction.
+    ///
+    /// A nonce already held by a different transaction that never qualified
+    /// (`Seen` or `Orphaned`) was taken over on a new fork: the old event is
+    /// replaced, and its (nonce, signature) is returned so it can be alerted
+    /// on. Any other existing nonce is kept as it is.
+    pub fn record_scanned<T: Serialize>(
+        &mut self,
+        queue: Queue,
+        signature: &str,
+        slot: u64,
+        events: &[(u64, T)],
+    ) -> Result<Vec<(u64, String)>, String> {
+        let tx = self.conn.transaction().map_err(db_err)?;
+        let ts = now();
+        let mut replaced = Vec::new();
+        for (nonce, event) in events {
+            let existing: Option<(String, String)> = tx
+                .query_row(
+                    &format!("SELECT source_signature, status FROM {} WHERE nonce = ?1", queue.table()),
+                    params![*nonce as i64],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .map_err(db_err)?;
+            if let Some((old_signature, status)) = existing {
+                let unqualified = matches!(
+                    EventStatus::parse(&status)?,
+                    EventStatus::Seen | EventStatus::Orphaned
+                );
+                if old_signature == signature || !unqualified {
+                    continue;
+                }
+                replaced.push((*nonce, old_signature));
+            }
+
+            let json = serde_json::to_string(event).map_err(|e| e.to_string())?;
+            tx.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO {} (nonce, source_signature, source_slot, event, status, observed_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+                    queue.table()
+                ),
+                params![*nonce as i64, signature, slot as i64, json, EventStatus::Seen.as_str(), ts],
+            )
+            .map_err(db_err)?;
+        }
+        tx.execute(
+            "INSERT INTO cursors (name, value) VALUES (?1, ?2)
+             ON CONFLICT(name) DO UPDATE SET value = excluded.value",
+            params![queue.cursor_key(), signature],
+        )
+        .map_err(db_err)?;
+        tx.commit().map_err(db_err)?;
+        Ok(replaced)
+    }
+
+    /// Record events found outside the normal scan (log subscriptions,
+    /// backfill, replay) as `Seen`, leaving nonces already in the store and
+    /// the cursor alone. Returns how many were new.
+    pub fn insert_missing<T: Serialize>(
+        &mut self,
+        queue: Queue,
+        signature: &str,
+        slot: u64,
+        events: &[(u64, T)],
+    ) -> Result<u64, String> {
+        let tx = self.conn.transaction().map_err(db_err)?;
+        let ts = now();
+        let mut inserted = 0u64;
+        for (nonce, event) in events {
+            let json = serde_json::to_string(event).map_err(|e| e.to_string())?;
+            inserted += tx
+                .execute(
+                    &format!(
+                        "INSERT OR IGNORE INTO {} (nonce, source_signature, source_slot, event, status, observed_at, updated_at)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+                        queue.table()
+                    ),
+                    params![*nonce as i64, signature, slot as i64, json, EventStatus::Seen.as_str(), ts],
+                )
+                .map_err(db_err)? as u64;
+        }
+        tx.commit().map_err(db_err)?;
+        Ok(inserted)
+    }
+
+    /// Put relayed, failed and dead-lettered events from `from_nonce` on back
+    /// to `Pending` so they are relayed again; relaying is idempotent on both
+    /// bridges. Dead-lettered events get a fresh attempt budget. Returns how
+    /// many were re-queued.
+    pub fn requeue_from(&mut self, queue: Queue, from_nonce: u64) -> Result<u64, String> {
+        self.conn
+            .execute(
+                &format!(
+                    "UPDATE {} SET status = ?1, last_error = NULL, updated_at = ?2,
+                         attempts = CASE WHEN status = ?6 THEN 0 ELSE attempts END
+                     WHERE nonce >= ?3 AND status IN (?4, ?5, ?6)",
+                    queue.table()
+                ),
+                params![
+                    EventStatus::Pending.as_str(),
+                    now(),
+                    from_nonce as i64,
+                    EventStatus::Relayed.as_str(),
+                    EventStatus::Failed.as_str(),
+                    EventStatus::DeadLettered.as_str(),
+                ],
+            )
+            .map(|n| n as u64)
+            .map_err(db_err)
+    }
+
+    /// Import deposit records and each queue's scan cursor and relayed
+    /// high-water mark in one transaction, e.g. from the legacy JSON state
+    /// file. Existing rows are left untouched.
+    pub fn import<T: Serialize>(
+        &mut self,
+        deposits: &[EventRecord<T>],
+        deposit_progress: &LegacyProgress,
+        burn_progress: &LegacyProgress,
+    ) -> Result<(), String> {
+        let tx = self.conn.transaction().map_err(db_err)?;
+        for record in deposits {
+            let json = serde_json::to_string(&record.event).map_err(|e| e.to_string())?;
+            tx.execute(
+                &format!(
+                    "INSERT OR IGNORE INTO deposits ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    RECORD_COLUMNS
+                ),
+                params![
+                    record.nonce as i64,
+                    record.source_signature,
+                    record.source_slot as i64,
+                    json,
+                    record.status.as_str(),
+                    record.attempts,
+                    record.last_error,
+                    record.relay_signature,
+                    record.observed_at,
+                    record.updated_at,
+                ],
+            )
+            .map_err(db_err)?;
+        }
+        for (queue, progress) in [(Queue::Deposits, deposit_progress), (Queue::Burns, burn_progress)] {
+            let entries = [
+                (queue.cursor_key(), progress.signature.clone()),
+                (queue.legacy_nonce_key(), progress.last_nonce.map(|n| n.to_string())),
+            ];
+            for (name, value) in entries {
+                if let Some(value) = value {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO cursors (name, value) VALUES (?1, ?2)",
+                        params![name, value],
+                    )
+                    .map_err(db_err)?;
+                }
+            }
+        }
+        tx.commit().map_err(db_err)
+    }
+
+    /// Highest nonce relayed by the relayer this store was imported from.
+    pub fn legacy_last_nonce(&self, queue: Queue) -> Result<Option<u64>, String> {
+        let value: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM cursors WHERE name = ?1",
+                params![queue.legacy_nonce_key()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_err)?;
+        value
+            .map(|v| v.parse().map_err(|_| format!("Corrupt {} in store: {}", queue.legacy_nonce_key(), v)))
+            .transpose()
+    }
+
+    /// The event recorded under `nonce`, whatever its status.
+    pub fn event<T: DeserializeOwned>(&self, queue: Queue, nonce: u64) -> Result<Option<EventRecord<T>>, String> {
+        let mut records = self.records_where(queue, "nonce = ?1", params![nonce as i64])?;
+        Ok(records.pop())
+    }
+
+    /// Every event whose source transaction landed at or before `slot`,
+    /// whatever its status, oldest nonce first.
+    pub fn events_through<T: DeserializeOwned>(&self, queue: Queue, slot: u64) -> Result<Vec<EventRecord<T>>, String> {
+        self.records_where(queue, "source_slot <= ?1", params![slot as i64])
+    }
+
+    /// Every confirmed event not yet relayed, oldest nonce first.
+    pub fn unrelayed<T: DeserializeOwned>(&self, queue: Queue) -> Result<Vec<EventRecord<T>>, String> {
+        self.records_with_status(queue, &[EventStatus::Pending, EventStatus::Failed])
+    }
+
+    /// Every event that used up its relay attempts, oldest nonce first.
+    pub fn dead_lettered<T: DeserializeOwned>(&self, queue: Queue) -> Result<Vec<EventRecord<T>>, String> {
+        self.records_with_status(queue, &[EventStatus::DeadLettered])
+    }
+
+    /// Every event still waiting on its confirmation policy, including
+    /// orphaned ones so they are picked up again if they reappear.
+    pub fn unconfirmed<T: DeserializeOwned>(&self, queue: Queue) -> Result<Vec<EventRecord<T>>, String> {
+        self.records_with_status(queue, &[EventStatus::Seen, EventStatus::Orphaned])
+    }
+
+    fn records_with_status<T: DeserializeOwned>(
+        &self,
+        queue: Queue,
+        statuses: &[EventStatus],
+    ) -> Result<Vec<EventRecord<T>>, String> {
+        let filter = statuses
+            .iter()
+            .map(|s| format!("'{}'", s.as_str()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.records_where(queue, &format!("status IN ({})", filter), [])
+    }
+
+    fn records_where<T: DeserializeOwned>(
+        &self,
+        queue: Queue,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<EventRecord<T>>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {} FROM {} WHERE {} ORDER BY nonce",
+                RECORD_COLUMNS,
+                queue.table(),
+                condition
+            ))
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params, |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, u32>(5)?,
+                    row.get::<_, Option<String>>(6)?,
+                    row.get::<_, Option<String>>(7)?,
+                    row.get::<_, i64>(8)?,
+                    row.get::<_, i64>(9)?,
+                ))
+            })
+            .map_err(db_err)?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let (
+                nonce,
+                source_signature,
+                source_slot,
+                event,
+                status,
+                attempts,
+                last_error,
+                relay_signature,
+                observed_at,
+                updated_at,
+            ) = row.map_err(db_err)?;
+            records.push(EventRecord {
+                nonce: nonce as u64,
+                source_signature,
+                source_slot: source_slot as u64,
+                event: serde_json::from_str(&event)
+                    .map_err(|e| format!("Corrupt event for nonce {}: {}", nonce, e))?,
+                status: EventStatus::parse(&status)?,
+                attempts,
+                last_error,
+                relay_signature,
+                observed_at,
+                updated_at,
+            });
+        }
+        Ok(records)
+    }
+
+    /// Move a `Seen` or `Orphaned` event to `Pending` once its source
+    /// transaction meets the confirmation policy.
+    pub fn mark_confirmed(&mut self, queue: Queue, nonce: u64) -> Result<(), String> {
+        self.set_unconfirmed_status(queue, nonce, EventStatus::Pending, None)
+    }
+
+    pub fn mark_orphaned(&mut self, queue: Queue, nonce: u64) -> Result<(), String> {
+        self.set_unconfirmed_status(queue, nonce, EventStatus::Orphaned, None)
+    }
+
+    /// Record that an unconfirmed event's transaction is (again) on chain,
+    /// possibly in a different slot after a fork.
+    pub fn mark_seen(&mut self, queue: Queue, nonce: u64, slot: u64) -> Result<(), String> {
+        self.set_unconfirmed_status(queue, nonce, EventStatus::Seen, Some(slot))
+    }
+
+    fn set_unconfirmed_status(
+        &mut self,
+        queue: Queue,
+        nonce: u64,
+        status: EventStatus,
+        slot: Option<u64>,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                &format!(
+                    "UPDATE {} SET status = ?1, source_slot = COALESCE(?2, source_slot), updated_at = ?3
+                     WHERE nonce = ?4 AND status IN (?5, ?6)",
+                    queue.table()
+                ),
+                params![
+                    status.as_str(),
+                    slot.map(|s| s as i64),
+                    now(),
+                    nonce as i64,
+                    EventStatus::Seen.as_str(),
+                    EventStatus::Orphaned.as_str(),
+                ],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    /// Mark an event relayed. `relay_signature` is None when the destination
+    /// chain already had it processed and nothing was sent.
+    pub fn mark_relayed(&mut self, queue: Queue, nonce: u64, relay_signature: Option<&str>) -> Result<(), String> {
+        self.conn
+            .execute(
+                &format!(
+                    "UPDATE {} SET status = ?1, attempts = attempts + 1, last_error = NULL,
+                         relay_signature = COALESCE(?2, relay_signature), updated_at = ?3
+                     WHERE nonce = ?4",
+                    queue.table()
+                ),
+                params![EventStatus::Relayed.as_str(), relay_signature, now(), nonce as i64],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    /// Record that an event is not relayable yet (no covering root, committee
+    /// short of its threshold). Unlike a failure this uses up no attempt, so
+    /// waiting never dead-letters an event.
+    pub fn mark_waiting(&mut self, queue: Queue, nonce: u64, reason: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                &format!(
+                    "UPDATE {} SET last_error = ?1, updated_at = ?2 WHERE nonce = ?3",
+                    queue.table()
+                ),
+                params![reason, now(), nonce as i64],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    /// Record a failed relay. The event is dead-lettered instead once this
+    /// is its `max_attempts`th attempt; returns whether it was.
+    pub fn mark_failed(&mut self, queue: Queue, nonce: u64, error: &str, max_attempts: u32) -> Result<bool, String> {
+        let tx = self.conn.transaction().map_err(db_err)?;
+        tx.execute(
+            &format!(
+                "UPDATE {} SET attempts = attempts + 1, last_error = ?2, updated_at = ?3,
+                     status = CASE WHEN attempts + 1 >= ?4 THEN ?5 ELSE ?1 END
+                 WHERE nonce = ?6",
+                queue.table()
+            ),
+            params![
+                EventStatus::Failed.as_str(),
+                error,
+                now(),
+                max_attempts,
+                EventStatus::DeadLettered.as_str(),
+                nonce as i64
+            ],
+        )
+        .map_err(db_err)?;
+        let status: Option<String> = tx
+            .query_row(
+                &format!("SELECT status FROM {} WHERE nonce = ?1", queue.table()),
+                params![nonce as i64],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_err)?;
+        tx.commit().map_err(db_err)?;
+        Ok(status.as_deref() == Some(EventStatus::DeadLettered.as_str()))
+    }
+
+    /// Number of events per status, for metrics.
+    pub fn status_counts(&self, queue: Queue) -> Result<Vec<(String, u64)>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT status, COUNT(*) FROM {} GROUP BY status ORDER BY status",
+                queue.table()
+            ))
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64)))
+            .map_err(db_err)?;
+        rows.collect::<Result<_, _>>().map_err(db_err)
+    }
+
+    /// Highest nonce relayed so far, if any, counting what the legacy
+    /// relayer had relayed before the store was imported.
+    pub fn max_relayed_nonce(&self, queue: Queue) -> Result<Option<u64>, String> {
+        let stored = self
+            .conn
+            .query_row(
+                &format!("SELECT MAX(nonce) FROM {} WHERE status = ?1", queue.table()),
+                params![EventStatus::Relayed.as_str()],
+                |row| row.get::<_, Option<i64>>(0),
+            )
+            .map(|n| n.map(|n| n as u64))
+            .map_err(db_err)?;
+        Ok(stored.max(self.legacy_last_nonce(queue)?))
+    }
+
+    /// Lowest nonce not relayed yet: the first one, counting up from what the
+    /// legacy relayer had relayed, that is missing from the store or not
+    /// `Relayed`. Everything below it has been relayed.
+    pub fn lowest_unrelayed_nonce(&self, queue: Queue) -> Result<u64, String> {
+        let floor = self.legacy_last_nonce(queue)?.map_or(0, |n| n + 1);
+        let table = queue.table();
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT CASE
+                         WHEN NOT EXISTS (SELECT 1 FROM {t} WHERE nonce = ?1 AND status = ?2) THEN ?1
+                         ELSE (SELECT MIN(r.nonce) + 1 FROM {t} r
+                               WHERE r.nonce >= ?1 AND r.status = ?2
+                                 AND NOT EXISTS (SELECT 1 FROM {t} n
+                                                 WHERE n.nonce = r.nonce + 1 AND n.status = ?2))
+                     END",
+                    t = table
+                ),
+                params![floor as i64, EventStatus::Relayed.as_str()],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|n| n as u64)
+            .map_err(db_err)
+    }
+
+    /// Start tracking the L1 withdrawal for `nonce`; no-op if already tracked.
+    pub fn track_withdrawal(&mut self, nonce: u64) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO withdrawals (nonce, state, updated_at) VALUES (?1, ?2, ?3)",
+                params![nonce as i64, WithdrawalState::Open.as_str(), now()],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    /// Withdrawals that are neither finalized nor cancelled, oldest first.
+    pub fn open_withdrawals(&self) -> Result<Vec<TrackedWithdrawal>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT nonce, state, challenge_deadline, attempts, last_error, updated_at
+                 FROM withdrawals WHERE state IN (?1, ?2) ORDER BY nonce",
+            )
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(
+                params![WithdrawalState::Open.as_str(), WithdrawalState::Challenged.as_str()],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<i64>>(2)?,
+                        row.get::<_, u32>(3)?,
+                        row.get::<_, Option<String>>(4)?,
+                        row.get::<_, i64>(5)?,
+                    ))
+                },
+            )
+            .map_err(db_err)?;
+
+        let mut withdrawals = Vec::new();
+        for row in rows {
+            let (nonce, state, challenge_deadline, attempts, last_error, updated_at) =
+                row.map_err(db_err)?;
+            withdrawals.push(TrackedWithdrawal {
+                nonce: nonce as u64,
+                state: WithdrawalState::parse(&state)?,
+                challenge_deadline,
+                attempts,
+                last_error,
+                updated_at,
+            });
+        }
+        Ok(withdrawals)
+    }
+
+    /// Record the state and deadline last read from the WithdrawalRequest.
+    pub fn set_withdrawal_state(
+        &mut self,
+        nonce: u64,
+        state: WithdrawalState,
+        challenge_deadline: i64,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE withdrawals SET state = ?1, challenge_deadline = ?2, updated_at = ?3
+                 WHERE nonce = ?4",
+                params![state.as_str(), challenge_deadline, now(), nonce as i64],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    /// Record a finalize transaction: finalized with its signature on
+    /// success, otherwise the error for the next retry.
+    pub fn record_finalize_attempt(&mut self, nonce: u64, result: Result<&str, &str>) -> Result<(), String> {
+        let (state, signature, error) = match result {
+            Ok(signature) => (WithdrawalState::Finalized, Some(signature), None),
+            Err(error) => (WithdrawalState::Open, None, Some(error)),
+        };
+        self.conn
+            .execute(
+                "UPDATE withdrawals SET state = ?1, attempts = attempts + 1, last_error = ?2,
+                     finalize_signature = COALESCE(?3, finalize_signature), updated_at = ?4
+                 WHERE nonce = ?5",
+                params![state.as_str(), error, signature, now(), nonce as i64],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    /// Number of tracked withdrawals per state, for metrics.
+    pub fn withdrawal_counts(&self) -> Result<Vec<(String, u64)>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT state, COUNT(*) FROM withdrawals GROUP BY state ORDER BY state")
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64)))
+            .map_err(db_err)?;
+        rows.collect::<Result<_, _>>().map_err(db_err)
+    }
+
+    /// Watchtower cursor: newest L1 signature whose withdrawals are recorded.
+    pub fn watch_cursor(&self) -> Result<Option<String>, String> {
+        self.conn
+            .query_row(
+                "SELECT value FROM cursors WHERE name = 'last_watch_signature'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_err)
+    }
+
+    /// Record the L1 withdrawals initiated by one scanned transaction as
+    /// unverified and advance the watch cursor, in a single transaction.
+    pub fn record_watched<T: Serialize>(
+        &mut self,
+        l1_signature: &str,
+        withdrawals: &[(u64, T)],
+    ) -> Result<(), String> {
+        let tx = self.conn.transaction().map_err(db_err)?;
+        let ts = now();
+        for (nonce, event) in withdrawals {
+            let json = serde_json::to_string(event).map_err(|e| e.to_string())?;
+            tx.execute(
+                "INSERT OR IGNORE INTO watched_withdrawals (nonce, l1_signature, event, verdict, observed_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                params![*nonce as i64, l1_signature, json, WatchVerdict::Unverified.as_str(), ts],
+            )
+            .map_err(db_err)?;
+        }
+        tx.execute(
+            "INSERT INTO cursors (name, value) VALUES ('last_watch_signature', ?1)
+             ON CONFLICT(name) DO UPDATE SET value = excluded.value",
+            params![l1_signature],
+        )
+        .map_err(db_err)?;
+        tx.commit().map_err(db_err)
+    }
+
+    /// Watched withdrawals still waiting for a verdict, oldest first.
+    pub fn unverified_withdrawals<T: DeserializeOwned>(&self) -> Result<Vec<WatchedWithdrawal<T>>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT nonce, l1_signature, event FROM watched_withdrawals
+                 WHERE verdict = ?1 ORDER BY nonce",
+            )
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![WatchVerdict::Unverified.as_str()], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })
+            .map_err(db_err)?;
+
+        let mut withdrawals = Vec::new();
+        for row in rows {
+            let (nonce, l1_signature, event) = row.map_err(db_err)?;
+            withdrawals.push(WatchedWithdrawal {
+                nonce: nonce as u64,
+                l1_signature,
+                event: serde_json::from_str(&event)
+                    .map_err(|e| format!("Corrupt withdrawal for nonce {}: {}", nonce, e))?,
+            });
+        }
+        Ok(withdrawals)
+    }
+
+    /// Record the watchtower's verdict, with what it was based on and the
+    /// challenge transaction if one was sent.
+    pub fn set_verdict(
+        &mut self,
+        nonce: u64,
+        verdict: WatchVerdict,
+        detail: &str,
+        challenge_signature: Option<&str>,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE watched_withdrawals SET verdict = ?1, detail = ?2,
+                     challenge_signature = COALESCE(?3, challenge_signature), updated_at = ?4
+                 WHERE nonce = ?5",
+                params![verdict.as_str(), detail, challenge_signature, now(), nonce as i64],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    /// Record a settlement root the watchtower disputed, with the evidence
+    /// and the ChallengeStateRoot transaction if we sent one. A root already
+    /// on record keeps its first entry.
+    pub fn record_root_dispute(
+        &mut self,
+        l2_slot: u64,
+        detail: &str,
+        challenge_signature: Option<&str>,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO root_disputes (l2_slot, detail, challenge_signature, state, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![l2_slot as i64, detail, challenge_signature, DisputeState::Open.as_str(), now()],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    /// L2 slots of the roots whose dispute game is still open, oldest first.
+    pub fn open_root_disputes(&self) -> Result<Vec<u64>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT l2_slot FROM root_disputes WHERE state = ?1 ORDER BY l2_slot")
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(params![DisputeState::Open.as_str()], |row| row.get::<_, i64>(0))
+            .map_err(db_err)?;
+        rows.map(|row| row.map(|slot| slot as u64).map_err(db_err)).collect()
+    }
+
+    pub fn set_dispute_state(&mut self, l2_slot: u64, state: DisputeState) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE root_disputes SET state = ?1, updated_at = ?2 WHERE l2_slot = ?3",
+                params![state.as_str(), now(), l2_slot as i64],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    /// Record a state root we just posted, with the batch still unpublished.
+    pub fn record_posted_root(
+        &mut self,
+        l2_slot: u64,
+        first_slot: u64,
+        state_root: &str,
+        transaction_count: u32,
+        post_signature: &str,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO posted_roots
+                     (l2_slot, first_slot, state_root, transaction_count, state, post_signature, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    l2_slot as i64,
+                    first_slot as i64,
+                    state_root,
+                    transaction_count,
+                    RootState::Posted.as_str(),
+                    post_signature,
+                    now()
+                ],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    /// Posted roots that are neither finalized nor invalidated, oldest first.
+    pub fn unsettled_roots(&self) -> Result<Vec<PostedRoot>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT l2_slot, first_slot, state, batch_available FROM posted_roots
+                 WHERE state IN (?1, ?2) ORDER BY l2_slot",
+            )
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map(
+                params![RootState::Posted.as_str(), RootState::Challenged.as_str()],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, i64>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, bool>(3)?,
+                    ))
+                },
+            )
+            .map_err(db_err)?;
+
+        let mut roots = Vec::new();
+        for row in rows {
+            let (l2_slot, first_slot, state, batch_available) = row.map_err(db_err)?;
+            roots.push(PostedRoot {
+                l2_slot: l2_slot as u64,
+                first_slot: first_slot as u64,
+                state: RootState::parse(&state)?,
+                batch_available,
+            });
+        }
+        Ok(roots)
+    }
+
+    pub fn set_batch_available(&mut self, l2_slot: u64) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE posted_roots SET batch_available = 1, updated_at = ?1 WHERE l2_slot = ?2",
+                params![now(), l2_slot as i64],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+
+    /// Record the root's status as last read from L1, and the finalize
+    /// transaction if we sent one.
+    pub fn set_root_state(
+        &mut self,
+        l2_slot: u64,
+        state: RootState,
+        finalize_signature: Option<&str>,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE posted_roots SET state = ?1,
+                     finalize_signature = COALESCE(?2, finalize_signature), updated_at = ?3
+                 WHERE l2_slot = ?4",
+                params![state.as_str(), finalize_signature, now(), l2_slot as i64],
+            )
+            .map_err(db_err)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn store() -> RelayerStore {
+        RelayerStore::open_in_memory().unwrap()
+    }
+
+    fn deposit(amount: u64) -> Value {
+        json!({ "Deposit": { "amount": amount } })
+    }
+
+    fn status(store: &RelayerStore, queue: Queue, nonce: u64) -> EventStatus {
+        store.event::<Value>(queue, nonce).unwrap().unwrap().status
+    }
+
+    #[test]
+    fn test_record_scanned_advances_cursor_and_keeps_existing_events() {
+        let mut store = store();
+        assert!(store.is_empty().unwrap());
+
+        let replaced = store
+            .record_scanned(Queue::Deposits, "sig1", 10, &[(0, deposit(1)), (1, deposit(2))])
+            .unwrap();
+        assert!(replaced.is_empty());
+        assert_eq!(store.cursor(Queue::Deposits).unwrap().as_deref(), Some("sig1"));
+        assert_eq!(store.cursor(Queue::Burns).unwrap(), None);
+        assert_eq!(store.unconfirmed::<Value>(Queue::Deposits).unwrap().len(), 2);
+        assert!(store.unrelayed::<Value>(Queue::Deposits).unwrap().is_empty());
+
+        // A different transaction claiming an unconfirmed nonce replaces it
+        store.mark_confirmed(Queue::Deposits, 0).unwrap();
+        let replaced = store
+            .record_scanned(Queue::Deposits, "sig2", 11, &[(0, deposit(9)), (1, deposit(9))])
+            .unwrap();
+        assert_eq!(replaced, vec![(1, "sig1".to_string())]);
+        assert_eq!(store.cursor(Queue::Deposits).unwrap().as_deref(), Some("sig2"));
+
+        let kept = store.event::<Value>(Queue::Deposits, 0).unwrap().unwrap();
+        assert_eq!((kept.source_signature.as_str(), kept.event), ("sig1", deposit(1)));
+        let taken = store.event::<Value>(Queue::Deposits, 1).unwrap().unwrap();
+        assert_eq!((taken.source_signature.as_str(), taken.source_slot), ("sig2", 11));
+    }
+
+    #[test]
+    fn test_insert_missing_leaves_cursor_and_known_nonces() {
+        let mut store = store();
+        store.record_scanned(Queue::Burns, "sig1", 5, &[(3, deposit(1))]).unwrap();
+        let inserted = store
+            .insert_missing(Queue::Burns, "sig0", 4, &[(2, deposit(0)), (3, deposit(7))])
+            .unwrap();
+        assert_eq!(inserted, 1);
+        assert_eq!(store.cursor(Queue::Burns).unwrap().as_deref(), Some("sig1"));
+        assert_eq!(store.event::<Value>(Queue::Burns, 3).unwrap().unwrap().event, deposit(1));
+        assert_eq!(store.events_through::<Value>(Queue::Burns, 4).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_confirmation_and_orphan_transitions() {
+        let mut store = store();
+        store.record_scanned(Queue::Deposits, "sig1", 10, &[(0, deposit(1))]).unwrap();
+
+        store.mark_orphaned(Queue::Deposits, 0).unwrap();
+        assert_eq!(status(&store, Queue::Deposits, 0), EventStatus::Orphaned);
+        assert_eq!(store.unconfirmed::<Value>(Queue::Deposits).unwrap().len(), 1);
+
+        store.mark_seen(Queue::Deposits, 0, 12).unwrap();
+        let record = store.event::<Value>(Queue::Deposits, 0).unwrap().unwrap();
+        assert_eq!((record.status, record.source_slot), (EventStatus::Seen, 12));
+
+        store.mark_confirmed(Queue::Deposits, 0).unwrap();
+        assert_eq!(status(&store, Queue::Deposits, 0), EventStatus::Pending);
+
+        // Only unconfirmed events move; a pending one cannot be orphaned
+        store.mark_orphaned(Queue::Deposits, 0).unwrap();
+        assert_eq!(status(&store, Queue::Deposits, 0), EventStatus::Pending);
+    }
+
+    #[test]
+    fn test_failed_relays_are_dead_lettered_at_the_attempt_cap() {
+        let mut store = store();
+        store.record_scanned(Queue::Deposits, "sig1", 10, &[(0, deposit(1)), (1, deposit(2))]).unwrap();
+        store.mark_confirmed(Queue::Deposits, 0).unwrap();
+        store.mark_confirmed(Queue::Deposits, 1).unwrap();
+
+        assert!(!store.mark_failed(Queue::Deposits, 0, "rpc down", 3).unwrap());
+        assert!(!store.mark_failed(Queue::Deposits, 0, "rpc down", 3).unwrap());
+        assert_eq!(status(&store, Queue::Deposits, 0), EventStatus::Failed);
+        assert!(store.mark_failed(Queue::Deposits, 0, "still down", 3).unwrap());
+
+        let dead = store.dead_lettered::<Value>(Queue::Deposits).unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!((dead[0].attempts, dead[0].last_error.as_deref()), (3, Some("still down")));
+        let unrelayed = store.unrelayed::<Value>(Queue::Deposits).unwrap();
+        assert_eq!(unrelayed.iter().map(|r| r.nonce).collect::<Vec<_>>(), vec![1]);
+
+        // Backfill re-queues it with a fresh attempt budget
+        store.mark_relayed(Queue::Deposits, 1, Some("relay1")).unwrap();
+        assert_eq!(store.requeue_from(Queue::Deposits, 0).unwrap(), 2);
+        let record = store.event::<Value>(Queue::Deposits, 0).unwrap().unwrap();
+        assert_eq!((record.status, record.attempts, record.last_error), (EventStatus::Pending, 0, None));
+        let record = store.event::<Value>(Queue::Deposits, 1).unwrap().unwrap();
+        assert_eq!((record.attempts, record.relay_signature.as_deref()), (1, Some("relay1")));
+    }
+
+    #[test]
+    fn test_waiting_relays_use_up_no_attempts() {
+        let mut store = store();
+        store.record_scanned(Queue::Burns, "sig1", 10, &[(0, deposit(1))]).unwrap();
+        store.mark_confirmed(Queue::Burns, 0).unwrap();
+
+        for _ in 0..5 {
+            store.mark_waiting(Queue::Burns, 0, "no root covering L2 slot 10 yet").unwrap();
+        }
+        let record = store.event::<Value>(Queue::Burns, 0).unwrap().unwrap();
+        assert_eq!(
+            (record.status, record.attempts, record.last_error.as_deref()),
+            (EventStatus::Pending, 0, Some("no root covering L2 slot 10 yet"))
+        );
+
+        // A real failure after waiting still counts from zero
+        assert!(!store.mark_failed(Queue::Burns, 0, "rpc down", 2).unwrap());
+        store.mark_waiting(Queue::Burns, 0, "1 of 2 committee attestations").unwrap();
+        assert!(store.dead_lettered::<Value>(Queue::Burns).unwrap().is_empty());
+        assert_eq!(store.unrelayed::<Value>(Queue::Burns).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_import_carries_cursors_and_legacy_nonces() {
+        let mut store = store();
+        let now = now();
+        let deposits = vec![EventRecord {
+            nonce: 8,
+            source_signature: "sig8".to_string(),
+            source_slot: 0,
+            event: deposit(8),
+            status: EventStatus::Failed,
+            attempts: 2,
+            last_error: Some("boom".to_string()),
+            relay_signature: None,
+            observed_at: now,
+            updated_at: now,
+        }];
+        store
+            .import(
+                &deposits,
+                &LegacyProgress { signature: Some("dsig".to_string()), last_nonce: Some(7) },
+                &LegacyProgress { signature: Some("bsig".to_string()), last_nonce: Some(41) },
+            )
+            .unwrap();
+
+        assert_eq!(store.cursor(Queue::Deposits).unwrap().as_deref(), Some("dsig"));
+        assert_eq!(store.cursor(Queue::Burns).unwrap().as_deref(), Some("bsig"));
+        assert_eq!(store.legacy_last_nonce(Queue::Deposits).unwrap(), Some(7));
+        assert_eq!(store.max_relayed_nonce(Queue::Burns).unwrap(), Some(41));
+        assert_eq!(store.max_relayed_nonce(Queue::Deposits).unwrap(), Some(7));
+        let record = store.event::<Value>(Queue::Deposits, 8).unwrap().unwrap();
+        assert_eq!((record.status, record.attempts), (EventStatus::Failed, 2));
+
+        // Relaying past the legacy mark moves the high-water mark on
+        store.mark_relayed(Queue::Deposits, 8, Some("relay8")).unwrap();
+        assert_eq!(store.max_relayed_nonce(Queue::Deposits).unwrap(), Some(8));
+    }
+
+    #[test]
+    fn test_lowest_unrelayed_nonce_stops_at_gaps_and_unrelayed_events() {
+        let mut store = store();
+        assert_eq!(store.lowest_unrelayed_nonce(Queue::Deposits).unwrap(), 0);
+
+        let events: Vec<_> = [0, 1, 2, 4].iter().map(|&n| (n, deposit(n))).collect();
+        store.record_scanned(Queue::Deposits, "sig1", 10, &events).unwrap();
+        for nonce in [0, 1, 2, 4] {
+            store.mark_confirmed(Queue::Deposits, nonce).unwrap();
+        }
+        assert_eq!(store.lowest_unrelayed_nonce(Queue::Deposits).unwrap(), 0);
+
+        for nonce in [0, 2, 4] {
+            store.mark_relayed(Queue::Deposits, nonce, None).unwrap();
+        }
+        // Nonce 1 failed while later ones went through
+        store.mark_failed(Queue::Deposits, 1, "boom", 5).unwrap();
+        assert_eq!(store.lowest_unrelayed_nonce(Queue::Deposits).unwrap(), 1);
+        assert_eq!(store.max_relayed_nonce(Queue::Deposits).unwrap(), Some(4));
+
+        // Nonce 3 was never seen at all
+        store.mark_relayed(Queue::Deposits, 1, None).unwrap();
+        assert_eq!(store.lowest_unrelayed_nonce(Queue::Deposits).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_lowest_unrelayed_nonce_starts_after_legacy_nonce() {
+        let mut store = store();
+        let legacy = LegacyProgress { signature: None, last_nonce: Some(9) };
+        store.import::<Value>(&[], &legacy, &LegacyProgress::default()).unwrap();
+        assert_eq!(store.lowest_unrelayed_nonce(Queue::Deposits).unwrap(), 10);
+        assert_eq!(store.lowest_unrelayed_nonce(Queue::Burns).unwrap(), 0);
+
+        store.insert_missing(Queue::Deposits, "sig", 1, &[(10, deposit(10))]).unwrap();
+        store.mark_relayed(Queue::Deposits, 10, None).unwrap();
+        assert_eq!(store.lowest_unrelayed_nonce(Queue::Deposits).unwrap(), 11);
+    }
+
+    #[test]
+    fn test_migrate_tracks_relayed_burns_for_finalization() {
+        let mut store = store();
+        store
+            .record_scanned(
+                Queue::Burns,
+                "sig1",
+                10,
+                &[(0, json!({ "Burn": { "amount": 1 } })), (1, json!({ "Message": {} }))],
+            )
+            .unwrap();
+        for nonce in [0, 1] {
+            store.mark_confirmed(Queue::Burns, nonce).unwrap();
+            store.mark_relayed(Queue::Burns, nonce, None).unwrap();
+        }
+        store.migrate().unwrap();
+
+        let open = store.open_withdrawals().unwrap();
+        assert_eq!(open.iter().map(|w| w.nonce).collect::<Vec<_>>(), vec![0]);
+
+        store.record_finalize_attempt(0, Err("too early")).unwrap();
+        let open = store.open_withdrawals().unwrap();
+        assert_eq!((open[0].attempts, open[0].last_error.as_deref()), (1, Some("too early")));
+        store.record_finalize_attempt(0, Ok("fin0")).unwrap();
+        assert!(store.open_withdrawals().unwrap().is_empty());
+        assert_eq!(store.withdrawal_counts().unwrap(), vec![("finalized".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_root_disputes_stay_open_until_settled() {
+        let mut store = store();
+        store.record_root_dispute(200, "first", Some("chal")).unwrap();
+        store.record_root_dispute(100, "other", None).unwrap();
+        store.record_root_dispute(200, "again", None).unwrap();
+        assert_eq!(store.open_root_disputes().unwrap(), vec![100, 200]);
+
+        store.set_dispute_state(100, DisputeState::Won).unwrap();
+        assert_eq!(store.open_root_disputes().unwrap(), vec![200]);
+        let (detail, signature): (String, Option<String>) = store
+            .conn
+            .query_row(
+                "SELECT detail, challenge_signature FROM root_disputes WHERE l2_slot = 200",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((detail.as_str(), signature.as_deref()), ("first", Some("chal")));
+    }
+}
@@ -0,0 +1,1028 @@
+// Watchtower mode (`mythic-relayer watchtower`).
+//
+// Independently checks every InitiateWithdrawal on L1 against the L2 event
+// with the same withdraw_nonce, and files ChallengeWithdrawal with a bond
+// when they disagree. It needs no sequencer or relayer authority; the
+// keypair only pays fees and bonds. A withdrawal proven against a
+// settlement root that disagrees with L2 is never marked valid: the
+// watchtower files ChallengeStateRoot against the root and answers the
+// defender's bisection moves from its own L2 index until the game ends.
+
+use super::*;
+
+/// What the watchtower concluded about one L1 withdrawal.
+enum Finding {
+    Valid(String),
+    /// Evidence payload for ChallengeWithdrawal.
+    Fraud(String),
+    /// The withdrawal matches L2 but the root it was proven against does
+    /// not; evidence payload for ChallengeStateRoot.
+    BadRoot(String),
+    /// No L2 evidence either way yet.
+    Unknown(String),
+}
+
+/// What came of disputing a settlement root.
+enum RootDispute {
+    /// ChallengeStateRoot was sent.
+    Filed(Signature),
+    /// Our dispute game against the root is already open.
+    Open,
+    /// The root was already invalidated.
+    Invalidated,
+    /// The root finalized, or its challenge window closed, first.
+    TooLate,
+}
+
+/// The watchtower's next move in a dispute game it started.
+#[derive(Debug, PartialEq)]
+enum DisputeMove {
+    /// Our turn: agree or disagree with the defender's midpoint hash.
+    Respond,
+    /// The defender's turn, with time left in the round.
+    Wait,
+    /// We let the round lapse; the challenge will be rejected.
+    Missed,
+    /// The defender let the round lapse; ResolveChallenge awards us the bonds.
+    DefenderTimedOut,
+}
+
+#[derive(BorshSerialize)]
+struct ResolveChallengeParams {
+    l2_slot: u64,
+    challenger: Pubkey,
+}
+
+#[derive(BorshSerialize)]
+struct ChallengeWithdrawalParams {
+    withdrawal_nonce: u64,
+    fraud_proof: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+struct ChallengeStateRootParams {
+    l2_slot: u64,
+    fraud_proof_type: u8,
+    proof_data: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+struct RespondBisectionParams {
+    l2_slot: u64,
+    agree: bool,
+}
+
+/// Read-only mirror of the settlement `DisputeTurn`.
+#[derive(BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+enum DisputeTurn {
+    Defender,
+    Challenger,
+    OneStep,
+}
+
+/// The bisection game in a settlement ChallengeAccount: each round halves
+/// the disputed step range [lo_step, hi_step] until one step remains.
+#[derive(Debug, Clone)]
+struct DisputeGame {
+    active: bool,
+    defender: Pubkey,
+    lo_step: u32,
+    hi_step: u32,
+    mid_hash: [u8; 32],
+    turn: DisputeTurn,
+    round: u16,
+    round_deadline: u64,
+}
+
+impl DisputeGame {
+    fn parse(data: &[u8]) -> Option<Self> {
+        // ChallengeStatus::Active is 0
+        let active = *data.get(CHALLENGE_STATUS_OFFSET)? == 0;
+        let game = data.get(CHALLENGE_GAME_OFFSET..SETTLEMENT_CHALLENGE_SIZE)?;
+        Some(Self {
+            active,
+            defender: Pubkey::new_from_array(
+                data.get(CHALLENGE_DEFENDER_OFFSET..CHALLENGE_DEFENDER_OFFSET + 32)?.try_into().unwrap(),
+            ),
+            lo_step: u32::from_le_bytes(game[0..4].try_into().unwrap()),
+            hi_step: u32::from_le_bytes(game[36..40].try_into().unwrap()),
+            mid_hash: game[72..104].try_into().unwrap(),
+            turn: DisputeTurn::deserialize(&mut &game[104..105]).ok()?,
+            round: u16::from_le_bytes(game[105..107].try_into().unwrap()),
+            round_deadline: u64::from_le_bytes(game[107..115].try_into().unwrap()),
+        })
+    }
+
+    /// Step the defender posts `mid_hash` for, as in settlement.
+    fn mid_step(&self) -> u32 {
+        self.lo_step + (self.hi_step - self.lo_step) / 2
+    }
+}
+
+/// Build ChallengeWithdrawal (IX 4). The bridge takes the bond from the
+/// challenger and holds it in the challenge_bond PDA until resolution.
+fn build_challenge_withdrawal_ix(
+    bridge_l1_program: &Pubkey,
+    challenger: &Pubkey,
+    withdrawal_nonce: u64,
+    fraud_proof: Vec<u8>,
+) -> Instruction {
+    let (config_pda, _) =
+        Pubkey::find_program_address(&[BRIDGE_CONFIG_SEED], bridge_l1_program);
+    let nonce_bytes = withdrawal_nonce.to_le_bytes();
+    let (withdrawal_pda, _) =
+        Pubkey::find_program_address(&[WITHDRAWAL_SEED, &nonce_bytes], bridge_l1_program);
+    let (bond_pda, _) =
+        Pubkey::find_program_address(&[CHALLENGE_BOND_SEED, &nonce_bytes], bridge_l1_program);
+
+    let mut data = vec![IX_CHALLENGE_WITHDRAWAL];
+    let params = ChallengeWithdrawalParams {
+        withdrawal_nonce,
+        fraud_proof,
+    };
+    params.serialize(&mut data).unwrap();
+
+    // Account order must match process_challenge_withdrawal in bridge (L1)
+    Instruction {
+        program_id: *bridge_l1_program,
+        accounts: vec![
+            AccountMeta::new(*challenger, true),                 // 0. challenger (signer, bond payer)
+            AccountMeta::new(withdrawal_pda, false),             // 1. withdrawal_request PDA
+            AccountMeta::new_readonly(config_pda, false),        // 2. bridge_config PDA
+            AccountMeta::new(bond_pda, false),                   // 3. challenge_bond PDA
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 4. system_program
+        ],
+        data,
+    }
+}
+
+/// Build ChallengeStateRoot (IX 2). Settlement escrows its configured
+/// `min_challenger_bond` and opens a bisection game over the root's batch.
+fn build_challenge_state_root_ix(challenger: &Pubkey, l2_slot: u64, proof_data: Vec<u8>) -> Instruction {
+    let slot_bytes = l2_slot.to_le_bytes();
+    let config_pda = find_settlement_pda(&[SETTLEMENT_CONFIG_SEED]);
+    let state_root_pda = find_settlement_pda(&[STATE_ROOT_SEED, &slot_bytes]);
+    let challenge_pda = find_settlement_pda(&[CHALLENGE_SEED, &slot_bytes, challenger.as_ref()]);
+    let set_pda = find_settlement_pda(&[SEQUENCER_SET_SEED]);
+
+    let mut data = vec![IX_CHALLENGE_STATE_ROOT];
+    let params = ChallengeStateRootParams {
+        l2_slot,
+        fraud_proof_type: FRAUD_PROOF_INVALID_STATE_TRANSITION,
+        proof_data,
+    };
+    params.serialize(&mut data).unwrap();
+
+    // Account order must match process_challenge_state_root in settlement
+    Instruction {
+        program_id: Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap(),
+        accounts: vec![
+            AccountMeta::new(*challenger, true),                 // 0. challenger (signer, bond payer)
+            AccountMeta::new(config_pda, false),                 // 1. settlement_config PDA
+            AccountMeta::new(state_root_pda, false),             // 2. state_root PDA
+            AccountMeta::new(challenge_pda, false),              // 3. challenge PDA
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // 4. system_program
+            AccountMeta::new(set_pda, false),                    // 5. sequencer_set PDA
+        ],
+        data,
+    }
+}
+
+/// Build ResolveChallenge (IX 3) for a game whose defender timed out. The
+/// challenger gets both bonds back and, when a sequencer set exists, the
+/// defender's slashed MYTH bond in `challenger_token`.
+fn build_resolve_challenge_ix(
+    challenger: &Pubkey,
+    l2_slot: u64,
+    defender: &Pubkey,
+    challenger_token: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let slot_bytes = l2_slot.to_le_bytes();
+    let config_pda = find_settlement_pda(&[SETTLEMENT_CONFIG_SEED]);
+    let state_root_pda = find_settlement_pda(&[STATE_ROOT_SEED, &slot_bytes]);
+    let challenge_pda = find_settlement_pda(&[CHALLENGE_SEED, &slot_bytes, challenger.as_ref()]);
+    let set_pda = find_settlement_pda(&[SEQUENCER_SET_SEED]);
+    let vault_pda = find_settlement_pda(&[SEQUENCER_VAULT_SEED]);
+
+    let mut data = vec![IX_RESOLVE_CHALLENGE];
+    ResolveChallengeParams {
+        l2_slot,
+        challenger: *challenger,
+    }
+    .serialize(&mut data)
+    .unwrap();
+
+    // Account order must match process_resolve_challenge in settlement
+    Instruction {
+        program_id: Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap(),
+        accounts: vec![
+            AccountMeta::new_readonly(*challenger, true),        // 0. caller (signer)
+            AccountMeta::new(config_pda, false),                 // 1. settlement_config PDA
+            AccountMeta::new(state_root_pda, false),             // 2. state_root PDA
+            AccountMeta::new(challenge_pda, false),              // 3. challenge PDA
+            AccountMeta::new(*challenger, false),                // 4. challenger
+            AccountMeta::new(*defender, false),                  // 5. defender
+            AccountMeta::new(set_pda, false),                    // 6. sequencer_set PDA
+            AccountMeta::new(vault_pda, false),                  // 7. bond vault PDA
+            AccountMeta::new(*challenger_token, false),          // 8. challenger MYTH token account
+            AccountMeta::new_readonly(*token_program, false),     // 9. token program
+        ],
+        data,
+    }
+}
+
+/// Build RespondBisection (IX 15): the challenger's verdict on the
+/// defender's midpoint hash.
+fn build_respond_bisection_ix(challenger: &Pubkey, l2_slot: u64, agree: bool) -> Instruction {
+    let slot_bytes = l2_slot.to_le_bytes();
+    let state_root_pda = find_settlement_pda(&[STATE_ROOT_SEED, &slot_bytes]);
+    let challenge_pda = find_settlement_pda(&[CHALLENGE_SEED, &slot_bytes, challenger.as_ref()]);
+
+    let mut data = vec![IX_RESPOND_BISECTION];
+    RespondBisectionParams { l2_slot, agree }.serialize(&mut data).unwrap();
+
+    // Account order must match process_respond_bisection in settlement
+    Instruction {
+        program_id: Pubkey::from_str(SETTLEMENT_PROGRAM_ID).unwrap(),
+        accounts: vec![
+            AccountMeta::new_readonly(*challenger, true),        // 0. challenger (signer)
+            AccountMeta::new_readonly(state_root_pda, false),    // 1. state_root PDA
+            AccountMeta::new(challenge_pda, false),              // 2. challenge PDA
+        ],
+        data,
+    }
+}
+
+pub async fn run_watchtower(config: RelayerConfig) {
+    let l1 = FailoverRpc::connect("L1", &config.l1_rpc_urls, CommitmentConfig::confirmed()).await;
+    let l2 = FailoverRpc::connect("L2", &config.l2_rpc_urls, CommitmentConfig::confirmed()).await;
+
+    let mut store = match RelayerStore::open(&config.store_path) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("[STORE] Failed to open {}: {}", config.store_path.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("=== Mythic L2 Bridge Watchtower ===");
+    println!("L1 RPC:          {}", config.l1_rpc_urls.join(", "));
+    println!("L2 RPC:          {}", config.l2_rpc_urls.join(", "));
+    println!("Bridge L1:       {}", config.bridge_l1_program);
+    println!("Bridge L2:       {}", config.bridge_l2_program);
+    println!("Settlement:      {}", SETTLEMENT_PROGRAM_ID);
+    println!("Challenger:      {}", config.relayer_keypair.pubkey());
+    println!("Store:           {}", config.store_path.display());
+    println!();
+    if config.l2_rpc_urls.len() < 2 {
+        eprintln!("[WATCH] Only one L2 endpoint configured; unissued-nonce withdrawals cannot be confirmed and will not be challenged");
+    }
+
+    loop {
+        // L2 first, so a burn is indexed before its L1 withdrawal is checked
+        if let Err(e) = index_l2_withdrawals(&l2.client(), &config, &mut store).await {
+            eprintln!("[WATCH] Error indexing L2: {}", e);
+            l2.check().await;
+        }
+        match watch_l1_withdrawals(&l1.client(), &l2.client(), &config, &mut store).await {
+            Ok(count) => {
+                if count > 0 {
+                    println!("[WATCH] Challenged {} withdrawals", count);
+                }
+            }
+            Err(e) => {
+                eprintln!("[WATCH] Error checking L1 withdrawals: {}", e);
+                l1.check().await;
+                l2.check().await;
+            }
+        }
+        if let Err(e) = play_root_disputes(&l1.client(), &config, &mut store).await {
+            eprintln!("[WATCH] Error playing root disputes: {}", e);
+            l1.check().await;
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(config.poll_interval_ms)).await;
+    }
+}
+
+/// Record new L1 withdrawals, then check every one still unverified.
+/// Returns how many were challenged.
+async fn watch_l1_withdrawals(
+    l1_client: &RpcClient,
+    l2_client: &RpcClient,
+    config: &RelayerConfig,
+    store: &mut RelayerStore,
+) -> Result<u64, String> {
+    let cursor = store.watch_cursor()?;
+    let sigs = fetch_signatures_since(
+        l1_client,
+        &config.bridge_l1_program,
+        cursor.as_deref(),
+        cursor.is_none(),
+    )
+    .await?;
+
+    for sig_info in &sigs {
+        let mut withdrawals = Vec::new();
+        if sig_info.err.is_none() {
+            let logs = fetch_transaction_logs(l1_client, &sig_info.signature).await?;
+            for event in parse_initiate_withdrawal_events(&logs) {
+                withdrawals.push((event.nonce, event));
+            }
+        }
+        store.record_watched(&sig_info.signature, &withdrawals)?;
+    }
+
+    let mut challenged = 0u64;
+    for watched in store.unverified_withdrawals::<InitiateWithdrawalEvent>()? {
+        let nonce = watched.nonce;
+        let finding = match check_withdrawal(l1_client, l2_client, config, store, &watched.event).await {
+            Ok(finding) => finding,
+            Err(e) => {
+                eprintln!("[WATCH] Could not check withdrawal nonce={}: {}", nonce, e);
+                continue;
+            }
+        };
+        let expired = store::now() >= watched.event.challenge_deadline;
+
+        match finding {
+            Finding::Valid(detail) => {
+                store.set_verdict(nonce, WatchVerdict::Valid, &detail, None)?;
+            }
+            Finding::Unknown(detail) if expired => {
+                eprintln!(
+                    "[ALERT] Withdrawal nonce={} (tx {}) left its challenge period unverified: {}",
+                    nonce, watched.l1_signature, detail
+                );
+                store.set_verdict(nonce, WatchVerdict::Expired, &detail, None)?;
+            }
+            Finding::Unknown(_) => {}
+            Finding::Fraud(evidence) if expired => {
+                eprintln!(
+                    "[ALERT] Invalid withdrawal nonce={} (tx {}) is past its challenge period: {}",
+                    nonce, watched.l1_signature, evidence
+                );
+                store.set_verdict(nonce, WatchVerdict::Expired, &evidence, None)?;
+            }
+            Finding::Fraud(evidence) => {
+                eprintln!(
+                    "[ALERT] Invalid withdrawal nonce={} (tx {}): {}",
+                    nonce, watched.l1_signature, evidence
+                );
+                match challenge_withdrawal(l1_client, config, &watched.event, evidence.clone()).await {
+                    Ok(Some(tx_sig)) => {
+                        println!("CHALLENGED WITHDRAWAL: nonce={} tx={}", nonce, tx_sig);
+                        store.set_verdict(
+                            nonce,
+                            WatchVerdict::Challenged,
+                            &evidence,
+                            Some(&tx_sig.to_string()),
+                        )?;
+                        challenged += 1;
+                    }
+                    Ok(None) => {
+                        // Someone else challenged it first, or it was resolved
+                        store.set_verdict(nonce, WatchVerdict::Challenged, &evidence, None)?;
+                    }
+                    Err(e) => {
+                        eprintln!("[WATCH] Failed to challenge nonce={}: {}", nonce, e);
+                    }
+                }
+            }
+            Finding::BadRoot(evidence) => {
+                let l2_slot = watched.event.l2_slot;
+                eprintln!(
+                    "[ALERT] Withdrawal nonce={} (tx {}) was proven against a bad settlement root: {}",
+                    nonce, watched.l1_signature, evidence
+                );
+                match dispute_state_root(l1_client, config, l2_slot, &evidence).await {
+                    Ok(RootDispute::Filed(tx_sig)) => {
+                        println!("CHALLENGED STATE ROOT: l2_slot={} tx={}", l2_slot, tx_sig);
+                        let tx_sig = tx_sig.to_string();
+                        store.record_root_dispute(l2_slot, &evidence, Some(&tx_sig))?;
+                        store.set_verdict(nonce, WatchVerdict::RootDisputed, &evidence, Some(&tx_sig))?;
+                        challenged += 1;
+                    }
+                    Ok(RootDispute::Open) => {
+                        store.record_root_dispute(l2_slot, &evidence, None)?;
+                        store.set_verdict(nonce, WatchVerdict::RootDisputed, &evidence, None)?;
+                    }
+                    Ok(RootDispute::Invalidated) => {
+                        // Nothing can finalize against it any more
+                        store.set_verdict(nonce, WatchVerdict::RootDisputed, &evidence, None)?;
+                    }
+                    Ok(RootDispute::TooLate) => {
+                        eprintln!(
+                            "[ALERT] Settlement root for L2 slot {} can no longer be disputed",
+                            l2_slot
+                        );
+                        store.set_verdict(nonce, WatchVerdict::Expired, &evidence, None)?;
+                    }
+                    Err(e) => {
+                        eprintln!("[WATCH] Failed to dispute the root for L2 slot {}: {}", l2_slot, e);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(challenged)
+}
+
+/// The finalized L2 withdraw nonce, or None while the endpoint has not
+/// finalized `l2_slot` (a nonce read then proves nothing about it).
+async fn finalized_withdraw_nonce(
+    l2_client: &RpcClient,
+    config: &RelayerConfig,
+    l2_slot: u64,
+) -> Result<Option<u64>, String> {
+    let finalized_slot = l2_client
+        .get_slot_with_commitment(CommitmentConfig::finalized())
+        .await
+        .map_err(|e| format!("Failed to read the finalized L2 slot: {}", e))?;
+    if finalized_slot < l2_slot {
+        return Ok(None);
+    }
+    fetch_l2_withdraw_nonce(l2_client, &config.bridge_l2_program, CommitmentConfig::finalized())
+        .await
+        .map(Some)
+}
+
+/// `finalized_withdraw_nonce` from the first other configured endpoint that
+/// answers. None when there is no other endpoint.
+async fn recheck_l2_withdraw_nonce(
+    l2_client: &RpcClient,
+    config: &RelayerConfig,
+    l2_slot: u64,
+) -> Option<Result<Option<u64>, String>> {
+    let mut last_err = None;
+    for url in config.l2_rpc_urls.iter().filter(|url| **url != l2_client.url()) {
+        let client = RpcClient::new_with_commitment(url.clone(), CommitmentConfig::finalized());
+        match finalized_withdraw_nonce(&client, config, l2_slot).await {
+            Ok(next_nonce) => return Some(Ok(next_nonce)),
+            Err(e) => last_err = Some(Err(format!("{}: {}", url, e))),
+        }
+    }
+    last_err
+}
+
+/// Compare an L1 withdrawal with the L2 event holding its nonce.
+async fn check_withdrawal(
+    l1_client: &RpcClient,
+    l2_client: &RpcClient,
+    config: &RelayerConfig,
+    store: &RelayerStore,
+    withdrawal: &InitiateWithdrawalEvent,
+) -> Result<Finding, String> {
+    let nonce = withdrawal.nonce;
+    let recipient = Pubkey::from_str(&withdrawal.recipient)
+        .map_err(|_| format!("Invalid recipient: {}", withdrawal.recipient))?;
+    let token_mint = Pubkey::from_str(&withdrawal.token_mint)
+        .map_err(|_| format!("Invalid token_mint: {}", withdrawal.token_mint))?;
+
+    let record = match store.event::<L2Event>(Queue::Burns, nonce)? {
+        Some(record) => record,
+        None => {
+            // A lagging or lying endpoint must not cost us the bond: only a
+            // nonce finalized past the withdrawal's L2 slot, confirmed by a
+            // second endpoint, is evidence
+            let next_nonce = match finalized_withdraw_nonce(l2_client, config, withdrawal.l2_slot).await? {
+                Some(next_nonce) if nonce >= next_nonce => next_nonce,
+                _ => return Ok(Finding::Unknown(format!("no indexed L2 event for nonce {}", nonce))),
+            };
+            return Ok(match recheck_l2_withdraw_nonce(l2_client, config, withdrawal.l2_slot).await {
+                Some(Ok(Some(confirmed))) if nonce >= confirmed => Finding::Fraud(
+                    serde_json::json!({
+                        "reason": "nonce_not_issued_on_l2",
+                        "nonce": nonce,
+                        "l2_withdraw_nonce": next_nonce.max(confirmed),
+                    })
+                    .to_string(),
+                ),
+                Some(Ok(Some(confirmed))) => Finding::Unknown(format!(
+                    "L2 endpoints disagree on the withdraw nonce ({} vs {})",
+                    next_nonce, confirmed
+                )),
+                Some(Ok(None)) => Finding::Unknown(format!(
+                    "second L2 endpoint has not finalized slot {}",
+                    withdrawal.l2_slot
+                )),
+                Some(Err(e)) => Finding::Unknown(format!("could not confirm the L2 withdraw nonce: {}", e)),
+                None => Finding::Unknown(format!(
+                    "nonce {} is not issued on {} but no second L2 endpoint can confirm it",
+                    nonce,
+                    l2_client.url()
+                )),
+            });
+        }
+    };
+
+    let event = match &record.event {
+        L2Event::Burn(event) => event,
+        L2Event::Message(_) => {
+            return Ok(Finding::Fraud(
+                serde_json::json!({
+                    "reason": "nonce_is_l2_message",
+                    "nonce": nonce,
+                    "l2_signature": record.source_signature,
+                })
+                .to_string(),
+            ));
+        }
+    };
+
+    let (l2_recipient, l2_mint, l2_amount) = expected_l1_withdrawal(event)?;
+    if (l2_recipient, l2_mint, l2_amount) != (recipient, token_mint, withdrawal.amount) {
+        return Ok(Finding::Fraud(
+            serde_json::json!({
+                "reason": "mismatch",
+                "nonce": nonce,
+                "l2_signature": record.source_signature,
+                "l2": { "recipient": l2_recipient.to_string(), "token_mint": l2_mint.to_string(), "amount": l2_amount },
+                "l1": { "recipient": withdrawal.recipient, "token_mint": withdrawal.token_mint, "amount": withdrawal.amount },
+            })
+            .to_string(),
+        ));
+    }
+
+    // The withdrawal itself is genuine; a bad root behind it is a
+    // settlement dispute, not a bridge challenge.
+    Ok(match check_state_root(l1_client, store, withdrawal, &recipient, &token_mint).await {
+        Ok(None) => Finding::Valid(format!(
+            "matches L2 event {}; state root agrees with L2",
+            record.source_signature
+        )),
+        Ok(Some(evidence)) => Finding::BadRoot(evidence),
+        Err(e) => Finding::Unknown(format!(
+            "matches L2 event {}, but its state root could not be checked: {}",
+            record.source_signature, e
+        )),
+    })
+}
+
+/// Check that the settlement root the withdrawal was proven against agrees
+/// with L2: the withdrawal's Merkle path in the tree over the indexed L2
+/// withdrawals must fold to the posted root. Returns the dispute evidence
+/// if it does not.
+async fn check_state_root(
+    l1_client: &RpcClient,
+    store: &RelayerStore,
+    withdrawal: &InitiateWithdrawalEvent,
+    recipient: &Pubkey,
+    token_mint: &Pubkey,
+) -> Result<Option<String>, String> {
+    let posted_root = read_posted_state_root(l1_client, withdrawal.l2_slot).await?;
+    let records = store.events_through::<L2Event>(Queue::Burns, withdrawal.l2_slot)?;
+    let proof = withdrawal_proof(&records, withdrawal.nonce, withdrawal.l2_slot)?;
+
+    let leaf = withdrawal_leaf_hash(recipient, withdrawal.amount, token_mint, withdrawal.nonce);
+    let computed = merkle_root(leaf, &proof.proof, proof.leaf_index);
+    if computed == posted_root {
+        return Ok(None);
+    }
+    Ok(Some(
+        serde_json::json!({
+            "reason": "state_root_mismatch",
+            "l2_slot": withdrawal.l2_slot,
+            "nonce": withdrawal.nonce,
+            "posted_root": bytes_to_hex(&posted_root),
+            "l2_root": bytes_to_hex(&computed),
+        })
+        .to_string(),
+    ))
+}
+
+/// File ChallengeWithdrawal with `evidence` as the fraud proof. Returns
+/// `Ok(None)` if the withdrawal is no longer Pending (already challenged).
+async fn challenge_withdrawal(
+    l1_client: &RpcClient,
+    config: &RelayerConfig,
+    withdrawal: &InitiateWithdrawalEvent,
+    evidence: String,
+) -> Result<Option<Signature>, String> {
+    let (withdrawal_pda, _) = Pubkey::find_program_address(
+        &[WITHDRAWAL_SEED, &withdrawal.nonce.to_le_bytes()],
+        &config.bridge_l1_program,
+    );
+    let data = l1_client
+        .get_account_data(&withdrawal_pda)
+        .await
+        .map_err(|e| format!("Failed to read withdrawal: {}", e))?;
+    let request = L1WithdrawalRequest::deserialize(&mut data.as_slice())
+        .map_err(|e| format!("Unreadable WithdrawalRequest: {}", e))?;
+    if request.status != L1WithdrawalStatus::Pending {
+        return Ok(None);
+    }
+
+    let challenger = config.relayer_keypair.pubkey();
+    let ix = build_challenge_withdrawal_ix(
+        &config.bridge_l1_program,
+        &challenger,
+        withdrawal.nonce,
+        evidence.into_bytes(),
+    );
+
+    // The deployed bridge sets the bond; simulating the challenge reads it
+    // from the bond account it would create, and fails up front if the
+    // challenger cannot cover it.
+    let (bond_pda, _) = Pubkey::find_program_address(
+        &[CHALLENGE_BOND_SEED, &withdrawal.nonce.to_le_bytes()],
+        &config.bridge_l1_program,
+    );
+    let bond = simulated_challenge_bond(l1_client, config, &ix, &bond_pda).await?;
+    println!(
+        "[WATCH] Challenging withdrawal nonce={} with a bond of {} lamports",
+        withdrawal.nonce, bond
+    );
+    send_with_retry(l1_client, config, &[ix]).await.map(Some)
+}
+
+/// Simulate ChallengeWithdrawal and read `bond_amount` from the
+/// challenge_bond account it creates.
+async fn simulated_challenge_bond(
+    l1_client: &RpcClient,
+    config: &RelayerConfig,
+    ix: &Instruction,
+    bond_pda: &Pubkey,
+) -> Result<u64, String> {
+    let tx = sign_transaction(l1_client, config, std::slice::from_ref(ix)).await?;
+    let result = l1_client
+        .simulate_transaction_with_config(
+            &tx,
+            RpcSimulateTransactionConfig {
+                commitment: Some(l1_client.commitment()),
+                accounts: Some(RpcSimulateTransactionAccountsConfig {
+                    encoding: None,
+                    addresses: vec![bond_pda.to_string()],
+                }),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .await
+        .map_err(|e| format!("simulate: {}", e))?
+        .value;
+    if let Some(err) = result.err {
+        let logs = result.logs.unwrap_or_default().join("\n    ");
+        return Err(format!("ChallengeWithdrawal would fail: {}\n    {}", err, logs));
+    }
+    let account = result
+        .accounts
+        .and_then(|accounts| accounts.into_iter().next().flatten())
+        .and_then(|account| account.decode::<solana_sdk::account::Account>())
+        .ok_or("Simulation did not return the challenge bond account")?;
+    account
+        .data
+        .get(CHALLENGE_BOND_AMOUNT_OFFSET..CHALLENGE_BOND_AMOUNT_OFFSET + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| "Challenge bond account too short".to_string())
+}
+
+/// Status and challenge deadline (an L1 slot) of the posted root for `l2_slot`.
+async fn read_root_status(l1_client: &RpcClient, l2_slot: u64) -> Result<(SettlementRootStatus, i64), String> {
+    let data = l1_client
+        .get_account_data(&find_settlement_pda(&[STATE_ROOT_SEED, &l2_slot.to_le_bytes()]))
+        .await
+        .map_err(|e| format!("Failed to read state root for L2 slot {}: {}", l2_slot, e))?;
+    let fields = data
+        .get(STATE_ROOT_DEADLINE_OFFSET..STATE_ROOT_DEADLINE_OFFSET + 8 + 1)
+        .ok_or_else(|| format!("StateRootAccount for L2 slot {} too short", l2_slot))?;
+    let challenge_deadline = i64::from_le_bytes(fields[..8].try_into().unwrap());
+    let status = SettlementRootStatus::deserialize(&mut &fields[8..])
+        .map_err(|e| format!("Unreadable status for L2 slot {}: {}", l2_slot, e))?;
+    Ok((status, challenge_deadline))
+}
+
+/// File ChallengeStateRoot against the root for `l2_slot` with `evidence`
+/// as the proof data, unless our game against it is already open. The bond
+/// is the settlement config's `min_challenger_bond`.
+async fn dispute_state_root(
+    l1_client: &RpcClient,
+    config: &RelayerConfig,
+    l2_slot: u64,
+    evidence: &str,
+) -> Result<RootDispute, String> {
+    let challenger = config.relayer_keypair.pubkey();
+    let challenge_pda = find_settlement_pda(&[CHALLENGE_SEED, &l2_slot.to_le_bytes(), challenger.as_ref()]);
+    if account_exists(l1_client, &challenge_pda).await? {
+        return Ok(RootDispute::Open);
+    }
+
+    let (status, challenge_deadline) = read_root_status(l1_client, l2_slot).await?;
+    match status {
+        SettlementRootStatus::Invalidated => return Ok(RootDispute::Invalidated),
+        SettlementRootStatus::Finalized => return Ok(RootDispute::TooLate),
+        SettlementRootStatus::Posted | SettlementRootStatus::Challenged => {}
+    }
+    let l1_slot = l1_client
+        .get_slot()
+        .await
+        .map_err(|e| format!("Failed to read L1 slot: {}", e))?;
+    if l1_slot as i64 > challenge_deadline {
+        return Ok(RootDispute::TooLate);
+    }
+
+    let settlement_config = l1_client
+        .get_account_data(&find_settlement_pda(&[SETTLEMENT_CONFIG_SEED]))
+        .await
+        .map_err(|e| format!("Failed to read SettlementConfig: {}", e))?;
+    let bond = settlement_config
+        .get(SETTLEMENT_MIN_BOND_OFFSET..SETTLEMENT_MIN_BOND_OFFSET + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or("SettlementConfig too short")?;
+    let rent = l1_client
+        .get_minimum_balance_for_rent_exemption(SETTLEMENT_CHALLENGE_SIZE)
+        .await
+        .map_err(|e| format!("Failed to read rent: {}", e))?;
+    let balance = l1_client
+        .get_balance(&challenger)
+        .await
+        .map_err(|e| format!("Failed to read challenger balance: {}", e))?;
+    if balance <= bond.saturating_add(rent) {
+        return Err(format!(
+            "Challenger balance {} cannot cover bond {} plus rent {}",
+            balance, bond, rent
+        ));
+    }
+
+    let ix = build_challenge_state_root_ix(&challenger, l2_slot, evidence.as_bytes().to_vec());
+    send_with_retry(l1_client, config, &[ix]).await.map(RootDispute::Filed)
+}
+
+/// Play every dispute game we started: answer the defender's midpoint
+/// hashes from our own L2 index, and record how each game ended.
+async fn play_root_disputes(
+    l1_client: &RpcClient,
+    config: &RelayerConfig,
+    store: &mut RelayerStore,
+) -> Result<(), String> {
+    let disputes = store.open_root_disputes()?;
+    if disputes.is_empty() {
+        return Ok(());
+    }
+    let challenger = config.relayer_keypair.pubkey();
+    let l1_slot = l1_client
+        .get_slot()
+        .await
+        .map_err(|e| format!("Failed to read L1 slot: {}", e))?;
+
+    for l2_slot in disputes {
+        let challenge_pda = find_settlement_pda(&[CHALLENGE_SEED, &l2_slot.to_le_bytes(), challenger.as_ref()]);
+        let account = l1_client
+            .get_account_with_commitment(&challenge_pda, l1_client.commitment())
+            .await
+            .map_err(|e| format!("Failed to read challenge for L2 slot {}: {}", l2_slot, e))?
+            .value;
+        let game = match account {
+            Some(account) => Some(
+                DisputeGame::parse(&account.data)
+                    .ok_or_else(|| format!("Unreadable challenge for L2 slot {}", l2_slot))?,
+            ),
+            None => None,
+        };
+        let game = match game {
+            Some(game) if game.active => game,
+            _ => {
+                // Settled games are closed; the root's status says who won
+                let (status, _) = read_root_status(l1_client, l2_slot).await?;
+                if status == SettlementRootStatus::Invalidated {
+                    println!("WON ROOT DISPUTE: l2_slot={}", l2_slot);
+                    store.set_dispute_state(l2_slot, DisputeState::Won)?;
+                } else {
+                    eprintln!(
+                        "[ALERT] Dispute against the settlement root for L2 slot {} was lost; the root stands",
+                        l2_slot
+                    );
+                    store.set_dispute_state(l2_slot, DisputeState::Lost)?;
+                }
+                continue;
+            }
+        };
+
+        match dispute_move(&game, l1_slot) {
+            DisputeMove::Wait => {}
+            DisputeMove::DefenderTimedOut => match resolve_timed_out_dispute(l1_client, config, l2_slot, &game).await {
+                // The next poll sees the closed game and records the win
+                Ok(tx_sig) => println!(
+                    "RESOLVED ROOT DISPUTE: l2_slot={} round={} tx={}",
+                    l2_slot, game.round, tx_sig
+                ),
+                Err(e) => eprintln!(
+                    "[WATCH] Defender missed round {} for L2 slot {} but ResolveChallenge failed: {}",
+                    game.round, l2_slot, e
+                ),
+            },
+            DisputeMove::Missed => eprintln!(
+                "[ALERT] Round {} of the dispute over L2 slot {} lapsed unanswered",
+                game.round, l2_slot
+            ),
+            DisputeMove::Respond => {
+                let Some(blocks) = read_published_batch(l1_client, l2_slot).await? else {
+                    eprintln!(
+                        "[ALERT] Cannot answer round {} of the dispute over L2 slot {}: its batch is not published",
+                        game.round, l2_slot
+                    );
+                    continue;
+                };
+                let step = game.mid_step();
+                let records = store.events_through::<L2Event>(Queue::Burns, l2_slot)?;
+                let agree = batch_step_root(&records, &blocks, step)? == game.mid_hash;
+                let ix = build_respond_bisection_ix(&challenger, l2_slot, agree);
+                match send_with_retry(l1_client, config, &[ix]).await {
+                    Ok(tx_sig) => println!(
+                        "ANSWERED BISECTION: l2_slot={} round={} step={} agree={} tx={}",
+                        l2_slot, game.round, step, agree, tx_sig
+                    ),
+                    Err(e) => eprintln!(
+                        "[WATCH] Failed to answer round {} for L2 slot {}: {}",
+                        game.round, l2_slot, e
+                    ),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Send ResolveChallenge for a game the defender let lapse. The defender's
+/// MYTH bond is slashed to our associated token account, created first if
+/// missing; with no sequencer set there is no bond and the token accounts
+/// are unused.
+async fn resolve_timed_out_dispute(
+    l1_client: &RpcClient,
+    config: &RelayerConfig,
+    l2_slot: u64,
+    game: &DisputeGame,
+) -> Result<Signature, String> {
+    let challenger = config.relayer_keypair.pubkey();
+    let set = l1_client
+        .get_account_with_commitment(&find_settlement_pda(&[SEQUENCER_SET_SEED]), l1_client.commitment())
+        .await
+        .map_err(|e| format!("Failed to read the sequencer set: {}", e))?
+        .value;
+    // SequencerSet: is_initialized, myth_mint
+    let myth_mint = set
+        .filter(|set| set.data.first() == Some(&1))
+        .map(|set| {
+            set.data
+                .get(1..33)
+                .map(|b| Pubkey::new_from_array(b.try_into().unwrap()))
+                .ok_or("SequencerSet too short")
+        })
+        .transpose()?;
+
+    let mut ixs = Vec::new();
+    let (challenger_token, token_program) = match myth_mint {
+        Some(myth_mint) => {
+            let (token_program, _) = fetch_mint(l1_client, &myth_mint).await?;
+            let (ata, create_ata_ix) = build_create_ata_ix(&challenger, &challenger, &myth_mint, &token_program);
+            ixs.push(create_ata_ix);
+            (ata, token_program)
+        }
+        None => (challenger, solana_sdk::system_program::id()),
+    };
+    ixs.push(build_resolve_challenge_ix(
+        &challenger,
+        l2_slot,
+        &game.defender,
+        &challenger_token,
+        &token_program,
+    ));
+    send_with_retry(l1_client, config, &ixs).await
+}
+
+fn dispute_move(game: &DisputeGame, l1_slot: u64) -> DisputeMove {
+    let lapsed = l1_slot > game.round_deadline;
+    match (game.turn, lapsed) {
+        (DisputeTurn::Challenger, false) => DisputeMove::Respond,
+        (DisputeTurn::Challenger, true) => DisputeMove::Missed,
+        (DisputeTurn::Defender | DisputeTurn::OneStep, false) => DisputeMove::Wait,
+        (DisputeTurn::Defender | DisputeTurn::OneStep, true) => DisputeMove::DefenderTimedOut,
+    }
+}
+
+/// The transaction batch published for the root at `l2_slot`, or None
+/// while it is not sealed on L1.
+async fn read_published_batch(l1_client: &RpcClient, l2_slot: u64) -> Result<Option<Vec<L2BatchBlock>>, String> {
+    let slot_bytes = l2_slot.to_le_bytes();
+    let header_account = l1_client
+        .get_account_with_commitment(&find_settlement_pda(&[BATCH_DATA_SEED, &slot_bytes]), l1_client.commitment())
+        .await
+        .map_err(|e| format!("Failed to read batch header for L2 slot {}: {}", l2_slot, e))?
+        .value;
+    let Some(header) = header_account.map(|account| account.data) else {
+        return Ok(None);
+    };
+    if header.get(BATCH_AVAILABLE_OFFSET) != Some(&1) {
+        return Ok(None);
+    }
+    let chunk_count = header
+        .get(BATCH_CHUNK_COUNT_OFFSET..BATCH_CHUNK_COUNT_OFFSET + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| format!("Batch header for L2 slot {} too short", l2_slot))?;
+
+    let chunk_pdas: Vec<Pubkey> = (0..chunk_count as u16)
+        .map(|i| find_settlement_pda(&[BATCH_CHUNK_SEED, &slot_bytes, &i.to_le_bytes()]))
+        .collect();
+    let mut bytes = Vec::new();
+    // getMultipleAccounts takes at most 100 addresses
+    for pdas in chunk_pdas.chunks(100) {
+        let accounts = l1_client
+            .get_multiple_accounts(pdas)
+            .await
+            .map_err(|e| format!("Failed to read batch chunks for L2 slot {}: {}", l2_slot, e))?;
+        for (pda, account) in pdas.iter().zip(accounts) {
+            let account = account.ok_or_else(|| format!("Batch chunk {} is missing", pda))?;
+            bytes.extend_from_slice(&account.data);
+        }
+    }
+    Vec::<L2BatchBlock>::try_from_slice(&bytes)
+        .map(Some)
+        .map_err(|e| format!("Unreadable batch for L2 slot {}: {}", l2_slot, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A settlement ChallengeAccount mid-game, laid out field by field.
+    fn challenge_account(status: u8, turn: u8, round_deadline: u64) -> Vec<u8> {
+        let mut data = vec![0u8; CHALLENGE_STATUS_OFFSET];
+        data.push(status);
+        data.push(0); // bump
+        data.extend_from_slice(&[9; 32]); // defender
+        data.resize(CHALLENGE_GAME_OFFSET, 0);
+        data.extend_from_slice(&2u32.to_le_bytes()); // lo_step
+        data.extend_from_slice(&[1; 32]); // lo_hash
+        data.extend_from_slice(&7u32.to_le_bytes()); // hi_step
+        data.extend_from_slice(&[2; 32]); // hi_hash
+        data.extend_from_slice(&[3; 32]); // mid_hash
+        data.push(turn);
+        data.extend_from_slice(&3u16.to_le_bytes()); // round
+        data.extend_from_slice(&round_deadline.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_dispute_game_reads_the_settlement_layout() {
+        let data = challenge_account(0, 1, 500);
+        assert_eq!(data.len(), SETTLEMENT_CHALLENGE_SIZE);
+        let game = DisputeGame::parse(&data).unwrap();
+        assert!(game.active);
+        assert_eq!(game.defender, Pubkey::new_from_array([9; 32]));
+        assert_eq!((game.lo_step, game.hi_step, game.mid_step()), (2, 7, 4));
+        assert_eq!((game.mid_hash, game.turn, game.round), ([3; 32], DisputeTurn::Challenger, 3));
+
+        assert!(!DisputeGame::parse(&challenge_account(2, 1, 500)).unwrap().active);
+        assert!(DisputeGame::parse(&data[..SETTLEMENT_CHALLENGE_SIZE - 1]).is_none());
+    }
+
+    #[test]
+    fn test_dispute_move_answers_in_time_and_flags_lapsed_rounds() {
+        let game = |turn| DisputeGame::parse(&challenge_account(0, turn, 500)).unwrap();
+        assert_eq!(dispute_move(&game(1), 500), DisputeMove::Respond);
+        assert_eq!(dispute_move(&game(1), 501), DisputeMove::Missed);
+        assert_eq!(dispute_move(&game(0), 500), DisputeMove::Wait);
+        assert_eq!(dispute_move(&game(2), 500), DisputeMove::Wait);
+        assert_eq!(dispute_move(&game(0), 501), DisputeMove::DefenderTimedOut);
+        assert_eq!(dispute_move(&game(2), 501), DisputeMove::DefenderTimedOut);
+    }
+
+    #[test]
+    fn test_root_dispute_instruction_layouts() {
+        let challenger = Pubkey::new_unique();
+        let challenge_pda = find_settlement_pda(&[CHALLENGE_SEED, &100u64.to_le_bytes(), challenger.as_ref()]);
+
+        let ix = build_challenge_state_root_ix(&challenger, 100, b"evidence".to_vec());
+        let mut expected = vec![IX_CHALLENGE_STATE_ROOT];
+        expected.extend_from_slice(&100u64.to_le_bytes());
+        expected.push(FRAUD_PROOF_INVALID_STATE_TRANSITION);
+        expected.extend_from_slice(&8u32.to_le_bytes());
+        expected.extend_from_slice(b"evidence");
+        assert_eq!(ix.data, expected);
+        assert_eq!(ix.accounts.len(), 6);
+        assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+        assert_eq!(ix.accounts[2].pubkey, find_settlement_pda(&[STATE_ROOT_SEED, &100u64.to_le_bytes()]));
+        assert_eq!(ix.accounts[3].pubkey, challenge_pda);
+        assert_eq!(ix.accounts[5].pubkey, find_settlement_pda(&[SEQUENCER_SET_SEED]));
+
+        let ix = build_respond_bisection_ix(&challenger, 100, true);
+        assert_eq!(ix.data, [&[IX_RESPOND_BISECTION][..], &100u64.to_le_bytes(), &[1]].concat());
+        assert!(ix.accounts[0].is_signer && !ix.accounts[0].is_writable);
+        assert_eq!(ix.accounts[2].pubkey, challenge_pda);
+        assert!(ix.accounts[2].is_writable);
+
+        let (defender, challenger_token, token_program) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = build_resolve_challenge_ix(&challenger, 100, &defender, &challenger_token, &token_program);
+        assert_eq!(ix.data, [&[IX_RESOLVE_CHALLENGE][..], &100u64.to_le_bytes(), challenger.as_ref()].concat());
+        assert_eq!(ix.accounts.len(), 10);
+        assert!(ix.accounts[0].is_signer);
+        assert!(ix.accounts[1].is_writable && ix.accounts[1].pubkey == find_settlement_pda(&[SETTLEMENT_CONFIG_SEED]));
+        assert_eq!(ix.accounts[3].pubkey, challenge_pda);
+        assert!(ix.accounts[4].is_writable && ix.accounts[4].pubkey == challenger);
+        assert!(ix.accounts[5].is_writable && ix.accounts[5].pubkey == defender);
+        assert_eq!(ix.accounts[7].pubkey, find_settlement_pda(&[SEQUENCER_VAULT_SEED]));
+        assert_eq!((ix.accounts[8].pubkey, ix.accounts[9].pubkey), (challenger_token, token_program));
+    }
+}
@@ -73,6 +73,7 @@ pub struct SubmitResultArgs {
     pub output_hash: [u8; 32],
     pub logit_fingerprint: Vec<[f32; 4]>,
     pub compute_units_used: u64,
+    pub output_tokens: u32,
 }
 
 #[derive(BorshSerialize)]
@@ -115,6 +116,13 @@ pub fn find_inference_result(request_key: &Pubkey) -> (Pubkey, u8) {
     )
 }
 
+pub fn find_logit_fingerprint(request_key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LOGITS_SEED, request_key.as_ref()],
+        &AI_PRECOMPILES_PROGRAM_ID,
+    )
+}
+
 pub fn find_verification(request_key: &Pubkey, verifier: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(
         &[VERIFICATION_SEED, request_key.as_ref(), verifier.as_ref()],
@@ -295,22 +303,28 @@ pub fn create_request_inference_instruction(
 ///   0. `[signer, writable]` validator_authority (payer)
 ///   1. `[writable]` inference_request PDA
 ///   2. `[writable]` inference_result PDA (seeds: ["result", request_key])
-///   3. `[]` ai_validator PDA
+///   3. `[writable]` ai_validator PDA
 ///   4. `[]` system_program
+///   5. `[writable]` logit fingerprint PDA (seeds: ["logits", request_key])
+///   6. `[]` ai_config PDA
 pub fn create_submit_result_instruction(
     validator_authority: &Pubkey,
     request_key: &Pubkey,
     output_hash: [u8; 32],
     logit_fingerprint: Vec<[f32; 4]>,
     compute_units_used: u64,
+    output_tokens: u32,
 ) -> Instruction {
     let (result_pda, _) = find_inference_result(request_key);
     let (validator_pda, _) = find_ai_validator(validator_authority);
+    let (fingerprint_pda, _) = find_logit_fingerprint(request_key);
+    let (config_pda, _) = find_ai_config();
 
     let args = SubmitResultArgs {
         output_hash,
         logit_fingerprint,
         compute_units_used,
+        output_tokens,
     };
     let mut data = vec![IX_SUBMIT_RESULT];
     args.serialize(&mut data).unwrap();
@@ -321,8 +335,10 @@ pub fn create_submit_result_instruction(
             AccountMeta::new(*validator_authority, true),
             AccountMeta::new(*request_key, false),
             AccountMeta::new(result_pda, false),
-            AccountMeta::new_readonly(validator_pda, false),
+            AccountMeta::new(validator_pda, false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(fingerprint_pda, false),
+            AccountMeta::new_readonly(config_pda, false),
         ],
         data,
     }
@@ -374,12 +390,14 @@ pub fn create_verify_logits_instruction(
 /// Claim the inference fee after the challenge window.
 ///
 /// Accounts:
-///   0. `[signer]` validator_authority
+///   0. `[signer, writable]` validator_authority
 ///   1. `[writable]` inference_request PDA
 ///   2. `[writable]` escrow_vault
 ///   3. `[]` ai_config PDA
 ///   4. `[writable]` foundation account
 ///   5. `[writable]` burn_address
+///   6. `[]` system_program
+///   7. `[writable]` ai_validator PDA of validator_authority
 pub fn create_claim_inference_fee_instruction(
     validator_authority: &Pubkey,
     request_key: &Pubkey,
@@ -394,12 +412,14 @@ pub fn create_claim_inference_fee_instruction(
     Instruction {
         program_id: AI_PRECOMPILES_PROGRAM_ID,
         accounts: vec![
-            AccountMeta::new_readonly(*validator_authority, true),
+            AccountMeta::new(*validator_authority, true),
             AccountMeta::new(*request_key, false),
             AccountMeta::new(*escrow_vault, false),
             AccountMeta::new_readonly(*config_key, false),
             AccountMeta::new(*foundation, false),
             AccountMeta::new(*burn_address, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_ai_validator(validator_authority).0, false),
         ],
         data,
     }
@@ -11,8 +11,15 @@
 //!   4 = ChallengeWithdrawal
 //!   5 = FinalizeWithdrawal
 //!   6 = UpdateConfig
+//!   13 = ResolveChallenge
+//!   14 = SendMessage
+//!   15 = ReceiveMessage
+//!   16 = RequestFastWithdrawal
+//!   17 = FillFastWithdrawal
+//!   18 = CloseFastWithdrawal
 
 use borsh::BorshSerialize;
+use sha2::{Digest, Sha256};
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
@@ -20,6 +27,7 @@ use solana_program::{
 };
 
 use crate::constants::*;
+use crate::settlement::find_state_root;
 
 // ── Instruction Discriminators ──────────────────────────────────────────────
 
@@ -30,6 +38,13 @@ const IX_INITIATE_WITHDRAWAL: u8 = 3;
 const IX_CHALLENGE_WITHDRAWAL: u8 = 4;
 const IX_FINALIZE_WITHDRAWAL: u8 = 5;
 const IX_UPDATE_CONFIG: u8 = 6;
+const IX_RESOLVE_CHALLENGE: u8 = 13;
+const IX_SEND_MESSAGE: u8 = 14;
+const IX_RECEIVE_MESSAGE: u8 = 15;
+const IX_REQUEST_FAST_WITHDRAWAL: u8 = 16;
+const IX_FILL_FAST_WITHDRAWAL: u8 = 17;
+const IX_CLOSE_FAST_WITHDRAWAL: u8 = 18;
+const IX_MIGRATE_WITHDRAWAL: u8 = 19;
 
 // ── Param Structs (exact Borsh match to program) ────────────────────────────
 
@@ -56,8 +71,10 @@ pub struct InitiateWithdrawalParams {
     pub recipient: Pubkey,
     pub amount: u64,
     pub token_mint: Pubkey,
-    pub merkle_proof: [u8; 32],
     pub nonce: u64,
+    pub l2_slot: u64,
+    pub leaf_index: u64,
+    pub merkle_proof: Vec<[u8; 32]>,
 }
 
 #[derive(BorshSerialize)]
@@ -69,6 +86,13 @@ pub struct ChallengeWithdrawalParams {
 #[derive(BorshSerialize)]
 pub struct FinalizeWithdrawalParams {
     pub withdrawal_nonce: u64,
+    pub leaf_index: u64,
+    pub merkle_proof: Vec<[u8; 32]>,
+}
+
+#[derive(BorshSerialize)]
+pub struct ResolveChallengeParams {
+    pub withdrawal_nonce: u64,
 }
 
 #[derive(BorshSerialize)]
@@ -77,6 +101,63 @@ pub struct UpdateConfigParams {
     pub new_challenge_period: Option<i64>,
 }
 
+/// An account the target program receives when a message is delivered.
+#[derive(BorshSerialize, Debug, Clone, PartialEq)]
+pub struct MessageAccount {
+    pub pubkey: Pubkey,
+    pub is_writable: bool,
+}
+
+/// Cross-domain message; same layout in both bridge programs.
+#[derive(BorshSerialize, Debug, Clone)]
+pub struct CrossDomainMessage {
+    pub nonce: u64,
+    pub sender: Pubkey,
+    pub target_program: Pubkey,
+    pub accounts: Vec<MessageAccount>,
+    pub payload: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+pub struct SendMessageParams {
+    pub target_program: Pubkey,
+    pub accounts: Vec<MessageAccount>,
+    pub payload: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+pub struct ReceiveMessageParams {
+    pub message: CrossDomainMessage,
+    pub l2_slot: u64,
+    pub leaf_index: u64,
+    pub merkle_proof: Vec<[u8; 32]>,
+}
+
+#[derive(BorshSerialize)]
+pub struct RequestFastWithdrawalParams {
+    pub withdrawal_nonce: u64,
+    pub fee: u64,
+}
+
+#[derive(BorshSerialize)]
+pub struct FillFastWithdrawalParams {
+    pub withdrawal_nonce: u64,
+    pub fee: u64,
+}
+
+#[derive(BorshSerialize)]
+pub struct CloseFastWithdrawalParams {
+    pub withdrawal_nonce: u64,
+}
+
+#[derive(BorshSerialize)]
+pub struct MigrateWithdrawalParams {
+    pub withdrawal_nonce: u64,
+    pub l2_slot: u64,
+    pub leaf_index: u64,
+    pub merkle_proof: Vec<[u8; 32]>,
+}
+
 // ── PDA Helpers ─────────────────────────────────────────────────────────────
 
 pub fn find_bridge_config() -> (Pubkey, u8) {
@@ -98,6 +179,46 @@ pub fn find_withdrawal(nonce: u64) -> (Pubkey, u8) {
     )
 }
 
+pub fn find_challenge_bond(withdrawal_nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CHALLENGE_BOND_SEED, &withdrawal_nonce.to_le_bytes()],
+        &BRIDGE_PROGRAM_ID,
+    )
+}
+
+pub fn find_fast_withdrawal(withdrawal_nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[FAST_WITHDRAWAL_SEED, &withdrawal_nonce.to_le_bytes()],
+        &BRIDGE_PROGRAM_ID,
+    )
+}
+
+pub fn find_processed_message(nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MESSAGE_SEED, &nonce.to_le_bytes()], &BRIDGE_PROGRAM_ID)
+}
+
+/// Signer PDA that L1 targets see for messages sent by `l2_sender`.
+pub fn find_message_origin(l2_sender: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MESSAGE_ORIGIN_SEED, l2_sender.as_ref()], &BRIDGE_PROGRAM_ID)
+}
+
+// ── Message Hashing ─────────────────────────────────────────────────────────
+
+/// sha256 of the Borsh-encoded message (attested on L2, proven on L1).
+pub fn message_hash(message: &CrossDomainMessage) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(borsh::to_vec(message).unwrap());
+    hasher.finalize().into()
+}
+
+/// Withdrawal-tree leaf for an L2→L1 message: sha256(0x02 || message_hash).
+pub fn message_leaf_hash(message_hash: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x02]);
+    hasher.update(message_hash);
+    hasher.finalize().into()
+}
+
 // ── Instruction Builders ────────────────────────────────────────────────────
 
 /// Initialize the bridge.
@@ -205,32 +326,41 @@ pub fn create_deposit_sol_instruction(
     }
 }
 
-/// Initiate a withdrawal (sequencer only).
+/// Initiate a withdrawal (sequencer only). `merkle_proof` / `leaf_index`
+/// prove the withdrawal leaf against the settlement state root at `l2_slot`.
+/// `payer` escrows a 0.1 SOL relayer bond on top of the request's rent.
 ///
 /// Accounts:
 ///   0. `[signer]` sequencer
 ///   1. `[signer, writable]` payer
 ///   2. `[writable]` withdrawal_request PDA
-///   3. `[]` bridge_config PDA
+///   3. `[writable]` bridge_config PDA
 ///   4. `[]` system_program
+///   5. `[]` settlement state_root PDA
+#[allow(clippy::too_many_arguments)]
 pub fn create_initiate_withdrawal_instruction(
     sequencer: &Pubkey,
     payer: &Pubkey,
     recipient: &Pubkey,
     amount: u64,
     token_mint: &Pubkey,
-    merkle_proof: [u8; 32],
     nonce: u64,
+    l2_slot: u64,
+    leaf_index: u64,
+    merkle_proof: Vec<[u8; 32]>,
 ) -> Instruction {
     let (config_pda, _) = find_bridge_config();
     let (withdrawal_pda, _) = find_withdrawal(nonce);
+    let (state_root_pda, _) = find_state_root(l2_slot);
 
     let params = InitiateWithdrawalParams {
         recipient: *recipient,
         amount,
         token_mint: *token_mint,
-        merkle_proof,
         nonce,
+        l2_slot,
+        leaf_index,
+        merkle_proof,
     };
     let mut data = vec![IX_INITIATE_WITHDRAWAL];
     params.serialize(&mut data).unwrap();
@@ -241,8 +371,9 @@ pub fn create_initiate_withdrawal_instruction(
             AccountMeta::new_readonly(*sequencer, true),
             AccountMeta::new(*payer, true),
             AccountMeta::new(withdrawal_pda, false),
-            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(config_pda, false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(state_root_pda, false),
         ],
         data,
     }
@@ -254,7 +385,8 @@ pub fn create_initiate_withdrawal_instruction(
 ///   0. `[signer, writable]` challenger (posts bond)
 ///   1. `[writable]` withdrawal_request PDA
 ///   2. `[]` bridge_config PDA
-///   3. `[]` system_program
+///   3. `[writable]` challenge_bond PDA
+///   4. `[]` system_program
 pub fn create_challenge_withdrawal_instruction(
     challenger: &Pubkey,
     withdrawal_nonce: u64,
@@ -262,6 +394,7 @@ pub fn create_challenge_withdrawal_instruction(
 ) -> Instruction {
     let (config_pda, _) = find_bridge_config();
     let (withdrawal_pda, _) = find_withdrawal(withdrawal_nonce);
+    let (bond_pda, _) = find_challenge_bond(withdrawal_nonce);
 
     let params = ChallengeWithdrawalParams {
         withdrawal_nonce,
@@ -276,13 +409,69 @@ pub fn create_challenge_withdrawal_instruction(
             AccountMeta::new(*challenger, true),
             AccountMeta::new(withdrawal_pda, false),
             AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(bond_pda, false),
             AccountMeta::new_readonly(system_program::id(), false),
         ],
         data,
     }
 }
 
-/// Finalize a withdrawal after the challenge period.
+/// Resolve a challenged withdrawal. The verdict follows the settlement state
+/// root at `l2_slot` and fails until that root is Finalized or Invalidated;
+/// an upheld challenge also pays the challenger the withdrawal's relayer
+/// bond. `payee` is whoever finalization would pay: the LP if the withdrawal
+/// was filled fast, otherwise the recipient.
+///
+/// Accounts:
+///   0. `[signer]` resolver
+///   1. `[writable]` withdrawal_request PDA
+///   2. `[]` bridge_config PDA
+///   3. `[writable]` challenge_bond PDA
+///   4. `[writable]` challenger
+///   5. `[writable]` withdrawal payee
+///   6. `[]` fast_withdrawal PDA
+///   7. `[]` settlement state_root PDA
+pub fn create_resolve_challenge_instruction(
+    resolver: &Pubkey,
+    withdrawal_nonce: u64,
+    challenger: &Pubkey,
+    payee: &Pubkey,
+    l2_slot: u64,
+) -> Instruction {
+    let (config_pda, _) = find_bridge_config();
+    let (withdrawal_pda, _) = find_withdrawal(withdrawal_nonce);
+    let (bond_pda, _) = find_challenge_bond(withdrawal_nonce);
+    let (fast_withdrawal_pda, _) = find_fast_withdrawal(withdrawal_nonce);
+    let (state_root_pda, _) = find_state_root(l2_slot);
+
+    let params = ResolveChallengeParams { withdrawal_nonce };
+    let mut data = vec![IX_RESOLVE_CHALLENGE];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*resolver, true),
+            AccountMeta::new(withdrawal_pda, false),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(bond_pda, false),
+            AccountMeta::new(*challenger, false),
+            AccountMeta::new(*payee, false),
+            AccountMeta::new_readonly(fast_withdrawal_pda, false),
+            AccountMeta::new_readonly(state_root_pda, false),
+        ],
+        data,
+    }
+}
+
+/// Finalize a withdrawal after the challenge period. The settlement state root
+/// at `l2_slot` (recorded on the withdrawal request) must be finalized. If an
+/// LP filled the withdrawal fast, `recipient_token_account` must be the LP's.
+///
+/// Accounts 7 to 9 are required; transactions built for the older
+/// 7-account layout fail with NotEnoughAccountKeys. `sequencer` must be the
+/// bridge sequencer, which gets back the relayer bond it escrowed. Follow up with
+/// `create_close_fast_withdrawal_instruction` if an offer was opened.
 ///
 /// Accounts:
 ///   0. `[signer, writable]` payer / anyone can finalize
@@ -292,17 +481,31 @@ pub fn create_challenge_withdrawal_instruction(
 ///   4. `[]` token mint
 ///   5. `[]` bridge_config PDA
 ///   6. `[]` token_program
+///   7. `[]` settlement state_root PDA
+///   8. `[]` fast_withdrawal PDA
+///   9. `[writable]` sequencer
+#[allow(clippy::too_many_arguments)]
 pub fn create_finalize_withdrawal_instruction(
     payer: &Pubkey,
+    sequencer: &Pubkey,
     withdrawal_nonce: u64,
     token_mint: &Pubkey,
     recipient_token_account: &Pubkey,
+    l2_slot: u64,
+    leaf_index: u64,
+    merkle_proof: Vec<[u8; 32]>,
 ) -> Instruction {
     let (config_pda, _) = find_bridge_config();
     let (withdrawal_pda, _) = find_withdrawal(withdrawal_nonce);
     let (vault_token, _) = find_vault(token_mint);
+    let (state_root_pda, _) = find_state_root(l2_slot);
+    let (fast_withdrawal_pda, _) = find_fast_withdrawal(withdrawal_nonce);
 
-    let params = FinalizeWithdrawalParams { withdrawal_nonce };
+    let params = FinalizeWithdrawalParams {
+        withdrawal_nonce,
+        leaf_index,
+        merkle_proof,
+    };
     let mut data = vec![IX_FINALIZE_WITHDRAWAL];
     params.serialize(&mut data).unwrap();
 
@@ -316,6 +519,191 @@ pub fn create_finalize_withdrawal_instruction(
             AccountMeta::new_readonly(*token_mint, false),
             AccountMeta::new_readonly(config_pda, false),
             AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(state_root_pda, false),
+            AccountMeta::new_readonly(fast_withdrawal_pda, false),
+            AccountMeta::new(*sequencer, false),
+        ],
+        data,
+    }
+}
+
+/// Offer a pending withdrawal to LPs for `fee`, or reprice an unfilled offer.
+///
+/// Accounts:
+///   0. `[signer, writable]` withdrawal recipient
+///   1. `[]` withdrawal_request PDA
+///   2. `[writable]` fast_withdrawal PDA
+///   3. `[]` system_program
+pub fn create_request_fast_withdrawal_instruction(
+    recipient: &Pubkey,
+    withdrawal_nonce: u64,
+    fee: u64,
+) -> Instruction {
+    let (withdrawal_pda, _) = find_withdrawal(withdrawal_nonce);
+    let (fast_withdrawal_pda, _) = find_fast_withdrawal(withdrawal_nonce);
+
+    let params = RequestFastWithdrawalParams {
+        withdrawal_nonce,
+        fee,
+    };
+    let mut data = vec![IX_REQUEST_FAST_WITHDRAWAL];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*recipient, true),
+            AccountMeta::new_readonly(withdrawal_pda, false),
+            AccountMeta::new(fast_withdrawal_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Fill a fast-withdrawal offer for an SPL token withdrawal: the LP pays the
+/// recipient `amount - fee` and is paid the full amount at finalization.
+///
+/// Accounts:
+///   0. `[signer, writable]` lp
+///   1. `[]` withdrawal_request PDA
+///   2. `[writable]` fast_withdrawal PDA
+///   3. `[writable]` recipient token account
+///   4. `[]` token_program
+///   5. `[writable]` lp token account
+pub fn create_fill_fast_withdrawal_instruction(
+    lp: &Pubkey,
+    withdrawal_nonce: u64,
+    fee: u64,
+    recipient_token_account: &Pubkey,
+    lp_token_account: &Pubkey,
+) -> Instruction {
+    let (withdrawal_pda, _) = find_withdrawal(withdrawal_nonce);
+    let (fast_withdrawal_pda, _) = find_fast_withdrawal(withdrawal_nonce);
+
+    let params = FillFastWithdrawalParams {
+        withdrawal_nonce,
+        fee,
+    };
+    let mut data = vec![IX_FILL_FAST_WITHDRAWAL];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*lp, true),
+            AccountMeta::new_readonly(withdrawal_pda, false),
+            AccountMeta::new(fast_withdrawal_pda, false),
+            AccountMeta::new(*recipient_token_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(*lp_token_account, false),
+        ],
+        data,
+    }
+}
+
+/// Fill a fast-withdrawal offer for a native SOL withdrawal.
+///
+/// Accounts:
+///   0. `[signer, writable]` lp
+///   1. `[]` withdrawal_request PDA
+///   2. `[writable]` fast_withdrawal PDA
+///   3. `[writable]` recipient
+///   4. `[]` system_program
+pub fn create_fill_fast_sol_withdrawal_instruction(
+    lp: &Pubkey,
+    withdrawal_nonce: u64,
+    fee: u64,
+    recipient: &Pubkey,
+) -> Instruction {
+    let (withdrawal_pda, _) = find_withdrawal(withdrawal_nonce);
+    let (fast_withdrawal_pda, _) = find_fast_withdrawal(withdrawal_nonce);
+
+    let params = FillFastWithdrawalParams {
+        withdrawal_nonce,
+        fee,
+    };
+    let mut data = vec![IX_FILL_FAST_WITHDRAWAL];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*lp, true),
+            AccountMeta::new_readonly(withdrawal_pda, false),
+            AccountMeta::new(fast_withdrawal_pda, false),
+            AccountMeta::new(*recipient, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Close a fast-withdrawal offer once its withdrawal is finalized or
+/// cancelled, returning the rent to `offer_recipient`. Permissionless.
+///
+/// Accounts:
+///   0. `[]` withdrawal_request PDA
+///   1. `[writable]` fast_withdrawal PDA
+///   2. `[writable]` offer recipient
+pub fn create_close_fast_withdrawal_instruction(
+    withdrawal_nonce: u64,
+    offer_recipient: &Pubkey,
+) -> Instruction {
+    let (withdrawal_pda, _) = find_withdrawal(withdrawal_nonce);
+    let (fast_withdrawal_pda, _) = find_fast_withdrawal(withdrawal_nonce);
+
+    let params = CloseFastWithdrawalParams { withdrawal_nonce };
+    let mut data = vec![IX_CLOSE_FAST_WITHDRAWAL];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(withdrawal_pda, false),
+            AccountMeta::new(fast_withdrawal_pda, false),
+            AccountMeta::new(*offer_recipient, false),
+        ],
+        data,
+    }
+}
+
+/// Rewrite a withdrawal opened before the upgrade in the current layout,
+/// proving it against the state root posted for `l2_slot` (sequencer only).
+/// The freed rent goes to the sequencer.
+///
+/// Accounts:
+///   0. `[signer, writable]` sequencer
+///   1. `[writable]` withdrawal_request PDA
+///   2. `[]` bridge_config PDA
+///   3. `[]` settlement state_root PDA
+pub fn create_migrate_withdrawal_instruction(
+    sequencer: &Pubkey,
+    withdrawal_nonce: u64,
+    l2_slot: u64,
+    leaf_index: u64,
+    merkle_proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let (config_pda, _) = find_bridge_config();
+    let (withdrawal_pda, _) = find_withdrawal(withdrawal_nonce);
+    let (state_root_pda, _) = find_state_root(l2_slot);
+
+    let params = MigrateWithdrawalParams {
+        withdrawal_nonce,
+        l2_slot,
+        leaf_index,
+        merkle_proof,
+    };
+    let mut data = vec![IX_MIGRATE_WITHDRAWAL];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*sequencer, true),
+            AccountMeta::new(withdrawal_pda, false),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new_readonly(state_root_pda, false),
         ],
         data,
     }
@@ -349,3 +737,88 @@ pub fn create_update_config_instruction(
         data,
     }
 }
+
+/// Send an L1→L2 message. `sender` may be a program PDA signing via CPI.
+///
+/// Accounts:
+///   0. `[signer]` sender
+///   1. `[writable]` bridge_config PDA
+pub fn create_send_message_instruction(
+    sender: &Pubkey,
+    target_program: &Pubkey,
+    accounts: Vec<MessageAccount>,
+    payload: Vec<u8>,
+) -> Instruction {
+    let (config_pda, _) = find_bridge_config();
+
+    let params = SendMessageParams {
+        target_program: *target_program,
+        accounts,
+        payload,
+    };
+    let mut data = vec![IX_SEND_MESSAGE];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*sender, true),
+            AccountMeta::new(config_pda, false),
+        ],
+        data,
+    }
+}
+
+/// Deliver an L2→L1 message proven against the finalized state root at `l2_slot`.
+///
+/// Accounts:
+///   0. `[signer, writable]` payer
+///   1. `[]` bridge_config PDA
+///   2. `[writable]` processed_message PDA
+///   3. `[]` settlement state_root PDA
+///   4. `[]` message_origin PDA
+///   5. `[]` target program
+///   6. `[]` system_program
+///   7+ message accounts, in order
+pub fn create_receive_message_instruction(
+    payer: &Pubkey,
+    message: CrossDomainMessage,
+    l2_slot: u64,
+    leaf_index: u64,
+    merkle_proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let (config_pda, _) = find_bridge_config();
+    let (processed_pda, _) = find_processed_message(message.nonce);
+    let (state_root_pda, _) = find_state_root(l2_slot);
+    let (origin_pda, _) = find_message_origin(&message.sender);
+
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(config_pda, false),
+        AccountMeta::new(processed_pda, false),
+        AccountMeta::new_readonly(state_root_pda, false),
+        AccountMeta::new_readonly(origin_pda, false),
+        AccountMeta::new_readonly(message.target_program, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(message.accounts.iter().map(|a| AccountMeta {
+        pubkey: a.pubkey,
+        is_signer: false,
+        is_writable: a.is_writable,
+    }));
+
+    let params = ReceiveMessageParams {
+        message,
+        l2_slot,
+        leaf_index,
+        merkle_proof,
+    };
+    let mut data = vec![IX_RECEIVE_MESSAGE];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
@@ -1,16 +1,23 @@
 //! L2 Bridge instruction builders — wrapped token management, mint/burn lifecycle.
 //!
 //! Matches: programs/bridge-l2/src/lib.rs
-//! Program ID: MythBrdgL2111111111111111111111111111111111
+//! Program ID: 5t8JwXzGQ3c7PCY6p6oJqZgFt8gff2d6uTLrqa1jFrKP
 //!
 //! Instructions:
 //!   0 = Initialize
-//!   1 = RegisterWrappedToken
-//!   2 = MintWrapped
-//!   3 = BurnWrapped
 //!   4 = UpdateConfig
+//!   8 = RegisterWrappedToken
+//!   9 = MintWrapped
+//!  10 = BurnWrapped
+//!  11 = AttestDeposit
+//!  12 = AddRelayer
+//!  13 = RemoveRelayer
+//!  14 = SendMessage
+//!  15 = ReceiveMessage
+//!  16 = UpdateWrappedLimits
 
 use borsh::BorshSerialize;
+use sha2::{Digest, Sha256};
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
@@ -18,15 +25,22 @@ use solana_program::{
     sysvar,
 };
 
+use crate::bridge::{message_hash, CrossDomainMessage, MessageAccount};
 use crate::constants::*;
 
 // ── Instruction Discriminators ──────────────────────────────────────────────
 
 const IX_INITIALIZE: u8 = 0;
-const IX_REGISTER_WRAPPED_TOKEN: u8 = 1;
-const IX_MINT_WRAPPED: u8 = 2;
-const IX_BURN_WRAPPED: u8 = 3;
 const IX_UPDATE_CONFIG: u8 = 4;
+const IX_REGISTER_WRAPPED_TOKEN: u8 = 8;
+const IX_MINT_WRAPPED: u8 = 9;
+const IX_BURN_WRAPPED: u8 = 10;
+const IX_ATTEST_DEPOSIT: u8 = 11;
+const IX_ADD_RELAYER: u8 = 12;
+const IX_REMOVE_RELAYER: u8 = 13;
+const IX_SEND_MESSAGE: u8 = 14;
+const IX_RECEIVE_MESSAGE: u8 = 15;
+const IX_UPDATE_WRAPPED_LIMITS: u8 = 16;
 
 // ── Param Structs (exact Borsh match to program) ────────────────────────────
 
@@ -48,6 +62,15 @@ pub struct MintWrappedParams {
     pub amount: u64,
     pub l1_mint: Pubkey,
     pub l1_tx_signature: [u8; 64],
+    pub l1_decimals: u8,
+}
+
+#[derive(BorshSerialize)]
+pub struct UpdateWrappedLimitsParams {
+    pub l1_mint: Pubkey,
+    pub min_release: u64,
+    pub max_release: u64,
+    pub daily_release_limit: u64,
 }
 
 #[derive(BorshSerialize)]
@@ -62,6 +85,31 @@ pub struct UpdateConfigParams {
     pub new_relayer: Option<Pubkey>,
 }
 
+#[derive(BorshSerialize)]
+pub struct AttestDepositParams {
+    pub l1_deposit_nonce: u64,
+    pub deposit_hash: [u8; 32],
+}
+
+#[derive(BorshSerialize)]
+pub struct SendMessageParams {
+    pub target_program: Pubkey,
+    pub accounts: Vec<MessageAccount>,
+    pub payload: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+pub struct ReceiveMessageParams {
+    pub message: CrossDomainMessage,
+    pub l1_tx_signature: [u8; 64],
+}
+
+#[derive(BorshSerialize)]
+pub struct UpdateCommitteeParams {
+    pub relayer: Pubkey,
+    pub threshold: u8,
+}
+
 // ── PDA Helpers ─────────────────────────────────────────────────────────────
 
 pub fn find_l2_bridge_config() -> (Pubkey, u8) {
@@ -89,6 +137,43 @@ pub fn find_processed_deposit(l1_deposit_nonce: u64) -> (Pubkey, u8) {
     )
 }
 
+pub fn find_deposit_attestation(l1_deposit_nonce: u64, deposit_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[DEPOSIT_ATTESTATION_SEED, &l1_deposit_nonce.to_le_bytes(), deposit_hash],
+        &BRIDGE_L2_PROGRAM_ID,
+    )
+}
+
+/// Signer PDA that L2 targets see for messages sent by `l1_sender`.
+pub fn find_l2_message_origin(l1_sender: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[MESSAGE_ORIGIN_SEED, l1_sender.as_ref()],
+        &BRIDGE_L2_PROGRAM_ID,
+    )
+}
+
+// ── Deposit Hash ────────────────────────────────────────────────────────────
+
+/// sha256(l1_tx_signature || nonce || amount || recipient [|| l1_mint]).
+/// Pass `None` for native MYTH releases and the L1 mint for wrapped tokens.
+pub fn deposit_hash(
+    l1_tx_signature: &[u8; 64],
+    l1_deposit_nonce: u64,
+    amount: u64,
+    recipient: &Pubkey,
+    l1_mint: Option<&Pubkey>,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(l1_tx_signature);
+    hasher.update(l1_deposit_nonce.to_le_bytes());
+    hasher.update(amount.to_le_bytes());
+    hasher.update(recipient.as_ref());
+    if let Some(mint) = l1_mint {
+        hasher.update(mint.as_ref());
+    }
+    hasher.finalize().into()
+}
+
 // ── Instruction Builders ────────────────────────────────────────────────────
 
 /// Initialize the L2 bridge.
@@ -164,12 +249,18 @@ pub fn create_register_wrapped_token_instruction(
 ///   0. `[signer]` relayer
 ///   1. `[signer, writable]` payer
 ///   2. `[]` l2_bridge_config PDA
-///   3. `[]` wrapped_token_info PDA
+///   3. `[writable]` wrapped_token_info PDA
 ///   4. `[writable]` l2_mint account
 ///   5. `[writable]` recipient token account (ATA)
 ///   6. `[writable]` processed_deposit PDA
 ///   7. `[]` token_program
 ///   8. `[]` system_program
+///   9. `[writable]` deposit_attestation PDA (checked and closed when a relayer committee is set)
+///
+/// `l1_decimals` is read from the L1 mint and must match the registered
+/// wrapped mint. `token_program` is the owner of the L2 mint (SPL Token or
+/// Token-2022).
+#[allow(clippy::too_many_arguments)]
 pub fn create_mint_wrapped_instruction(
     relayer: &Pubkey,
     payer: &Pubkey,
@@ -178,14 +269,22 @@ pub fn create_mint_wrapped_instruction(
     amount: u64,
     l1_mint: &Pubkey,
     l1_tx_signature: [u8; 64],
+    l1_decimals: u8,
+    token_program: &Pubkey,
 ) -> Instruction {
     let (config_pda, _) = find_l2_bridge_config();
     let (wrapped_info_pda, _) = find_wrapped_token_info(l1_mint);
     let (l2_mint_pda, _) = find_l2_mint(l1_mint);
     let (processed_pda, _) = find_processed_deposit(l1_deposit_nonce);
+    let hash = deposit_hash(&l1_tx_signature, l1_deposit_nonce, amount, recipient, Some(l1_mint));
+    let (attestation_pda, _) = find_deposit_attestation(l1_deposit_nonce, &hash);
 
     let recipient_ata =
-        spl_associated_token_account::get_associated_token_address(recipient, &l2_mint_pda);
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            recipient,
+            &l2_mint_pda,
+            token_program,
+        );
 
     let params = MintWrappedParams {
         l1_deposit_nonce,
@@ -193,6 +292,7 @@ pub fn create_mint_wrapped_instruction(
         amount,
         l1_mint: *l1_mint,
         l1_tx_signature,
+        l1_decimals,
     };
     let mut data = vec![IX_MINT_WRAPPED];
     params.serialize(&mut data).unwrap();
@@ -203,12 +303,50 @@ pub fn create_mint_wrapped_instruction(
             AccountMeta::new_readonly(*relayer, true),
             AccountMeta::new(*payer, true),
             AccountMeta::new_readonly(config_pda, false),
-            AccountMeta::new_readonly(wrapped_info_pda, false),
+            AccountMeta::new(wrapped_info_pda, false),
             AccountMeta::new(l2_mint_pda, false),
             AccountMeta::new(recipient_ata, false),
             AccountMeta::new(processed_pda, false),
-            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*token_program, false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(attestation_pda, false),
+        ],
+        data,
+    }
+}
+
+/// Set the release limits of a wrapped token, in its base units (admin only).
+/// Newly registered tokens have zero limits and mint nothing until this runs.
+///
+/// Accounts:
+///   0. `[signer]` admin
+///   1. `[]` l2_bridge_config PDA
+///   2. `[writable]` wrapped_token_info PDA
+pub fn create_update_wrapped_limits_instruction(
+    admin: &Pubkey,
+    l1_mint: &Pubkey,
+    min_release: u64,
+    max_release: u64,
+    daily_release_limit: u64,
+) -> Instruction {
+    let (config_pda, _) = find_l2_bridge_config();
+    let (wrapped_info_pda, _) = find_wrapped_token_info(l1_mint);
+
+    let params = UpdateWrappedLimitsParams {
+        l1_mint: *l1_mint,
+        min_release,
+        max_release,
+        daily_release_limit,
+    };
+    let mut data = vec![IX_UPDATE_WRAPPED_LIMITS];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_L2_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(wrapped_info_pda, false),
         ],
         data,
     }
@@ -223,18 +361,25 @@ pub fn create_mint_wrapped_instruction(
 ///   3. `[]` wrapped_token_info PDA
 ///   4. `[writable]` l2_bridge_config PDA
 ///   5. `[]` token_program
+///
+/// `token_program` is the owner of the L2 mint (SPL Token or Token-2022).
 pub fn create_burn_wrapped_instruction(
     burner: &Pubkey,
     amount: u64,
     l1_mint: &Pubkey,
     l1_recipient: [u8; 32],
+    token_program: &Pubkey,
 ) -> Instruction {
     let (config_pda, _) = find_l2_bridge_config();
     let (wrapped_info_pda, _) = find_wrapped_token_info(l1_mint);
     let (l2_mint_pda, _) = find_l2_mint(l1_mint);
 
     let burner_ata =
-        spl_associated_token_account::get_associated_token_address(burner, &l2_mint_pda);
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            burner,
+            &l2_mint_pda,
+            token_program,
+        );
 
     let params = BurnWrappedParams {
         amount,
@@ -252,7 +397,7 @@ pub fn create_burn_wrapped_instruction(
             AccountMeta::new(l2_mint_pda, false),
             AccountMeta::new_readonly(wrapped_info_pda, false),
             AccountMeta::new(config_pda, false),
-            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*token_program, false),
         ],
         data,
     }
@@ -282,3 +427,211 @@ pub fn create_update_config_instruction(
         data,
     }
 }
+
+/// Attest that `deposit_hash` describes L1 deposit `l1_deposit_nonce`
+/// (relayer committee members only).
+///
+/// Accounts:
+///   0. `[signer, writable]` relayer (payer)
+///   1. `[]` l2_bridge_config PDA
+///   2. `[writable]` deposit_attestation PDA
+///   3. `[]` system_program
+pub fn create_attest_deposit_instruction(
+    relayer: &Pubkey,
+    l1_deposit_nonce: u64,
+    deposit_hash: [u8; 32],
+) -> Instruction {
+    let (config_pda, _) = find_l2_bridge_config();
+    let (attestation_pda, _) = find_deposit_attestation(l1_deposit_nonce, &deposit_hash);
+
+    let params = AttestDepositParams {
+        l1_deposit_nonce,
+        deposit_hash,
+    };
+    let mut data = vec![IX_ATTEST_DEPOSIT];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_L2_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*relayer, true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(attestation_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Add a relayer committee member and set the new threshold (admin only).
+///
+/// Accounts:
+///   0. `[signer, writable]` admin
+///   1. `[writable]` l2_bridge_config PDA
+///   2. `[]` system_program
+pub fn create_add_relayer_instruction(
+    admin: &Pubkey,
+    relayer: &Pubkey,
+    threshold: u8,
+) -> Instruction {
+    let (config_pda, _) = find_l2_bridge_config();
+
+    let params = UpdateCommitteeParams {
+        relayer: *relayer,
+        threshold,
+    };
+    let mut data = vec![IX_ADD_RELAYER];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_L2_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(config_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Remove a relayer committee member and set the new threshold (admin only).
+///
+/// Accounts:
+///   0. `[signer]` admin
+///   1. `[writable]` l2_bridge_config PDA
+pub fn create_remove_relayer_instruction(
+    admin: &Pubkey,
+    relayer: &Pubkey,
+    threshold: u8,
+) -> Instruction {
+    let (config_pda, _) = find_l2_bridge_config();
+
+    let params = UpdateCommitteeParams {
+        relayer: *relayer,
+        threshold,
+    };
+    let mut data = vec![IX_REMOVE_RELAYER];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_L2_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config_pda, false),
+        ],
+        data,
+    }
+}
+
+/// Send an L2→L1 message. `sender` may be a program PDA signing via CPI.
+///
+/// Accounts:
+///   0. `[signer]` sender
+///   1. `[writable]` l2_bridge_config PDA
+pub fn create_send_message_instruction(
+    sender: &Pubkey,
+    target_program: &Pubkey,
+    accounts: Vec<MessageAccount>,
+    payload: Vec<u8>,
+) -> Instruction {
+    let (config_pda, _) = find_l2_bridge_config();
+
+    let params = SendMessageParams {
+        target_program: *target_program,
+        accounts,
+        payload,
+    };
+    let mut data = vec![IX_SEND_MESSAGE];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_L2_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*sender, true),
+            AccountMeta::new(config_pda, false),
+        ],
+        data,
+    }
+}
+
+/// Deliver an L1→L2 message (relayer only; attested when a committee is set).
+///
+/// Accounts:
+///   0. `[signer]` relayer
+///   1. `[signer, writable]` payer
+///   2. `[]` l2_bridge_config PDA
+///   3. `[writable]` processed_deposit PDA
+///   4. `[writable]` deposit_attestation PDA for the message hash
+///   5. `[]` message_origin PDA
+///   6. `[]` target program
+///   7. `[]` system_program
+///   8+ message accounts, in order
+pub fn create_receive_message_instruction(
+    relayer: &Pubkey,
+    payer: &Pubkey,
+    message: CrossDomainMessage,
+    l1_tx_signature: [u8; 64],
+) -> Instruction {
+    let (config_pda, _) = find_l2_bridge_config();
+    let (processed_pda, _) = find_processed_deposit(message.nonce);
+    let (attestation_pda, _) = find_deposit_attestation(message.nonce, &message_hash(&message));
+    let (origin_pda, _) = find_l2_message_origin(&message.sender);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*relayer, true),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(config_pda, false),
+        AccountMeta::new(processed_pda, false),
+        AccountMeta::new(attestation_pda, false),
+        AccountMeta::new_readonly(origin_pda, false),
+        AccountMeta::new_readonly(message.target_program, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(message.accounts.iter().map(|a| AccountMeta {
+        pubkey: a.pubkey,
+        is_signer: false,
+        is_writable: a.is_writable,
+    }));
+
+    let params = ReceiveMessageParams {
+        message,
+        l1_tx_signature,
+    };
+    let mut data = vec![IX_RECEIVE_MESSAGE];
+    params.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: BRIDGE_L2_PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_burn_wrapped_uses_token_2022_accounts() {
+        let token_2022 = Pubkey::from_str("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb").unwrap();
+        let burner = Pubkey::new_unique();
+        let l1_mint = Pubkey::new_unique();
+        let (l2_mint, _) = find_l2_mint(&l1_mint);
+
+        let ix = create_burn_wrapped_instruction(&burner, 1_000, &l1_mint, [7u8; 32], &token_2022);
+
+        let token_2022_ata =
+            spl_associated_token_account::get_associated_token_address_with_program_id(
+                &burner,
+                &l2_mint,
+                &token_2022,
+            );
+        assert_eq!(ix.accounts[1].pubkey, token_2022_ata);
+        assert_ne!(
+            ix.accounts[1].pubkey,
+            spl_associated_token_account::get_associated_token_address(&burner, &l2_mint)
+        );
+        assert_eq!(ix.accounts[5].pubkey, token_2022);
+    }
+}
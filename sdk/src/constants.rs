@@ -8,7 +8,7 @@ use solana_program::pubkey::Pubkey;
 pub const BRIDGE_PROGRAM_ID: Pubkey =
     solana_program::pubkey!("BE2pz9kxPJLHd65B9tVBuZUwp3y5mKYczb6JLMsyPymA");
 
-/// L2 Bridge program — native transfer bridge on Mythic L2.
+/// L2 Bridge program — native MYTH transfers and wrapped SPL tokens on Mythic L2.
 pub const BRIDGE_L2_PROGRAM_ID: Pubkey =
     solana_program::pubkey!("5t8JwXzGQ3c7PCY6p6oJqZgFt8gff2d6uTLrqa1jFrKP");
 
@@ -38,12 +38,20 @@ pub const BRIDGE_CONFIG_SEED: &[u8] = b"bridge_config";
 pub const VAULT_SEED: &[u8] = b"vault";
 pub const SOL_VAULT_SEED: &[u8] = b"sol_vault";
 pub const WITHDRAWAL_SEED: &[u8] = b"withdrawal";
+pub const CHALLENGE_BOND_SEED: &[u8] = b"challenge_bond";
+pub const MESSAGE_SEED: &[u8] = b"message";
+/// Also used by the L2 bridge for its own origin signer PDAs.
+pub const MESSAGE_ORIGIN_SEED: &[u8] = b"message_origin";
+pub const FAST_WITHDRAWAL_SEED: &[u8] = b"fast_withdrawal";
 
 // ── PDA Seeds: Bridge (L2) ──────────────────────────────────────────────────
 
 pub const L2_BRIDGE_CONFIG_SEED: &[u8] = b"l2_bridge_config";
 pub const BRIDGE_RESERVE_SEED: &[u8] = b"bridge_reserve";
 pub const PROCESSED_DEPOSIT_SEED: &[u8] = b"processed";
+pub const WRAPPED_MINT_SEED: &[u8] = b"wrapped_mint";
+pub const L2_MINT_SEED: &[u8] = b"mint";
+pub const DEPOSIT_ATTESTATION_SEED: &[u8] = b"attestation";
 
 // ── MYTH Token Mints ────────────────────────────────────────────────────────
 
@@ -58,6 +66,7 @@ pub const MODEL_SEED: &[u8] = b"model";
 pub const AI_VALIDATOR_SEED: &[u8] = b"ai_validator";
 pub const INFERENCE_SEED: &[u8] = b"inference";
 pub const RESULT_SEED: &[u8] = b"result";
+pub const LOGITS_SEED: &[u8] = b"logits";
 pub const VERIFICATION_SEED: &[u8] = b"verification";
 
 // ── PDA Seeds: Compute Market ───────────────────────────────────────────────
@@ -74,6 +83,10 @@ pub const DISPUTE_SEED: &[u8] = b"dispute";
 pub const SETTLEMENT_CONFIG_SEED: &[u8] = b"settlement_config";
 pub const STATE_ROOT_SEED: &[u8] = b"state_root";
 pub const CHALLENGE_SEED: &[u8] = b"challenge";
+pub const SEQUENCER_SET_SEED: &[u8] = b"sequencer_set";
+pub const SEQUENCER_VAULT_SEED: &[u8] = b"sequencer_vault";
+pub const BATCH_DATA_SEED: &[u8] = b"batch_data";
+pub const BATCH_CHUNK_SEED: &[u8] = b"batch_chunk";
 
 // ── PDA Seeds: MYTH Token ───────────────────────────────────────────────────
 
@@ -11,12 +11,26 @@
 //!   4 = FinalizeStateRoot
 //!   5 = UpdateConfig
 //!   6 = GetLatestFinalized
+//!  14 = Bisect
+//!  15 = RespondBisection
+//!  16 = ProveStep
+//!  17 = (reserved; was DepositSequencerBond)
+//!  18 = ProveDoubleSequencing
+//!  19 = InitializeSequencerSet
+//!  20 = RequestSequencerExit
+//!  21 = WithdrawSequencerBond
+//!  22 = InitBatchData
+//!  23 = WriteBatchChunk
+//!  24 = SealBatchChunks
+//!  25 = CloseBatchData
+//!  26 = RegisterSequencer
 
 use borsh::BorshSerialize;
+use sha2::{Digest, Sha256};
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    system_program,
+    system_program, sysvar,
 };
 
 use crate::constants::*;
@@ -30,6 +44,21 @@ const IX_RESOLVE_CHALLENGE: u8 = 3;
 const IX_FINALIZE_STATE_ROOT: u8 = 4;
 const IX_UPDATE_CONFIG: u8 = 5;
 const IX_GET_LATEST_FINALIZED: u8 = 6;
+const IX_BISECT: u8 = 14;
+const IX_RESPOND_BISECTION: u8 = 15;
+const IX_PROVE_STEP: u8 = 16;
+const IX_PROVE_DOUBLE_SEQUENCING: u8 = 18;
+const IX_INITIALIZE_SEQUENCER_SET: u8 = 19;
+const IX_REQUEST_SEQUENCER_EXIT: u8 = 20;
+const IX_WITHDRAW_SEQUENCER_BOND: u8 = 21;
+const IX_INIT_BATCH_DATA: u8 = 22;
+const IX_WRITE_BATCH_CHUNK: u8 = 23;
+const IX_SEAL_BATCH_CHUNKS: u8 = 24;
+const IX_CLOSE_BATCH_DATA: u8 = 25;
+const IX_REGISTER_SEQUENCER: u8 = 26;
+
+/// Batch data is published in chunks of this many bytes (last may be shorter).
+pub const BATCH_CHUNK_SIZE: usize = 8_192;
 
 // ── Param Structs (exact Borsh match to program) ────────────────────────────
 
@@ -71,7 +100,73 @@ pub struct ChallengeStateRootArgs {
 pub struct ResolveChallengeArgs {
     pub l2_slot: u64,
     pub challenger: Pubkey,
-    pub is_valid: bool,
+}
+
+#[derive(BorshSerialize)]
+pub struct BisectArgs {
+    pub l2_slot: u64,
+    pub challenger: Pubkey,
+    pub mid_hash: [u8; 32],
+}
+
+#[derive(BorshSerialize)]
+pub struct RespondBisectionArgs {
+    pub l2_slot: u64,
+    pub agree: bool,
+}
+
+#[derive(BorshSerialize)]
+pub struct ProveStepArgs {
+    pub l2_slot: u64,
+    pub challenger: Pubkey,
+    pub proof: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+pub struct InitializeSequencerSetArgs {
+    pub min_bond: u64,
+    pub rotation_slots: u64,
+    pub skip_after_slots: u64,
+    pub exit_delay_slots: u64,
+}
+
+#[derive(BorshSerialize)]
+pub struct RegisterSequencerArgs {
+    pub bond_amount: u64,
+}
+
+#[derive(BorshSerialize)]
+pub struct ProveDoubleSequencingArgs {
+    pub l2_slot: u64,
+    pub root_a: [u8; 32],
+    pub root_b: [u8; 32],
+}
+
+#[derive(BorshSerialize)]
+pub struct InitBatchDataArgs {
+    pub l2_slot: u64,
+    pub total_len: u32,
+}
+
+#[derive(BorshSerialize)]
+pub struct WriteBatchChunkArgs {
+    pub l2_slot: u64,
+    pub chunk_index: u16,
+    pub offset: u32,
+    pub data: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+pub struct SealBatchChunksArgs {
+    pub l2_slot: u64,
+    pub first_chunk: u16,
+}
+
+#[derive(BorshSerialize)]
+pub struct CloseBatchDataArgs {
+    pub l2_slot: u64,
+    pub first_chunk: u16,
+    pub close_header: bool,
 }
 
 #[derive(BorshSerialize)]
@@ -84,6 +179,7 @@ pub struct UpdateConfigArgs {
     pub sequencer: Option<Pubkey>,
     pub challenge_period_slots: Option<u64>,
     pub min_challenger_bond: Option<u64>,
+    pub step_verifier: Option<Pubkey>,
 }
 
 // ── PDA Helpers ─────────────────────────────────────────────────────────────
@@ -106,6 +202,50 @@ pub fn find_challenge(l2_slot: u64, challenger: &Pubkey) -> (Pubkey, u8) {
     )
 }
 
+pub fn find_sequencer_set() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SEQUENCER_SET_SEED], &SETTLEMENT_PROGRAM_ID)
+}
+
+pub fn find_sequencer_vault() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SEQUENCER_VAULT_SEED], &SETTLEMENT_PROGRAM_ID)
+}
+
+pub fn find_batch_data(l2_slot: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[BATCH_DATA_SEED, &l2_slot.to_le_bytes()],
+        &SETTLEMENT_PROGRAM_ID,
+    )
+}
+
+pub fn find_batch_chunk(l2_slot: u64, chunk_index: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[BATCH_CHUNK_SEED, &l2_slot.to_le_bytes(), &chunk_index.to_le_bytes()],
+        &SETTLEMENT_PROGRAM_ID,
+    )
+}
+
+/// `transaction_batch_hash` for a serialized batch: sha256 over the
+/// concatenated sha256 of each BATCH_CHUNK_SIZE chunk.
+pub fn batch_data_hash(batch: &[u8]) -> [u8; 32] {
+    let mut outer = Sha256::new();
+    for chunk in batch.chunks(BATCH_CHUNK_SIZE) {
+        outer.update(Sha256::digest(chunk));
+    }
+    outer.finalize().into()
+}
+
+/// Message a sequencer signs to commit to `state_root` at `l2_slot`.
+/// Two signed messages for the same slot with different roots are a
+/// DoubleSequencing proof.
+pub fn state_root_message(l2_chain_id: &[u8; 16], l2_slot: u64, state_root: &[u8; 32]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(17 + 16 + 8 + 32);
+    message.extend_from_slice(b"MYTHIC_STATE_ROOT");
+    message.extend_from_slice(l2_chain_id);
+    message.extend_from_slice(&l2_slot.to_le_bytes());
+    message.extend_from_slice(state_root);
+    message
+}
+
 // ── Instruction Builders ────────────────────────────────────────────────────
 
 /// Initialize the settlement program.
@@ -151,6 +291,7 @@ pub fn create_initialize_instruction(
 ///   1. `[writable]` settlement_config PDA
 ///   2. `[writable]` state_root PDA (seeds: ["state_root", l2_slot_bytes])
 ///   3. `[]` system_program
+///   4. `[writable]` sequencer_set PDA
 pub fn create_post_state_root_instruction(
     sequencer: &Pubkey,
     l2_slot: u64,
@@ -173,6 +314,7 @@ pub fn create_post_state_root_instruction(
     };
     let mut data = vec![IX_POST_STATE_ROOT];
     args.serialize(&mut data).unwrap();
+    let (set_pda, _) = find_sequencer_set();
 
     Instruction {
         program_id: SETTLEMENT_PROGRAM_ID,
@@ -181,6 +323,7 @@ pub fn create_post_state_root_instruction(
             AccountMeta::new(config_pda, false),
             AccountMeta::new(state_root_pda, false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(set_pda, false),
         ],
         data,
     }
@@ -194,6 +337,7 @@ pub fn create_post_state_root_instruction(
 ///   2. `[writable]` state_root PDA
 ///   3. `[writable]` challenge PDA (seeds: ["challenge", l2_slot_bytes, challenger])
 ///   4. `[]` system_program
+///   5. `[writable]` sequencer_set PDA
 pub fn create_challenge_state_root_instruction(
     challenger: &Pubkey,
     l2_slot: u64,
@@ -203,6 +347,7 @@ pub fn create_challenge_state_root_instruction(
     let (config_pda, _) = find_settlement_config();
     let (state_root_pda, _) = find_state_root(l2_slot);
     let (challenge_pda, _) = find_challenge(l2_slot, challenger);
+    let (set_pda, _) = find_sequencer_set();
 
     let args = ChallengeStateRootArgs {
         l2_slot,
@@ -220,24 +365,36 @@ pub fn create_challenge_state_root_instruction(
             AccountMeta::new(state_root_pda, false),
             AccountMeta::new(challenge_pda, false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(set_pda, false),
         ],
         data,
     }
 }
 
-/// Resolve a challenge (admin only).
+/// Settle a dispute game after the current round deadline (anyone).
+/// The party whose turn it was forfeits both bonds to the other side; a
+/// losing sequencer's MYTH bond is slashed to `challenger_token`, which must
+/// be the challenger's own MYTH account. A challenger win invalidates the
+/// root and rewinds the chain tip to the last finalized root.
 ///
 /// Accounts:
-///   0. `[signer]` admin
-///   1. `[]` settlement_config PDA
+///   0. `[signer]` caller
+///   1. `[writable]` settlement_config PDA
 ///   2. `[writable]` state_root PDA
 ///   3. `[writable]` challenge PDA
 ///   4. `[writable]` challenger account
+///   5. `[writable]` sequencer that posted the root
+///   6. `[writable]` sequencer_set PDA
+///   7. `[writable]` sequencer bond vault PDA
+///   8. `[writable]` challenger MYTH token account
+///   9. `[]` token program
 pub fn create_resolve_challenge_instruction(
-    admin: &Pubkey,
+    caller: &Pubkey,
     l2_slot: u64,
     challenger: &Pubkey,
-    is_valid: bool,
+    sequencer: &Pubkey,
+    challenger_token: &Pubkey,
+    token_program: &Pubkey,
 ) -> Instruction {
     let (config_pda, _) = find_settlement_config();
     let (state_root_pda, _) = find_state_root(l2_slot);
@@ -246,37 +403,169 @@ pub fn create_resolve_challenge_instruction(
     let args = ResolveChallengeArgs {
         l2_slot,
         challenger: *challenger,
-        is_valid,
     };
     let mut data = vec![IX_RESOLVE_CHALLENGE];
     args.serialize(&mut data).unwrap();
+    let (set_pda, _) = find_sequencer_set();
+    let (vault_pda, _) = find_sequencer_vault();
 
     Instruction {
         program_id: SETTLEMENT_PROGRAM_ID,
         accounts: vec![
-            AccountMeta::new_readonly(*admin, true),
-            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new(config_pda, false),
             AccountMeta::new(state_root_pda, false),
             AccountMeta::new(challenge_pda, false),
             AccountMeta::new(*challenger, false),
+            AccountMeta::new(*sequencer, false),
+            AccountMeta::new(set_pda, false),
+            AccountMeta::new(vault_pda, false),
+            AccountMeta::new(*challenger_token, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data,
+    }
+}
+
+/// Post the sequencer's state hash at the midpoint of the disputed range.
+/// The first call escrows a bond matching the challenger's.
+///
+/// Accounts:
+///   0. `[signer, writable]` sequencer
+///   1. `[]` state_root PDA
+///   2. `[writable]` challenge PDA
+///   3. `[]` system_program
+pub fn create_bisect_instruction(
+    sequencer: &Pubkey,
+    l2_slot: u64,
+    challenger: &Pubkey,
+    mid_hash: [u8; 32],
+) -> Instruction {
+    let (state_root_pda, _) = find_state_root(l2_slot);
+    let (challenge_pda, _) = find_challenge(l2_slot, challenger);
+
+    let args = BisectArgs {
+        l2_slot,
+        challenger: *challenger,
+        mid_hash,
+    };
+    let mut data = vec![IX_BISECT];
+    args.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: SETTLEMENT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*sequencer, true),
+            AccountMeta::new_readonly(state_root_pda, false),
+            AccountMeta::new(challenge_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Agree or disagree with the sequencer's midpoint hash.
+///
+/// Accounts:
+///   0. `[signer]` challenger
+///   1. `[]` state_root PDA
+///   2. `[writable]` challenge PDA
+pub fn create_respond_bisection_instruction(
+    challenger: &Pubkey,
+    l2_slot: u64,
+    agree: bool,
+) -> Instruction {
+    let (state_root_pda, _) = find_state_root(l2_slot);
+    let (challenge_pda, _) = find_challenge(l2_slot, challenger);
+
+    let args = RespondBisectionArgs { l2_slot, agree };
+    let mut data = vec![IX_RESPOND_BISECTION];
+    args.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: SETTLEMENT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*challenger, true),
+            AccountMeta::new_readonly(state_root_pda, false),
+            AccountMeta::new(challenge_pda, false),
         ],
         data,
     }
 }
 
+/// Prove the final disputed step through the configured step verifier.
+/// `verifier_accounts` are forwarded to the verifier program as-is.
+///
+/// Accounts:
+///   0. `[signer, writable]` sequencer
+///   1. `[]` settlement_config PDA
+///   2. `[writable]` state_root PDA
+///   3. `[writable]` challenge PDA
+///   4. `[]` step verifier program
+///   5. `[writable]` sequencer_set PDA
+///   6.. verifier accounts
+pub fn create_prove_step_instruction(
+    sequencer: &Pubkey,
+    l2_slot: u64,
+    challenger: &Pubkey,
+    step_verifier: &Pubkey,
+    proof: Vec<u8>,
+    verifier_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let (config_pda, _) = find_settlement_config();
+    let (state_root_pda, _) = find_state_root(l2_slot);
+    let (challenge_pda, _) = find_challenge(l2_slot, challenger);
+    let (set_pda, _) = find_sequencer_set();
+
+    let args = ProveStepArgs {
+        l2_slot,
+        challenger: *challenger,
+        proof,
+    };
+    let mut data = vec![IX_PROVE_STEP];
+    args.serialize(&mut data).unwrap();
+
+    let mut accounts = vec![
+        AccountMeta::new(*sequencer, true),
+        AccountMeta::new_readonly(config_pda, false),
+        AccountMeta::new(state_root_pda, false),
+        AccountMeta::new(challenge_pda, false),
+        AccountMeta::new_readonly(*step_verifier, false),
+        AccountMeta::new(set_pda, false),
+    ];
+    accounts.extend(verifier_accounts);
+
+    Instruction {
+        program_id: SETTLEMENT_PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
+
 /// Finalize a state root after the challenge period.
+/// Roots with transactions, or that change the state root, need their batch
+/// data published and sealed first.
+/// Roots finalize in chain order, each building on the root at the config's
+/// `last_finalized_slot`; a root discarded by a rewind is marked Invalidated.
 ///
 /// Accounts:
 ///   0. `[]` caller (anyone)
 ///   1. `[writable]` settlement_config PDA
 ///   2. `[writable]` state_root PDA
+///   3. `[]` batch_data PDA
+///   4. `[]` state_root PDA at `last_finalized_slot` (read by configs that
+///      predate recording the finalized root)
 pub fn create_finalize_state_root_instruction(
     caller: &Pubkey,
     l2_slot: u64,
+    last_finalized_slot: u64,
 ) -> Instruction {
     let (config_pda, _) = find_settlement_config();
     let (state_root_pda, _) = find_state_root(l2_slot);
 
+    let (batch_data_pda, _) = find_batch_data(l2_slot);
+    let (anchor_pda, _) = find_state_root(last_finalized_slot);
+
     let args = FinalizeStateRootArgs { l2_slot };
     let mut data = vec![IX_FINALIZE_STATE_ROOT];
     args.serialize(&mut data).unwrap();
@@ -287,12 +576,15 @@ pub fn create_finalize_state_root_instruction(
             AccountMeta::new_readonly(*caller, false),
             AccountMeta::new(config_pda, false),
             AccountMeta::new(state_root_pda, false),
+            AccountMeta::new_readonly(batch_data_pda, false),
+            AccountMeta::new_readonly(anchor_pda, false),
         ],
         data,
     }
 }
 
-/// Update settlement configuration (admin only).
+/// Update settlement configuration (admin only). `step_verifier` is only
+/// accepted while none is set; replacing it needs the timelocked update.
 ///
 /// Accounts:
 ///   0. `[signer]` admin
@@ -302,6 +594,7 @@ pub fn create_update_config_instruction(
     sequencer: Option<Pubkey>,
     challenge_period_slots: Option<u64>,
     min_challenger_bond: Option<u64>,
+    step_verifier: Option<Pubkey>,
 ) -> Instruction {
     let (config_pda, _) = find_settlement_config();
 
@@ -309,6 +602,7 @@ pub fn create_update_config_instruction(
         sequencer,
         challenge_period_slots,
         min_challenger_bond,
+        step_verifier,
     };
     let mut data = vec![IX_UPDATE_CONFIG];
     args.serialize(&mut data).unwrap();
@@ -340,3 +634,325 @@ pub fn create_get_latest_finalized_instruction() -> Instruction {
         data,
     }
 }
+
+/// Create the sequencer set and its MYTH bond vault (admin only).
+///
+/// Accounts:
+///   0. `[signer, writable]` admin
+///   1. `[]` settlement_config PDA
+///   2. `[writable]` sequencer_set PDA (seeds: ["sequencer_set"])
+///   3. `[writable]` bond vault PDA (seeds: ["sequencer_vault"])
+///   4. `[]` MYTH mint
+///   5. `[]` token program
+///   6. `[]` system_program
+///   7. `[]` rent sysvar
+pub fn create_initialize_sequencer_set_instruction(
+    admin: &Pubkey,
+    myth_mint: &Pubkey,
+    token_program: &Pubkey,
+    args: InitializeSequencerSetArgs,
+) -> Instruction {
+    let (config_pda, _) = find_settlement_config();
+    let (set_pda, _) = find_sequencer_set();
+    let (vault_pda, _) = find_sequencer_vault();
+
+    let mut data = vec![IX_INITIALIZE_SEQUENCER_SET];
+    args.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: SETTLEMENT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(config_pda, false),
+            AccountMeta::new(set_pda, false),
+            AccountMeta::new(vault_pda, false),
+            AccountMeta::new_readonly(*myth_mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Join the sequencer set, or top up an existing bond, with MYTH.
+///
+/// Accounts:
+///   0. `[signer]` sequencer
+///   1. `[writable]` sequencer_set PDA
+///   2. `[writable]` sequencer MYTH token account
+///   3. `[writable]` bond vault PDA
+///   4. `[]` token program
+pub fn create_register_sequencer_instruction(
+    sequencer: &Pubkey,
+    sequencer_token: &Pubkey,
+    token_program: &Pubkey,
+    bond_amount: u64,
+) -> Instruction {
+    let (set_pda, _) = find_sequencer_set();
+    let (vault_pda, _) = find_sequencer_vault();
+
+    let args = RegisterSequencerArgs { bond_amount };
+    let mut data = vec![IX_REGISTER_SEQUENCER];
+    args.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: SETTLEMENT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*sequencer, true),
+            AccountMeta::new(set_pda, false),
+            AccountMeta::new(*sequencer_token, false),
+            AccountMeta::new(vault_pda, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data,
+    }
+}
+
+/// Leave the rotation. The bond is withdrawable after the exit delay.
+///
+/// Accounts:
+///   0. `[signer]` sequencer
+///   1. `[writable]` sequencer_set PDA
+pub fn create_request_sequencer_exit_instruction(sequencer: &Pubkey) -> Instruction {
+    let (set_pda, _) = find_sequencer_set();
+
+    Instruction {
+        program_id: SETTLEMENT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*sequencer, true),
+            AccountMeta::new(set_pda, false),
+        ],
+        data: vec![IX_REQUEST_SEQUENCER_EXIT],
+    }
+}
+
+/// Withdraw an exited sequencer's bond once the exit delay has elapsed and
+/// no dispute against its roots is still open.
+///
+/// Accounts:
+///   0. `[signer]` sequencer
+///   1. `[writable]` sequencer_set PDA
+///   2. `[writable]` bond vault PDA
+///   3. `[writable]` sequencer MYTH token account
+///   4. `[]` token program
+pub fn create_withdraw_sequencer_bond_instruction(
+    sequencer: &Pubkey,
+    sequencer_token: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    let (set_pda, _) = find_sequencer_set();
+    let (vault_pda, _) = find_sequencer_vault();
+
+    Instruction {
+        program_id: SETTLEMENT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*sequencer, true),
+            AccountMeta::new(set_pda, false),
+            AccountMeta::new(vault_pda, false),
+            AccountMeta::new(*sequencer_token, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data: vec![IX_WITHDRAW_SEQUENCER_BOND],
+    }
+}
+
+/// Prove the sequencer that posted `l2_slot` signed two different roots for it.
+///
+/// Must be preceded in the same transaction by ed25519 program instructions
+/// verifying that sequencer's signatures over `state_root_message` for
+/// `root_a` and `root_b`, with all offsets pointing into those instructions.
+/// Invalidating a root that is not yet final rewinds the chain tip.
+///
+/// Accounts:
+///   0. `[signer]` challenger
+///   1. `[writable]` settlement_config PDA
+///   2. `[writable]` state_root PDA
+///   3. `[writable]` sequencer_set PDA
+///   4. `[writable]` bond vault PDA
+///   5. `[writable]` challenger MYTH token account (receives the slashed bond)
+///   6. `[]` token program
+///   7. `[]` instructions sysvar
+pub fn create_prove_double_sequencing_instruction(
+    challenger: &Pubkey,
+    challenger_token: &Pubkey,
+    token_program: &Pubkey,
+    l2_slot: u64,
+    root_a: [u8; 32],
+    root_b: [u8; 32],
+) -> Instruction {
+    let (config_pda, _) = find_settlement_config();
+    let (state_root_pda, _) = find_state_root(l2_slot);
+    let (set_pda, _) = find_sequencer_set();
+    let (vault_pda, _) = find_sequencer_vault();
+
+    let args = ProveDoubleSequencingArgs {
+        l2_slot,
+        root_a,
+        root_b,
+    };
+    let mut data = vec![IX_PROVE_DOUBLE_SEQUENCING];
+    args.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: SETTLEMENT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*challenger, true),
+            AccountMeta::new(config_pda, false),
+            AccountMeta::new(state_root_pda, false),
+            AccountMeta::new(set_pda, false),
+            AccountMeta::new(vault_pda, false),
+            AccountMeta::new(*challenger_token, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Open the data-availability header for a posted root (posting sequencer only).
+///
+/// Accounts:
+///   0. `[signer, writable]` sequencer that posted the root
+///   1. `[]` state_root PDA
+///   2. `[writable]` batch_data PDA (seeds: ["batch_data", l2_slot_bytes])
+///   3. `[]` system_program
+pub fn create_init_batch_data_instruction(
+    sequencer: &Pubkey,
+    l2_slot: u64,
+    total_len: u32,
+) -> Instruction {
+    let (state_root_pda, _) = find_state_root(l2_slot);
+    let (batch_data_pda, _) = find_batch_data(l2_slot);
+
+    let args = InitBatchDataArgs { l2_slot, total_len };
+    let mut data = vec![IX_INIT_BATCH_DATA];
+    args.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: SETTLEMENT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*sequencer, true),
+            AccountMeta::new_readonly(state_root_pda, false),
+            AccountMeta::new(batch_data_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Write `bytes` at `offset` within chunk `chunk_index` of a batch.
+///
+/// Accounts:
+///   0. `[signer, writable]` sequencer that posted the root
+///   1. `[writable]` batch_data PDA
+///   2. `[writable]` batch_chunk PDA (seeds: ["batch_chunk", l2_slot_bytes, chunk_index_bytes])
+///   3. `[]` system_program
+pub fn create_write_batch_chunk_instruction(
+    sequencer: &Pubkey,
+    l2_slot: u64,
+    chunk_index: u16,
+    offset: u32,
+    bytes: Vec<u8>,
+) -> Instruction {
+    let (batch_data_pda, _) = find_batch_data(l2_slot);
+    let (chunk_pda, _) = find_batch_chunk(l2_slot, chunk_index);
+
+    let args = WriteBatchChunkArgs {
+        l2_slot,
+        chunk_index,
+        offset,
+        data: bytes,
+    };
+    let mut data = vec![IX_WRITE_BATCH_CHUNK];
+    args.serialize(&mut data).unwrap();
+
+    Instruction {
+        program_id: SETTLEMENT_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*sequencer, true),
+            AccountMeta::new(batch_data_pda, false),
+            AccountMeta::new(chunk_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Seal `count` chunks starting at `first_chunk` (anyone). Sealing the last
+/// unsealed chunk checks the batch against `transaction_batch_hash`.
+///
+/// Accounts:
+///   0. `[]` state_root PDA
+///   1. `[writable]` batch_data PDA
+///   2.. `[]` batch_chunk PDAs, in order
+pub fn create_seal_batch_chunks_instruction(
+    l2_slot: u64,
+    first_chunk: u16,
+    count: u16,
+) -> Instruction {
+    let (state_root_pda, _) = find_state_root(l2_slot);
+    let (batch_data_pda, _) = find_batch_data(l2_slot);
+
+    let args = SealBatchChunksArgs { l2_slot, first_chunk };
+    let mut data = vec![IX_SEAL_BATCH_CHUNKS];
+    args.serialize(&mut data).unwrap();
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(state_root_pda, false),
+        AccountMeta::new(batch_data_pda, false),
+    ];
+    accounts.extend(
+        (first_chunk..first_chunk + count)
+            .map(|i| AccountMeta::new_readonly(find_batch_chunk(l2_slot, i).0, false)),
+    );
+
+    Instruction {
+        program_id: SETTLEMENT_PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
+
+/// Reclaim rent from `count` chunks starting at `first_chunk`, and optionally
+/// the header, once the root is Finalized or Invalidated.
+///
+/// Accounts:
+///   0. `[signer, writable]` sequencer that posted the batch
+///   1. `[]` state_root PDA
+///   2. `[writable]` batch_data PDA
+///   3.. `[writable]` batch_chunk PDAs, in order
+pub fn create_close_batch_data_instruction(
+    sequencer: &Pubkey,
+    l2_slot: u64,
+    first_chunk: u16,
+    count: u16,
+    close_header: bool,
+) -> Instruction {
+    let (state_root_pda, _) = find_state_root(l2_slot);
+    let (batch_data_pda, _) = find_batch_data(l2_slot);
+
+    let args = CloseBatchDataArgs {
+        l2_slot,
+        first_chunk,
+        close_header,
+    };
+    let mut data = vec![IX_CLOSE_BATCH_DATA];
+    args.serialize(&mut data).unwrap();
+
+    let mut accounts = vec![
+        AccountMeta::new(*sequencer, true),
+        AccountMeta::new_readonly(state_root_pda, false),
+        AccountMeta::new(batch_data_pda, false),
+    ];
+    accounts.extend(
+        (first_chunk..first_chunk + count)
+            .map(|i| AccountMeta::new(find_batch_chunk(l2_slot, i).0, false)),
+    );
+
+    Instruction {
+        program_id: SETTLEMENT_PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
@@ -3,6 +3,7 @@ import {
   Keypair,
   PublicKey,
   SystemProgram,
+  SYSVAR_SLOT_HASHES_PUBKEY,
   Transaction,
   TransactionInstruction,
   sendAndConfirmTransaction,
@@ -52,6 +53,20 @@ export function deriveInferencePDA(nonce: bigint): [PublicKey, number] {
   );
 }
 
+export function deriveModelValidatorsPDA(modelHash: Buffer): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [SEEDS.modelValidators, modelHash],
+    CONFIG.aiProgramId
+  );
+}
+
+export function deriveLogitsPDA(requestPubkey: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [SEEDS.logits, requestPubkey.toBuffer()],
+    CONFIG.aiProgramId
+  );
+}
+
 export function deriveResultPDA(requestPubkey: PublicKey): [PublicKey, number] {
   return PublicKey.findProgramAddressSync(
     [SEEDS.result, requestPubkey.toBuffer()],
@@ -82,6 +97,18 @@ const DISC = {
   ClaimInferenceFee: 6,
   Pause: 7,
   Unpause: 8,
+  InvokeCallback: 9,
+  AssignValidator: 10,
+  ExpireRequest: 11,
+  ResolveDispute: 12,
+  RequestUnstake: 13,
+  WithdrawStake: 14,
+  SetVerificationParams: 15,
+  ClaimVerifierReward: 16,
+  PenalizeDissenter: 17,
+  MigrateAccount: 18,
+  CreateValidatorSet: 19,
+  SettleVotes: 20,
 } as const;
 
 /**
@@ -138,6 +165,12 @@ export async function registerValidator(
       { pubkey: configPDA, isSigner: false, isWritable: false },
       { pubkey: stakeVault, isSigner: false, isWritable: true },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      // One validator set per supported model, in the same order
+      ...supportedModelHashes.map((h) => ({
+        pubkey: deriveModelValidatorsPDA(h)[0],
+        isSigner: false,
+        isWritable: true,
+      })),
     ],
     data,
   });
@@ -148,6 +181,60 @@ export async function registerValidator(
   return sig;
 }
 
+/**
+ * Crank validator assignment for a request. Every member of the model's
+ * validator set must be passed, in set order, followed by the lapsed
+ * assignee if it has left the set.
+ */
+export async function assignValidator(
+  connection: Connection,
+  payer: Keypair,
+  requestPubkey: PublicKey,
+  modelHash: Buffer
+): Promise<string> {
+  const [setPDA] = deriveModelValidatorsPDA(modelHash);
+  const setData = await connection.getAccountInfo(setPDA);
+  if (!setData?.data) throw new Error("Model validator set not found");
+
+  // ModelValidatorSet: model_hash(32) + bump(1) + validators Vec<(Pubkey, u64 stake)>
+  let offset = 32 + 1;
+  const count = setData.data.readUInt32LE(offset);
+  offset += 4;
+  const members: PublicKey[] = [];
+  for (let i = 0; i < count; i++) {
+    members.push(new PublicKey(setData.data.subarray(offset, offset + 32)));
+    offset += 32 + 8;
+  }
+
+  // InferenceRequest: status(u8) at 116, assigned_validator at 117..149.
+  // Unused trailing accounts are ignored, so the lapsed assignee is passed
+  // whenever it is not a member.
+  const reqData = await connection.getAccountInfo(requestPubkey);
+  if (!reqData?.data) throw new Error("Inference request not found");
+  const extra: PublicKey[] = [];
+  const ASSIGNED = 1;
+  if (reqData.data[116] === ASSIGNED) {
+    const [lapsedPDA] = deriveValidatorPDA(new PublicKey(reqData.data.subarray(117, 149)));
+    if (!members.some((m) => m.equals(lapsedPDA))) extra.push(lapsedPDA);
+  }
+
+  const ix = new TransactionInstruction({
+    programId: CONFIG.aiProgramId,
+    keys: [
+      { pubkey: requestPubkey, isSigner: false, isWritable: true },
+      { pubkey: setPDA, isSigner: false, isWritable: true },
+      { pubkey: SYSVAR_SLOT_HASHES_PUBKEY, isSigner: false, isWritable: false },
+      ...[...members, ...extra].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })),
+    ],
+    data: Buffer.from([DISC.AssignValidator]),
+  });
+
+  const tx = new Transaction().add(ix);
+  const sig = await sendAndConfirmTransaction(connection, tx, [payer]);
+  log.info({ sig, request: requestPubkey.toBase58() }, "Cranked validator assignment");
+  return sig;
+}
+
 /**
  * Submit inference result on-chain.
  */
@@ -158,14 +245,18 @@ export async function submitResult(
   validatorPDA: PublicKey,
   outputHash: Buffer,
   logitFingerprint: number[][],
-  computeUnitsUsed: bigint
+  computeUnitsUsed: bigint,
+  outputTokens: number
 ): Promise<string> {
   const [resultPDA] = deriveResultPDA(requestPubkey);
+  const [logitsPDA] = deriveLogitsPDA(requestPubkey);
+  const [configPDA] = deriveConfigPDA();
 
   // Encode SubmitResultArgs
   // output_hash: [u8; 32]
   // logit_fingerprint: Vec<[f32; 4]>
   // compute_units_used: u64
+  // output_tokens: u32
 
   // logit_fingerprint vec encoding
   const fpLenBuf = Buffer.alloc(4);
@@ -182,12 +273,16 @@ export async function submitResult(
   const cuBuf = Buffer.alloc(8);
   cuBuf.writeBigUInt64LE(computeUnitsUsed);
 
+  const tokensBuf = Buffer.alloc(4);
+  tokensBuf.writeUInt32LE(outputTokens);
+
   const data = Buffer.concat([
     Buffer.from([DISC.SubmitResult]),
     outputHash,
     fpLenBuf,
     ...fpBufs,
     cuBuf,
+    tokensBuf,
   ]);
 
   const ix = new TransactionInstruction({
@@ -198,6 +293,8 @@ export async function submitResult(
       { pubkey: resultPDA, isSigner: false, isWritable: true },
       { pubkey: validatorPDA, isSigner: false, isWritable: true },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: logitsPDA, isSigner: false, isWritable: true },
+      { pubkey: configPDA, isSigner: false, isWritable: false },
     ],
     data,
   });
@@ -232,6 +329,8 @@ export async function claimFee(
       { pubkey: configPDA, isSigner: false, isWritable: false },
       { pubkey: foundationPubkey, isSigner: false, isWritable: true },
       { pubkey: burnAddress, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: deriveValidatorPDA(payer.publicKey)[0], isSigner: false, isWritable: true },
     ],
     data,
   });
@@ -53,4 +53,6 @@ export const SEEDS = {
   inference: Buffer.from("inference"),
   result: Buffer.from("result"),
   verification: Buffer.from("verification"),
+  modelValidators: Buffer.from("model_validators"),
+  logits: Buffer.from("logits"),
 } as const;
@@ -207,9 +207,10 @@ class InferenceEngine {
     outputHash: Buffer,
     tokenCount: number
   ): number[][] {
-    // Sample logit fingerprints at evenly-spaced positions
-    // In production: actual top-4 logit values from the model at each token position
-    const sampleCount = Math.min(tokenCount, 32);
+    // One entry per output token up to 32, and at least one per verifier the
+    // quorum can require (16). In production: the actual top-4 logit values at
+    // the token the program derives for each entry (fingerprint_token_index)
+    const sampleCount = Math.min(Math.max(tokenCount, 16), 32);
     const fingerprint: number[][] = [];
 
     for (let i = 0; i < sampleCount; i++) {
@@ -5,6 +5,7 @@ import {
   deriveConfigPDA,
   deriveInferencePDA,
   deriveValidatorPDA,
+  assignValidator,
   submitResult,
   claimFee,
   sha256,
@@ -153,9 +154,17 @@ export class ChainWatcher {
     const [requestPDA] = deriveInferencePDA(nonce);
 
     try {
-      const requestData = await this.connection.getAccountInfo(requestPDA);
+      let requestData = await this.connection.getAccountInfo(requestPDA);
       if (!requestData?.data) return;
 
+      // Pending requests need an assignee first; anyone may crank the draw
+      if (requestData.data[32 + 32 + 32 + 4 + 8 + 8] === 0) {
+        const modelHash = Buffer.from(requestData.data.subarray(32, 64));
+        await assignValidator(this.connection, this.keypair, requestPDA, modelHash);
+        requestData = await this.connection.getAccountInfo(requestPDA);
+        if (!requestData?.data) return;
+      }
+
       // Parse InferenceRequest
       const data = requestData.data;
       let offset = 0;
@@ -178,12 +187,15 @@ export class ChainWatcher {
       const escrowedAmount = data.readBigUInt64LE(offset);
       offset += 8;
 
-      const status = data[offset]; // 0=Pending
+      const status = data[offset]; // 1=Assigned
       offset += 1;
 
-      // Only process Pending requests (status == 0)
-      if (status !== 0) {
-        log.debug({ nonce: nonce.toString(), status }, "Skipping non-pending request");
+      const assignedValidator = new PublicKey(data.subarray(offset, offset + 32));
+      offset += 32;
+
+      // Only serve requests assigned to us (status == 1)
+      if (status !== 1 || !assignedValidator.equals(this.keypair.publicKey)) {
+        log.debug({ nonce: nonce.toString(), status }, "Skipping request not assigned to us");
         return;
       }
 
@@ -231,7 +243,8 @@ export class ChainWatcher {
         this.validatorPDA,
         result.outputHash,
         result.logitFingerprint,
-        result.computeUnits
+        result.computeUnits,
+        result.tokensGenerated
       );
 
       // Track for fee claiming
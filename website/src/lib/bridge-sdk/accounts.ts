@@ -2,6 +2,7 @@ import { Connection, PublicKey } from '@solana/web3.js'
 import {
   BRIDGE_L1_PROGRAM_ID,
   BRIDGE_L2_PROGRAM_ID,
+  SETTLEMENT_PROGRAM_ID,
   BRIDGE_CONFIG_SEED,
   SOL_VAULT_SEED,
   VAULT_SEED,
@@ -11,6 +12,8 @@ import {
   MINT_SEED,
   PROCESSED_SEED,
   FEE_VAULT_SEED,
+  FAST_WITHDRAWAL_SEED,
+  STATE_ROOT_SEED,
   BridgeConfig,
   L2BridgeConfig,
   WithdrawalRequest,
@@ -45,6 +48,18 @@ export function deriveWithdrawalRequest(nonce: bigint): [PublicKey, number] {
   return PublicKey.findProgramAddressSync([WITHDRAWAL_SEED, buf], BRIDGE_L1_PROGRAM_ID)
 }
 
+export function deriveFastWithdrawal(nonce: bigint): [PublicKey, number] {
+  const buf = Buffer.alloc(8)
+  buf.writeBigUInt64LE(nonce)
+  return PublicKey.findProgramAddressSync([FAST_WITHDRAWAL_SEED, buf], BRIDGE_L1_PROGRAM_ID)
+}
+
+export function deriveStateRoot(l2Slot: bigint): [PublicKey, number] {
+  const buf = Buffer.alloc(8)
+  buf.writeBigUInt64LE(l2Slot)
+  return PublicKey.findProgramAddressSync([STATE_ROOT_SEED, buf], SETTLEMENT_PROGRAM_ID)
+}
+
 export function deriveL2BridgeConfig(): [PublicKey, number] {
   return PublicKey.findProgramAddressSync([L2_BRIDGE_CONFIG_SEED], BRIDGE_L2_PROGRAM_ID)
 }
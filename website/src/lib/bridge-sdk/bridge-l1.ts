@@ -17,7 +17,9 @@ import {
 } from './types'
 import {
   deriveBridgeConfig,
+  deriveFastWithdrawal,
   deriveSolVault,
+  deriveStateRoot,
   deriveTokenVault,
   deriveWithdrawalRequest,
 } from './accounts'
@@ -108,10 +110,19 @@ export function createDepositSPLInstruction(
 //   4. [] token mint
 //   5. [] bridge_config PDA
 //   6. [] token_program (SPL Token OR Token-2022)
+//   7. [] settlement state_root PDA for the withdrawal's l2_slot
+//   8. [] fast_withdrawal PDA (may be uninitialized)
+//
+// Accounts 7 and 8 and the Merkle proof are required; the older 7-account
+// form fails with NotEnoughAccountKeys. If an LP filled the withdrawal,
+// recipientTokenAccount must be the LP's.
 
 export function createFinalizeWithdrawalInstruction(
   payer: PublicKey,
   withdrawalNonce: bigint,
+  l2Slot: bigint,
+  leafIndex: bigint,
+  merkleProof: Uint8Array[],
   vaultTokenAccount: PublicKey,
   recipientTokenAccount: PublicKey,
   tokenMint: PublicKey,
@@ -119,11 +130,17 @@ export function createFinalizeWithdrawalInstruction(
 ): TransactionInstruction {
   const [configPda] = deriveBridgeConfig()
   const [withdrawalPda] = deriveWithdrawalRequest(withdrawalNonce)
+  const [stateRootPda] = deriveStateRoot(l2Slot)
+  const [fastWithdrawalPda] = deriveFastWithdrawal(withdrawalNonce)
 
-  // Serialize: discriminator(1) + withdrawal_nonce(8)
-  const data = Buffer.alloc(1 + 8)
+  // Serialize: discriminator(1) + withdrawal_nonce(8) + leaf_index(8)
+  //            + merkle_proof (u32 len + 32 * len)
+  const data = Buffer.alloc(1 + 8 + 8 + 4 + 32 * merkleProof.length)
   data[0] = L1_IX.FINALIZE_WITHDRAWAL
   data.writeBigUInt64LE(withdrawalNonce, 1)
+  data.writeBigUInt64LE(leafIndex, 9)
+  data.writeUInt32LE(merkleProof.length, 17)
+  merkleProof.forEach((hash, i) => Buffer.from(hash).copy(data, 21 + 32 * i))
 
   return new TransactionInstruction({
     programId: BRIDGE_L1_PROGRAM_ID,
@@ -135,6 +152,37 @@ export function createFinalizeWithdrawalInstruction(
       { pubkey: tokenMint, isSigner: false, isWritable: false },
       { pubkey: configPda, isSigner: false, isWritable: false },
       { pubkey: tokenProgramId, isSigner: false, isWritable: false },
+      { pubkey: stateRootPda, isSigner: false, isWritable: false },
+      { pubkey: fastWithdrawalPda, isSigner: false, isWritable: false },
+    ],
+    data,
+  })
+}
+
+// ── Close Fast Withdrawal ───────────────────────────────────────────────────
+// Accounts (must match on-chain process_close_fast_withdrawal):
+//   0. [] withdrawal_request PDA
+//   1. [writable] fast_withdrawal PDA
+//   2. [writable] offer recipient (receives the rent)
+
+export function createCloseFastWithdrawalInstruction(
+  withdrawalNonce: bigint,
+  offerRecipient: PublicKey,
+): TransactionInstruction {
+  const [withdrawalPda] = deriveWithdrawalRequest(withdrawalNonce)
+  const [fastWithdrawalPda] = deriveFastWithdrawal(withdrawalNonce)
+
+  // Serialize: discriminator(1) + withdrawal_nonce(8)
+  const data = Buffer.alloc(1 + 8)
+  data[0] = L1_IX.CLOSE_FAST_WITHDRAWAL
+  data.writeBigUInt64LE(withdrawalNonce, 1)
+
+  return new TransactionInstruction({
+    programId: BRIDGE_L1_PROGRAM_ID,
+    keys: [
+      { pubkey: withdrawalPda, isSigner: false, isWritable: false },
+      { pubkey: fastWithdrawalPda, isSigner: false, isWritable: true },
+      { pubkey: offerRecipient, isSigner: false, isWritable: true },
     ],
     data,
   })
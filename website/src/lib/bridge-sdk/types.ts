@@ -4,6 +4,7 @@ import { PublicKey } from '@solana/web3.js'
 
 export const BRIDGE_L1_PROGRAM_ID = new PublicKey('oEQfREm4FQkaVeRoxJHkJLB1feHprrntY6eJuW2zbqQ')
 export const BRIDGE_L2_PROGRAM_ID = new PublicKey('MythBrdgL2111111111111111111111111111111111')
+export const SETTLEMENT_PROGRAM_ID = new PublicKey('4TrowzShv4CrsuqZeUdLLVMdnDDkqkmnER1MZ5NsSaav')
 
 // ── Token Mints (L1 Solana Mainnet) ─────────────────────────────────────────
 
@@ -22,6 +23,8 @@ export const BRIDGE_RESERVE_SEED = Buffer.from('bridge_reserve')
 export const PROCESSED_SEED = Buffer.from('processed')
 export const MINT_SEED = Buffer.from('mint')
 export const FEE_VAULT_SEED = Buffer.from('bridge_vault')
+export const FAST_WITHDRAWAL_SEED = Buffer.from('fast_withdrawal')
+export const STATE_ROOT_SEED = Buffer.from('state_root')
 
 // ── Instruction Discriminators ──────────────────────────────────────────────
 
@@ -36,6 +39,7 @@ export const L1_IX = {
   PAUSE_BRIDGE: 7,
   UNPAUSE_BRIDGE: 8,
   SET_LIMITS: 9,
+  CLOSE_FAST_WITHDRAWAL: 18,
 } as const
 
 export const L2_IX = {